@@ -5,8 +5,13 @@ use std::{
 };
 
 use anyhow::{anyhow, Context as _};
+use futures_util::{stream, StreamExt, TryStreamExt};
 use matrix_sdk::{
-    media::{MediaFileHandle as SdkMediaFileHandle, MediaFormat, MediaRequest, MediaThumbnailSize},
+    image_packs::ImagePackSource,
+    media::{
+        AvatarSize, MediaFileHandle as SdkMediaFileHandle, MediaFormat, MediaRequest,
+        MediaThumbnailSize,
+    },
     oidc::{
         requests::account_management::AccountManagementActionFull,
         types::{
@@ -32,13 +37,16 @@ use matrix_sdk::{
             AnyInitialStateEvent, AnyToDeviceEvent, InitialStateEvent,
         },
         serde::Raw,
-        EventEncryptionAlgorithm, RoomId, TransactionId, UInt, UserId,
+        EventEncryptionAlgorithm, OwnedMxcUri, RoomId, TransactionId, UInt, UserId,
     },
     AuthApi, AuthSession, Client as MatrixClient, SessionChange, SessionTokens,
 };
-use matrix_sdk_ui::notification_client::{
-    NotificationClient as MatrixNotificationClient,
-    NotificationProcessSetup as MatrixNotificationProcessSetup,
+use matrix_sdk_ui::{
+    frequent_reactions,
+    notification_client::{
+        NotificationClient as MatrixNotificationClient,
+        NotificationProcessSetup as MatrixNotificationProcessSetup,
+    },
 };
 use mime::Mime;
 use ruma::{
@@ -60,15 +68,21 @@ use super::{room::Room, session_verification::SessionVerificationController, RUN
 use crate::{
     client,
     encryption::Encryption,
+    error::{AvatarError, JoinByLinkError},
     notification::NotificationClient,
     notification_settings::NotificationSettings,
     room_directory_search::RoomDirectorySearch,
+    room_info::RoomInfo,
     room_preview::RoomPreview,
     sync_service::{SyncService, SyncServiceBuilder},
     task_handle::TaskHandle,
     ClientError,
 };
 
+/// SC: Maximum number of room infos built at the same time by
+/// [`Client::rooms_info`].
+const MAX_CONCURRENT_ROOM_INFOS: usize = 16;
+
 #[derive(Clone, uniffi::Record)]
 pub struct PusherIdentifiers {
     pub pushkey: String,
@@ -567,6 +581,16 @@ impl Client {
             .await?)
     }
 
+    /// SC: Get the data of the avatar with the given MXC URI, at the given
+    /// size.
+    ///
+    /// See [`matrix_sdk::media::Media::get_avatar`] for the details about the
+    /// cache of the avatars.
+    pub async fn avatar_data(&self, mxc: String, size: AvatarSize) -> Result<Vec<u8>, AvatarError> {
+        let mxc = OwnedMxcUri::from(mxc);
+        Ok(self.inner.media().get_avatar(&mxc, size).await?)
+    }
+
     pub async fn get_session_verification_controller(
         &self,
     ) -> Result<Arc<SessionVerificationController>, ClientError> {
@@ -661,6 +685,30 @@ impl Client {
         self.inner.rooms().into_iter().map(|room| Arc::new(Room::new(room))).collect()
     }
 
+    /// SC: Get the infos of the given rooms at once, in the same order.
+    ///
+    /// The infos are built concurrently and share the notification settings,
+    /// which is much faster than getting them room by room. Unknown rooms are
+    /// skipped.
+    pub async fn rooms_info(&self, room_ids: Vec<String>) -> Result<Vec<RoomInfo>, ClientError> {
+        let rooms = room_ids
+            .into_iter()
+            .map(|room_id| Ok(self.inner.get_room(&RoomId::parse(room_id)?)))
+            .collect::<Result<Vec<_>, ClientError>>()?;
+        let notification_settings = self.inner.notification_settings().await;
+        let notification_settings = &notification_settings;
+
+        let infos: Vec<_> = stream::iter(rooms.into_iter().flatten())
+            .map(|room| async move {
+                RoomInfo::with_notification_settings(&room, notification_settings).await
+            })
+            .buffered(MAX_CONCURRENT_ROOM_INFOS)
+            .try_collect()
+            .await?;
+
+        Ok(infos)
+    }
+
     pub fn get_dm_room(&self, user_id: String) -> Result<Option<Arc<Room>>, ClientError> {
         let user_id = UserId::parse(user_id)?;
         let sdk_room = self.inner.get_dm_room(&user_id);
@@ -735,13 +783,13 @@ impl Client {
 
     pub async fn ignore_user(&self, user_id: String) -> Result<(), ClientError> {
         let user_id = UserId::parse(user_id)?;
-        self.inner.account().ignore_user(&user_id).await?;
+        self.inner.ignore_user(&user_id).await?;
         Ok(())
     }
 
     pub async fn unignore_user(&self, user_id: String) -> Result<(), ClientError> {
         let user_id = UserId::parse(user_id)?;
-        self.inner.account().unignore_user(&user_id).await?;
+        self.inner.unignore_user(&user_id).await?;
         Ok(())
     }
 
@@ -757,6 +805,18 @@ impl Client {
         })))
     }
 
+    /// SC: Set whether the successor of a room is joined automatically when
+    /// the room gets a tombstone during sync.
+    pub fn set_auto_follow_tombstones(&self, enabled: bool) {
+        self.inner.set_auto_follow_tombstones(enabled);
+    }
+
+    /// SC: Get the custom emoji and sticker packs available to the user,
+    /// from their account data and the state of the joined rooms.
+    pub async fn image_packs(&self) -> Result<Vec<ImagePack>, ClientError> {
+        Ok(self.inner.image_packs().await?.into_iter().map(Into::into).collect())
+    }
+
     pub fn room_directory_search(&self) -> Arc<RoomDirectorySearch> {
         Arc::new(RoomDirectorySearch::new(
             matrix_sdk::room_directory_search::RoomDirectorySearch::new((*self.inner).clone()),
@@ -795,6 +855,13 @@ impl Client {
         Ok(Arc::new(Room::new(room)))
     }
 
+    /// SC: Join the room a `matrix:` URI or a `matrix.to` link points to, see
+    /// [`matrix_sdk::Client::join_by_link`].
+    pub async fn join_by_link(&self, link: String) -> Result<Arc<Room>, JoinByLinkError> {
+        let room = self.inner.join_by_link(&link).await?;
+        Ok(Arc::new(Room::new(room)))
+    }
+
     pub async fn get_recently_visited_rooms(&self) -> Result<Vec<String>, ClientError> {
         Ok(self.inner.account().get_recently_visited_rooms().await?)
     }
@@ -804,6 +871,12 @@ impl Client {
         Ok(())
     }
 
+    /// SC: Get at most `limit` of the reactions the user reacts with most,
+    /// completed with a default set of reactions.
+    pub async fn frequent_reactions(&self, limit: u32) -> Result<Vec<String>, ClientError> {
+        Ok(frequent_reactions::frequent_reactions(&self.inner, limit as usize).await?)
+    }
+
     /// Resolves the given room alias to a room ID (and a list of servers), if
     /// possible.
     pub async fn resolve_room_alias(
@@ -815,6 +888,31 @@ impl Client {
         Ok(response.into())
     }
 
+    /// SC: Given a room id or alias, get the preview of a room, e.g. to
+    /// confirm joining it.
+    ///
+    /// The list of `via_servers` must be a list of servers that know
+    /// about the room and can resolve it, and that may appear as a `via`
+    /// parameter in e.g. a permalink URL. This list can be empty.
+    pub async fn get_room_preview(
+        &self,
+        room_id_or_alias: String,
+        via_servers: Vec<String>,
+    ) -> Result<RoomPreview, ClientError> {
+        let room_or_alias_id = RoomOrAliasId::parse(&room_id_or_alias)
+            .context("room_id_or_alias is not a valid room id or alias")?;
+
+        let via_servers = via_servers
+            .into_iter()
+            .map(ServerName::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .context("at least one `via` server name is invalid")?;
+
+        let sdk_room_preview = self.inner.get_room_preview(&room_or_alias_id, via_servers).await?;
+
+        Ok(RoomPreview::from_sdk(sdk_room_preview))
+    }
+
     /// Given a room id, get the preview of a room, to interact with it.
     ///
     /// The list of `via_servers` must be a list of servers that know
@@ -904,6 +1002,51 @@ impl From<get_alias::v3::Response> for ResolvedRoomAlias {
     }
 }
 
+/// SC: A pack of custom emojis or stickers.
+#[derive(uniffi::Record)]
+pub struct ImagePack {
+    /// The room the pack is defined in, or `None` for the user's own pack.
+    pub room_id: Option<String>,
+    /// The display name of the pack, if any.
+    pub display_name: Option<String>,
+    /// The images of the pack, sorted by shortcode.
+    pub images: Vec<PackImage>,
+}
+
+/// SC: An image of an [`ImagePack`].
+#[derive(uniffi::Record)]
+pub struct PackImage {
+    /// The shortcode of the image, without the surrounding colons.
+    pub shortcode: String,
+    /// The `mxc://` URI of the image.
+    pub url: String,
+    /// A textual description of the image, if any.
+    pub body: Option<String>,
+}
+
+impl From<matrix_sdk::image_packs::ImagePack> for ImagePack {
+    fn from(value: matrix_sdk::image_packs::ImagePack) -> Self {
+        let room_id = match value.source {
+            ImagePackSource::User => None,
+            ImagePackSource::Room { room_id, .. } => Some(room_id.to_string()),
+        };
+
+        Self {
+            room_id,
+            display_name: value.display_name,
+            images: value
+                .images
+                .into_iter()
+                .map(|image| PackImage {
+                    shortcode: image.shortcode,
+                    url: image.url.to_string(),
+                    body: image.body,
+                })
+                .collect(),
+        }
+    }
+}
+
 #[derive(uniffi::Record)]
 pub struct SearchUsersResults {
     pub results: Vec<UserProfile>,
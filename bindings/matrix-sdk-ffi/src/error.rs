@@ -172,6 +172,9 @@ pub enum NotificationSettingsError {
     /// Unable to update push rule.
     #[error("Unable to update push rule")]
     UnableToUpdatePushRule,
+    /// SC: Unable to save the Do-Not-Disturb state.
+    #[error("Unable to save the Do-Not-Disturb state")]
+    UnableToSaveDnd,
 }
 
 impl From<SdkNotificationSettingsError> for NotificationSettingsError {
@@ -183,6 +186,7 @@ impl From<SdkNotificationSettingsError> for NotificationSettingsError {
             SdkNotificationSettingsError::UnableToSavePushRules => Self::UnableToSavePushRules,
             SdkNotificationSettingsError::InvalidParameter(msg) => Self::InvalidParameter { msg },
             SdkNotificationSettingsError::UnableToUpdatePushRule => Self::UnableToUpdatePushRule,
+            SdkNotificationSettingsError::UnableToSaveDnd => Self::UnableToSaveDnd,
         }
     }
 }
@@ -192,3 +196,57 @@ impl From<matrix_sdk::Error> for NotificationSettingsError {
         Self::Generic { msg: e.to_string() }
     }
 }
+
+/// SC: Errors of `Client::join_by_link`, see [`matrix_sdk::JoinByLinkError`].
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum JoinByLinkError {
+    /// The link isn't a `matrix:` URI or a `matrix.to` link.
+    #[error("Invalid link")]
+    InvalidLink,
+    /// The link doesn't point to a room.
+    #[error("The link doesn't point to a room")]
+    NotARoom,
+    /// The room can only be joined after knocking.
+    #[error("The room requires knocking to join")]
+    KnockRequired,
+    /// The user is banned from the room.
+    #[error("The user is banned from the room")]
+    Banned,
+    #[error("client error: {msg}")]
+    Generic { msg: String },
+}
+
+impl From<matrix_sdk::JoinByLinkError> for JoinByLinkError {
+    fn from(e: matrix_sdk::JoinByLinkError) -> Self {
+        match e {
+            matrix_sdk::JoinByLinkError::InvalidLink => Self::InvalidLink,
+            matrix_sdk::JoinByLinkError::NotARoom => Self::NotARoom,
+            matrix_sdk::JoinByLinkError::KnockRequired => Self::KnockRequired,
+            matrix_sdk::JoinByLinkError::Banned => Self::Banned,
+            matrix_sdk::JoinByLinkError::Sdk(e) => Self::Generic { msg: e.to_string() },
+        }
+    }
+}
+
+/// SC: Errors of `Client::avatar_data`, see [`matrix_sdk::media::AvatarError`].
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum AvatarError {
+    /// The media doesn't exist, or was deleted.
+    #[error("The avatar was not found")]
+    NotFound,
+    /// The server refused to return the media.
+    #[error("The avatar access is forbidden")]
+    Forbidden,
+    #[error("client error: {msg}")]
+    Generic { msg: String },
+}
+
+impl From<matrix_sdk::media::AvatarError> for AvatarError {
+    fn from(e: matrix_sdk::media::AvatarError) -> Self {
+        match e {
+            matrix_sdk::media::AvatarError::NotFound => Self::NotFound,
+            matrix_sdk::media::AvatarError::Forbidden => Self::Forbidden,
+            matrix_sdk::media::AvatarError::Sdk(e) => Self::Generic { msg: e.to_string() },
+        }
+    }
+}
@@ -42,6 +42,15 @@ pub struct NotificationItem {
     /// information to create a push context.
     pub is_noisy: Option<bool>,
     pub has_mention: Option<bool>,
+    /// SC: Whether the event could be decrypted. If not, the event is still
+    /// encrypted and the notification should say so.
+    pub is_decryptable: bool,
+    /// SC: The plain text body of the message, `None` if it's not a message or
+    /// couldn't be decrypted.
+    pub body_preview: Option<String>,
+    /// SC: The keywords that caused this notification, empty if it was caused
+    /// by another rule, like a mention or a DM.
+    pub matched_keywords: Vec<String>,
 }
 
 impl NotificationItem {
@@ -72,6 +81,9 @@ impl NotificationItem {
             },
             is_noisy: item.is_noisy,
             has_mention: item.has_mention,
+            is_decryptable: item.is_decryptable,
+            body_preview: item.body_preview,
+            matched_keywords: item.matched_keywords,
         }
     }
 }
@@ -1,5 +1,6 @@
 use std::sync::{Arc, RwLock};
 
+use futures_util::{pin_mut, StreamExt};
 use matrix_sdk::{
     event_handler::EventHandlerHandle,
     notification_settings::{
@@ -15,7 +16,7 @@ use ruma::{
 };
 use tokio::sync::RwLock as AsyncRwLock;
 
-use crate::error::NotificationSettingsError;
+use crate::{error::NotificationSettingsError, task_handle::TaskHandle, RUNTIME};
 
 /// Enum representing the push notification modes for a room.
 #[derive(Clone, uniffi::Enum)]
@@ -54,6 +55,18 @@ pub trait NotificationSettingsDelegate: Sync + Send {
     fn settings_did_change(&self);
 }
 
+/// SC: Listener for changes of the Do-Not-Disturb state.
+#[uniffi::export(callback_interface)]
+pub trait DndListener: Sync + Send {
+    fn call(&self, enabled: bool);
+}
+
+/// SC: Listener for changes of the keywords which have enabled rules.
+#[uniffi::export(callback_interface)]
+pub trait KeywordsListener: Sync + Send {
+    fn call(&self, keywords: Vec<String>);
+}
+
 /// `RoomNotificationSettings` represents the current settings for a `Room`
 #[derive(Clone, uniffi::Record)]
 pub struct RoomNotificationSettings {
@@ -263,6 +276,74 @@ impl NotificationSettings {
         notification_settings.contains_keyword_rules().await
     }
 
+    /// SC: Get the keywords which have enabled rules.
+    pub async fn get_keywords(&self) -> Vec<String> {
+        let notification_settings = self.sdk_notification_settings.read().await;
+        notification_settings.enabled_keywords().await.into_iter().collect()
+    }
+
+    /// SC: Replace all the keywords which have enabled rules by `keywords`.
+    ///
+    /// With `highlight`, the messages matching the keywords are also
+    /// highlighted. Otherwise, the actions of the existing rules are kept.
+    ///
+    /// Changes are reported to the delegate, like other changes of the push
+    /// rules.
+    pub async fn set_keywords(
+        &self,
+        keywords: Vec<String>,
+        highlight: bool,
+    ) -> Result<(), NotificationSettingsError> {
+        let notification_settings = self.sdk_notification_settings.read().await;
+        notification_settings.set_keywords(keywords, highlight).await?;
+        Ok(())
+    }
+
+    /// SC: Subscribe to the keywords which have enabled rules.
+    ///
+    /// The listener is called with the current keywords first, then every
+    /// time they change.
+    pub async fn subscribe_to_keywords(
+        &self,
+        listener: Box<dyn KeywordsListener>,
+    ) -> Arc<TaskHandle> {
+        let stream = self.sdk_notification_settings.read().await.subscribe_to_keywords();
+        Arc::new(TaskHandle::new(RUNTIME.spawn(async move {
+            pin_mut!(stream);
+            while let Some(keywords) = stream.next().await {
+                listener.call(keywords.into_iter().collect());
+            }
+        })))
+    }
+
+    /// SC: Get whether Do-Not-Disturb is enabled.
+    pub async fn is_dnd_enabled(&self) -> bool {
+        let notification_settings = self.sdk_notification_settings.read().await;
+        notification_settings.is_dnd_enabled().await
+    }
+
+    /// SC: Enable or disable Do-Not-Disturb, which makes all notifications
+    /// silent without changing the push rules.
+    pub async fn set_dnd(&self, enabled: bool) -> Result<(), NotificationSettingsError> {
+        let notification_settings = self.sdk_notification_settings.read().await;
+        notification_settings.set_dnd(enabled).await?;
+        Ok(())
+    }
+
+    /// SC: Subscribe to the Do-Not-Disturb state.
+    ///
+    /// The listener is called with the current state first, then every time
+    /// it changes.
+    pub async fn subscribe_to_dnd(&self, listener: Box<dyn DndListener>) -> Arc<TaskHandle> {
+        let stream = self.sdk_notification_settings.read().await.subscribe_to_dnd();
+        Arc::new(TaskHandle::new(RUNTIME.spawn(async move {
+            pin_mut!(stream);
+            while let Some(enabled) = stream.next().await {
+                listener.call(enabled);
+            }
+        })))
+    }
+
     /// Get whether room mentions are enabled.
     pub async fn is_room_mention_enabled(&self) -> Result<bool, NotificationSettingsError> {
         let notification_settings = self.sdk_notification_settings.read().await;
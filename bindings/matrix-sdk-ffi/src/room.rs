@@ -1,10 +1,14 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use anyhow::{Context, Result};
+use futures_util::{pin_mut, StreamExt};
 use matrix_sdk::{
     event_cache::paginator::PaginatorError,
-    room::{power_levels::RoomPowerLevelChanges, Room as SdkRoom, RoomMemberRole},
-    ComposerDraft, RoomHero as SdkRoomHero, RoomMemberships, RoomState,
+    room::{
+        power_levels::RoomPowerLevelChanges, ExportFormat, Room as SdkRoom, RoomMemberFilter,
+        RoomMemberRole,
+    },
+    ComposerDraft, RoomHero as SdkRoomHero, RoomMemberships, RoomState, ScUnreadCountSource,
 };
 use matrix_sdk_ui::timeline::{PaginationError, RoomExt, TimelineFocus};
 use mime::Mime;
@@ -19,7 +23,7 @@ use ruma::{
         },
         TimelineEventType,
     },
-    EventId, Int, RoomAliasId, UserId,
+    EventId, Int, MilliSecondsSinceUnixEpoch, RoomAliasId, RoomId, ServerName, UserId,
 };
 use tokio::sync::RwLock;
 use tracing::error;
@@ -31,8 +35,8 @@ use crate::{
     event::{MessageLikeEventType, StateEventType},
     room_info::RoomInfo,
     room_member::RoomMember,
-    ruma::{ImageInfo, Mentions, NotifyType},
-    space_child_info::{SpaceChildInfo, space_children_info},
+    ruma::{ImageInfo, Mentions, NotifyType, PresenceState},
+    space_child_info::{space_children_info, SpaceChildInfo, SpaceChildSummary},
     timeline::{FocusEventError, ReceiptType, Timeline},
     utils::u64_to_uint,
     TaskHandle,
@@ -43,6 +47,7 @@ pub enum Membership {
     Invited,
     Joined,
     Left,
+    Knocked,
 }
 
 impl From<RoomState> for Membership {
@@ -51,6 +56,7 @@ impl From<RoomState> for Membership {
             RoomState::Invited => Membership::Invited,
             RoomState::Joined => Membership::Joined,
             RoomState::Left => Membership::Left,
+            RoomState::Knocked => Membership::Knocked,
         }
     }
 }
@@ -86,6 +92,12 @@ impl Room {
         Some(self.inner.cached_display_name()?.to_string())
     }
 
+    /// SC: Recompute the display name of the room, fetching its members if
+    /// needed, and update the cached value returned by `display_name`.
+    pub async fn recompute_display_name(&self) -> Result<String, ClientError> {
+        Ok(self.inner.recompute_display_name().await?.to_string())
+    }
+
     /// The raw name as present in the room state event.
     pub fn raw_name(&self) -> Option<String> {
         self.inner.name()
@@ -100,7 +112,7 @@ impl Room {
     }
 
     pub fn is_direct(&self) -> bool {
-        RUNTIME.block_on(self.inner.is_direct()).unwrap_or(false)
+        self.inner.is_direct_cached()
     }
 
     pub fn is_public(&self) -> bool {
@@ -115,6 +127,18 @@ impl Room {
         return space_children_info(&self.inner);
     }
 
+    /// SC: Fetch a summary of this space's children from the homeserver.
+    ///
+    /// Joined children report their live local state, unjoined ones the
+    /// summary returned by the `/hierarchy` endpoint.
+    pub async fn fetch_space_summary(
+        &self,
+        suggested_only: bool,
+    ) -> Result<Vec<SpaceChildSummary>, ClientError> {
+        let summaries = self.inner.fetch_space_summary(suggested_only).await?;
+        Ok(summaries.into_iter().map(Into::into).collect())
+    }
+
     pub fn is_tombstoned(&self) -> bool {
         self.inner.is_tombstoned()
     }
@@ -187,6 +211,19 @@ impl Room {
         }
     }
 
+    /// SC: Returns a detached timeline, that only contains the events
+    /// imported with [`Timeline::import_events`].
+    ///
+    /// Note: this timeline is independent from that returned with
+    /// [`Self::timeline`], and as such it is not cached.
+    pub async fn detached_timeline(&self) -> Result<Arc<Timeline>, ClientError> {
+        let timeline = matrix_sdk_ui::timeline::Timeline::builder(&self.inner)
+            .with_focus(TimelineFocus::Detached)
+            .build()
+            .await?;
+        Ok(Timeline::new(timeline))
+    }
+
     /// Returns a timeline focused on the given event.
     ///
     /// Note: this timeline is independent from that returned with
@@ -243,6 +280,58 @@ impl Room {
         )))
     }
 
+    /// SC: Get a page of the members matching the given query, see
+    /// [`matrix_sdk::Room::members_filtered`].
+    pub async fn members_filtered(
+        &self,
+        query: String,
+        membership: RoomMemberFilter,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<RoomMember>, ClientError> {
+        let members = self
+            .inner
+            .members_filtered(&query, membership, limit as usize, offset as usize)
+            .await?;
+        Ok(members.into_iter().map(Into::into).collect())
+    }
+
+    /// SC: Export the history of this room to the file at the given path, see
+    /// [`matrix_sdk::Room::export_timeline`].
+    pub async fn export_timeline(
+        &self,
+        path: String,
+        format: ExportFormat,
+    ) -> Result<(), ClientError> {
+        let file =
+            tokio::fs::File::create(path).await.context("Failed to create the export file")?;
+        let file = tokio::io::BufWriter::new(file);
+        Ok(self.inner.export_timeline(file, format).await?)
+    }
+
+    /// SC: Get the preview of the given URL, see
+    /// [`matrix_sdk::Room::get_url_preview`].
+    pub async fn get_url_preview(&self, url: String) -> Result<UrlPreview, ClientError> {
+        Ok(self.inner.get_url_preview(&url).await?.into())
+    }
+
+    /// SC: Whether the user enabled URL previews in this room, see
+    /// [`matrix_sdk::Room::are_url_previews_enabled`].
+    pub async fn are_url_previews_enabled(&self) -> Result<bool, ClientError> {
+        Ok(self.inner.are_url_previews_enabled().await?)
+    }
+
+    /// SC: Search the messages of this room on the server, see
+    /// [`matrix_sdk::Room::search_messages`].
+    pub async fn search_messages(
+        &self,
+        query: String,
+        limit: u32,
+        from: Option<String>,
+    ) -> Result<SearchResults, ClientError> {
+        Ok(self.inner.search_messages(&query, limit as usize, from).await?.into())
+    }
+
     pub async fn member(&self, user_id: String) -> Result<RoomMember, ClientError> {
         let user_id = UserId::parse(&*user_id).context("Invalid user id.")?;
         let member = self.inner.get_member(&user_id).await?.context("User not found")?;
@@ -287,6 +376,97 @@ impl Room {
         })))
     }
 
+    /// SC: Like [`Self::subscribe_to_room_info_updates`], but the listener is
+    /// called at most once every `interval_ms` milliseconds, see
+    /// [`matrix_sdk::Room::info_stream_debounced`].
+    pub fn subscribe_to_room_info_updates_debounced(
+        self: Arc<Self>,
+        interval_ms: u64,
+        listener: Box<dyn RoomInfoListener>,
+    ) -> Arc<TaskHandle> {
+        let stream = self.inner.info_stream_debounced(Duration::from_millis(interval_ms));
+        Arc::new(TaskHandle::new(RUNTIME.spawn(async move {
+            pin_mut!(stream);
+            while stream.next().await.is_some() {
+                match self.room_info().await {
+                    Ok(room_info) => listener.call(room_info),
+                    Err(e) => {
+                        error!("Failed to compute new RoomInfo: {e}");
+                    }
+                }
+            }
+        })))
+    }
+
+    /// SC: Subscribe to changes of this space's children, see
+    /// [`matrix_sdk::room::Room::space_children_stream`].
+    pub fn subscribe_to_space_children_updates(
+        self: Arc<Self>,
+        listener: Box<dyn SpaceChildrenListener>,
+    ) -> Arc<TaskHandle> {
+        Arc::new(TaskHandle::new(RUNTIME.spawn(async move {
+            let stream = self.inner.space_children_stream();
+            pin_mut!(stream);
+            while let Some(space_children) = stream.next().await {
+                listener.call(space_children.into_iter().map(Into::into).collect());
+            }
+        })))
+    }
+
+    /// SC: Get the last-known presence of the other member of this DM, or
+    /// `None` if this isn't a DM with a single other member or the presence
+    /// is unknown.
+    ///
+    /// It may be stale if the server doesn't push presence.
+    pub fn dm_partner_presence(&self) -> Option<PresenceState> {
+        self.inner.dm_partner_presence().map(Into::into)
+    }
+
+    /// SC: Subscribe to the last-known presence of the other member of this
+    /// DM, see [`Self::dm_partner_presence`].
+    pub fn subscribe_to_dm_partner_presence(
+        self: Arc<Self>,
+        listener: Box<dyn DmPartnerPresenceListener>,
+    ) -> Arc<TaskHandle> {
+        Arc::new(TaskHandle::new(RUNTIME.spawn(async move {
+            let stream = self.inner.subscribe_to_dm_partner_presence();
+            pin_mut!(stream);
+            while let Some(presence) = stream.next().await {
+                listener.call(presence.map(Into::into));
+            }
+        })))
+    }
+
+    /// SC: Subscribe to the state of the call in this room, see
+    /// [`matrix_sdk::room::Room::call_state_stream`].
+    pub fn subscribe_to_call_state(
+        self: Arc<Self>,
+        listener: Box<dyn CallStateListener>,
+    ) -> Arc<TaskHandle> {
+        Arc::new(TaskHandle::new(RUNTIME.spawn(async move {
+            let stream = self.inner.call_state_stream();
+            pin_mut!(stream);
+            while let Some(call_state) = stream.next().await {
+                listener.call(call_state.into());
+            }
+        })))
+    }
+
+    /// SC: Subscribe to the encryption state of this room, see
+    /// [`matrix_sdk::room::Room::encryption_state_stream`].
+    pub fn subscribe_to_encryption_state(
+        self: Arc<Self>,
+        listener: Box<dyn EncryptionStateListener>,
+    ) -> Arc<TaskHandle> {
+        Arc::new(TaskHandle::new(RUNTIME.spawn(async move {
+            let stream = self.inner.encryption_state_stream();
+            pin_mut!(stream);
+            while let Some(encryption_state) = stream.next().await {
+                listener.call(encryption_state.into());
+            }
+        })))
+    }
+
     pub async fn set_is_favourite(
         &self,
         is_favourite: bool,
@@ -381,12 +561,20 @@ impl Room {
 
     /// Leave this room.
     ///
-    /// Only invited and joined rooms can be left.
+    /// Only invited, knocked and joined rooms can be left.
     pub async fn leave(&self) -> Result<(), ClientError> {
         self.inner.leave().await?;
         Ok(())
     }
 
+    /// SC: Retract the knock on this room.
+    ///
+    /// Only knocked rooms can have their knock cancelled.
+    pub async fn cancel_knock(&self) -> Result<(), ClientError> {
+        self.inner.cancel_knock().await?;
+        Ok(())
+    }
+
     /// Join this room.
     ///
     /// Only invited and left rooms can be joined via this method.
@@ -395,6 +583,22 @@ impl Room {
         Ok(())
     }
 
+    /// SC: Accept the invite to this room.
+    ///
+    /// Only invited rooms can be accepted.
+    pub async fn accept_invite(&self) -> Result<(), ClientError> {
+        self.inner.accept_invite().await?;
+        Ok(())
+    }
+
+    /// SC: Decline the invite to this room, with an optional reason.
+    ///
+    /// Only invited rooms can be declined.
+    pub async fn decline_invite(&self, reason: Option<String>) -> Result<(), ClientError> {
+        self.inner.decline_invite(reason).await?;
+        Ok(())
+    }
+
     /// Sets a new name to the room.
     pub async fn set_name(&self, name: String) -> Result<(), ClientError> {
         self.inner.set_name(name).await?;
@@ -505,6 +709,58 @@ impl Room {
         Ok(self.inner.kick_user(&user_id, reason.as_deref()).await?)
     }
 
+    pub async fn can_user_manage_spaces(&self, user_id: String) -> Result<bool, ClientError> {
+        let user_id = UserId::parse(&user_id)?;
+        Ok(self.inner.can_user_manage_spaces(&user_id).await?)
+    }
+
+    pub async fn can_user_enable_encryption(&self, user_id: String) -> Result<bool, ClientError> {
+        let user_id = UserId::parse(&user_id)?;
+        Ok(self.inner.can_user_enable_encryption(&user_id).await?)
+    }
+
+    /// SC: Enable encryption in this room, failing early if the current user
+    /// isn't allowed to do so. Enabling encryption can't be undone.
+    pub async fn enable_encryption_checked(&self) -> Result<(), ClientError> {
+        Ok(self.inner.enable_encryption_checked().await?)
+    }
+
+    /// SC: Add a room as a child of this space.
+    ///
+    /// `order` must be at most 50 printable ASCII characters, and `via` must
+    /// contain at least one server name.
+    pub async fn add_space_child(
+        &self,
+        child_id: String,
+        order: Option<String>,
+        suggested: bool,
+        via: Vec<String>,
+    ) -> Result<(), ClientError> {
+        let child_id = RoomId::parse(child_id)?;
+        let via = via.into_iter().map(ServerName::parse).collect::<Result<Vec<_>, _>>()?;
+        self.inner.add_space_child(&child_id, order, suggested, via).await?;
+        Ok(())
+    }
+
+    /// SC: Remove a room from the children of this space.
+    pub async fn remove_space_child(&self, child_id: String) -> Result<(), ClientError> {
+        let child_id = RoomId::parse(child_id)?;
+        self.inner.remove_space_child(&child_id).await?;
+        Ok(())
+    }
+
+    /// SC: Set, or clear if `None`, the ordering string of a child of this
+    /// space.
+    pub async fn set_space_child_order(
+        &self,
+        child_id: String,
+        order: Option<String>,
+    ) -> Result<(), ClientError> {
+        let child_id = RoomId::parse(child_id)?;
+        self.inner.set_space_child_order(&child_id, order).await?;
+        Ok(())
+    }
+
     pub async fn can_user_send_state(
         &self,
         user_id: String,
@@ -565,12 +821,57 @@ impl Room {
         })))
     }
 
+    /// SC: Join the room that replaced this tombstoned room, carrying over
+    /// its favourite and low priority tags and its unread marker.
+    pub async fn follow_tombstone(&self) -> Result<Arc<Room>, ClientError> {
+        let room = self.inner.follow_tombstone().await?;
+        Ok(Arc::new(Room::new(room)))
+    }
+
     /// Set (or unset) a flag on the room to indicate that the user has
     /// explicitly marked it as unread.
     pub async fn set_unread_flag(&self, new_value: bool) -> Result<(), ClientError> {
         Ok(self.inner.set_unread_flag(new_value).await?)
     }
 
+    /// SC: Snooze the notifications of this room until `until_ms`, in
+    /// milliseconds since the Unix epoch.
+    ///
+    /// A timestamp in the past cancels the snooze.
+    pub async fn snooze_notifications(&self, until_ms: u64) -> Result<(), ClientError> {
+        let until = MilliSecondsSinceUnixEpoch(u64_to_uint(until_ms));
+        Ok(self.inner.snooze_notifications(until).await?)
+    }
+
+    /// SC: Mute or unmute the messages of `user_id` in this room only.
+    ///
+    /// Unlike ignoring a user, this keeps receiving their events, which are
+    /// only collapsed by timelines configured to do so. The muted users are
+    /// kept on this device and aren't shared with the other sessions.
+    pub async fn set_user_muted_locally(
+        &self,
+        user_id: String,
+        muted: bool,
+    ) -> Result<(), ClientError> {
+        let user_id = UserId::parse(&*user_id).context("Invalid user id.")?;
+        Ok(self.inner.set_user_muted_locally(&user_id, muted).await?)
+    }
+
+    /// SC: Choose where the unread counts of this room come from, or `None` to
+    /// follow the default source.
+    pub async fn set_unread_count_source(
+        &self,
+        source: Option<ScUnreadCountSource>,
+    ) -> Result<(), ClientError> {
+        Ok(self.inner.set_unread_count_source(source).await?)
+    }
+
+    /// SC: Choose whether the notifications of this room only make it unread,
+    /// without counting in the badges.
+    pub async fn set_bold_unread_only(&self, enabled: bool) -> Result<(), ClientError> {
+        Ok(self.inner.set_bold_unread_only(enabled).await?)
+    }
+
     /// Mark a room as read, by attaching a read receipt on the latest event.
     ///
     /// Note: this does NOT unset the unread flag; it's the caller's
@@ -636,6 +937,27 @@ impl Room {
         Ok(self.inner.matrix_to_event_permalink(event_id).await?.to_string())
     }
 
+    /// SC: Get the servers that are likely to know about this room, suitable
+    /// as `via` parameters when sharing a link to it or joining it.
+    ///
+    /// This loads the joined members of the room, so it's computed on demand
+    /// rather than kept in the [`RoomInfo`].
+    pub async fn suggested_via_servers(&self) -> Result<Vec<String>, ClientError> {
+        let servers = self.inner.suggested_via_servers().await?;
+        Ok(servers.into_iter().map(|server| server.to_string()).collect())
+    }
+
+    /// SC: Get a `matrix:` permalink to this room.
+    pub async fn matrix_permalink(&self, join: bool) -> Result<String, ClientError> {
+        Ok(self.inner.matrix_permalink(join).await?.to_string())
+    }
+
+    /// SC: Get a `matrix:` permalink to an event in this room.
+    pub async fn matrix_event_permalink(&self, event_id: String) -> Result<String, ClientError> {
+        let event_id = EventId::parse(event_id)?;
+        Ok(self.inner.matrix_event_permalink(event_id).await?.to_string())
+    }
+
     /// This will only send a call notification event if appropriate.
     ///
     /// This function is supposed to be called whenever the user creates a room
@@ -763,11 +1085,134 @@ impl From<RumaPowerLevels> for RoomPowerLevels {
     }
 }
 
+/// SC: The messages found by [`Room::search_messages`].
+#[derive(uniffi::Record)]
+pub struct SearchResults {
+    /// The messages found, from the most recent to the oldest one.
+    pub results: Vec<SearchResult>,
+    /// The token to pass to the next search to get the next results, if
+    /// there are more.
+    pub next_batch: Option<String>,
+}
+
+impl From<matrix_sdk::room::SearchResults> for SearchResults {
+    fn from(value: matrix_sdk::room::SearchResults) -> Self {
+        Self {
+            results: value.results.into_iter().map(Into::into).collect(),
+            next_batch: value.next_batch,
+        }
+    }
+}
+
+/// SC: A message found by [`Room::search_messages`].
+#[derive(uniffi::Record)]
+pub struct SearchResult {
+    /// The ID of the event, e.g. to focus a timeline on it.
+    pub event_id: String,
+    /// Whether the event is encrypted and couldn't be decrypted.
+    pub is_undecryptable: bool,
+}
+
+impl From<matrix_sdk::room::SearchResult> for SearchResult {
+    fn from(value: matrix_sdk::room::SearchResult) -> Self {
+        Self { event_id: value.event_id.to_string(), is_undecryptable: value.is_undecryptable }
+    }
+}
+
+/// SC: The preview of a URL, see [`Room::get_url_preview`].
+#[derive(uniffi::Record)]
+pub struct UrlPreview {
+    /// The title of the page.
+    pub title: Option<String>,
+    /// The description of the page.
+    pub description: Option<String>,
+    /// The name of the site of the page.
+    pub site_name: Option<String>,
+    /// The MXC URI of the image of the page.
+    pub image_url: Option<String>,
+    /// The width of the image, in pixels.
+    pub image_width: Option<u64>,
+    /// The height of the image, in pixels.
+    pub image_height: Option<u64>,
+    /// The size of the image, in bytes.
+    pub image_size: Option<u64>,
+}
+
+impl From<matrix_sdk::room::UrlPreview> for UrlPreview {
+    fn from(value: matrix_sdk::room::UrlPreview) -> Self {
+        Self {
+            title: value.title,
+            description: value.description,
+            site_name: value.site_name,
+            image_url: value.image.map(|uri| uri.to_string()),
+            image_width: value.image_width.map(Into::into),
+            image_height: value.image_height.map(Into::into),
+            image_size: value.image_size.map(Into::into),
+        }
+    }
+}
+
 #[uniffi::export(callback_interface)]
 pub trait RoomInfoListener: Sync + Send {
     fn call(&self, room_info: RoomInfo);
 }
 
+#[uniffi::export(callback_interface)]
+pub trait SpaceChildrenListener: Sync + Send {
+    fn call(&self, space_children: Vec<SpaceChildInfo>);
+}
+
+#[uniffi::export(callback_interface)]
+pub trait DmPartnerPresenceListener: Sync + Send {
+    fn call(&self, presence: Option<PresenceState>);
+}
+
+#[uniffi::export(callback_interface)]
+pub trait CallStateListener: Sync + Send {
+    fn call(&self, call_state: CallState);
+}
+
+/// SC: The state of the call in a room.
+#[derive(uniffi::Record)]
+pub struct CallState {
+    /// The participants of the call, from the oldest membership to the newest.
+    pub participants: Vec<String>,
+    /// When the call started, in milliseconds since the Unix epoch.
+    pub started_at: Option<u64>,
+}
+
+impl From<matrix_sdk::room::CallState> for CallState {
+    fn from(value: matrix_sdk::room::CallState) -> Self {
+        Self {
+            participants: value.participants.iter().map(ToString::to_string).collect(),
+            started_at: value.started_at.map(|ts| ts.0.into()),
+        }
+    }
+}
+
+#[uniffi::export(callback_interface)]
+pub trait EncryptionStateListener: Sync + Send {
+    fn call(&self, encryption_state: EncryptionState);
+}
+
+/// SC: The encryption state of a room.
+#[derive(uniffi::Enum)]
+pub enum EncryptionState {
+    Encrypted,
+    NotEncrypted,
+    Unknown,
+}
+
+impl From<matrix_sdk::room::EncryptionState> for EncryptionState {
+    fn from(value: matrix_sdk::room::EncryptionState) -> Self {
+        match value {
+            matrix_sdk::room::EncryptionState::Encrypted => Self::Encrypted,
+            matrix_sdk::room::EncryptionState::NotEncrypted => Self::NotEncrypted,
+            matrix_sdk::room::EncryptionState::Unknown => Self::Unknown,
+        }
+    }
+}
+
 #[uniffi::export(callback_interface)]
 pub trait TypingNotificationsListener: Sync + Send {
     fn call(&self, typing_user_ids: Vec<String>);
@@ -1,6 +1,12 @@
 use std::collections::HashMap;
 
-use matrix_sdk::RoomState;
+use matrix_sdk::{
+    notification_settings::NotificationSettings,
+    ruma::events::{
+        room::message::SyncRoomMessageEvent, AnySyncMessageLikeEvent, AnySyncTimelineEvent,
+    },
+    RoomState, ScUnreadCountSource,
+};
 
 use crate::{
     notification_settings::RoomNotificationMode,
@@ -23,6 +29,8 @@ pub struct RoomInfo {
     is_public: bool,
     is_space: bool,
     is_tombstoned: bool,
+    /// SC: The room that replaced this room, if it has a tombstone.
+    successor_room: Option<SuccessorRoom>,
     is_favourite: bool,
     is_low_priority: bool,
     canonical_alias: Option<String>,
@@ -39,6 +47,9 @@ pub struct RoomInfo {
     invited_members_count: u64,
     joined_members_count: u64,
     user_power_levels: HashMap<String, i64>,
+    /// SC: Whether the current user is allowed to enable encryption in this
+    /// room.
+    can_user_enable_encryption: bool,
     highlight_count: u64,
     notification_count: u64,
     unread_count: u64,
@@ -47,8 +58,19 @@ pub struct RoomInfo {
     active_room_call_participants: Vec<String>,
     /// SC: Space-specific fields
     space_children: Vec<SpaceChildInfo>,
+    /// SC: The IDs of the events pinned in this room.
+    pinned_event_ids: Vec<String>,
     /// Whether this room has been explicitly marked as unread
     is_marked_unread: bool,
+    /// SC: Until when the notifications of this room are snoozed, in
+    /// milliseconds since the Unix epoch, if they are currently snoozed.
+    snoozed_until: Option<u64>,
+    /// SC: The source of the unread counts chosen for this room, if it
+    /// overrides the default source.
+    unread_count_source: Option<ScUnreadCountSource>,
+    /// SC: Whether the room is unread but should only be shown in bold,
+    /// without a count, because its notifications don't count in the badges.
+    bold_unread: bool,
     /// "Interesting" messages received in that room, independently of the
     /// notification settings.
     num_unread_messages: u64,
@@ -62,6 +84,17 @@ pub struct RoomInfo {
 
 impl RoomInfo {
     pub(crate) async fn new(room: &matrix_sdk::Room) -> matrix_sdk::Result<Self> {
+        let notification_settings = room.client().notification_settings().await;
+        Self::with_notification_settings(room, &notification_settings).await
+    }
+
+    /// SC: Build the info of the room, reusing the given notification settings
+    /// instead of loading them again, e.g. when building the infos of many
+    /// rooms at once.
+    pub(crate) async fn with_notification_settings(
+        room: &matrix_sdk::Room,
+        notification_settings: &NotificationSettings,
+    ) -> matrix_sdk::Result<Self> {
         let unread_notification_counts = room.unread_notification_counts();
         let unread_count = room.unread_count();
 
@@ -77,10 +110,11 @@ impl RoomInfo {
             raw_name: room.name(),
             topic: room.topic(),
             avatar_url: room.avatar_url().map(Into::into),
-            is_direct: room.is_direct().await?,
+            is_direct: room.is_direct_cached(),
             is_public: room.is_public(),
             is_space: room.is_space(),
             is_tombstoned: room.is_tombstoned(),
+            successor_room: room.successor_room().map(Into::into),
             is_favourite: room.is_favourite(),
             is_low_priority: room.is_low_priority(),
             canonical_alias: room.canonical_alias().map(Into::into),
@@ -100,13 +134,20 @@ impl RoomInfo {
             invited_members_count: room.invited_members_count(),
             joined_members_count: room.joined_members_count(),
             user_power_levels,
+            can_user_enable_encryption: room
+                .can_user_enable_encryption(room.own_user_id())
+                .await
+                .unwrap_or(false),
             highlight_count: unread_notification_counts.highlight_count,
             notification_count: unread_notification_counts.notification_count,
             unread_count: unread_count.unwrap_or_default(),
-            user_defined_notification_mode: room
-                .user_defined_notification_mode()
-                .await
-                .map(Into::into),
+            user_defined_notification_mode: match room.state() {
+                RoomState::Joined => notification_settings
+                    .get_user_defined_room_notification_mode(room.room_id())
+                    .await
+                    .map(Into::into),
+                _ => None,
+            },
             has_room_call: room.has_active_room_call(),
             active_room_call_participants: room
                 .active_room_call_participants()
@@ -114,10 +155,97 @@ impl RoomInfo {
                 .map(|u| u.to_string())
                 .collect(),
             is_marked_unread: room.is_marked_unread(),
+            snoozed_until: room.snoozed_until().map(|until| until.0.into()),
+            unread_count_source: room.unread_count_source(),
+            bold_unread: room.is_bold_unread_only()
+                && (room.num_unread_notifications() > 0 || room.is_marked_unread()),
             space_children: space_children_info(&room),
+            pinned_event_ids: room.pinned_event_ids().iter().map(ToString::to_string).collect(),
             num_unread_messages: room.num_unread_messages(),
             num_unread_notifications: room.num_unread_notifications(),
             num_unread_mentions: room.num_unread_mentions(),
         })
     }
 }
+
+/// SC: The room that replaced a tombstoned room.
+#[derive(uniffi::Record)]
+pub struct SuccessorRoom {
+    /// The ID of the new room.
+    room_id: String,
+    /// The reason of the upgrade given in the tombstone, if any.
+    reason: Option<String>,
+    /// Whether the user has already joined the new room.
+    is_joined: bool,
+}
+
+impl From<matrix_sdk::room::SuccessorRoom> for SuccessorRoom {
+    fn from(value: matrix_sdk::room::SuccessorRoom) -> Self {
+        Self {
+            room_id: value.room_id.to_string(),
+            reason: value.reason,
+            is_joined: value.is_joined,
+        }
+    }
+}
+
+/// SC: The few infos needed to show a room in the room list.
+///
+/// Unlike [`RoomInfo`], it's cheap to build since it doesn't need any async
+/// lookup, so the full [`RoomInfo`] should only be fetched when the room is
+/// opened.
+#[derive(uniffi::Record)]
+pub struct RoomListEntryInfo {
+    id: String,
+    /// The room's name from the room state event if received from sync, or one
+    /// that's been computed otherwise.
+    display_name: Option<String>,
+    avatar_url: Option<String>,
+    /// The plain text body of the latest message, if the latest event is a
+    /// message.
+    latest_preview: Option<String>,
+    is_favourite: bool,
+    is_low_priority: bool,
+    membership: Membership,
+    /// Whether this room has been explicitly marked as unread
+    is_marked_unread: bool,
+    /// "Interesting" messages received in that room, independently of the
+    /// notification settings.
+    num_unread_messages: u64,
+    /// Events that will notify the user, according to their
+    /// notification settings.
+    num_unread_notifications: u64,
+    /// Events causing mentions/highlights for the user, according to their
+    /// notification settings.
+    num_unread_mentions: u64,
+}
+
+impl RoomListEntryInfo {
+    pub(crate) fn new(room: &matrix_sdk::Room) -> Self {
+        Self {
+            id: room.room_id().to_string(),
+            display_name: room.cached_display_name().map(|name| name.to_string()),
+            avatar_url: room.avatar_url().map(Into::into),
+            latest_preview: latest_preview(room),
+            is_favourite: room.is_favourite(),
+            is_low_priority: room.is_low_priority(),
+            membership: room.state().into(),
+            is_marked_unread: room.is_marked_unread(),
+            num_unread_messages: room.num_unread_messages(),
+            num_unread_notifications: room.num_unread_notifications(),
+            num_unread_mentions: room.num_unread_mentions(),
+        }
+    }
+}
+
+/// The plain text body of the latest event of the room, if it's a message.
+fn latest_preview(room: &matrix_sdk::Room) -> Option<String> {
+    let event = room.latest_event()?.event().event.deserialize().ok()?;
+
+    match event {
+        AnySyncTimelineEvent::MessageLike(AnySyncMessageLikeEvent::RoomMessage(
+            SyncRoomMessageEvent::Original(ev),
+        )) => Some(ev.content.body().to_owned()),
+        _ => None,
+    }
+}
@@ -16,11 +16,12 @@ use matrix_sdk_ui::{
     room_list_service::{
         filters::{
             new_filter_all, new_filter_any, new_filter_category, new_filter_favourite,
-            new_filter_fuzzy_match_room_name, new_filter_invite, new_filter_joined,
-            new_filter_non_left, new_filter_none, new_filter_normalized_match_room_name,
-            new_filter_unread, RoomCategory,
+            new_filter_fuzzy_match_room_name, new_filter_invite, new_filter_is_encrypted,
+            new_filter_joined, new_filter_never_opened, new_filter_non_left, new_filter_none,
+            new_filter_normalized_match_room_name, new_filter_orphan_rooms, new_filter_unread,
+            RoomCategory,
         },
-        BoxedFilterFn,
+        BoxedFilterFn, RoomListSummary,
     },
     timeline::default_event_filter,
     unable_to_decrypt_hook::UtdHookManager,
@@ -30,8 +31,8 @@ use tokio::sync::RwLock;
 use crate::{
     error::ClientError,
     room::Room,
-    room_info::RoomInfo,
-    timeline::{EventTimelineItem, Timeline},
+    room_info::{RoomInfo, RoomListEntryInfo},
+    timeline::{DividerGranularity, EventTimelineItem, Timeline},
     timeline_event_filter::TimelineEventTypeFilter,
     TaskHandle, RUNTIME,
 };
@@ -56,6 +57,18 @@ pub enum RoomListError {
     InitializingTimeline { error: String },
     #[error("Event cache ran into an error: {error}")]
     EventCache { error: String },
+    #[error("Store ran into an error: {error}")]
+    Store { error: String },
+    #[error("{} rooms couldn't be marked as read", .failures.len())]
+    MarkAllRead { num_marked: u64, failures: Vec<MarkReadFailure> },
+}
+
+/// SC: A room that couldn't be marked as read, see
+/// [`RoomListService::mark_all_read`].
+#[derive(Debug, uniffi::Record)]
+pub struct MarkReadFailure {
+    pub room_id: String,
+    pub error: String,
 }
 
 impl From<matrix_sdk_ui::room_list_service::Error> for RoomListError {
@@ -74,6 +87,17 @@ impl From<matrix_sdk_ui::room_list_service::Error> for RoomListError {
                 Self::InitializingTimeline { error: source.to_string() }
             }
             EventCache(error) => Self::EventCache { error: error.to_string() },
+            Store(error) => Self::Store { error: error.to_string() },
+            MarkAllRead { num_marked, failures } => Self::MarkAllRead {
+                num_marked: num_marked as u64,
+                failures: failures
+                    .into_iter()
+                    .map(|(room_id, error)| MarkReadFailure {
+                        room_id: room_id.to_string(),
+                        error: error.to_string(),
+                    })
+                    .collect(),
+            },
         }
     }
 }
@@ -134,6 +158,28 @@ impl RoomListService {
         }))
     }
 
+    /// SC: Get the statistics of the last client-side sort of a room list.
+    fn last_sort_stats(&self) -> matrix_sdk_ui::room_list_service::sorters::SortStats {
+        self.inner.last_sort_stats()
+    }
+
+    /// SC: Get a one-shot copy of all the rooms sorted with `sort_order`, to
+    /// preview it without changing the sort order of the live room lists.
+    async fn sorted_snapshot(
+        &self,
+        sort_order: matrix_sdk_ui::room_list_service::sorters::ScSortOrder,
+    ) -> Result<Vec<Arc<RoomListItem>>, RoomListError> {
+        Ok(self
+            .inner
+            .sorted_snapshot(&sort_order)
+            .await?
+            .into_iter()
+            .map(|room| {
+                Arc::new(RoomListItem { inner: Arc::new(room), utd_hook: self.utd_hook.clone() })
+            })
+            .collect())
+    }
+
     async fn all_rooms(self: Arc<Self>) -> Result<Arc<RoomList>, RoomListError> {
         Ok(Arc::new(RoomList {
             room_list_service: self.clone(),
@@ -179,6 +225,62 @@ impl RoomListService {
             }
         })))
     }
+
+    /// SC: Mark the room `room_id` as the most recently opened one.
+    async fn mark_opened(&self, room_id: String) -> Result<(), RoomListError> {
+        let room_id = <&RoomId>::try_from(room_id.as_str()).map_err(RoomListError::from)?;
+
+        Ok(self.inner.mark_opened(room_id).await?)
+    }
+
+    /// SC: Get at most `limit` of the most recently opened rooms, the most
+    /// recent first.
+    async fn recently_opened(&self, limit: u32) -> Result<Vec<String>, RoomListError> {
+        let rooms = self.inner.recently_opened(limit as usize).await?;
+
+        Ok(rooms.into_iter().map(|room_id| room_id.to_string()).collect())
+    }
+
+    /// SC: Mark all the joined rooms matching `filter`, or all the joined
+    /// rooms if `filter` is `None`, as read.
+    ///
+    /// Returns the number of rooms that have been marked as read, or
+    /// [`RoomListError::MarkAllRead`] with the rooms that couldn't be.
+    async fn mark_all_read(
+        &self,
+        filter: Option<RoomListEntriesDynamicFilterKind>,
+    ) -> Result<u64, RoomListError> {
+        let filter = filter.map(|kind| FilterWrapper::from(self.inner.client(), kind).0);
+
+        Ok(self.inner.mark_all_read(filter).await? as u64)
+    }
+
+    /// SC: Listen to the counts of the rooms by tier, see
+    /// [`RoomListSummary`].
+    fn summary(&self, listener: Box<dyn RoomListSummaryListener>) -> Arc<TaskHandle> {
+        let summary_stream = self.inner.summary();
+
+        Arc::new(TaskHandle::new(RUNTIME.spawn(async move {
+            pin_mut!(summary_stream);
+
+            while let Some(summary) = summary_stream.next().await {
+                listener.on_update(summary);
+            }
+        })))
+    }
+
+    /// SC: Listen to the total badge count of the app.
+    fn total_badge_count(&self, listener: Box<dyn RoomListBadgeCountListener>) -> Arc<TaskHandle> {
+        let badge_count_stream = self.inner.total_badge_count();
+
+        Arc::new(TaskHandle::new(RUNTIME.spawn(async move {
+            pin_mut!(badge_count_stream);
+
+            while let Some(badge_count) = badge_count_stream.next().await {
+                listener.on_update(badge_count);
+            }
+        })))
+    }
 }
 
 #[derive(uniffi::Object)]
@@ -347,6 +449,16 @@ pub trait RoomListServiceSyncIndicatorListener: Send + Sync + Debug {
     fn on_update(&self, sync_indicator: RoomListServiceSyncIndicator);
 }
 
+#[uniffi::export(callback_interface)]
+pub trait RoomListSummaryListener: Send + Sync + Debug {
+    fn on_update(&self, summary: RoomListSummary);
+}
+
+#[uniffi::export(callback_interface)]
+pub trait RoomListBadgeCountListener: Send + Sync + Debug {
+    fn on_update(&self, badge_count: u64);
+}
+
 #[derive(uniffi::Enum)]
 pub enum RoomListEntriesUpdate {
     Append { values: Vec<RoomListEntry> },
@@ -417,6 +529,11 @@ impl RoomListDynamicEntriesController {
         self.inner.set_filter(filter)
     }
 
+    /// SC: Set the client-side sort order, applied right away.
+    fn set_sort_order(&self, sort_order: matrix_sdk_ui::room_list_service::sorters::ScSortOrder) {
+        self.inner.set_sort_order(sort_order, &self.client);
+    }
+
     fn add_one_page(&self) {
         self.inner.add_one_page();
     }
@@ -426,6 +543,24 @@ impl RoomListDynamicEntriesController {
     }
 }
 
+/// SC: Describe the sort order with a compact and stable string, e.g. to
+/// attach it to bug reports.
+#[uniffi::export]
+pub fn describe_sort_order(
+    sort_order: matrix_sdk_ui::room_list_service::sorters::ScSortOrder,
+) -> String {
+    sort_order.describe()
+}
+
+/// SC: Parse a description produced by `describe_sort_order`, or return `None`
+/// if it isn't canonical.
+#[uniffi::export]
+pub fn sort_order_from_description(
+    description: String,
+) -> Option<matrix_sdk_ui::room_list_service::sorters::ScSortOrder> {
+    matrix_sdk_ui::room_list_service::sorters::ScSortOrder::from_description(&description)
+}
+
 #[derive(uniffi::Enum)]
 pub enum RoomListEntriesDynamicFilterKind {
     All { filters: Vec<RoomListEntriesDynamicFilterKind> },
@@ -439,6 +574,17 @@ pub enum RoomListEntriesDynamicFilterKind {
     None,
     NormalizedMatchRoomName { pattern: String },
     FuzzyMatchRoomName { pattern: String },
+    /// SC: Rooms that aren't a child of any of the given spaces.
+    OrphanRooms {
+        space_ids: Vec<String>,
+    },
+    /// SC: Joined rooms that the user never opened.
+    NeverOpened,
+    /// SC: Rooms that are encrypted if `encrypted` is true, or unencrypted
+    /// otherwise.
+    IsEncrypted {
+        encrypted: bool,
+    },
 }
 
 #[derive(uniffi::Enum)]
@@ -484,6 +630,14 @@ impl FilterWrapper {
             Kind::FuzzyMatchRoomName { pattern } => {
                 Self(Box::new(new_filter_fuzzy_match_room_name(client, &pattern)))
             }
+            Kind::OrphanRooms { space_ids } => Self(Box::new(new_filter_orphan_rooms(
+                client,
+                space_ids.iter().filter_map(|id| RoomId::parse(id).ok()).collect(),
+            ))),
+            Kind::NeverOpened => Self(Box::new(new_filter_never_opened(client))),
+            Kind::IsEncrypted { encrypted } => {
+                Self(Box::new(new_filter_is_encrypted(client, encrypted)))
+            }
         }
     }
 }
@@ -512,7 +666,7 @@ impl RoomListItem {
     }
 
     fn is_direct(&self) -> bool {
-        RUNTIME.block_on(self.inner.inner_room().is_direct()).unwrap_or(false)
+        self.inner.inner_room().is_direct_cached()
     }
 
     fn canonical_alias(&self) -> Option<String> {
@@ -523,6 +677,12 @@ impl RoomListItem {
         Ok(RoomInfo::new(self.inner.inner_room()).await?)
     }
 
+    /// SC: The few infos needed to show the room in the room list, cheaper to
+    /// get than [`Self::room_info`].
+    fn entry_info(&self) -> RoomListEntryInfo {
+        RoomListEntryInfo::new(self.inner.inner_room())
+    }
+
     /// Build a full `Room` FFI object, filling its associated timeline.
     ///
     /// If its internal timeline hasn't been initialized, it'll fail.
@@ -552,10 +712,27 @@ impl RoomListItem {
     /// * `internal_id_prefix` - An optional String that will be prepended to
     ///   all the timeline item's internal IDs, making it possible to
     ///   distinguish different timeline instances from each other.
+    /// * `collapse_state_events` - SC: Whether runs of consecutive state events
+    ///   should be collapsed into [`VirtualTimelineItem::StateGroup`] items.
+    /// * `collapse_muted_users` - SC: Whether runs of consecutive messages from
+    ///   the users muted in the room should be collapsed into
+    ///   [`VirtualTimelineItem::HiddenMessages`] items.
+    /// * `date_dividers` - SC: Whether [`VirtualTimelineItem::DayDivider`]
+    ///   items should be inserted between the events of different days.
+    /// * `divider_granularity` - SC: The period grouped under a
+    ///   [`VirtualTimelineItem::DayDivider`].
+    ///
+    /// [`VirtualTimelineItem::DayDivider`]: crate::timeline::VirtualTimelineItem::DayDivider
+    /// [`VirtualTimelineItem::StateGroup`]: crate::timeline::VirtualTimelineItem::StateGroup
+    /// [`VirtualTimelineItem::HiddenMessages`]: crate::timeline::VirtualTimelineItem::HiddenMessages
     async fn init_timeline(
         &self,
         event_type_filter: Option<Arc<TimelineEventTypeFilter>>,
         internal_id_prefix: Option<String>,
+        collapse_state_events: bool,
+        collapse_muted_users: bool,
+        date_dividers: bool,
+        divider_granularity: DividerGranularity,
     ) -> Result<(), RoomListError> {
         let mut timeline_builder = self
             .inner
@@ -578,6 +755,16 @@ impl RoomListItem {
             timeline_builder = timeline_builder.with_unable_to_decrypt_hook(utd_hook);
         }
 
+        timeline_builder = timeline_builder
+            .collapse_state_events(collapse_state_events)
+            .collapse_muted_users(collapse_muted_users)
+            .with_date_dividers(date_dividers);
+
+        let (divider_granularity, first_day_of_week) = divider_granularity.into();
+        timeline_builder = timeline_builder
+            .divider_granularity(divider_granularity)
+            .first_day_of_week(first_day_of_week);
+
         self.inner.init_timeline_with_builder(timeline_builder).map_err(RoomListError::from).await
     }
 
@@ -61,6 +61,21 @@ pub fn matrix_to_user_permalink(user_id: String) -> Result<String, ClientError>
     Ok(user_id.matrix_to_uri().to_string())
 }
 
+/// SC: The color of the user with the given ID among `buckets` colors, see
+/// [`matrix_sdk::user_color_index`].
+#[uniffi::export]
+pub fn user_color_index(user_id: String, buckets: u8) -> Result<u8, ClientError> {
+    let user_id = UserId::parse(user_id)?;
+    Ok(matrix_sdk::user_color_index(&user_id, buckets))
+}
+
+/// SC: The initials to show in a text avatar for the given name, see
+/// [`matrix_sdk::avatar_initials`].
+#[uniffi::export]
+pub fn avatar_initials(name: String) -> String {
+    matrix_sdk::avatar_initials(&name)
+}
+
 #[derive(uniffi::Record)]
 pub struct RoomMember {
     pub user_id: String,
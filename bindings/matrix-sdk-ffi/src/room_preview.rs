@@ -1,6 +1,8 @@
 use matrix_sdk::{room_preview::RoomPreview as SdkRoomPreview, RoomState};
 use ruma::space::SpaceRoomJoinRule;
 
+use crate::space_child_info::SpaceChildJoinRule;
+
 /// The preview of a room, be it invited/joined/left, or not.
 #[derive(uniffi::Record)]
 pub struct RoomPreview {
@@ -24,6 +26,11 @@ pub struct RoomPreview {
     pub is_joined: bool,
     /// Is the current user invited to this room?
     pub is_invited: bool,
+    /// SC: Has the current user knocked on this room, and is waiting for the
+    /// request to be accepted?
+    pub is_knocked: bool,
+    /// SC: The join rule of this room.
+    pub join_rule: SpaceChildJoinRule,
     /// is the join rule public for this room?
     pub is_public: bool,
     /// Can we knock (or restricted-knock) to this room?
@@ -43,6 +50,8 @@ impl RoomPreview {
             is_history_world_readable: preview.is_world_readable,
             is_joined: preview.state.map_or(false, |state| state == RoomState::Joined),
             is_invited: preview.state.map_or(false, |state| state == RoomState::Invited),
+            is_knocked: preview.state.map_or(false, |state| state == RoomState::Knocked),
+            join_rule: preview.join_rule.clone().into(),
             is_public: preview.join_rule == SpaceRoomJoinRule::Public,
             can_knock: matches!(
                 preview.join_rule,
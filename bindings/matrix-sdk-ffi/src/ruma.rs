@@ -400,6 +400,24 @@ impl From<NotifyType> for RumaNotifyType {
     }
 }
 
+/// SC: The presence of a user.
+#[derive(Clone, uniffi::Enum)]
+pub enum PresenceState {
+    Online,
+    Unavailable,
+    Offline,
+}
+
+impl From<RumaPresenceState> for PresenceState {
+    fn from(value: RumaPresenceState) -> Self {
+        match value {
+            RumaPresenceState::Online => Self::Online,
+            RumaPresenceState::Unavailable => Self::Unavailable,
+            _ => Self::Offline,
+        }
+    }
+}
+
 #[derive(Clone, uniffi::Record)]
 pub struct EmoteMessageContent {
     pub body: String,
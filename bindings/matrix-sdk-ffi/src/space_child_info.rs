@@ -1,3 +1,6 @@
+use matrix_sdk::room::{SpaceChild, SpaceChildSummary as SdkSpaceChildSummary};
+use ruma::space::SpaceRoomJoinRule;
+
 #[derive(uniffi::Record)]
 pub struct SpaceChildInfo {
     room_id: String,
@@ -5,41 +8,78 @@ pub struct SpaceChildInfo {
     suggested: bool,
 }
 
-impl SpaceChildInfo {
-    pub(crate) fn new(
-        room_id: String,
-        order: Option<String>,
-        suggested: bool,
-    ) -> Self {
-        Self {
-            room_id: room_id,
-            order: order,
-            suggested: suggested,
-        }
+impl From<SpaceChild> for SpaceChildInfo {
+    fn from(value: SpaceChild) -> Self {
+        Self { room_id: value.room_id.to_string(), order: value.order, suggested: value.suggested }
     }
 }
 
-
 pub fn space_children_info(room: &matrix_sdk::Room) -> Vec<SpaceChildInfo> {
-    let mut space_children = Vec::new();
-    if !room.is_space() {
-        return space_children;
+    room.sorted_space_children().into_iter().map(Into::into).collect()
+}
+
+/// The join rule of a space child, as reported in a space summary.
+#[derive(uniffi::Enum)]
+pub enum SpaceChildJoinRule {
+    Public,
+    Knock,
+    Invite,
+    Private,
+    Restricted,
+    KnockRestricted,
+    Custom { repr: String },
+}
+
+impl From<SpaceRoomJoinRule> for SpaceChildJoinRule {
+    fn from(value: SpaceRoomJoinRule) -> Self {
+        match value {
+            SpaceRoomJoinRule::Public => Self::Public,
+            SpaceRoomJoinRule::Knock => Self::Knock,
+            SpaceRoomJoinRule::Invite => Self::Invite,
+            SpaceRoomJoinRule::Private => Self::Private,
+            SpaceRoomJoinRule::Restricted => Self::Restricted,
+            SpaceRoomJoinRule::KnockRestricted => Self::KnockRestricted,
+            other => Self::Custom { repr: other.as_str().to_owned() },
+        }
     }
-    for (r, s) in room.space_children().iter() {
-        // Has room been removed from space again?
-        if let Some(ev) = s.as_original() {
-            // Hasn't been replaced by empty state event?
-            // The spec tells us to ignore children without `via`
-            if !ev.content.via.is_empty() {
-                space_children.push(
-                    SpaceChildInfo::new(
-                        r.to_string(),
-                        ev.content.order.clone(),
-                        ev.content.suggested,
-                    )
-                );
-            }
+}
+
+/// SC: Summary of a space child, merging the local state for joined children
+/// with what the homeserver reports via `/hierarchy` for unjoined ones.
+#[derive(uniffi::Record)]
+pub struct SpaceChildSummary {
+    room_id: String,
+    /// The child's name, if set.
+    name: Option<String>,
+    /// The child's topic, if set.
+    topic: Option<String>,
+    /// The MXC URI to the child's avatar, if set.
+    avatar_url: Option<String>,
+    /// The number of joined members.
+    num_joined_members: u64,
+    join_rule: SpaceChildJoinRule,
+    /// The room type (space, custom) or nothing, if it's a regular room.
+    room_type: Option<String>,
+    /// Whether the current user has joined the child, in which case the
+    /// other fields reflect the live local state.
+    is_joined: bool,
+    order: Option<String>,
+    suggested: bool,
+}
+
+impl From<SdkSpaceChildSummary> for SpaceChildSummary {
+    fn from(value: SdkSpaceChildSummary) -> Self {
+        Self {
+            room_id: value.room_id.to_string(),
+            name: value.name,
+            topic: value.topic,
+            avatar_url: value.avatar_url.map(|url| url.to_string()),
+            num_joined_members: value.num_joined_members,
+            join_rule: value.join_rule.into(),
+            room_type: value.room_type.map(|room_type| room_type.to_string()),
+            is_joined: value.is_joined,
+            order: value.order,
+            suggested: value.suggested,
         }
     }
-    return space_children;
 }
@@ -12,18 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use matrix_sdk::{crypto::types::events::UtdCause, room::power_levels::power_level_user_changes};
-use matrix_sdk_ui::timeline::{PollResult, TimelineDetails};
-use ruma::events::{
-    room::{message::RoomMessageEventContentWithoutRelation, MediaSource},
-    FullStateEventContent,
+use matrix_sdk_ui::timeline::{
+    LiveLocationState, LiveLocationStatus as SdkLiveLocationStatus, LocationAssetType, PollResult,
+    TimelineDetails,
 };
+use ruma::events::room::{message::RoomMessageEventContentWithoutRelation, MediaSource};
 use tracing::warn;
+use url::Url;
 
 use super::ProfileDetails;
-use crate::ruma::{ImageInfo, MessageType, PollKind};
+use crate::{
+    error::ClientError,
+    ruma::{AssetType, ImageInfo, MessageType, PollKind},
+};
 
 #[derive(Clone, uniffi::Object)]
 pub struct TimelineItemContent(pub(crate) matrix_sdk_ui::timeline::TimelineItemContent);
@@ -45,6 +49,7 @@ impl TimelineItemContent {
                 }
             }
             Content::Poll(poll_state) => TimelineItemContentKind::from(poll_state.results()),
+            Content::LiveLocation(state) => TimelineItemContentKind::from(state),
             Content::CallInvite => TimelineItemContentKind::CallInvite,
             Content::CallNotify => TimelineItemContentKind::CallNotify,
             Content::UnableToDecrypt(msg) => {
@@ -52,14 +57,9 @@ impl TimelineItemContent {
             }
             Content::MembershipChange(membership) => TimelineItemContentKind::RoomMembership {
                 user_id: membership.user_id().to_string(),
-                user_display_name: if let FullStateEventContent::Original { content, .. } =
-                    membership.content()
-                {
-                    content.displayname.clone()
-                } else {
-                    None
-                },
+                user_display_name: membership.display_name(),
                 change: membership.change().map(Into::into),
+                reason: membership.reason().map(ToOwned::to_owned),
             },
             Content::ProfileChange(profile) => {
                 let (display_name, prev_display_name) = profile
@@ -82,6 +82,10 @@ impl TimelineItemContent {
                     prev_avatar_url: prev_avatar_url.flatten(),
                 }
             }
+            Content::PinnedEventsChange(change) => TimelineItemContentKind::PinnedEventsChange {
+                pinned: change.pinned().iter().map(ToString::to_string).collect(),
+                unpinned: change.unpinned().iter().map(ToString::to_string).collect(),
+            },
             Content::OtherState(state) => TimelineItemContentKind::State {
                 state_key: state.state_key().to_owned(),
                 content: state.content().into(),
@@ -125,6 +129,20 @@ pub enum TimelineItemContentKind {
         votes: HashMap<String, Vec<String>>,
         end_time: Option<u64>,
         has_been_edited: bool,
+        /// SC: The answers of the own user that are counted, empty if they
+        /// didn't vote.
+        own_answers: Vec<String>,
+    },
+    /// SC: A live location share.
+    LiveLocation {
+        status: LiveLocationStatus,
+        description: Option<String>,
+        asset: AssetType,
+        started_at: u64,
+        expires_at: u64,
+        stopped_at: Option<u64>,
+        last_location: Option<Location>,
+        last_location_ts: Option<u64>,
     },
     CallInvite,
     CallNotify,
@@ -135,6 +153,8 @@ pub enum TimelineItemContentKind {
         user_id: String,
         user_display_name: Option<String>,
         change: Option<MembershipChange>,
+        /// The reason given for the change, e.g. for kicks and bans.
+        reason: Option<String>,
     },
     ProfileChange {
         display_name: Option<String>,
@@ -142,6 +162,13 @@ pub enum TimelineItemContentKind {
         avatar_url: Option<String>,
         prev_avatar_url: Option<String>,
     },
+    /// SC: A change of the events pinned in the room.
+    PinnedEventsChange {
+        /// The IDs of the events that were pinned.
+        pinned: Vec<String>,
+        /// The IDs of the events that were unpinned.
+        unpinned: Vec<String>,
+    },
     State {
         state_key: String,
         content: OtherState,
@@ -170,6 +197,20 @@ impl Message {
         self.0.msgtype().body().to_owned()
     }
 
+    /// SC: The plain text body, without the reply fallback.
+    pub fn plain_body_without_reply_fallback(&self) -> String {
+        self.0.plain_body_without_reply_fallback().to_owned()
+    }
+
+    /// SC: The sanitized HTML body, if any, with the sources of images
+    /// resolved on the homeserver at `homeserver_url`.
+    pub fn formatted_body_sanitized(
+        &self,
+        homeserver_url: String,
+    ) -> Result<Option<String>, ClientError> {
+        Ok(self.0.formatted_body_sanitized(&Url::parse(&homeserver_url)?))
+    }
+
     pub fn in_reply_to(&self) -> Option<InReplyToDetails> {
         self.0.in_reply_to().map(InReplyToDetails::from)
     }
@@ -185,6 +226,160 @@ impl Message {
     pub fn content(&self) -> Arc<RoomMessageEventContentWithoutRelation> {
         Arc::new(RoomMessageEventContentWithoutRelation::new(self.0.msgtype().clone()))
     }
+
+    /// SC: The users and rooms mentioned by this message.
+    pub fn resolved_mentions(&self) -> ResolvedMentions {
+        self.0.resolved_mentions().into()
+    }
+
+    /// SC: Whether this is an audio message recorded as a voice message.
+    pub fn is_voice_message(&self) -> bool {
+        self.0.is_voice_message()
+    }
+
+    /// SC: The duration of this audio message, if known.
+    pub fn duration(&self) -> Option<Duration> {
+        self.0.duration()
+    }
+
+    /// SC: The waveform of this audio message, empty if it doesn't have one.
+    pub fn waveform(&self) -> Vec<u16> {
+        self.0.waveform()
+    }
+
+    /// SC: The parsed location, if this is a location message with a valid
+    /// `geo:` URI.
+    pub fn location(&self) -> Option<Location> {
+        self.0.location().map(Into::into)
+    }
+}
+
+/// SC: A location parsed from a `geo:` URI.
+#[derive(uniffi::Record)]
+pub struct Location {
+    pub geo_uri: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// The uncertainty of the location, in meters.
+    pub uncertainty: Option<f64>,
+    pub description: Option<String>,
+    pub asset: AssetType,
+}
+
+impl From<matrix_sdk_ui::timeline::Location> for Location {
+    fn from(value: matrix_sdk_ui::timeline::Location) -> Self {
+        Self {
+            geo_uri: value.geo_uri,
+            latitude: value.latitude,
+            longitude: value.longitude,
+            uncertainty: value.uncertainty,
+            description: value.description,
+            asset: value.asset.into(),
+        }
+    }
+}
+
+impl From<LocationAssetType> for AssetType {
+    fn from(value: LocationAssetType) -> Self {
+        match value {
+            LocationAssetType::Sender => Self::Sender,
+            LocationAssetType::Pin => Self::Pin,
+        }
+    }
+}
+
+/// SC: The state of a live location share.
+#[derive(uniffi::Enum)]
+pub enum LiveLocationStatus {
+    Started,
+    Updated,
+    Stopped,
+}
+
+impl From<SdkLiveLocationStatus> for LiveLocationStatus {
+    fn from(value: SdkLiveLocationStatus) -> Self {
+        match value {
+            SdkLiveLocationStatus::Started => Self::Started,
+            SdkLiveLocationStatus::Updated => Self::Updated,
+            SdkLiveLocationStatus::Stopped => Self::Stopped,
+        }
+    }
+}
+
+impl From<&LiveLocationState> for TimelineItemContentKind {
+    fn from(value: &LiveLocationState) -> Self {
+        let last_location = value.last_location();
+        Self::LiveLocation {
+            status: value.status().into(),
+            description: value.description().map(ToOwned::to_owned),
+            asset: value.asset().into(),
+            started_at: value.started_at().0.into(),
+            expires_at: value.expires_at().0.into(),
+            stopped_at: value.stopped_at().map(|ts| ts.0.into()),
+            last_location: last_location.map(|update| update.location.clone().into()),
+            last_location_ts: last_location.map(|update| update.timestamp.0.into()),
+        }
+    }
+}
+
+/// SC: The users and rooms mentioned by a message.
+#[derive(uniffi::Record)]
+pub struct ResolvedMentions {
+    pub user_ids: Vec<String>,
+    pub room: bool,
+    pub links: Vec<MentionLink>,
+}
+
+impl From<matrix_sdk_ui::mentions::ResolvedMentions> for ResolvedMentions {
+    fn from(value: matrix_sdk_ui::mentions::ResolvedMentions) -> Self {
+        Self {
+            user_ids: value.user_ids.iter().map(ToString::to_string).collect(),
+            room: value.room,
+            links: value.links.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// SC: A link to a user or a room in the formatted body of a message.
+///
+/// The range is the byte range of the text of the link in the text of the
+/// formatted body, i.e. the concatenation of its text nodes.
+#[derive(uniffi::Record)]
+pub struct MentionLink {
+    pub target: MentionTarget,
+    pub text: String,
+    pub range_start: u64,
+    pub range_end: u64,
+}
+
+impl From<matrix_sdk_ui::mentions::MentionLink> for MentionLink {
+    fn from(value: matrix_sdk_ui::mentions::MentionLink) -> Self {
+        Self {
+            target: value.target.into(),
+            text: value.text,
+            range_start: value.range.start as u64,
+            range_end: value.range.end as u64,
+        }
+    }
+}
+
+#[derive(uniffi::Enum)]
+pub enum MentionTarget {
+    User { user_id: String },
+    Room { room_id: String },
+    RoomAlias { alias: String },
+}
+
+impl From<matrix_sdk_ui::mentions::MentionTarget> for MentionTarget {
+    fn from(value: matrix_sdk_ui::mentions::MentionTarget) -> Self {
+        use matrix_sdk_ui::mentions::MentionTarget as Target;
+
+        match value {
+            Target::User(user_id) => Self::User { user_id: user_id.to_string() },
+            Target::Room(room_id) => Self::Room { room_id: room_id.to_string() },
+            Target::RoomAlias(alias) => Self::RoomAlias { alias: alias.to_string() },
+        }
+    }
 }
 
 #[derive(uniffi::Record)]
@@ -269,6 +464,9 @@ pub struct Reaction {
     pub key: String,
     pub count: u64,
     pub senders: Vec<ReactionSenderData>,
+    /// SC: The `mxc://` URI of the image to render, if this is a custom emoji
+    /// reaction.
+    pub media_url: Option<String>,
 }
 
 #[derive(Clone, uniffi::Record)]
@@ -438,6 +636,7 @@ impl From<PollResult> for TimelineItemContentKind {
             votes: value.votes,
             end_time: value.end_time,
             has_been_edited: value.has_been_edited,
+            own_answers: value.own_answers,
         }
     }
 }
@@ -19,12 +19,17 @@ use as_variant::as_variant;
 use content::{InReplyToDetails, RepliedToEventDetails};
 use eyeball_im::VectorDiff;
 use futures_util::{pin_mut, StreamExt as _};
-use matrix_sdk::attachment::{
-    AttachmentConfig, AttachmentInfo, BaseAudioInfo, BaseFileInfo, BaseImageInfo,
-    BaseThumbnailInfo, BaseVideoInfo, Thumbnail,
+use matrix_sdk::{
+    attachment::{
+        AttachmentConfig, AttachmentInfo, BaseAudioInfo, BaseFileInfo, BaseImageInfo,
+        BaseThumbnailInfo, BaseVideoInfo, Thumbnail,
+    },
+    deserialized_responses::ShieldState as RustShieldState,
+    ComposerDraft,
 };
 use matrix_sdk_ui::timeline::{
-    EventItemOrigin, LiveBackPaginationStatus, Profile, RepliedToEvent, TimelineDetails,
+    self as sdk_timeline, EventItemOrigin, ForwardPaginationStatus, LiveBackPaginationStatus,
+    OwnDeliveryStatus, Profile, ReactionKey, RepliedToEvent, ShieldReason, TimelineDetails,
 };
 use mime::Mime;
 use ruma::{
@@ -32,7 +37,6 @@ use ruma::{
         location::{AssetType as RumaAssetType, LocationContent, ZoomLevel},
         poll::{
             unstable_end::UnstablePollEndEventContent,
-            unstable_response::UnstablePollResponseEventContent,
             unstable_start::{
                 NewUnstablePollStartEventContent, UnstablePollAnswer, UnstablePollAnswers,
                 UnstablePollStartContentBlock,
@@ -44,8 +48,9 @@ use ruma::{
             ForwardThread, LocationMessageEventContent, MessageType,
             RoomMessageEventContentWithoutRelation,
         },
-        AnyMessageLikeEventContent,
+        AnyMessageLikeEventContent, AnySyncTimelineEvent,
     },
+    serde::Raw,
     EventId, OwnedTransactionId,
 };
 use tokio::{
@@ -60,6 +65,7 @@ use crate::{
     client::ProgressWatcher,
     error::{ClientError, RoomError},
     helpers::unwrap_or_clone_arc,
+    room_member::MembershipState,
     ruma::{
         AssetType, AudioInfo, FileInfo, FormattedBody, ImageInfo, PollKind, ThumbnailInfo,
         VideoInfo,
@@ -167,17 +173,176 @@ impl Timeline {
         self.inner.fetch_members().await
     }
 
+    /// SC: Expand the state group with the given unique ID.
+    ///
+    /// Returns whether a state group with that ID was found.
+    pub async fn expand_state_group(&self, unique_id: String) -> bool {
+        self.inner.expand_state_group(&unique_id).await
+    }
+
+    /// SC: Reveal the hidden messages with the given unique ID.
+    ///
+    /// Returns whether hidden messages with that ID were found.
+    pub async fn expand_hidden_messages(&self, unique_id: String) -> bool {
+        self.inner.expand_hidden_messages(&unique_id).await
+    }
+
+    /// SC: Change the period grouped under a day divider, moving the
+    /// existing dividers accordingly.
+    pub async fn set_divider_granularity(&self, granularity: DividerGranularity) {
+        let (granularity, first_day_of_week) = granularity.into();
+        self.inner.set_divider_granularity(granularity, first_day_of_week).await;
+    }
+
+    /// SC: Get the events pinned in the room, see
+    /// [`matrix_sdk_ui::timeline::Timeline::pinned_timeline`].
+    pub async fn pinned_timeline(&self) -> Result<Vec<Arc<EventTimelineItem>>, ClientError> {
+        let items = self.inner.pinned_timeline().await?;
+        Ok(items.into_iter().map(|item| Arc::new(EventTimelineItem(item))).collect())
+    }
+
+    /// SC: Subscribe to the events pinned in the room.
+    ///
+    /// The listener is called with the current pinned events first, then every
+    /// time they change.
+    pub fn subscribe_to_pinned_timeline(
+        self: Arc<Self>,
+        listener: Box<dyn PinnedTimelineListener>,
+    ) -> Arc<TaskHandle> {
+        Arc::new(TaskHandle::new(RUNTIME.spawn(async move {
+            let pinned_stream = self.inner.subscribe_pinned_timeline();
+            pin_mut!(pinned_stream);
+
+            while let Some(result) = pinned_stream.next().await {
+                match result {
+                    Ok(items) => listener.on_update(
+                        items.into_iter().map(|item| Arc::new(EventTimelineItem(item))).collect(),
+                    ),
+                    Err(err) => error!("Failed to load the pinned events: {err}"),
+                }
+            }
+        })))
+    }
+
+    /// SC: Subscribe to the aggregated reactions of the given event, see
+    /// [`matrix_sdk_ui::timeline::Timeline::reactions_summary`].
+    pub async fn subscribe_to_reactions_summary(
+        self: Arc<Self>,
+        event_id: String,
+        listener: Box<dyn ReactionsSummaryListener>,
+    ) -> Result<Arc<TaskHandle>, ClientError> {
+        let event_id = EventId::parse(event_id)?;
+
+        Ok(Arc::new(TaskHandle::new(RUNTIME.spawn(async move {
+            let summary_stream = self.inner.reactions_summary(&event_id).await;
+            pin_mut!(summary_stream);
+
+            while let Some(summary) = summary_stream.next().await {
+                listener.on_update(
+                    summary
+                        .into_iter()
+                        .map(|(key, count, reacted_by_me)| ReactionSummary {
+                            key,
+                            count: count as u64,
+                            reacted_by_me,
+                        })
+                        .collect(),
+                );
+            }
+        }))))
+    }
+
+    /// SC: Subscribe to the index of the read marker in the timeline items,
+    /// see [`matrix_sdk_ui::timeline::Timeline::read_marker_position`].
+    pub fn subscribe_to_read_marker_position(
+        self: Arc<Self>,
+        listener: Box<dyn ReadMarkerPositionListener>,
+    ) -> Arc<TaskHandle> {
+        Arc::new(TaskHandle::new(RUNTIME.spawn(async move {
+            let position_stream = self.inner.read_marker_position().await;
+            pin_mut!(position_stream);
+
+            while let Some(position) = position_stream.next().await {
+                listener.on_update(position.map(|position| position as u64));
+            }
+        })))
+    }
+
+    /// SC: Pin the given event, see
+    /// [`matrix_sdk_ui::timeline::Timeline::pin_event`].
+    pub async fn pin_event(&self, event_id: String) -> Result<bool, ClientError> {
+        let event_id = EventId::parse(event_id)?;
+        Ok(self.inner.pin_event(&event_id).await?)
+    }
+
+    /// SC: Pin the given event, keeping at most `max` pinned events, see
+    /// [`matrix_sdk_ui::timeline::Timeline::pin_event_with_cap`].
+    ///
+    /// Returns the IDs of the events that were unpinned to make room.
+    pub async fn pin_event_with_cap(
+        &self,
+        event_id: String,
+        max: u32,
+    ) -> Result<Vec<String>, ClientError> {
+        let event_id = EventId::parse(event_id)?;
+        let evicted = self.inner.pin_event_with_cap(&event_id, max as usize).await?;
+        Ok(evicted.into_iter().map(|event_id| event_id.to_string()).collect())
+    }
+
+    /// SC: Pin the latest event of the timeline, see
+    /// [`matrix_sdk_ui::timeline::Timeline::pin_latest`].
+    pub async fn pin_latest(&self) -> Result<bool, ClientError> {
+        Ok(self.inner.pin_latest().await?)
+    }
+
+    /// SC: Unpin the given event, see
+    /// [`matrix_sdk_ui::timeline::Timeline::unpin_event`].
+    pub async fn unpin_event(&self, event_id: String) -> Result<bool, ClientError> {
+        let event_id = EventId::parse(event_id)?;
+        Ok(self.inner.unpin_event(&event_id).await?)
+    }
+
+    /// SC: Search the text messages currently loaded in the timeline, see
+    /// [`matrix_sdk_ui::timeline::Timeline::search_local`].
+    pub async fn search_local(&self, query: String) -> Vec<EventItemIdentifier> {
+        self.inner.search_local(&query).await.into_iter().map(Into::into).collect()
+    }
+
+    /// SC: Set a listener called when an item that was unable-to-decrypt gets
+    /// decrypted, see
+    /// [`matrix_sdk_ui::timeline::Timeline::on_late_decryption`].
+    pub async fn on_late_decryption(&self, listener: Box<dyn LateDecryptionListener>) {
+        self.inner
+            .on_late_decryption(Box::new(move |id, time_to_decrypt| {
+                listener.on_late_decryption(
+                    id.into(),
+                    time_to_decrypt.as_millis().try_into().unwrap_or(u64::MAX),
+                )
+            }))
+            .await;
+    }
+
+    /// SC: Import the given JSON events into a detached timeline, see
+    /// [`matrix_sdk_ui::timeline::Timeline::import_events`].
+    pub async fn import_events(&self, events: Vec<String>) -> Result<(), ClientError> {
+        let events = events
+            .into_iter()
+            .map(|event| serde_json::from_str::<Raw<AnySyncTimelineEvent>>(&event))
+            .collect::<Result<_, _>>()?;
+        Ok(self.inner.import_events(events).await?)
+    }
+
+    /// Subscribe to the back-pagination status, whether we are in focused
+    /// mode or in live mode. The current status is sent right away.
     pub async fn subscribe_to_back_pagination_status(
         &self,
         listener: Box<dyn PaginationStatusListener>,
     ) -> Result<Arc<TaskHandle>, ClientError> {
-        let (initial, mut subscriber) = self
-            .inner
-            .live_back_pagination_status()
-            .await
-            .context("can't subscribe to the back-pagination status on a focused timeline")?;
+        let (initial, subscriber) = self.inner.back_pagination_status().await;
 
         Ok(Arc::new(TaskHandle::new(RUNTIME.spawn(async move {
+            pin_mut!(subscriber);
+
             // Send the current state even if it hasn't changed right away.
             listener.on_update(initial);
 
@@ -194,6 +359,32 @@ impl Timeline {
         Ok(self.inner.paginate_backwards(num_events).await?)
     }
 
+    /// SC: Subscribe to the forward-pagination status. The current status is
+    /// sent right away.
+    pub async fn subscribe_to_forward_pagination_status(
+        &self,
+        listener: Box<dyn ForwardPaginationStatusListener>,
+    ) -> Arc<TaskHandle> {
+        let (initial, subscriber) = self.inner.forward_pagination_status().await;
+
+        Arc::new(TaskHandle::new(RUNTIME.spawn(async move {
+            pin_mut!(subscriber);
+
+            listener.on_update(initial);
+
+            while let Some(status) = subscriber.next().await {
+                listener.on_update(status);
+            }
+        })))
+    }
+
+    /// SC: Paginate forwards, whether we are in focused mode or in live mode.
+    ///
+    /// Returns whether we hit the end of the timeline or not.
+    pub async fn paginate_forwards(&self, num_events: u16) -> Result<bool, ClientError> {
+        Ok(self.inner.paginate_forwards(num_events).await?)
+    }
+
     /// Paginate forwards, when in focused mode.
     ///
     /// Returns whether we hit the end of the timeline or not.
@@ -213,11 +404,24 @@ impl Timeline {
         Ok(())
     }
 
+    /// SC: Report the events of the items that are currently visible on screen,
+    /// to send a read receipt for the latest of them once they were visible
+    /// long enough.
+    pub async fn mark_visible(&self, event_ids: Vec<String>) -> Result<(), ClientError> {
+        let event_ids = event_ids.into_iter().map(EventId::parse).collect::<Result<_, _>>()?;
+        self.inner.mark_visible(event_ids).await?;
+        Ok(())
+    }
+
     /// SC: get fully_read marker event ID
     pub fn fully_read_event_id(&self) -> Option<String> {
-        RUNTIME.block_on(async {
-            self.inner.fully_read_event_id().await.map(Into::into)
-        })
+        RUNTIME.block_on(async { self.inner.fully_read_event_id().await.map(Into::into) })
+    }
+
+    /// SC: Get the ID of the first unread event, see
+    /// [`matrix_sdk_ui::timeline::Timeline::first_unread_event_id`].
+    pub async fn first_unread_event_id(&self) -> Option<String> {
+        self.inner.first_unread_event_id().await.map(Into::into)
     }
 
     /// SC: same as send_read_receipt(), but with force_
@@ -443,12 +647,8 @@ impl Timeline {
     ) -> Result<(), ClientError> {
         let poll_start_event_id =
             EventId::parse(poll_start_id).context("Failed to parse EventId")?;
-        let poll_response_event_content =
-            UnstablePollResponseEventContent::new(answers, poll_start_event_id);
-        let event_content =
-            AnyMessageLikeEventContent::UnstablePollResponse(poll_response_event_content);
 
-        if let Err(err) = self.inner.send(event_content).await {
+        if let Err(err) = self.inner.vote_poll(&poll_start_event_id, answers).await {
             error!("unable to send poll response: {err}");
         }
 
@@ -514,6 +714,19 @@ impl Timeline {
         Ok(())
     }
 
+    /// SC: Cancel editing the given item.
+    ///
+    /// Discards the composer draft of the edit and aborts the edits of the
+    /// item that haven't been sent yet, restoring its previous content.
+    ///
+    /// Returns whether an edit has been aborted.
+    pub async fn cancel_edit(
+        &self,
+        edit_item: Arc<EventTimelineItem>,
+    ) -> Result<bool, ClientError> {
+        Ok(self.inner.cancel_edit(&edit_item.0).await?)
+    }
+
     pub async fn send_location(
         self: Arc<Self>,
         body: String,
@@ -595,6 +808,41 @@ impl Timeline {
         Ok(Arc::new(EventTimelineItem(item)))
     }
 
+    /// SC: Get a `matrix.to` permalink to the event of the given item.
+    ///
+    /// Returns `None` if the item is a local echo that hasn't been sent yet.
+    pub async fn matrix_to_permalink_for_item(
+        &self,
+        item: Arc<EventTimelineItem>,
+    ) -> Result<Option<String>, ClientError> {
+        let permalink = item.0.matrix_to_permalink(self.inner.room()).await?;
+        Ok(permalink.map(|uri| uri.to_string()))
+    }
+
+    /// SC: Get a `matrix:` permalink to the event of the given item.
+    ///
+    /// Returns `None` if the item is a local echo that hasn't been sent yet.
+    pub async fn matrix_permalink_for_item(
+        &self,
+        item: Arc<EventTimelineItem>,
+    ) -> Result<Option<String>, ClientError> {
+        let permalink = item.0.matrix_permalink(self.inner.room()).await?;
+        Ok(permalink.map(|uri| uri.to_string()))
+    }
+
+    /// SC: Restore the composer draft of the room, re-entering the edit mode
+    /// if the draft is an in-progress edit.
+    ///
+    /// A draft for an edit of an event that has been redacted in the meantime
+    /// is discarded, and returned as
+    /// [`RestoredComposerDraft::EditTargetRedacted`] to notify the user.
+    pub async fn restore_composer_draft(
+        &self,
+    ) -> Result<Option<RestoredComposerDraft>, ClientError> {
+        let draft = self.inner.restore_composer_draft().await?;
+        Ok(draft.map(Into::into))
+    }
+
     /// Redacts an event from the timeline.
     ///
     /// Only works for events that exist as timeline items.
@@ -702,6 +950,44 @@ pub trait PaginationStatusListener: Sync + Send {
     fn on_update(&self, status: LiveBackPaginationStatus);
 }
 
+/// SC: Listener for [`Timeline::subscribe_to_forward_pagination_status`].
+#[uniffi::export(callback_interface)]
+pub trait ForwardPaginationStatusListener: Sync + Send {
+    fn on_update(&self, status: ForwardPaginationStatus);
+}
+
+/// SC: Listener for [`Timeline::on_late_decryption`].
+#[uniffi::export(callback_interface)]
+pub trait LateDecryptionListener: Sync + Send {
+    fn on_late_decryption(&self, id: EventItemIdentifier, time_to_decrypt_ms: u64);
+}
+
+/// SC: Listener for [`Timeline::subscribe_to_pinned_timeline`].
+#[uniffi::export(callback_interface)]
+pub trait PinnedTimelineListener: Sync + Send {
+    fn on_update(&self, pinned_events: Vec<Arc<EventTimelineItem>>);
+}
+
+/// SC: Listener for [`Timeline::subscribe_to_reactions_summary`].
+#[uniffi::export(callback_interface)]
+pub trait ReactionsSummaryListener: Sync + Send {
+    fn on_update(&self, summary: Vec<ReactionSummary>);
+}
+
+/// SC: Listener for [`Timeline::subscribe_to_read_marker_position`].
+#[uniffi::export(callback_interface)]
+pub trait ReadMarkerPositionListener: Sync + Send {
+    fn on_update(&self, position: Option<u64>);
+}
+
+/// SC: The aggregated reactions of an event for a given key.
+#[derive(Clone, uniffi::Record)]
+pub struct ReactionSummary {
+    pub key: String,
+    pub count: u64,
+    pub reacted_by_me: bool,
+}
+
 #[derive(Clone, uniffi::Object)]
 pub enum TimelineDiff {
     Append { values: Vec<Arc<TimelineItem>> },
@@ -856,6 +1142,20 @@ impl TimelineItem {
         match self.0.as_virtual()? {
             VItem::DayDivider(ts) => Some(VirtualTimelineItem::DayDivider { ts: ts.0.into() }),
             VItem::ReadMarker => Some(VirtualTimelineItem::ReadMarker),
+            VItem::StateGroup { count, summary, expanded } => {
+                Some(VirtualTimelineItem::StateGroup {
+                    count: *count as u64,
+                    summary: summary.clone().into(),
+                    expanded: *expanded,
+                })
+            }
+            VItem::HiddenMessages { sender, count, expanded } => {
+                Some(VirtualTimelineItem::HiddenMessages {
+                    sender: sender.to_string(),
+                    count: *count as u64,
+                    expanded: *expanded,
+                })
+            }
         }
     }
 
@@ -878,12 +1178,18 @@ pub enum EventSendState {
     SendingFailed {
         /// Stringified error message.
         error: String,
+        /// SC: The cause of the failure.
+        send_error: SendError,
         /// Whether the error is considered recoverable or not.
         ///
         /// An error that's recoverable will disable the room's send queue,
         /// while an unrecoverable error will be parked, until the user
         /// decides to cancel sending it.
         is_recoverable: bool,
+        /// SC: When sending the event will be retried automatically, in
+        /// milliseconds since the unix epoch, if the homeserver rate-limited
+        /// the user.
+        retry_at: Option<u64>,
     },
     /// The local event has been sent successfully to the server.
     Sent { event_id: String },
@@ -895,14 +1201,112 @@ impl From<&matrix_sdk_ui::timeline::EventSendState> for EventSendState {
 
         match value {
             NotSentYet => Self::NotSentYet,
-            SendingFailed { error, is_recoverable } => {
-                Self::SendingFailed { error: error.to_string(), is_recoverable: *is_recoverable }
-            }
+            SendingFailed { error, send_error, is_recoverable, retry_at } => Self::SendingFailed {
+                error: error.to_string(),
+                send_error: send_error.clone().into(),
+                is_recoverable: *is_recoverable,
+                retry_at: retry_at.map(|ts| ts.0.into()),
+            },
             Sent { event_id } => Self::Sent { event_id: event_id.to_string() },
         }
     }
 }
 
+/// SC: Why sending a local event failed, see
+/// [`matrix_sdk_ui::timeline::SendError`].
+#[derive(Clone, uniffi::Record)]
+pub struct SendError {
+    pub kind: SendErrorKind,
+    pub status_code: Option<u16>,
+    pub errcode: Option<String>,
+    /// Whether sending the event again later might succeed.
+    pub is_retryable: bool,
+}
+
+impl From<matrix_sdk_ui::timeline::SendError> for SendError {
+    fn from(value: matrix_sdk_ui::timeline::SendError) -> Self {
+        Self {
+            is_retryable: value.is_retryable(),
+            kind: value.kind.into(),
+            status_code: value.status_code,
+            errcode: value.errcode,
+        }
+    }
+}
+
+/// SC: The cause of a [`SendError`].
+#[derive(Clone, uniffi::Enum)]
+pub enum SendErrorKind {
+    Network,
+    RateLimited { retry_after_ms: Option<u64> },
+    Forbidden,
+    TooLarge,
+    Other,
+}
+
+impl From<matrix_sdk_ui::timeline::SendErrorKind> for SendErrorKind {
+    fn from(value: matrix_sdk_ui::timeline::SendErrorKind) -> Self {
+        use matrix_sdk_ui::timeline::SendErrorKind as Kind;
+
+        match value {
+            Kind::Network => Self::Network,
+            Kind::RateLimited { retry_after } => Self::RateLimited {
+                retry_after_ms: retry_after.map(|delay| delay.as_millis() as u64),
+            },
+            Kind::Forbidden => Self::Forbidden,
+            Kind::TooLarge => Self::TooLarge,
+            Kind::Other => Self::Other,
+        }
+    }
+}
+
+/// SC: Either the transaction ID of a local echo, or the event ID of an
+/// event.
+#[derive(Clone, uniffi::Enum)]
+pub enum EventItemIdentifier {
+    TransactionId { transaction_id: String },
+    EventId { event_id: String },
+}
+
+impl From<matrix_sdk_ui::timeline::EventItemIdentifier> for EventItemIdentifier {
+    fn from(value: matrix_sdk_ui::timeline::EventItemIdentifier) -> Self {
+        use matrix_sdk_ui::timeline::EventItemIdentifier as Identifier;
+
+        match value {
+            Identifier::TransactionId(transaction_id) => {
+                Self::TransactionId { transaction_id: transaction_id.to_string() }
+            }
+            Identifier::EventId(event_id) => Self::EventId { event_id: event_id.to_string() },
+        }
+    }
+}
+
+/// SC: The composer draft of a room, as restored by
+/// [`Timeline::restore_composer_draft`].
+#[derive(uniffi::Enum)]
+pub enum RestoredComposerDraft {
+    /// A draft for a new message, or for a reply.
+    Draft { draft: ComposerDraft },
+    /// A draft for an edit of `item`, if it's loaded in the timeline.
+    Edit { draft: ComposerDraft, item: Option<Arc<EventTimelineItem>> },
+    /// A discarded draft for an edit of an event that has been redacted.
+    EditTargetRedacted { draft: ComposerDraft },
+}
+
+impl From<matrix_sdk_ui::timeline::RestoredComposerDraft> for RestoredComposerDraft {
+    fn from(value: matrix_sdk_ui::timeline::RestoredComposerDraft) -> Self {
+        use matrix_sdk_ui::timeline::RestoredComposerDraft as Draft;
+
+        match value {
+            Draft::Draft(draft) => Self::Draft { draft },
+            Draft::Edit { draft, item } => {
+                Self::Edit { draft, item: item.map(|item| Arc::new(EventTimelineItem(item))) }
+            }
+            Draft::EditTargetRedacted(draft) => Self::EditTargetRedacted { draft },
+        }
+    }
+}
+
 #[derive(uniffi::Object)]
 pub struct EventTimelineItem(pub(crate) matrix_sdk_ui::timeline::EventTimelineItem);
 
@@ -932,6 +1336,11 @@ impl EventTimelineItem {
         self.0.sender_profile().into()
     }
 
+    /// SC: The current membership of the sender in the room, if known.
+    pub fn sender_membership_now(&self) -> Option<MembershipState> {
+        self.0.sender_membership_now().cloned().map(Into::into)
+    }
+
     pub fn is_own(&self) -> bool {
         self.0.is_own()
     }
@@ -944,6 +1353,17 @@ impl EventTimelineItem {
         Arc::new(TimelineItemContent(self.0.content().clone()))
     }
 
+    /// SC: The details of the event this item replies to, if it is a reply.
+    pub fn in_reply_to(&self) -> Option<InReplyToDetails> {
+        self.0.in_reply_to().as_ref().map(Into::into)
+    }
+
+    /// SC: The ID of the event this item replaces, if it's the local echo of
+    /// an edit of an event that isn't in the timeline.
+    pub fn replaces_event_id(&self) -> Option<String> {
+        self.0.replaces_event_id().map(|event_id| event_id.to_string())
+    }
+
     pub fn timestamp(&self) -> u64 {
         self.0.timestamp().0.into()
     }
@@ -962,6 +1382,7 @@ impl EventTimelineItem {
                         timestamp: v.timestamp.0.into(),
                     })
                     .collect(),
+                media_url: ReactionKey::parse(k).media_url().map(ToString::to_string),
             })
             .collect()
     }
@@ -989,6 +1410,80 @@ impl EventTimelineItem {
     pub fn can_be_replied_to(&self) -> bool {
         self.0.can_be_replied_to()
     }
+
+    /// SC: Whether this event is pinned in the room.
+    pub fn is_pinned(&self) -> bool {
+        self.0.is_pinned()
+    }
+
+    /// SC: The delivery status of this event, if it was sent by the current
+    /// user.
+    pub fn own_delivery_status(&self) -> Option<OwnDeliveryStatus> {
+        self.0.own_delivery_status()
+    }
+
+    /// SC: Why the authenticity of this event can't be guaranteed, if that's
+    /// the case.
+    pub fn shield_reason(&self) -> Option<ShieldReason> {
+        self.0.shield_reason()
+    }
+
+    /// SC: The shield to show for this event, in the mode of the timeline.
+    pub fn shield(&self) -> ShieldState {
+        self.0.shield().into()
+    }
+
+    /// SC: The shield to show for this event, in strict or lax mode.
+    pub fn get_shield(&self, strict: bool) -> ShieldState {
+        self.0.get_shield(strict).into()
+    }
+
+    /// SC: The details of the timestamp of this event, to diagnose events
+    /// whose sender's clock is off.
+    pub fn timestamp_info(&self) -> TimestampInfo {
+        self.0.timestamp_info().into()
+    }
+}
+
+/// SC: The details of the timestamp of an event, see
+/// [`EventTimelineItem::timestamp_info`].
+#[derive(uniffi::Record)]
+pub struct TimestampInfo {
+    pub origin_server_ts: u64,
+    pub age_derived_ts: Option<u64>,
+    pub clock_skew_ms: Option<i64>,
+}
+
+impl From<matrix_sdk_ui::timeline::TimestampInfo> for TimestampInfo {
+    fn from(value: matrix_sdk_ui::timeline::TimestampInfo) -> Self {
+        Self {
+            origin_server_ts: value.origin_server_ts.0.into(),
+            age_derived_ts: value.age_derived_ts.map(|ts| ts.0.into()),
+            clock_skew_ms: value.clock_skew_ms,
+        }
+    }
+}
+
+/// SC: The shield to show for an event, see
+/// [`EventTimelineItem::get_shield`].
+#[derive(uniffi::Enum)]
+pub enum ShieldState {
+    /// A red shield with a tooltip containing the given message.
+    Red { message: String },
+    /// A grey shield with a tooltip containing the given message.
+    Grey { message: String },
+    /// No shield.
+    None,
+}
+
+impl From<RustShieldState> for ShieldState {
+    fn from(value: RustShieldState) -> Self {
+        match value {
+            RustShieldState::Red { message } => Self::Red { message: message.to_owned() },
+            RustShieldState::Grey { message } => Self::Grey { message: message.to_owned() },
+            RustShieldState::None => Self::None,
+        }
+    }
 }
 
 #[derive(uniffi::Record)]
@@ -1009,12 +1504,48 @@ pub struct EventTimelineItemDebugInfo {
     latest_edit_json: Option<String>,
 }
 
+/// SC: The period grouped under a day divider.
+#[derive(Clone, Copy, uniffi::Enum)]
+pub enum DividerGranularity {
+    Day,
+    Week { starts_on_sunday: bool },
+    Month,
+}
+
+impl From<DividerGranularity> for (sdk_timeline::DividerGranularity, sdk_timeline::Weekday) {
+    fn from(granularity: DividerGranularity) -> Self {
+        match granularity {
+            DividerGranularity::Day => {
+                (sdk_timeline::DividerGranularity::Day, sdk_timeline::Weekday::Mon)
+            }
+            DividerGranularity::Week { starts_on_sunday } => (
+                sdk_timeline::DividerGranularity::Week,
+                if starts_on_sunday {
+                    sdk_timeline::Weekday::Sun
+                } else {
+                    sdk_timeline::Weekday::Mon
+                },
+            ),
+            DividerGranularity::Month => {
+                (sdk_timeline::DividerGranularity::Month, sdk_timeline::Weekday::Mon)
+            }
+        }
+    }
+}
+
 #[derive(uniffi::Enum)]
 pub enum ProfileDetails {
     Unavailable,
     Pending,
-    Ready { display_name: Option<String>, display_name_ambiguous: bool, avatar_url: Option<String> },
-    Error { message: String },
+    Ready {
+        display_name: Option<String>,
+        display_name_ambiguous: bool,
+        avatar_url: Option<String>,
+        membership: Option<MembershipState>,
+    },
+    Error {
+        message: String,
+    },
 }
 
 impl From<&TimelineDetails<Profile>> for ProfileDetails {
@@ -1026,6 +1557,7 @@ impl From<&TimelineDetails<Profile>> for ProfileDetails {
                 display_name: profile.display_name.clone(),
                 display_name_ambiguous: profile.display_name_ambiguous,
                 avatar_url: profile.avatar_url.as_ref().map(ToString::to_string),
+                membership: profile.membership.clone().map(Into::into),
             },
             TimelineDetails::Error(e) => Self::Error { message: e.to_string() },
         }
@@ -1108,6 +1640,41 @@ pub enum VirtualTimelineItem {
 
     /// The user's own read marker.
     ReadMarker,
+
+    /// SC: A header for a run of consecutive state events.
+    ///
+    /// The `count` event items following this one belong to the group, and
+    /// should be hidden while it isn't `expanded`.
+    StateGroup { count: u64, summary: StateGroupSummary, expanded: bool },
+
+    /// SC: A header for a run of consecutive messages from a user muted in the
+    /// room.
+    ///
+    /// The `count` event items following this one are hidden messages, and
+    /// should be hidden while it isn't `expanded`.
+    HiddenMessages { sender: String, count: u64, expanded: bool },
+}
+
+/// SC: A summary of the events grouped in a
+/// [`VirtualTimelineItem::StateGroup`].
+#[derive(uniffi::Record)]
+pub struct StateGroupSummary {
+    /// The distinct senders of the grouped events, in timeline order.
+    pub senders: Vec<String>,
+    pub membership_changes: u64,
+    pub profile_changes: u64,
+    pub other_state_changes: u64,
+}
+
+impl From<matrix_sdk_ui::timeline::StateGroupSummary> for StateGroupSummary {
+    fn from(value: matrix_sdk_ui::timeline::StateGroupSummary) -> Self {
+        Self {
+            senders: value.senders.iter().map(ToString::to_string).collect(),
+            membership_changes: value.membership_changes as u64,
+            profile_changes: value.profile_changes as u64,
+            other_state_changes: value.other_state_changes as u64,
+        }
+    }
 }
 
 /// A [`TimelineItem`](super::TimelineItem) that doesn't correspond to an event.
@@ -111,6 +111,20 @@ pub async fn generate_webview_url(
     .map(|url| url.to_string())?)
 }
 
+/// SC: Get the URL of the Element Call widget for the given room, to offer to
+/// join its call.
+///
+/// This is a shortcut for [`new_virtual_element_call_widget`] followed by
+/// [`generate_webview_url`].
+#[uniffi::export(async_runtime = "tokio")]
+pub async fn element_call_url(
+    room: Arc<Room>,
+    options: VirtualElementCallWidgetOptions,
+    props: ClientProperties,
+) -> Result<String, ParseError> {
+    Ok(room.inner.element_call_url(options.into(), props.into()).await?.to_string())
+}
+
 /// Defines if a call is encrypted and which encryption system should be used.
 ///
 /// This controls the url parameters: `perParticipantE2EE`, `password`.
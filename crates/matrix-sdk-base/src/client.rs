@@ -38,6 +38,7 @@ use ruma::{
     api::client as api,
     events::{
         ignored_user_list::IgnoredUserListEvent,
+        presence::PresenceState,
         push_rules::{PushRulesEvent, PushRulesEventContent},
         room::{
             member::{MembershipState, RoomMemberEventContent, SyncRoomMemberEvent},
@@ -47,8 +48,8 @@ use ruma::{
         },
         AnyGlobalAccountDataEvent, AnyRoomAccountDataEvent, AnyStrippedStateEvent,
         AnySyncEphemeralRoomEvent, AnySyncMessageLikeEvent, AnySyncStateEvent,
-        AnySyncTimelineEvent, GlobalAccountDataEventType, StateEvent, StateEventType,
-        SyncStateEvent,
+        AnySyncTimelineEvent, GlobalAccountDataEventType, RoomAccountDataEvent, StateEvent,
+        StateEventType, StaticEventContent, SyncStateEvent,
     },
     push::{Action, PushConditionRoomCtx, Ruleset},
     serde::Raw,
@@ -66,7 +67,10 @@ use crate::RoomMemberships;
 use crate::{
     deserialized_responses::{RawAnySyncOrStrippedTimelineEvent, SyncTimelineEvent},
     error::{Error, Result},
-    rooms::{normal::RoomInfoUpdate, Room, RoomInfo, RoomState},
+    rooms::{
+        normal::RoomInfoUpdate, DisplayNameGenerator, Room, RoomInfo, RoomState,
+        ScBoldUnreadEventContent, ScSnoozeEventContent, ScUnreadCountSourceEventContent,
+    },
     store::{
         ambiguity_map::AmbiguityCache, DynStateStore, MemoryStore, Result as StoreResult,
         StateChanges, StateStoreDataKey, StateStoreDataValue, StateStoreExt, Store, StoreConfig,
@@ -97,6 +101,14 @@ pub struct BaseClient {
     /// Observable of when a user is ignored/unignored.
     pub(crate) ignore_user_list_changes: SharedObservable<Vec<String>>,
 
+    /// SC: The last-known presence of the users, as received in sync
+    /// responses.
+    pub(crate) presence: SharedObservable<BTreeMap<OwnedUserId, PresenceState>>,
+
+    /// SC: The IDs of the valid children of every space, kept up to date
+    /// from the room infos.
+    pub(crate) space_children: SharedObservable<BTreeMap<OwnedRoomId, BTreeSet<OwnedRoomId>>>,
+
     /// A sender that is used to communicate changes to room information. Each
     /// event contains the room and a boolean whether this event should
     /// trigger a room list update.
@@ -135,6 +147,8 @@ impl BaseClient {
             #[cfg(feature = "e2e-encryption")]
             olm_machine: Default::default(),
             ignore_user_list_changes: Default::default(),
+            presence: Default::default(),
+            space_children: Default::default(),
             roominfo_update_sender,
         }
     }
@@ -147,7 +161,20 @@ impl BaseClient {
         #[cfg(feature = "e2e-encryption")]
         let config = config.crypto_store(self.crypto_store.clone());
 
-        Self::with_store_config(config)
+        let mut client = Self::with_store_config(config);
+        client.store.display_name_generator = self.store.display_name_generator.clone();
+        client
+    }
+
+    /// SC: Use a custom room name computation for rooms that have neither an
+    /// explicit name nor a canonical alias.
+    ///
+    /// The generator is consulted by [`Room::compute_display_name`]; when it
+    /// returns `None`, the heroes-based name from the spec is used. This must
+    /// be set before the session is restored to apply to every room.
+    pub fn with_display_name_generator(mut self, generator: DisplayNameGenerator) -> Self {
+        self.store.display_name_generator = Some(generator);
+        self
     }
 
     /// Get the session meta information.
@@ -216,6 +243,17 @@ impl BaseClient {
         debug!(user_id = ?session_meta.user_id, device_id = ?session_meta.device_id, "Restoring login");
         self.store.set_session_meta(session_meta.clone(), &self.roominfo_update_sender).await?;
 
+        // SC: Index the children of the restored spaces.
+        let room_infos: Vec<_> = self.store.rooms().iter().map(Room::clone_info).collect();
+        self.update_space_children(room_infos.iter());
+
+        // SC: Restore the ignored users, so that they're known before the first sync.
+        if let Some(event) =
+            self.store.get_account_data_event(GlobalAccountDataEventType::IgnoredUserList).await?
+        {
+            self.update_ignore_user_list(&event);
+        }
+
         #[cfg(feature = "e2e-encryption")]
         self.regenerate_olm(custom_account).await?;
 
@@ -473,6 +511,16 @@ impl BaseClient {
             }
         }
 
+        // SC: Cache whether the invite is for a direct message, to know it without
+        // reading the store, see `Room::is_direct_cached`.
+        if let Some(AnyStrippedStateEvent::RoomMember(member)) = state_events
+            .get(&StateEventType::RoomMember)
+            .and_then(|members| members.get(room.own_user_id().as_str()))
+            .and_then(|event| event.deserialize().ok())
+        {
+            room_info.set_direct_invite(member.content.is_direct.unwrap_or(false));
+        }
+
         changes.stripped_state.insert(room_info.room_id().to_owned(), state_events.clone());
 
         // We need to check for notifications after we have handled all state
@@ -596,6 +644,41 @@ impl BaseClient {
                         });
                     }
 
+                    // SC: The snooze is a custom event.
+                    _ if event.event_type().to_string() == ScSnoozeEventContent::TYPE => {
+                        if let Ok(event) =
+                            raw_event.deserialize_as::<RoomAccountDataEvent<ScSnoozeEventContent>>()
+                        {
+                            on_room_info(room_id, changes, self, |room_info| {
+                                room_info.base_info.snoozed_until = Some(event.content.until);
+                            });
+                        }
+                    }
+
+                    // SC: The source of the unread counts is a custom event.
+                    _ if event.event_type().to_string()
+                        == ScUnreadCountSourceEventContent::TYPE =>
+                    {
+                        if let Ok(event) = raw_event.deserialize_as::<RoomAccountDataEvent<
+                            ScUnreadCountSourceEventContent,
+                        >>() {
+                            on_room_info(room_id, changes, self, |room_info| {
+                                room_info.base_info.unread_count_source = event.content.source;
+                            });
+                        }
+                    }
+
+                    // SC: Rooms that are only shown in bold use a custom event.
+                    _ if event.event_type().to_string() == ScBoldUnreadEventContent::TYPE => {
+                        if let Ok(event) = raw_event
+                            .deserialize_as::<RoomAccountDataEvent<ScBoldUnreadEventContent>>()
+                        {
+                            on_room_info(room_id, changes, self, |room_info| {
+                                room_info.base_info.bold_unread_only = event.content.enabled;
+                            });
+                        }
+                    }
+
                     // Nothing.
                     _ => {}
                 }
@@ -1043,6 +1126,38 @@ impl BaseClient {
             new_rooms.invite.insert(room_id, new_info);
         }
 
+        for (room_id, new_info) in response.rooms.knock {
+            let room = self.store.get_or_create_room(
+                &room_id,
+                RoomState::Knocked,
+                self.roominfo_update_sender.clone(),
+            );
+
+            let mut room_info = room.clone_info();
+            room_info.mark_as_knocked();
+            room_info.mark_state_fully_synced();
+
+            let mut state_events = BTreeMap::new();
+
+            for raw_event in &new_info.knock_state.events {
+                match raw_event.deserialize() {
+                    Ok(e) => {
+                        room_info.handle_stripped_state_event(&e);
+                        state_events
+                            .entry(e.event_type())
+                            .or_insert_with(BTreeMap::new)
+                            .insert(e.state_key().to_owned(), raw_event.clone());
+                    }
+                    Err(err) => {
+                        warn!(?room_id, "Couldn't deserialize stripped state event: {err:?}");
+                    }
+                }
+            }
+
+            changes.stripped_state.insert(room_id, state_events);
+            changes.add_room(room_info);
+        }
+
         // TODO remove this, we're processing account data events here again
         // because we want to have the push rules in place before we process
         // rooms and their events, but we want to create the rooms before we
@@ -1089,31 +1204,125 @@ impl BaseClient {
     }
 
     pub(crate) fn apply_changes(&self, changes: &StateChanges, trigger_room_list_update: bool) {
-        if changes.account_data.contains_key(&GlobalAccountDataEventType::IgnoredUserList) {
-            if let Some(event) =
-                changes.account_data.get(&GlobalAccountDataEventType::IgnoredUserList)
-            {
-                match event.deserialize_as::<IgnoredUserListEvent>() {
-                    Ok(event) => {
-                        let user_ids: Vec<String> =
-                            event.content.ignored_users.keys().map(|id| id.to_string()).collect();
+        if let Some(event) = changes.account_data.get(&GlobalAccountDataEventType::IgnoredUserList)
+        {
+            self.update_ignore_user_list(event);
+        }
 
-                        self.ignore_user_list_changes.set(user_ids);
-                    }
-                    Err(error) => {
-                        warn!("Failed to deserialize ignored user list event: {error}")
+        for (room_id, room_info) in &changes.room_infos {
+            if let Some(room) = self.store.room(room_id) {
+                room.set_room_info(room_info.clone(), trigger_room_list_update)
+            }
+        }
+
+        if !changes.presence.is_empty() {
+            self.apply_presence_changes(changes);
+        }
+
+        self.update_space_children(changes.room_infos.values());
+    }
+
+    /// SC: Update the index of the children of the given rooms if they are
+    /// spaces, and trigger a room list update for the rooms that were added
+    /// to or removed from a space.
+    fn update_space_children<'a>(&self, room_infos: impl Iterator<Item = &'a RoomInfo>) {
+        let mut updated_rooms = BTreeSet::new();
+
+        self.space_children.update(|index| {
+            for room_info in room_infos {
+                let children: BTreeSet<_> = room_info
+                    .base_info
+                    .space_children
+                    .iter()
+                    // Children without `via` are to be ignored, per the spec.
+                    .filter(|(_, event)| {
+                        event.as_original().is_some_and(|event| !event.content.via.is_empty())
+                    })
+                    .map(|(room_id, _)| room_id.clone())
+                    .collect();
+
+                let previous = if children.is_empty() {
+                    index.remove(room_info.room_id())
+                } else {
+                    index.insert(room_info.room_id().to_owned(), children.clone())
+                };
+
+                updated_rooms
+                    .extend(previous.unwrap_or_default().symmetric_difference(&children).cloned());
+            }
+        });
+
+        for room_id in updated_rooms {
+            // Ignore the error: it means nobody is listening.
+            let _ = self
+                .roominfo_update_sender
+                .send(RoomInfoUpdate { room_id, trigger_room_list_update: true });
+        }
+    }
+
+    /// SC: Whether the given room is a valid child of any of the given spaces.
+    ///
+    /// The children of the spaces are indexed as the spaces are synced, so
+    /// this doesn't need to read the state of the spaces.
+    pub fn is_space_child(&self, room_id: &RoomId, space_ids: &[OwnedRoomId]) -> bool {
+        let index = self.space_children.read();
+        space_ids
+            .iter()
+            .any(|space_id| index.get(space_id).is_some_and(|children| children.contains(room_id)))
+    }
+
+    /// SC: Update the presence cache, and trigger a room list update for the
+    /// DMs whose partner's presence changed.
+    fn apply_presence_changes(&self, changes: &StateChanges) {
+        let mut updated_users = BTreeSet::new();
+
+        self.presence.update(|presence| {
+            for (user_id, event) in &changes.presence {
+                match event.deserialize() {
+                    Ok(event) => {
+                        if presence.get(user_id) != Some(&event.content.presence) {
+                            presence.insert(user_id.clone(), event.content.presence);
+                            updated_users.insert(user_id.clone());
+                        }
                     }
+                    Err(error) => warn!("Failed to deserialize presence event: {error}"),
                 }
             }
+        });
+
+        if updated_users.is_empty() {
+            return;
         }
 
-        for (room_id, room_info) in &changes.room_infos {
-            if let Some(room) = self.store.room(room_id) {
-                room.set_room_info(room_info.clone(), trigger_room_list_update)
+        for room in self.store.rooms() {
+            let dm_targets = room.direct_targets();
+            if dm_targets.len() == 1 && dm_targets.iter().any(|id| updated_users.contains(id)) {
+                // Ignore the error: it means nobody is listening.
+                let _ = self.roominfo_update_sender.send(RoomInfoUpdate {
+                    room_id: room.room_id().to_owned(),
+                    trigger_room_list_update: true,
+                });
             }
         }
     }
 
+    /// SC: Get the last-known presence of the given user.
+    ///
+    /// This is only populated from the presence events received in sync
+    /// responses, so it may be stale, or missing if the server doesn't send
+    /// presence.
+    pub fn presence(&self, user_id: &UserId) -> Option<PresenceState> {
+        self.presence.read().get(user_id).cloned()
+    }
+
+    /// SC: Returns a subscriber that publishes the last-known presence of all
+    /// the users every time it changes.
+    pub fn subscribe_to_presence_changes(
+        &self,
+    ) -> Subscriber<BTreeMap<OwnedUserId, PresenceState>> {
+        self.presence.subscribe()
+    }
+
     /// Receive a get member events response and convert it to a deserialized
     /// `MembersResponse`
     ///
@@ -1454,6 +1663,27 @@ impl BaseClient {
         self.ignore_user_list_changes.subscribe()
     }
 
+    /// SC: Get the users currently ignored by the user, as of the last sync.
+    pub fn ignored_users(&self) -> Vec<String> {
+        self.ignore_user_list_changes.get()
+    }
+
+    /// Publish the ignored users of the given `m.ignored_user_list` event, if
+    /// they changed.
+    fn update_ignore_user_list(&self, event: &Raw<AnyGlobalAccountDataEvent>) {
+        match event.deserialize_as::<IgnoredUserListEvent>() {
+            Ok(event) => {
+                let user_ids: Vec<String> =
+                    event.content.ignored_users.keys().map(|id| id.to_string()).collect();
+
+                self.ignore_user_list_changes.set_if_not_eq(user_ids);
+            }
+            Err(error) => {
+                warn!("Failed to deserialize ignored user list event: {error}")
+            }
+        }
+    }
+
     pub(crate) fn deserialize_state_events(
         raw_events: &[Raw<AnySyncStateEvent>],
     ) -> Vec<(Raw<AnySyncStateEvent>, AnySyncStateEvent)> {
@@ -1519,8 +1749,8 @@ fn handle_room_member_event_for_profiles(
 #[cfg(test)]
 mod tests {
     use matrix_sdk_test::{
-        async_test, response_from_file, sync_timeline_event, InvitedRoomBuilder, LeftRoomBuilder,
-        StateTestEvent, StrippedStateTestEvent, SyncResponseBuilder,
+        async_test, response_from_file, sync_timeline_event, InvitedRoomBuilder, JoinedRoomBuilder,
+        LeftRoomBuilder, StateTestEvent, StrippedStateTestEvent, SyncResponseBuilder,
     };
     use ruma::{
         api::{client as api, IncomingResponse},
@@ -1580,6 +1810,100 @@ mod tests {
         assert_eq!(client.get_room(room_id).unwrap().state(), RoomState::Invited);
     }
 
+    #[async_test]
+    async fn test_direct_invite_is_cached() {
+        let user_id = user_id!("@alice:example.org");
+        let room_id = room_id!("!test:example.org");
+
+        let client = logged_in_base_client(Some(user_id)).await;
+
+        let response = SyncResponseBuilder::new()
+            .add_invited_room(InvitedRoomBuilder::new(room_id).add_state_event(
+                StrippedStateTestEvent::Custom(json!({
+                    "content": {
+                        "displayname": "Alice",
+                        "is_direct": true,
+                        "membership": "invite",
+                    },
+                    "sender": "@example:example.org",
+                    "state_key": user_id,
+                    "type": "m.room.member",
+                })),
+            ))
+            .build_sync_response();
+        client.receive_sync_response(response).await.unwrap();
+
+        let room = client.get_room(room_id).unwrap();
+        assert!(room.is_direct_cached());
+        assert!(room.is_direct().await.unwrap());
+    }
+
+    #[async_test]
+    async fn test_knock_then_join() {
+        let user_id = user_id!("@alice:example.org");
+        let room_id = room_id!("!test:example.org");
+
+        let client = logged_in_base_client(Some(user_id)).await;
+
+        let response = api::sync::sync_events::v3::Response::try_from_http_response(
+            response_from_file(&json!({
+                "next_batch": "asdkl;fjasdkl;fj;asdkl;f",
+                "rooms": {
+                    "knock": {
+                        "!test:example.org": {
+                            "knock_state": {
+                                "events": [
+                                    {
+                                        "content": {
+                                            "name": "My Room Name"
+                                        },
+                                        "sender": "@bob:example.org",
+                                        "state_key": "",
+                                        "type": "m.room.name"
+                                    },
+                                    {
+                                        "content": {
+                                            "membership": "knock"
+                                        },
+                                        "sender": user_id,
+                                        "state_key": user_id,
+                                        "type": "m.room.member"
+                                    }
+                                ]
+                            }
+                        }
+                    }
+                }
+            })),
+        )
+        .expect("static json doesn't fail to parse");
+
+        client.receive_sync_response(response).await.unwrap();
+
+        let room = client.get_room(room_id).unwrap();
+        assert_eq!(room.state(), RoomState::Knocked);
+        assert_eq!(room.name().as_deref(), Some("My Room Name"));
+
+        // The knock has been accepted, and the user joined the room.
+        let response = SyncResponseBuilder::new()
+            .add_joined_room(JoinedRoomBuilder::new(room_id).add_timeline_event(
+                sync_timeline_event!({
+                    "content": {
+                        "membership": "join",
+                    },
+                    "event_id": "$143273582443PhrSn:example.org",
+                    "origin_server_ts": 1432735824653u64,
+                    "sender": user_id,
+                    "state_key": user_id,
+                    "type": "m.room.member",
+                }),
+            ))
+            .build_sync_response();
+        client.receive_sync_response(response).await.unwrap();
+
+        assert_eq!(client.get_room(room_id).unwrap().state(), RoomState::Joined);
+    }
+
     #[async_test]
     async fn test_invite_displayname() {
         let user_id = user_id!("@alice:example.org");
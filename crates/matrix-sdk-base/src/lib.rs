@@ -52,8 +52,10 @@ pub use http;
 pub use matrix_sdk_crypto as crypto;
 pub use once_cell;
 pub use rooms::{
-    DisplayName, Room, RoomCreateWithCreatorEventContent, RoomHero, RoomInfo, RoomInfoUpdate,
-    RoomMember, RoomMemberships, RoomState, RoomStateFilter,
+    avatar_initials, user_color_index, DisplayName, DisplayNameGenerator, DisplayNameGeneratorFn,
+    Room, RoomCreateWithCreatorEventContent, RoomHero, RoomInfo, RoomInfoUpdate, RoomMember,
+    RoomMemberships, RoomNameContext, RoomState, RoomStateFilter, ScBoldUnreadEventContent,
+    ScSnoozeEventContent, ScUnreadCountSource, ScUnreadCountSourceEventContent,
 };
 pub use store::{
     ComposerDraft, StateChanges, StateStore, StateStoreDataKey, StateStoreDataValue, StoreError,
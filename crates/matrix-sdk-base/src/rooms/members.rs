@@ -233,6 +233,48 @@ impl RoomMember {
     pub fn is_ignored(&self) -> bool {
         self.is_ignored
     }
+
+    /// SC: The color of this member among `buckets` colors, see
+    /// [`user_color_index`].
+    pub fn color_index(&self, buckets: u8) -> u8 {
+        user_color_index(self.user_id(), buckets)
+    }
+
+    /// SC: The initials to show in the text avatar of this member, see
+    /// [`avatar_initials`].
+    pub fn fallback_avatar_initials(&self) -> String {
+        avatar_initials(self.name())
+    }
+}
+
+/// SC: The color of the user with the given ID among `buckets` colors.
+///
+/// The color only depends on the user ID, and is computed like other Matrix
+/// clients do, so the user has the same color everywhere. Returns `0` if
+/// `buckets` is `0`.
+pub fn user_color_index(user_id: &UserId, buckets: u8) -> u8 {
+    if buckets == 0 {
+        return 0;
+    }
+
+    // The Java `String.hashCode()`, over the UTF-16 code units of the ID.
+    let hash = user_id
+        .as_str()
+        .encode_utf16()
+        .fold(0i32, |hash, unit| hash.wrapping_mul(31).wrapping_add(i32::from(unit)));
+
+    (hash.unsigned_abs() % u32::from(buckets)) as u8
+}
+
+/// SC: The initials to show in the text avatar of a user or a room with the
+/// given name.
+///
+/// This is the first character of the name, uppercased, skipping the sigil of
+/// a user ID or a room alias. Returns an empty string if the name is empty.
+pub fn avatar_initials(name: &str) -> String {
+    let name = name.strip_prefix(['@', '#', '!', '+']).filter(|s| !s.is_empty()).unwrap_or(name);
+
+    name.chars().next().map(|c| c.to_uppercase().collect()).unwrap_or_default()
 }
 
 // Information about the room a member is in.
@@ -243,3 +285,27 @@ pub(crate) struct MemberRoomInfo<'a> {
     pub(crate) users_display_names: BTreeMap<&'a str, BTreeSet<OwnedUserId>>,
     pub(crate) ignored_users: Option<BTreeSet<OwnedUserId>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use ruma::user_id;
+
+    use super::{avatar_initials, user_color_index};
+
+    #[test]
+    fn test_user_color_index() {
+        assert_eq!(user_color_index(user_id!("@alice:example.org"), 8), 6);
+        assert_eq!(user_color_index(user_id!("@bob:example.org"), 8), 3);
+        assert_eq!(user_color_index(user_id!("@alice:example.org"), 0), 0);
+    }
+
+    #[test]
+    fn test_avatar_initials() {
+        assert_eq!(avatar_initials("alice"), "A");
+        assert_eq!(avatar_initials("@bob:example.org"), "B");
+        assert_eq!(avatar_initials("#news:example.org"), "N");
+        assert_eq!(avatar_initials("ßeta"), "SS");
+        assert_eq!(avatar_initials("@"), "@");
+        assert_eq!(avatar_initials(""), "");
+    }
+}
@@ -7,10 +7,11 @@ use std::{
     collections::{BTreeMap, HashSet, HashMap},
     fmt,
     hash::Hash,
+    sync::Arc,
 };
 
 use bitflags::bitflags;
-pub use members::RoomMember;
+pub use members::{avatar_initials, user_color_index, RoomMember};
 pub use normal::{Room, RoomHero, RoomInfo, RoomInfoUpdate, RoomState, RoomStateFilter};
 use ruma::{
     assign,
@@ -27,16 +28,17 @@ use ruma::{
             join_rules::RoomJoinRulesEventContent,
             member::MembershipState,
             name::RoomNameEventContent,
+            pinned_events::RoomPinnedEventsEventContent,
             tombstone::RoomTombstoneEventContent,
             topic::RoomTopicEventContent,
         },
         space::child::SpaceChildEventContent,
         tag::{TagName, Tags},
         AnyStrippedStateEvent, AnySyncStateEvent, EmptyStateKey, RedactContent,
-        RedactedStateEventContent, StaticStateEventContent, SyncStateEvent,
+        RedactedStateEventContent, StateEventContent, StaticStateEventContent, SyncStateEvent,
     },
     room::RoomType,
-    EventId, OwnedUserId, OwnedRoomId, RoomVersionId,
+    EventId, MilliSecondsSinceUnixEpoch, OwnedRoomId, OwnedUserId, RoomId, RoomVersionId, UserId,
 };
 use serde::{Deserialize, Serialize};
 
@@ -72,6 +74,63 @@ impl fmt::Display for DisplayName {
     }
 }
 
+/// SC: The information handed to a [`DisplayNameGenerator`] when computing
+/// the name of a room that has neither an explicit name nor a canonical
+/// alias.
+#[derive(Debug)]
+pub struct RoomNameContext<'a> {
+    /// The room whose name is being computed.
+    pub room_id: &'a RoomId,
+    /// Our own user id.
+    pub own_user_id: &'a UserId,
+    /// The state of the room.
+    pub room_state: RoomState,
+    /// The display names of the heroes of the room, excluding ourselves.
+    pub heroes: &'a [String],
+    /// The (possibly estimated) number of joined members.
+    pub joined_member_count: u64,
+    /// The number of invited members.
+    pub invited_member_count: u64,
+}
+
+/// SC: The signature of a custom room name computation, see
+/// [`DisplayNameGenerator`].
+pub type DisplayNameGeneratorFn = dyn Fn(&RoomNameContext<'_>) -> Option<String> + Send + Sync;
+
+/// SC: A custom room name computation.
+///
+/// It is consulted by [`Room::compute_display_name`] before falling back to
+/// the heroes-based name from the spec, which is used whenever the generator
+/// returns `None`.
+#[derive(Clone)]
+pub struct DisplayNameGenerator(Arc<DisplayNameGeneratorFn>);
+
+impl DisplayNameGenerator {
+    /// Wrap the given closure.
+    pub fn new(
+        generator: impl Fn(&RoomNameContext<'_>) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(generator))
+    }
+
+    pub(crate) fn generate(&self, context: &RoomNameContext<'_>) -> Option<String> {
+        (self.0)(context)
+    }
+}
+
+impl From<Box<DisplayNameGeneratorFn>> for DisplayNameGenerator {
+    fn from(generator: Box<DisplayNameGeneratorFn>) -> Self {
+        Self(generator.into())
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+impl fmt::Debug for DisplayNameGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DisplayNameGenerator").finish_non_exhaustive()
+    }
+}
+
 /// A base room info struct that is the backbone of normal as well as stripped
 /// rooms. Holds all the state events that are important to present a room to
 /// users.
@@ -98,6 +157,9 @@ pub struct BaseRoomInfo {
     pub(crate) max_power_level: i64,
     /// The `m.room.name` of this room.
     pub(crate) name: Option<MinimalStateEvent<RoomNameEventContent>>,
+    /// SC: The `m.room.pinned_events` of this room.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) pinned_events: Option<MinimalStateEvent<RoomPinnedEventsEventContent>>,
     /// The `m.room.tombstone` event content of this room.
     pub(crate) tombstone: Option<MinimalStateEvent<RoomTombstoneEventContent>>,
     /// The topic of this room.
@@ -118,6 +180,18 @@ pub struct BaseRoomInfo {
     /// others, and this field collects them.
     #[serde(skip_serializing_if = "RoomNotableTags::is_empty", default)]
     pub(crate) notable_tags: RoomNotableTags,
+    /// SC: Until when the notifications of this room are snoozed, see
+    /// [`ScSnoozeEventContent`].
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) snoozed_until: Option<MilliSecondsSinceUnixEpoch>,
+    /// SC: The source of the unread counts chosen for this room, see
+    /// [`ScUnreadCountSourceEventContent`].
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) unread_count_source: Option<ScUnreadCountSource>,
+    /// SC: Whether the notifications of this room only make it unread, without
+    /// counting in the badges, see [`ScBoldUnreadEventContent`].
+    #[serde(default)]
+    pub(crate) bold_unread_only: bool,
 }
 
 impl BaseRoomInfo {
@@ -126,6 +200,70 @@ impl BaseRoomInfo {
         Self::default()
     }
 
+    /// SC: Whether this `BaseRoomInfo` holds the same data as `other`, see
+    /// [`RoomInfo::is_same_as`].
+    pub(crate) fn is_same_as(&self, other: &BaseRoomInfo) -> bool {
+        let Self {
+            avatar,
+            canonical_alias,
+            create,
+            dm_targets,
+            encryption,
+            guest_access,
+            history_visibility,
+            join_rules,
+            max_power_level,
+            name,
+            pinned_events,
+            tombstone,
+            topic,
+            space_children,
+            rtc_member,
+            is_marked_unread,
+            notable_tags,
+            snoozed_until,
+            unread_count_source,
+            bold_unread_only,
+        } = self;
+
+        let is_same_encryption = match (encryption, &other.encryption) {
+            (None, None) => true,
+            (Some(this), Some(other)) => {
+                this.algorithm == other.algorithm
+                    && this.rotation_period_ms == other.rotation_period_ms
+                    && this.rotation_period_msgs == other.rotation_period_msgs
+            }
+            _ => false,
+        };
+
+        is_same_state_event(avatar.as_ref(), other.avatar.as_ref())
+            && is_same_state_event(canonical_alias.as_ref(), other.canonical_alias.as_ref())
+            && is_same_state_event(create.as_ref(), other.create.as_ref())
+            && *dm_targets == other.dm_targets
+            && is_same_encryption
+            && is_same_state_event(guest_access.as_ref(), other.guest_access.as_ref())
+            && is_same_state_event(history_visibility.as_ref(), other.history_visibility.as_ref())
+            && is_same_state_event(join_rules.as_ref(), other.join_rules.as_ref())
+            && *max_power_level == other.max_power_level
+            && is_same_state_event(name.as_ref(), other.name.as_ref())
+            && is_same_state_event(pinned_events.as_ref(), other.pinned_events.as_ref())
+            && is_same_state_event(tombstone.as_ref(), other.tombstone.as_ref())
+            && is_same_state_event(topic.as_ref(), other.topic.as_ref())
+            && space_children.len() == other.space_children.len()
+            && space_children
+                .iter()
+                .all(|(id, ev)| is_same_state_event(Some(ev), other.space_children.get(id)))
+            && rtc_member.len() == other.rtc_member.len()
+            && rtc_member
+                .iter()
+                .all(|(id, ev)| is_same_state_event(Some(ev), other.rtc_member.get(id)))
+            && *is_marked_unread == other.is_marked_unread
+            && notable_tags.bits() == other.notable_tags.bits()
+            && *snoozed_until == other.snoozed_until
+            && *unread_count_source == other.unread_count_source
+            && *bold_unread_only == other.bold_unread_only
+    }
+
     /// Get the room version of this room.
     ///
     /// For room versions earlier than room version 11, if the event is
@@ -176,6 +314,9 @@ impl BaseRoomInfo {
             AnySyncStateEvent::RoomPowerLevels(p) => {
                 self.max_power_level = p.power_levels().max().into();
             }
+            AnySyncStateEvent::RoomPinnedEvents(p) => {
+                self.pinned_events = Some(p.into());
+            }
             AnySyncStateEvent::SpaceChild(s) => {
                 self.space_children.remove(s.state_key());
                 self.space_children.insert(
@@ -292,6 +433,8 @@ impl BaseRoomInfo {
             self.tombstone.as_mut().unwrap().redact(&room_version);
         } else if self.topic.has_event_id(redacts) {
             self.topic.as_mut().unwrap().redact(&room_version);
+        } else if self.pinned_events.has_event_id(redacts) {
+            self.pinned_events.as_mut().unwrap().redact(&room_version);
         } else {
             self.space_children.retain(|_, s| s.event_id() != Some(redacts));
             self.rtc_member.retain(|_, member_event| member_event.event_id() != Some(redacts));
@@ -329,6 +472,29 @@ bitflags! {
     }
 }
 
+/// SC: Whether the state events `a` and `b` are the same, according to their
+/// event ID.
+///
+/// Events without an ID can't be told apart, so they are considered different.
+fn is_same_state_event<C>(
+    a: Option<&MinimalStateEvent<C>>,
+    b: Option<&MinimalStateEvent<C>>,
+) -> bool
+where
+    C: StateEventContent + RedactContent,
+    C::Redacted: RedactedStateEventContent,
+{
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => {
+            a.event_id().is_some()
+                && a.event_id() == b.event_id()
+                && a.as_original().is_some() == b.as_original().is_some()
+        }
+        _ => false,
+    }
+}
+
 trait OptionExt {
     fn has_event_id(&self, ev_id: &EventId) -> bool;
 }
@@ -356,16 +522,93 @@ impl Default for BaseRoomInfo {
             join_rules: None,
             max_power_level: 100,
             name: None,
+            pinned_events: None,
             tombstone: None,
             topic: None,
             space_children: Default::default(),
             rtc_member: BTreeMap::new(),
             is_marked_unread: false,
             notable_tags: RoomNotableTags::empty(),
+            snoozed_until: None,
+            unread_count_source: None,
+            bold_unread_only: false,
         }
     }
 }
 
+/// SC: The content of a room account data event snoozing the notifications of
+/// a room.
+///
+/// The notifications of the room are considered muted until `until`, after
+/// which the snooze has no effect anymore.
+#[derive(Clone, Debug, Deserialize, Serialize, EventContent)]
+#[ruma_event(type = "chat.schildi.snooze", kind = RoomAccountData)]
+pub struct ScSnoozeEventContent {
+    /// Until when the notifications of the room are snoozed.
+    pub until: MilliSecondsSinceUnixEpoch,
+}
+
+impl ScSnoozeEventContent {
+    /// Create a new `ScSnoozeEventContent` snoozing the room until `until`.
+    pub fn new(until: MilliSecondsSinceUnixEpoch) -> Self {
+        Self { until }
+    }
+}
+
+/// SC: Where the unread counts of a room come from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[serde(rename_all = "snake_case")]
+pub enum ScUnreadCountSource {
+    /// The counts computed by the client from the read receipts, see
+    /// [`crate::read_receipts::RoomReadReceipts`].
+    Client,
+
+    /// The counts sent by the server, see
+    /// [`crate::sync::UnreadNotificationsCount`].
+    Server,
+}
+
+/// SC: The content of a room account data event overriding where the unread
+/// counts of a room come from.
+///
+/// A room without this event, or without a `source`, uses the default source
+/// chosen by the client.
+#[derive(Clone, Debug, Deserialize, Serialize, EventContent)]
+#[ruma_event(type = "chat.schildi.unread_count_source", kind = RoomAccountData)]
+pub struct ScUnreadCountSourceEventContent {
+    /// The source of the unread counts of the room.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<ScUnreadCountSource>,
+}
+
+impl ScUnreadCountSourceEventContent {
+    /// Create a new `ScUnreadCountSourceEventContent` with the given source.
+    pub fn new(source: Option<ScUnreadCountSource>) -> Self {
+        Self { source }
+    }
+}
+
+/// SC: The content of a room account data event making the notifications of a
+/// room only show it as unread, e.g. in bold, without counting in the badges.
+///
+/// This is meant for rooms the user follows without wanting to be bothered by
+/// a number, unlike muted rooms which aren't unread at all.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, EventContent)]
+#[ruma_event(type = "chat.schildi.bold_unread", kind = RoomAccountData)]
+pub struct ScBoldUnreadEventContent {
+    /// Whether the notifications of the room only make it unread.
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+impl ScBoldUnreadEventContent {
+    /// Create a new `ScBoldUnreadEventContent`.
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+}
+
 /// The content of an `m.room.create` event, with a required `creator` field.
 ///
 /// Starting with room version 11, the `creator` field should be removed and the
@@ -15,7 +15,7 @@
 #[cfg(all(feature = "e2e-encryption", feature = "experimental-sliding-sync"))]
 use std::sync::RwLock as SyncRwLock;
 use std::{
-    collections::{BTreeMap, HashSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashSet, HashMap},
     mem,
     sync::{atomic::AtomicBool, Arc},
 };
@@ -49,16 +49,17 @@ use ruma::{
     },
     room::RoomType,
     serde::Raw,
-    EventId, MxcUri, OwnedEventId, OwnedMxcUri, OwnedRoomAliasId, OwnedRoomId, OwnedUserId,
-    RoomAliasId, RoomId, RoomVersionId, UserId,
+    EventId, MilliSecondsSinceUnixEpoch, MxcUri, OwnedEventId, OwnedMxcUri, OwnedRoomAliasId,
+    OwnedRoomId, OwnedUserId, RoomAliasId, RoomId, RoomVersionId, UserId,
 };
 use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
-use tracing::{debug, field::debug, info, instrument, warn};
+use tracing::{debug, field::debug, info, instrument, trace, warn};
 
 use super::{
-    members::MemberRoomInfo, BaseRoomInfo, DisplayName, RoomCreateWithCreatorEventContent,
-    RoomMember, RoomNotableTags,
+    members::MemberRoomInfo, BaseRoomInfo, DisplayName, DisplayNameGenerator,
+    RoomCreateWithCreatorEventContent, RoomMember, RoomNameContext, RoomNotableTags,
+    ScUnreadCountSource,
 };
 #[cfg(feature = "experimental-sliding-sync")]
 use crate::latest_event::LatestEvent;
@@ -105,11 +106,15 @@ pub struct Room {
     /// to disk but held in memory.
     #[cfg(all(feature = "e2e-encryption", feature = "experimental-sliding-sync"))]
     pub latest_encrypted_events: Arc<SyncRwLock<RingBuffer<Raw<AnySyncTimelineEvent>>>>,
+
+    /// SC: A custom room name computation, consulted before the heroes-based
+    /// fallback in [`Room::compute_display_name`].
+    display_name_generator: Option<DisplayNameGenerator>,
 }
 
 /// The room summary containing member counts and members that should be used to
 /// calculate the room display name.
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct RoomSummary {
     /// The heroes of the room, members that can be used as a fallback for the
     /// room's display name or avatar if these haven't been set.
@@ -154,17 +159,20 @@ pub enum RoomState {
     Left,
     /// The room is in a invited state.
     Invited,
+    /// The room is in a knocked state, i.e. the user asked to join it and is
+    /// waiting for the request to be accepted.
+    Knocked,
 }
 
 impl From<&MembershipState> for RoomState {
     fn from(membership_state: &MembershipState) -> Self {
-        // We consider Ban, Knock and Leave to be Left, because they all mean we are not
+        // We consider Ban and Leave to be Left, because they all mean we are not
         // in the room.
         match membership_state {
             MembershipState::Ban => Self::Left,
             MembershipState::Invite => Self::Invited,
             MembershipState::Join => Self::Joined,
-            MembershipState::Knock => Self::Left,
+            MembershipState::Knock => Self::Knocked,
             MembershipState::Leave => Self::Left,
             _ => panic!("Unexpected MembershipState: {}", membership_state),
         }
@@ -213,9 +221,19 @@ impl Room {
                 Self::MAX_ENCRYPTED_EVENTS,
             ))),
             roominfo_update_sender,
+            display_name_generator: None,
         }
     }
 
+    /// SC: Use the given custom room name computation for this room.
+    pub(crate) fn with_display_name_generator(
+        mut self,
+        display_name_generator: Option<DisplayNameGenerator>,
+    ) -> Self {
+        self.display_name_generator = display_name_generator;
+        self
+    }
+
     /// Get the unique room id of the room.
     pub fn room_id(&self) -> &RoomId {
         &self.room_id
@@ -369,7 +387,7 @@ impl Room {
     #[instrument(skip_all, fields(room_id = ?self.room_id))]
     pub async fn is_direct(&self) -> StoreResult<bool> {
         match self.state() {
-            RoomState::Joined | RoomState::Left => {
+            RoomState::Joined | RoomState::Left | RoomState::Knocked => {
                 Ok(!self.inner.read().base_info.dm_targets.is_empty())
             }
 
@@ -395,6 +413,23 @@ impl Room {
         }
     }
 
+    /// SC: Is this room considered a direct message, without reading the
+    /// store.
+    ///
+    /// This is the same as [`Self::is_direct`], except that for invites it
+    /// relies on the `is_direct` flag of the invite cached during sync, so it
+    /// can be used in room list filters and sorters.
+    pub fn is_direct_cached(&self) -> bool {
+        let inner = self.inner.read();
+
+        match inner.room_state {
+            RoomState::Invited => inner.is_direct_invite,
+            RoomState::Joined | RoomState::Left | RoomState::Knocked => {
+                !inner.base_info.dm_targets.is_empty()
+            }
+        }
+    }
+
     /// If this room is a direct message, get the members that we're sharing the
     /// room with.
     ///
@@ -475,6 +510,17 @@ impl Room {
         self.inner.read().topic().map(ToOwned::to_owned)
     }
 
+    /// SC: Get the IDs of the events pinned in this room, in the order of the
+    /// `m.room.pinned_events` state event.
+    pub fn pinned_event_ids(&self) -> Vec<OwnedEventId> {
+        self.inner.read().pinned_event_ids()
+    }
+
+    /// SC: Whether the event with the given ID is pinned in this room.
+    pub fn is_pinned_event(&self, event_id: &EventId) -> bool {
+        self.inner.read().is_pinned_event(event_id)
+    }
+
     /// Is there a non expired membership with application "m.call" and scope
     /// "m.room" in this room
     pub fn has_active_room_call(&self) -> bool {
@@ -493,6 +539,11 @@ impl Room {
         self.inner.read().active_room_call_participants()
     }
 
+    /// SC: When the ongoing room call started, if there is one.
+    pub fn active_room_call_start_ts(&self) -> Option<MilliSecondsSinceUnixEpoch> {
+        self.inner.read().active_room_call_start_ts()
+    }
+
     /// Return the cached display name of the room if it was provided via sync,
     /// or otherwise calculate it, taking into account its name, aliases and
     /// members.
@@ -602,6 +653,21 @@ impl Room {
             _ => (summary.joined_member_count, summary.invited_member_count),
         };
 
+        if let Some(generator) = &self.display_name_generator {
+            let context = RoomNameContext {
+                room_id: self.room_id(),
+                own_user_id: self.own_user_id(),
+                room_state: self.state(),
+                heroes: &heroes,
+                joined_member_count: num_joined,
+                invited_member_count: num_invited,
+            };
+
+            if let Some(name) = generator.generate(&context) {
+                return Ok(update_cache(DisplayName::Calculated(name)));
+            }
+        }
+
         debug!(
             room_id = ?self.room_id(),
             own_user = ?self.own_user_id,
@@ -769,8 +835,22 @@ impl Room {
     ///
     /// This also triggers an update for room info observers if
     /// `trigger_room_list_update` is true.
+    ///
+    /// SC: If the given `RoomInfo` is the same as the current one, e.g. during
+    /// a burst of state events that don't change anything, the subscribers of
+    /// [`Self::subscribe_info`] are not woken up, and no update is sent unless
+    /// `trigger_room_list_update` is true.
     pub fn set_room_info(&self, room_info: RoomInfo, trigger_room_list_update: bool) {
-        self.inner.set(room_info);
+        let is_unchanged = self.inner.read().is_same_as(&room_info);
+
+        if is_unchanged {
+            if !trigger_room_list_update {
+                trace!(room_id = ?self.room_id, "Ignoring unchanged room info");
+                return;
+            }
+        } else {
+            self.inner.set(room_info);
+        }
 
         // Ignore error if no receiver exists.
         let _ = self
@@ -899,6 +979,60 @@ impl Room {
     pub fn is_marked_unread(&self) -> bool {
         self.inner.read().base_info.is_marked_unread
     }
+
+    /// SC: Get until when the notifications of this room are snoozed.
+    ///
+    /// Returns `None` if the room isn't snoozed, or if the snooze has elapsed.
+    pub fn snoozed_until(&self) -> Option<MilliSecondsSinceUnixEpoch> {
+        self.inner
+            .read()
+            .base_info
+            .snoozed_until
+            .filter(|until| *until > MilliSecondsSinceUnixEpoch::now())
+    }
+
+    /// SC: Whether the user opened this room at least once, as recorded by
+    /// [`RoomInfo::mark_opened`].
+    pub fn was_opened(&self) -> bool {
+        self.inner.read().was_opened
+    }
+
+    /// SC: The users muted locally in this room, as recorded by
+    /// [`RoomInfo::set_user_muted_locally`].
+    pub fn locally_muted_users(&self) -> BTreeSet<OwnedUserId> {
+        self.inner.read().locally_muted_users.clone()
+    }
+
+    /// SC: Check whether the notifications of this room are currently snoozed.
+    pub fn is_snoozed(&self) -> bool {
+        self.snoozed_until().is_some()
+    }
+
+    /// SC: Get the source of the unread counts chosen for this room, if it
+    /// overrides the default source of the client.
+    pub fn unread_count_source(&self) -> Option<ScUnreadCountSource> {
+        self.inner.read().base_info.unread_count_source
+    }
+
+    /// SC: Check whether the notifications of this room only make it unread,
+    /// e.g. shown in bold, without counting in the badges.
+    pub fn is_bold_unread_only(&self) -> bool {
+        self.inner.read().base_info.bold_unread_only
+    }
+
+    /// SC: Get the unread notification counts from the source chosen for this
+    /// room, or from `default_source` if the room doesn't override it.
+    pub fn unread_notification_counts_from(
+        &self,
+        default_source: ScUnreadCountSource,
+    ) -> UnreadNotificationsCount {
+        let inner = self.inner.read();
+
+        match inner.base_info.unread_count_source.unwrap_or(default_source) {
+            ScUnreadCountSource::Client => (&inner.read_receipts).into(),
+            ScUnreadCountSource::Server => inner.notification_counts,
+        }
+    }
 }
 
 /// The underlying pure data structure for joined and left rooms.
@@ -960,6 +1094,20 @@ pub struct RoomInfo {
     /// filled at start when creating a room, or on every successful sync.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) cached_display_name: Option<DisplayName>,
+
+    /// SC: Whether the user opened this room at least once.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) was_opened: bool,
+
+    /// SC: Whether the invite to this room is for a direct message, according
+    /// to the `m.room.member` event of the own user.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) is_direct_invite: bool,
+
+    /// SC: The users whose messages are muted in this room on this device
+    /// only.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub(crate) locally_muted_users: BTreeSet<OwnedUserId>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -980,6 +1128,66 @@ pub(crate) enum SyncInfo {
 }
 
 impl RoomInfo {
+    /// SC: Whether this `RoomInfo` holds the same data as `other`.
+    ///
+    /// `RoomInfo` can't implement `PartialEq` because it contains raw events,
+    /// so events are compared by their ID instead. When in doubt, e.g. for
+    /// events without an ID, the data is considered different.
+    pub(crate) fn is_same_as(&self, other: &RoomInfo) -> bool {
+        let Self {
+            room_id,
+            room_state,
+            notification_counts,
+            unread_count,
+            summary,
+            members_synced,
+            last_prev_batch,
+            sync_info,
+            encryption_state_synced,
+            #[cfg(feature = "experimental-sliding-sync")]
+            latest_event,
+            read_receipts,
+            base_info,
+            warned_about_unknown_room_version: _,
+            cached_display_name,
+            was_opened,
+            is_direct_invite,
+            locally_muted_users,
+        } = self;
+
+        #[cfg(feature = "experimental-sliding-sync")]
+        let is_same_latest_event = match (latest_event, &other.latest_event) {
+            (None, None) => true,
+            (Some(this), Some(other)) => {
+                this.event_id().is_some()
+                    && this.event_id() == other.event_id()
+                    && this.sender_display_name() == other.sender_display_name()
+                    && this.sender_avatar_url() == other.sender_avatar_url()
+                    && this.sender_name_ambiguous() == other.sender_name_ambiguous()
+            }
+            _ => false,
+        };
+        #[cfg(not(feature = "experimental-sliding-sync"))]
+        let is_same_latest_event = true;
+
+        *room_id == other.room_id
+            && *room_state == other.room_state
+            && *notification_counts == other.notification_counts
+            && *unread_count == other.unread_count
+            && *summary == other.summary
+            && *members_synced == other.members_synced
+            && *last_prev_batch == other.last_prev_batch
+            && *sync_info == other.sync_info
+            && *encryption_state_synced == other.encryption_state_synced
+            && is_same_latest_event
+            && *read_receipts == other.read_receipts
+            && base_info.is_same_as(&other.base_info)
+            && *cached_display_name == other.cached_display_name
+            && *was_opened == other.was_opened
+            && *is_direct_invite == other.is_direct_invite
+            && *locally_muted_users == other.locally_muted_users
+    }
+
     #[doc(hidden)] // used by store tests, otherwise it would be pub(crate)
     pub fn new(room_id: &RoomId, room_state: RoomState) -> Self {
         Self {
@@ -998,6 +1206,9 @@ impl RoomInfo {
             base_info: Box::new(BaseRoomInfo::new()),
             warned_about_unknown_room_version: Arc::new(false.into()),
             cached_display_name: None,
+            was_opened: false,
+            is_direct_invite: false,
+            locally_muted_users: Default::default(),
         }
     }
 
@@ -1016,6 +1227,11 @@ impl RoomInfo {
         self.room_state = RoomState::Invited;
     }
 
+    /// Mark this Room as knocked.
+    pub fn mark_as_knocked(&mut self) {
+        self.room_state = RoomState::Knocked;
+    }
+
     /// Set the membership RoomState of this Room
     pub fn set_state(&mut self, room_state: RoomState) {
         self.room_state = room_state;
@@ -1046,6 +1262,32 @@ impl RoomInfo {
         self.sync_info = SyncInfo::NoState;
     }
 
+    /// SC: Set whether the invite to this room is for a direct message.
+    pub(crate) fn set_direct_invite(&mut self, is_direct: bool) {
+        self.is_direct_invite = is_direct;
+    }
+
+    /// SC: Mark this Room as opened by the user at least once.
+    pub fn mark_opened(&mut self) {
+        self.was_opened = true;
+    }
+
+    /// SC: Mute or unmute the messages of `user_id` in this Room.
+    ///
+    /// Returns whether the muted users changed.
+    pub fn set_user_muted_locally(&mut self, user_id: &UserId, muted: bool) -> bool {
+        if muted {
+            self.locally_muted_users.insert(user_id.to_owned())
+        } else {
+            self.locally_muted_users.remove(user_id)
+        }
+    }
+
+    /// SC: The users muted locally in this Room.
+    pub fn locally_muted_users(&self) -> &BTreeSet<OwnedUserId> {
+        &self.locally_muted_users
+    }
+
     /// Mark this Room as having the encryption state synced.
     pub fn mark_encryption_state_synced(&mut self) {
         self.encryption_state_synced = true;
@@ -1078,6 +1320,12 @@ impl RoomInfo {
         self.base_info.encryption.is_some()
     }
 
+    /// SC: Returns whether the encryption state of this room has been
+    /// requested from the server, see [`Room::is_encryption_state_synced`].
+    pub fn is_encryption_state_synced(&self) -> bool {
+        self.encryption_state_synced
+    }
+
     /// Set the encryption event content in this room.
     pub fn set_encryption_event(&mut self, event: Option<RoomEncryptionEventContent>) {
         self.base_info.encryption = event;
@@ -1338,6 +1586,25 @@ impl RoomInfo {
         Some(&self.base_info.topic.as_ref()?.as_original()?.content.topic)
     }
 
+    /// SC: Returns the IDs of the events pinned in this room.
+    pub fn pinned_event_ids(&self) -> Vec<OwnedEventId> {
+        self.base_info
+            .pinned_events
+            .as_ref()
+            .and_then(|ev| ev.as_original())
+            .map(|ev| ev.content.pinned.clone())
+            .unwrap_or_default()
+    }
+
+    /// SC: Whether the event with the given ID is pinned in this room.
+    pub fn is_pinned_event(&self, event_id: &EventId) -> bool {
+        self.base_info
+            .pinned_events
+            .as_ref()
+            .and_then(|ev| ev.as_original())
+            .is_some_and(|ev| ev.content.pinned.iter().any(|id| id == event_id))
+    }
+
     /// Get a list of all the valid (non expired) matrixRTC memberships and
     /// associated UserId's in this room.
     ///
@@ -1391,6 +1658,12 @@ impl RoomInfo {
         self.active_room_call_memberships().iter().map(|(user_id, _)| user_id.clone()).collect()
     }
 
+    /// SC: When the oldest active membership of the room call was created,
+    /// i.e. when the ongoing call started, if there is one.
+    pub fn active_room_call_start_ts(&self) -> Option<MilliSecondsSinceUnixEpoch> {
+        self.active_room_call_memberships().first()?.1.created_ts()
+    }
+
     /// Returns the latest (decrypted) event recorded for this room.
     #[cfg(feature = "experimental-sliding-sync")]
     pub fn latest_event(&self) -> Option<&LatestEvent> {
@@ -1446,6 +1719,8 @@ bitflags! {
         const INVITED  = 0b00000010;
         /// The room is in a left state.
         const LEFT     = 0b00000100;
+        /// The room is in a knocked state.
+        const KNOCKED  = 0b00001000;
     }
 }
 
@@ -1460,6 +1735,7 @@ impl RoomStateFilter {
             RoomState::Joined => Self::JOINED,
             RoomState::Left => Self::LEFT,
             RoomState::Invited => Self::INVITED,
+            RoomState::Knocked => Self::KNOCKED,
         };
 
         self.contains(bit_state)
@@ -1478,6 +1754,9 @@ impl RoomStateFilter {
         if self.contains(Self::INVITED) {
             states.push(RoomState::Invited);
         }
+        if self.contains(Self::KNOCKED) {
+            states.push(RoomState::Knocked);
+        }
 
         states
     }
@@ -1562,7 +1841,9 @@ mod tests {
 
     #[cfg(feature = "experimental-sliding-sync")]
     use super::SyncInfo;
-    use super::{compute_display_name_from_heroes, Room, RoomHero, RoomInfo, RoomState};
+    use super::{
+        compute_display_name_from_heroes, DisplayNameGenerator, Room, RoomHero, RoomInfo, RoomState,
+    };
     #[cfg(any(feature = "experimental-sliding-sync", feature = "e2e-encryption"))]
     use crate::latest_event::LatestEvent;
     use crate::{
@@ -1608,6 +1889,9 @@ mod tests {
             read_receipts: Default::default(),
             warned_about_unknown_room_version: Arc::new(false.into()),
             cached_display_name: None,
+            was_opened: false,
+            is_direct_invite: false,
+            locally_muted_users: Default::default(),
         };
 
         let info_json = json!({
@@ -1640,6 +1924,7 @@ mod tests {
             },
             "base_info": {
                 "avatar": null,
+                "bold_unread_only": false,
                 "canonical_alias": null,
                 "create": null,
                 "dm_targets": [],
@@ -1900,6 +2185,57 @@ mod tests {
         assert!(room.is_low_priority().not());
     }
 
+    #[test]
+    fn test_unchanged_room_info_updates_are_coalesced() {
+        let store = Arc::new(MemoryStore::new());
+        let (sender, mut receiver) = tokio::sync::broadcast::channel(10);
+        let room = Room::new(
+            user_id!("@me:example.org"),
+            store,
+            room_id!("!test:localhost"),
+            RoomState::Joined,
+            sender,
+        );
+        let mut room_info_subscriber = room.subscribe_info();
+
+        // Duplicate updates of the same room info don't wake anyone up.
+        for _ in 0..3 {
+            room.set_room_info(room.clone_info(), false);
+        }
+        assert_pending!(room_info_subscriber);
+        assert!(receiver.try_recv().is_err());
+
+        // A burst of updates resulting in the same new room info only emits once.
+        let mut room_info = room.clone_info();
+        room_info.last_prev_batch = Some("pb".to_owned());
+        for _ in 0..3 {
+            room.set_room_info(room_info.clone(), false);
+        }
+        assert_ready!(room_info_subscriber);
+        assert_pending!(room_info_subscriber);
+        assert!(receiver.try_recv().is_ok());
+        assert!(receiver.try_recv().is_err());
+
+        // State events are compared by their ID.
+        let mut room_info = room.clone_info();
+        room_info.base_info.name = Some(MinimalStateEvent::Original(OriginalMinimalStateEvent {
+            content: RoomNameEventContent::new("Test Room".to_owned()),
+            event_id: Some(event_id!("$name").to_owned()),
+        }));
+        for _ in 0..3 {
+            room.set_room_info(room_info.clone(), false);
+        }
+        assert_ready!(room_info_subscriber);
+        assert_pending!(room_info_subscriber);
+        assert!(receiver.try_recv().is_ok());
+        assert!(receiver.try_recv().is_err());
+
+        // The room list can still be notified explicitly.
+        room.set_room_info(room.clone_info(), true);
+        assert_pending!(room_info_subscriber);
+        assert!(receiver.try_recv().unwrap().trigger_room_list_update);
+    }
+
     fn make_room_test_helper(room_type: RoomState) -> (Arc<MemoryStore>, Room) {
         let store = Arc::new(MemoryStore::new());
         let user_id = user_id!("@me:example.org");
@@ -2112,6 +2448,46 @@ mod tests {
         );
     }
 
+    #[async_test]
+    async fn test_display_name_generator() {
+        let (store, room) = make_room_test_helper(RoomState::Joined);
+        let room = room.with_display_name_generator(Some(DisplayNameGenerator::new(|context| {
+            (context.heroes.len() == 1).then(|| format!("Chat with {}", context.heroes[0]))
+        })));
+        let room_id = room_id!("!test:localhost");
+        let matthew = user_id!("@matthew:example.org");
+        let me = user_id!("@me:example.org");
+        let mut changes = StateChanges::new("".to_owned());
+        let summary = assign!(RumaSummary::new(), {
+            joined_member_count: Some(2u32.into()),
+            heroes: vec![me.to_owned(), matthew.to_owned()],
+        });
+
+        let members = changes
+            .state
+            .entry(room_id.to_owned())
+            .or_default()
+            .entry(StateEventType::RoomMember)
+            .or_default();
+        members.insert(matthew.into(), make_member_event(matthew, "Matthew").cast());
+        members.insert(me.into(), make_member_event(me, "Me").cast());
+
+        store.save_changes(&changes).await.unwrap();
+
+        room.inner.update_if(|info| info.update_from_ruma_summary(&summary));
+        assert_eq!(
+            room.compute_display_name().await.unwrap(),
+            DisplayName::Calculated("Chat with Matthew".to_owned())
+        );
+
+        // When the generator declines, the heroes-based name is used.
+        let room = room.with_display_name_generator(Some(DisplayNameGenerator::new(|_| None)));
+        assert_eq!(
+            room.compute_display_name().await.unwrap(),
+            DisplayName::Calculated("Matthew".to_owned())
+        );
+    }
+
     #[async_test]
     async fn test_display_name_dm_joined_no_heroes() {
         let (store, room) = make_room_test_helper(RoomState::Joined);
@@ -2501,6 +2877,11 @@ mod tests {
             room.active_room_call_participants()
         );
         assert!(room.has_active_room_call());
+
+        // The call started with the oldest membership, 20min ago.
+        let start_ts = room.active_room_call_start_ts().unwrap();
+        assert!(start_ts <= timestamp(20));
+        assert!(start_ts > timestamp(21));
     }
 
     #[test]
@@ -2517,6 +2898,30 @@ mod tests {
         assert!(!room.has_active_room_call());
     }
 
+    #[test]
+    fn test_pinned_event_ids() {
+        let (_, room) = make_room_test_helper(RoomState::Joined);
+        assert!(room.pinned_event_ids().is_empty());
+
+        let pinned_events: AnySyncStateEvent = serde_json::from_value(json!({
+            "content": { "pinned": ["$a", "$b"] },
+            "event_id": "$pinned",
+            "origin_server_ts": 0,
+            "sender": ALICE.as_str(),
+            "state_key": "",
+            "type": "m.room.pinned_events",
+        }))
+        .unwrap();
+        receive_state_events(&room, vec![&pinned_events]);
+
+        assert_eq!(
+            room.pinned_event_ids(),
+            [event_id!("$a").to_owned(), event_id!("$b").to_owned()]
+        );
+        assert!(room.is_pinned_event(event_id!("$b")));
+        assert!(!room.is_pinned_event(event_id!("$c")));
+    }
+
     #[test]
     fn test_calculate_room_name() {
         let mut actual = compute_display_name_from_heroes(2, 0, vec!["a"]);
@@ -237,7 +237,7 @@ impl BaseClient {
                         .or_insert_with(LeftRoomUpdate::default)
                         .account_data
                         .append(&mut raw.to_vec()),
-                    RoomState::Invited => {}
+                    RoomState::Invited | RoomState::Knocked => {}
                 }
             }
         }
@@ -454,6 +454,8 @@ impl BaseClient {
             )),
 
             RoomState::Invited => Ok((room_info, None, None, invited_room)),
+
+            RoomState::Knocked => Ok((room_info, None, None, None)),
         }
     }
 
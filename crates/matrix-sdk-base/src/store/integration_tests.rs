@@ -36,7 +36,7 @@ use super::DynStateStore;
 use crate::{
     deserialized_responses::MemberEvent,
     media::{MediaFormat, MediaRequest, MediaThumbnailSize},
-    store::{Result, StateStoreExt},
+    store::{FrequentReaction, Result, StateStoreExt},
     RoomInfo, RoomMemberships, RoomState, StateChanges, StateStoreDataKey, StateStoreDataValue,
 };
 
@@ -63,6 +63,10 @@ pub trait StateStoreIntegrationTests {
     async fn test_user_avatar_url_saving(&self);
     /// Test sync token saving.
     async fn test_sync_token_saving(&self);
+    /// SC: Test saving the recently opened rooms.
+    async fn test_recently_opened_rooms_saving(&self);
+    /// SC: Test saving the frequent reactions.
+    async fn test_frequent_reactions_saving(&self);
     /// Test UtdHookManagerData saving.
     async fn test_utd_hook_manager_data_saving(&self);
     /// Test stripped room member saving.
@@ -77,6 +81,8 @@ pub trait StateStoreIntegrationTests {
     async fn test_persist_invited_room(&self) -> Result<()>;
     /// Test stripped and non-stripped room member saving.
     async fn test_stripped_non_stripped(&self) -> Result<()>;
+    /// Test stripped room member saving for a knocked room.
+    async fn test_knocked_room_stripped_state(&self) -> Result<()>;
     /// Test room removal.
     async fn test_room_removal(&self) -> Result<()>;
     /// Test profile removal.
@@ -597,6 +603,54 @@ impl StateStoreIntegrationTests for DynStateStore {
         assert_matches!(self.get_kv_data(StateStoreDataKey::SyncToken).await, Ok(None));
     }
 
+    async fn test_recently_opened_rooms_saving(&self) {
+        let rooms =
+            vec![room_id!("!r1:example.org").to_owned(), room_id!("!r0:example.org").to_owned()];
+
+        assert_matches!(self.get_kv_data(StateStoreDataKey::RecentlyOpenedRooms).await, Ok(None));
+
+        self.set_kv_data(
+            StateStoreDataKey::RecentlyOpenedRooms,
+            StateStoreDataValue::RecentlyOpenedRooms(rooms.clone()),
+        )
+        .await
+        .unwrap();
+
+        assert_let!(
+            Ok(Some(StateStoreDataValue::RecentlyOpenedRooms(stored_rooms))) =
+                self.get_kv_data(StateStoreDataKey::RecentlyOpenedRooms).await
+        );
+        assert_eq!(stored_rooms, rooms);
+
+        self.remove_kv_data(StateStoreDataKey::RecentlyOpenedRooms).await.unwrap();
+        assert_matches!(self.get_kv_data(StateStoreDataKey::RecentlyOpenedRooms).await, Ok(None));
+    }
+
+    async fn test_frequent_reactions_saving(&self) {
+        let reactions = vec![
+            FrequentReaction { key: "👍".to_owned(), score: 2.5 },
+            FrequentReaction { key: "🎉".to_owned(), score: 1.0 },
+        ];
+
+        assert_matches!(self.get_kv_data(StateStoreDataKey::FrequentReactions).await, Ok(None));
+
+        self.set_kv_data(
+            StateStoreDataKey::FrequentReactions,
+            StateStoreDataValue::FrequentReactions(reactions.clone()),
+        )
+        .await
+        .unwrap();
+
+        assert_let!(
+            Ok(Some(StateStoreDataValue::FrequentReactions(stored_reactions))) =
+                self.get_kv_data(StateStoreDataKey::FrequentReactions).await
+        );
+        assert_eq!(stored_reactions, reactions);
+
+        self.remove_kv_data(StateStoreDataKey::FrequentReactions).await.unwrap();
+        assert_matches!(self.get_kv_data(StateStoreDataKey::FrequentReactions).await, Ok(None));
+    }
+
     async fn test_utd_hook_manager_data_saving(&self) {
         // Before any data is written, the getter should return None.
         assert!(
@@ -1049,6 +1103,42 @@ impl StateStoreIntegrationTests for DynStateStore {
         Ok(())
     }
 
+    async fn test_knocked_room_stripped_state(&self) -> Result<()> {
+        let room_id = room_id!("!test_knocked_room_stripped_state:localhost");
+        let user_id = user_id();
+
+        let mut changes = StateChanges::default();
+        changes
+            .state
+            .entry(room_id.to_owned())
+            .or_default()
+            .entry(StateEventType::RoomMember)
+            .or_default()
+            .insert(user_id.into(), membership_event().cast());
+        changes.add_room(RoomInfo::new(room_id, RoomState::Left));
+        self.save_changes(&changes).await.unwrap();
+
+        let mut changes = StateChanges::default();
+        changes.add_stripped_member(room_id, user_id, custom_stripped_membership_event(user_id));
+        changes.add_room(RoomInfo::new(room_id, RoomState::Knocked));
+        self.save_changes(&changes).await.unwrap();
+
+        // The stripped state of a knocked room is stored like the one of an
+        // invited room.
+        let member_event =
+            self.get_member_event(room_id, user_id).await.unwrap().unwrap().deserialize().unwrap();
+        assert!(matches!(member_event, MemberEvent::Stripped(_)));
+        #[allow(deprecated)]
+        let stripped_rooms = self.get_stripped_room_infos().await?;
+        assert_eq!(stripped_rooms.len(), 1);
+        assert_eq!(stripped_rooms[0].state(), RoomState::Knocked);
+
+        let members = self.get_user_ids(room_id, RoomMemberships::empty()).await.unwrap();
+        assert_eq!(members, vec![user_id.to_owned()]);
+
+        Ok(())
+    }
+
     async fn test_room_removal(&self) -> Result<()> {
         let room_id = room_id();
         let user_id = user_id();
@@ -1385,6 +1475,18 @@ macro_rules! statestore_integration_tests {
             store.test_sync_token_saving().await
         }
 
+        #[async_test]
+        async fn test_recently_opened_rooms_saving() {
+            let store = get_store().await.unwrap().into_state_store();
+            store.test_recently_opened_rooms_saving().await
+        }
+
+        #[async_test]
+        async fn test_frequent_reactions_saving() {
+            let store = get_store().await.unwrap().into_state_store();
+            store.test_frequent_reactions_saving().await
+        }
+
         #[async_test]
         async fn test_utd_hook_manager_data_saving() {
              let store = get_store().await.expect("creating store failed").into_state_store();
@@ -1427,6 +1529,12 @@ macro_rules! statestore_integration_tests {
             store.test_stripped_non_stripped().await
         }
 
+        #[async_test]
+        async fn test_knocked_room_stripped_state() -> StoreResult<()> {
+            let store = get_store().await.unwrap().into_state_store();
+            store.test_knocked_room_stripped_state().await
+        }
+
         #[async_test]
         async fn test_room_removal() -> StoreResult<()> {
             let store = get_store().await?.into_state_store();
@@ -36,7 +36,10 @@ use ruma::{
 };
 use tracing::{debug, warn};
 
-use super::{traits::ComposerDraft, Result, RoomInfo, StateChanges, StateStore, StoreError};
+use super::{
+    traits::{ComposerDraft, FrequentReaction},
+    Result, RoomInfo, StateChanges, StateStore, StoreError,
+};
 use crate::{
     deserialized_responses::RawAnySyncOrStrippedState,
     media::{MediaRequest, UniqueKey as _},
@@ -50,6 +53,8 @@ use crate::{
 #[derive(Debug)]
 pub struct MemoryStore {
     recently_visited_rooms: StdRwLock<HashMap<String, Vec<String>>>,
+    recently_opened_rooms: StdRwLock<Option<Vec<OwnedRoomId>>>,
+    frequent_reactions: StdRwLock<Option<Vec<FrequentReaction>>>,
     composer_drafts: StdRwLock<HashMap<OwnedRoomId, ComposerDraft>>,
     user_avatar_url: StdRwLock<HashMap<String, String>>,
     sync_token: StdRwLock<Option<String>>,
@@ -94,6 +99,8 @@ impl Default for MemoryStore {
     fn default() -> Self {
         Self {
             recently_visited_rooms: Default::default(),
+            recently_opened_rooms: Default::default(),
+            frequent_reactions: Default::default(),
             composer_drafts: Default::default(),
             user_avatar_url: Default::default(),
             sync_token: Default::default(),
@@ -191,6 +198,18 @@ impl StateStore for MemoryStore {
                 .get(user_id.as_str())
                 .cloned()
                 .map(StateStoreDataValue::RecentlyVisitedRooms),
+            StateStoreDataKey::RecentlyOpenedRooms => self
+                .recently_opened_rooms
+                .read()
+                .unwrap()
+                .clone()
+                .map(StateStoreDataValue::RecentlyOpenedRooms),
+            StateStoreDataKey::FrequentReactions => self
+                .frequent_reactions
+                .read()
+                .unwrap()
+                .clone()
+                .map(StateStoreDataValue::FrequentReactions),
             StateStoreDataKey::UtdHookManagerData => self
                 .utd_hook_manager_data
                 .read()
@@ -237,6 +256,20 @@ impl StateStore for MemoryStore {
                         .expect("Session data not a list of recently visited rooms"),
                 );
             }
+            StateStoreDataKey::RecentlyOpenedRooms => {
+                *self.recently_opened_rooms.write().unwrap() = Some(
+                    value
+                        .into_recently_opened_rooms()
+                        .expect("Session data not a list of recently opened rooms"),
+                );
+            }
+            StateStoreDataKey::FrequentReactions => {
+                *self.frequent_reactions.write().unwrap() = Some(
+                    value
+                        .into_frequent_reactions()
+                        .expect("Session data not a list of frequent reactions"),
+                );
+            }
             StateStoreDataKey::UtdHookManagerData => {
                 *self.utd_hook_manager_data.write().unwrap() = Some(
                     value
@@ -267,6 +300,12 @@ impl StateStore for MemoryStore {
             StateStoreDataKey::RecentlyVisitedRooms(user_id) => {
                 self.recently_visited_rooms.write().unwrap().remove(user_id.as_str());
             }
+            StateStoreDataKey::RecentlyOpenedRooms => {
+                *self.recently_opened_rooms.write().unwrap() = None
+            }
+            StateStoreDataKey::FrequentReactions => {
+                *self.frequent_reactions.write().unwrap() = None
+            }
             StateStoreDataKey::UtdHookManagerData => {
                 *self.utd_hook_manager_data.write().unwrap() = None
             }
@@ -704,7 +743,7 @@ impl StateStore for MemoryStore {
             .read()
             .unwrap()
             .values()
-            .filter(|r| matches!(r.state(), RoomState::Invited))
+            .filter(|r| matches!(r.state(), RoomState::Invited | RoomState::Knocked))
             .cloned()
             .collect())
     }
@@ -126,6 +126,9 @@ impl RoomInfoV1 {
             base_info: base_info.migrate(create),
             warned_about_unknown_room_version: Arc::new(false.into()),
             cached_display_name: None,
+            was_opened: false,
+            is_direct_invite: false,
+            locally_muted_users: Default::default(),
         }
     }
 }
@@ -207,12 +210,16 @@ impl BaseRoomInfoV1 {
             join_rules,
             max_power_level,
             name,
+            pinned_events: None,
             tombstone,
             topic,
             space_children: Default::default(),
             rtc_member: BTreeMap::new(),
             is_marked_unread: false,
             notable_tags: RoomNotableTags::empty(),
+            snoozed_until: None,
+            unread_count_source: None,
+            bold_unread_only: false,
         })
     }
 }
@@ -53,7 +53,7 @@ use ruma::{
 use tokio::sync::{broadcast, Mutex, RwLock};
 
 use crate::{
-    rooms::{normal::RoomInfoUpdate, RoomInfo, RoomState},
+    rooms::{normal::RoomInfoUpdate, DisplayNameGenerator, RoomInfo, RoomState},
     MinimalRoomMemberEvent, Room, RoomStateFilter, SessionMeta,
 };
 
@@ -66,8 +66,8 @@ pub use self::integration_tests::StateStoreIntegrationTests;
 pub use self::{
     memory_store::MemoryStore,
     traits::{
-        ComposerDraft, ComposerDraftType, DynStateStore, IntoStateStore, StateStore,
-        StateStoreDataKey, StateStoreDataValue, StateStoreExt,
+        ComposerDraft, ComposerDraftType, DynStateStore, FrequentReaction, IntoStateStore,
+        StateStore, StateStoreDataKey, StateStoreDataValue, StateStoreExt,
     },
 };
 
@@ -143,6 +143,8 @@ pub(crate) struct Store {
     /// A lock to synchronize access to the store, such that data by the sync is
     /// never overwritten.
     sync_lock: Arc<Mutex<()>>,
+    /// SC: The custom room name computation handed to every room.
+    pub(crate) display_name_generator: Option<DisplayNameGenerator>,
 }
 
 impl Store {
@@ -154,6 +156,7 @@ impl Store {
             sync_token: Default::default(),
             rooms: Default::default(),
             sync_lock: Default::default(),
+            display_name_generator: None,
         }
     }
 
@@ -179,7 +182,8 @@ impl Store {
                 self.inner.clone(),
                 info,
                 roominfo_update_sender.clone(),
-            );
+            )
+            .with_display_name_generator(self.display_name_generator.clone());
 
             self.rooms.write().unwrap().insert(room.room_id().to_owned(), room);
         }
@@ -236,6 +240,7 @@ impl Store {
             .entry(room_id.to_owned())
             .or_insert_with(|| {
                 Room::new(user_id, self.inner.clone(), room_id, room_type, roominfo_update_sender)
+                    .with_display_name_generator(self.display_name_generator.clone())
             })
             .clone()
     }
@@ -33,7 +33,7 @@ use ruma::{
         RoomAccountDataEventType, StateEventType, StaticEventContent, StaticStateEventContent,
     },
     serde::Raw,
-    EventId, MxcUri, OwnedEventId, OwnedUserId, RoomId, UserId,
+    EventId, MxcUri, OwnedEventId, OwnedRoomId, OwnedUserId, RoomId, UserId,
 };
 use serde::{Deserialize, Serialize};
 
@@ -811,6 +811,14 @@ pub enum StateStoreDataValue {
     /// A list of recently visited room identifiers for the current user
     RecentlyVisitedRooms(Vec<String>),
 
+    /// SC: A list of recently opened room identifiers for the current user,
+    /// the most recent first.
+    RecentlyOpenedRooms(Vec<OwnedRoomId>),
+
+    /// SC: The reactions the current user reacts with most, the most frequent
+    /// first.
+    FrequentReactions(Vec<FrequentReaction>),
+
     /// Persistent data for
     /// `matrix_sdk_ui::unable_to_decrypt_hook::UtdHookManager`.
     UtdHookManagerData(GrowableBloom),
@@ -835,6 +843,16 @@ pub struct ComposerDraft {
     pub draft_type: ComposerDraftType,
 }
 
+/// SC: A reaction the current user has reacted with, and how frequently.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FrequentReaction {
+    /// The key of the reaction, usually an emoji.
+    pub key: String,
+    /// How frequently the reaction has been used recently, older uses
+    /// weighing less than recent ones.
+    pub score: f64,
+}
+
 /// The type of draft of the composer.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
@@ -874,6 +892,16 @@ impl StateStoreDataValue {
         as_variant!(self, Self::RecentlyVisitedRooms)
     }
 
+    /// SC: Get this value if it is a list of recently opened rooms.
+    pub fn into_recently_opened_rooms(self) -> Option<Vec<OwnedRoomId>> {
+        as_variant!(self, Self::RecentlyOpenedRooms)
+    }
+
+    /// SC: Get this value if it is a list of frequent reactions.
+    pub fn into_frequent_reactions(self) -> Option<Vec<FrequentReaction>> {
+        as_variant!(self, Self::FrequentReactions)
+    }
+
     /// Get this value if it is the data for the `UtdHookManager`.
     pub fn into_utd_hook_manager_data(self) -> Option<GrowableBloom> {
         as_variant!(self, Self::UtdHookManagerData)
@@ -900,6 +928,12 @@ pub enum StateStoreDataKey<'a> {
     /// Recently visited room identifiers
     RecentlyVisitedRooms(&'a UserId),
 
+    /// SC: Recently opened room identifiers.
+    RecentlyOpenedRooms,
+
+    /// SC: The reactions the current user reacts with most.
+    FrequentReactions,
+
     /// Persistent data for
     /// `matrix_sdk_ui::unable_to_decrypt_hook::UtdHookManager`.
     UtdHookManagerData,
@@ -924,6 +958,14 @@ impl StateStoreDataKey<'_> {
     /// [`RecentlyVisitedRooms`][Self::RecentlyVisitedRooms] variant.
     pub const RECENTLY_VISITED_ROOMS: &'static str = "recently_visited_rooms";
 
+    /// Key to use for the [`RecentlyOpenedRooms`][Self::RecentlyOpenedRooms]
+    /// variant.
+    pub const RECENTLY_OPENED_ROOMS: &'static str = "recently_opened_rooms";
+
+    /// Key to use for the [`FrequentReactions`][Self::FrequentReactions]
+    /// variant.
+    pub const FREQUENT_REACTIONS: &'static str = "frequent_reactions";
+
     /// Key to use for the [`UtdHookManagerData`][Self::UtdHookManagerData]
     /// variant.
     pub const UTD_HOOK_MANAGER_DATA: &'static str = "utd_hook_manager_data";
@@ -35,6 +35,7 @@ use serde::{Deserialize, Serialize};
 use crate::{
     debug::{DebugInvitedRoom, DebugListOfRawEvents, DebugListOfRawEventsNoId},
     deserialized_responses::{AmbiguityChange, RawAnySyncOrStrippedTimelineEvent},
+    read_receipts::RoomReadReceipts,
     store::Store,
 };
 
@@ -181,6 +182,17 @@ impl From<RumaUnreadNotificationsCount> for UnreadNotificationsCount {
     }
 }
 
+/// SC: The counts computed by the client, see
+/// [`ScUnreadCountSource::Client`](crate::ScUnreadCountSource::Client).
+impl From<&RoomReadReceipts> for UnreadNotificationsCount {
+    fn from(read_receipts: &RoomReadReceipts) -> Self {
+        Self {
+            highlight_count: read_receipts.num_mentions,
+            notification_count: read_receipts.num_notifications,
+        }
+    }
+}
+
 /// Updates to left rooms.
 #[derive(Clone, Default)]
 pub struct LeftRoomUpdate {
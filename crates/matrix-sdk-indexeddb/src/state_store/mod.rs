@@ -25,7 +25,7 @@ use indexed_db_futures::prelude::*;
 use matrix_sdk_base::{
     deserialized_responses::RawAnySyncOrStrippedState,
     media::{MediaRequest, UniqueKey},
-    store::{ComposerDraft, StateChanges, StateStore, StoreError},
+    store::{ComposerDraft, FrequentReaction, StateChanges, StateStore, StoreError},
     MinimalRoomMemberEvent, RoomInfo, RoomMemberships, RoomState, StateStoreDataKey,
     StateStoreDataValue,
 };
@@ -42,7 +42,8 @@ use ruma::{
         GlobalAccountDataEventType, RoomAccountDataEventType, StateEventType, SyncStateEvent,
     },
     serde::Raw,
-    CanonicalJsonObject, EventId, MxcUri, OwnedEventId, OwnedUserId, RoomId, RoomVersionId, UserId,
+    CanonicalJsonObject, EventId, MxcUri, OwnedEventId, OwnedRoomId, OwnedUserId, RoomId,
+    RoomVersionId, UserId,
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tracing::{debug, warn};
@@ -389,6 +390,12 @@ impl IndexeddbStateStore {
             StateStoreDataKey::RecentlyVisitedRooms(user_id) => {
                 self.encode_key(keys::KV, (StateStoreDataKey::RECENTLY_VISITED_ROOMS, user_id))
             }
+            StateStoreDataKey::RecentlyOpenedRooms => {
+                self.encode_key(keys::KV, StateStoreDataKey::RECENTLY_OPENED_ROOMS)
+            }
+            StateStoreDataKey::FrequentReactions => {
+                self.encode_key(keys::KV, StateStoreDataKey::FREQUENT_REACTIONS)
+            }
             StateStoreDataKey::UtdHookManagerData => {
                 self.encode_key(keys::KV, StateStoreDataKey::UTD_HOOK_MANAGER_DATA)
             }
@@ -456,6 +463,14 @@ impl_state_store!({
                 .map(|f| self.deserialize_event::<Vec<String>>(&f))
                 .transpose()?
                 .map(StateStoreDataValue::RecentlyVisitedRooms),
+            StateStoreDataKey::RecentlyOpenedRooms => value
+                .map(|f| self.deserialize_event::<Vec<OwnedRoomId>>(&f))
+                .transpose()?
+                .map(StateStoreDataValue::RecentlyOpenedRooms),
+            StateStoreDataKey::FrequentReactions => value
+                .map(|f| self.deserialize_event::<Vec<FrequentReaction>>(&f))
+                .transpose()?
+                .map(StateStoreDataValue::FrequentReactions),
             StateStoreDataKey::UtdHookManagerData => value
                 .map(|f| self.deserialize_event::<GrowableBloom>(&f))
                 .transpose()?
@@ -490,6 +505,16 @@ impl_state_store!({
                     .into_recently_visited_rooms()
                     .expect("Session data not a recently visited room list"),
             ),
+            StateStoreDataKey::RecentlyOpenedRooms => self.serialize_event(
+                &value
+                    .into_recently_opened_rooms()
+                    .expect("Session data not a recently opened room list"),
+            ),
+            StateStoreDataKey::FrequentReactions => self.serialize_event(
+                &value
+                    .into_frequent_reactions()
+                    .expect("Session data not a list of frequent reactions"),
+            ),
             StateStoreDataKey::UtdHookManagerData => self.serialize_event(
                 &value.into_utd_hook_manager_data().expect("Session data not UtdHookManagerData"),
             ),
@@ -1055,7 +1080,7 @@ impl_state_store!({
                 let value = cursor.value();
                 let info = self.deserialize_event::<RoomInfo>(&value)?;
 
-                if info.state() == RoomState::Invited {
+                if matches!(info.state(), RoomState::Invited | RoomState::Knocked) {
                     infos.push(info);
                 }
 
@@ -278,6 +278,12 @@ impl SqliteStateStore {
             StateStoreDataKey::RecentlyVisitedRooms(b) => {
                 Cow::Owned(format!("{}:{b}", StateStoreDataKey::RECENTLY_VISITED_ROOMS))
             }
+            StateStoreDataKey::RecentlyOpenedRooms => {
+                Cow::Borrowed(StateStoreDataKey::RECENTLY_OPENED_ROOMS)
+            }
+            StateStoreDataKey::FrequentReactions => {
+                Cow::Borrowed(StateStoreDataKey::FREQUENT_REACTIONS)
+            }
             StateStoreDataKey::UtdHookManagerData => {
                 Cow::Borrowed(StateStoreDataKey::UTD_HOOK_MANAGER_DATA)
             }
@@ -902,6 +908,12 @@ impl StateStore for SqliteStateStore {
                     StateStoreDataKey::RecentlyVisitedRooms(_) => {
                         StateStoreDataValue::RecentlyVisitedRooms(self.deserialize_value(&data)?)
                     }
+                    StateStoreDataKey::RecentlyOpenedRooms => {
+                        StateStoreDataValue::RecentlyOpenedRooms(self.deserialize_value(&data)?)
+                    }
+                    StateStoreDataKey::FrequentReactions => {
+                        StateStoreDataValue::FrequentReactions(self.deserialize_value(&data)?)
+                    }
                     StateStoreDataKey::UtdHookManagerData => {
                         StateStoreDataValue::UtdHookManagerData(self.deserialize_value(&data)?)
                     }
@@ -931,6 +943,16 @@ impl StateStore for SqliteStateStore {
             StateStoreDataKey::RecentlyVisitedRooms(_) => self.serialize_value(
                 &value.into_recently_visited_rooms().expect("Session data not breadcrumbs"),
             )?,
+            StateStoreDataKey::RecentlyOpenedRooms => self.serialize_value(
+                &value
+                    .into_recently_opened_rooms()
+                    .expect("Session data not a list of recently opened rooms"),
+            )?,
+            StateStoreDataKey::FrequentReactions => self.serialize_value(
+                &value
+                    .into_frequent_reactions()
+                    .expect("Session data not a list of frequent reactions"),
+            )?,
             StateStoreDataKey::UtdHookManagerData => self.serialize_value(
                 &value.into_utd_hook_manager_data().expect("Session data not UtdHookManagerData"),
             )?,
@@ -1000,7 +1022,8 @@ impl StateStore for SqliteStateStore {
                 }
 
                 for (room_id, room_info) in room_infos {
-                    let stripped = room_info.state() == RoomState::Invited;
+                    let stripped =
+                        matches!(room_info.state(), RoomState::Invited | RoomState::Knocked);
                     // Remove non-stripped data for stripped rooms and vice-versa.
                     this.remove_maybe_stripped_room_data(txn, &room_id, !stripped)?;
 
@@ -1421,8 +1444,10 @@ impl StateStore for SqliteStateStore {
     }
 
     async fn get_stripped_room_infos(&self) -> Result<Vec<RoomInfo>> {
-        let states =
-            vec![self.encode_key(keys::ROOM_INFO, serde_json::to_string(&RoomState::Invited)?)];
+        let states = vec![
+            self.encode_key(keys::ROOM_INFO, serde_json::to_string(&RoomState::Invited)?),
+            self.encode_key(keys::ROOM_INFO, serde_json::to_string(&RoomState::Knocked)?),
+        ];
         self.acquire()
             .await?
             .get_room_infos(states)
@@ -0,0 +1,149 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: The reactions the user reacts with most, e.g. to populate a
+//! quick-reaction bar.
+//!
+//! Every reaction sent with [`crate::Timeline::toggle_reaction`] is recorded.
+//! Older uses weigh less and less, so the most frequent reactions reflect the
+//! recent habits of the user.
+
+use matrix_sdk::Client;
+use matrix_sdk_base::{
+    store::FrequentReaction, StateStoreDataKey, StateStoreDataValue, StoreError,
+};
+
+/// The reactions used to fill the list when the user hasn't reacted enough.
+const DEFAULT_REACTIONS: &[&str] = &["👍️", "❤️", "😂", "😮", "😢", "🎉"];
+
+/// How much the previous uses of all the reactions weigh every time a reaction
+/// is used.
+const DECAY: f64 = 0.95;
+
+/// The score under which a reaction is forgotten.
+const MIN_SCORE: f64 = 0.01;
+
+/// The maximum number of reactions that are persisted.
+const MAX_REACTIONS: usize = 100;
+
+/// Load the frequent reactions from the store, the most frequent first.
+async fn load(client: &Client) -> Result<Vec<FrequentReaction>, StoreError> {
+    Ok(client
+        .store()
+        .get_kv_data(StateStoreDataKey::FrequentReactions)
+        .await?
+        .map(|value| {
+            value
+                .into_frequent_reactions()
+                .expect("Session data is not a list of frequent reactions")
+        })
+        .unwrap_or_default())
+}
+
+/// Record a use of the reaction `key` in `reactions`, keeping them sorted by
+/// frequency.
+fn record(reactions: &mut Vec<FrequentReaction>, key: &str) {
+    for reaction in reactions.iter_mut() {
+        reaction.score *= DECAY;
+    }
+
+    match reactions.iter_mut().find(|reaction| reaction.key == key) {
+        Some(reaction) => reaction.score += 1.0,
+        None => reactions.push(FrequentReaction { key: key.to_owned(), score: 1.0 }),
+    }
+
+    reactions.retain(|reaction| reaction.score >= MIN_SCORE);
+    reactions.sort_by(|a, b| b.score.total_cmp(&a.score));
+    reactions.truncate(MAX_REACTIONS);
+}
+
+/// Fill `keys` with the default reactions that it doesn't contain yet, up to
+/// `limit` keys.
+fn fill_with_defaults(keys: &mut Vec<String>, limit: usize) {
+    for default in DEFAULT_REACTIONS {
+        if keys.len() >= limit {
+            break;
+        }
+
+        if !keys.iter().any(|key| key == default) {
+            keys.push((*default).to_owned());
+        }
+    }
+
+    keys.truncate(limit);
+}
+
+/// Record a use of the reaction `key` by the user, and persist it.
+pub(crate) async fn record_reaction(client: &Client, key: &str) -> Result<(), StoreError> {
+    let mut reactions = load(client).await?;
+    record(&mut reactions, key);
+
+    client
+        .store()
+        .set_kv_data(
+            StateStoreDataKey::FrequentReactions,
+            StateStoreDataValue::FrequentReactions(reactions),
+        )
+        .await
+}
+
+/// Get at most `limit` of the reactions the user reacts with most, the most
+/// frequent first.
+///
+/// If the user hasn't used enough reactions yet, the list is completed with a
+/// default set of reactions.
+pub async fn frequent_reactions(client: &Client, limit: usize) -> Result<Vec<String>, StoreError> {
+    let mut keys: Vec<_> = load(client).await?.into_iter().map(|reaction| reaction.key).collect();
+    fill_with_defaults(&mut keys, limit);
+
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fill_with_defaults, record, DEFAULT_REACTIONS};
+
+    fn keys(reactions: &[matrix_sdk_base::store::FrequentReaction]) -> Vec<&str> {
+        reactions.iter().map(|reaction| reaction.key.as_str()).collect()
+    }
+
+    #[test]
+    fn test_record_prefers_recent_uses() {
+        let mut reactions = Vec::new();
+
+        record(&mut reactions, "🐈");
+        record(&mut reactions, "🐈");
+        record(&mut reactions, "🐕");
+        assert_eq!(keys(&reactions), ["🐈", "🐕"]);
+
+        // Many recent uses overtake older ones.
+        for _ in 0..3 {
+            record(&mut reactions, "🐕");
+        }
+        assert_eq!(keys(&reactions), ["🐕", "🐈"]);
+    }
+
+    #[test]
+    fn test_fill_with_defaults() {
+        let mut keys = vec!["🐈".to_owned(), DEFAULT_REACTIONS[1].to_owned()];
+        fill_with_defaults(&mut keys, 4);
+
+        // The defaults come after the used reactions, without duplicates.
+        assert_eq!(keys, ["🐈", DEFAULT_REACTIONS[1], DEFAULT_REACTIONS[0], DEFAULT_REACTIONS[2]]);
+
+        // The used reactions are capped too.
+        fill_with_defaults(&mut keys, 1);
+        assert_eq!(keys, ["🐈"]);
+    }
+}
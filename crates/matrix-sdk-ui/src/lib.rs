@@ -17,6 +17,8 @@ use ruma::html::HtmlSanitizerMode;
 mod events;
 
 pub mod encryption_sync_service;
+pub mod frequent_reactions;
+pub mod mentions;
 pub mod notification_client;
 pub mod room_list_service;
 pub mod sync_service;
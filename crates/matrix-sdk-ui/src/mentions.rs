@@ -0,0 +1,289 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: The users and rooms mentioned in a message, e.g. to render pills
+//! without parsing the HTML of the message.
+//!
+//! Mentions come from the `m.mentions` field of the message, or from the
+//! legacy `matrix.to` (or `matrix:`) links of its formatted body when it
+//! doesn't have this field.
+
+use std::{collections::BTreeSet, ops::Range};
+
+use ruma::{
+    events::{
+        room::message::{MessageFormat, MessageType},
+        Mentions,
+    },
+    html::{sanitize_html, Html, HtmlSanitizerMode, NodeRef, RemoveReplyFallback},
+    matrix_uri::MatrixId,
+    MatrixToUri, MatrixUri, OwnedRoomAliasId, OwnedRoomId, OwnedUserId, UserId,
+};
+
+/// SC: What a mention links to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MentionTarget {
+    /// A user.
+    User(OwnedUserId),
+
+    /// A room, by its ID.
+    Room(OwnedRoomId),
+
+    /// A room, by its alias.
+    RoomAlias(OwnedRoomAliasId),
+}
+
+/// SC: A link to a user or a room in the formatted body of a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MentionLink {
+    /// What the link points to.
+    pub target: MentionTarget,
+
+    /// The text of the link.
+    pub text: String,
+
+    /// The byte range of the text of the link in the text of the formatted
+    /// body, i.e. the concatenation of its text nodes.
+    pub range: Range<usize>,
+}
+
+/// SC: The mentions of a message, see [`resolve_mentions`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResolvedMentions {
+    /// The users mentioned in `m.mentions`, or with a link if the message
+    /// doesn't have `m.mentions`.
+    pub user_ids: BTreeSet<OwnedUserId>,
+
+    /// Whether the whole room is mentioned in `m.mentions`.
+    pub room: bool,
+
+    /// The links to users and rooms in the formatted body, in order.
+    pub links: Vec<MentionLink>,
+}
+
+impl ResolvedMentions {
+    /// Whether the user `user_id` is mentioned, directly or with `@room`.
+    pub fn mentions_user(&self, user_id: &UserId) -> bool {
+        self.room || self.user_ids.contains(user_id)
+    }
+}
+
+/// SC: Resolve the mentions of the message with the given `msgtype` and
+/// `m.mentions`.
+///
+/// When `mentions` is set, it's authoritative: the links of the formatted
+/// body are still resolved, but they don't add mentioned users. The links of
+/// the reply fallback of the formatted body, if any, are ignored.
+pub fn resolve_mentions(msgtype: &MessageType, mentions: Option<&Mentions>) -> ResolvedMentions {
+    let mut resolved = ResolvedMentions::default();
+
+    if let Some(mentions) = mentions {
+        resolved.user_ids.extend(mentions.user_ids.iter().cloned());
+        resolved.room = mentions.room;
+    }
+
+    let Some(html) = formatted_html(msgtype) else {
+        return resolved;
+    };
+    // The reply fallback contains a link to the sender of the replied-to event,
+    // which isn't a mention.
+    let html =
+        Html::parse(&sanitize_html(html, HtmlSanitizerMode::Compat, RemoveReplyFallback::Yes));
+
+    let mut text = String::new();
+    let mut links = Vec::new();
+    for node in html.children() {
+        collect_links(node, &mut text, &mut links);
+    }
+
+    for (href, range) in links {
+        let Some(target) = mention_target(&href) else {
+            continue;
+        };
+
+        if let (None, MentionTarget::User(user_id)) = (mentions, &target) {
+            resolved.user_ids.insert(user_id.clone());
+        }
+
+        resolved.links.push(MentionLink { target, text: text[range.clone()].to_owned(), range });
+    }
+
+    resolved
+}
+
+/// The HTML formatted body of the message with the given `msgtype`, if any.
+pub(crate) fn formatted_html(msgtype: &MessageType) -> Option<&str> {
+    let formatted = match msgtype {
+        MessageType::Emote(content) => content.formatted.as_ref(),
+        MessageType::Notice(content) => content.formatted.as_ref(),
+        MessageType::Text(content) => content.formatted.as_ref(),
+        _ => None,
+    }?;
+
+    (formatted.format == MessageFormat::Html).then_some(formatted.body.as_str())
+}
+
+/// The length of the HTML tag at the start of `html`, up to its closing `>`.
+pub(crate) fn tag_len(html: &str) -> usize {
+    let mut in_quotes = false;
+
+    for (idx, c) in html.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '>' if !in_quotes => return idx + 1,
+            _ => {}
+        }
+    }
+
+    html.len()
+}
+
+/// What the link `uri` points to, if it's a user or a room.
+fn mention_target(uri: &str) -> Option<MentionTarget> {
+    let id = match MatrixToUri::parse(uri) {
+        Ok(uri) => uri.id().clone(),
+        Err(_) => MatrixUri::parse(uri).ok()?.id().clone(),
+    };
+
+    match id {
+        MatrixId::User(user_id) => Some(MentionTarget::User(user_id)),
+        MatrixId::Room(room_id) => Some(MentionTarget::Room(room_id)),
+        MatrixId::RoomAlias(alias) => Some(MentionTarget::RoomAlias(alias)),
+        _ => None,
+    }
+}
+
+/// Append the text of `node` to `text`, and the target of the links it
+/// contains and the range of their text in `text` to `links`.
+fn collect_links(node: NodeRef<'_>, text: &mut String, links: &mut Vec<(String, Range<usize>)>) {
+    if let Some(node_text) = node.as_text() {
+        text.push_str(node_text);
+        return;
+    }
+
+    let href = node
+        .as_element()
+        .filter(|element| &*element.name.local == "a")
+        .and_then(|element| element.attrs.iter().find(|attr| &*attr.name.local == "href"))
+        .map(|attr| attr.value.to_string());
+
+    let start = text.len();
+    for child in node.children() {
+        collect_links(child, text, links);
+    }
+
+    if let Some(href) = href {
+        links.push((href, start..text.len()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ruma::{
+        events::{
+            room::message::{MessageType, TextMessageEventContent},
+            Mentions,
+        },
+        owned_user_id, room_alias_id, user_id,
+    };
+
+    use super::{resolve_mentions, MentionLink, MentionTarget};
+
+    #[test]
+    fn test_explicit_mentions() {
+        let msgtype = MessageType::text_plain("Hello everyone");
+        let mentions = Mentions::with_user_ids([owned_user_id!("@alice:localhost")]);
+
+        let resolved = resolve_mentions(&msgtype, Some(&mentions));
+        assert!(resolved.mentions_user(user_id!("@alice:localhost")));
+        assert!(!resolved.mentions_user(user_id!("@bob:localhost")));
+        assert!(resolved.links.is_empty());
+
+        let resolved = resolve_mentions(&msgtype, Some(&Mentions::with_room_mention()));
+        assert!(resolved.mentions_user(user_id!("@bob:localhost")));
+    }
+
+    #[test]
+    fn test_link_mentions() {
+        let msgtype = MessageType::Text(TextMessageEventContent::html(
+            "Alice: see #news:localhost, Alice",
+            "<a href=\"https://matrix.to/#/@alice:localhost\">Alice</a>: see \
+             <a href=\"https://matrix.to/#/%23news:localhost?via=localhost&amp;via=example.org\">\
+             <b>#news:localhost</b></a>, <a href=\"https://example.org\">Alice</a>",
+        ));
+
+        let resolved = resolve_mentions(&msgtype, None);
+        assert!(resolved.mentions_user(user_id!("@alice:localhost")));
+        assert_eq!(
+            resolved.links,
+            [
+                MentionLink {
+                    target: MentionTarget::User(owned_user_id!("@alice:localhost")),
+                    text: "Alice".to_owned(),
+                    range: 0..5,
+                },
+                MentionLink {
+                    target: MentionTarget::RoomAlias(room_alias_id!("#news:localhost").to_owned()),
+                    text: "#news:localhost".to_owned(),
+                    range: 11..26,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_link_range_is_the_position_of_the_link() {
+        let msgtype = MessageType::Text(TextMessageEventContent::html(
+            "Alice & Bob, ping Alice",
+            "Alice &amp; Bob, ping <a href=\"https://matrix.to/#/@alice:localhost\">Alice</a>",
+        ));
+
+        let resolved = resolve_mentions(&msgtype, None);
+        assert_eq!(
+            resolved.links,
+            [MentionLink {
+                target: MentionTarget::User(owned_user_id!("@alice:localhost")),
+                text: "Alice".to_owned(),
+                range: 18..23,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_explicit_mentions_are_authoritative() {
+        let msgtype = MessageType::Text(TextMessageEventContent::html(
+            "Alice: hi",
+            "<a href=\"https://matrix.to/#/@alice:localhost\">Alice</a>: hi",
+        ));
+
+        let resolved = resolve_mentions(&msgtype, Some(&Mentions::new()));
+        assert!(!resolved.mentions_user(user_id!("@alice:localhost")));
+        // The link is still resolved, e.g. to render a pill.
+        assert_eq!(resolved.links.len(), 1);
+    }
+
+    #[test]
+    fn test_reply_fallback_is_not_a_mention() {
+        let msgtype = MessageType::Text(TextMessageEventContent::html(
+            "> <@alice:localhost> Hello\n\nHi",
+            "<mx-reply><blockquote><a href=\"https://matrix.to/#/!room:localhost/$event\">\
+             In reply to</a> <a href=\"https://matrix.to/#/@alice:localhost\">\
+             @alice:localhost</a><br>Hello</blockquote></mx-reply>Hi",
+        ));
+
+        let resolved = resolve_mentions(&msgtype, None);
+        assert!(!resolved.mentions_user(user_id!("@alice:localhost")));
+        assert!(resolved.links.is_empty());
+    }
+}
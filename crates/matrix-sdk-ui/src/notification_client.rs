@@ -45,6 +45,7 @@ use tracing::{debug, info, instrument, trace, warn};
 
 use crate::{
     encryption_sync_service::{EncryptionSyncPermit, EncryptionSyncService, WithLocking},
+    mentions::resolve_mentions,
     sync_service::SyncService,
     DEFAULT_SANITIZER_MODE,
 };
@@ -230,7 +231,10 @@ impl NotificationClient {
                                 wait *= 2;
                             }
                             Err(err) => {
-                                return Err(err.into());
+                                // SC: Don't abort, an undecrypted notification is still better
+                                // than no notification.
+                                warn!("Failed to decrypt the notification event: {err}");
+                                return Ok(None);
                             }
                         }
                     }
@@ -593,8 +597,22 @@ pub struct NotificationItem {
     /// action)
     ///
     /// It is set if and only if the push actions could be determined.
+    ///
+    /// SC: Always `Some(false)` while Do-Not-Disturb is enabled.
     pub is_noisy: Option<bool>,
     pub has_mention: Option<bool>,
+    /// SC: Whether the event could be decrypted, i.e. it isn't an unable to
+    /// decrypt event.
+    pub is_decryptable: bool,
+    /// SC: The plain text body of the message, if any.
+    ///
+    /// It is `None` if the event isn't a message, or couldn't be decrypted.
+    pub body_preview: Option<String>,
+    /// SC: The keywords that caused this notification, if it was caused by
+    /// keyword rules.
+    ///
+    /// It is empty if it was caused by another rule, like a mention or a DM.
+    pub matched_keywords: Vec<String>,
 }
 
 impl NotificationItem {
@@ -620,6 +638,12 @@ impl NotificationItem {
             ),
         };
 
+        let is_decryptable = match &event {
+            NotificationEvent::Timeline(event) => !is_event_encrypted(event.event_type()),
+            NotificationEvent::Invite(_) => true,
+        };
+        let body_preview = body_preview(&event);
+
         let sender = match room.state() {
             RoomState::Invited => room.invite_details().await?.inviter,
             _ => room.get_member_no_sync(event.sender()).await?,
@@ -659,8 +683,36 @@ impl NotificationItem {
             }
         }
 
-        let is_noisy = push_actions.map(|actions| actions.iter().any(|a| a.sound().is_some()));
-        let has_mention = push_actions.map(|actions| actions.iter().any(|a| a.is_highlight()));
+        // SC: A snoozed room is muted, so its notifications can't be noisy.
+        let is_snoozed = room.is_snoozed();
+        // SC: Neither can notifications while Do-Not-Disturb is enabled.
+        let is_dnd = room.client().account().is_dnd_enabled().await.unwrap_or_else(|error| {
+            warn!("Unable to load the Do-Not-Disturb state: {error}");
+            false
+        });
+        let is_noisy = if is_dnd {
+            Some(false)
+        } else {
+            push_actions.map(|actions| !is_snoozed && actions.iter().any(|a| a.sound().is_some()))
+        };
+        // SC: Without push actions, look for the mentions of the message.
+        let has_mention = push_actions
+            .map(|actions| actions.iter().any(|a| a.is_highlight()))
+            .or_else(|| mentions_own_user(&event, room.own_user_id()));
+        let matched_keywords = match &raw_event {
+            RawNotificationEvent::Timeline(raw_event) => {
+                match room.evaluate_push_rules(raw_event).await {
+                    Ok(evaluation) => {
+                        evaluation.map(|evaluation| evaluation.matched_keywords).unwrap_or_default()
+                    }
+                    Err(error) => {
+                        warn!("Unable to evaluate the push rules of the notification: {error}");
+                        Vec::new()
+                    }
+                }
+            }
+            RawNotificationEvent::Invite(_) => Vec::new(),
+        };
 
         let item = NotificationItem {
             event,
@@ -676,12 +728,44 @@ impl NotificationItem {
             joined_members_count: room.joined_members_count(),
             is_noisy,
             has_mention,
+            is_decryptable,
+            body_preview,
+            matched_keywords,
         };
 
         Ok(item)
     }
 }
 
+/// SC: Whether the message `event` mentions the user `own_user_id`, or `None` if
+/// it's not a message.
+fn mentions_own_user(event: &NotificationEvent, own_user_id: &UserId) -> Option<bool> {
+    let NotificationEvent::Timeline(AnySyncTimelineEvent::MessageLike(
+        AnySyncMessageLikeEvent::RoomMessage(SyncRoomMessageEvent::Original(ev)),
+    )) = event
+    else {
+        return None;
+    };
+
+    Some(
+        resolve_mentions(&ev.content.msgtype, ev.content.mentions.as_ref())
+            .mentions_user(own_user_id),
+    )
+}
+
+/// SC: The plain text body of the message `event`, or `None` if it's not a
+/// message.
+fn body_preview(event: &NotificationEvent) -> Option<String> {
+    let NotificationEvent::Timeline(AnySyncTimelineEvent::MessageLike(
+        AnySyncMessageLikeEvent::RoomMessage(SyncRoomMessageEvent::Original(ev)),
+    )) = event
+    else {
+        return None;
+    };
+
+    Some(ev.content.body().to_owned())
+}
+
 /// An error for the [`NotificationClient`].
 #[derive(Debug, Error)]
 pub enum Error {
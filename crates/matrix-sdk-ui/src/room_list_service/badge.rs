@@ -0,0 +1,188 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: The total badge count of the app, e.g. for the dock or the taskbar.
+
+use std::collections::HashMap;
+
+use async_stream::stream;
+use futures_util::Stream;
+use matrix_sdk::{
+    notification_settings::{NotificationSettings, RoomNotificationMode},
+    Client, Room,
+};
+use matrix_sdk_base::{sync::UnreadNotificationsCount, RoomState, ScUnreadCountSource};
+use ruma::{OwnedRoomId, RoomId};
+use tokio::{select, sync::broadcast::error::RecvError};
+
+/// The badge of a room, ignoring whether it's muted or snoozed: its number of
+/// unread notifications, or 1 if it's only marked as unread, like
+/// [`super::filters::new_filter_unread`].
+fn unread_badge(counts: UnreadNotificationsCount, is_marked_unread: bool) -> u64 {
+    if counts.notification_count > 0 {
+        counts.notification_count
+    } else {
+        u64::from(is_marked_unread)
+    }
+}
+
+/// The badge of `room`, which is 0 if the room isn't joined, if it's muted or
+/// snoozed, or if its notifications only make it unread.
+async fn room_badge(settings: &NotificationSettings, room: &Room) -> u64 {
+    if room.state() != RoomState::Joined || room.is_snoozed() || room.is_bold_unread_only() {
+        return 0;
+    }
+
+    if settings.get_user_defined_room_notification_mode(room.room_id()).await
+        == Some(RoomNotificationMode::Mute)
+    {
+        return 0;
+    }
+
+    let counts = room.unread_notification_counts_from(ScUnreadCountSource::Client);
+
+    unread_badge(counts, room.is_marked_unread())
+}
+
+/// Keeps the badge of every room with a badge, and their sum.
+#[derive(Debug, Default)]
+struct BadgeTracker {
+    badges: HashMap<OwnedRoomId, u64>,
+    total: u64,
+}
+
+impl BadgeTracker {
+    /// Set the badge of the room `room_id`.
+    ///
+    /// Returns whether the total has changed.
+    fn update(&mut self, room_id: &RoomId, badge: u64) -> bool {
+        let previous = if badge > 0 {
+            self.badges.insert(room_id.to_owned(), badge)
+        } else {
+            self.badges.remove(room_id)
+        }
+        .unwrap_or(0);
+
+        self.total = self.total - previous + badge;
+
+        previous != badge
+    }
+
+    /// Recompute the badges of all the rooms of `client`.
+    ///
+    /// Returns whether the total has changed.
+    async fn reset(&mut self, client: &Client, settings: &NotificationSettings) -> bool {
+        let previous = self.total;
+        *self = Self::default();
+
+        for room in client.rooms() {
+            let badge = room_badge(settings, &room).await;
+            self.update(room.room_id(), badge);
+        }
+
+        previous != self.total
+    }
+}
+
+/// Create a [`Stream`] of the total badge count for all the rooms of `client`.
+///
+/// The current total is yielded first, then a new total is yielded only when
+/// it changes.
+pub(super) fn badge_count_stream(client: Client) -> impl Stream<Item = u64> {
+    let mut roominfo_update_recv = client.roominfo_update_receiver();
+
+    stream! {
+        let settings = client.notification_settings().await;
+        // Muting or unmuting a room doesn't update its room info.
+        let mut settings_changes_recv = settings.subscribe_to_changes();
+
+        let mut tracker = BadgeTracker::default();
+        tracker.reset(&client, &settings).await;
+
+        yield tracker.total;
+
+        loop {
+            let changed = select! {
+                update = roominfo_update_recv.recv() => match update {
+                    Ok(update) => {
+                        let badge = match client.get_room(&update.room_id) {
+                            Some(room) => room_badge(&settings, &room).await,
+                            None => 0,
+                        };
+                        tracker.update(&update.room_id, badge)
+                    }
+
+                    // Some updates have been missed, recompute everything.
+                    Err(RecvError::Lagged(_)) => tracker.reset(&client, &settings).await,
+
+                    Err(RecvError::Closed) => break,
+                },
+
+                change = settings_changes_recv.recv() => match change {
+                    Ok(()) | Err(RecvError::Lagged(_)) => tracker.reset(&client, &settings).await,
+                    Err(RecvError::Closed) => break,
+                },
+            };
+
+            if changed {
+                yield tracker.total;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use matrix_sdk_base::read_receipts::RoomReadReceipts;
+    use ruma::room_id;
+
+    use super::{unread_badge, BadgeTracker};
+
+    #[test]
+    fn test_unread_badge() {
+        let mut read_receipts = RoomReadReceipts::default();
+        assert_eq!(unread_badge((&read_receipts).into(), false), 0);
+
+        // A room marked as unread counts once.
+        assert_eq!(unread_badge((&read_receipts).into(), true), 1);
+
+        // Unread messages that don't notify don't count.
+        read_receipts.num_unread = 5;
+        assert_eq!(unread_badge((&read_receipts).into(), false), 0);
+
+        read_receipts.num_notifications = 3;
+        assert_eq!(unread_badge((&read_receipts).into(), false), 3);
+        assert_eq!(unread_badge((&read_receipts).into(), true), 3);
+    }
+
+    #[test]
+    fn test_total_follows_badges() {
+        let mut tracker = BadgeTracker::default();
+
+        assert!(tracker.update(room_id!("!r0:bar.org"), 3));
+        assert!(tracker.update(room_id!("!r1:bar.org"), 1));
+        assert_eq!(tracker.total, 4);
+
+        // The same badge again doesn't change anything.
+        assert!(!tracker.update(room_id!("!r0:bar.org"), 3));
+
+        // The room has been read.
+        assert!(tracker.update(room_id!("!r0:bar.org"), 0));
+        assert_eq!(tracker.total, 1);
+
+        // Unknown rooms without a badge are ignored.
+        assert!(!tracker.update(room_id!("!r2:bar.org"), 0));
+        assert_eq!(tracker.total, 1);
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use matrix_sdk::{Client, RoomListEntry};
+
+use super::Filter;
+
+struct EncryptedRoomMatcher<F>
+where
+    F: Fn(&RoomListEntry) -> Option<bool>,
+{
+    is_encrypted: F,
+    expected: bool,
+}
+
+impl<F> EncryptedRoomMatcher<F>
+where
+    F: Fn(&RoomListEntry) -> Option<bool>,
+{
+    fn matches(&self, room_list_entry: &RoomListEntry) -> bool {
+        if !matches!(room_list_entry, RoomListEntry::Filled(_) | RoomListEntry::Invalidated(_)) {
+            return false;
+        }
+
+        (self.is_encrypted)(room_list_entry)
+            .is_some_and(|is_encrypted| is_encrypted == self.expected)
+    }
+}
+
+/// SC: Create a new filter that will accept all filled or invalidated entries
+/// of rooms that are encrypted if `encrypted` is `true`, or unencrypted
+/// otherwise (see [`matrix_sdk_base::Room::is_encrypted`]).
+///
+/// A room that enables encryption stops matching the filter of unencrypted
+/// rooms and starts matching the one of encrypted rooms when it's updated.
+pub fn new_filter(client: &Client, encrypted: bool) -> impl Filter {
+    let client = client.clone();
+
+    let matcher = EncryptedRoomMatcher {
+        is_encrypted: move |room| {
+            let room_id = room.as_room_id()?;
+            let room = client.get_room(room_id)?;
+
+            Some((*room).is_encrypted())
+        },
+        expected: encrypted,
+    };
+
+    move |room_list_entry| -> bool { matcher.matches(room_list_entry) }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Not;
+
+    use matrix_sdk::RoomListEntry;
+    use ruma::room_id;
+
+    use super::EncryptedRoomMatcher;
+
+    #[test]
+    fn test_is_encrypted() {
+        let matcher = EncryptedRoomMatcher { is_encrypted: |_| Some(true), expected: true };
+
+        assert!(matcher.matches(&RoomListEntry::Empty).not());
+        assert!(matcher.matches(&RoomListEntry::Filled(room_id!("!r0:bar.org").to_owned())));
+        assert!(matcher.matches(&RoomListEntry::Invalidated(room_id!("!r0:bar.org").to_owned())));
+
+        let matcher = EncryptedRoomMatcher { is_encrypted: |_| Some(true), expected: false };
+
+        assert!(matcher.matches(&RoomListEntry::Filled(room_id!("!r0:bar.org").to_owned())).not());
+    }
+
+    #[test]
+    fn test_is_not_encrypted() {
+        let matcher = EncryptedRoomMatcher { is_encrypted: |_| Some(false), expected: false };
+
+        assert!(matcher.matches(&RoomListEntry::Empty).not());
+        assert!(matcher.matches(&RoomListEntry::Filled(room_id!("!r0:bar.org").to_owned())));
+        assert!(matcher.matches(&RoomListEntry::Invalidated(room_id!("!r0:bar.org").to_owned())));
+
+        let matcher = EncryptedRoomMatcher { is_encrypted: |_| Some(false), expected: true };
+
+        assert!(matcher.matches(&RoomListEntry::Filled(room_id!("!r0:bar.org").to_owned())).not());
+    }
+
+    #[test]
+    fn test_encryption_state_cannot_be_found() {
+        for expected in [true, false] {
+            let matcher = EncryptedRoomMatcher { is_encrypted: |_| None, expected };
+
+            assert!(matcher
+                .matches(&RoomListEntry::Filled(room_id!("!r0:bar.org").to_owned()))
+                .not());
+        }
+    }
+}
@@ -60,11 +60,14 @@ mod category;
 mod favourite;
 mod fuzzy_match_room_name;
 mod invite;
+mod is_encrypted;
 mod joined;
+mod never_opened;
 mod non_left;
 mod none;
 mod normalized_match_room_name;
 mod not;
+mod orphan_rooms;
 mod unread;
 
 pub use all::new_filter as new_filter_all;
@@ -73,12 +76,15 @@ pub use category::{new_filter as new_filter_category, RoomCategory};
 pub use favourite::new_filter as new_filter_favourite;
 pub use fuzzy_match_room_name::new_filter as new_filter_fuzzy_match_room_name;
 pub use invite::new_filter as new_filter_invite;
+pub use is_encrypted::new_filter as new_filter_is_encrypted;
 pub use joined::new_filter as new_filter_joined;
 use matrix_sdk::RoomListEntry;
+pub use never_opened::new_filter as new_filter_never_opened;
 pub use non_left::new_filter as new_filter_non_left;
 pub use none::new_filter as new_filter_none;
 pub use normalized_match_room_name::new_filter as new_filter_normalized_match_room_name;
 pub use not::new_filter as new_filter_not;
+pub use orphan_rooms::new_filter as new_filter_orphan_rooms;
 use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
 pub use unread::new_filter as new_filter_unread;
 
@@ -93,7 +99,7 @@ impl<F> Filter for F where F: Fn(&RoomListEntry) -> bool {}
 /// Normalize a string, i.e. decompose it into NFD (Normalization Form D, i.e. a
 /// canonical decomposition, see http://www.unicode.org/reports/tr15/) and
 /// filter out the combining marks.
-fn normalize_string(str: &str) -> String {
+pub(crate) fn normalize_string(str: &str) -> String {
     str.nfd().filter(|c| !is_combining_mark(*c)).collect::<String>()
 }
 
@@ -0,0 +1,99 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use matrix_sdk::{Client, RoomListEntry};
+use matrix_sdk_base::RoomState;
+
+use super::Filter;
+
+struct NeverOpenedRoomMatcher<F>
+where
+    F: Fn(&RoomListEntry) -> Option<(RoomState, bool)>,
+{
+    state_and_was_opened: F,
+}
+
+impl<F> NeverOpenedRoomMatcher<F>
+where
+    F: Fn(&RoomListEntry) -> Option<(RoomState, bool)>,
+{
+    fn matches(&self, room: &RoomListEntry) -> bool {
+        if !matches!(room, RoomListEntry::Filled(_) | RoomListEntry::Invalidated(_)) {
+            return false;
+        }
+
+        match (self.state_and_was_opened)(room) {
+            Some((state, was_opened)) => state == RoomState::Joined && !was_opened,
+            None => false,
+        }
+    }
+}
+
+/// SC: Create a new filter that will accept all filled or invalidated entries
+/// of joined rooms that the user never opened.
+///
+/// A room stops matching once it's marked as opened by
+/// [`RoomListService::mark_opened`](crate::RoomListService::mark_opened).
+pub fn new_filter(client: &Client) -> impl Filter {
+    let client = client.clone();
+
+    let matcher = NeverOpenedRoomMatcher {
+        state_and_was_opened: move |room| {
+            let room_id = room.as_room_id()?;
+            let room = client.get_room(room_id)?;
+            Some((room.state(), room.was_opened()))
+        },
+    };
+
+    move |room_list_entry| -> bool { matcher.matches(room_list_entry) }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Not;
+
+    use matrix_sdk::RoomListEntry;
+    use matrix_sdk_base::RoomState;
+    use ruma::room_id;
+
+    use super::NeverOpenedRoomMatcher;
+
+    #[test]
+    fn test_joined_and_never_opened() {
+        let matcher =
+            NeverOpenedRoomMatcher { state_and_was_opened: |_| Some((RoomState::Joined, false)) };
+
+        assert!(matcher.matches(&RoomListEntry::Empty).not());
+        assert!(matcher.matches(&RoomListEntry::Filled(room_id!("!r0:bar.org").to_owned())));
+        assert!(matcher.matches(&RoomListEntry::Invalidated(room_id!("!r0:bar.org").to_owned())));
+    }
+
+    #[test]
+    fn test_joined_and_opened() {
+        let matcher =
+            NeverOpenedRoomMatcher { state_and_was_opened: |_| Some((RoomState::Joined, true)) };
+
+        assert!(matcher.matches(&RoomListEntry::Filled(room_id!("!r0:bar.org").to_owned())).not());
+    }
+
+    #[test]
+    fn test_not_joined() {
+        let matcher =
+            NeverOpenedRoomMatcher { state_and_was_opened: |_| Some((RoomState::Invited, false)) };
+        assert!(matcher.matches(&RoomListEntry::Filled(room_id!("!r0:bar.org").to_owned())).not());
+
+        let matcher = NeverOpenedRoomMatcher { state_and_was_opened: |_| None };
+        assert!(matcher.matches(&RoomListEntry::Filled(room_id!("!r0:bar.org").to_owned())).not());
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use matrix_sdk::{Client, RoomListEntry};
+use ruma::OwnedRoomId;
+
+use super::Filter;
+
+struct OrphanRoomMatcher<F>
+where
+    F: Fn(&RoomListEntry) -> Option<bool>,
+{
+    is_space_child: F,
+}
+
+impl<F> OrphanRoomMatcher<F>
+where
+    F: Fn(&RoomListEntry) -> Option<bool>,
+{
+    fn matches(&self, room_list_entry: &RoomListEntry) -> bool {
+        if !matches!(room_list_entry, RoomListEntry::Filled(_) | RoomListEntry::Invalidated(_)) {
+            return false;
+        }
+
+        (self.is_space_child)(room_list_entry)
+            .map(|is_space_child| !is_space_child)
+            .unwrap_or(false)
+    }
+}
+
+/// Create a new filter that will accept all filled or invalidated entries, but
+/// filters out rooms that are a child of any of the given spaces.
+///
+/// The children of the spaces are indexed by the client as the spaces are
+/// synced, see [`Client::is_space_child`]. Adding or removing a child updates
+/// the room list entry of the child, so it is filtered again right away.
+pub fn new_filter(client: &Client, space_ids: Vec<OwnedRoomId>) -> impl Filter {
+    let client = client.clone();
+
+    let matcher = OrphanRoomMatcher {
+        is_space_child: move |room| {
+            let room_id = room.as_room_id()?;
+
+            Some(client.is_space_child(room_id, &space_ids))
+        },
+    };
+
+    move |room_list_entry| -> bool { matcher.matches(room_list_entry) }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Not;
+
+    use matrix_sdk::RoomListEntry;
+    use ruma::room_id;
+
+    use super::OrphanRoomMatcher;
+
+    #[test]
+    fn test_is_orphan() {
+        let matcher = OrphanRoomMatcher { is_space_child: |_| Some(false) };
+
+        assert!(matcher.matches(&RoomListEntry::Empty).not());
+        assert!(matcher.matches(&RoomListEntry::Filled(room_id!("!r0:bar.org").to_owned())));
+        assert!(matcher.matches(&RoomListEntry::Invalidated(room_id!("!r0:bar.org").to_owned())));
+    }
+
+    #[test]
+    fn test_is_space_child() {
+        let matcher = OrphanRoomMatcher { is_space_child: |_| Some(true) };
+
+        assert!(matcher.matches(&RoomListEntry::Empty).not());
+        assert!(matcher.matches(&RoomListEntry::Filled(room_id!("!r0:bar.org").to_owned())).not());
+        assert!(matcher
+            .matches(&RoomListEntry::Invalidated(room_id!("!r0:bar.org").to_owned()))
+            .not());
+    }
+
+    #[test]
+    fn test_space_children_cannot_be_found() {
+        let matcher = OrphanRoomMatcher { is_space_child: |_| None };
+
+        assert!(matcher.matches(&RoomListEntry::Empty).not());
+        assert!(matcher.matches(&RoomListEntry::Filled(room_id!("!r0:bar.org").to_owned())).not());
+        assert!(matcher
+            .matches(&RoomListEntry::Invalidated(room_id!("!r0:bar.org").to_owned()))
+            .not());
+    }
+}
@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use matrix_sdk::{Client, RoomListEntry};
-use matrix_sdk_base::read_receipts::RoomReadReceipts;
+use matrix_sdk_base::{sync::UnreadNotificationsCount, ScUnreadCountSource};
 
 use super::Filter;
 
@@ -21,42 +21,46 @@ type IsMarkedUnread = bool;
 
 struct UnreadRoomMatcher<F>
 where
-    F: Fn(&RoomListEntry) -> Option<(RoomReadReceipts, IsMarkedUnread)>,
+    F: Fn(&RoomListEntry) -> Option<(UnreadNotificationsCount, IsMarkedUnread)>,
 {
-    read_receipts_and_unread: F,
+    counts_and_unread: F,
 }
 
 impl<F> UnreadRoomMatcher<F>
 where
-    F: Fn(&RoomListEntry) -> Option<(RoomReadReceipts, IsMarkedUnread)>,
+    F: Fn(&RoomListEntry) -> Option<(UnreadNotificationsCount, IsMarkedUnread)>,
 {
     fn matches(&self, room_list_entry: &RoomListEntry) -> bool {
         if !matches!(room_list_entry, RoomListEntry::Filled(_) | RoomListEntry::Invalidated(_)) {
             return false;
         }
 
-        let Some((read_receipts, is_marked_unread)) =
-            (self.read_receipts_and_unread)(room_list_entry)
-        else {
+        let Some((counts, is_marked_unread)) = (self.counts_and_unread)(room_list_entry) else {
             return false;
         };
 
-        read_receipts.num_notifications > 0 || is_marked_unread
+        counts.notification_count > 0 || is_marked_unread
     }
 }
 
 /// Create a new filter that will accept all filled or invalidated entries, but
 /// filters out rooms that have no unread notifications (different from unread
 /// messages), or is not marked as unread.
+///
+/// SC: The counts computed by the client are used, unless a room overrides
+/// their source, like the room list summary.
 pub fn new_filter(client: &Client) -> impl Filter {
     let client = client.clone();
 
     let matcher = UnreadRoomMatcher {
-        read_receipts_and_unread: move |room| {
+        counts_and_unread: move |room| {
             let room_id = room.as_room_id()?;
             let room = client.get_room(room_id)?;
 
-            Some((room.read_receipts(), room.is_marked_unread()))
+            Some((
+                room.unread_notification_counts_from(ScUnreadCountSource::Client),
+                room.is_marked_unread(),
+            ))
         },
     };
 
@@ -68,7 +72,7 @@ mod tests {
     use std::ops::Not;
 
     use matrix_sdk::RoomListEntry;
-    use matrix_sdk_base::read_receipts::RoomReadReceipts;
+    use matrix_sdk_base::{read_receipts::RoomReadReceipts, sync::UnreadNotificationsCount};
     use ruma::room_id;
 
     use super::UnreadRoomMatcher;
@@ -77,12 +81,12 @@ mod tests {
     fn test_has_unread_notifications() {
         for is_marked_as_unread in [true, false] {
             let matcher = UnreadRoomMatcher {
-                read_receipts_and_unread: |_| {
+                counts_and_unread: |_| {
                     let mut read_receipts = RoomReadReceipts::default();
                     read_receipts.num_unread = 42;
                     read_receipts.num_notifications = 42;
 
-                    Some((read_receipts, is_marked_as_unread))
+                    Some(((&read_receipts).into(), is_marked_as_unread))
                 },
             };
 
@@ -97,12 +101,12 @@ mod tests {
     #[test]
     fn test_has_unread_messages_but_no_unread_notifications_and_is_not_marked_as_unread() {
         let matcher = UnreadRoomMatcher {
-            read_receipts_and_unread: |_| {
+            counts_and_unread: |_| {
                 let mut read_receipts = RoomReadReceipts::default();
                 read_receipts.num_unread = 42;
                 read_receipts.num_notifications = 0;
 
-                Some((read_receipts, false))
+                Some(((&read_receipts).into(), false))
             },
         };
 
@@ -116,12 +120,12 @@ mod tests {
     #[test]
     fn test_has_unread_messages_but_no_unread_notifications_and_is_marked_as_unread() {
         let matcher = UnreadRoomMatcher {
-            read_receipts_and_unread: |_| {
+            counts_and_unread: |_| {
                 let mut read_receipts = RoomReadReceipts::default();
                 read_receipts.num_unread = 42;
                 read_receipts.num_notifications = 0;
 
-                Some((read_receipts, true))
+                Some(((&read_receipts).into(), true))
             },
         };
 
@@ -133,7 +137,7 @@ mod tests {
     #[test]
     fn test_has_no_unread_notifications_and_is_not_marked_as_unread() {
         let matcher = UnreadRoomMatcher {
-            read_receipts_and_unread: |_| Some((RoomReadReceipts::default(), false)),
+            counts_and_unread: |_| Some((UnreadNotificationsCount::default(), false)),
         };
 
         assert!(matcher.matches(&RoomListEntry::Empty).not());
@@ -146,7 +150,7 @@ mod tests {
     #[test]
     fn test_has_no_unread_notifications_and_is_marked_as_unread() {
         let matcher = UnreadRoomMatcher {
-            read_receipts_and_unread: |_| Some((RoomReadReceipts::default(), true)),
+            counts_and_unread: |_| Some((UnreadNotificationsCount::default(), true)),
         };
 
         assert!(matcher.matches(&RoomListEntry::Empty).not());
@@ -156,7 +160,7 @@ mod tests {
 
     #[test]
     fn test_read_receipts_cannot_be_found() {
-        let matcher = UnreadRoomMatcher { read_receipts_and_unread: |_| None };
+        let matcher = UnreadRoomMatcher { counts_and_unread: |_| None };
 
         assert!(matcher.matches(&RoomListEntry::Empty).not());
         assert!(matcher.matches(&RoomListEntry::Filled(room_id!("!r0:bar.org").to_owned())).not());
@@ -61,10 +61,15 @@
 //! [`RoomListService::state`] provides a way to get a stream of the state
 //! machine's state, which can be pretty helpful for the client app.
 
+mod badge;
 pub mod filters;
+mod read_all;
+mod recently_opened;
 mod room;
 mod room_list;
+pub mod sorters;
 mod state;
+mod summary;
 
 use std::{
     future::ready,
@@ -81,7 +86,8 @@ use matrix_sdk::{
     event_cache::EventCacheError, sliding_sync::Ranges, Client, Error as SlidingSyncError,
     SlidingSync, SlidingSyncList, SlidingSyncListBuilder, SlidingSyncMode,
 };
-use matrix_sdk_base::ring_buffer::RingBuffer;
+use matrix_sdk_base::{ring_buffer::RingBuffer, StoreError};
+pub use read_all::MarkReadError;
 pub use room::*;
 pub use room_list::*;
 use ruma::{
@@ -94,9 +100,12 @@ use ruma::{
     OwnedRoomId, RoomId,
 };
 pub use state::*;
+pub use summary::RoomListSummary;
 use thiserror::Error;
 use tokio::{sync::Mutex, time::timeout};
+use tracing::warn;
 
+use self::sorters::{ScSortOrder, SortStats};
 use crate::timeline;
 
 /// The [`RoomListService`] type. See the module's documentation to learn more.
@@ -126,6 +135,9 @@ pub struct RoomListService {
     current_space_ids: Mutex<Option<Vec<String>>>,
     /// Same as viewport_ranges but for SPACE_VISIBLE_ROOMS_LIST_NAME.
     space_viewport_ranges: Mutex<Ranges>,
+
+    /// SC: The statistics of the last client-side sort of a room list.
+    sort_stats: Arc<StdMutex<SortStats>>,
 }
 
 impl RoomListService {
@@ -240,6 +252,7 @@ impl RoomListService {
             viewport_ranges: Mutex::new(vec![VISIBLE_ROOMS_DEFAULT_RANGE]),
             current_space_ids: Mutex::new(None),
             space_viewport_ranges: Mutex::new(vec![VISIBLE_ROOMS_DEFAULT_RANGE]),
+            sort_stats: Default::default(),
         })
     }
 
@@ -418,8 +431,34 @@ impl RoomListService {
         self.state.subscribe()
     }
 
+    /// SC: Get a [`Stream`] of [`RoomListSummary`], i.e. the counts of the
+    /// rooms by tier (unread, invites, low priority…).
+    ///
+    /// The current summary is yielded first. Then a new summary is yielded
+    /// only when a room moves from one tier to another.
+    pub fn summary(&self) -> impl Stream<Item = RoomListSummary> {
+        summary::summary_stream(self.client.clone())
+    }
+
+    /// SC: Get a [`Stream`] of the total badge count of the app, i.e. the sum
+    /// of the unread notifications of the joined rooms, like
+    /// [`filters::new_filter_unread`].
+    ///
+    /// A room that is only marked as unread counts once, and muted or snoozed
+    /// rooms don't count. The current total is yielded first, then a new
+    /// total is yielded only when it changes.
+    pub fn total_badge_count(&self) -> impl Stream<Item = u64> {
+        badge::badge_count_stream(self.client.clone())
+    }
+
     async fn list_for(&self, sliding_sync_list_name: &str) -> Result<RoomList, Error> {
-        RoomList::new(&self.sliding_sync, sliding_sync_list_name, self.state()).await
+        RoomList::new(
+            &self.sliding_sync,
+            sliding_sync_list_name,
+            self.state(),
+            self.sort_stats.clone(),
+        )
+        .await
     }
 
     /// Get a [`RoomList`] for all rooms.
@@ -553,6 +592,96 @@ impl RoomListService {
         Ok(room)
     }
 
+    /// SC: Mark the room `room_id` as the most recently opened one.
+    ///
+    /// The list of recently opened rooms is persisted, deduplicated, and
+    /// capped, see [`Self::recently_opened`]. The room is also recorded as
+    /// opened at least once, see [`filters::new_filter_never_opened`].
+    pub async fn mark_opened(&self, room_id: &RoomId) -> Result<(), Error> {
+        let Some(room) = self.client.get_room(room_id) else {
+            return Err(Error::RoomNotFound(room_id.to_owned()));
+        };
+
+        recently_opened::mark_opened(&self.client, room_id).await?;
+        Ok(room.mark_opened().await?)
+    }
+
+    /// SC: Get at most `limit` of the most recently opened rooms, as marked by
+    /// [`Self::mark_opened`], the most recent first.
+    ///
+    /// This is independent of the sort order of the room lists. Rooms that no
+    /// longer exist, or that have been left, are skipped.
+    pub async fn recently_opened(&self, limit: usize) -> Result<Vec<OwnedRoomId>, Error> {
+        Ok(recently_opened::recently_opened(&self.client, limit).await?)
+    }
+
+    /// SC: Get the statistics of the last client-side sort of the entries of a
+    /// room list, see [`RoomListDynamicEntriesController::set_sort_order`].
+    ///
+    /// The entries are sorted again on every update of the list, so this
+    /// helps finding out whether a sorter is too expensive. Returns the
+    /// default statistics if no entries have been sorted yet.
+    pub fn last_sort_stats(&self) -> SortStats {
+        *self.sort_stats.lock().unwrap()
+    }
+
+    /// SC: Get a one-shot copy of all the rooms, sorted with `sort_order`.
+    ///
+    /// This doesn't change the sort order of the live room lists, set with
+    /// [`RoomListDynamicEntriesController::set_sort_order`], so it can be used
+    /// to preview a sort order before applying it. Empty entries, and rooms
+    /// that can't be found, are skipped.
+    ///
+    /// Unlike [`Self::room`], this doesn't add the rooms to the cache of the
+    /// service, to not evict the rooms used by the live room lists.
+    pub async fn sorted_snapshot(&self, sort_order: &ScSortOrder) -> Result<Vec<Room>, Error> {
+        let mut entries = self
+            .sliding_sync
+            .on_list(ALL_ROOMS_LIST_NAME, |list| ready(list.room_list::<RoomListEntry>()))
+            .await
+            .ok_or_else(|| Error::UnknownList(ALL_ROOMS_LIST_NAME.to_owned()))?;
+
+        if let Some(sorter) = sort_order.sorter(&self.client) {
+            entries.sort_by(|left, right| sorter(left, right));
+        }
+
+        let cached_rooms = self.rooms.lock().unwrap();
+
+        Ok(entries
+            .iter()
+            .filter_map(RoomListEntry::as_room_id)
+            .filter_map(|room_id| {
+                if let Some(room) = cached_rooms.iter().rfind(|room| room.id() == room_id) {
+                    return Some(room.clone());
+                }
+
+                match Room::new(&self.client, room_id, &self.sliding_sync) {
+                    Ok(room) => Some(room),
+                    Err(error) => {
+                        warn!("Skipping room {room_id} in the sorted snapshot: {error}");
+                        None
+                    }
+                }
+            })
+            .collect())
+    }
+
+    /// SC: Mark all the joined rooms matching `filter`, or all the joined
+    /// rooms if `filter` is `None`, as read.
+    ///
+    /// The fully-read marker and the read receipt of each room are moved to
+    /// its latest event, and the room is no longer marked as unread. Rooms
+    /// with nothing unread are skipped, and at most a few rooms are handled
+    /// concurrently.
+    ///
+    /// Returns the number of rooms that have been marked as read. If some rooms
+    /// failed to be marked as read, [`Error::MarkAllRead`] is returned with
+    /// the error of each of them, the other rooms being marked as read
+    /// nonetheless.
+    pub async fn mark_all_read(&self, filter: Option<BoxedFilterFn>) -> Result<usize, Error> {
+        read_all::mark_all_read(&self.client, filter).await
+    }
+
     #[cfg(test)]
     pub fn sliding_sync(&self) -> &SlidingSync {
         &self.sliding_sync
@@ -612,6 +741,20 @@ pub enum Error {
 
     #[error("The attached event cache ran into an error")]
     EventCache(#[from] EventCacheError),
+
+    /// SC: The store ran into an error.
+    #[error("The store ran into an error")]
+    Store(#[from] StoreError),
+
+    /// SC: Some rooms couldn't be marked as read, see
+    /// [`RoomListService::mark_all_read`].
+    #[error("{} rooms couldn't be marked as read", .failures.len())]
+    MarkAllRead {
+        /// The number of rooms that have been marked as read.
+        num_marked: usize,
+        /// The rooms that couldn't be marked as read, with the reason.
+        failures: Vec<(OwnedRoomId, MarkReadError)>,
+    },
 }
 
 /// An input for the [`RoomList`]' state machine.
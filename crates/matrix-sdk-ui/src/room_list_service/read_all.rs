@@ -0,0 +1,156 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: Mark many rooms of the room list as read at once.
+
+use futures_util::{stream, StreamExt};
+use matrix_sdk::{
+    room::{MessagesOptions, Receipts},
+    Client, Room, RoomListEntry,
+};
+use matrix_sdk_base::read_receipts::RoomReadReceipts;
+use ruma::{assign, uint, OwnedEventId, OwnedRoomId};
+use thiserror::Error;
+use tracing::warn;
+
+use super::{BoxedFilterFn, Error};
+
+/// SC: An error when marking a room as read, see
+/// [`RoomListService::mark_all_read`](super::RoomListService::mark_all_read).
+#[derive(Debug, Error)]
+pub enum MarkReadError {
+    /// The room has unread messages, but none of its events could be found to
+    /// move the read receipt to.
+    #[error("no event to mark as read was found")]
+    NoEvent,
+
+    /// The requests to mark the room as read failed.
+    #[error(transparent)]
+    Sdk(#[from] matrix_sdk::Error),
+}
+
+/// The maximum number of rooms that are marked as read concurrently.
+const MAX_CONCURRENT_ROOMS: usize = 10;
+
+/// Whether a room has something to mark as read.
+fn has_unread(read_receipts: &RoomReadReceipts, is_marked_unread: bool) -> bool {
+    read_receipts.num_unread > 0 || read_receipts.num_notifications > 0 || is_marked_unread
+}
+
+/// The ID of the latest event of `room`.
+///
+/// The latest event computed from sync is used if any, otherwise it's
+/// requested from the server.
+async fn latest_event_id(room: &Room) -> Result<Option<OwnedEventId>, MarkReadError> {
+    if let Some(event_id) = room.latest_event().and_then(|event| event.event_id()) {
+        return Ok(Some(event_id));
+    }
+
+    let options = assign!(MessagesOptions::backward(), { limit: uint!(1) });
+    let messages = room.messages(options).await?;
+
+    Ok(messages.chunk.first().and_then(|event| event.event.get_field("event_id").ok().flatten()))
+}
+
+/// Move the fully-read marker and the read receipt of `room` to its latest
+/// event, and clear its unread flag.
+async fn mark_read(room: &Room) -> Result<(), MarkReadError> {
+    let read_receipts = room.read_receipts();
+
+    // A room that is only marked as unread doesn't need a receipt.
+    if has_unread(&read_receipts, false) {
+        let event_id = latest_event_id(room).await?.ok_or(MarkReadError::NoEvent)?;
+
+        room.send_multiple_receipts(
+            Receipts::new().fully_read_marker(event_id.clone()).public_read_receipt(event_id),
+        )
+        .await?;
+    }
+
+    if room.is_marked_unread() {
+        room.set_unread_flag(false).await?;
+    }
+
+    Ok(())
+}
+
+/// Mark all the joined rooms of `client` matching `filter` as read.
+///
+/// Returns the number of rooms that have been marked as read, or
+/// [`Error::MarkAllRead`] with the rooms that failed to be marked as read.
+pub(super) async fn mark_all_read(
+    client: &Client,
+    filter: Option<BoxedFilterFn>,
+) -> Result<usize, Error> {
+    let rooms = client.joined_rooms().into_iter().filter(|room| {
+        has_unread(&room.read_receipts(), room.is_marked_unread())
+            && filter
+                .as_ref()
+                .map_or(true, |filter| filter(&RoomListEntry::Filled(room.room_id().to_owned())))
+    });
+
+    let results = stream::iter(rooms)
+        .map(|room| async move {
+            let result = mark_read(&room).await;
+
+            if let Err(error) = &result {
+                warn!(room_id = ?room.room_id(), ?error, "Failed to mark the room as read");
+            }
+
+            (room.room_id().to_owned(), result)
+        })
+        .buffer_unordered(MAX_CONCURRENT_ROOMS)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut num_marked = 0;
+    let mut failures = Vec::<(OwnedRoomId, MarkReadError)>::new();
+
+    for (room_id, result) in results {
+        match result {
+            Ok(()) => num_marked += 1,
+            Err(error) => failures.push((room_id, error)),
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(num_marked)
+    } else {
+        Err(Error::MarkAllRead { num_marked, failures })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use matrix_sdk_base::read_receipts::RoomReadReceipts;
+
+    use super::has_unread;
+
+    #[test]
+    fn test_has_unread() {
+        let read = RoomReadReceipts::default();
+        assert!(!has_unread(&read, false));
+
+        // A room explicitly marked as unread is marked as read.
+        assert!(has_unread(&read, true));
+
+        let mut unread = RoomReadReceipts::default();
+        unread.num_unread = 1;
+        assert!(has_unread(&unread, false));
+
+        let mut notifications = RoomReadReceipts::default();
+        notifications.num_notifications = 1;
+        assert!(has_unread(&notifications, false));
+    }
+}
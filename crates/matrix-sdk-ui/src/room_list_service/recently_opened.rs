@@ -0,0 +1,113 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: The most recently opened rooms, independently of the sort order of the
+//! room list.
+
+use matrix_sdk::Client;
+use matrix_sdk_base::{RoomState, StateStoreDataKey, StateStoreDataValue, StoreError};
+use ruma::{OwnedRoomId, RoomId};
+
+/// The maximum number of recently opened rooms that are persisted.
+const RECENTLY_OPENED_ROOMS_LIMIT: usize = 50;
+
+/// Whether the room `room_id` is still known by `client`, and not left.
+fn room_exists(client: &Client, room_id: &RoomId) -> bool {
+    client.get_room(room_id).is_some_and(|room| room.state() != RoomState::Left)
+}
+
+/// Load the recently opened rooms from the store, the most recent first.
+async fn load(client: &Client) -> Result<Vec<OwnedRoomId>, StoreError> {
+    Ok(client
+        .store()
+        .get_kv_data(StateStoreDataKey::RecentlyOpenedRooms)
+        .await?
+        .map(|value| {
+            value
+                .into_recently_opened_rooms()
+                .expect("Session data is not a list of recently opened rooms")
+        })
+        .unwrap_or_default())
+}
+
+/// Move or insert `room_id` at the front of `rooms`, and cap `rooms` to
+/// `limit` entries.
+fn push_front(rooms: &mut Vec<OwnedRoomId>, room_id: &RoomId, limit: usize) {
+    rooms.retain(|room| room != room_id);
+    rooms.insert(0, room_id.to_owned());
+    rooms.truncate(limit);
+}
+
+/// Mark `room_id` as the most recently opened room, and persist the list.
+///
+/// Rooms that no longer exist are dropped from the persisted list.
+pub(super) async fn mark_opened(client: &Client, room_id: &RoomId) -> Result<(), StoreError> {
+    let mut rooms = load(client).await?;
+    rooms.retain(|room| room_exists(client, room));
+    push_front(&mut rooms, room_id, RECENTLY_OPENED_ROOMS_LIMIT);
+
+    client
+        .store()
+        .set_kv_data(
+            StateStoreDataKey::RecentlyOpenedRooms,
+            StateStoreDataValue::RecentlyOpenedRooms(rooms),
+        )
+        .await
+}
+
+/// Get at most `limit` recently opened rooms that still exist, the most recent
+/// first.
+pub(super) async fn recently_opened(
+    client: &Client,
+    limit: usize,
+) -> Result<Vec<OwnedRoomId>, StoreError> {
+    Ok(load(client)
+        .await?
+        .into_iter()
+        .filter(|room| room_exists(client, room))
+        .take(limit)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use ruma::{owned_room_id, room_id};
+
+    use super::push_front;
+
+    #[test]
+    fn test_push_front_dedupes_and_caps() {
+        let mut rooms = Vec::new();
+
+        push_front(&mut rooms, room_id!("!r0:bar.org"), 3);
+        push_front(&mut rooms, room_id!("!r1:bar.org"), 3);
+        assert_eq!(rooms, [owned_room_id!("!r1:bar.org"), owned_room_id!("!r0:bar.org")]);
+
+        // Opening a room again moves it to the front, without duplicating it.
+        push_front(&mut rooms, room_id!("!r0:bar.org"), 3);
+        assert_eq!(rooms, [owned_room_id!("!r0:bar.org"), owned_room_id!("!r1:bar.org")]);
+
+        // The oldest rooms are dropped when the limit is reached.
+        push_front(&mut rooms, room_id!("!r2:bar.org"), 3);
+        push_front(&mut rooms, room_id!("!r3:bar.org"), 3);
+        assert_eq!(
+            rooms,
+            [
+                owned_room_id!("!r3:bar.org"),
+                owned_room_id!("!r2:bar.org"),
+                owned_room_id!("!r0:bar.org")
+            ]
+        );
+    }
+}
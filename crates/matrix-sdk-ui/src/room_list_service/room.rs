@@ -17,10 +17,16 @@
 use std::{ops::Deref, sync::Arc};
 
 use async_once_cell::OnceCell as AsyncOnceCell;
-use matrix_sdk::{Client, SlidingSync};
-use ruma::{api::client::sync::sync_events::v4::RoomSubscription, events::StateEventType, RoomId};
+use matrix_sdk::{room::RoomMember, Client, SlidingSync};
+use ruma::{
+    api::client::sync::sync_events::v4::RoomSubscription, events::StateEventType,
+    MilliSecondsSinceUnixEpoch, RoomId,
+};
 
-use super::Error;
+use super::{
+    sorters::{AttentionSignals, ScAttentionWeights},
+    Error,
+};
 use crate::{
     timeline::{EventTimelineItem, TimelineBuilder},
     Timeline,
@@ -88,6 +94,26 @@ impl Room {
         &self.inner.room
     }
 
+    /// SC: Get how much this room needs the attention of the user right now,
+    /// with the given weights, as used by
+    /// [`super::sorters::new_sorter_attention`].
+    ///
+    /// See [`ScAttentionWeights`] for the formula.
+    pub fn attention_score(&self, weights: &ScAttentionWeights) -> f64 {
+        let signals =
+            AttentionSignals::from_room(&self.inner.room, MilliSecondsSinceUnixEpoch::now());
+        weights.score(&signals)
+    }
+
+    /// SC: Get the member who has sent the invite, if this room is an invite
+    /// and the inviter is known.
+    ///
+    /// Useful to present the rooms matched by
+    /// [`super::filters::new_filter_invite`].
+    pub async fn inviter(&self) -> Option<RoomMember> {
+        self.inner.room.invite_details().await.ok()?.inviter
+    }
+
     /// Subscribe to this room.
     ///
     /// It means that all events from this room will be received every time, no
@@ -105,6 +131,15 @@ impl Room {
             settings.required_state.push((StateEventType::RoomCreate, "".to_owned()));
         }
 
+        // SC: Same for the pinned events, so that they can be shown for the room.
+        if !settings
+            .required_state
+            .iter()
+            .any(|(event_type, _state_key)| *event_type == StateEventType::RoomPinnedEvents)
+        {
+            settings.required_state.push((StateEventType::RoomPinnedEvents, "".to_owned()));
+        }
+
         self.inner
             .sliding_sync
             .subscribe_to_room(self.inner.room.room_id().to_owned(), Some(settings))
@@ -12,7 +12,11 @@
 // See the License for that specific language governing permissions and
 // limitations under the License.
 
-use std::{future::ready, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    future::ready,
+    sync::{Arc, Mutex as StdMutex},
+};
 
 use async_cell::sync::AsyncCell;
 use async_rx::StreamExt as _;
@@ -23,12 +27,17 @@ use eyeball_im_util::vector::VectorObserverExt;
 use futures_util::{pin_mut, stream, Stream, StreamExt as _};
 use matrix_sdk::{
     executor::{spawn, JoinHandle},
-    RoomListEntry, SlidingSync, SlidingSyncList,
+    Client, RoomListEntry, SlidingSync, SlidingSyncList,
 };
-use matrix_sdk_base::RoomInfoUpdate;
+use matrix_sdk_base::{instant::Instant, RoomInfoUpdate};
+use ruma::{OwnedRoomId, RoomId};
 use tokio::{select, sync::broadcast};
 
-use super::{filters::Filter, Error, State};
+use super::{
+    filters::Filter,
+    sorters::{BoxedSorterFn, ScSortOrder, SortStats},
+    Error, State,
+};
 
 /// A `RoomList` represents a list of rooms, from a
 /// [`RoomListService`](super::RoomListService).
@@ -37,6 +46,7 @@ pub struct RoomList {
     sliding_sync_list: SlidingSyncList,
     loading_state: SharedObservable<RoomListLoadingState>,
     loading_state_task: JoinHandle<()>,
+    sort_stats: Arc<StdMutex<SortStats>>,
 }
 
 impl Drop for RoomList {
@@ -50,6 +60,7 @@ impl RoomList {
         sliding_sync: &SlidingSync,
         sliding_sync_list_name: &str,
         room_list_service_state: Subscriber<State>,
+        sort_stats: Arc<StdMutex<SortStats>>,
     ) -> Result<Self, Error> {
         let sliding_sync_list = sliding_sync
             .on_list(sliding_sync_list_name, |list| ready(list.clone()))
@@ -67,6 +78,7 @@ impl RoomList {
         Ok(Self {
             sliding_sync_list: sliding_sync_list.clone(),
             loading_state: loading_state.clone(),
+            sort_stats,
             loading_state_task: spawn(async move {
                 pin_mut!(room_list_service_state);
 
@@ -129,14 +141,17 @@ impl RoomList {
     ) -> (impl Stream<Item = Vec<VectorDiff<RoomListEntry>>>, RoomListDynamicEntriesController)
     {
         let list = self.sliding_sync_list.clone();
+        let sort_stats = self.sort_stats.clone();
 
         let filter_fn_cell = AsyncCell::shared();
+        let sorter_fn = SharedObservable::new(None);
 
         let limit = SharedObservable::<usize>::new(page_size);
         let limit_stream = limit.subscribe();
 
         let dynamic_entries_controller = RoomListDynamicEntriesController::new(
             filter_fn_cell.clone(),
+            sorter_fn.clone(),
             page_size,
             limit,
             list.maximum_number_of_rooms_stream(),
@@ -150,7 +165,10 @@ impl RoomList {
                 // Combine normal stream events with other updates from rooms
                 let merged_stream = merge_stream_and_receiver(raw_values.clone(), raw_stream, roominfo_update_recv.resubscribe());
 
-                let (values, stream) = (raw_values, merged_stream)
+                // SC: Sort before filtering, the sort being stable, it doesn't matter.
+                let (sorted_values, sorted_stream) = sort_entries(raw_values, merged_stream, sorter_fn.subscribe(), sort_stats.clone());
+
+                let (values, stream) = (sorted_values, sorted_stream)
                     .filter(filter_fn)
                     .dynamic_limit_with_initial_value(page_size, limit_stream.clone());
 
@@ -213,6 +231,238 @@ fn merge_stream_and_receiver(
     }
 }
 
+/// SC: The function creating the client-side sorter of a room list, if any.
+///
+/// A new sorter is created for every sort, so that the sorters can compute
+/// what they compare the entries with only once per sort. If the function
+/// returns `None`, the order of the server is kept.
+type SharedSorter = Option<Arc<dyn Fn() -> Option<BoxedSorterFn> + Send + Sync>>;
+
+/// SC: Sort the room list entries with `sorter`, if any.
+///
+/// Every update of the underlying list, and every change of `sorter`, results
+/// in the minimal diffs between the previously and the newly sorted entries,
+/// see [`diff_sorted_entries`]. Without a sorter, the updates of the
+/// underlying list are forwarded untouched. The statistics of every sort are
+/// stored in `sort_stats`.
+fn sort_entries(
+    mut raw_current_values: Vector<RoomListEntry>,
+    raw_stream: impl Stream<Item = Vec<VectorDiff<RoomListEntry>>>,
+    mut sorter: Subscriber<SharedSorter>,
+    sort_stats: Arc<StdMutex<SortStats>>,
+) -> (Vector<RoomListEntry>, impl Stream<Item = Vec<VectorDiff<RoomListEntry>>>) {
+    let mut current_sorter = sorter.get();
+    let mut current_values = sort_values(&current_sorter, &raw_current_values, &sort_stats);
+    let values = current_values.clone();
+
+    let stream = stream! {
+        pin_mut!(raw_stream);
+
+        loop {
+            select! {
+                biased;
+
+                Some(new_sorter) = sorter.next() => {
+                    current_sorter = new_sorter;
+
+                    let values = sort_values(&current_sorter, &raw_current_values, &sort_stats);
+                    let diffs = diff_sorted_entries(&current_values, &values, &BTreeSet::new());
+                    current_values = values;
+
+                    if !diffs.is_empty() {
+                        yield diffs;
+                    }
+                }
+
+                diffs = raw_stream.next() => {
+                    let Some(diffs) = diffs else {
+                        break;
+                    };
+
+                    if current_sorter.is_none() {
+                        for diff in &diffs {
+                            diff.clone().apply(&mut raw_current_values);
+                        }
+                        current_values = raw_current_values.clone();
+
+                        yield diffs;
+                        continue;
+                    }
+
+                    // The rooms whose info was updated must be set again even if they
+                    // don't move.
+                    let mut updated = BTreeSet::new();
+                    for diff in diffs {
+                        if let VectorDiff::Set { value, .. } = &diff {
+                            updated.extend(value.as_room_id().map(ToOwned::to_owned));
+                        }
+                        diff.apply(&mut raw_current_values);
+                    }
+
+                    let values = sort_values(&current_sorter, &raw_current_values, &sort_stats);
+                    let diffs = diff_sorted_entries(&current_values, &values, &updated);
+                    current_values = values;
+
+                    if !diffs.is_empty() {
+                        yield diffs;
+                    }
+                }
+            }
+        }
+    };
+
+    (values, stream)
+}
+
+/// SC: Sort `values` with `sorter`, and store the statistics of the sort in
+/// `sort_stats`.
+///
+/// Without a sorter, the values are returned untouched.
+fn sort_values(
+    sorter: &SharedSorter,
+    values: &Vector<RoomListEntry>,
+    sort_stats: &StdMutex<SortStats>,
+) -> Vector<RoomListEntry> {
+    let Some(sorter) = sorter.as_ref().and_then(|new_sorter| new_sorter()) else {
+        return values.clone();
+    };
+
+    let start = Instant::now();
+    let mut comparisons = 0;
+
+    let mut values = values.iter().cloned().enumerate().collect::<Vec<_>>();
+    // A stable sort, so that the order of the server is kept for the
+    // entries that compare equal.
+    values.sort_by(|(_, left), (_, right)| {
+        comparisons += 1;
+        sorter(left, right)
+    });
+
+    let moved = values
+        .iter()
+        .enumerate()
+        .filter(|(index, (server_index, _))| index != server_index)
+        .count();
+    *sort_stats.lock().unwrap() =
+        SortStats { comparisons, moved: moved as u64, duration: start.elapsed() };
+
+    values.into_iter().map(|(_, value)| value).collect()
+}
+
+/// SC: Compute the diffs that turn `old` into `new`, two orders of the same
+/// room list.
+///
+/// The entries are matched by room ID, and the empty entries in order. The
+/// entries that are in the same relative order in both lists keep their
+/// place, the other ones are removed and inserted at their new position, so
+/// that a room that moves only results in one removal and one insertion. The
+/// entries that keep their place are set again if they changed, or if their
+/// room is in `updated`.
+///
+/// If this results in more diffs than entries, a single [`VectorDiff::Reset`]
+/// is returned instead.
+fn diff_sorted_entries(
+    old: &Vector<RoomListEntry>,
+    new: &Vector<RoomListEntry>,
+    updated: &BTreeSet<OwnedRoomId>,
+) -> Vec<VectorDiff<RoomListEntry>> {
+    let mut new_indices = HashMap::<Option<&RoomId>, VecDeque<usize>>::new();
+    for (index, entry) in new.iter().enumerate() {
+        new_indices.entry(entry.as_room_id()).or_default().push_back(index);
+    }
+
+    let matches = old
+        .iter()
+        .map(|entry| new_indices.get_mut(&entry.as_room_id()).and_then(VecDeque::pop_front))
+        .collect::<Vec<_>>();
+    let kept = longest_increasing_subsequence(&matches);
+
+    // The old index of the entries of `new` that keep their place.
+    let mut kept_from = vec![None; new.len()];
+    for (old_index, new_index) in matches.iter().enumerate() {
+        if let Some(new_index) = new_index.filter(|_| kept[old_index]) {
+            kept_from[new_index] = Some(old_index);
+        }
+    }
+
+    let mut diffs = Vec::new();
+
+    // Remove from the end, so that the indices of the next removals are still
+    // valid.
+    for index in (0..old.len()).rev() {
+        if !kept[index] {
+            diffs.push(VectorDiff::Remove { index });
+        }
+    }
+
+    // Insert from the start, so that all the entries before the inserted one
+    // are already at their place.
+    for (index, entry) in new.iter().enumerate() {
+        match kept_from[index] {
+            None => diffs.push(VectorDiff::Insert { index, value: entry.clone() }),
+            Some(old_index) => {
+                let is_updated =
+                    entry.as_room_id().is_some_and(|room_id| updated.contains(room_id));
+                if is_updated || !is_same_entry(&old[old_index], entry) {
+                    diffs.push(VectorDiff::Set { index, value: entry.clone() });
+                }
+            }
+        }
+    }
+
+    if diffs.len() > new.len() {
+        return vec![VectorDiff::Reset { values: new.clone() }];
+    }
+
+    diffs
+}
+
+/// SC: Whether both entries are the same, i.e. the same variant for the same
+/// room.
+fn is_same_entry(left: &RoomListEntry, right: &RoomListEntry) -> bool {
+    match (left, right) {
+        (RoomListEntry::Empty, RoomListEntry::Empty) => true,
+        (RoomListEntry::Filled(left), RoomListEntry::Filled(right))
+        | (RoomListEntry::Invalidated(left), RoomListEntry::Invalidated(right)) => left == right,
+        _ => false,
+    }
+}
+
+/// SC: Find the longest strictly increasing subsequence of `sequence`,
+/// ignoring the `None`s.
+///
+/// Returns whether each element of `sequence` is part of it.
+fn longest_increasing_subsequence(sequence: &[Option<usize>]) -> Vec<bool> {
+    // The `(index, value)` of the smallest last element of the increasing
+    // subsequences found so far, by length.
+    let mut tails = Vec::<(usize, usize)>::new();
+    let mut predecessors = vec![None; sequence.len()];
+
+    for (index, value) in sequence.iter().enumerate() {
+        let Some(value) = *value else {
+            continue;
+        };
+
+        let length = tails.partition_point(|(_, tail)| *tail < value);
+        predecessors[index] = length.checked_sub(1).map(|length| tails[length].0);
+
+        if length == tails.len() {
+            tails.push((index, value));
+        } else {
+            tails[length] = (index, value);
+        }
+    }
+
+    let mut in_subsequence = vec![false; sequence.len()];
+    let mut next = tails.last().map(|(index, _)| *index);
+    while let Some(index) = next {
+        in_subsequence[index] = true;
+        next = predecessors[index];
+    }
+
+    in_subsequence
+}
+
 /// The loading state of a [`RoomList`].
 ///
 /// When a [`RoomList`] is displayed to the user, it can be in various states.
@@ -263,6 +513,7 @@ pub type BoxedFilterFn = Box<dyn Filter + Send + Sync>;
 /// [`RoomList::entries_with_dynamic_adapters`]
 pub struct RoomListDynamicEntriesController {
     filter: Arc<AsyncCell<BoxedFilterFn>>,
+    sorter: SharedObservable<SharedSorter>,
     page_size: usize,
     limit: SharedObservable<usize>,
     maximum_number_of_rooms: Subscriber<Option<u32>>,
@@ -271,11 +522,12 @@ pub struct RoomListDynamicEntriesController {
 impl RoomListDynamicEntriesController {
     fn new(
         filter: Arc<AsyncCell<BoxedFilterFn>>,
+        sorter: SharedObservable<SharedSorter>,
         page_size: usize,
         limit_stream: SharedObservable<usize>,
         maximum_number_of_rooms: Subscriber<Option<u32>>,
     ) -> Self {
-        Self { filter, page_size, limit: limit_stream, maximum_number_of_rooms }
+        Self { filter, sorter, page_size, limit: limit_stream, maximum_number_of_rooms }
     }
 
     /// Set the filter.
@@ -294,6 +546,19 @@ impl RoomListDynamicEntriesController {
         }
     }
 
+    /// SC: Set the client-side sort order.
+    ///
+    /// The entries are sorted again right away, and the stream of entries
+    /// yields the diffs to the new order.
+    pub fn set_sort_order(&self, sort_order: ScSortOrder, client: &Client) {
+        if sort_order.is_server_order() {
+            self.sorter.set(None);
+        } else {
+            let client = client.clone();
+            self.sorter.set(Some(Arc::new(move || sort_order.sorter(&client))));
+        }
+    }
+
     /// Add one page, i.e. view `page_size` more entries in the room list if
     /// any.
     pub fn add_one_page(&self) {
@@ -319,3 +584,136 @@ impl RoomListDynamicEntriesController {
         self.limit.set_if_not_eq(self.page_size);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, sync::Arc};
+
+    use eyeball::SharedObservable;
+    use eyeball_im::{ObservableVector, Vector, VectorDiff};
+    use futures_util::{pin_mut, FutureExt, StreamExt};
+    use imbl::vector;
+    use matrix_sdk::RoomListEntry;
+    use ruma::{room_id, OwnedRoomId};
+
+    use super::{diff_sorted_entries, sort_entries, SharedSorter};
+    use crate::room_list_service::sorters::BoxedSorterFn;
+
+    fn entry(room_id: &str) -> RoomListEntry {
+        RoomListEntry::Filled(room_id.try_into().unwrap())
+    }
+
+    fn room_ids(entries: &Vector<RoomListEntry>) -> Vec<Option<String>> {
+        entries.iter().map(|entry| entry.as_room_id().map(ToString::to_string)).collect()
+    }
+
+    /// Check that the diffs between `old` and `new` turn the first into the
+    /// second, and return them.
+    fn diffs(
+        old: &Vector<RoomListEntry>,
+        new: &Vector<RoomListEntry>,
+        updated: &BTreeSet<OwnedRoomId>,
+    ) -> Vec<VectorDiff<RoomListEntry>> {
+        let diffs = diff_sorted_entries(old, new, updated);
+
+        let mut values = old.clone();
+        for diff in diffs.clone() {
+            diff.apply(&mut values);
+        }
+        assert_eq!(room_ids(&values), room_ids(new));
+
+        diffs
+    }
+
+    #[test]
+    fn test_diff_sorted_entries() {
+        let old = vector![entry("!a:b.c"), entry("!b:b.c"), entry("!c:b.c"), entry("!d:b.c")];
+
+        // Nothing changed.
+        assert!(diffs(&old, &old, &BTreeSet::new()).is_empty());
+
+        // A room was updated without moving.
+        let updated = BTreeSet::from([room_id!("!b:b.c").to_owned()]);
+        let changes = diffs(&old, &old, &updated);
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], VectorDiff::Set { index: 1, .. }));
+
+        // A room moved to the top.
+        let new = vector![entry("!d:b.c"), entry("!a:b.c"), entry("!b:b.c"), entry("!c:b.c")];
+        let changes = diffs(&old, &new, &BTreeSet::new());
+        assert_eq!(changes.len(), 2);
+        assert!(matches!(&changes[0], VectorDiff::Remove { index: 3 }));
+        assert!(matches!(&changes[1], VectorDiff::Insert { index: 0, .. }));
+
+        // A room moved to the bottom.
+        let new = vector![entry("!b:b.c"), entry("!c:b.c"), entry("!d:b.c"), entry("!a:b.c")];
+        assert_eq!(diffs(&old, &new, &BTreeSet::new()).len(), 2);
+
+        // A room was removed, and another one was added.
+        let new = vector![entry("!a:b.c"), entry("!e:b.c"), entry("!c:b.c"), entry("!d:b.c")];
+        assert_eq!(diffs(&old, &new, &BTreeSet::new()).len(), 2);
+
+        // Empty entries are loaded.
+        let old = vector![entry("!a:b.c"), RoomListEntry::Empty, RoomListEntry::Empty];
+        let new = vector![entry("!b:b.c"), entry("!a:b.c"), RoomListEntry::Empty];
+        diffs(&old, &new, &BTreeSet::new());
+
+        // Everything changed.
+        let old = vector![entry("!a:b.c"), entry("!b:b.c")];
+        let new = vector![entry("!c:b.c"), entry("!d:b.c")];
+        assert!(matches!(
+            diffs(&old, &new, &BTreeSet::new()).as_slice(),
+            [VectorDiff::Reset { .. }]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sort_entries_when_the_sort_order_changes() {
+        let mut raw_values = ObservableVector::new();
+        raw_values.append(vector![entry("!b:b.c"), entry("!a:b.c")]);
+        let sorter = SharedObservable::<SharedSorter>::new(None);
+
+        let (values, raw_stream) = raw_values.subscribe().into_values_and_batched_stream();
+        let (mut values, stream) =
+            sort_entries(values, raw_stream, sorter.subscribe(), Default::default());
+        pin_mut!(stream);
+
+        // Without a sorter, the order of the server is kept.
+        assert_eq!(room_ids(&values), [Some("!b:b.c".to_owned()), Some("!a:b.c".to_owned())]);
+
+        // Setting a sorter sorts the entries right away.
+        sorter.set(Some(Arc::new(|| {
+            Some(Box::new(|left: &RoomListEntry, right: &RoomListEntry| {
+                left.as_room_id().cmp(&right.as_room_id())
+            }) as BoxedSorterFn)
+        })));
+        for diff in stream.next().await.unwrap() {
+            diff.apply(&mut values);
+        }
+        assert_eq!(room_ids(&values), [Some("!a:b.c".to_owned()), Some("!b:b.c".to_owned())]);
+
+        // A new entry is inserted at its place, without resetting the list.
+        raw_values.push_back(entry("!0:b.c"));
+        let diffs = stream.next().await.unwrap();
+        assert!(matches!(diffs.as_slice(), [VectorDiff::Insert { index: 0, .. }]));
+        for diff in diffs {
+            diff.apply(&mut values);
+        }
+
+        // Updating a room that doesn't move only sets it again.
+        raw_values.set(0, entry("!b:b.c"));
+        let diffs = stream.next().await.unwrap();
+        assert!(matches!(diffs.as_slice(), [VectorDiff::Set { index: 2, .. }]));
+
+        // Removing the sorter restores the order of the server.
+        sorter.set(None);
+        for diff in stream.next().await.unwrap() {
+            diff.apply(&mut values);
+        }
+        assert_eq!(
+            room_ids(&values),
+            [Some("!b:b.c".to_owned()), Some("!a:b.c".to_owned()), Some("!0:b.c".to_owned())]
+        );
+        assert!(stream.next().now_or_never().is_none());
+    }
+}
@@ -0,0 +1,288 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use matrix_sdk::{Client, RoomListEntry};
+use ruma::{MilliSecondsSinceUnixEpoch, OwnedRoomId};
+
+use super::Sorter;
+
+/// The weights of the attention score of a room, which estimates how much a
+/// room needs the attention of the user.
+///
+/// The attention score of a room is:
+///
+/// ```text
+/// score = mentions × num_mentions
+///       + notifications × num_notifications
+///       + recency × 0.5 ^ (age / recency_half_life)
+///       + active_call × (1 if a call is active in the room, else 0)
+/// ```
+///
+/// where `num_mentions` and `num_notifications` are the unread counts
+/// computed by the client, so an unread mention counts in both, and `age` is
+/// the time since the latest event of the room. The recency term is `recency`
+/// for an event received right now, halves every `recency_half_life`, and is
+/// 0 if the latest event of the room is unknown.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct ScAttentionWeights {
+    /// The weight of each unread mention.
+    pub mentions: f64,
+
+    /// The weight of each unread notification.
+    pub notifications: f64,
+
+    /// The weight of a room whose latest event was just received.
+    pub recency: f64,
+
+    /// How long it takes for the recency term to halve.
+    ///
+    /// A zero half-life disables the recency term.
+    pub recency_half_life: Duration,
+
+    /// The weight of a call being active in the room.
+    pub active_call: f64,
+}
+
+impl Default for ScAttentionWeights {
+    fn default() -> Self {
+        Self {
+            mentions: 10.0,
+            notifications: 1.0,
+            recency: 5.0,
+            recency_half_life: Duration::from_secs(24 * 60 * 60),
+            active_call: 20.0,
+        }
+    }
+}
+
+impl ScAttentionWeights {
+    /// Compute the attention score of a room from its signals.
+    pub(crate) fn score(&self, signals: &AttentionSignals) -> f64 {
+        let recency = match signals.age {
+            Some(age) if !self.recency_half_life.is_zero() => {
+                self.recency
+                    * 0.5_f64.powf(age.as_secs_f64() / self.recency_half_life.as_secs_f64())
+            }
+            _ => 0.0,
+        };
+        let active_call = if signals.has_active_call { self.active_call } else { 0.0 };
+
+        self.mentions * signals.num_mentions as f64
+            + self.notifications * signals.num_notifications as f64
+            + recency
+            + active_call
+    }
+}
+
+/// What the attention score of a room is computed from.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct AttentionSignals {
+    num_mentions: u64,
+    num_notifications: u64,
+    /// The time since the latest event of the room, if it's known.
+    age: Option<Duration>,
+    has_active_call: bool,
+}
+
+impl AttentionSignals {
+    /// Collect the signals of `room`, measuring the age of its latest event at
+    /// `now`.
+    pub(crate) fn from_room(room: &matrix_sdk::Room, now: MilliSecondsSinceUnixEpoch) -> Self {
+        let latest_event_ts = room.latest_event().and_then(|latest_event| {
+            latest_event
+                .event()
+                .event
+                .get_field::<MilliSecondsSinceUnixEpoch>("origin_server_ts")
+                .ok()
+                .flatten()
+        });
+
+        Self {
+            num_mentions: room.num_unread_mentions(),
+            num_notifications: room.num_unread_notifications(),
+            age: latest_event_ts
+                .map(|ts| Duration::from_millis(u64::from(now.0).saturating_sub(u64::from(ts.0)))),
+            has_active_call: room.has_active_room_call(),
+        }
+    }
+}
+
+struct AttentionMatcher<F>
+where
+    F: Fn(&RoomListEntry) -> Option<AttentionSignals>,
+{
+    signals: F,
+    weights: ScAttentionWeights,
+    /// The scores of the rooms that were already computed.
+    scores: Mutex<HashMap<OwnedRoomId, f64>>,
+}
+
+impl<F> AttentionMatcher<F>
+where
+    F: Fn(&RoomListEntry) -> Option<AttentionSignals>,
+{
+    fn new(signals: F, weights: ScAttentionWeights) -> Self {
+        Self { signals, weights, scores: Default::default() }
+    }
+
+    /// The score of the entry: higher scores come first.
+    ///
+    /// Entries that aren't known rooms have a score of 0. The score of a room
+    /// is only computed the first time it's needed.
+    fn score(&self, room_list_entry: &RoomListEntry) -> f64 {
+        let Some(room_id) = room_list_entry.as_room_id() else {
+            return 0.0;
+        };
+
+        if let Some(score) = self.scores.lock().unwrap().get(room_id) {
+            return *score;
+        }
+
+        let score =
+            (self.signals)(room_list_entry).map_or(0.0, |signals| self.weights.score(&signals));
+        self.scores.lock().unwrap().insert(room_id.to_owned(), score);
+        score
+    }
+}
+
+/// Create a new sorter that puts the rooms with the highest attention score
+/// first, see [`ScAttentionWeights`] for how the score is computed.
+///
+/// The score of each room is computed once, the first time the room is
+/// compared, and the age of the latest events is measured when the sorter is
+/// created, so the order stays consistent during a sort. The sorter must
+/// therefore only be used for one sort: create a new one for every sort.
+pub fn new_sorter(client: &Client, weights: ScAttentionWeights) -> impl Sorter {
+    let client = client.clone();
+    let now = MilliSecondsSinceUnixEpoch::now();
+
+    let matcher = AttentionMatcher::new(
+        move |room_list_entry: &RoomListEntry| {
+            let room = client.get_room(room_list_entry.as_room_id()?)?;
+            Some(AttentionSignals::from_room(&room, now))
+        },
+        weights,
+    );
+
+    move |left, right| matcher.score(right).total_cmp(&matcher.score(left))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cmp::Ordering,
+        sync::atomic::{self, AtomicUsize},
+        time::Duration,
+    };
+
+    use matrix_sdk::RoomListEntry;
+    use ruma::{room_id, RoomId};
+
+    use super::{AttentionMatcher, AttentionSignals, ScAttentionWeights};
+
+    fn entry(room_id: &RoomId) -> RoomListEntry {
+        RoomListEntry::Filled(room_id.to_owned())
+    }
+
+    #[test]
+    fn test_score() {
+        let weights = ScAttentionWeights {
+            mentions: 10.0,
+            notifications: 1.0,
+            recency: 4.0,
+            recency_half_life: Duration::from_secs(60),
+            active_call: 20.0,
+        };
+
+        assert_eq!(weights.score(&AttentionSignals::default()), 0.0);
+
+        let signals =
+            AttentionSignals { num_mentions: 1, num_notifications: 3, ..Default::default() };
+        assert_eq!(weights.score(&signals), 13.0);
+
+        // The recency term halves every half-life.
+        let signals = AttentionSignals { age: Some(Duration::ZERO), ..Default::default() };
+        assert_eq!(weights.score(&signals), 4.0);
+        let signals =
+            AttentionSignals { age: Some(Duration::from_secs(120)), ..Default::default() };
+        assert_eq!(weights.score(&signals), 1.0);
+
+        let signals = AttentionSignals { has_active_call: true, ..Default::default() };
+        assert_eq!(weights.score(&signals), 20.0);
+
+        // A zero half-life disables the recency term.
+        let weights = ScAttentionWeights { recency_half_life: Duration::ZERO, ..weights };
+        let signals = AttentionSignals { age: Some(Duration::ZERO), ..Default::default() };
+        assert_eq!(weights.score(&signals), 0.0);
+    }
+
+    #[test]
+    fn test_order() {
+        let matcher = AttentionMatcher::new(
+            |room_list_entry: &RoomListEntry| match room_list_entry.as_room_id()?.as_str() {
+                "!mentioned:bar.org" => Some(AttentionSignals {
+                    num_mentions: 1,
+                    num_notifications: 1,
+                    ..Default::default()
+                }),
+                "!busy:bar.org" => {
+                    Some(AttentionSignals { num_notifications: 5, ..Default::default() })
+                }
+                "!quiet:bar.org" => Some(AttentionSignals::default()),
+                _ => None,
+            },
+            ScAttentionWeights::default(),
+        );
+
+        let mentioned = entry(room_id!("!mentioned:bar.org"));
+        let busy = entry(room_id!("!busy:bar.org"));
+        let quiet = entry(room_id!("!quiet:bar.org"));
+        let unknown = entry(room_id!("!unknown:bar.org"));
+
+        // A mention weighs more than a few notifications.
+        assert_eq!(matcher.score(&busy).total_cmp(&matcher.score(&mentioned)), Ordering::Less);
+        assert_eq!(matcher.score(&quiet).total_cmp(&matcher.score(&busy)), Ordering::Less);
+
+        // Unknown rooms don't need any attention.
+        assert_eq!(matcher.score(&unknown), matcher.score(&quiet));
+        assert_eq!(matcher.score(&RoomListEntry::Empty), 0.0);
+    }
+
+    #[test]
+    fn test_score_is_computed_once_per_room() {
+        let computed = AtomicUsize::new(0);
+        let matcher = AttentionMatcher::new(
+            |room_list_entry: &RoomListEntry| {
+                room_list_entry.as_room_id()?;
+                computed.fetch_add(1, atomic::Ordering::SeqCst);
+                Some(AttentionSignals::default())
+            },
+            ScAttentionWeights::default(),
+        );
+
+        let mut entries = vec![
+            entry(room_id!("!a:bar.org")),
+            RoomListEntry::Empty,
+            entry(room_id!("!b:bar.org")),
+            entry(room_id!("!c:bar.org")),
+            entry(room_id!("!d:bar.org")),
+        ];
+        entries.sort_by(|left, right| matcher.score(right).total_cmp(&matcher.score(left)));
+
+        assert_eq!(computed.load(atomic::Ordering::SeqCst), 4);
+    }
+}
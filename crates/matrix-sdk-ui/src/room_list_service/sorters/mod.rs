@@ -0,0 +1,248 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: A collection of room sorters.
+//!
+//! The entries provided by
+//! [`super::RoomList::entries_with_dynamic_adapters`] are in the order of the
+//! server. They can be sorted further on the client side with these sorters,
+//! through [`super::RoomListDynamicEntriesController::set_sort_order`]. The
+//! sorts are stable, so entries that compare equal keep the order of the
+//! server.
+
+use std::{cmp::Ordering, time::Duration};
+
+use matrix_sdk::{Client, RoomListEntry};
+
+mod attention;
+mod presence;
+mod snoozed;
+
+pub(super) use attention::AttentionSignals;
+pub use attention::{new_sorter as new_sorter_attention, ScAttentionWeights};
+pub use presence::new_sorter as new_sorter_presence;
+pub use snoozed::new_sorter as new_sorter_snoozed;
+
+/// A trait “alias” that represents a _sorter_.
+///
+/// A sorter is simply a function that receives two `&RoomListEntry` and
+/// returns how they compare.
+pub trait Sorter: Fn(&RoomListEntry, &RoomListEntry) -> Ordering {}
+
+impl<F> Sorter for F where F: Fn(&RoomListEntry, &RoomListEntry) -> Ordering {}
+
+/// Type alias for a boxed sorter function.
+pub type BoxedSorterFn = Box<dyn Sorter + Send + Sync>;
+
+/// Statistics about a sort of the room list entries, see
+/// [`RoomListService::last_sort_stats`](super::RoomListService::last_sort_stats).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct SortStats {
+    /// The number of times two entries were compared.
+    pub comparisons: u64,
+
+    /// The number of entries that aren't at the same position in the sorted
+    /// list as in the order of the server.
+    pub moved: u64,
+
+    /// How long the sort took.
+    pub duration: Duration,
+}
+
+/// The description of [`ScSortOrder::snoozed_last`] in
+/// [`ScSortOrder::describe`].
+const SNOOZED_DESCRIPTION: &str = "snoozed";
+
+/// The description of [`ScSortOrder::by_presence`] in
+/// [`ScSortOrder::describe`].
+const PRESENCE_DESCRIPTION: &str = "presence";
+
+/// The description of [`ScSortOrder::by_attention`] in
+/// [`ScSortOrder::describe`].
+const ATTENTION_DESCRIPTION: &str = "attention";
+
+/// The description of the order of the server, which always applies last, in
+/// [`ScSortOrder::describe`].
+const SERVER_DESCRIPTION: &str = "server";
+
+/// The client-side sort orders that can be applied to the room list.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct ScSortOrder {
+    /// Sort DMs by the presence of the other party, see
+    /// [`new_sorter_presence`].
+    pub by_presence: bool,
+
+    /// Put the rooms whose notifications are snoozed last, see
+    /// [`new_sorter_snoozed`].
+    ///
+    /// This takes precedence over the other sorts.
+    pub snoozed_last: bool,
+
+    /// Put the rooms that need the most attention first, according to their
+    /// attention score, see [`new_sorter_attention`].
+    ///
+    /// The other sorts take precedence over this one.
+    pub by_attention: bool,
+
+    /// The weights of the attention score, used when sorting
+    /// [by attention](Self::by_attention).
+    pub attention_weights: ScAttentionWeights,
+}
+
+impl ScSortOrder {
+    /// Whether this sort order keeps the order of the server untouched.
+    pub fn is_server_order(&self) -> bool {
+        !self.by_presence && !self.snoozed_last && !self.by_attention
+    }
+
+    /// Describe this sort order with a compact and stable string, e.g. to
+    /// attach it to bug reports.
+    ///
+    /// The description lists the enabled sorts in order of precedence,
+    /// separated by `|`, and always ends with `server` since the entries that
+    /// compare equal keep the order of the server, e.g.
+    /// `snoozed|presence|server`. It can be parsed back with
+    /// [`Self::from_description`].
+    ///
+    /// The [attention weights](Self::attention_weights) aren't part of the
+    /// description.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+
+        if self.snoozed_last {
+            parts.push(SNOOZED_DESCRIPTION);
+        }
+
+        if self.by_presence {
+            parts.push(PRESENCE_DESCRIPTION);
+        }
+
+        if self.by_attention {
+            parts.push(ATTENTION_DESCRIPTION);
+        }
+
+        parts.push(SERVER_DESCRIPTION);
+        parts.join("|")
+    }
+
+    /// Parse a description produced by [`Self::describe`].
+    ///
+    /// Returns `None` if the description isn't canonical, i.e. if it contains
+    /// unknown or duplicate sorts, if the sorts aren't in order of precedence,
+    /// or if it doesn't end with `server`. The attention weights are the
+    /// default ones.
+    pub fn from_description(description: &str) -> Option<Self> {
+        let mut parts = description.split('|').peekable();
+        let mut sort_order = Self::default();
+
+        if parts.next_if_eq(&SNOOZED_DESCRIPTION).is_some() {
+            sort_order.snoozed_last = true;
+        }
+
+        if parts.next_if_eq(&PRESENCE_DESCRIPTION).is_some() {
+            sort_order.by_presence = true;
+        }
+
+        if parts.next_if_eq(&ATTENTION_DESCRIPTION).is_some() {
+            sort_order.by_attention = true;
+        }
+
+        (parts.next() == Some(SERVER_DESCRIPTION) && parts.next().is_none()).then_some(sort_order)
+    }
+
+    /// Create the sorter for this sort order, or `None` if it keeps the
+    /// order of the server untouched.
+    ///
+    /// The sorter must only be used for one sort, since some sorts compute
+    /// what they compare the entries with once per sorter, see
+    /// [`new_sorter_attention`].
+    ///
+    /// The enabled sorts are applied in order of precedence: an entry is only
+    /// compared by a sort when all the sorts before it put both entries in the
+    /// same tier. Entries that are in the same tier for all the sorts keep the
+    /// order of the server.
+    pub fn sorter(&self, client: &Client) -> Option<BoxedSorterFn> {
+        let mut sorters = Vec::<BoxedSorterFn>::new();
+
+        if self.snoozed_last {
+            sorters.push(Box::new(new_sorter_snoozed(client)));
+        }
+
+        if self.by_presence {
+            sorters.push(Box::new(new_sorter_presence(client)));
+        }
+
+        if self.by_attention {
+            sorters.push(Box::new(new_sorter_attention(client, self.attention_weights)));
+        }
+
+        match sorters.len() {
+            0 => None,
+            1 => sorters.pop(),
+            // The first sorter that doesn't consider the entries equal wins.
+            _ => Some(Box::new(move |left, right| {
+                sorters.iter().fold(Ordering::Equal, |ordering, sorter| {
+                    ordering.then_with(|| sorter(left, right))
+                })
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScSortOrder;
+
+    #[test]
+    fn test_describe_round_trip() {
+        for snoozed_last in [false, true] {
+            for by_presence in [false, true] {
+                for by_attention in [false, true] {
+                    let sort_order = ScSortOrder {
+                        by_presence,
+                        snoozed_last,
+                        by_attention,
+                        ..Default::default()
+                    };
+                    let description = sort_order.describe();
+
+                    assert_eq!(ScSortOrder::from_description(&description), Some(sort_order));
+                }
+            }
+        }
+
+        assert_eq!(ScSortOrder::default().describe(), "server");
+        assert_eq!(
+            ScSortOrder { by_presence: true, snoozed_last: true, ..Default::default() }.describe(),
+            "snoozed|presence|server"
+        );
+        assert_eq!(
+            ScSortOrder { snoozed_last: true, by_attention: true, ..Default::default() }.describe(),
+            "snoozed|attention|server"
+        );
+    }
+
+    #[test]
+    fn test_from_non_canonical_description() {
+        assert_eq!(ScSortOrder::from_description(""), None);
+        assert_eq!(ScSortOrder::from_description("presence"), None);
+        assert_eq!(ScSortOrder::from_description("presence|snoozed|server"), None);
+        assert_eq!(ScSortOrder::from_description("snoozed|snoozed|server"), None);
+        assert_eq!(ScSortOrder::from_description("recency|server"), None);
+        assert_eq!(ScSortOrder::from_description("server|presence"), None);
+        assert_eq!(ScSortOrder::from_description("attention|presence|server"), None);
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use matrix_sdk::{Client, RoomListEntry};
+use ruma::events::presence::PresenceState;
+
+use super::Sorter;
+
+struct PresenceMatcher<F>
+where
+    F: Fn(&RoomListEntry) -> Option<PresenceState>,
+{
+    dm_partner_presence: F,
+}
+
+impl<F> PresenceMatcher<F>
+where
+    F: Fn(&RoomListEntry) -> Option<PresenceState>,
+{
+    /// The tier of the entry: lower tiers come first.
+    ///
+    /// Entries that aren't DMs, or whose partner's presence is unknown, are
+    /// in the same neutral tier as offline partners.
+    fn tier(&self, room_list_entry: &RoomListEntry) -> u8 {
+        match (self.dm_partner_presence)(room_list_entry) {
+            Some(PresenceState::Online) => 0,
+            Some(PresenceState::Unavailable) => 1,
+            _ => 2,
+        }
+    }
+}
+
+/// Create a new sorter that ranks DMs by the presence of the other party:
+/// online, then unavailable, then offline.
+///
+/// Rooms that aren't DMs with a single other member are in a neutral tier,
+/// along with the DMs whose partner is offline or whose presence is unknown.
+/// Presence is only known if the server sends it, and the room list is
+/// re-sorted as presence events arrive.
+pub fn new_sorter(client: &Client) -> impl Sorter {
+    let client = client.clone();
+
+    let matcher = PresenceMatcher {
+        dm_partner_presence: move |room_list_entry| {
+            client.get_room(room_list_entry.as_room_id()?)?.dm_partner_presence()
+        },
+    };
+
+    move |left, right| matcher.tier(left).cmp(&matcher.tier(right))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use matrix_sdk::RoomListEntry;
+    use ruma::{events::presence::PresenceState, room_id, RoomId};
+
+    use super::PresenceMatcher;
+
+    fn entry(room_id: &RoomId) -> RoomListEntry {
+        RoomListEntry::Filled(room_id.to_owned())
+    }
+
+    #[test]
+    fn test_tiers() {
+        let matcher = PresenceMatcher {
+            dm_partner_presence: |room_list_entry| match room_list_entry.as_room_id()?.as_str() {
+                "!online:bar.org" => Some(PresenceState::Online),
+                "!unavailable:bar.org" => Some(PresenceState::Unavailable),
+                "!offline:bar.org" => Some(PresenceState::Offline),
+                _ => None,
+            },
+        };
+
+        let online = entry(room_id!("!online:bar.org"));
+        let unavailable = entry(room_id!("!unavailable:bar.org"));
+        let offline = entry(room_id!("!offline:bar.org"));
+        let group = entry(room_id!("!group:bar.org"));
+
+        assert_eq!(matcher.tier(&online).cmp(&matcher.tier(&unavailable)), Ordering::Less);
+        assert_eq!(matcher.tier(&unavailable).cmp(&matcher.tier(&offline)), Ordering::Less);
+
+        // Non-DMs are neutral.
+        assert_eq!(matcher.tier(&offline), matcher.tier(&group));
+        assert_eq!(matcher.tier(&RoomListEntry::Empty), matcher.tier(&group));
+    }
+}
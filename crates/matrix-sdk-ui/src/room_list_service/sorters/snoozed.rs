@@ -0,0 +1,88 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use matrix_sdk::{Client, RoomListEntry};
+
+use super::Sorter;
+
+struct SnoozedMatcher<F>
+where
+    F: Fn(&RoomListEntry) -> Option<bool>,
+{
+    is_snoozed: F,
+}
+
+impl<F> SnoozedMatcher<F>
+where
+    F: Fn(&RoomListEntry) -> Option<bool>,
+{
+    /// The tier of the entry: lower tiers come first.
+    fn tier(&self, room_list_entry: &RoomListEntry) -> u8 {
+        match (self.is_snoozed)(room_list_entry) {
+            Some(true) => 1,
+            _ => 0,
+        }
+    }
+}
+
+/// Create a new sorter that puts the rooms whose notifications are snoozed
+/// after all the other rooms.
+///
+/// A snooze that has elapsed doesn't count.
+pub fn new_sorter(client: &Client) -> impl Sorter {
+    let client = client.clone();
+
+    let matcher = SnoozedMatcher {
+        is_snoozed: move |room_list_entry| {
+            Some(client.get_room(room_list_entry.as_room_id()?)?.is_snoozed())
+        },
+    };
+
+    move |left, right| matcher.tier(left).cmp(&matcher.tier(right))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use matrix_sdk::RoomListEntry;
+    use ruma::{room_id, RoomId};
+
+    use super::SnoozedMatcher;
+
+    fn entry(room_id: &RoomId) -> RoomListEntry {
+        RoomListEntry::Filled(room_id.to_owned())
+    }
+
+    #[test]
+    fn test_tiers() {
+        let matcher = SnoozedMatcher {
+            is_snoozed: |room_list_entry| match room_list_entry.as_room_id()?.as_str() {
+                "!snoozed:bar.org" => Some(true),
+                "!awake:bar.org" => Some(false),
+                _ => None,
+            },
+        };
+
+        let snoozed = entry(room_id!("!snoozed:bar.org"));
+        let awake = entry(room_id!("!awake:bar.org"));
+        let unknown = entry(room_id!("!unknown:bar.org"));
+
+        assert_eq!(matcher.tier(&awake).cmp(&matcher.tier(&snoozed)), Ordering::Less);
+
+        // Unknown rooms aren't snoozed.
+        assert_eq!(matcher.tier(&awake), matcher.tier(&unknown));
+        assert_eq!(matcher.tier(&RoomListEntry::Empty), matcher.tier(&awake));
+    }
+}
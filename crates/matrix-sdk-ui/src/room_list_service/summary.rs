@@ -0,0 +1,227 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: Counts of the rooms in the room list, see [`RoomListSummary`].
+
+use std::collections::HashMap;
+
+use async_stream::stream;
+use futures_util::Stream;
+use matrix_sdk::{Client, Room};
+use matrix_sdk_base::{RoomState, ScUnreadCountSource};
+use ruma::{OwnedRoomId, RoomId};
+use tokio::sync::broadcast::error::RecvError;
+
+/// SC: Counts of the rooms known by the client, e.g. to display badges on the
+/// tabs of a room list.
+///
+/// Left rooms aren't counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct RoomListSummary {
+    /// The number of rooms.
+    pub total: u64,
+
+    /// The number of rooms with unread notifications, or marked as unread,
+    /// like [`super::filters::new_filter_unread`].
+    ///
+    /// The counts computed by the client are used, unless a room overrides
+    /// their source.
+    pub unread: u64,
+
+    /// The number of rooms with unread mentions.
+    pub mentions: u64,
+
+    /// The number of invites, like [`super::filters::new_filter_invite`].
+    pub invites: u64,
+
+    /// The number of low priority rooms.
+    pub low_priority: u64,
+}
+
+/// The tier a single room falls in for the summary.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct RoomTier {
+    unread: bool,
+    mentions: bool,
+    invite: bool,
+    low_priority: bool,
+}
+
+impl RoomTier {
+    /// The tier of `room`, or `None` if the room must not be counted.
+    fn new(room: &Room) -> Option<Self> {
+        if room.state() == RoomState::Left {
+            return None;
+        }
+
+        let counts = room.unread_notification_counts_from(ScUnreadCountSource::Client);
+
+        Some(Self {
+            unread: counts.notification_count > 0 || room.is_marked_unread(),
+            mentions: counts.highlight_count > 0,
+            invite: room.state() == RoomState::Invited,
+            low_priority: room.is_low_priority(),
+        })
+    }
+}
+
+/// Keeps the tier of every room, and the summary of all the tiers.
+#[derive(Debug, Default)]
+struct SummaryTracker {
+    tiers: HashMap<OwnedRoomId, RoomTier>,
+    summary: RoomListSummary,
+}
+
+impl SummaryTracker {
+    fn add(&mut self, tier: RoomTier) {
+        self.apply(tier, |count| *count += 1);
+    }
+
+    fn remove(&mut self, tier: RoomTier) {
+        self.apply(tier, |count| *count -= 1);
+    }
+
+    fn apply(&mut self, tier: RoomTier, op: impl Fn(&mut u64)) {
+        let summary = &mut self.summary;
+
+        op(&mut summary.total);
+
+        for (flag, count) in [
+            (tier.unread, &mut summary.unread),
+            (tier.mentions, &mut summary.mentions),
+            (tier.invite, &mut summary.invites),
+            (tier.low_priority, &mut summary.low_priority),
+        ] {
+            if flag {
+                op(count);
+            }
+        }
+    }
+
+    /// Set the tier of the room `room_id`, `None` meaning that the room must
+    /// not be counted.
+    ///
+    /// Returns whether the summary has changed.
+    fn update(&mut self, room_id: &RoomId, tier: Option<RoomTier>) -> bool {
+        let previous = match tier {
+            Some(tier) => self.tiers.insert(room_id.to_owned(), tier),
+            None => self.tiers.remove(room_id),
+        };
+
+        if previous == tier {
+            return false;
+        }
+
+        if let Some(previous) = previous {
+            self.remove(previous);
+        }
+
+        if let Some(tier) = tier {
+            self.add(tier);
+        }
+
+        true
+    }
+
+    /// Recompute the tiers of all the rooms of `client`.
+    ///
+    /// Returns whether the summary has changed.
+    fn reset(&mut self, client: &Client) -> bool {
+        let previous = self.summary;
+        *self = Self::default();
+
+        for room in client.rooms() {
+            self.update(room.room_id(), RoomTier::new(&room));
+        }
+
+        previous != self.summary
+    }
+}
+
+/// Create a [`Stream`] of [`RoomListSummary`] for all the rooms of `client`.
+///
+/// The current summary is yielded first, then a new summary is yielded only
+/// when a room moves from one tier to another.
+pub(super) fn summary_stream(client: Client) -> impl Stream<Item = RoomListSummary> {
+    let mut roominfo_update_recv = client.roominfo_update_receiver();
+
+    stream! {
+        let mut tracker = SummaryTracker::default();
+        tracker.reset(&client);
+
+        yield tracker.summary;
+
+        loop {
+            let changed = match roominfo_update_recv.recv().await {
+                Ok(update) => {
+                    let tier = client.get_room(&update.room_id).as_ref().and_then(RoomTier::new);
+                    tracker.update(&update.room_id, tier)
+                }
+
+                // Some updates have been missed, recompute everything.
+                Err(RecvError::Lagged(_)) => tracker.reset(&client),
+
+                Err(RecvError::Closed) => break,
+            };
+
+            if changed {
+                yield tracker.summary;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ruma::room_id;
+
+    use super::{RoomListSummary, RoomTier, SummaryTracker};
+
+    #[test]
+    fn test_summary_follows_tiers() {
+        let mut tracker = SummaryTracker::default();
+
+        let unread = RoomTier { unread: true, mentions: true, ..Default::default() };
+        let invite = RoomTier { invite: true, ..Default::default() };
+
+        assert!(tracker.update(room_id!("!r0:bar.org"), Some(unread)));
+        assert!(tracker.update(room_id!("!r1:bar.org"), Some(invite)));
+        assert_eq!(
+            tracker.summary,
+            RoomListSummary { total: 2, unread: 1, mentions: 1, invites: 1, low_priority: 0 }
+        );
+
+        // The same tier again doesn't change anything.
+        assert!(!tracker.update(room_id!("!r0:bar.org"), Some(unread)));
+
+        // The room has been read, and moved to low priority.
+        let read = RoomTier { low_priority: true, ..Default::default() };
+        assert!(tracker.update(room_id!("!r0:bar.org"), Some(read)));
+        assert_eq!(
+            tracker.summary,
+            RoomListSummary { total: 2, unread: 0, mentions: 0, invites: 1, low_priority: 1 }
+        );
+
+        // The invite has been declined.
+        assert!(tracker.update(room_id!("!r1:bar.org"), None));
+        assert_eq!(
+            tracker.summary,
+            RoomListSummary { total: 1, unread: 0, mentions: 0, invites: 0, low_priority: 1 }
+        );
+
+        // Unknown rooms are ignored.
+        assert!(!tracker.update(room_id!("!r2:bar.org"), None));
+    }
+}
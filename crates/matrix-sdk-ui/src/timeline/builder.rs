@@ -12,16 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::BTreeSet, sync::Arc};
+use std::{collections::BTreeSet, sync::Arc, time::Duration};
 
+use chrono::Weekday;
 use futures_util::{pin_mut, StreamExt};
 use matrix_sdk::{
     event_cache::{EventsOrigin, RoomEventCacheUpdate},
-    executor::spawn,
+    executor::{spawn, JoinHandle},
     send_queue::{LocalEcho, RoomSendQueueUpdate},
     Room,
 };
-use ruma::{events::AnySyncTimelineEvent, RoomVersionId};
+use ruma::{
+    events::{AnySyncTimelineEvent, TimelineEventType},
+    serde::Raw,
+    OwnedUserId, RoomVersionId, UserId,
+};
 use tokio::sync::broadcast::error::RecvError;
 use tracing::{info, info_span, trace, warn, Instrument, Span};
 
@@ -29,12 +34,12 @@ use tracing::{info, info_span, trace, warn, Instrument, Span};
 use super::to_device::{handle_forwarded_room_key_event, handle_room_key_event};
 use super::{
     inner::{TimelineInner, TimelineInnerSettings},
-    Error, Timeline, TimelineDropHandle, TimelineFocus,
+    DividerGranularity, Error, Timeline, TimelineDropHandle, TimelineFocus,
 };
 use crate::{
     timeline::{
         event_handler::TimelineEventKind, event_item::RemoteEventOrigin, inner::TimelineEnd,
-        EventSendState,
+        EventSendState, SendError, TimelineItemContent,
     },
     unable_to_decrypt_hook::UtdHookManager,
 };
@@ -141,6 +146,135 @@ impl TimelineBuilder {
         self
     }
 
+    /// SC: Whether to collapse runs of consecutive state events (membership
+    /// changes, profile changes and other state events) into a
+    /// [`VirtualTimelineItem::StateGroup`](super::VirtualTimelineItem::StateGroup).
+    ///
+    /// The groups are recomputed whenever the timeline changes, e.g. when a new
+    /// event splits a group, and can be expanded with
+    /// [`Timeline::expand_state_group`].
+    ///
+    /// Defaults to `false`.
+    pub fn collapse_state_events(mut self, collapse: bool) -> Self {
+        self.settings.collapse_state_events = collapse;
+        self
+    }
+
+    /// SC: Whether to collapse runs of consecutive messages from the users
+    /// muted with [`Room::set_user_muted_locally`] into a
+    /// [`VirtualTimelineItem::HiddenMessages`](super::VirtualTimelineItem::HiddenMessages).
+    ///
+    /// The muted users are followed as they change, and the hidden messages
+    /// can be revealed with [`Timeline::expand_hidden_messages`]. This doesn't
+    /// affect the users ignored with `m.ignored_user_list`, whose events are
+    /// removed from the timeline altogether.
+    ///
+    /// Defaults to `false`.
+    pub fn collapse_muted_users(mut self, collapse: bool) -> Self {
+        self.settings.collapse_muted_users = collapse;
+        self
+    }
+
+    /// SC: Whether to insert a
+    /// [`VirtualTimelineItem::DayDivider`](super::VirtualTimelineItem::DayDivider)
+    /// between the events of different days.
+    ///
+    /// Disabling them is useful for compact views rendering their own
+    /// separators, as the timeline then never emits updates for them. The read
+    /// marker is still inserted.
+    ///
+    /// Defaults to `true`.
+    pub fn with_date_dividers(mut self, enabled: bool) -> Self {
+        self.settings.date_dividers = enabled;
+        self
+    }
+
+    /// SC: The period grouped under a
+    /// [`VirtualTimelineItem::DayDivider`](super::VirtualTimelineItem::DayDivider).
+    ///
+    /// Periods are computed in local time, like days. This can be changed
+    /// later with [`Timeline::set_divider_granularity`].
+    ///
+    /// Defaults to [`DividerGranularity::Day`].
+    pub fn divider_granularity(mut self, granularity: DividerGranularity) -> Self {
+        self.settings.divider_granularity = granularity;
+        self
+    }
+
+    /// SC: The first day of the week, used when grouping the events by
+    /// [`DividerGranularity::Week`].
+    ///
+    /// Defaults to [`Weekday::Mon`].
+    pub fn first_day_of_week(mut self, first_day_of_week: Weekday) -> Self {
+        self.settings.first_day_of_week = first_day_of_week;
+        self
+    }
+
+    /// SC: Keep at most `max_items` event items in a live timeline.
+    ///
+    /// When new events are received from sync, the oldest items are evicted
+    /// once the limit is exceeded. They can be reloaded with
+    /// [`Timeline::paginate_backwards`], before paginating further from the
+    /// server.
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.settings.max_items = Some(max_items);
+        self
+    }
+
+    /// SC: Compute the shields of the events in strict mode by default, see
+    /// [`EventTimelineItem::shield`](super::EventTimelineItem::shield).
+    ///
+    /// Defaults to `false`, the lax mode.
+    pub fn strict_shields(mut self, strict_shields: bool) -> Self {
+        self.settings.strict_shields = strict_shields;
+        self
+    }
+
+    /// SC: Only send read receipts for the items that stayed visible for at
+    /// least `delay`, as reported by [`Timeline::mark_visible`].
+    ///
+    /// This avoids marking a room as read when scrolling quickly through it.
+    /// By default, the read receipt is sent as soon as an item is visible.
+    pub fn receipt_visibility_delay(mut self, delay: Duration) -> Self {
+        self.settings.receipt_visibility_delay = Some(delay);
+        self
+    }
+
+    /// SC: Wait for `debounce` after a reaction is toggled with
+    /// [`Timeline::toggle_reaction`] before sending the request, so that a
+    /// burst of toggles only sends its net result.
+    ///
+    /// The local echo is always updated right away. Defaults to 300
+    /// milliseconds, and [`Duration::ZERO`] sends the requests immediately.
+    pub fn reaction_debounce(mut self, debounce: Duration) -> Self {
+        self.settings.reaction_debounce = debounce;
+        self
+    }
+
+    /// SC: Send at most `max` reaction requests at the same time, the other
+    /// ones waiting for their turn.
+    ///
+    /// Defaults to 4.
+    pub fn max_concurrent_reactions(mut self, max: usize) -> Self {
+        self.settings.max_concurrent_reactions = max.max(1);
+        self
+    }
+
+    /// SC: Rewrite the content of the event items before they are displayed,
+    /// e.g. to expand spoilers or to mask profanity.
+    ///
+    /// The function is applied to the items of [`Timeline::subscribe`] and
+    /// [`Timeline::subscribe_batched`] every time they are updated, and
+    /// returning `None` hides the item entirely. It doesn't affect the events
+    /// that are stored or sent.
+    pub fn with_content_transform<F>(mut self, transform: F) -> Self
+    where
+        F: Fn(&TimelineItemContent) -> Option<TimelineItemContent> + Send + Sync + 'static,
+    {
+        self.settings.content_transform = Some(Arc::new(transform));
+        self
+    }
+
     /// Create a [`Timeline`] with the options set on this builder.
     #[tracing::instrument(
         skip(self),
@@ -162,9 +296,15 @@ impl TimelineBuilder {
         let (_, mut event_subscriber) = room_event_cache.subscribe().await?;
 
         let is_live = matches!(focus, TimelineFocus::Live);
+        let collapse_muted_users = settings.collapse_muted_users;
+
+        let inner =
+            TimelineInner::new(room, focus, internal_id_prefix, unable_to_decrypt_hook, settings);
 
-        let inner = TimelineInner::new(room, focus, internal_id_prefix, unable_to_decrypt_hook)
-            .with_settings(settings);
+        // SC: The events of the ignored users must be filtered out from the initial
+        // items too.
+        let mut ignore_user_list = client.subscribe_to_ignore_user_list_changes();
+        inner.set_ignored_users(ignored_user_ids(&ignore_user_list.get())).await;
 
         let has_events = inner.init_focus(&room_event_cache).await?;
 
@@ -235,6 +375,13 @@ impl TimelineBuilder {
                         RoomEventCacheUpdate::AddTimelineEvents { events, origin } => {
                             trace!("Received new timeline events.");
 
+                            // SC: Remember whose membership changed, to refresh
+                            // the current membership of their items.
+                            let membership_changes = events
+                                .iter()
+                                .filter_map(|event| member_state_key(&event.event))
+                                .collect::<BTreeSet<_>>();
+
                             inner.add_events_at(
                                 events,
                                 TimelineEnd::Back,
@@ -242,6 +389,14 @@ impl TimelineBuilder {
                                     EventsOrigin::Sync => RemoteEventOrigin::Sync,
                                 }
                             ).await;
+
+                            if !membership_changes.is_empty() {
+                                let user_ids = membership_changes
+                                    .iter()
+                                    .map(|user_id| &**user_id)
+                                    .collect::<BTreeSet<_>>();
+                                inner.force_update_sender_profiles(&user_ids).await;
+                            }
                         }
 
                         RoomEventCacheUpdate::AddEphemeralEvents { events } => {
@@ -266,97 +421,8 @@ impl TimelineBuilder {
             .instrument(span)
         });
 
-        let local_echo_listener_handle = if is_live {
-            Some(spawn({
-                let timeline = inner.clone();
-                let (local_echoes, mut listener) = room.send_queue().subscribe().await;
-
-                // Handles existing local echoes first.
-                for echo in local_echoes {
-                    timeline
-                        .handle_local_event(
-                            echo.transaction_id,
-                            TimelineEventKind::Message {
-                                content: echo.content,
-                                relations: Default::default(),
-                            },
-                            Some(echo.abort_handle),
-                        )
-                        .await;
-                }
-
-                let span = info_span!(parent: Span::none(), "local_echo_handler", room_id = ?room.room_id());
-                span.follows_from(Span::current());
-
-                // React to future local echoes too.
-                async move {
-                    info!("spawned the local echo handler!");
-
-                    loop {
-                        match listener.recv().await {
-                            Ok(update) => match update {
-                                RoomSendQueueUpdate::NewLocalEvent(LocalEcho {
-                                    transaction_id,
-                                    content,
-                                    abort_handle,
-                                }) => {
-                                    timeline
-                                        .handle_local_event(
-                                            transaction_id,
-                                            TimelineEventKind::Message {
-                                                content,
-                                                relations: Default::default(),
-                                            },
-                                            Some(abort_handle),
-                                        )
-                                        .await;
-                                }
-
-                                RoomSendQueueUpdate::CancelledLocalEvent { transaction_id } => {
-                                    if !timeline.discard_local_echo(&transaction_id).await {
-                                        warn!("couldn't find the local echo to discard");
-                                    }
-                                }
-
-                                RoomSendQueueUpdate::SendError {
-                                    transaction_id,
-                                    error,
-                                    is_recoverable,
-                                } => {
-                                    timeline
-                                        .update_event_send_state(
-                                            &transaction_id,
-                                            EventSendState::SendingFailed { error, is_recoverable },
-                                        )
-                                        .await;
-                                }
-
-                                RoomSendQueueUpdate::SentEvent { transaction_id, event_id } => {
-                                    timeline
-                                        .update_event_send_state(
-                                            &transaction_id,
-                                            EventSendState::Sent { event_id },
-                                        )
-                                        .await;
-                                }
-                            },
-
-                            Err(RecvError::Lagged(num_missed)) => {
-                                warn!("missed {num_missed} local echoes, ignoring those missed");
-                            }
-
-                            Err(RecvError::Closed) => {
-                                info!("channel closed, exiting the local echo handler");
-                                break;
-                            }
-                        }
-                    }
-                }
-                .instrument(span)
-            }))
-        } else {
-            None
-        };
+        let local_echo_listener_handle =
+            if is_live { Some(spawn_local_echo_handler(inner.clone(), &room).await) } else { None };
 
         // Not using room.add_event_handler here because RoomKey events are
         // to-device events that are not received in the context of a room.
@@ -406,15 +472,129 @@ impl TimelineBuilder {
             })
         };
 
+        // SC: Keep the `is_pinned` and `is_room_encrypted` flags of the timeline
+        // items, and the muted users if they're collapsed, in sync with the
+        // room's info.
+        let room_info_join_handle = {
+            let inner = inner.clone();
+            let mut room_info = room.subscribe_info();
+
+            let mut pinned_event_ids = room_info.get().pinned_event_ids();
+            inner.update_pinned_events(&pinned_event_ids).await;
+
+            let mut is_room_encrypted = room_info.get().is_encrypted();
+
+            let mut muted_users = room_info.get().locally_muted_users().clone();
+            if collapse_muted_users {
+                inner.set_muted_senders(muted_users.clone()).await;
+            }
+
+            spawn(async move {
+                while let Some(info) = room_info.next().await {
+                    let new_pinned_event_ids = info.pinned_event_ids();
+                    if new_pinned_event_ids != pinned_event_ids {
+                        inner.update_pinned_events(&new_pinned_event_ids).await;
+                        pinned_event_ids = new_pinned_event_ids;
+                    }
+
+                    if info.is_encrypted() != is_room_encrypted {
+                        is_room_encrypted = info.is_encrypted();
+                        inner.update_room_encrypted(is_room_encrypted).await;
+                    }
+
+                    if collapse_muted_users && *info.locally_muted_users() != muted_users {
+                        muted_users = info.locally_muted_users().clone();
+                        inner.set_muted_senders(muted_users.clone()).await;
+                    }
+                }
+            })
+        };
+
+        // SC: Recompute the shields of the timeline items when the devices or
+        // identities of their senders change, e.g. after they were verified.
+        #[cfg(feature = "e2e-encryption")]
+        let verification_states_join_handle = {
+            let inner = inner.clone();
+            let encryption = client.encryption();
+            let identity_updates = encryption.user_identities_stream().await;
+            let device_updates = encryption.devices_stream().await;
+
+            spawn(async move {
+                let (Ok(identity_updates), Ok(device_updates)) = (identity_updates, device_updates)
+                else {
+                    warn!("Failed to listen to the identity and device updates");
+                    return;
+                };
+
+                let identity_updates = identity_updates.map(|updates| {
+                    updates
+                        .new
+                        .into_keys()
+                        .chain(updates.changed.into_keys())
+                        .collect::<BTreeSet<_>>()
+                });
+                let device_updates = device_updates.map(|updates| {
+                    updates
+                        .new
+                        .into_keys()
+                        .chain(updates.changed.into_keys())
+                        .collect::<BTreeSet<_>>()
+                });
+                let user_ids = futures_util::stream::select(identity_updates, device_updates);
+                pin_mut!(user_ids);
+
+                while let Some(user_ids) = user_ids.next().await {
+                    inner.update_verification_states(Some(&user_ids)).await;
+                }
+            })
+        };
+
+        // SC: Remove or restore the events of the users when they're ignored or
+        // unignored.
+        let ignore_user_list_join_handle = {
+            let inner = inner.clone();
+            let room_event_cache = room_event_cache.clone();
+
+            spawn(async move {
+                while let Some(user_ids) = ignore_user_list.next().await {
+                    let has_unignored_users =
+                        inner.set_ignored_users(ignored_user_ids(&user_ids)).await;
+
+                    // The items of the unignored users can only be restored by reloading the
+                    // events from the event cache, which only holds the events of live timelines.
+                    if has_unignored_users && inner.is_live().await {
+                        match room_event_cache.subscribe().await {
+                            Ok((events, _)) => {
+                                inner
+                                    .replace_with_initial_remote_events(
+                                        events,
+                                        RemoteEventOrigin::Sync,
+                                    )
+                                    .await;
+                            }
+                            Err(err) => {
+                                warn!("Failed to restore the events of unignored users: {err}");
+                            }
+                        }
+                    }
+                }
+            })
+        };
+
         let timeline = Timeline {
             inner,
             event_cache: room_event_cache,
+            visible_items: Default::default(),
             drop_handle: Arc::new(TimelineDropHandle {
                 client,
                 event_handler_handles: handles,
                 room_update_join_handle,
                 room_key_from_backups_join_handle,
-                local_echo_listener_handle,
+                local_echo_listener_handle: std::sync::Mutex::new(local_echo_listener_handle),
+                room_info_join_handle,
+                #[cfg(feature = "e2e-encryption")]
+                verification_states_join_handle,
+                ignore_user_list_join_handle,
                 _event_cache_drop_handle: event_cache_drop,
             }),
         };
@@ -430,3 +610,120 @@ impl TimelineBuilder {
         Ok(timeline)
     }
 }
+
+/// SC: Spawn the task adding the local echoes of the room's send queue to the
+/// timeline, for a timeline receiving the live events of the room.
+pub(super) async fn spawn_local_echo_handler(
+    timeline: TimelineInner,
+    room: &Room,
+) -> JoinHandle<()> {
+    spawn({
+        let (local_echoes, mut listener) = room.send_queue().subscribe().await;
+
+        // Handles existing local echoes first.
+        for echo in local_echoes {
+            timeline
+                .handle_local_event(
+                    echo.transaction_id,
+                    TimelineEventKind::Message {
+                        content: echo.content,
+                        relations: Default::default(),
+                    },
+                    Some(echo.abort_handle),
+                )
+                .await;
+        }
+
+        let span =
+            info_span!(parent: Span::none(), "local_echo_handler", room_id = ?room.room_id());
+        span.follows_from(Span::current());
+
+        // React to future local echoes too.
+        async move {
+            info!("spawned the local echo handler!");
+
+            loop {
+                match listener.recv().await {
+                    Ok(update) => match update {
+                        RoomSendQueueUpdate::NewLocalEvent(LocalEcho {
+                            transaction_id,
+                            content,
+                            abort_handle,
+                        }) => {
+                            timeline
+                                .handle_local_event(
+                                    transaction_id,
+                                    TimelineEventKind::Message {
+                                        content,
+                                        relations: Default::default(),
+                                    },
+                                    Some(abort_handle),
+                                )
+                                .await;
+                        }
+
+                        RoomSendQueueUpdate::CancelledLocalEvent { transaction_id } => {
+                            if !timeline.discard_local_echo(&transaction_id).await {
+                                warn!("couldn't find the local echo to discard");
+                            }
+                        }
+
+                        RoomSendQueueUpdate::SendError {
+                            transaction_id,
+                            error,
+                            is_recoverable,
+                            retry_at,
+                        } => {
+                            let send_error = SendError::from(&*error);
+                            timeline
+                                .update_event_send_state(
+                                    &transaction_id,
+                                    EventSendState::SendingFailed {
+                                        error,
+                                        send_error,
+                                        is_recoverable,
+                                        retry_at,
+                                    },
+                                )
+                                .await;
+                        }
+
+                        RoomSendQueueUpdate::SentEvent { transaction_id, event_id } => {
+                            timeline
+                                .update_event_send_state(
+                                    &transaction_id,
+                                    EventSendState::Sent { event_id },
+                                )
+                                .await;
+                        }
+                    },
+
+                    Err(RecvError::Lagged(num_missed)) => {
+                        warn!("missed {num_missed} local echoes, ignoring those missed");
+                    }
+
+                    Err(RecvError::Closed) => {
+                        info!("channel closed, exiting the local echo handler");
+                        break;
+                    }
+                }
+            }
+        }
+        .instrument(span)
+    })
+}
+
+/// SC: The user ID of the state key of the event, if it's a member event.
+fn member_state_key(event: &Raw<AnySyncTimelineEvent>) -> Option<OwnedUserId> {
+    let event_type = event.get_field::<TimelineEventType>("type").ok().flatten()?;
+    if event_type != TimelineEventType::RoomMember {
+        return None;
+    }
+
+    event.get_field::<OwnedUserId>("state_key").ok().flatten()
+}
+
+/// Parse the user IDs of the ignored user list, skipping the invalid ones.
+fn ignored_user_ids(user_ids: &[String]) -> BTreeSet<OwnedUserId> {
+    user_ids.iter().filter_map(|user_id| UserId::parse(user_id).ok()).collect()
+}
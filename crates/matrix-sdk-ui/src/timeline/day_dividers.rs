@@ -17,15 +17,30 @@
 
 use std::{fmt::Display, sync::Arc};
 
+use chrono::Weekday;
 use eyeball_im::ObservableVectorTransaction;
 use ruma::MilliSecondsSinceUnixEpoch;
 use tracing::{error, event_enabled, instrument, trace, warn, Level};
 
 use super::{
-    inner::TimelineInnerMetadata, util::timestamp_to_date, TimelineItem, TimelineItemKind,
-    VirtualTimelineItem,
+    inner::TimelineInnerMetadata,
+    util::{timestamp_to_period, Date},
+    TimelineItem, TimelineItemKind, VirtualTimelineItem,
 };
 
+/// SC: The period of time grouped under a single
+/// [`VirtualTimelineItem::DayDivider`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DividerGranularity {
+    /// A divider is inserted between the events of different days.
+    #[default]
+    Day,
+    /// A divider is inserted between the events of different weeks.
+    Week,
+    /// A divider is inserted between the events of different months.
+    Month,
+}
+
 /// Algorithm ensuring that day dividers are adjusted correctly, according to
 /// new items that have been inserted.
 pub(super) struct DayDividerAdjuster {
@@ -36,6 +51,13 @@ pub(super) struct DayDividerAdjuster {
     /// A boolean indicating whether the struct has been used and thus must be
     /// mark unused manually by calling [`Self::run`].
     consumed: bool,
+
+    /// SC: The period grouped under a divider, copied from the metadata in
+    /// [`Self::run`].
+    granularity: DividerGranularity,
+
+    /// SC: The first day of the week, when grouping by week.
+    first_day_of_week: Weekday,
 }
 
 impl Drop for DayDividerAdjuster {
@@ -54,6 +76,8 @@ impl Default for DayDividerAdjuster {
             // The adjuster starts as consumed, and it will be marked no consumed iff it's used
             // with `mark_used`.
             consumed: true,
+            granularity: DividerGranularity::Day,
+            first_day_of_week: Weekday::Mon,
         }
     }
 }
@@ -86,6 +110,16 @@ impl DayDividerAdjuster {
         items: &mut ObservableVectorTransaction<'_, Arc<TimelineItem>>,
         meta: &mut TimelineInnerMetadata,
     ) {
+        if !meta.date_dividers {
+            // SC: Day dividers are disabled for this timeline, so there are none to
+            // adjust.
+            self.consumed = true;
+            return;
+        }
+
+        self.granularity = meta.divider_granularity;
+        self.first_day_of_week = meta.first_day_of_week;
+
         // We're going to record vector operations like inserting, replacing and
         // removing day dividers. Since we may remove or insert new items,
         // recorded offsets will change as we're iterating over the array. The
@@ -130,7 +164,11 @@ impl DayDividerAdjuster {
                     latest_event_ts = Some(ts);
                 }
 
-                TimelineItemKind::Virtual(VirtualTimelineItem::ReadMarker) => {
+                TimelineItemKind::Virtual(
+                    VirtualTimelineItem::ReadMarker
+                    | VirtualTimelineItem::StateGroup { .. }
+                    | VirtualTimelineItem::HiddenMessages { .. },
+                ) => {
                     // Nothing to do.
                 }
             }
@@ -184,6 +222,21 @@ impl DayDividerAdjuster {
         self.consumed = true;
     }
 
+    /// SC: The first date of the period of the given timestamp.
+    fn period(&self, ts: MilliSecondsSinceUnixEpoch) -> Date {
+        timestamp_to_period(ts, self.granularity, self.first_day_of_week)
+    }
+
+    /// Returns whether the two timestamps are in the same period or not.
+    #[inline]
+    fn is_same_period(
+        &self,
+        lhs: MilliSecondsSinceUnixEpoch,
+        rhs: MilliSecondsSinceUnixEpoch,
+    ) -> bool {
+        self.period(lhs) == self.period(rhs)
+    }
+
     /// Decides what to do with a day divider.
     ///
     /// Returns whether it's been removed or not.
@@ -203,7 +256,7 @@ impl DayDividerAdjuster {
         match prev_item.kind() {
             TimelineItemKind::Event(event) => {
                 // This day divider is preceded by an event.
-                if is_same_date_as(event.timestamp(), ts) {
+                if self.is_same_period(event.timestamp(), ts) {
                     // The event has the same date as the day divider: remove the current day
                     // divider.
                     trace!("removing day divider following event with same timestamp @ {i}");
@@ -219,8 +272,13 @@ impl DayDividerAdjuster {
                 return true;
             }
 
-            TimelineItemKind::Virtual(VirtualTimelineItem::ReadMarker) => {
-                // Nothing to do for read markers.
+            TimelineItemKind::Virtual(
+                VirtualTimelineItem::ReadMarker
+                | VirtualTimelineItem::StateGroup { .. }
+                | VirtualTimelineItem::HiddenMessages { .. },
+            ) => {
+                // Nothing to do for read markers, state groups and hidden
+                // messages.
             }
         }
 
@@ -249,21 +307,21 @@ impl DayDividerAdjuster {
                 // insert a day divider.
                 let prev_ts = prev_event.timestamp();
 
-                if !is_same_date_as(prev_ts, ts) {
+                if !self.is_same_period(prev_ts, ts) {
                     trace!("inserting day divider @ {} between two events with different dates", i);
                     self.ops.push(DayDividerOperation::Insert(i, ts));
                 }
             }
 
             TimelineItemKind::Virtual(VirtualTimelineItem::DayDivider(prev_ts)) => {
-                let event_date = timestamp_to_date(ts);
+                let event_date = self.period(ts);
 
                 // The event is preceded by a day divider.
-                if timestamp_to_date(*prev_ts) != event_date {
+                if self.period(*prev_ts) != event_date {
                     // The day divider is wrong. Should we replace it with the correct value, or
                     // remove it entirely?
                     if let Some(last_event_ts) = latest_event_ts {
-                        if timestamp_to_date(last_event_ts) == event_date {
+                        if self.period(last_event_ts) == event_date {
                             // There's a previous event with the same date: remove the divider.
                             trace!("removed day divider @ {item_index} between two events that have the same date");
                             self.ops.insert(insert_op_at, DayDividerOperation::Remove(item_index));
@@ -278,7 +336,11 @@ impl DayDividerAdjuster {
                 }
             }
 
-            TimelineItemKind::Virtual(VirtualTimelineItem::ReadMarker) => {
+            TimelineItemKind::Virtual(
+                VirtualTimelineItem::ReadMarker
+                | VirtualTimelineItem::StateGroup { .. }
+                | VirtualTimelineItem::HiddenMessages { .. },
+            ) => {
                 // Nothing to do.
             }
         }
@@ -423,7 +485,7 @@ impl DayDividerAdjuster {
 
                     // We have the same date as the previous event we've seen.
                     if let Some(prev_ts) = prev_event_ts {
-                        if !is_same_date_as(prev_ts, ts) {
+                        if !self.is_same_period(prev_ts, ts) {
                             report.errors.push(
                                 DayDividerInsertError::MissingDayDividerBetweenEvents { at: i },
                             );
@@ -432,7 +494,7 @@ impl DayDividerAdjuster {
 
                     // There is a day divider before us, and it's the same date as our timestamp.
                     if let Some(prev_ts) = prev_day_divider_ts {
-                        if !is_same_date_as(prev_ts, ts) {
+                        if !self.is_same_period(prev_ts, ts) {
                             report.errors.push(
                                 DayDividerInsertError::InconsistentDateAfterPreviousDayDivider {
                                     at: i,
@@ -451,7 +513,7 @@ impl DayDividerAdjuster {
                 {
                     // The previous day divider is for a different date.
                     if let Some(prev_ts) = prev_day_divider_ts {
-                        if is_same_date_as(prev_ts, *ts) {
+                        if self.is_same_period(prev_ts, *ts) {
                             report
                                 .errors
                                 .push(DayDividerInsertError::DuplicateDayDivider { at: i });
@@ -499,12 +561,6 @@ impl DayDividerOperation {
     }
 }
 
-/// Returns whether the two dates for the given timestamps are the same or not.
-#[inline]
-fn is_same_date_as(lhs: MilliSecondsSinceUnixEpoch, rhs: MilliSecondsSinceUnixEpoch) -> bool {
-    timestamp_to_date(lhs) == timestamp_to_date(rhs)
-}
-
 /// A report returned by [`DayDividerAdjuster::check_invariants`].
 struct DayDividerInvariantsReport<'a, 'o> {
     /// Initial state before inserting the items.
@@ -624,12 +680,17 @@ mod tests {
             event_id: owned_event_id!("$1"),
             reactions: Default::default(),
             read_receipts: Default::default(),
+            read_by_others: false,
             is_own: false,
             is_highlighted: false,
+            is_pinned: false,
+            is_room_encrypted: false,
+            strict_shields: false,
             encryption_info: None,
             original_json: None,
             latest_edit_json: None,
             origin: crate::timeline::event_item::RemoteEventOrigin::Sync,
+            age_derived_ts: None,
         });
         EventTimelineItem::new(
             owned_user_id!("@alice:example.org"),
@@ -645,7 +706,8 @@ mod tests {
         let mut items = ObservableVector::new();
         let mut txn = items.transaction();
 
-        let mut meta = TimelineInnerMetadata::new(ruma::RoomVersionId::V11, None, None);
+        let mut meta =
+            TimelineInnerMetadata::new(ruma::RoomVersionId::V11, None, None, &Default::default());
 
         let timestamp = MilliSecondsSinceUnixEpoch(uint!(42));
         let timestamp_next_day =
@@ -679,7 +741,8 @@ mod tests {
         let mut items = ObservableVector::new();
         let mut txn = items.transaction();
 
-        let mut meta = TimelineInnerMetadata::new(ruma::RoomVersionId::V11, None, None);
+        let mut meta =
+            TimelineInnerMetadata::new(ruma::RoomVersionId::V11, None, None, &Default::default());
 
         let timestamp = MilliSecondsSinceUnixEpoch(uint!(42));
         let timestamp_next_day =
@@ -711,7 +774,8 @@ mod tests {
         let mut items = ObservableVector::new();
         let mut txn = items.transaction();
 
-        let mut meta = TimelineInnerMetadata::new(ruma::RoomVersionId::V11, None, None);
+        let mut meta =
+            TimelineInnerMetadata::new(ruma::RoomVersionId::V11, None, None, &Default::default());
 
         let timestamp = MilliSecondsSinceUnixEpoch(uint!(42));
         let timestamp_next_day =
@@ -745,7 +809,8 @@ mod tests {
         let mut items = ObservableVector::new();
         let mut txn = items.transaction();
 
-        let mut meta = TimelineInnerMetadata::new(ruma::RoomVersionId::V11, None, None);
+        let mut meta =
+            TimelineInnerMetadata::new(ruma::RoomVersionId::V11, None, None, &Default::default());
 
         let timestamp = MilliSecondsSinceUnixEpoch(uint!(42));
         let timestamp_next_day =
@@ -775,7 +840,8 @@ mod tests {
         let mut items = ObservableVector::new();
         let mut txn = items.transaction();
 
-        let mut meta = TimelineInnerMetadata::new(ruma::RoomVersionId::V11, None, None);
+        let mut meta =
+            TimelineInnerMetadata::new(ruma::RoomVersionId::V11, None, None, &Default::default());
 
         let timestamp = MilliSecondsSinceUnixEpoch(uint!(42));
 
@@ -801,7 +867,8 @@ mod tests {
         let mut items = ObservableVector::new();
         let mut txn = items.transaction();
 
-        let mut meta = TimelineInnerMetadata::new(ruma::RoomVersionId::V11, None, None);
+        let mut meta =
+            TimelineInnerMetadata::new(ruma::RoomVersionId::V11, None, None, &Default::default());
 
         let timestamp = MilliSecondsSinceUnixEpoch(uint!(42));
 
@@ -825,7 +892,8 @@ mod tests {
         let mut items = ObservableVector::new();
         let mut txn = items.transaction();
 
-        let mut meta = TimelineInnerMetadata::new(ruma::RoomVersionId::V11, None, None);
+        let mut meta =
+            TimelineInnerMetadata::new(ruma::RoomVersionId::V11, None, None, &Default::default());
 
         let timestamp = MilliSecondsSinceUnixEpoch(uint!(42));
 
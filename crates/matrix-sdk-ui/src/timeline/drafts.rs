@@ -0,0 +1,112 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: Restoring the composer draft of a room, including in-progress edits.
+
+use matrix_sdk_base::store::{ComposerDraft, ComposerDraftType};
+use ruma::{events::AnyTimelineEvent, EventId};
+use tracing::{instrument, warn};
+
+use super::{Error, EventTimelineItem, TimelineItemContent};
+
+/// SC: The composer draft of a room, as restored by
+/// [`Timeline::restore_composer_draft`](super::Timeline::restore_composer_draft).
+#[derive(Clone, Debug)]
+pub enum RestoredComposerDraft {
+    /// A draft for a new message, or for a reply.
+    Draft(ComposerDraft),
+
+    /// A draft for an edit: the composer should re-enter the edit mode for
+    /// the edited item.
+    Edit {
+        /// The draft of the new content of the edited event.
+        draft: ComposerDraft,
+
+        /// The edited item, if it's loaded in the timeline.
+        item: Option<EventTimelineItem>,
+    },
+
+    /// A draft for an edit of an event that has been redacted in the meantime.
+    ///
+    /// The draft has been discarded from the store, it's only returned so the
+    /// user can be notified.
+    EditTargetRedacted(ComposerDraft),
+}
+
+impl super::Timeline {
+    /// SC: Restore the composer draft of the room, as saved with
+    /// [`matrix_sdk::Room::save_composer_draft`].
+    ///
+    /// If the draft is an edit whose target event has been redacted while the
+    /// draft was saved, the draft is discarded, see
+    /// [`RestoredComposerDraft::EditTargetRedacted`].
+    #[instrument(skip_all, fields(room_id = ?self.room().room_id()))]
+    pub async fn restore_composer_draft(&self) -> Result<Option<RestoredComposerDraft>, Error> {
+        let room = self.room();
+
+        let Some(draft) =
+            room.load_composer_draft().await.map_err(Error::FailedToRestoreComposerDraft)?
+        else {
+            return Ok(None);
+        };
+
+        let ComposerDraftType::Edit { event_id } = &draft.draft_type else {
+            return Ok(Some(RestoredComposerDraft::Draft(draft)));
+        };
+
+        let Ok(event_id) = EventId::parse(event_id) else {
+            warn!("Discarding an edit draft with an invalid event ID");
+            room.clear_composer_draft().await.map_err(Error::FailedToRestoreComposerDraft)?;
+            return Ok(None);
+        };
+
+        let item = self.item_by_event_id(&event_id).await;
+
+        let is_redacted = match &item {
+            Some(item) => matches!(item.content(), TimelineItemContent::RedactedMessage),
+            None => self.is_event_redacted(&event_id).await,
+        };
+
+        if is_redacted {
+            room.clear_composer_draft().await.map_err(Error::FailedToRestoreComposerDraft)?;
+            return Ok(Some(RestoredComposerDraft::EditTargetRedacted(draft)));
+        }
+
+        Ok(Some(RestoredComposerDraft::Edit { draft, item }))
+    }
+
+    /// Whether the event `event_id`, which isn't loaded in the timeline, has
+    /// been redacted.
+    ///
+    /// An event that can't be fetched is considered not redacted, so that the
+    /// draft of its edit isn't lost because of a network error.
+    async fn is_event_redacted(&self, event_id: &EventId) -> bool {
+        let event = match self.room().event(event_id).await {
+            Ok(event) => event,
+            Err(error) => {
+                warn!(?error, "Failed to fetch the target of an edit draft");
+                return false;
+            }
+        };
+
+        match event.event.deserialize() {
+            Ok(AnyTimelineEvent::MessageLike(event)) => event.original_content().is_none(),
+            Ok(_) => false,
+            Err(error) => {
+                warn!(?error, "Failed to deserialize the target of an edit draft");
+                false
+            }
+        }
+    }
+}
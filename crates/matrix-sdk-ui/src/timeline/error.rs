@@ -68,6 +68,27 @@ pub enum Error {
     /// An error happened during pagination.
     #[error("An error happened during pagination.")]
     PaginationError(#[from] PaginationError),
+
+    /// SC: A pinned event couldn't be fetched from the server.
+    #[error("Failed fetching a pinned event.")]
+    FailedToFetchPinnedEvent(#[source] matrix_sdk::Error),
+
+    /// SC: The pinned events of the room couldn't be updated.
+    #[error("Failed updating the pinned events.")]
+    FailedToUpdatePinnedEvents(#[source] matrix_sdk::Error),
+
+    /// SC: The timeline isn't detached, see
+    /// [`TimelineFocus::Detached`](super::TimelineFocus::Detached).
+    #[error("The timeline isn't detached")]
+    NotDetached,
+
+    /// SC: The composer draft of the room couldn't be restored.
+    #[error("Failed restoring the composer draft.")]
+    FailedToRestoreComposerDraft(#[source] matrix_sdk::Error),
+
+    /// SC: The composer draft of the room couldn't be cleared.
+    #[error("Failed clearing the composer draft.")]
+    FailedToClearComposerDraft(#[source] matrix_sdk::Error),
 }
 
 #[derive(Error, Debug)]
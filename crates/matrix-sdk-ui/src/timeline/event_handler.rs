@@ -23,6 +23,8 @@ use matrix_sdk::{
 };
 use ruma::{
     events::{
+        beacon::BeaconEventContent,
+        beacon_info::BeaconInfoEventContent,
         poll::{
             unstable_end::UnstablePollEndEventContent,
             unstable_response::UnstablePollResponseEventContent,
@@ -37,6 +39,7 @@ use ruma::{
         room::{
             member::RoomMemberEventContent,
             message::{self, RoomMessageEventContent, RoomMessageEventContentWithoutRelation},
+            pinned_events::RoomPinnedEventsEventContent,
         },
         AnyMessageLikeEventContent, AnySyncMessageLikeEvent, AnySyncStateEvent,
         AnySyncTimelineEvent, BundledMessageLikeRelations, EventContent, FullStateEventContent,
@@ -52,15 +55,18 @@ use tracing::{debug, error, field::debug, info, instrument, trace, warn};
 use super::{
     day_dividers::DayDividerAdjuster,
     event_item::{
-        AnyOtherFullStateEventContent, BundledReactions, EventItemIdentifier, EventSendState,
-        EventTimelineItemKind, LocalEventTimelineItem, Profile, RemoteEventOrigin,
+        age_derived_ts, AnyOtherFullStateEventContent, BundledReactions, EventItemIdentifier,
+        EventSendState, EventTimelineItemKind, LocalEventTimelineItem, Profile, RemoteEventOrigin,
         RemoteEventTimelineItem,
     },
     inner::{TimelineInnerMetadata, TimelineInnerStateTransaction},
+    location::LiveLocationState,
+    pending_edits::PreviousContent,
     polls::PollState,
+    read_receipts::{is_read_by_others_from, mark_own_items_read_by_others},
     util::{rfind_event_by_id, rfind_event_item},
-    EventTimelineItem, InReplyToDetails, Message, OtherState, ReactionGroup, ReactionSenderData,
-    Sticker, TimelineDetails, TimelineItem, TimelineItemContent,
+    EventTimelineItem, InReplyToDetails, Message, OtherState, PinnedEventsChange, ReactionGroup,
+    ReactionSenderData, Sticker, TimelineDetails, TimelineItem, TimelineItemContent,
 };
 use crate::{events::SyncTimelineEventWithoutContent, DEFAULT_SANITIZER_MODE};
 
@@ -102,6 +108,9 @@ pub(super) struct TimelineEventContext {
     pub(super) encryption_info: Option<EncryptionInfo>,
     pub(super) read_receipts: IndexMap<OwnedUserId, Receipt>,
     pub(super) is_highlighted: bool,
+    pub(super) is_pinned: bool,
+    pub(super) is_room_encrypted: bool,
+    pub(super) strict_shields: bool,
     pub(super) flow: Flow,
 }
 
@@ -132,7 +141,15 @@ pub(super) enum TimelineEventKind {
         sender: OwnedUserId,
     },
 
-    /// A state update that's not a [`Self::RoomMember`] event.
+    /// SC: An `m.beacon_info` event, starting or stopping a live location
+    /// share.
+    BeaconInfo { content: BeaconInfoEventContent },
+
+    /// SC: A timeline event for an update of the pinned events of the room.
+    RoomPinnedEvents { content: FullStateEventContent<RoomPinnedEventsEventContent> },
+
+    /// A state update that's not a [`Self::RoomMember`], [`Self::BeaconInfo`]
+    /// or [`Self::RoomPinnedEvents`] event.
     OtherState { state_key: String, content: AnyOtherFullStateEventContent },
 
     /// If the timeline is configured to display events that failed to parse, a
@@ -180,6 +197,20 @@ impl TimelineEventKind {
                         sender: ev.sender,
                     },
                 },
+                AnySyncStateEvent::RoomPinnedEvents(ev) => match ev {
+                    SyncStateEvent::Original(ev) => Self::RoomPinnedEvents {
+                        content: FullStateEventContent::Original {
+                            content: ev.content,
+                            prev_content: ev.unsigned.prev_content,
+                        },
+                    },
+                    SyncStateEvent::Redacted(ev) => Self::RoomPinnedEvents {
+                        content: FullStateEventContent::Redacted(ev.content),
+                    },
+                },
+                AnySyncStateEvent::BeaconInfo(SyncStateEvent::Original(ev)) => {
+                    Self::BeaconInfo { content: ev.content }
+                }
                 ev => Self::OtherState {
                     state_key: ev.state_key().to_owned(),
                     content: AnyOtherFullStateEventContent::with_event_content(ev.content()),
@@ -370,6 +401,10 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
                             hook.on_utd(event_id, cause).await;
                         }
                     }
+
+                    if let Flow::Remote { event_id, .. } = &self.ctx.flow {
+                        self.meta.late_decryption.mark_utd(event_id);
+                    }
                 }
                 AnyMessageLikeEventContent::Sticker(content) => {
                     if should_add {
@@ -384,6 +419,7 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
                 ) => self.handle_poll_start(c, should_add),
                 AnyMessageLikeEventContent::UnstablePollResponse(c) => self.handle_poll_response(c),
                 AnyMessageLikeEventContent::UnstablePollEnd(c) => self.handle_poll_end(c),
+                AnyMessageLikeEventContent::Beacon(c) => self.handle_beacon(c),
                 AnyMessageLikeEventContent::CallInvite(_) => {
                     if should_add {
                         self.add_item(TimelineItemContent::CallInvite);
@@ -423,6 +459,18 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
                 }
             }
 
+            TimelineEventKind::BeaconInfo { content } => {
+                self.handle_beacon_info(content, should_add);
+            }
+
+            TimelineEventKind::RoomPinnedEvents { content } => {
+                if should_add {
+                    self.add_item(TimelineItemContent::PinnedEventsChange(
+                        PinnedEventsChange::new(content),
+                    ));
+                }
+            }
+
             TimelineEventKind::OtherState { state_key, content } => {
                 if should_add {
                     self.add_item(TimelineItemContent::OtherState(OtherState {
@@ -477,6 +525,12 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
         &mut self,
         replacement: Replacement<RoomMessageEventContentWithoutRelation>,
     ) {
+        let mut msgtype = replacement.new_content.msgtype;
+        // Edit's content is never supposed to contain the reply fallback.
+        msgtype.sanitize(DEFAULT_SANITIZER_MODE, RemoveReplyFallback::No);
+        let mentions = replacement.new_content.mentions;
+
+        let mut previous = None;
         let found = self.update_timeline_item(&replacement.event_id, |this, event_item| {
             if this.ctx.sender != event_item.sender() {
                 info!(
@@ -494,16 +548,12 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
                 return None;
             };
 
-            let mut msgtype = replacement.new_content.msgtype;
-            // Edit's content is never supposed to contain the reply fallback.
-            msgtype.sanitize(DEFAULT_SANITIZER_MODE, RemoveReplyFallback::No);
-
             let new_content = TimelineItemContent::Message(Message {
-                msgtype,
+                msgtype: msgtype.clone(),
                 in_reply_to: msg.in_reply_to.clone(),
                 thread_root: msg.thread_root.clone(),
                 edited: true,
-                mentions: replacement.new_content.mentions,
+                mentions: mentions.clone(),
             });
 
             let edit_json = match &this.ctx.flow {
@@ -512,11 +562,48 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
             };
 
             trace!("Applying edit");
+            previous = Some(PreviousContent {
+                content: event_item.content().clone(),
+                latest_edit_json: event_item.latest_edit_json().cloned(),
+            });
             Some(event_item.with_content(new_content, edit_json))
         });
 
-        if !found {
-            debug!("Timeline item not found, discarding edit");
+        self.track_pending_edit(&replacement.event_id, previous);
+
+        if found {
+            return;
+        }
+
+        match &self.ctx.flow {
+            // SC: The edited item isn't in the timeline, e.g. because the edit was
+            // restored from the send queue, so show the edit as its own local echo,
+            // which knows the event it replaces.
+            Flow::Local { .. } => {
+                debug!("Timeline item not found, adding edit as a local echo");
+                let content = TimelineItemContent::Message(Message {
+                    msgtype,
+                    in_reply_to: None,
+                    thread_root: None,
+                    edited: true,
+                    mentions,
+                });
+                self.add_item_replacing(content, Some(replacement.event_id));
+            }
+            Flow::Remote { txn_id: Some(txn_id), .. } => {
+                if let Some((idx, _)) =
+                    rfind_event_item(self.items, |it| it.transaction_id() == Some(&**txn_id))
+                {
+                    debug!("Timeline item not found, removing the local echo of the edit");
+                    self.items.remove(idx);
+                    self.result.item_removed = true;
+                } else {
+                    debug!("Timeline item not found, discarding edit");
+                }
+            }
+            Flow::Remote { txn_id: None, .. } => {
+                debug!("Timeline item not found, discarding edit");
+            }
         }
     }
 
@@ -607,6 +694,7 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
         &mut self,
         replacement: Replacement<NewUnstablePollStartEventContentWithoutRelation>,
     ) {
+        let mut previous = None;
         let found = self.update_timeline_item(&replacement.event_id, |this, event_item| {
             if this.ctx.sender != event_item.sender() {
                 info!(
@@ -638,9 +726,15 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
             };
 
             trace!("Applying edit");
+            previous = Some(PreviousContent {
+                content: event_item.content().clone(),
+                latest_edit_json: event_item.latest_edit_json().cloned(),
+            });
             Some(event_item.with_content(new_content, edit_json))
         });
 
+        self.track_pending_edit(&replacement.event_id, previous);
+
         if !found {
             debug!("Timeline item not found, discarding poll edit");
         }
@@ -665,6 +759,7 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
             Some(event_item.with_content(
                 TimelineItemContent::Poll(poll_state.add_response(
                     &this.ctx.sender,
+                    this.ctx.is_own_event,
                     this.ctx.timestamp,
                     &c,
                 )),
@@ -676,6 +771,7 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
             self.meta.poll_pending_events.add_response(
                 &c.relates_to.event_id,
                 &self.ctx.sender,
+                self.ctx.is_own_event,
                 self.ctx.timestamp,
                 &c,
             );
@@ -701,6 +797,69 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
         }
     }
 
+    fn handle_beacon_info(&mut self, c: BeaconInfoEventContent, should_add: bool) {
+        if !c.live {
+            self.handle_beacon_info_stop();
+            return;
+        }
+
+        let mut state = LiveLocationState::new(&c);
+        if let Flow::Remote { event_id, .. } = self.ctx.flow.clone() {
+            self.meta.live_location_pending_events.apply(&event_id, &self.ctx.sender, &mut state);
+        }
+
+        if should_add {
+            self.add_item(TimelineItemContent::LiveLocation(state));
+        }
+    }
+
+    /// A `beacon_info` that isn't live stops the latest live location share of
+    /// its sender that started before it, it doesn't get its own timeline item.
+    fn handle_beacon_info_stop(&mut self) {
+        let sender = &self.ctx.sender;
+        let timestamp = self.ctx.timestamp;
+        let found = rfind_event_item(self.items, |it| {
+            *it.sender() == **sender
+                && as_variant!(it.content(), TimelineItemContent::LiveLocation).is_some_and(
+                    |state| state.stopped_at().is_none() && state.started_at() <= timestamp,
+                )
+        });
+
+        let Some((idx, item)) = found else {
+            self.meta.live_location_pending_events.add_stop(sender, timestamp);
+            return;
+        };
+
+        let Some(Ok(state)) = as_variant!(item.content(), TimelineItemContent::LiveLocation)
+            .map(|state| state.stop(timestamp))
+        else {
+            return;
+        };
+
+        trace!("Stopping live location share");
+        let new_item = item.with_content(TimelineItemContent::LiveLocation(state), None);
+        self.items.set(idx, TimelineItem::new(new_item, item.internal_id.to_owned()));
+        self.result.items_updated += 1;
+    }
+
+    fn handle_beacon(&mut self, c: BeaconEventContent) {
+        let found = self.update_timeline_item(&c.relates_to.event_id, |_, event_item| {
+            let state = as_variant!(event_item.content(), TimelineItemContent::LiveLocation)?;
+            Some(event_item.with_content(
+                TimelineItemContent::LiveLocation(state.update(&c.location, c.ts)),
+                None,
+            ))
+        });
+
+        if !found {
+            self.meta.live_location_pending_events.add_update(
+                &c.relates_to.event_id,
+                &c.location,
+                c.ts,
+            );
+        }
+    }
+
     /// Looks for the redacted event in all the timeline event items, and
     /// redacts it.
     ///
@@ -867,6 +1026,16 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
 
     /// Add a new event item in the timeline.
     fn add_item(&mut self, content: TimelineItemContent) {
+        self.add_item_replacing(content, None);
+    }
+
+    /// Add a new event item in the timeline, which is the local echo of an
+    /// edit of `replaces_event_id` if it is set.
+    fn add_item_replacing(
+        &mut self,
+        content: TimelineItemContent,
+        replaces_event_id: Option<OwnedEventId>,
+    ) {
         self.result.item_added = true;
 
         let sender = self.ctx.sender.to_owned();
@@ -879,6 +1048,7 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
                 send_state: EventSendState::NotSentYet,
                 transaction_id: txn_id.to_owned(),
                 abort_handle: abort_handle.clone(),
+                replaces_event_id,
             }
             .into(),
 
@@ -905,16 +1075,53 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
                         }),
                 };
 
+                // SC: The age of an event is only meaningful right after it was received
+                // from the server, so keep the estimate of the encrypted event on updates.
+                let age_derived_ts = match *position {
+                    TimelineItemPosition::Start { origin }
+                    | TimelineItemPosition::End { origin } => match origin {
+                        RemoteEventOrigin::Sync | RemoteEventOrigin::Pagination => {
+                            age_derived_ts(raw_event)
+                        }
+                        _ => None,
+                    },
+
+                    #[cfg(feature = "e2e-encryption")]
+                    TimelineItemPosition::Update(idx) => {
+                        self.items[idx].as_event().and_then(|ev| ev.as_remote()?.age_derived_ts)
+                    }
+                };
+
+                // SC: An own event was read by others if it has read receipts, or if a
+                // later event has.
+                let read_by_others = self.ctx.is_own_event
+                    && (!self.ctx.read_receipts.is_empty()
+                        || match *position {
+                            TimelineItemPosition::Start { .. } => {
+                                is_read_by_others_from(self.items, 0)
+                            }
+                            TimelineItemPosition::End { .. } => false,
+                            #[cfg(feature = "e2e-encryption")]
+                            TimelineItemPosition::Update(idx) => {
+                                is_read_by_others_from(self.items, idx)
+                            }
+                        });
+
                 RemoteEventTimelineItem {
                     event_id: event_id.clone(),
                     reactions,
                     read_receipts: self.ctx.read_receipts.clone(),
+                    read_by_others,
                     is_own: self.ctx.is_own_event,
                     is_highlighted: self.ctx.is_highlighted,
+                    is_pinned: self.ctx.is_pinned,
+                    is_room_encrypted: self.ctx.is_room_encrypted,
+                    strict_shields: self.ctx.strict_shields,
                     encryption_info: self.ctx.encryption_info.clone(),
                     original_json: Some(raw_event.clone()),
                     latest_edit_json: None,
                     origin,
+                    age_derived_ts,
                 }
                 .into()
             }
@@ -922,6 +1129,21 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
 
         let mut item = EventTimelineItem::new(sender, sender_profile, timestamp, content, kind);
 
+        // SC: The own events before an event that was read by others were read too.
+        if !self.ctx.read_receipts.is_empty() {
+            match &self.ctx.flow {
+                Flow::Remote { position: TimelineItemPosition::End { .. }, .. } => {
+                    let len = self.items.len();
+                    mark_own_items_read_by_others(self.items, len);
+                }
+                #[cfg(feature = "e2e-encryption")]
+                Flow::Remote { position: TimelineItemPosition::Update(idx), .. } => {
+                    mark_own_items_read_by_others(self.items, *idx);
+                }
+                _ => {}
+            }
+        }
+
         match &self.ctx.flow {
             Flow::Local { .. } => {
                 trace!("Adding new local timeline item");
@@ -983,6 +1205,13 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
 
                     transfer_details(&mut item, &old_item);
 
+                    // SC: Don't forget that a duplicate was already read.
+                    if let (Some(remote), Some(old_remote)) =
+                        (item.as_remote_mut(), old_item.as_remote())
+                    {
+                        remote.read_by_others |= old_remote.read_by_others;
+                    }
+
                     let old_item_id = old_item.internal_id;
 
                     if idx == self.items.len() - 1 {
@@ -1080,6 +1309,29 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
         }
     }
 
+    /// SC: Keep track of the local echo of an edit of the event
+    /// `edited_event_id`, whose content was `previous` if it was applied, so
+    /// it can be cancelled, or forget about it when its remote echo is
+    /// received.
+    fn track_pending_edit(&mut self, edited_event_id: &EventId, previous: Option<PreviousContent>) {
+        match &self.ctx.flow {
+            Flow::Local { txn_id, abort_handle } => {
+                if let Some(previous) = previous {
+                    self.meta.pending_edits.add(
+                        txn_id.clone(),
+                        edited_event_id.to_owned(),
+                        previous,
+                        abort_handle.clone(),
+                    );
+                }
+            }
+            Flow::Remote { txn_id: Some(txn_id), .. } => {
+                self.meta.pending_edits.mark_sent(txn_id);
+            }
+            Flow::Remote { txn_id: None, .. } => {}
+        }
+    }
+
     /// Updates the given timeline item.
     ///
     /// Returns true iff the item has been found (not necessarily updated),
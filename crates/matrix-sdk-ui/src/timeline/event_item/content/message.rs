@@ -14,7 +14,7 @@
 
 //! Timeline item content bits for `m.room.message` events.
 
-use std::{fmt, sync::Arc};
+use std::{fmt, sync::Arc, time::Duration};
 
 use imbl::{vector, Vector};
 use matrix_sdk::{deserialized_responses::TimelineEvent, Room};
@@ -29,15 +29,18 @@ use ruma::{
         AnyMessageLikeEventContent, AnySyncMessageLikeEvent, AnyTimelineEvent,
         BundledMessageLikeRelations, Mentions,
     },
-    html::RemoveReplyFallback,
-    OwnedEventId, OwnedUserId, RoomVersionId, UserId,
+    html::{sanitize_html, HtmlSanitizerMode, RemoveReplyFallback},
+    MxcUri, OwnedEventId, OwnedUserId, RoomVersionId, UserId,
 };
 use tracing::error;
+use url::Url;
 
 use super::TimelineItemContent;
 use crate::{
+    mentions::{formatted_html, resolve_mentions, tag_len, ResolvedMentions},
     timeline::{
         event_item::{EventTimelineItem, Profile, TimelineDetails},
+        location::Location,
         traits::RoomDataProvider,
         Error as TimelineError, TimelineItem,
     },
@@ -129,6 +132,39 @@ impl Message {
         self.msgtype.body()
     }
 
+    /// SC: Get the plain text body of this message, without the reply
+    /// fallback, e.g. for previews or screen readers.
+    ///
+    /// The reply fallback is already removed from the body of messages that
+    /// are known to be replies, this also removes it from replies whose
+    /// relation was lost, e.g. in some bridged messages.
+    pub fn plain_body_without_reply_fallback(&self) -> &str {
+        remove_plain_reply_fallback(self.body())
+    }
+
+    /// SC: Get the HTML body of this message, if any, sanitized with the
+    /// allowlist of the Matrix specification and without the reply fallback.
+    ///
+    /// The `mxc://` sources of images are replaced by their download URL on
+    /// `homeserver`, so the HTML can be rendered as is. Images with an invalid
+    /// source lose it.
+    pub fn formatted_body_sanitized(&self, homeserver: &Url) -> Option<String> {
+        let html = sanitize_html(
+            formatted_html(&self.msgtype)?,
+            HtmlSanitizerMode::Strict,
+            RemoveReplyFallback::Yes,
+        );
+
+        Some(resolve_image_sources(&html, homeserver))
+    }
+
+    /// SC: Get the users and rooms mentioned by this message, from its
+    /// `m.mentions`, and the links of its formatted body, see
+    /// [`resolve_mentions`].
+    pub fn resolved_mentions(&self) -> ResolvedMentions {
+        resolve_mentions(&self.msgtype, self.mentions.as_ref())
+    }
+
     /// Get the event this message is replying to, if any.
     pub fn in_reply_to(&self) -> Option<&InReplyToDetails> {
         self.in_reply_to.as_ref()
@@ -150,6 +186,43 @@ impl Message {
         self.mentions.as_ref()
     }
 
+    /// SC: Whether this is an `m.audio` message recorded as a voice message,
+    /// as marked by the MSC3245 `voice` content block.
+    pub fn is_voice_message(&self) -> bool {
+        matches!(&self.msgtype, MessageType::Audio(content) if content.voice.is_some())
+    }
+
+    /// SC: Get the duration of this `m.audio` message, if known.
+    ///
+    /// The duration of the MSC1767 audio details is preferred, then the one of
+    /// the `info` of the message.
+    pub fn duration(&self) -> Option<Duration> {
+        let MessageType::Audio(content) = &self.msgtype else { return None };
+        content
+            .audio
+            .as_ref()
+            .map(|audio| audio.duration)
+            .or_else(|| content.info.as_ref()?.duration)
+    }
+
+    /// SC: Get the waveform of this `m.audio` message, with amplitudes between
+    /// 0 and 1024.
+    ///
+    /// Returns an empty list if the message doesn't have a waveform, in which
+    /// case it should be rendered as a normal audio file.
+    pub fn waveform(&self) -> Vec<u16> {
+        let MessageType::Audio(content) = &self.msgtype else { return Vec::new() };
+        let Some(audio) = &content.audio else { return Vec::new() };
+        audio.waveform.iter().map(|amplitude| u16::try_from(amplitude.get()).unwrap_or(0)).collect()
+    }
+
+    /// SC: Get the parsed location of this message, if it's an `m.location`
+    /// message with a valid `geo:` URI.
+    pub fn location(&self) -> Option<Location> {
+        let MessageType::Location(content) = &self.msgtype else { return None };
+        Location::from_message_content(content)
+    }
+
     pub(in crate::timeline) fn to_content(&self) -> RoomMessageEventContent {
         // Like the `impl From<Message> for RoomMessageEventContent` below, but
         // takes &self and only copies what's needed.
@@ -165,6 +238,80 @@ impl Message {
     }
 }
 
+/// Remove the reply fallback from the plain text `body`, i.e. the lines quoting
+/// the replied-to message at its start, if any.
+fn remove_plain_reply_fallback(body: &str) -> &str {
+    // The fallback always starts with the quoted sender, e.g. `> <@alice:localhost>`.
+    if !body.starts_with("> <") {
+        return body;
+    }
+
+    let mut rest = body;
+    while rest.starts_with('>') {
+        rest = rest.split_once('\n').map_or("", |(_, rest)| rest);
+    }
+
+    // The fallback is separated from the body by an empty line.
+    rest.strip_prefix('\n').unwrap_or(rest)
+}
+
+/// Replace the `mxc://` sources of the `<img>` tags of the sanitized `html` by
+/// their download URL on `homeserver`.
+fn resolve_image_sources(html: &str, homeserver: &Url) -> String {
+    let mut resolved = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find("<img ") {
+        let (before, tag_and_after) = rest.split_at(start);
+        let (tag, after) = tag_and_after.split_at(tag_len(tag_and_after));
+
+        resolved.push_str(before);
+        resolved.push_str(&resolve_image_source(tag, homeserver));
+        rest = after;
+    }
+
+    resolved.push_str(rest);
+    resolved
+}
+
+/// Replace the `mxc://` source of the `<img>` tag by its download URL on
+/// `homeserver`, or remove it if it's invalid.
+fn resolve_image_source(tag: &str, homeserver: &Url) -> String {
+    const SRC: &str = " src=\"";
+
+    let Some(attr_start) = tag.find(SRC) else {
+        return tag.to_owned();
+    };
+    let value_start = attr_start + SRC.len();
+    let Some(value_len) = tag[value_start..].find('"') else {
+        return tag.to_owned();
+    };
+    let value = &tag[value_start..value_start + value_len];
+    let (before, after) = (&tag[..attr_start], &tag[value_start + value_len + 1..]);
+
+    match download_url(value.into(), homeserver) {
+        Some(url) => format!("{before}{SRC}{url}\"{after}"),
+        None => format!("{before}{after}"),
+    }
+}
+
+/// The URL to download the media `mxc` from `homeserver`.
+fn download_url(mxc: &MxcUri, homeserver: &Url) -> Option<Url> {
+    let (server_name, media_id) = mxc.parts().ok()?;
+
+    let mut url = homeserver.clone();
+    url.path_segments_mut().ok()?.pop_if_empty().extend([
+        "_matrix",
+        "media",
+        "v3",
+        "download",
+        server_name.as_str(),
+        media_id,
+    ]);
+
+    Some(url)
+}
+
 impl From<Message> for RoomMessageEventContent {
     fn from(msg: Message) -> Self {
         let relates_to =
@@ -220,6 +367,9 @@ pub struct InReplyToDetails {
     /// Use [`Timeline::fetch_details_for_event`] to fetch the data if it is
     /// unavailable.
     ///
+    /// SC: If the replied-to event is itself a reply, the details of its own
+    /// replied-to event are not fetched, only its ID is known.
+    ///
     /// [`Timeline::fetch_details_for_event`]: crate::Timeline::fetch_details_for_event
     pub event: TimelineDetails<Box<RepliedToEvent>>,
 }
@@ -263,6 +413,12 @@ impl RepliedToEvent {
         &self.sender_profile
     }
 
+    /// SC: Whether this event was redacted, i.e. the original message was
+    /// removed.
+    pub fn is_redacted(&self) -> bool {
+        self.content.is_redacted()
+    }
+
     pub(crate) fn from_timeline_item(timeline_item: &EventTimelineItem) -> Self {
         Self {
             content: timeline_item.content.clone(),
@@ -299,12 +455,15 @@ impl RepliedToEvent {
             }
         };
 
-        let Some(AnyMessageLikeEventContent::RoomMessage(c)) = event.original_content() else {
-            return Err(TimelineError::UnsupportedEvent);
+        let content = match event.original_content() {
+            Some(AnyMessageLikeEventContent::RoomMessage(c)) => {
+                TimelineItemContent::Message(Message::from_event(c, event.relations(), &vector![]))
+            }
+            Some(_) => return Err(TimelineError::UnsupportedEvent),
+            // The replied-to event was redacted, keep its sender so the reply can
+            // still be rendered.
+            None => TimelineItemContent::RedactedMessage,
         };
-
-        let content =
-            TimelineItemContent::Message(Message::from_event(c, event.relations(), &vector![]));
         let sender = event.sender().to_owned();
         let sender_profile = TimelineDetails::from_initial_value(
             room_data_provider.profile_from_user_id(&sender).await,
@@ -313,3 +472,106 @@ impl RepliedToEvent {
         Ok(Self { content, sender, sender_profile })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ruma::events::room::message::{MessageType, TextMessageEventContent};
+    use serde_json::json;
+    use url::Url;
+
+    use super::{remove_plain_reply_fallback, resolve_image_sources, Message};
+
+    fn message(msgtype: MessageType) -> Message {
+        Message { msgtype, in_reply_to: None, thread_root: None, edited: false, mentions: None }
+    }
+
+    #[test]
+    fn test_remove_plain_reply_fallback() {
+        assert_eq!(
+            remove_plain_reply_fallback("> <@alice:localhost> Hi\n> there\n\nHello!"),
+            "Hello!"
+        );
+
+        // Regular quotes are kept.
+        assert_eq!(remove_plain_reply_fallback("> Hi there\n\nHello!"), "> Hi there\n\nHello!");
+        assert_eq!(remove_plain_reply_fallback("Hello!"), "Hello!");
+    }
+
+    #[test]
+    fn test_resolve_image_sources() {
+        let homeserver = Url::parse("https://matrix.localhost/").unwrap();
+
+        assert_eq!(
+            resolve_image_sources(
+                r#"<p>Look <img alt="a > b" src="mxc://localhost/abc"> there</p>"#,
+                &homeserver
+            ),
+            r#"<p>Look <img alt="a > b" src="https://matrix.localhost/_matrix/media/v3/download/localhost/abc"> there</p>"#
+        );
+
+        // Invalid sources are removed.
+        assert_eq!(
+            resolve_image_sources(r#"<img src="mxc://localhost" alt="x">"#, &homeserver),
+            r#"<img alt="x">"#
+        );
+    }
+
+    #[test]
+    fn test_formatted_body_sanitized() {
+        let homeserver = Url::parse("https://matrix.localhost/").unwrap();
+
+        let msg = message(MessageType::Text(TextMessageEventContent::html(
+            "Hello",
+            "<mx-reply><blockquote>Hi</blockquote></mx-reply>\
+             <p onclick=\"alert()\">Hello <img src=\"mxc://localhost/abc\"></p>",
+        )));
+        assert_eq!(
+            msg.formatted_body_sanitized(&homeserver).unwrap(),
+            "<p>Hello <img src=\"https://matrix.localhost/_matrix/media/v3/download/localhost/abc\"></p>"
+        );
+
+        let plain = message(MessageType::text_plain("Hello"));
+        assert_eq!(plain.formatted_body_sanitized(&homeserver), None);
+    }
+
+    #[test]
+    fn test_voice_message() {
+        let voice = message(
+            serde_json::from_value(json!({
+                "msgtype": "m.audio",
+                "body": "Voice message",
+                "url": "mxc://localhost/voice",
+                "info": { "duration": 4000 },
+                "org.matrix.msc1767.audio": {
+                    "duration": 3500,
+                    "waveform": [0, 512, 1024],
+                },
+                "org.matrix.msc3245.voice": {},
+            }))
+            .unwrap(),
+        );
+        assert!(voice.is_voice_message());
+        assert_eq!(voice.duration(), Some(Duration::from_millis(3500)));
+        assert_eq!(voice.waveform(), vec![0, 512, 1024]);
+
+        // Without the voice extension, it's a normal audio file.
+        let audio = message(
+            serde_json::from_value(json!({
+                "msgtype": "m.audio",
+                "body": "song.ogg",
+                "url": "mxc://localhost/song",
+                "info": { "duration": 4000 },
+            }))
+            .unwrap(),
+        );
+        assert!(!audio.is_voice_message());
+        assert_eq!(audio.duration(), Some(Duration::from_millis(4000)));
+        assert!(audio.waveform().is_empty());
+
+        let text = message(MessageType::text_plain("Hello"));
+        assert!(!text.is_voice_message());
+        assert_eq!(text.duration(), None);
+    }
+}
@@ -51,11 +51,11 @@ use ruma::{
         AnyFullStateEventContent, AnySyncMessageLikeEvent, AnySyncTimelineEvent,
         BundledMessageLikeRelations, FullStateEventContent, MessageLikeEventType, StateEventType,
     },
-    OwnedDeviceId, OwnedMxcUri, OwnedUserId, RoomVersionId, UserId,
+    OwnedDeviceId, OwnedEventId, OwnedMxcUri, OwnedUserId, RoomVersionId, UserId,
 };
 use tracing::warn;
 
-use crate::timeline::{polls::PollState, TimelineItem};
+use crate::timeline::{location::LiveLocationState, polls::PollState, TimelineItem};
 
 mod message;
 
@@ -82,6 +82,9 @@ pub enum TimelineItemContent {
     /// A room member profile change.
     ProfileChange(MemberProfileChange),
 
+    /// SC: A change of the events pinned in the room.
+    PinnedEventsChange(PinnedEventsChange),
+
     /// Another state event.
     OtherState(OtherState),
 
@@ -109,6 +112,10 @@ pub enum TimelineItemContent {
     /// An `m.poll.start` event.
     Poll(PollState),
 
+    /// SC: An `m.beacon_info` event starting a live location share, with the
+    /// latest location and whether it was stopped.
+    LiveLocation(LiveLocationState),
+
     /// An `m.call.invite` event
     CallInvite,
 
@@ -256,10 +263,12 @@ impl TimelineItemContent {
             TimelineItemContent::UnableToDecrypt(_) => "an encrypted message we couldn't decrypt",
             TimelineItemContent::MembershipChange(_) => "a membership change",
             TimelineItemContent::ProfileChange(_) => "a profile change",
+            TimelineItemContent::PinnedEventsChange(_) => "a pinned events change",
             TimelineItemContent::OtherState(_) => "a state event",
             TimelineItemContent::FailedToParseMessageLike { .. }
             | TimelineItemContent::FailedToParseState { .. } => "an event that couldn't be parsed",
             TimelineItemContent::Poll(_) => "a poll",
+            TimelineItemContent::LiveLocation(_) => "a live location share",
             TimelineItemContent::CallInvite => "a call invite",
             TimelineItemContent::CallNotify => "a call notification",
         }
@@ -340,11 +349,13 @@ impl TimelineItemContent {
             | Self::RedactedMessage
             | Self::Sticker(_)
             | Self::Poll(_)
+            | Self::LiveLocation(_)
             | Self::CallInvite
             | Self::CallNotify
             | Self::UnableToDecrypt(_) => Self::RedactedMessage,
             Self::MembershipChange(ev) => Self::MembershipChange(ev.redact(room_version)),
             Self::ProfileChange(ev) => Self::ProfileChange(ev.redact()),
+            Self::PinnedEventsChange(ev) => Self::PinnedEventsChange(ev.redact(room_version)),
             Self::OtherState(ev) => Self::OtherState(ev.redact(room_version)),
             Self::FailedToParseMessageLike { .. } | Self::FailedToParseState { .. } => self.clone(),
         }
@@ -443,6 +454,19 @@ impl RoomMembershipChange {
         self.change
     }
 
+    /// The display name of the user, as set in this event.
+    pub fn display_name(&self) -> Option<String> {
+        as_variant!(&self.content, FullStateEventContent::Original { content, .. } => content)
+            .and_then(|content| content.displayname.clone())
+    }
+
+    /// The reason given for this membership change, e.g. why the user was
+    /// kicked or banned.
+    pub fn reason(&self) -> Option<&str> {
+        as_variant!(&self.content, FullStateEventContent::Original { content, .. } => content)
+            .and_then(|content| content.reason.as_deref())
+    }
+
     fn redact(&self, room_version: &RoomVersionId) -> Self {
         Self {
             user_id: self.user_id.clone(),
@@ -547,6 +571,73 @@ impl MemberProfileChange {
     }
 }
 
+/// SC: An event changing the events pinned in a room.
+///
+/// The pinned and unpinned events are computed by comparing the new list of
+/// pinned events with the previous one. If the previous list is unknown, all
+/// the events of the new list are considered pinned by this event.
+#[derive(Clone, Debug)]
+pub struct PinnedEventsChange {
+    pub(in crate::timeline) content: FullStateEventContent<RoomPinnedEventsEventContent>,
+    pub(in crate::timeline) pinned: Vec<OwnedEventId>,
+    pub(in crate::timeline) unpinned: Vec<OwnedEventId>,
+}
+
+impl PinnedEventsChange {
+    pub(in crate::timeline) fn new(
+        content: FullStateEventContent<RoomPinnedEventsEventContent>,
+    ) -> Self {
+        let (pinned, unpinned) = match &content {
+            FullStateEventContent::Original { content, prev_content } => {
+                let prev_pinned = prev_content
+                    .as_ref()
+                    .and_then(|prev_content| prev_content.pinned.as_deref())
+                    .unwrap_or_default();
+
+                let pinned = content
+                    .pinned
+                    .iter()
+                    .filter(|event_id| !prev_pinned.contains(event_id))
+                    .cloned()
+                    .collect();
+                let unpinned = prev_pinned
+                    .iter()
+                    .filter(|event_id| !content.pinned.contains(event_id))
+                    .cloned()
+                    .collect();
+
+                (pinned, unpinned)
+            }
+            FullStateEventContent::Redacted(_) => (Vec::new(), Vec::new()),
+        };
+
+        Self { content, pinned, unpinned }
+    }
+
+    /// The full content of the event.
+    pub fn content(&self) -> &FullStateEventContent<RoomPinnedEventsEventContent> {
+        &self.content
+    }
+
+    /// The IDs of the events that were pinned by this event.
+    pub fn pinned(&self) -> &[OwnedEventId] {
+        &self.pinned
+    }
+
+    /// The IDs of the events that were unpinned by this event.
+    pub fn unpinned(&self) -> &[OwnedEventId] {
+        &self.unpinned
+    }
+
+    fn redact(&self, room_version: &RoomVersionId) -> Self {
+        Self {
+            content: FullStateEventContent::Redacted(self.content.clone().redact(room_version)),
+            pinned: self.pinned.clone(),
+            unpinned: self.unpinned.clone(),
+        }
+    }
+}
+
 /// An enum over all the full state event contents that don't have their own
 /// `TimelineItemContent` variant.
 #[derive(Clone, Debug)]
@@ -588,6 +679,10 @@ pub enum AnyOtherFullStateEventContent {
     RoomName(FullStateEventContent<RoomNameEventContent>),
 
     /// m.room.pinned_events
+    ///
+    /// SC: The timeline reports these events as
+    /// [`TimelineItemContent::PinnedEventsChange`], this is only kept for
+    /// compatibility.
     RoomPinnedEvents(FullStateEventContent<RoomPinnedEventsEventContent>),
 
     /// m.room.power_levels
@@ -12,11 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use as_variant::as_variant;
-use matrix_sdk::{send_queue::AbortSendHandle, Error};
-use ruma::{EventId, OwnedEventId, OwnedTransactionId};
+use matrix_sdk::{send_queue::AbortSendHandle, Error, HttpError};
+use ruma::{
+    api::client::error::{ErrorKind, RetryAfter},
+    EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedTransactionId,
+};
 
 /// An item for an event that was created locally and not yet echoed back by
 /// the homeserver.
@@ -28,6 +31,9 @@ pub(in crate::timeline) struct LocalEventTimelineItem {
     pub transaction_id: OwnedTransactionId,
     /// A handle to abort sending this event, if possible.
     pub abort_handle: Option<AbortSendHandle>,
+    /// SC: The event this local echo replaces, if it's the echo of an edit of
+    /// an event that isn't in the timeline.
+    pub replaces_event_id: Option<OwnedEventId>,
 }
 
 impl LocalEventTimelineItem {
@@ -55,12 +61,17 @@ pub enum EventSendState {
     SendingFailed {
         /// Details about how sending the event failed.
         error: Arc<Error>,
+        /// SC: The cause of the failure, classified from `error`.
+        send_error: SendError,
         /// Whether the error is considered recoverable or not.
         ///
         /// An error that's recoverable will disable the room's send queue,
         /// while an unrecoverable error will be parked, until the user
         /// decides to cancel sending it.
         is_recoverable: bool,
+        /// SC: When sending the event will be retried automatically, if the
+        /// homeserver rate-limited the user.
+        retry_at: Option<MilliSecondsSinceUnixEpoch>,
     },
     /// The local event has been sent successfully to the server.
     Sent {
@@ -68,3 +79,73 @@ pub enum EventSendState {
         event_id: OwnedEventId,
     },
 }
+
+/// SC: Why sending a local event failed, so the UI can show an actionable
+/// message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SendError {
+    /// The cause of the failure.
+    pub kind: SendErrorKind,
+    /// The HTTP status code of the response of the homeserver, if any.
+    pub status_code: Option<u16>,
+    /// The Matrix error code of the response of the homeserver, like
+    /// `M_FORBIDDEN`, if any.
+    pub errcode: Option<String>,
+}
+
+impl SendError {
+    /// Whether sending the event again later might succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self.kind {
+            SendErrorKind::Network | SendErrorKind::RateLimited { .. } => true,
+            SendErrorKind::Forbidden | SendErrorKind::TooLarge => false,
+            SendErrorKind::Other => self.status_code.is_some_and(|status| status >= 500),
+        }
+    }
+}
+
+impl From<&Error> for SendError {
+    fn from(error: &Error) -> Self {
+        if matches!(error, Error::Http(HttpError::Reqwest(_))) {
+            return Self { kind: SendErrorKind::Network, status_code: None, errcode: None };
+        }
+
+        let status_code = error.as_client_api_error().map(|error| error.status_code.as_u16());
+        let error_kind = error.client_api_error_kind();
+
+        let kind = match (error_kind, status_code) {
+            (Some(ErrorKind::LimitExceeded { retry_after }), _) => {
+                let retry_after = retry_after.as_ref().and_then(|retry_after| match retry_after {
+                    RetryAfter::Delay(delay) => Some(*delay),
+                    RetryAfter::DateTime(_) => None,
+                });
+                SendErrorKind::RateLimited { retry_after }
+            }
+            (_, Some(429)) => SendErrorKind::RateLimited { retry_after: None },
+            (Some(ErrorKind::Forbidden { .. }), _) | (_, Some(403)) => SendErrorKind::Forbidden,
+            (Some(ErrorKind::TooLarge), _) | (_, Some(413)) => SendErrorKind::TooLarge,
+            _ => SendErrorKind::Other,
+        };
+
+        Self { kind, status_code, errcode: error_kind.map(ToString::to_string) }
+    }
+}
+
+/// SC: The cause of a [`SendError`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SendErrorKind {
+    /// The homeserver couldn't be reached.
+    Network,
+    /// The homeserver rate-limited the user.
+    RateLimited {
+        /// How long to wait before sending again, if the homeserver said so.
+        retry_after: Option<Duration>,
+    },
+    /// The user isn't allowed to send this event, e.g. because of the power
+    /// levels of the room.
+    Forbidden,
+    /// The event is too large.
+    TooLarge,
+    /// Any other error.
+    Other,
+}
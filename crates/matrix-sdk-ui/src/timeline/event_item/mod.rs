@@ -16,14 +16,23 @@ use std::sync::Arc;
 
 use as_variant::as_variant;
 use indexmap::IndexMap;
-use matrix_sdk::{deserialized_responses::EncryptionInfo, Client, Error};
+use matrix_sdk::{
+    deserialized_responses::{
+        DeviceLinkProblem, EncryptionInfo, ShieldState, VerificationLevel, VerificationState,
+    },
+    Client, Error, Room,
+};
 use matrix_sdk_base::{deserialized_responses::SyncTimelineEvent, latest_event::LatestEvent};
 use once_cell::sync::Lazy;
 use ruma::{
-    events::{receipt::Receipt, room::message::MessageType, AnySyncTimelineEvent},
+    events::{
+        receipt::Receipt,
+        room::{member::MembershipState, message::MessageType},
+        AnySyncTimelineEvent,
+    },
     serde::Raw,
-    EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedMxcUri, OwnedTransactionId,
-    OwnedUserId, RoomId, RoomVersionId, TransactionId, UserId,
+    EventId, MatrixToUri, MatrixUri, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedMxcUri,
+    OwnedTransactionId, OwnedUserId, RoomId, RoomVersionId, TransactionId, UserId,
 };
 use tracing::warn;
 
@@ -35,17 +44,21 @@ mod remote;
 pub use self::{
     content::{
         AnyOtherFullStateEventContent, EncryptedMessage, InReplyToDetails, MemberProfileChange,
-        MembershipChange, Message, OtherState, RepliedToEvent, RoomMembershipChange, Sticker,
-        TimelineItemContent,
+        MembershipChange, Message, OtherState, PinnedEventsChange, RepliedToEvent,
+        RoomMembershipChange, Sticker, TimelineItemContent,
     },
-    local::EventSendState,
-    reactions::{BundledReactions, ReactionGroup},
+    local::{EventSendState, SendError, SendErrorKind},
+    reactions::{BundledReactions, ReactionGroup, ReactionKey},
 };
 pub(super) use self::{
     local::LocalEventTimelineItem,
-    remote::{RemoteEventOrigin, RemoteEventTimelineItem},
+    remote::{age_derived_ts, RemoteEventOrigin, RemoteEventTimelineItem},
 };
 
+/// SC: The message of the shield of an event sent in the clear in an encrypted
+/// room.
+const SENT_IN_CLEAR: &str = "Not encrypted.";
+
 /// An item in the timeline that represents at least one event.
 ///
 /// There is always one main event that gives the `EventTimelineItem` its
@@ -141,12 +154,19 @@ impl EventTimelineItem {
             event_id,
             reactions,
             read_receipts,
+            read_by_others: false,
             is_own,
             is_highlighted,
+            is_pinned: false,
+            is_room_encrypted: client
+                .get_room(room_id)
+                .is_some_and(|room| room.is_room_encrypted()),
+            strict_shields: false,
             encryption_info,
             original_json: Some(raw_sync_event),
             latest_edit_json,
             origin,
+            age_derived_ts: None,
         }
         .into();
 
@@ -167,6 +187,75 @@ impl EventTimelineItem {
         Some(Self::new(sender, sender_profile, timestamp, item_content, event_kind))
     }
 
+    /// SC: Wrap an event that was fetched outside of the timeline, e.g. a
+    /// pinned event, as an `EventTimelineItem`.
+    ///
+    /// Redacted message-like events are wrapped as
+    /// [`TimelineItemContent::RedactedMessage`]. Returns `None` for events that
+    /// can't be represented on their own.
+    pub(super) async fn from_standalone_event(
+        client: Client,
+        room_id: &RoomId,
+        event: SyncTimelineEvent,
+    ) -> Option<EventTimelineItem> {
+        use super::traits::RoomDataProvider;
+
+        let SyncTimelineEvent { event: raw_sync_event, encryption_info, .. } = event;
+
+        let Ok(event) = raw_sync_event.deserialize() else {
+            warn!("Unable to deserialize standalone event as an AnySyncTimelineEvent!");
+            return None;
+        };
+
+        let timestamp = event.origin_server_ts();
+        let sender = event.sender().to_owned();
+        let event_id = event.event_id().to_owned();
+        let is_own = client.user_id().map(|uid| uid == sender).unwrap_or(false);
+
+        let is_redacted = matches!(
+            &event,
+            AnySyncTimelineEvent::MessageLike(ev) if ev.original_content().is_none()
+        );
+        let item_content = if is_redacted {
+            TimelineItemContent::RedactedMessage
+        } else {
+            TimelineItemContent::from_latest_event_content(event)?
+        };
+
+        let room = client.get_room(room_id);
+        let is_pinned = room.as_ref().is_some_and(|room| room.is_pinned_event(&event_id));
+        let is_room_encrypted = room.as_ref().is_some_and(|room| room.is_room_encrypted());
+
+        let event_kind = RemoteEventTimelineItem {
+            event_id,
+            reactions: IndexMap::new(),
+            read_receipts: IndexMap::new(),
+            read_by_others: false,
+            is_own,
+            is_highlighted: false,
+            is_pinned,
+            is_room_encrypted,
+            strict_shields: false,
+            encryption_info,
+            original_json: Some(raw_sync_event),
+            latest_edit_json: None,
+            origin: RemoteEventOrigin::Pagination,
+            age_derived_ts: None,
+        }
+        .into();
+
+        let sender_profile = match room {
+            Some(room) => room
+                .profile_from_user_id(&sender)
+                .await
+                .map(TimelineDetails::Ready)
+                .unwrap_or(TimelineDetails::Unavailable),
+            None => TimelineDetails::Unavailable,
+        };
+
+        Some(Self::new(sender, sender_profile, timestamp, item_content, event_kind))
+    }
+
     /// Check whether this item is a local echo.
     ///
     /// This returns `true` for events created locally, until the server echoes
@@ -208,6 +297,20 @@ impl EventTimelineItem {
         as_variant!(&self.kind, EventTimelineItemKind::Local(local) => &local.transaction_id)
     }
 
+    /// SC: Get the identifier of this item: its event ID if it's known, its
+    /// transaction ID otherwise.
+    pub fn identifier(&self) -> EventItemIdentifier {
+        match &self.kind {
+            EventTimelineItemKind::Local(local_event) => match local_event.event_id() {
+                Some(event_id) => EventItemIdentifier::EventId(event_id.to_owned()),
+                None => EventItemIdentifier::TransactionId(local_event.transaction_id.clone()),
+            },
+            EventTimelineItemKind::Remote(remote_event) => {
+                EventItemIdentifier::EventId(remote_event.event_id.clone())
+            }
+        }
+    }
+
     /// Get the event ID of this item.
     ///
     /// If this returns `Some(_)`, the event was successfully created by the
@@ -233,11 +336,66 @@ impl EventTimelineItem {
         &self.sender_profile
     }
 
+    /// SC: Get the current membership of the sender in the room, if known.
+    ///
+    /// Unlike the membership at the time of the event, which is always `join`
+    /// for messages, this reflects later changes: the item is updated when the
+    /// sender leaves or is kicked or banned while the timeline is open.
+    pub fn sender_membership_now(&self) -> Option<&MembershipState> {
+        as_variant!(&self.sender_profile, TimelineDetails::Ready(profile) => profile)?
+            .membership
+            .as_ref()
+    }
+
     /// Get the content of this item.
     pub fn content(&self) -> &TimelineItemContent {
         &self.content
     }
 
+    /// SC: Get the details of the event this item replies to, if it is a reply.
+    ///
+    /// The replied-to event is [`TimelineDetails::Unavailable`] if it wasn't
+    /// in the timeline when this item was created, use
+    /// [`Timeline::fetch_details_for_event`] to fetch it. If it was redacted,
+    /// [`RepliedToEvent::is_redacted`] is `true` and only its sender is known.
+    ///
+    /// [`Timeline::fetch_details_for_event`]: super::Timeline::fetch_details_for_event
+    pub fn in_reply_to(&self) -> Option<InReplyToDetails> {
+        self.content.as_message()?.in_reply_to().cloned()
+    }
+
+    /// SC: Get the ID of the event this item replaces, if it's the local echo
+    /// of an edit.
+    ///
+    /// The local echo of an edit is usually applied to the edited item
+    /// directly, so this is only set when the edited item isn't in the
+    /// timeline, e.g. for an edit restored from the send queue. The UI can use
+    /// it to attach the edit to the original event rather than showing it as
+    /// a new message.
+    pub fn replaces_event_id(&self) -> Option<OwnedEventId> {
+        self.as_local()?.replaces_event_id.clone()
+    }
+
+    /// SC: Get a `matrix.to` permalink to this item's event in `room`.
+    ///
+    /// Returns `Ok(None)` if the event ID of this item isn't known yet, i.e.
+    /// it's a local echo that hasn't been sent. See
+    /// [`Room::matrix_to_event_permalink`] for how the room is routed.
+    pub async fn matrix_to_permalink(&self, room: &Room) -> Result<Option<MatrixToUri>, Error> {
+        let Some(event_id) = self.event_id() else { return Ok(None) };
+        Ok(Some(room.matrix_to_event_permalink(event_id).await?))
+    }
+
+    /// SC: Get a `matrix:` permalink to this item's event in `room`.
+    ///
+    /// Returns `Ok(None)` if the event ID of this item isn't known yet, i.e.
+    /// it's a local echo that hasn't been sent. See
+    /// [`Room::matrix_event_permalink`] for how the room is routed.
+    pub async fn matrix_permalink(&self, room: &Room) -> Result<Option<MatrixUri>, Error> {
+        let Some(event_id) = self.event_id() else { return Ok(None) };
+        Ok(Some(room.matrix_event_permalink(event_id).await?))
+    }
+
     /// Get the reactions of this item.
     pub fn reactions(&self) -> &BundledReactions {
         // There's not much of a point in allowing reactions to local echoes.
@@ -271,6 +429,15 @@ impl EventTimelineItem {
         self.timestamp
     }
 
+    /// SC: Get the details of the timestamp of this item, to diagnose events
+    /// whose sender's clock is off.
+    pub fn timestamp_info(&self) -> TimestampInfo {
+        let age_derived_ts = self.as_remote().and_then(|remote| remote.age_derived_ts);
+        let clock_skew_ms = age_derived_ts.map(|ts| i64::from(self.timestamp.0) - i64::from(ts.0));
+
+        TimestampInfo { origin_server_ts: self.timestamp, age_derived_ts, clock_skew_ms }
+    }
+
     /// Whether this timeline item was sent by the logged-in user themselves.
     pub fn is_own(&self) -> bool {
         match &self.kind {
@@ -314,6 +481,31 @@ impl EventTimelineItem {
         }
     }
 
+    /// SC: Get the delivery status of this event, if it was sent by the
+    /// current user.
+    ///
+    /// A remote event is considered read as soon as another user has a read
+    /// receipt on it or on a later event. Returns `None` for the events of
+    /// other users.
+    pub fn own_delivery_status(&self) -> Option<OwnDeliveryStatus> {
+        match &self.kind {
+            EventTimelineItemKind::Local(local) => Some(match local.send_state {
+                EventSendState::NotSentYet => OwnDeliveryStatus::Sending,
+                EventSendState::SendingFailed { .. } => OwnDeliveryStatus::Failed,
+                EventSendState::Sent { .. } => OwnDeliveryStatus::Sent,
+            }),
+            EventTimelineItemKind::Remote(remote_event) => {
+                if !remote_event.is_own {
+                    None
+                } else if remote_event.read_by_others {
+                    Some(OwnDeliveryStatus::Read)
+                } else {
+                    Some(OwnDeliveryStatus::Sent)
+                }
+            }
+        }
+    }
+
     /// Get the encryption information for the event, if any.
     pub fn encryption_info(&self) -> Option<&EncryptionInfo> {
         match &self.kind {
@@ -322,6 +514,83 @@ impl EventTimelineItem {
         }
     }
 
+    /// SC: Why the authenticity of this event can't be guaranteed, if that's
+    /// the case.
+    ///
+    /// This is computed from the current encryption information of the event
+    /// and whether the room is encrypted, so it's updated when the event gets
+    /// decrypted again, when the device or identity of the sender changes, or
+    /// when the room becomes encrypted. Local echoes never have a reason.
+    pub fn shield_reason(&self) -> Option<ShieldReason> {
+        let remote = self.as_remote()?;
+
+        let Some(encryption_info) = &remote.encryption_info else {
+            // State events are never encrypted, and the content of undecryptable or
+            // redacted events is unknown.
+            let is_encryptable = matches!(
+                self.content,
+                TimelineItemContent::Message(_)
+                    | TimelineItemContent::Sticker(_)
+                    | TimelineItemContent::Poll(_)
+                    | TimelineItemContent::CallInvite
+                    | TimelineItemContent::CallNotify
+            );
+            return (remote.is_room_encrypted && is_encryptable)
+                .then_some(ShieldReason::SentInClear);
+        };
+
+        match &encryption_info.verification_state {
+            VerificationState::Verified => None,
+            VerificationState::Unverified(level) => Some(match level {
+                VerificationLevel::UnverifiedIdentity => ShieldReason::UnverifiedDevice,
+                VerificationLevel::UnsignedDevice => ShieldReason::UnsignedDevice,
+                VerificationLevel::None(DeviceLinkProblem::MissingDevice) => {
+                    ShieldReason::UnknownDevice
+                }
+                VerificationLevel::None(DeviceLinkProblem::InsecureSource) => {
+                    ShieldReason::AuthenticityNotGuaranteed
+                }
+            }),
+        }
+    }
+
+    /// SC: The shield to show for this event, in the mode set with
+    /// [`TimelineBuilder::strict_shields`].
+    ///
+    /// [`TimelineBuilder::strict_shields`]: crate::timeline::TimelineBuilder::strict_shields
+    pub fn shield(&self) -> ShieldState {
+        self.get_shield(self.as_remote().is_some_and(|remote| remote.strict_shields))
+    }
+
+    /// SC: The shield to show for this event, in strict or lax mode.
+    ///
+    /// The two modes only differ for encrypted events:
+    ///
+    /// - When the sender's device is signed by its owner, but we haven't
+    ///   verified the sender ([`ShieldReason::UnverifiedDevice`]), the shield
+    ///   is red in strict mode, and there is no shield in lax mode.
+    /// - When the key of the event was obtained from an insecure source
+    ///   ([`ShieldReason::AuthenticityNotGuaranteed`]), the shield is red in
+    ///   strict mode, and grey in lax mode.
+    ///
+    /// In both modes, events sent by unsigned or unknown devices, and events
+    /// sent in the clear in an encrypted room get a red shield, and local
+    /// echoes don't get a shield.
+    pub fn get_shield(&self, strict: bool) -> ShieldState {
+        let Some(remote) = self.as_remote() else {
+            return ShieldState::None;
+        };
+
+        match &remote.encryption_info {
+            Some(info) if strict => info.verification_state.to_shield_state_strict(),
+            Some(info) => info.verification_state.to_shield_state_lax(),
+            None if self.shield_reason() == Some(ShieldReason::SentInClear) => {
+                ShieldState::Red { message: SENT_IN_CLEAR }
+            }
+            None => ShieldState::None,
+        }
+    }
+
     /// Check whether this item can be replied to.
     pub fn can_be_replied_to(&self) -> bool {
         // This must be in sync with the early returns of `Timeline::send_reply`
@@ -334,6 +603,13 @@ impl EventTimelineItem {
         }
     }
 
+    /// SC: Whether this event is pinned in the room.
+    ///
+    /// Local echoes are never pinned.
+    pub fn is_pinned(&self) -> bool {
+        self.as_remote().is_some_and(|remote| remote.is_pinned)
+    }
+
     /// Get the raw JSON representation of the initial event (the one that
     /// caused this timeline item to be created).
     ///
@@ -450,6 +726,9 @@ pub struct Profile {
 
     /// The avatar URL, if set.
     pub avatar_url: Option<OwnedMxcUri>,
+
+    /// SC: The current membership of the user in the room, if known.
+    pub membership: Option<MembershipState>,
 }
 
 /// Some details of an [`EventTimelineItem`] that may require server requests
@@ -500,6 +779,61 @@ pub enum EventItemOrigin {
     Pagination,
 }
 
+/// SC: The delivery status of an event sent by the current user, see
+/// [`EventTimelineItem::own_delivery_status`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum OwnDeliveryStatus {
+    /// The event is being sent.
+    Sending,
+    /// The event could not be sent.
+    Failed,
+    /// The event was received by the server.
+    Sent,
+    /// The event was read by another user.
+    Read,
+}
+
+/// SC: The details of the timestamp of an event, see
+/// [`EventTimelineItem::timestamp_info`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimestampInfo {
+    /// The timestamp of the event, as set by the server of its sender, or the
+    /// time the local echo was created.
+    pub origin_server_ts: MilliSecondsSinceUnixEpoch,
+    /// When the event was sent according to the local clock, estimated from
+    /// its `unsigned.age` at the time it was received.
+    ///
+    /// This is `None` for local echoes, for events loaded from a cache, and
+    /// when the server didn't provide an age.
+    pub age_derived_ts: Option<MilliSecondsSinceUnixEpoch>,
+    /// The estimated difference between the clock of the server and the local
+    /// clock, in milliseconds, if [`Self::age_derived_ts`] is known.
+    ///
+    /// It is positive when the server's clock is ahead of the local one.
+    pub clock_skew_ms: Option<i64>,
+}
+
+/// SC: Why the authenticity of an event can't be guaranteed, see
+/// [`EventTimelineItem::shield_reason`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum ShieldReason {
+    /// The event was sent by a device of a user whose identity we haven't
+    /// verified.
+    UnverifiedDevice,
+    /// The event was sent by a device that its owner hasn't verified.
+    UnsignedDevice,
+    /// The event was sent by a device that is unknown, or that has been
+    /// deleted.
+    UnknownDevice,
+    /// The event wasn't encrypted, although the room is.
+    SentInClear,
+    /// The key used to decrypt the event was obtained from an insecure source,
+    /// like a key backup or an import from a file.
+    AuthenticityNotGuaranteed,
+}
+
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
@@ -514,7 +848,7 @@ mod tests {
         api::client::sync::sync_events::v4,
         events::{
             room::{
-                member::RoomMemberEventContent,
+                member::{MembershipState, RoomMemberEventContent},
                 message::{MessageFormat, MessageType},
             },
             AnySyncTimelineEvent,
@@ -587,7 +921,8 @@ mod tests {
             Profile {
                 display_name: Some("Alice Margatroid".to_owned()),
                 display_name_ambiguous: false,
-                avatar_url: Some(owned_mxc_uri!("mxc://e.org/SEs"))
+                avatar_url: Some(owned_mxc_uri!("mxc://e.org/SEs")),
+                membership: Some(MembershipState::Join),
             }
         );
     }
@@ -634,7 +969,8 @@ mod tests {
             Profile {
                 display_name: Some("Alice Margatroid".to_owned()),
                 display_name_ambiguous: false,
-                avatar_url: Some(owned_mxc_uri!("mxc://e.org/SEs"))
+                avatar_url: Some(owned_mxc_uri!("mxc://e.org/SEs")),
+                membership: None,
             }
         );
     }
@@ -16,17 +16,54 @@ use std::ops::Deref;
 
 use indexmap::IndexMap;
 use itertools::Itertools as _;
-use ruma::{OwnedEventId, OwnedTransactionId, UserId};
+use ruma::{MxcUri, OwnedEventId, OwnedMxcUri, OwnedTransactionId, UserId};
 
 use super::EventItemIdentifier;
 use crate::timeline::ReactionSenderData;
 
 /// The reactions grouped by key.
 ///
-/// Key: The reaction, usually an emoji.
+/// Key: The reaction, usually an emoji. See [`ReactionKey`] to handle custom
+/// emoji reactions.
 /// Value: The group of reactions.
 pub type BundledReactions = IndexMap<String, ReactionGroup>;
 
+/// SC: The key of a reaction, as used in [`BundledReactions`].
+///
+/// Reactions are grouped by their raw key in any case, so custom emoji
+/// reactions don't interfere with the grouping of unicode ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReactionKey {
+    /// A unicode emoji, or any other text.
+    Text(String),
+
+    /// A custom emoji, whose key is the `mxc://` URI of its image.
+    Image(OwnedMxcUri),
+}
+
+impl ReactionKey {
+    /// Parse the given raw reaction key.
+    ///
+    /// Keys that are valid `mxc://` URIs are considered custom emoji, all the
+    /// other ones are considered text.
+    pub fn parse(key: &str) -> Self {
+        let uri = <&MxcUri>::from(key);
+        if uri.is_valid() {
+            Self::Image(uri.to_owned())
+        } else {
+            Self::Text(key.to_owned())
+        }
+    }
+
+    /// The URL of the image to render for a custom emoji reaction.
+    pub fn media_url(&self) -> Option<&MxcUri> {
+        match self {
+            Self::Text(_) => None,
+            Self::Image(uri) => Some(uri),
+        }
+    }
+}
+
 /// A group of reaction events on the same event with the same key.
 ///
 /// This is a map of the event ID or transaction ID of the reactions to the ID
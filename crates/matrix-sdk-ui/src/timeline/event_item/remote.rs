@@ -15,12 +15,13 @@
 use std::fmt;
 
 use indexmap::IndexMap;
-use matrix_sdk::deserialized_responses::EncryptionInfo;
+use matrix_sdk::deserialized_responses::{EncryptionInfo, VerificationState};
 use ruma::{
     events::{receipt::Receipt, AnySyncTimelineEvent},
     serde::Raw,
-    OwnedEventId, OwnedUserId,
+    MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedUserId, UInt,
 };
+use serde::Deserialize;
 
 use super::BundledReactions;
 
@@ -41,12 +42,30 @@ pub(in crate::timeline) struct RemoteEventTimelineItem {
     /// Note that currently this ignores threads.
     pub read_receipts: IndexMap<OwnedUserId, Receipt>,
 
+    /// SC: Whether another user has read this event or a later one.
+    ///
+    /// This is only maintained for own events, and is never reset once set.
+    pub read_by_others: bool,
+
     /// Whether the event has been sent by the the logged-in user themselves.
     pub is_own: bool,
 
     /// Whether the item should be highlighted in the timeline.
     pub is_highlighted: bool,
 
+    /// SC: Whether the event is pinned in the room.
+    pub is_pinned: bool,
+
+    /// SC: Whether the room is encrypted, used to warn about events that were
+    /// sent in the clear.
+    pub is_room_encrypted: bool,
+
+    /// SC: Whether the shield of the event is computed in strict mode by
+    /// default, see [`TimelineBuilder::strict_shields`].
+    ///
+    /// [`TimelineBuilder::strict_shields`]: crate::timeline::TimelineBuilder::strict_shields
+    pub strict_shields: bool,
+
     /// Encryption information.
     pub encryption_info: Option<EncryptionInfo>,
 
@@ -67,6 +86,13 @@ pub(in crate::timeline) struct RemoteEventTimelineItem {
 
     /// Where we got this event from: A sync response or pagination.
     pub origin: RemoteEventOrigin,
+
+    /// SC: When the event was sent according to the local clock, estimated
+    /// from its `unsigned.age` at the time it was received.
+    ///
+    /// This is `None` if the server didn't provide an age, or if the event was
+    /// loaded from a cache, where its age is stale.
+    pub age_derived_ts: Option<MilliSecondsSinceUnixEpoch>,
 }
 
 impl RemoteEventTimelineItem {
@@ -75,6 +101,27 @@ impl RemoteEventTimelineItem {
         Self { reactions, ..self.clone() }
     }
 
+    /// SC: Clone the current event item, and update its `is_pinned` flag.
+    pub fn with_pinned(&self, is_pinned: bool) -> Self {
+        Self { is_pinned, ..self.clone() }
+    }
+
+    /// SC: Clone the current event item, and update the verification state of
+    /// its encryption information, if any.
+    pub fn with_verification_state(&self, verification_state: VerificationState) -> Self {
+        let mut new = self.clone();
+        if let Some(encryption_info) = &mut new.encryption_info {
+            encryption_info.verification_state = verification_state;
+        }
+        new
+    }
+
+    /// SC: Clone the current event item, and update its `is_room_encrypted`
+    /// flag.
+    pub fn with_room_encrypted(&self, is_room_encrypted: bool) -> Self {
+        Self { is_room_encrypted, ..self.clone() }
+    }
+
     /// Clone the current event item, and clear its `reactions` as well as the
     /// JSON representation fields.
     pub fn redact(&self) -> Self {
@@ -109,22 +156,46 @@ impl fmt::Debug for RemoteEventTimelineItem {
             event_id,
             reactions,
             read_receipts,
+            read_by_others,
             is_own,
             encryption_info,
             original_json: _,
             latest_edit_json: _,
             is_highlighted,
+            is_pinned,
+            is_room_encrypted,
+            strict_shields,
             origin,
+            age_derived_ts,
         } = self;
 
         f.debug_struct("RemoteEventTimelineItem")
             .field("event_id", event_id)
             .field("reactions", reactions)
             .field("read_receipts", read_receipts)
+            .field("read_by_others", read_by_others)
             .field("is_own", is_own)
             .field("is_highlighted", is_highlighted)
+            .field("is_pinned", is_pinned)
+            .field("is_room_encrypted", is_room_encrypted)
+            .field("strict_shields", strict_shields)
             .field("encryption_info", encryption_info)
             .field("origin", origin)
+            .field("age_derived_ts", age_derived_ts)
             .finish_non_exhaustive()
     }
 }
+
+/// SC: Estimate when the given event was sent according to the local clock,
+/// from the `unsigned.age` it was just received with.
+pub(in crate::timeline) fn age_derived_ts(
+    event: &Raw<AnySyncTimelineEvent>,
+) -> Option<MilliSecondsSinceUnixEpoch> {
+    #[derive(Deserialize)]
+    struct Unsigned {
+        age: Option<UInt>,
+    }
+
+    let age = event.get_field::<Unsigned>("unsigned").ok().flatten()?.age?;
+    MilliSecondsSinceUnixEpoch::now().0.checked_sub(age).map(MilliSecondsSinceUnixEpoch)
+}
@@ -0,0 +1,189 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: Algorithm placing [`VirtualTimelineItem::HiddenMessages`] headers in
+//! front of runs of consecutive messages from locally muted users.
+
+use std::{cmp::Reverse, sync::Arc};
+
+use eyeball_im::ObservableVectorTransaction;
+use ruma::OwnedUserId;
+use tracing::trace;
+
+use super::{
+    inner::TimelineInnerMetadata, state_groups::is_state_event, EventTimelineItem, TimelineItem,
+    TimelineItemKind, VirtualTimelineItem,
+};
+
+/// Makes sure that every run of consecutive messages from the same muted user
+/// is preceded by exactly one up-to-date hidden messages header, and that
+/// there are no other hidden messages headers.
+///
+/// Runs are interrupted by any item that isn't a message from the same muted
+/// user, including day dividers, the read marker and state events. Existing
+/// headers are reused where possible, so that their unique ID and their
+/// `expanded` flag are kept.
+pub(super) fn adjust_hidden_messages(
+    items: &mut ObservableVectorTransaction<'_, Arc<TimelineItem>>,
+    meta: &mut TimelineInnerMetadata,
+) {
+    let mut ops = Vec::new();
+    let mut run = HiddenRun::default();
+
+    for (i, item) in items.iter().enumerate() {
+        match item.kind() {
+            TimelineItemKind::Virtual(VirtualTimelineItem::HiddenMessages { .. }) => {
+                if run.first_event.is_some() {
+                    // This header may belong to the next run, if it's from another sender.
+                    run.trailing_headers.push(i);
+                } else {
+                    run.headers.push(i);
+                }
+            }
+            TimelineItemKind::Event(event) if is_hidden_message(event, meta) => {
+                if run.sender.as_deref().is_some_and(|sender| sender != event.sender()) {
+                    let trailing_headers = std::mem::take(&mut run.trailing_headers);
+                    std::mem::take(&mut run).finish(items, meta, &mut ops);
+                    run.headers = trailing_headers;
+                } else {
+                    let trailing_headers = std::mem::take(&mut run.trailing_headers);
+                    run.headers.extend(trailing_headers);
+                }
+                run.push_event(i, event);
+            }
+            _ => {
+                std::mem::take(&mut run).finish(items, meta, &mut ops);
+            }
+        }
+    }
+
+    run.finish(items, meta, &mut ops);
+
+    // Apply the operations from the back, so that the indices stay valid.
+    ops.sort_by_key(|op| Reverse(op.index()));
+
+    for op in ops {
+        match op {
+            HiddenMessagesOperation::Insert(i, item) => {
+                trace!("inserting hidden messages header @ {i}");
+                items.insert(i, item);
+            }
+            HiddenMessagesOperation::Replace(i, item) => {
+                trace!("updating hidden messages header @ {i}");
+                items.set(i, item);
+            }
+            HiddenMessagesOperation::Remove(i) => {
+                trace!("removing hidden messages header @ {i}");
+                items.remove(i);
+            }
+        }
+    }
+}
+
+/// Whether the event is a message from a muted user.
+fn is_hidden_message(event: &EventTimelineItem, meta: &TimelineInnerMetadata) -> bool {
+    !event.is_own() && !is_state_event(event) && meta.muted_senders.contains(event.sender())
+}
+
+/// A run of consecutive messages from a muted user, along with the hidden
+/// messages headers found since the last item interrupting a run.
+#[derive(Default)]
+struct HiddenRun {
+    headers: Vec<usize>,
+    /// The headers found after the last event of the run.
+    trailing_headers: Vec<usize>,
+    first_event: Option<usize>,
+    sender: Option<OwnedUserId>,
+    count: usize,
+}
+
+impl HiddenRun {
+    fn push_event(&mut self, i: usize, event: &EventTimelineItem) {
+        self.first_event.get_or_insert(i);
+        self.sender.get_or_insert_with(|| event.sender().to_owned());
+        self.count += 1;
+    }
+
+    fn finish(
+        self,
+        items: &ObservableVectorTransaction<'_, Arc<TimelineItem>>,
+        meta: &mut TimelineInnerMetadata,
+        ops: &mut Vec<HiddenMessagesOperation>,
+    ) {
+        let Self { headers, trailing_headers, first_event, sender, count } = self;
+        let mut headers = headers.into_iter();
+
+        ops.extend(trailing_headers.into_iter().map(HiddenMessagesOperation::Remove));
+
+        let (Some(first_event), Some(sender)) = (first_event, sender) else {
+            ops.extend(headers.map(HiddenMessagesOperation::Remove));
+            return;
+        };
+
+        match headers.next() {
+            Some(header_idx) => {
+                let header = &items[header_idx];
+                let (up_to_date, expanded) = match header.as_virtual() {
+                    Some(VirtualTimelineItem::HiddenMessages { sender: s, count: c, expanded }) => {
+                        (*s == sender && *c == count, *s == sender && *expanded)
+                    }
+                    _ => (false, false),
+                };
+                let item = header.with_kind(VirtualTimelineItem::HiddenMessages {
+                    sender,
+                    count,
+                    expanded,
+                });
+
+                if header_idx + 1 == first_event {
+                    if !up_to_date {
+                        ops.push(HiddenMessagesOperation::Replace(header_idx, item));
+                    }
+                } else {
+                    // The header isn't right before the run anymore: move it.
+                    ops.push(HiddenMessagesOperation::Remove(header_idx));
+                    ops.push(HiddenMessagesOperation::Insert(first_event, item));
+                }
+            }
+
+            None => {
+                let item = meta.new_timeline_item(VirtualTimelineItem::HiddenMessages {
+                    sender,
+                    count,
+                    expanded: false,
+                });
+                ops.push(HiddenMessagesOperation::Insert(first_event, item));
+            }
+        }
+
+        ops.extend(headers.map(HiddenMessagesOperation::Remove));
+    }
+}
+
+enum HiddenMessagesOperation {
+    /// Insert the item before the item at the given index.
+    Insert(usize, Arc<TimelineItem>),
+    Replace(usize, Arc<TimelineItem>),
+    Remove(usize),
+}
+
+impl HiddenMessagesOperation {
+    fn index(&self) -> usize {
+        match self {
+            HiddenMessagesOperation::Insert(i, _)
+            | HiddenMessagesOperation::Replace(i, _)
+            | HiddenMessagesOperation::Remove(i) => *i,
+        }
+    }
+}
@@ -12,11 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-#[cfg(feature = "e2e-encryption")]
-use std::collections::BTreeSet;
-use std::{fmt, sync::Arc};
+use std::{collections::BTreeSet, fmt, future::Future, sync::Arc, time::Duration};
 
 use as_variant::as_variant;
+use chrono::Weekday;
+use eyeball::{SharedObservable, Subscriber};
 use eyeball_im::{ObservableVectorEntry, VectorDiff};
 use eyeball_im_util::vector::VectorObserverExt;
 use futures_core::Stream;
@@ -25,8 +25,12 @@ use itertools::Itertools;
 #[cfg(all(test, feature = "e2e-encryption"))]
 use matrix_sdk::crypto::OlmMachine;
 use matrix_sdk::{
-    deserialized_responses::SyncTimelineEvent,
-    event_cache::{paginator::Paginator, RoomEventCache},
+    deserialized_responses::{SyncTimelineEvent, VerificationLevel, VerificationState},
+    event_cache::{
+        self,
+        paginator::{Paginator, PaginatorState},
+        RoomEventCache,
+    },
     send_queue::AbortSendHandle,
     Result, Room,
 };
@@ -43,12 +47,13 @@ use ruma::{
         relation::Annotation,
         room::message::{MessageType, Relation},
         AnyMessageLikeEventContent, AnySyncEphemeralRoomEvent, AnySyncMessageLikeEvent,
-        AnySyncTimelineEvent, MessageLikeEventType,
+        AnySyncStateEvent, AnySyncTimelineEvent, MessageLikeEventType, SyncStateEvent,
     },
     serde::Raw,
-    EventId, OwnedEventId, OwnedTransactionId, RoomVersionId, TransactionId, UserId,
+    EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedTransactionId, OwnedUserId,
+    RoomVersionId, TransactionId, UserId,
 };
-use tokio::sync::{RwLock, RwLockWriteGuard};
+use tokio::sync::{RwLock, RwLockWriteGuard, Semaphore, SemaphorePermit};
 use tracing::{debug, error, field::debug, info, instrument, trace, warn};
 #[cfg(feature = "e2e-encryption")]
 use tracing::{field, info_span, Instrument as _};
@@ -61,12 +66,16 @@ use super::{
     reactions::ReactionToggleResult,
     traits::RoomDataProvider,
     util::{rfind_event_by_id, rfind_event_item, RelativePosition},
-    AnnotationKey, Error, EventSendState, EventTimelineItem, InReplyToDetails, Message,
-    PaginationError, Profile, RepliedToEvent, TimelineDetails, TimelineFocus, TimelineItem,
-    TimelineItemContent, TimelineItemKind,
+    AnnotationKey, Error, EventItemIdentifier, EventSendState, EventTimelineItem, InReplyToDetails,
+    Message, PaginationError, Profile, RepliedToEvent, TimelineDetails, TimelineFocus,
+    TimelineItem, TimelineItemContent, TimelineItemKind, VirtualTimelineItem,
 };
 use crate::{
-    timeline::{day_dividers::DayDividerAdjuster, TimelineEventFilterFn},
+    room_list_service::filters::normalize_string,
+    timeline::{
+        day_dividers::{DayDividerAdjuster, DividerGranularity},
+        LateDecryptionHandler, TimelineContentTransformFn, TimelineEventFilterFn,
+    },
     unable_to_decrypt_hook::UtdHookManager,
 };
 
@@ -93,6 +102,42 @@ enum TimelineFocusData {
         /// Number of context events to request for the first request.
         num_context_events: u16,
     },
+
+    /// SC: The timeline only contains the events imported with
+    /// [`TimelineInner::import_events`].
+    Detached {
+        /// The imported events, ordered by [`ImportedEvent::order_key`] and
+        /// deduplicated.
+        events: Vec<ImportedEvent>,
+
+        /// The position of the next imported event.
+        next_position: usize,
+    },
+}
+
+/// SC: An event imported into a detached timeline.
+#[derive(Debug)]
+struct ImportedEvent {
+    /// The position of the event in the stream of imported events.
+    ///
+    /// The events of an export are in the order of the sync stream, so this is
+    /// also their sync position when they are imported from an export.
+    position: usize,
+
+    /// The event.
+    event: SyncTimelineEvent,
+}
+
+impl ImportedEvent {
+    /// The key by which the imported events are ordered.
+    ///
+    /// Events are ordered by timestamp, and the ties are broken by their sync
+    /// position, then by event ID, so that events with the same timestamp
+    /// keep the same order when they are imported again.
+    fn order_key(&self) -> (Option<MilliSecondsSinceUnixEpoch>, usize, Option<OwnedEventId>) {
+        let timestamp = self.event.event.get_field("origin_server_ts").ok().flatten();
+        (timestamp, self.position, self.event.event_id())
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -110,6 +155,14 @@ pub(super) struct TimelineInner<P: RoomDataProvider = Room> {
 
     /// Settings applied to this timeline.
     settings: TimelineInnerSettings,
+
+    /// SC: The events pinned in the room, including the ones that are being
+    /// pinned or unpinned optimistically.
+    pinned_event_ids: SharedObservable<Vec<OwnedEventId>>,
+
+    /// SC: Permits limiting the number of reaction requests sent at the same
+    /// time.
+    reaction_permits: Arc<Semaphore>,
 }
 
 #[derive(Debug, Clone)]
@@ -145,14 +198,57 @@ pub(super) struct TimelineInnerSettings {
     pub(super) event_filter: Arc<TimelineEventFilterFn>,
     /// Are unparsable events added as timeline items of their own kind?
     pub(super) add_failed_to_parse: bool,
+    /// SC: Are runs of consecutive state events collapsed into state groups?
+    pub(super) collapse_state_events: bool,
+    /// SC: Are runs of messages from locally muted users collapsed?
+    pub(super) collapse_muted_users: bool,
+    /// SC: Are day dividers inserted between the events of different days?
+    pub(super) date_dividers: bool,
+    /// SC: The period grouped under a day divider.
+    pub(super) divider_granularity: DividerGranularity,
+    /// SC: The first day of the week, when grouping by week.
+    pub(super) first_day_of_week: Weekday,
+    /// SC: Maximum number of event items kept in a live timeline, the oldest
+    /// ones being evicted when it's exceeded.
+    pub(super) max_items: Option<usize>,
+    /// SC: Are the shields of the events computed in strict mode by default?
+    pub(super) strict_shields: bool,
+    /// SC: How long an item must stay visible before a read receipt is sent
+    /// for it by [`Timeline::mark_visible`](super::Timeline::mark_visible).
+    pub(super) receipt_visibility_delay: Option<Duration>,
+    /// SC: Function rewriting the content of the event items before they are
+    /// displayed.
+    pub(super) content_transform: Option<Arc<TimelineContentTransformFn>>,
+    /// SC: How long to wait for more toggles of a reaction before sending the
+    /// net result.
+    pub(super) reaction_debounce: Duration,
+    /// SC: Maximum number of reaction requests sent at the same time.
+    pub(super) max_concurrent_reactions: usize,
 }
 
+/// SC: The default value of [`TimelineInnerSettings::reaction_debounce`].
+const DEFAULT_REACTION_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// SC: The default value of
+/// [`TimelineInnerSettings::max_concurrent_reactions`].
+const DEFAULT_MAX_CONCURRENT_REACTIONS: usize = 4;
+
 #[cfg(not(tarpaulin_include))]
 impl fmt::Debug for TimelineInnerSettings {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("TimelineInnerSettings")
             .field("track_read_receipts", &self.track_read_receipts)
             .field("add_failed_to_parse", &self.add_failed_to_parse)
+            .field("collapse_state_events", &self.collapse_state_events)
+            .field("collapse_muted_users", &self.collapse_muted_users)
+            .field("date_dividers", &self.date_dividers)
+            .field("divider_granularity", &self.divider_granularity)
+            .field("first_day_of_week", &self.first_day_of_week)
+            .field("max_items", &self.max_items)
+            .field("strict_shields", &self.strict_shields)
+            .field("receipt_visibility_delay", &self.receipt_visibility_delay)
+            .field("reaction_debounce", &self.reaction_debounce)
+            .field("max_concurrent_reactions", &self.max_concurrent_reactions)
             .finish_non_exhaustive()
     }
 }
@@ -163,6 +259,17 @@ impl Default for TimelineInnerSettings {
             track_read_receipts: false,
             event_filter: Arc::new(default_event_filter),
             add_failed_to_parse: true,
+            collapse_state_events: false,
+            collapse_muted_users: false,
+            date_dividers: true,
+            divider_granularity: DividerGranularity::Day,
+            first_day_of_week: Weekday::Mon,
+            max_items: None,
+            strict_shields: false,
+            receipt_visibility_delay: None,
+            content_transform: None,
+            reaction_debounce: DEFAULT_REACTION_DEBOUNCE,
+            max_concurrent_reactions: DEFAULT_MAX_CONCURRENT_REACTIONS,
         }
     }
 }
@@ -239,6 +346,14 @@ pub fn default_event_filter(event: &AnySyncTimelineEvent, room_version: &RoomVer
             }
         }
 
+        AnySyncTimelineEvent::State(AnySyncStateEvent::BeaconInfo(SyncStateEvent::Original(
+            ev,
+        ))) => {
+            // A live location share is stopped by a new `beacon_info` that isn't live,
+            // which only updates the item of the share.
+            ev.content.live
+        }
+
         AnySyncTimelineEvent::State(_) => {
             // All the state events may get displayed by default.
             true
@@ -252,6 +367,7 @@ impl<P: RoomDataProvider> TimelineInner<P> {
         focus: TimelineFocus,
         internal_id_prefix: Option<String>,
         unable_to_decrypt_hook: Option<Arc<UtdHookManager>>,
+        settings: TimelineInnerSettings,
     ) -> Self {
         let (focus_data, is_live) = match focus {
             TimelineFocus::Live => (TimelineFocusData::Live, true),
@@ -262,6 +378,9 @@ impl<P: RoomDataProvider> TimelineInner<P> {
                     false,
                 )
             }
+            TimelineFocus::Detached => {
+                (TimelineFocusData::Detached { events: Vec::new(), next_position: 0 }, false)
+            }
         };
 
         let state = TimelineInnerState::new(
@@ -269,13 +388,16 @@ impl<P: RoomDataProvider> TimelineInner<P> {
             is_live,
             internal_id_prefix,
             unable_to_decrypt_hook,
+            &settings,
         );
 
         Self {
             state: Arc::new(RwLock::new(state)),
             focus: Arc::new(RwLock::new(focus_data)),
             room_data_provider,
-            settings: Default::default(),
+            pinned_event_ids: Default::default(),
+            reaction_permits: Arc::new(Semaphore::new(settings.max_concurrent_reactions)),
+            settings,
         }
     }
 
@@ -323,9 +445,53 @@ impl<P: RoomDataProvider> TimelineInner<P> {
 
                 Ok(has_events)
             }
+
+            TimelineFocusData::Detached { .. } => Ok(false),
         }
     }
 
+    /// SC: Import events into a detached timeline, see
+    /// [`Timeline::import_events`](super::Timeline::import_events).
+    pub(super) async fn import_events(
+        &self,
+        events: Vec<Raw<AnySyncTimelineEvent>>,
+    ) -> Result<(), Error> {
+        let mut focus_guard = self.focus.write().await;
+
+        let TimelineFocusData::Detached { events: imported_events, next_position } =
+            &mut *focus_guard
+        else {
+            return Err(Error::NotDetached);
+        };
+
+        for event in events {
+            let Some(event_id) = event.get_field::<OwnedEventId>("event_id").ok().flatten() else {
+                warn!("Not importing an event without an event ID");
+                continue;
+            };
+
+            if imported_events.iter().any(|e| e.event.event_id().as_ref() == Some(&event_id)) {
+                trace!(?event_id, "Not importing a duplicate event");
+                continue;
+            }
+
+            imported_events.push(ImportedEvent {
+                position: *next_position,
+                event: SyncTimelineEvent::new(event),
+            });
+            *next_position += 1;
+        }
+
+        imported_events.sort_by_cached_key(ImportedEvent::order_key);
+
+        let events = imported_events.iter().map(|e| e.event.clone()).collect();
+        drop(focus_guard);
+
+        self.replace_with_initial_remote_events(events, RemoteEventOrigin::Cache).await;
+
+        Ok(())
+    }
+
     /// Run a backward pagination (in focused mode) and append the results to
     /// the timeline.
     ///
@@ -335,7 +501,9 @@ impl<P: RoomDataProvider> TimelineInner<P> {
         num_events: u16,
     ) -> Result<bool, PaginationError> {
         let pagination = match &*self.focus.read().await {
-            TimelineFocusData::Live => return Err(PaginationError::NotEventFocusMode),
+            TimelineFocusData::Live | TimelineFocusData::Detached { .. } => {
+                return Err(PaginationError::NotEventFocusMode)
+            }
             TimelineFocusData::Event { paginator, .. } => paginator
                 .paginate_backward(num_events.into())
                 .await
@@ -357,7 +525,9 @@ impl<P: RoomDataProvider> TimelineInner<P> {
         num_events: u16,
     ) -> Result<bool, PaginationError> {
         let pagination = match &*self.focus.read().await {
-            TimelineFocusData::Live => return Err(PaginationError::NotEventFocusMode),
+            TimelineFocusData::Live | TimelineFocusData::Detached { .. } => {
+                return Err(PaginationError::NotEventFocusMode)
+            }
             TimelineFocusData::Event { paginator, .. } => paginator
                 .paginate_forward(num_events.into())
                 .await
@@ -375,9 +545,37 @@ impl<P: RoomDataProvider> TimelineInner<P> {
         matches!(&*self.focus.read().await, TimelineFocusData::Live)
     }
 
-    pub(super) fn with_settings(mut self, settings: TimelineInnerSettings) -> Self {
-        self.settings = settings;
-        self
+    /// SC: Subscribe to the state of the paginator of a timeline focused on an
+    /// event, or `None` if the timeline isn't in this mode.
+    pub(super) async fn focused_paginator_state(&self) -> Option<Subscriber<PaginatorState>> {
+        match &*self.focus.read().await {
+            TimelineFocusData::Event { paginator, .. } => Some(paginator.state()),
+            TimelineFocusData::Live | TimelineFocusData::Detached { .. } => None,
+        }
+    }
+
+    /// SC: Whether a timeline focused on an event has hit the start of the
+    /// room, i.e. back-paginating wouldn't have any effect.
+    pub(super) async fn focused_hit_timeline_start(&self) -> bool {
+        match &*self.focus.read().await {
+            TimelineFocusData::Event { paginator, .. } => paginator.hit_timeline_start(),
+            TimelineFocusData::Live | TimelineFocusData::Detached { .. } => true,
+        }
+    }
+
+    /// SC: Whether a timeline focused on an event has hit the end of the room,
+    /// i.e. forward-paginating wouldn't have any effect.
+    pub(super) async fn focused_hit_timeline_end(&self) -> bool {
+        match &*self.focus.read().await {
+            TimelineFocusData::Event { paginator, .. } => paginator.hit_timeline_end(),
+            TimelineFocusData::Live | TimelineFocusData::Detached { .. } => true,
+        }
+    }
+
+    /// The [`RoomDataProvider`] of this timeline.
+    #[cfg(test)]
+    pub(super) fn room_data_provider(&self) -> &P {
+        &self.room_data_provider
     }
 
     /// Get a copy of the current items in the list.
@@ -391,6 +589,30 @@ impl<P: RoomDataProvider> TimelineInner<P> {
         self.state.read().await.meta.fully_read_event.clone()
     }
 
+    /// SC: The ID of the first event after the fully-read marker, see
+    /// [`Timeline::first_unread_event_id`](super::Timeline::first_unread_event_id).
+    pub(super) async fn first_unread_event_id(&self) -> Option<OwnedEventId> {
+        // The marker might not have been loaded in the timeline yet.
+        let fully_read_event_id = match self.fully_read_event_id().await {
+            Some(event_id) => event_id,
+            None => self.room_data_provider.load_fully_read_marker().await?,
+        };
+
+        let state = self.state.read().await;
+        let Some((idx, _)) = rfind_event_by_id(&state.items, &fully_read_event_id) else {
+            // The fully-read event isn't loaded, the first unread event can't be either.
+            return Some(fully_read_event_id);
+        };
+
+        state
+            .items
+            .iter()
+            .skip(idx + 1)
+            .filter_map(|item| item.as_event()?.event_id())
+            .next()
+            .map(ToOwned::to_owned)
+    }
+
     pub(super) async fn subscribe(
         &self,
     ) -> (Vector<Arc<TimelineItem>>, impl Stream<Item = VectorDiff<Arc<TimelineItem>>>) {
@@ -573,6 +795,139 @@ impl<P: RoomDataProvider> TimelineInner<P> {
         self.state.write().await.clear();
     }
 
+    /// SC: Add back up to `num_events` of the events evicted because of
+    /// [`TimelineInnerSettings::max_items`].
+    ///
+    /// `load_events_before` is only called if some events were evicted, to
+    /// get the given number of events before the given one from the room's
+    /// event cache, the most recent first, or `None` if the event isn't
+    /// cached.
+    ///
+    /// Returns `false` if there was no evicted event to reload.
+    pub(super) async fn reload_evicted_events<F, Fut>(
+        &self,
+        num_events: u16,
+        load_events_before: F,
+    ) -> bool
+    where
+        F: FnOnce(OwnedEventId, usize) -> Fut,
+        Fut: Future<Output = Option<Vec<SyncTimelineEvent>>>,
+    {
+        let mut state = self.state.write().await;
+
+        if !state.meta.has_evicted_items {
+            return false;
+        }
+
+        let events = match state.meta.all_events.front() {
+            Some(oldest) => load_events_before(oldest.event_id.clone(), num_events.into()).await,
+            None => None,
+        };
+
+        let Some(events) = events.filter(|events| !events.is_empty()) else {
+            // Either the timeline or the event cache has been reset, or everything has been
+            // reloaded already.
+            state.meta.has_evicted_items = false;
+            return false;
+        };
+
+        if events.len() < num_events.into() {
+            state.meta.has_evicted_items = false;
+        }
+
+        // The events are already in reverse topological order, as expected for
+        // prepended events.
+        state
+            .add_remote_events_at(
+                events,
+                TimelineEnd::Front,
+                RemoteEventOrigin::Cache,
+                &self.room_data_provider,
+                &self.settings,
+            )
+            .await;
+
+        true
+    }
+
+    /// SC: Attach a timeline focused on an event to the live sync, once its
+    /// forward pagination reached the end of the room.
+    ///
+    /// The events of the room's event cache received after the last event of
+    /// the timeline are added to it, so that the ones that came via sync
+    /// during the pagination aren't missed. Duplicates are handled like for
+    /// any other remote event.
+    ///
+    /// If the event cache doesn't contain the last event of the timeline,
+    /// there might be a gap between them, so the timeline is reset to the
+    /// events of the event cache instead, like a new live timeline.
+    ///
+    /// The timeline then has a live focus: it handles the updates of the event
+    /// cache like a live timeline, including clears, and back-paginates through
+    /// the event cache.
+    ///
+    /// Returns whether the timeline was attached by this call, i.e. it wasn't
+    /// live already.
+    pub(super) async fn attach_to_sync(
+        &self,
+        room_event_cache: &RoomEventCache,
+    ) -> event_cache::Result<bool> {
+        let mut focus_guard = self.focus.write().await;
+
+        if matches!(&*focus_guard, TimelineFocusData::Live) {
+            return Ok(false);
+        }
+
+        let mut state = self.state.write().await;
+
+        *focus_guard = TimelineFocusData::Live;
+        drop(focus_guard);
+
+        state.is_live_timeline = true;
+
+        // Take the events while holding the lock, so that no sync update is handled
+        // in between.
+        let (mut cached_events, _) = room_event_cache.subscribe().await?;
+
+        let last = state.meta.all_events.back().and_then(|latest| {
+            cached_events
+                .iter()
+                .position(|event| event.event_id().as_ref() == Some(&latest.event_id))
+        });
+
+        match last {
+            Some(last) => {
+                let missed_events = cached_events.split_off(last + 1);
+
+                state
+                    .add_remote_events_at(
+                        missed_events,
+                        TimelineEnd::Back,
+                        RemoteEventOrigin::Sync,
+                        &self.room_data_provider,
+                        &self.settings,
+                    )
+                    .await;
+            }
+
+            // If the event cache is empty, the pagination already returned everything
+            // that it could know about.
+            None if cached_events.is_empty() => {}
+
+            None => {
+                debug!("The latest event isn't in the event cache, resetting to the live timeline");
+                self.replace_with_initial_remote_events_locked(
+                    &mut state,
+                    cached_events,
+                    RemoteEventOrigin::Cache,
+                )
+                .await;
+            }
+        }
+
+        Ok(true)
+    }
+
     /// Replaces the content of the current timeline with initial events.
     ///
     /// Also sets up read receipts and the read marker for a live timeline of a
@@ -586,7 +941,17 @@ impl<P: RoomDataProvider> TimelineInner<P> {
         origin: RemoteEventOrigin,
     ) {
         let mut state = self.state.write().await;
+        self.replace_with_initial_remote_events_locked(&mut state, events, origin).await;
+    }
 
+    /// Same as [`Self::replace_with_initial_remote_events`], with the lock of
+    /// the state already held.
+    async fn replace_with_initial_remote_events_locked(
+        &self,
+        state: &mut TimelineInnerState,
+        events: Vec<SyncTimelineEvent>,
+        origin: RemoteEventOrigin,
+    ) {
         state.clear();
 
         let track_read_markers = self.settings.track_read_receipts;
@@ -794,6 +1159,60 @@ impl<P: RoomDataProvider> TimelineInner<P> {
         Ok(follow_up_action)
     }
 
+    /// SC: Get the request to send for a reaction once a burst of toggles is
+    /// over, given the `action` returned for the first toggle.
+    ///
+    /// The toggles that happened in the meantime may cancel each other, in
+    /// which case the local echo is reconciled right away without sending any
+    /// request. Since the reaction state only tracks the net result of the
+    /// toggles, this works for bursts of any length.
+    pub(super) async fn settle_reaction(
+        &self,
+        annotation: &Annotation,
+        action: ReactionAction,
+    ) -> Result<ReactionAction, Error> {
+        let mut state = self.state.write().await;
+        let user_id = self.room_data_provider.own_user_id();
+        let annotation_key: AnnotationKey = annotation.into();
+
+        let result = match (&action, state.meta.reaction_state.get(&annotation_key)) {
+            // The reaction was added, then removed before being sent: the local echo is
+            // already gone.
+            (ReactionAction::SendRemote(_), Some(ReactionState::Redacting(None))) => None,
+            // The reaction was removed, then added back before being redacted: keep the
+            // remote echo instead of the new local echo.
+            (ReactionAction::RedactRemote(event_id), Some(ReactionState::Sending(txn_id))) => {
+                Some(ReactionToggleResult::AddSuccess {
+                    event_id: event_id.clone(),
+                    txn_id: txn_id.clone(),
+                })
+            }
+            _ => return Ok(action),
+        };
+
+        trace!("The toggles of the reaction cancelled each other, not sending any request");
+        state.meta.in_flight_reaction.swap_remove(&annotation_key);
+        state.meta.reaction_state.swap_remove(&annotation_key);
+
+        if let Some(result) = result {
+            state.update_timeline_reaction(user_id, annotation, &result)?;
+        }
+
+        Ok(ReactionAction::None)
+    }
+
+    /// SC: Wait until a new reaction request can be sent, see
+    /// [`TimelineInnerSettings::max_concurrent_reactions`].
+    pub(super) async fn reaction_permit(&self) -> SemaphorePermit<'_> {
+        self.reaction_permits.acquire().await.expect("the reaction semaphore is never closed")
+    }
+
+    /// SC: How long to wait for more toggles of a reaction before sending the
+    /// net result.
+    pub(super) fn reaction_debounce(&self) -> Duration {
+        self.settings.reaction_debounce
+    }
+
     pub(super) async fn discard_local_echo(&self, txn_id: &TransactionId) -> bool {
         let mut state = self.state.write().await;
 
@@ -803,12 +1222,326 @@ impl<P: RoomDataProvider> TimelineInner<P> {
             state.items.remove(idx);
             debug!("Discarded local echo");
             true
+        } else if state.meta.pending_edits.contains(txn_id) {
+            // SC: The local echo of an edit is applied to the edited item, restore it.
+            if let Some((event_id, previous)) = state.meta.pending_edits.remove(txn_id) {
+                if let Some((idx, item)) = rfind_event_by_id(&state.items, &event_id) {
+                    let restored = TimelineItem::new(
+                        item.with_content(previous.content, previous.latest_edit_json),
+                        item.internal_id.to_owned(),
+                    );
+                    state.items.set(idx, restored);
+                }
+            }
+            debug!("Discarded local echo of an edit");
+            true
         } else {
             debug!("Can't find local echo to discard");
             false
         }
     }
 
+    /// SC: The handles to abort the local echoes of the edits of the event
+    /// `event_id` that haven't been sent yet, the latest first.
+    pub(super) async fn pending_edit_abort_handles(
+        &self,
+        event_id: &EventId,
+    ) -> Vec<AbortSendHandle> {
+        self.state.read().await.meta.pending_edits.abort_handles(event_id)
+    }
+
+    /// SC: Marks the state group with the given unique ID as expanded.
+    ///
+    /// Returns whether a state group with that ID was found.
+    pub(super) async fn expand_state_group(&self, unique_id: &str) -> bool {
+        let mut state = self.state.write().await;
+        let mut txn = state.transaction();
+
+        let Some((idx, count, summary, expanded)) =
+            txn.items.iter().enumerate().find_map(|(idx, item)| match item.as_virtual() {
+                Some(VirtualTimelineItem::StateGroup { count, summary, expanded })
+                    if item.unique_id() == unique_id =>
+                {
+                    Some((idx, *count, summary.clone(), *expanded))
+                }
+                _ => None,
+            })
+        else {
+            return false;
+        };
+
+        if !expanded {
+            let item = txn.items[idx].with_kind(VirtualTimelineItem::StateGroup {
+                count,
+                summary,
+                expanded: true,
+            });
+            txn.items.set(idx, item);
+            txn.commit();
+        }
+
+        true
+    }
+
+    /// SC: Update the users whose messages are collapsed into
+    /// [`VirtualTimelineItem::HiddenMessages`] items.
+    pub(super) async fn set_muted_senders(&self, muted_senders: BTreeSet<OwnedUserId>) {
+        let mut state = self.state.write().await;
+
+        if state.meta.muted_senders == muted_senders {
+            return;
+        }
+
+        let mut txn = state.transaction();
+        txn.meta.muted_senders = muted_senders;
+        txn.commit();
+    }
+
+    /// SC: Update the users in the `m.ignored_user_list` of the account, and
+    /// remove the items of the events of the newly ignored users.
+    ///
+    /// Returns whether some users were unignored, in which case their events
+    /// must be reloaded to be restored.
+    pub(super) async fn set_ignored_users(&self, ignored_users: BTreeSet<OwnedUserId>) -> bool {
+        let mut state = self.state.write().await;
+
+        if state.meta.ignored_users == ignored_users {
+            return false;
+        }
+
+        let mut txn = state.transaction();
+        let has_unignored_users = !txn.meta.ignored_users.is_subset(&ignored_users);
+        let mut has_removed_items = false;
+
+        for idx in (0..txn.items.len()).rev() {
+            let Some(event) = txn.items[idx].as_event() else { continue };
+
+            if event.is_own() || !ignored_users.contains(event.sender()) {
+                continue;
+            }
+
+            if let Some(event_id) = event.event_id() {
+                if let Some(event_meta) =
+                    txn.meta.all_events.iter_mut().find(|meta| meta.event_id == event_id)
+                {
+                    event_meta.visible = false;
+                }
+            }
+
+            trace!(sender = ?event.sender(), "Removing the item of an ignored user");
+            txn.items.remove(idx);
+            has_removed_items = true;
+        }
+
+        if has_removed_items {
+            // Adjust the day dividers, if needs be.
+            let mut adjuster = DayDividerAdjuster::default();
+            adjuster.run(&mut txn.items, &mut txn.meta);
+        }
+
+        txn.meta.ignored_users = ignored_users;
+        txn.commit();
+
+        has_unignored_users
+    }
+
+    /// SC: Change the period grouped under a day divider, and adjust the
+    /// existing day dividers accordingly.
+    pub(super) async fn set_divider_granularity(
+        &self,
+        granularity: DividerGranularity,
+        first_day_of_week: Weekday,
+    ) {
+        let mut state = self.state.write().await;
+        let mut txn = state.transaction();
+
+        if txn.meta.divider_granularity == granularity
+            && txn.meta.first_day_of_week == first_day_of_week
+        {
+            return;
+        }
+
+        txn.meta.divider_granularity = granularity;
+        txn.meta.first_day_of_week = first_day_of_week;
+
+        let mut adjuster = DayDividerAdjuster::default();
+        adjuster.mark_used();
+        adjuster.run(&mut txn.items, &mut txn.meta);
+
+        txn.commit();
+    }
+
+    /// SC: Marks the hidden messages with the given unique ID as expanded.
+    ///
+    /// Returns whether hidden messages with that ID were found.
+    pub(super) async fn expand_hidden_messages(&self, unique_id: &str) -> bool {
+        let mut state = self.state.write().await;
+        let mut txn = state.transaction();
+
+        let Some((idx, sender, count, expanded)) =
+            txn.items.iter().enumerate().find_map(|(idx, item)| match item.as_virtual() {
+                Some(VirtualTimelineItem::HiddenMessages { sender, count, expanded })
+                    if item.unique_id() == unique_id =>
+                {
+                    Some((idx, sender.clone(), *count, *expanded))
+                }
+                _ => None,
+            })
+        else {
+            return false;
+        };
+
+        if !expanded {
+            let item = txn.items[idx].with_kind(VirtualTimelineItem::HiddenMessages {
+                sender,
+                count,
+                expanded: true,
+            });
+            txn.items.set(idx, item);
+            txn.commit();
+        }
+
+        true
+    }
+
+    /// SC: Set the function called when an unable-to-decrypt item gets
+    /// decrypted, see
+    /// [`Timeline::on_late_decryption`](super::Timeline::on_late_decryption).
+    pub(super) async fn set_late_decryption_handler(&self, handler: LateDecryptionHandler) {
+        self.state.write().await.meta.late_decryption.set_handler(handler);
+    }
+
+    /// SC: Search the text messages currently loaded in the timeline, see
+    /// [`Timeline::search_local`](super::Timeline::search_local).
+    pub(super) async fn search_local(&self, query: &str) -> Vec<EventItemIdentifier> {
+        let query = normalize_string(query).to_lowercase();
+        if query.trim().is_empty() {
+            return Vec::new();
+        }
+
+        let state = self.state.read().await;
+        state
+            .items
+            .iter()
+            .rev()
+            .filter_map(|item| item.as_event())
+            .filter(|event| {
+                let TimelineItemContent::Message(message) = event.content() else {
+                    return false;
+                };
+
+                matches!(
+                    message.msgtype(),
+                    MessageType::Text(_) | MessageType::Notice(_) | MessageType::Emote(_)
+                ) && normalize_string(message.body()).to_lowercase().contains(&query)
+            })
+            .map(|event| event.identifier())
+            .collect()
+    }
+
+    /// SC: The events currently considered pinned by this timeline.
+    pub(super) fn pinned_event_ids(&self) -> Vec<OwnedEventId> {
+        self.pinned_event_ids.get()
+    }
+
+    /// SC: Subscribe to the events currently considered pinned by this
+    /// timeline.
+    pub(super) fn subscribe_pinned_event_ids(&self) -> Subscriber<Vec<OwnedEventId>> {
+        self.pinned_event_ids.subscribe()
+    }
+
+    /// SC: Update the `is_pinned` flag of the remote events in the timeline,
+    /// given the new list of pinned events of the room.
+    ///
+    /// This is also used to reflect pinning or unpinning optimistically,
+    /// before the server has confirmed it.
+    pub(super) async fn update_pinned_events(&self, pinned_event_ids: &[OwnedEventId]) {
+        self.pinned_event_ids.set_if_not_eq(pinned_event_ids.to_vec());
+
+        let mut state = self.state.write().await;
+        let mut entries = state.items.entries();
+        while let Some(mut entry) = entries.next() {
+            let Some(event_item) = entry.as_event() else { continue };
+            let Some(remote_event) = event_item.as_remote() else { continue };
+
+            let is_pinned = pinned_event_ids.contains(&remote_event.event_id);
+            if remote_event.is_pinned != is_pinned {
+                trace!(event_id = ?remote_event.event_id, is_pinned, "Updating pinned state");
+                let updated_item = event_item.with_kind(remote_event.with_pinned(is_pinned));
+                let new_item = entry.with_kind(updated_item);
+                ObservableVectorEntry::set(&mut entry, new_item);
+            }
+        }
+    }
+
+    /// SC: Recompute the verification state of the encrypted events sent by
+    /// the given users, or by everyone if `user_ids` is `None`, so that their
+    /// [`ShieldReason`] follows the verification of the senders' devices and
+    /// identities.
+    ///
+    /// The state of events whose key can't be linked to a device is kept, as
+    /// verifying the sender doesn't change it.
+    ///
+    /// [`ShieldReason`]: crate::timeline::ShieldReason
+    pub(super) async fn update_verification_states(
+        &self,
+        user_ids: Option<&BTreeSet<OwnedUserId>>,
+    ) {
+        let mut state = self.state.write().await;
+        let mut entries = state.items.entries();
+        while let Some(mut entry) = entries.next() {
+            let Some(event_item) = entry.as_event() else { continue };
+            let Some(remote_event) = event_item.as_remote() else { continue };
+            let Some(encryption_info) = &remote_event.encryption_info else { continue };
+            let Some(device_id) = &encryption_info.sender_device else { continue };
+
+            if user_ids.is_some_and(|user_ids| !user_ids.contains(&encryption_info.sender))
+                || matches!(
+                    encryption_info.verification_state,
+                    VerificationState::Unverified(VerificationLevel::None(_))
+                )
+            {
+                continue;
+            }
+
+            let Some(verification_state) = self
+                .room_data_provider
+                .device_verification_state(&encryption_info.sender, device_id)
+                .await
+            else {
+                continue;
+            };
+
+            if encryption_info.verification_state != verification_state {
+                trace!(event_id = ?remote_event.event_id, ?verification_state, "Updating verification state");
+                let updated_item =
+                    event_item.with_kind(remote_event.with_verification_state(verification_state));
+                let new_item = entry.with_kind(updated_item);
+                ObservableVectorEntry::set(&mut entry, new_item);
+            }
+        }
+    }
+
+    /// SC: Update the `is_room_encrypted` flag of the remote events in the
+    /// timeline, so that their [`ShieldReason`] is recomputed.
+    ///
+    /// [`ShieldReason`]: crate::timeline::ShieldReason
+    pub(super) async fn update_room_encrypted(&self, is_room_encrypted: bool) {
+        let mut state = self.state.write().await;
+        let mut entries = state.items.entries();
+        while let Some(mut entry) = entries.next() {
+            let Some(event_item) = entry.as_event() else { continue };
+            let Some(remote_event) = event_item.as_remote() else { continue };
+
+            if remote_event.is_room_encrypted != is_room_encrypted {
+                let updated_item =
+                    event_item.with_kind(remote_event.with_room_encrypted(is_room_encrypted));
+                let new_item = entry.with_kind(updated_item);
+                ObservableVectorEntry::set(&mut entry, new_item);
+            }
+        }
+    }
+
     #[cfg(test)]
     pub(super) async fn set_fully_read_event(&self, fully_read_event_id: OwnedEventId) {
         self.state.write().await.set_fully_read_event(fully_read_event_id);
@@ -1083,6 +1816,40 @@ impl<P: RoomDataProvider> TimelineInner<P> {
     ) -> Option<OwnedEventId> {
         self.state.read().await.latest_user_read_receipt_timeline_event_id(user_id)
     }
+
+    /// SC: How long an item must stay visible before a read receipt is sent
+    /// for it.
+    pub(super) fn receipt_visibility_delay(&self) -> Option<Duration> {
+        self.settings.receipt_visibility_delay
+    }
+
+    /// SC: The function rewriting the content of the event items before they
+    /// are displayed, if any.
+    pub(super) fn content_transform(&self) -> Option<Arc<TimelineContentTransformFn>> {
+        self.settings.content_transform.clone()
+    }
+
+    /// SC: Get the ID of the most recent of the given events in the timeline.
+    ///
+    /// Returns `None` if none of the events are in the timeline.
+    pub(super) async fn latest_event_id_among(
+        &self,
+        event_ids: impl IntoIterator<Item = OwnedEventId>,
+    ) -> Option<OwnedEventId> {
+        let event_ids: BTreeSet<_> = event_ids.into_iter().collect();
+        if event_ids.is_empty() {
+            return None;
+        }
+
+        let state = self.state.read().await;
+        state
+            .meta
+            .all_events
+            .iter()
+            .rev()
+            .find(|event_meta| event_ids.contains(&event_meta.event_id))
+            .map(|event_meta| event_meta.event_id.clone())
+    }
 }
 
 impl TimelineInner {
@@ -12,8 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::VecDeque, future::Future, sync::Arc};
+use std::{
+    collections::{BTreeSet, VecDeque},
+    future::Future,
+    sync::Arc,
+};
 
+use chrono::Weekday;
 use eyeball_im::{ObservableVector, ObservableVectorTransaction, ObservableVectorTransactionEntry};
 use indexmap::IndexMap;
 use matrix_sdk::{deserialized_responses::SyncTimelineEvent, send_queue::AbortSendHandle};
@@ -33,19 +38,24 @@ use super::{HandleManyEventsResult, ReactionState, TimelineInnerSettings};
 use crate::{
     events::SyncTimelineEventWithoutContent,
     timeline::{
-        day_dividers::DayDividerAdjuster,
+        day_dividers::{DayDividerAdjuster, DividerGranularity},
         event_handler::{
             Flow, HandleEventResult, TimelineEventContext, TimelineEventHandler, TimelineEventKind,
             TimelineItemPosition,
         },
         event_item::{EventItemIdentifier, RemoteEventOrigin},
+        hidden_messages::adjust_hidden_messages,
+        late_decryption::LateDecryptionTracker,
+        location::LiveLocationPendingEvents,
+        pending_edits::PendingEdits,
         polls::PollPendingEvents,
         reactions::{ReactionToggleResult, Reactions},
         read_receipts::ReadReceipts,
+        state_groups::adjust_state_groups,
         traits::RoomDataProvider,
         util::{rfind_event_by_id, rfind_event_item, RelativePosition},
         AnnotationKey, Error as TimelineError, Profile, ReactionSenderData, TimelineItem,
-        TimelineItemKind,
+        TimelineItemKind, VirtualTimelineItem,
     },
     unable_to_decrypt_hook::UtdHookManager,
 };
@@ -77,6 +87,7 @@ impl TimelineInnerState {
         is_live_timeline: bool,
         internal_id_prefix: Option<String>,
         unable_to_decrypt_hook: Option<Arc<UtdHookManager>>,
+        settings: &TimelineInnerSettings,
     ) -> Self {
         Self {
             // Upstream default capacity is currently 16, which is making
@@ -87,6 +98,7 @@ impl TimelineInnerState {
                 room_version,
                 internal_id_prefix,
                 unable_to_decrypt_hook,
+                settings,
             ),
             is_live_timeline,
         }
@@ -176,6 +188,11 @@ impl TimelineInnerState {
             read_receipts: Default::default(),
             // An event sent by ourself is never matched against push rules.
             is_highlighted: false,
+            // A local echo can't have been pinned yet.
+            is_pinned: false,
+            // Only used for remote events.
+            is_room_encrypted: false,
+            strict_shields: false,
             flow: Flow::Local { txn_id, abort_handle },
         };
 
@@ -223,6 +240,10 @@ impl TimelineInnerState {
                 continue;
             };
 
+            if let Some(event_id) = txn.items[idx].as_event().and_then(|item| item.event_id()) {
+                txn.meta.late_decryption.mark_decrypted(event_id);
+            }
+
             event.push_actions = push_rules_context.as_ref().map(|(push_rules, push_context)| {
                 push_rules.get_actions(&event.event, push_context).to_owned()
             });
@@ -416,6 +437,11 @@ impl TimelineInnerStateTransaction<'_> {
     ) -> HandleManyEventsResult {
         let mut total = HandleManyEventsResult::default();
 
+        let max_items = match position {
+            TimelineEnd::Back if self.is_live_timeline => settings.max_items,
+            _ => None,
+        };
+
         let position = match position {
             TimelineEnd::Front => TimelineItemPosition::Start { origin },
             TimelineEnd::Back => TimelineItemPosition::End { origin },
@@ -448,9 +474,58 @@ impl TimelineInnerStateTransaction<'_> {
 
         self.adjust_day_dividers(day_divider_adjuster);
 
+        if let Some(max_items) = max_items {
+            self.evict_oldest_items(max_items);
+        }
+
         total
     }
 
+    /// SC: Remove the oldest items, until there are at most `max_items` event
+    /// items in the timeline.
+    ///
+    /// The evicted events are also forgotten from the metadata, and the day
+    /// dividers are adjusted so that the new first event still has one.
+    fn evict_oldest_items(&mut self, max_items: usize) {
+        let mut num_events = self.items.iter().filter(|item| item.as_event().is_some()).count();
+        if num_events <= max_items {
+            return;
+        }
+
+        while num_events > max_items {
+            let Some(item) = self.items.pop_front() else {
+                break;
+            };
+
+            match item.kind() {
+                TimelineItemKind::Event(event) => {
+                    num_events -= 1;
+
+                    if let Some(event_id) = event.event_id() {
+                        if let Some(pos) =
+                            self.meta.all_events.iter().position(|meta| meta.event_id == event_id)
+                        {
+                            self.meta.all_events.drain(..=pos);
+                        }
+                    }
+                }
+
+                TimelineItemKind::Virtual(VirtualTimelineItem::ReadMarker) => {
+                    self.meta.has_up_to_date_read_marker_item = false;
+                }
+
+                TimelineItemKind::Virtual(_) => {}
+            }
+        }
+
+        trace!(num_events, "Evicted the oldest timeline items");
+        self.meta.has_evicted_items = true;
+
+        let mut day_divider_adjuster = DayDividerAdjuster::default();
+        day_divider_adjuster.mark_used();
+        self.adjust_day_dividers(day_divider_adjuster);
+    }
+
     /// Handle a remote event.
     ///
     /// Returns the number of timeline updates that were made.
@@ -467,7 +542,8 @@ impl TimelineInnerStateTransaction<'_> {
         {
             Ok(event) => {
                 let room_version = room_data_provider.room_version();
-                let should_add = (settings.event_filter)(&event, &room_version);
+                let should_add = (settings.event_filter)(&event, &room_version)
+                    && !self.meta.ignored_users.contains(event.sender());
                 (
                     event.event_id().to_owned(),
                     event.sender().to_owned(),
@@ -479,14 +555,17 @@ impl TimelineInnerStateTransaction<'_> {
             }
 
             Err(e) => match raw.deserialize_as::<SyncTimelineEventWithoutContent>() {
-                Ok(event) if settings.add_failed_to_parse => (
-                    event.event_id().to_owned(),
-                    event.sender().to_owned(),
-                    event.origin_server_ts(),
-                    event.transaction_id().map(ToOwned::to_owned),
-                    TimelineEventKind::failed_to_parse(event, e),
-                    true,
-                ),
+                Ok(event) if settings.add_failed_to_parse => {
+                    let should_add = !self.meta.ignored_users.contains(event.sender());
+                    (
+                        event.event_id().to_owned(),
+                        event.sender().to_owned(),
+                        event.origin_server_ts(),
+                        event.transaction_id().map(ToOwned::to_owned),
+                        TimelineEventKind::failed_to_parse(event, e),
+                        should_add,
+                    )
+                }
 
                 Ok(event) => {
                     let event_type = event.event_type();
@@ -574,6 +653,9 @@ impl TimelineInnerStateTransaction<'_> {
                 Default::default()
             },
             is_highlighted: event.push_actions.iter().any(Action::is_highlight),
+            is_pinned: room_data_provider.is_pinned_event(&event_id),
+            is_room_encrypted: room_data_provider.is_room_encrypted(),
+            strict_shields: settings.strict_shields,
             flow: Flow::Remote {
                 event_id: event_id.clone(),
                 raw_event: raw.clone(),
@@ -622,6 +704,9 @@ impl TimelineInnerStateTransaction<'_> {
         self.meta.read_receipts.clear();
         self.meta.reactions.clear();
         self.meta.fully_read_event = None;
+        self.meta.has_evicted_items = false;
+        self.meta.late_decryption.clear();
+        self.meta.pending_edits.clear();
         // We forgot about the fully read marker right above, so wait for a new one
         // before attempting to update it for each new timeline item.
         self.meta.has_up_to_date_read_marker_item = true;
@@ -642,7 +727,18 @@ impl TimelineInnerStateTransaction<'_> {
         self.meta.update_read_marker(&mut self.items);
     }
 
-    pub(super) fn commit(self) {
+    pub(super) fn commit(mut self) {
+        if self.meta.collapse_state_events {
+            // Any change to the items may have created, split or merged runs of state
+            // events, so update the state groups before publishing the changes.
+            adjust_state_groups(&mut self.items, &mut self.meta);
+        }
+
+        if self.meta.collapse_muted_users {
+            // Likewise for the runs of messages from muted users.
+            adjust_hidden_messages(&mut self.items, &mut self.meta);
+        }
+
         let Self { items, previous_meta, meta, .. } = self;
 
         // Replace the pointer to the previous meta with the new one.
@@ -748,6 +844,8 @@ pub(in crate::timeline) struct TimelineInnerMetadata {
 
     pub reactions: Reactions,
     pub poll_pending_events: PollPendingEvents,
+    /// SC: Live location updates and stops whose share wasn't handled yet.
+    pub live_location_pending_events: LiveLocationPendingEvents,
     pub fully_read_event: Option<OwnedEventId>,
 
     /// Whether we have a fully read-marker item in the timeline, that's up to
@@ -768,8 +866,48 @@ pub(in crate::timeline) struct TimelineInnerMetadata {
     /// The hook to call whenever we run into a unable-to-decrypt event.
     pub(crate) unable_to_decrypt_hook: Option<Arc<UtdHookManager>>,
 
+    /// SC: Measure of the time it takes to decrypt the unable-to-decrypt
+    /// events.
+    pub late_decryption: LateDecryptionTracker,
+
+    /// SC: The local echoes of edits that haven't been sent yet.
+    pub pending_edits: PendingEdits,
+
     /// Matrix room version of the timeline's room, or a sensible default.
     pub room_version: RoomVersionId,
+
+    /// SC: Whether runs of consecutive state events get a
+    /// [`VirtualTimelineItem::StateGroup`](crate::timeline::VirtualTimelineItem::StateGroup)
+    /// header.
+    pub collapse_state_events: bool,
+
+    /// SC: Whether runs of consecutive messages from the users in
+    /// `muted_senders` get a
+    /// [`VirtualTimelineItem::HiddenMessages`](crate::timeline::VirtualTimelineItem::HiddenMessages)
+    /// header.
+    pub collapse_muted_users: bool,
+
+    /// SC: The users muted locally in the room.
+    pub muted_senders: BTreeSet<OwnedUserId>,
+
+    /// SC: Whether day dividers are inserted between the events of different
+    /// days.
+    pub date_dividers: bool,
+
+    /// SC: The period grouped under a day divider.
+    pub divider_granularity: DividerGranularity,
+
+    /// SC: The first day of the week, when grouping by week.
+    pub first_day_of_week: Weekday,
+
+    /// SC: The users in the `m.ignored_user_list` of the account, whose events
+    /// don't get timeline items.
+    pub ignored_users: BTreeSet<OwnedUserId>,
+
+    /// SC: Whether the oldest items have been evicted because of
+    /// [`TimelineInnerSettings::max_items`], and haven't all been reloaded
+    /// since.
+    pub has_evicted_items: bool,
 }
 
 impl TimelineInnerMetadata {
@@ -777,12 +915,16 @@ impl TimelineInnerMetadata {
         room_version: RoomVersionId,
         internal_id_prefix: Option<String>,
         unable_to_decrypt_hook: Option<Arc<UtdHookManager>>,
+        settings: &TimelineInnerSettings,
     ) -> Self {
+        // The state groups, hidden messages and day dividers are maintained by
+        // the state transactions, which don't have access to the settings.
         Self {
             all_events: Default::default(),
             next_internal_id: Default::default(),
             reactions: Default::default(),
             poll_pending_events: Default::default(),
+            live_location_pending_events: Default::default(),
             fully_read_event: Default::default(),
             // It doesn't make sense to set this to false until we fill the `fully_read_event`
             // field, otherwise we'll keep on exiting early in `Self::update_read_marker`.
@@ -792,7 +934,17 @@ impl TimelineInnerMetadata {
             in_flight_reaction: Default::default(),
             room_version,
             unable_to_decrypt_hook,
+            late_decryption: Default::default(),
+            pending_edits: Default::default(),
             internal_id_prefix,
+            collapse_state_events: settings.collapse_state_events,
+            collapse_muted_users: settings.collapse_muted_users,
+            muted_senders: Default::default(),
+            date_dividers: settings.date_dividers,
+            divider_granularity: settings.divider_granularity,
+            first_day_of_week: settings.first_day_of_week,
+            ignored_users: Default::default(),
+            has_evicted_items: false,
         }
     }
 
@@ -0,0 +1,81 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: Measure of the time it takes for events that were added to the
+//! timeline as unable-to-decrypt to be decrypted, see
+//! [`Timeline::on_late_decryption`](super::Timeline::on_late_decryption).
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use ruma::{EventId, OwnedEventId};
+
+use super::EventItemIdentifier;
+
+/// SC: A function called when an event that was added to the timeline as
+/// unable-to-decrypt gets decrypted, with the time it took.
+pub type LateDecryptionHandler = Box<dyn Fn(EventItemIdentifier, Duration) + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub(super) struct LateDecryptionTracker {
+    handler: Option<Arc<LateDecryptionHandler>>,
+
+    /// When the events that are still unable-to-decrypt were added to the
+    /// timeline. Only tracked while there's a handler.
+    utd_since: HashMap<OwnedEventId, Instant>,
+}
+
+impl LateDecryptionTracker {
+    pub(super) fn set_handler(&mut self, handler: LateDecryptionHandler) {
+        self.handler = Some(Arc::new(handler));
+    }
+
+    /// Remember that the event was added to the timeline as
+    /// unable-to-decrypt, unless it already was.
+    pub(super) fn mark_utd(&mut self, event_id: &EventId) {
+        if self.handler.is_some() {
+            self.utd_since.entry(event_id.to_owned()).or_insert_with(Instant::now);
+        }
+    }
+
+    /// Report that the event has been decrypted, if it was previously marked
+    /// as unable-to-decrypt.
+    pub(super) fn mark_decrypted(&mut self, event_id: &EventId) {
+        let Some(utd_since) = self.utd_since.remove(event_id) else {
+            return;
+        };
+
+        if let Some(handler) = &self.handler {
+            handler(EventItemIdentifier::EventId(event_id.to_owned()), utd_since.elapsed());
+        }
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.utd_since.clear();
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+impl fmt::Debug for LateDecryptionTracker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LateDecryptionTracker")
+            .field("has_handler", &self.handler.is_some())
+            .field("utd_since", &self.utd_since)
+            .finish()
+    }
+}
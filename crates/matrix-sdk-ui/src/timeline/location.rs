@@ -0,0 +1,395 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module handles rendering of MSC3488 static locations and MSC3489 live
+//! location sharing in the timeline.
+
+use std::{collections::HashMap, time::Duration};
+
+use ruma::{
+    events::{
+        beacon_info::BeaconInfoEventContent,
+        location::{AssetType, LocationContent},
+        room::message::LocationMessageEventContent,
+    },
+    EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedUserId, UserId,
+};
+
+/// SC: A location, parsed from an `m.location` message or a live location
+/// update.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location {
+    /// The `geo:` URI of the location, as it was sent.
+    pub geo_uri: String,
+
+    /// The latitude, in degrees.
+    pub latitude: f64,
+
+    /// The longitude, in degrees.
+    pub longitude: f64,
+
+    /// The uncertainty of the location, in meters, if known.
+    pub uncertainty: Option<f64>,
+
+    /// The description of the location, if any.
+    pub description: Option<String>,
+
+    /// What the location is about.
+    pub asset: LocationAssetType,
+}
+
+impl Location {
+    /// Parse the location of an `m.location` message.
+    ///
+    /// The MSC3488 `m.location` content block is preferred, then the
+    /// `geo_uri` field, and finally a `geo:` URI found in the fallback body.
+    /// Returns `None` if none of them contains a valid `geo:` URI.
+    pub(super) fn from_message_content(content: &LocationMessageEventContent) -> Option<Self> {
+        let description = content.location.as_ref().and_then(|l| l.description.clone());
+        let asset = content.asset.as_ref().map(|a| (&a.type_).into()).unwrap_or_default();
+
+        let candidates = content
+            .location
+            .as_ref()
+            .map(|l| l.uri.as_str())
+            .into_iter()
+            .chain([content.geo_uri.as_str()])
+            .chain(geo_uri_in_body(&content.body));
+
+        candidates.find_map(|uri| Self::parse(uri, description.clone(), asset))
+    }
+
+    /// Parse the location of a live location update.
+    pub(super) fn from_location_content(
+        content: &LocationContent,
+        asset: LocationAssetType,
+    ) -> Option<Self> {
+        Self::parse(&content.uri, content.description.clone(), asset)
+    }
+
+    fn parse(geo_uri: &str, description: Option<String>, asset: LocationAssetType) -> Option<Self> {
+        let (latitude, longitude, uncertainty) = parse_geo_uri(geo_uri)?;
+        Some(Self {
+            geo_uri: geo_uri.to_owned(),
+            latitude,
+            longitude,
+            uncertainty,
+            description,
+            asset,
+        })
+    }
+}
+
+/// SC: What a shared location is about.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LocationAssetType {
+    /// The location of the sender.
+    #[default]
+    Sender,
+
+    /// A location picked by the sender, e.g. a meeting point.
+    Pin,
+}
+
+impl From<&AssetType> for LocationAssetType {
+    fn from(value: &AssetType) -> Self {
+        match value {
+            AssetType::Pin => Self::Pin,
+            // Unknown asset types default to the sender, like a missing one.
+            _ => Self::Sender,
+        }
+    }
+}
+
+/// Parse a RFC 5870 `geo:` URI into its latitude, longitude and uncertainty.
+///
+/// Only the WGS-84 coordinate reference system is supported, which is the
+/// default one and the one used by all Matrix clients.
+fn parse_geo_uri(uri: &str) -> Option<(f64, f64, Option<f64>)> {
+    let prefix = uri.get(..4)?;
+    if !prefix.eq_ignore_ascii_case("geo:") {
+        return None;
+    }
+
+    let mut parts = uri[4..].split(';');
+    let mut coordinates = parts.next()?.split(',');
+
+    let latitude: f64 = coordinates.next()?.trim().parse().ok()?;
+    let longitude: f64 = coordinates.next()?.trim().parse().ok()?;
+    // An optional altitude is allowed, but nothing else.
+    if coordinates.nth(1).is_some() {
+        return None;
+    }
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return None;
+    }
+
+    let mut uncertainty = None;
+    for param in parts {
+        let Some((key, value)) = param.split_once('=') else { continue };
+        if key.eq_ignore_ascii_case("crs") && !value.eq_ignore_ascii_case("wgs84") {
+            return None;
+        }
+        if key.eq_ignore_ascii_case("u") {
+            uncertainty = value.parse().ok().filter(|u: &f64| *u >= 0.0);
+        }
+    }
+
+    Some((latitude, longitude, uncertainty))
+}
+
+/// Find the first `geo:` URI in the fallback body of a location message.
+fn geo_uri_in_body(body: &str) -> Option<&str> {
+    let start = body.to_ascii_lowercase().find("geo:")?;
+    body[start..].split_whitespace().next()
+}
+
+/// SC: Holds the state of a live location share.
+///
+/// This struct should be created for each `beacon_info` event that starts a
+/// live location share, and then updated whenever handling a `beacon` event
+/// relating to it or a `beacon_info` event from the same user that stops it.
+#[derive(Clone, Debug)]
+pub struct LiveLocationState {
+    description: Option<String>,
+    asset: LocationAssetType,
+    started_at: MilliSecondsSinceUnixEpoch,
+    timeout: Duration,
+    stopped_at: Option<MilliSecondsSinceUnixEpoch>,
+    last_location: Option<LiveLocationUpdate>,
+}
+
+/// SC: The latest location received for a live location share.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiveLocationUpdate {
+    /// The location.
+    pub location: Location,
+
+    /// When the location was measured, according to the sender.
+    pub timestamp: MilliSecondsSinceUnixEpoch,
+}
+
+/// SC: The state of a live location share.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiveLocationStatus {
+    /// The share has begun, but no location was received yet.
+    Started,
+
+    /// At least one location was received and the share wasn't stopped.
+    ///
+    /// The share might have timed out though, see
+    /// [`LiveLocationState::is_live()`].
+    Updated,
+
+    /// The sender stopped sharing their location.
+    Stopped,
+}
+
+impl LiveLocationState {
+    pub(super) fn new(content: &BeaconInfoEventContent) -> Self {
+        Self {
+            description: content.description.clone(),
+            asset: (&content.asset.type_).into(),
+            started_at: content.ts,
+            timeout: content.timeout,
+            stopped_at: None,
+            last_location: None,
+        }
+    }
+
+    /// Apply a location update, unless it's older than the current one.
+    pub(super) fn update(
+        &self,
+        content: &LocationContent,
+        timestamp: MilliSecondsSinceUnixEpoch,
+    ) -> Self {
+        let mut clone = self.clone();
+        clone.apply_update(content, timestamp);
+        clone
+    }
+
+    fn apply_update(&mut self, content: &LocationContent, timestamp: MilliSecondsSinceUnixEpoch) {
+        if self.last_location.as_ref().is_some_and(|last| last.timestamp > timestamp) {
+            return;
+        }
+
+        if let Some(location) = Location::from_location_content(content, self.asset) {
+            self.last_location = Some(LiveLocationUpdate { location, timestamp });
+        }
+    }
+
+    pub(super) fn stop(&self, timestamp: MilliSecondsSinceUnixEpoch) -> Result<Self, ()> {
+        if self.stopped_at.is_none() {
+            let mut clone = self.clone();
+            clone.stopped_at = Some(timestamp);
+            Ok(clone)
+        } else {
+            Err(())
+        }
+    }
+
+    /// The description of the share, if any.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// What the shared location is about.
+    pub fn asset(&self) -> LocationAssetType {
+        self.asset
+    }
+
+    /// When the share started, according to the sender.
+    pub fn started_at(&self) -> MilliSecondsSinceUnixEpoch {
+        self.started_at
+    }
+
+    /// When the share times out if it isn't stopped before.
+    pub fn expires_at(&self) -> MilliSecondsSinceUnixEpoch {
+        let timeout = self.timeout.as_millis().try_into().unwrap_or(u64::MAX);
+        let expires_at = u64::from(self.started_at.get()).saturating_add(timeout);
+        MilliSecondsSinceUnixEpoch(expires_at.try_into().unwrap_or(ruma::UInt::MAX))
+    }
+
+    /// When the sender stopped the share, if they did.
+    pub fn stopped_at(&self) -> Option<MilliSecondsSinceUnixEpoch> {
+        self.stopped_at
+    }
+
+    /// The latest location received, if any.
+    pub fn last_location(&self) -> Option<&LiveLocationUpdate> {
+        self.last_location.as_ref()
+    }
+
+    /// The state of the share.
+    pub fn status(&self) -> LiveLocationStatus {
+        if self.stopped_at.is_some() {
+            LiveLocationStatus::Stopped
+        } else if self.last_location.is_some() {
+            LiveLocationStatus::Updated
+        } else {
+            LiveLocationStatus::Started
+        }
+    }
+
+    /// Whether the share is still live, i.e. it wasn't stopped and didn't
+    /// time out.
+    pub fn is_live(&self) -> bool {
+        self.stopped_at.is_none() && MilliSecondsSinceUnixEpoch::now() < self.expires_at()
+    }
+}
+
+/// Acts as a cache for location updates and stops handled before the
+/// `beacon_info` event that started the share, e.g. when paginating backwards.
+#[derive(Clone, Debug, Default)]
+pub(super) struct LiveLocationPendingEvents {
+    pending_updates: HashMap<OwnedEventId, (LocationContent, MilliSecondsSinceUnixEpoch)>,
+    pending_stops: HashMap<OwnedUserId, MilliSecondsSinceUnixEpoch>,
+}
+
+impl LiveLocationPendingEvents {
+    pub(super) fn add_update(
+        &mut self,
+        start_id: &EventId,
+        content: &LocationContent,
+        timestamp: MilliSecondsSinceUnixEpoch,
+    ) {
+        let is_newer = self.pending_updates.get(start_id).map_or(true, |(_, ts)| *ts <= timestamp);
+        if is_newer {
+            self.pending_updates.insert(start_id.to_owned(), (content.clone(), timestamp));
+        }
+    }
+
+    pub(super) fn add_stop(&mut self, sender: &UserId, timestamp: MilliSecondsSinceUnixEpoch) {
+        // When paginating backwards, the oldest stop is the one that ends the
+        // share that will be handled next.
+        let stop = self.pending_stops.entry(sender.to_owned()).or_insert(timestamp);
+        *stop = (*stop).min(timestamp);
+    }
+
+    /// Applies the update and stop present in the cache that belong to the
+    /// share started by the given event to the given state.
+    pub(super) fn apply(
+        &mut self,
+        start_event_id: &EventId,
+        sender: &UserId,
+        state: &mut LiveLocationState,
+    ) {
+        if let Some((content, timestamp)) = self.pending_updates.remove(start_event_id) {
+            state.apply_update(&content, timestamp);
+        }
+        if let Some(stopped_at) = self.pending_stops.get(sender) {
+            if *stopped_at >= state.started_at {
+                state.stopped_at = Some(*stopped_at);
+                self.pending_stops.remove(sender);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches2::assert_let;
+    use ruma::events::room::message::LocationMessageEventContent;
+
+    use super::{parse_geo_uri, Location, LocationAssetType};
+
+    #[test]
+    fn test_parse_geo_uri() {
+        assert_eq!(parse_geo_uri("geo:51.5008,0.1247"), Some((51.5008, 0.1247, None)));
+        assert_eq!(parse_geo_uri("GEO:-33.9,18.4,12;u=35"), Some((-33.9, 18.4, Some(35.0))));
+        assert_eq!(parse_geo_uri("geo:48.2,16.3;crs=wgs84;u=10"), Some((48.2, 16.3, Some(10.0))));
+
+        assert_eq!(parse_geo_uri("geo:91,0"), None);
+        assert_eq!(parse_geo_uri("geo:0,181"), None);
+        assert_eq!(parse_geo_uri("geo:1,2,3,4"), None);
+        assert_eq!(parse_geo_uri("geo:1,2;crs=Moon-2011"), None);
+        assert_eq!(parse_geo_uri("https://example.org"), None);
+        assert_eq!(parse_geo_uri("geo:"), None);
+    }
+
+    #[test]
+    fn test_location_from_fallback_body() {
+        let content = LocationMessageEventContent::new(
+            "Location was shared at geo:51.5008,0.1247;u=35 as of 2024-01-01".to_owned(),
+            "not a geo uri".to_owned(),
+        );
+
+        assert_let!(Some(location) = Location::from_message_content(&content));
+        assert_eq!(location.geo_uri, "geo:51.5008,0.1247;u=35");
+        assert_eq!(location.latitude, 51.5008);
+        assert_eq!(location.longitude, 0.1247);
+        assert_eq!(location.uncertainty, Some(35.0));
+        assert_eq!(location.asset, LocationAssetType::Sender);
+    }
+
+    #[test]
+    fn test_location_from_msc3488_content() {
+        let content: LocationMessageEventContent = serde_json::from_value(serde_json::json!({
+            "msgtype": "m.location",
+            "body": "Our meeting point",
+            "geo_uri": "geo:0,0",
+            "org.matrix.msc3488.location": {
+                "uri": "geo:48.2,16.3",
+                "description": "Our meeting point",
+            },
+            "org.matrix.msc3488.asset": { "type": "m.pin" },
+        }))
+        .unwrap();
+
+        assert_let!(Some(location) = Location::from_message_content(&content));
+        assert_eq!(location.geo_uri, "geo:48.2,16.3");
+        assert_eq!(location.description.as_deref(), Some("Our meeting point"));
+        assert_eq!(location.asset, LocationAssetType::Pin);
+    }
+}
@@ -18,27 +18,33 @@
 
 use std::{path::PathBuf, pin::Pin, sync::Arc, task::Poll};
 
+use async_stream::stream;
 use eyeball_im::VectorDiff;
 use futures_core::Stream;
+use futures_util::{future::Either, pin_mut, StreamExt};
 use imbl::Vector;
 use matrix_sdk::{
     attachment::AttachmentConfig,
     event_cache::{EventCacheDropHandles, RoomEventCache},
     event_handler::EventHandlerHandle,
     executor::JoinHandle,
-    room::{Receipts, Room},
+    room::{MemberLoadProgress, Receipts, Room},
     send_queue::{AbortSendHandle, RoomSendQueueError},
     Client, Result,
 };
-use matrix_sdk_base::RoomState;
+use matrix_sdk_base::{store::ComposerDraftType, RoomState};
 use mime::Mime;
 use pin_project_lite::pin_project;
 use ruma::{
-    api::client::receipt::create_receipt::v3::ReceiptType,
+    api::client::{error::ErrorKind, receipt::create_receipt::v3::ReceiptType},
     events::{
-        poll::unstable_start::{
-            ReplacementUnstablePollStartEventContent, UnstablePollStartContentBlock,
-            UnstablePollStartEventContent,
+        poll::{
+            unstable_end::UnstablePollEndEventContent,
+            unstable_response::UnstablePollResponseEventContent,
+            unstable_start::{
+                ReplacementUnstablePollStartEventContent, UnstablePollStartContentBlock,
+                UnstablePollStartEventContent,
+            },
         },
         reaction::ReactionEventContent,
         receipt::{Receipt, ReceiptThread},
@@ -48,14 +54,17 @@ use ruma::{
                 AddMentions, ForwardThread, OriginalRoomMessageEvent, ReplacementMetadata,
                 RoomMessageEventContentWithoutRelation,
             },
+            pinned_events::RoomPinnedEventsEventContent,
             redaction::RoomRedactionEventContent,
         },
         AnyMessageLikeEventContent, AnySyncTimelineEvent,
     },
+    serde::Raw,
     uint, EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedTransactionId, RoomVersionId,
     TransactionId, UserId,
 };
 use thiserror::Error;
+use tokio::time::sleep;
 use tracing::{error, instrument, trace, warn};
 
 use self::{
@@ -64,20 +73,28 @@ use self::{
     futures::SendAttachment,
     util::rfind_event_item,
 };
+use crate::frequent_reactions;
 
 mod builder;
 mod day_dividers;
+mod drafts;
 mod error;
 mod event_handler;
 mod event_item;
 pub mod event_type_filter;
 pub mod futures;
+mod hidden_messages;
 mod inner;
 mod item;
+mod late_decryption;
+mod location;
 mod pagination;
+mod pending_edits;
 mod polls;
 mod reactions;
 mod read_receipts;
+mod state_groups;
+mod subscription;
 #[cfg(test)]
 mod tests;
 #[cfg(feature = "e2e-encryption")]
@@ -85,29 +102,42 @@ mod to_device;
 mod traits;
 mod util;
 mod virtual_item;
+mod visibility;
+
+/// SC: Re-exported for [`TimelineBuilder::first_day_of_week`].
+pub use chrono::Weekday;
 
 pub use self::{
     builder::TimelineBuilder,
+    day_dividers::DividerGranularity,
+    drafts::RestoredComposerDraft,
     error::{Error, PaginationError, UnsupportedEditItem, UnsupportedReplyItem},
     event_item::{
-        AnyOtherFullStateEventContent, BundledReactions, EncryptedMessage, EventItemOrigin,
-        EventSendState, EventTimelineItem, InReplyToDetails, MemberProfileChange, MembershipChange,
-        Message, OtherState, Profile, ReactionGroup, RepliedToEvent, RoomMembershipChange, Sticker,
-        TimelineDetails, TimelineItemContent,
+        AnyOtherFullStateEventContent, BundledReactions, EncryptedMessage, EventItemIdentifier,
+        EventItemOrigin, EventSendState, EventTimelineItem, InReplyToDetails, MemberProfileChange,
+        MembershipChange, Message, OtherState, OwnDeliveryStatus, PinnedEventsChange, Profile,
+        ReactionGroup, ReactionKey, RepliedToEvent, RoomMembershipChange, SendError, SendErrorKind,
+        ShieldReason, Sticker, TimelineDetails, TimelineItemContent, TimestampInfo,
     },
     event_type_filter::TimelineEventTypeFilter,
     inner::default_event_filter,
     item::{TimelineItem, TimelineItemKind},
-    pagination::LiveBackPaginationStatus,
+    late_decryption::LateDecryptionHandler,
+    location::{
+        LiveLocationState, LiveLocationStatus, LiveLocationUpdate, Location, LocationAssetType,
+    },
+    pagination::{ForwardPaginationStatus, LiveBackPaginationStatus},
     polls::PollResult,
     reactions::ReactionSenderData,
+    subscription::TimelineSubscription,
     traits::RoomExt,
-    virtual_item::VirtualTimelineItem,
+    virtual_item::{StateGroupSummary, VirtualTimelineItem},
 };
 use self::{
     inner::{ReactionAction, TimelineInner},
     reactions::ReactionToggleResult,
     util::rfind_event_by_id,
+    visibility::VisibleItems,
 };
 
 /// A high-level view into a regular¹ room's contents.
@@ -126,6 +156,10 @@ pub struct Timeline {
 
     /// References to long-running tasks held by the timeline.
     drop_handle: Arc<TimelineDropHandle>,
+
+    /// SC: The items currently visible on screen, see
+    /// [`Timeline::mark_visible`].
+    visible_items: std::sync::Mutex<VisibleItems>,
 }
 
 // Implements hash etc
@@ -150,6 +184,11 @@ pub enum TimelineFocus {
 
     /// Focus on a specific event, e.g. after clicking a permalink.
     Event { target: OwnedEventId, num_context_events: u16 },
+
+    /// SC: Don't load anything from the server or from sync: the timeline
+    /// only contains the events imported with [`Timeline::import_events`],
+    /// e.g. to inspect a backup.
+    Detached,
 }
 
 impl Timeline {
@@ -243,6 +282,38 @@ impl Timeline {
         Some(item.to_owned())
     }
 
+    /// SC: Import previously exported events into this timeline, without
+    /// sending them to the server nor writing them to the store.
+    ///
+    /// This is only possible for a timeline built with
+    /// [`TimelineFocus::Detached`], otherwise [`Error::NotDetached`] is
+    /// returned. The timeline is rebuilt with all the events imported so far,
+    /// ordered by their `origin_server_ts`. Events that were already imported,
+    /// or that don't have an event ID, are ignored.
+    pub async fn import_events(&self, events: Vec<Raw<AnySyncTimelineEvent>>) -> Result<(), Error> {
+        self.inner.import_events(events).await
+    }
+
+    /// SC: Set a function called when an item that was added to the timeline
+    /// as unable-to-decrypt gets decrypted, with the time it took.
+    ///
+    /// It is called at most once per item, and only for the items that were
+    /// unable-to-decrypt while the handler was set. It is called while the
+    /// timeline is being updated, so it must not block.
+    pub async fn on_late_decryption(&self, handler: LateDecryptionHandler) {
+        self.inner.set_late_decryption_handler(handler).await;
+    }
+
+    /// SC: Search the text messages currently loaded in the timeline, without
+    /// hitting the server.
+    ///
+    /// The search is case-insensitive and accent-insensitive. Redacted items
+    /// and messages that aren't text, notices or emotes are skipped. The
+    /// identifiers of the matching items are returned newest first.
+    pub async fn search_local(&self, query: &str) -> Vec<EventItemIdentifier> {
+        self.inner.search_local(query).await
+    }
+
     /// Get the latest of the timeline's event items.
     pub async fn latest_event(&self) -> Option<EventTimelineItem> {
         if self.inner.is_live().await {
@@ -252,31 +323,307 @@ impl Timeline {
         }
     }
 
+    /// SC: Get the events pinned in the room as timeline items, in the order of
+    /// the room's pinned events.
+    ///
+    /// Events that are in this timeline already are reused, the other ones are
+    /// fetched from the server. Pinned events that the server doesn't know
+    /// about, or that can't be represented as a timeline item, are skipped.
+    /// Redacted pinned events are returned with
+    /// [`TimelineItemContent::RedactedMessage`].
+    ///
+    /// Events being pinned or unpinned with [`Self::pin_event`] and
+    /// [`Self::unpin_event`] are reflected before the server confirms it.
+    pub async fn pinned_timeline(&self) -> Result<Vec<EventTimelineItem>, Error> {
+        let room = self.room();
+        let mut items = Vec::new();
+
+        for event_id in self.inner.pinned_event_ids() {
+            if let Some(item) = self.item_by_event_id(&event_id).await {
+                items.push(item);
+                continue;
+            }
+
+            let event = match room.event(&event_id).await {
+                Ok(event) => event,
+                Err(err) if err.client_api_error_kind() == Some(&ErrorKind::NotFound) => {
+                    warn!(%event_id, "Pinned event not found on the server");
+                    continue;
+                }
+                Err(err) => return Err(Error::FailedToFetchPinnedEvent(err)),
+            };
+
+            match EventTimelineItem::from_standalone_event(
+                room.client(),
+                room.room_id(),
+                event.into(),
+            )
+            .await
+            {
+                Some(item) => items.push(item),
+                None => warn!(%event_id, "Pinned event can't be represented as a timeline item"),
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// SC: Get a stream of the events pinned in the room, as returned by
+    /// [`Self::pinned_timeline`].
+    ///
+    /// The current pinned events are emitted first, then the whole list is
+    /// emitted again every time the room's pinned events change.
+    pub fn subscribe_pinned_timeline(
+        &self,
+    ) -> impl Stream<Item = Result<Vec<EventTimelineItem>, Error>> + '_ {
+        let mut pinned_event_ids = self.inner.subscribe_pinned_event_ids();
+
+        stream! {
+            yield self.pinned_timeline().await;
+
+            while pinned_event_ids.next().await.is_some() {
+                yield self.pinned_timeline().await;
+            }
+        }
+    }
+
+    /// SC: Pin the event with the given ID, by sending an updated
+    /// `m.room.pinned_events` state event.
+    ///
+    /// The timeline item and [`Self::subscribe_pinned_timeline`] reflect the
+    /// change right away, and are rolled back if the request fails.
+    ///
+    /// Returns `false` if the event was already pinned.
+    pub async fn pin_event(&self, event_id: &EventId) -> Result<bool, Error> {
+        let mut pinned_event_ids = self.inner.pinned_event_ids();
+        if pinned_event_ids.iter().any(|id| id == event_id) {
+            return Ok(false);
+        }
+
+        pinned_event_ids.push(event_id.to_owned());
+        self.update_pinned_events(pinned_event_ids).await?;
+        Ok(true)
+    }
+
+    /// SC: Pin the event with the given ID like [`Self::pin_event`], keeping at
+    /// most `max` pinned events.
+    ///
+    /// If pinning the event would exceed `max`, the oldest pinned events are
+    /// unpinned in the same request. At least one event is always kept pinned,
+    /// even if `max` is zero.
+    ///
+    /// Returns the IDs of the unpinned events, oldest first. Nothing is sent
+    /// if the event was already pinned.
+    pub async fn pin_event_with_cap(
+        &self,
+        event_id: &EventId,
+        max: usize,
+    ) -> Result<Vec<OwnedEventId>, Error> {
+        let mut pinned_event_ids = self.inner.pinned_event_ids();
+        if pinned_event_ids.iter().any(|id| id == event_id) {
+            return Ok(Vec::new());
+        }
+
+        let excess = (pinned_event_ids.len() + 1).saturating_sub(max.max(1));
+        let evicted: Vec<_> = pinned_event_ids.drain(..excess).collect();
+
+        pinned_event_ids.push(event_id.to_owned());
+        self.update_pinned_events(pinned_event_ids).await?;
+        Ok(evicted)
+    }
+
+    /// SC: Pin the latest event of the timeline that was echoed back by the
+    /// server, like [`Self::pin_event`].
+    ///
+    /// Returns `false` if the event was already pinned, and
+    /// [`Error::RemoteEventNotInTimeline`] if the timeline has no such event.
+    pub async fn pin_latest(&self) -> Result<bool, Error> {
+        let event_id = self
+            .inner
+            .items()
+            .await
+            .iter()
+            .rev()
+            .find_map(|item| item.as_event()?.event_id().map(ToOwned::to_owned))
+            .ok_or(Error::RemoteEventNotInTimeline)?;
+
+        self.pin_event(&event_id).await
+    }
+
+    /// SC: Unpin the event with the given ID, by sending an updated
+    /// `m.room.pinned_events` state event.
+    ///
+    /// The timeline item and [`Self::subscribe_pinned_timeline`] reflect the
+    /// change right away, and are rolled back if the request fails.
+    ///
+    /// Returns `false` if the event wasn't pinned.
+    pub async fn unpin_event(&self, event_id: &EventId) -> Result<bool, Error> {
+        let mut pinned_event_ids = self.inner.pinned_event_ids();
+        let Some(idx) = pinned_event_ids.iter().position(|id| id == event_id) else {
+            return Ok(false);
+        };
+
+        pinned_event_ids.remove(idx);
+        self.update_pinned_events(pinned_event_ids).await?;
+        Ok(true)
+    }
+
+    /// Optimistically apply the new list of pinned events, then send it to
+    /// the server, rolling back to the room's pinned events on failure.
+    async fn update_pinned_events(&self, pinned_event_ids: Vec<OwnedEventId>) -> Result<(), Error> {
+        self.inner.update_pinned_events(&pinned_event_ids).await;
+
+        let content = RoomPinnedEventsEventContent::new(pinned_event_ids);
+        if let Err(err) = self.room().send_state_event(content).await {
+            warn!("Failed to update the pinned events, rolling back: {err}");
+            self.inner.update_pinned_events(&self.room().pinned_event_ids()).await;
+            return Err(Error::FailedToUpdatePinnedEvents(err));
+        }
+
+        Ok(())
+    }
+
     /// Get the current timeline items, and a stream of changes.
     ///
     /// You can poll this stream to receive updates. See
     /// [`futures_util::StreamExt`] for a high-level API on top of [`Stream`].
+    ///
+    /// SC: The changes are buffered, but the buffer is bounded. If the stream
+    /// isn't polled fast enough and changes are dropped, a
+    /// [`VectorDiff::Reset`] with the full current items is emitted instead
+    /// of the missed changes, so applying every diff always results in the
+    /// same items as the timeline. See also [`Self::subscription`].
     pub async fn subscribe(
         &self,
     ) -> (Vector<Arc<TimelineItem>>, impl Stream<Item = VectorDiff<Arc<TimelineItem>>>) {
         let (items, stream) = self.inner.subscribe().await;
+
+        let (items, stream) = match self.inner.content_transform() {
+            Some(transform) => {
+                let (items, stream) = (items, stream)
+                    .filter_map(move |item| transform_item_content(item, &*transform));
+                (items, Either::Left(stream))
+            }
+            None => (items, Either::Right(stream)),
+        };
+
         let stream = TimelineStream::new(stream, self.drop_handle.clone());
         (items, stream)
     }
 
+    /// SC: Get a [`TimelineSubscription`] to the timeline items.
+    ///
+    /// This is like [`Self::subscribe`], but the current items and the stream
+    /// of changes are kept together in a named type, which documents how
+    /// lagging behind is handled.
+    pub async fn subscription(&self) -> TimelineSubscription {
+        let (items, stream) = self.subscribe().await;
+        TimelineSubscription::new(items, stream)
+    }
+
     /// Get the current timeline items, and a batched stream of changes.
     ///
     /// In contrast to [`subscribe`](Self::subscribe), this stream can yield
     /// multiple diffs at once. The batching is done such that no arbitrary
     /// delays are added.
+    ///
+    /// SC: Like with [`subscribe`](Self::subscribe), lagging behind results in
+    /// a [`VectorDiff::Reset`] instead of missed changes.
     pub async fn subscribe_batched(
         &self,
     ) -> (Vector<Arc<TimelineItem>>, impl Stream<Item = Vec<VectorDiff<Arc<TimelineItem>>>>) {
         let (items, stream) = self.inner.subscribe_batched().await;
+
+        let (items, stream) = match self.inner.content_transform() {
+            Some(transform) => {
+                let (items, stream) = (items, stream)
+                    .filter_map(move |item| transform_item_content(item, &*transform));
+                (items, Either::Left(stream))
+            }
+            None => (items, Either::Right(stream)),
+        };
+
         let stream = TimelineStream::new(stream, self.drop_handle.clone());
         (items, stream)
     }
 
+    /// SC: Get a stream of the aggregated reactions of the given event, e.g.
+    /// for an emoji summary bar.
+    ///
+    /// Each summary contains the key, the number of reactions and whether the
+    /// local user reacted, for every key, in the order in which the keys were
+    /// first used. The current summary is emitted first, then a new one is
+    /// emitted only when the reactions actually change, not on every update of
+    /// the event (read receipts, edits, …).
+    ///
+    /// The summary is empty while the event isn't in the timeline.
+    pub async fn reactions_summary(
+        &self,
+        event_id: &EventId,
+    ) -> impl Stream<Item = Vec<(String, usize, bool)>> {
+        let (mut items, stream) = self.subscribe_batched().await;
+        let own_user_id = self.room().own_user_id().to_owned();
+        let event_id = event_id.to_owned();
+
+        let summarize = move |items: &Vector<Arc<TimelineItem>>| {
+            rfind_event_by_id(items, &event_id)
+                .map(|(_, item)| reactions_summary(item.reactions(), &own_user_id))
+                .unwrap_or_default()
+        };
+
+        stream! {
+            pin_mut!(stream);
+
+            let mut summary = summarize(&items);
+            yield summary.clone();
+
+            while let Some(diffs) = stream.next().await {
+                for diff in diffs {
+                    diff.apply(&mut items);
+                }
+
+                let new_summary = summarize(&items);
+                if new_summary != summary {
+                    summary = new_summary;
+                    yield summary.clone();
+                }
+            }
+        }
+    }
+
+    /// SC: Get a stream of the index of the read marker in the timeline items,
+    /// e.g. to show a button to jump to the first unread message.
+    ///
+    /// The current index is emitted first, then a new one is emitted only
+    /// when the read marker moves, or `None` when it's removed, e.g. because
+    /// it would be at the end of the timeline.
+    pub async fn read_marker_position(&self) -> impl Stream<Item = Option<usize>> {
+        let (mut items, stream) = self.subscribe_batched().await;
+
+        let position = |items: &Vector<Arc<TimelineItem>>| {
+            items.iter().rposition(|item| item.is_read_marker())
+        };
+
+        stream! {
+            pin_mut!(stream);
+
+            let mut current = position(&items);
+            yield current;
+
+            while let Some(diffs) = stream.next().await {
+                for diff in diffs {
+                    diff.apply(&mut items);
+                }
+
+                let new_position = position(&items);
+                if new_position != current {
+                    current = new_position;
+                    yield current;
+                }
+            }
+        }
+    }
+
     /// Send a message to the room, and add it to the timeline as a local echo.
     ///
     /// For simplicity, this method doesn't currently allow custom message
@@ -469,19 +816,115 @@ impl Timeline {
         Ok(())
     }
 
+    /// SC: Vote in the poll started by the given event.
+    ///
+    /// The vote is added to the results of the poll right away, with the local
+    /// echo of the response. Sending an empty list of answers removes the
+    /// vote.
+    pub async fn vote_poll(
+        &self,
+        poll_start_id: &EventId,
+        answer_ids: Vec<String>,
+    ) -> Result<(), SendEventError> {
+        let content = UnstablePollResponseEventContent::new(answer_ids, poll_start_id.to_owned());
+        self.send(AnyMessageLikeEventContent::UnstablePollResponse(content)).await?;
+        Ok(())
+    }
+
+    /// SC: End the poll started by the given event.
+    ///
+    /// The poll is marked as ended right away, with the local echo of the end
+    /// event. Only the sender of the poll and moderators are allowed to end
+    /// it.
+    pub async fn end_poll(&self, poll_start_id: &EventId) -> Result<(), SendEventError> {
+        let content =
+            UnstablePollEndEventContent::new("The poll has ended", poll_start_id.to_owned());
+        self.send(AnyMessageLikeEventContent::UnstablePollEnd(content)).await?;
+        Ok(())
+    }
+
+    /// SC: Cancel editing the given event.
+    ///
+    /// This discards the composer draft of the edit, if any, and aborts
+    /// sending the edits of this event that haven't been sent yet. The local
+    /// echoes of the aborted edits are removed, which restores the previous
+    /// content of `edit_item` with a [`VectorDiff::Set`].
+    ///
+    /// Returns whether an edit has been aborted. Edits that are already being
+    /// sent can't be aborted anymore.
+    #[instrument(skip_all)]
+    pub async fn cancel_edit(&self, edit_item: &EventTimelineItem) -> Result<bool, Error> {
+        let Some(event_id) = edit_item.event_id() else {
+            // Only events with an event ID can be edited.
+            return Ok(false);
+        };
+
+        let room = self.room();
+        let draft = room.load_composer_draft().await.map_err(Error::FailedToClearComposerDraft)?;
+
+        let is_draft_of_edit = draft.is_some_and(|draft| {
+            matches!(
+                draft.draft_type,
+                ComposerDraftType::Edit { event_id: edited } if edited == event_id.as_str()
+            )
+        });
+
+        if is_draft_of_edit {
+            room.clear_composer_draft().await.map_err(Error::FailedToClearComposerDraft)?;
+        }
+
+        let mut aborted = false;
+
+        for handle in self.inner.pending_edit_abort_handles(event_id).await {
+            aborted |= handle.abort().await;
+        }
+
+        Ok(aborted)
+    }
+
     /// Toggle a reaction on an event
     ///
     /// Adds or redacts a reaction based on the state of the reaction at the
-    /// time it is called.
+    /// time it is called: if the local user hasn't reacted to the event with
+    /// the annotation's key yet, a reaction is sent, otherwise their existing
+    /// reaction is redacted. The change is applied to the local
+    /// [`ReactionGroup`] right away, and reconciled with the server's response
+    /// afterwards.
     ///
     /// When redacting an event, the redaction reason is not sent.
     ///
     /// Ensures that only one reaction is sent at a time to avoid race
     /// conditions and spamming the homeserver with requests.
+    ///
+    /// SC: The request is only sent once the reaction hasn't been toggled for
+    /// [`TimelineBuilder::reaction_debounce`], so that a burst of toggles only
+    /// sends its net result, if anything. At most
+    /// [`TimelineBuilder::max_concurrent_reactions`] reaction requests are sent
+    /// at the same time.
     pub async fn toggle_reaction(&self, annotation: &Annotation) -> Result<(), Error> {
         // Always toggle the local reaction immediately
         let mut action = self.inner.toggle_reaction_local(annotation).await?;
 
+        // SC: Remember the reactions the user adds, see `frequent_reactions`.
+        if matches!(action, ReactionAction::SendRemote(_)) {
+            if let Err(error) =
+                frequent_reactions::record_reaction(&self.room().client(), &annotation.key).await
+            {
+                warn!(?error, "Failed to record the reaction as a frequent reaction");
+            }
+        }
+
+        // SC: Wait for the end of a burst of toggles of this reaction, the following
+        // toggles only update the local echo while this one is pending.
+        if !matches!(action, ReactionAction::None) {
+            let debounce = self.inner.reaction_debounce();
+
+            if !debounce.is_zero() {
+                sleep(debounce).await;
+                action = self.inner.settle_reaction(annotation, action).await?;
+            }
+        }
+
         // The local echo may have been updated while a reaction is in flight
         // so until it matches the state of the server, keep reconciling
         loop {
@@ -493,9 +936,11 @@ impl Timeline {
                     break;
                 }
                 ReactionAction::SendRemote(txn_id) => {
+                    let _permit = self.inner.reaction_permit().await;
                     self.send_reaction(annotation, txn_id.to_owned()).await
                 }
                 ReactionAction::RedactRemote(event_id) => {
+                    let _permit = self.inner.reaction_permit().await;
                     self.redact_reaction(&event_id.to_owned()).await
                 }
             };
@@ -638,22 +1083,61 @@ impl Timeline {
         self.inner.fetch_in_reply_to_details(event_id).await
     }
 
+    /// SC: Expand the [`VirtualTimelineItem::StateGroup`] with the given
+    /// [unique ID](TimelineItem::unique_id).
+    ///
+    /// Returns whether a state group with that ID was found in the timeline.
+    pub async fn expand_state_group(&self, unique_id: &str) -> bool {
+        self.inner.expand_state_group(unique_id).await
+    }
+
+    /// SC: Reveal the messages of the [`VirtualTimelineItem::HiddenMessages`]
+    /// with the given [unique ID](TimelineItem::unique_id).
+    ///
+    /// Returns whether hidden messages with that ID were found in the timeline.
+    pub async fn expand_hidden_messages(&self, unique_id: &str) -> bool {
+        self.inner.expand_hidden_messages(unique_id).await
+    }
+
+    /// SC: Change the period grouped under a
+    /// [`VirtualTimelineItem::DayDivider`], and the first day of the week
+    /// used when grouping by week.
+    ///
+    /// The existing day dividers are moved, removed or inserted accordingly.
+    pub async fn set_divider_granularity(
+        &self,
+        granularity: DividerGranularity,
+        first_day_of_week: Weekday,
+    ) {
+        self.inner.set_divider_granularity(granularity, first_day_of_week).await;
+    }
+
     /// Fetch all member events for the room this timeline is displaying.
     ///
     /// If the full member list is not known, sender profiles are currently
     /// likely not going to be available. This will be fixed in the future.
     ///
-    /// If fetching the members fails, any affected timeline items will have
-    /// the `sender_profile` set to [`TimelineDetails::Error`].
+    /// This follows the progress of [`Room::ensure_members`]: missing sender
+    /// profiles are marked as pending while the members are loading, and
+    /// filled in once they are loaded. If fetching the members fails, any
+    /// affected timeline items will have the `sender_profile` set to
+    /// [`TimelineDetails::Error`].
     #[instrument(skip_all)]
     pub async fn fetch_members(&self) {
-        self.inner.set_sender_profiles_pending().await;
-        match self.room().sync_members().await {
-            Ok(_) => {
-                self.inner.update_missing_sender_profiles().await;
-            }
-            Err(e) => {
-                self.inner.set_sender_profiles_error(Arc::new(e)).await;
+        let progress = self.room().ensure_members();
+        pin_mut!(progress);
+
+        while let Some(progress) = progress.next().await {
+            match progress {
+                MemberLoadProgress::Loading => {
+                    self.inner.set_sender_profiles_pending().await;
+                }
+                MemberLoadProgress::Loaded => {
+                    self.inner.update_missing_sender_profiles().await;
+                }
+                MemberLoadProgress::Failed(error) => {
+                    self.inner.set_sender_profiles_error(error).await;
+                }
             }
         }
     }
@@ -717,7 +1201,19 @@ impl Timeline {
         self.inner.fully_read_event_id().await
     }
 
-    /// SC: Same as send_single_receipt(), but without the should_send_receipt()-check
+    /// SC: Get the ID of the first unread event, to jump to it.
+    ///
+    /// This is the first event after the fully-read marker in the loaded
+    /// items, even if the read marker item isn't in the timeline yet. If the
+    /// event of the marker isn't loaded, the ID of this event is returned
+    /// instead, so the timeline can be focused on it. Returns `None` if there
+    /// is no fully-read marker, or if every loaded event is read.
+    pub async fn first_unread_event_id(&self) -> Option<OwnedEventId> {
+        self.inner.first_unread_event_id().await
+    }
+
+    /// SC: Same as send_single_receipt(), but without the
+    /// should_send_receipt()-check
     pub async fn force_send_single_receipt(
         &self,
         receipt_type: ReceiptType,
@@ -795,6 +1291,27 @@ impl Timeline {
             Ok(false)
         }
     }
+
+    /// SC: Report the events of the items that are currently visible on screen,
+    /// to send a public read receipt for the latest of them.
+    ///
+    /// This should be called every time the visible items change. If a
+    /// [`TimelineBuilder::receipt_visibility_delay`] was set, the receipt is
+    /// only sent for the items that stay visible for at least that delay,
+    /// otherwise it's sent right away.
+    #[instrument(skip(self), fields(room_id = ?self.room().room_id()))]
+    pub async fn mark_visible(&self, event_ids: Vec<OwnedEventId>) -> Result<()> {
+        let Some(delay) = self.inner.receipt_visibility_delay() else {
+            if let Some(event_id) = self.inner.latest_event_id_among(event_ids).await {
+                self.send_single_receipt(ReceiptType::Read, ReceiptThread::Unthreaded, event_id)
+                    .await?;
+            }
+            return Ok(());
+        };
+
+        self.visible_items.lock().unwrap().update(event_ids, delay, self.inner.clone());
+        Ok(())
+    }
 }
 
 /// Test helpers, likely not very useful in production.
@@ -821,23 +1338,56 @@ struct TimelineDropHandle {
     event_handler_handles: Vec<EventHandlerHandle>,
     room_update_join_handle: JoinHandle<()>,
     room_key_from_backups_join_handle: JoinHandle<()>,
-    local_echo_listener_handle: Option<JoinHandle<()>>,
+    local_echo_listener_handle: std::sync::Mutex<Option<JoinHandle<()>>>,
+    room_info_join_handle: JoinHandle<()>,
+    #[cfg(feature = "e2e-encryption")]
+    verification_states_join_handle: JoinHandle<()>,
+    ignore_user_list_join_handle: JoinHandle<()>,
     _event_cache_drop_handle: Arc<EventCacheDropHandles>,
 }
 
+impl TimelineDropHandle {
+    /// SC: Set the handle of the local echo listener, once a timeline focused
+    /// on an event is attached to the live sync.
+    fn set_local_echo_listener(&self, handle: JoinHandle<()>) {
+        if let Some(previous) = self.local_echo_listener_handle.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
+    }
+}
+
 impl Drop for TimelineDropHandle {
     fn drop(&mut self) {
         for handle in self.event_handler_handles.drain(..) {
             self.client.remove_event_handler(handle);
         }
-        if let Some(handle) = self.local_echo_listener_handle.take() {
+        if let Some(handle) = self.local_echo_listener_handle.get_mut().unwrap().take() {
             handle.abort()
         };
         self.room_update_join_handle.abort();
         self.room_key_from_backups_join_handle.abort();
+        self.room_info_join_handle.abort();
+        #[cfg(feature = "e2e-encryption")]
+        self.verification_states_join_handle.abort();
+        self.ignore_user_list_join_handle.abort();
     }
 }
 
+/// Aggregate the given reactions into `(key, count, reacted by own user)`
+/// tuples.
+fn reactions_summary(
+    reactions: &BundledReactions,
+    own_user_id: &UserId,
+) -> Vec<(String, usize, bool)> {
+    reactions
+        .iter()
+        .filter(|(_, group)| !group.is_empty())
+        .map(|(key, group)| {
+            (key.clone(), group.len(), group.by_sender(own_user_id).next().is_some())
+        })
+        .collect()
+}
+
 pin_project! {
     struct TimelineStream<S> {
         #[pin]
@@ -865,3 +1415,25 @@ impl<S: Stream> Stream for TimelineStream<S> {
 
 pub type TimelineEventFilterFn =
     dyn Fn(&AnySyncTimelineEvent, &RoomVersionId) -> bool + Send + Sync;
+
+/// SC: A function rewriting the content of the event items before they are
+/// displayed, see [`TimelineBuilder::with_content_transform`].
+pub type TimelineContentTransformFn =
+    dyn Fn(&TimelineItemContent) -> Option<TimelineItemContent> + Send + Sync;
+
+/// SC: Apply the content transform to the given timeline item.
+///
+/// Returns `None` if the item should be hidden.
+fn transform_item_content(
+    item: Arc<TimelineItem>,
+    transform: &TimelineContentTransformFn,
+) -> Option<Arc<TimelineItem>> {
+    let Some(event_item) = item.as_event() else {
+        // Virtual items are never transformed.
+        return Some(item);
+    };
+
+    let mut event_item = event_item.clone();
+    event_item.content = transform(&event_item.content)?;
+    Some(item.with_kind(event_item))
+}
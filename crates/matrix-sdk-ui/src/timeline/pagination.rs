@@ -25,7 +25,7 @@ use matrix_sdk::event_cache::{
 };
 use tracing::{instrument, trace, warn};
 
-use super::Error;
+use super::{builder::spawn_local_echo_handler, Error};
 use crate::timeline::{event_item::RemoteEventOrigin, inner::TimelineEnd};
 
 impl super::Timeline {
@@ -41,13 +41,40 @@ impl super::Timeline {
         }
     }
 
+    /// SC: Add more events to the end of the timeline.
+    ///
+    /// A live timeline is always at the end of the room, so this only has an
+    /// effect when the timeline is focused on an event, see
+    /// [`Self::focused_paginate_forwards()`].
+    ///
+    /// Returns whether we hit the end of the timeline.
+    #[instrument(skip_all, fields(room_id = ?self.room().room_id()))]
+    pub async fn paginate_forwards(&self, num_events: u16) -> Result<bool, Error> {
+        if self.inner.is_live().await {
+            Ok(true)
+        } else {
+            self.focused_paginate_forwards(num_events).await
+        }
+    }
+
     /// Assuming the timeline is focused on an event, starts a forwards
     /// pagination.
     ///
+    /// SC: Once the end of the timeline is reached, the timeline also receives
+    /// the new events from sync.
+    ///
     /// Returns whether we hit the end of the timeline.
     #[instrument(skip_all)]
     pub async fn focused_paginate_forwards(&self, num_events: u16) -> Result<bool, Error> {
-        Ok(self.inner.focused_paginate_forwards(num_events).await?)
+        let hit_end = self.inner.focused_paginate_forwards(num_events).await?;
+
+        if hit_end && self.inner.attach_to_sync(&self.event_cache).await? {
+            // The timeline is live now, so it shows the local echoes too.
+            let handle = spawn_local_echo_handler(self.inner.clone(), self.room()).await;
+            self.drop_handle.set_local_echo_listener(handle);
+        }
+
+        Ok(hit_end)
     }
 
     /// Assuming the timeline is focused on an event, starts a backwards
@@ -67,6 +94,17 @@ impl super::Timeline {
     /// Returns whether we hit the start of the timeline.
     #[instrument(skip_all, fields(room_id = ?self.room().room_id()))]
     pub async fn live_paginate_backwards(&self, batch_size: u16) -> event_cache::Result<bool> {
+        // SC: Reload the items evicted because of `TimelineBuilder::max_items` first.
+        let reloaded = self
+            .inner
+            .reload_evicted_events(batch_size, |event_id, count| async move {
+                self.event_cache.events_before(&event_id, count).await
+            })
+            .await;
+        if reloaded {
+            return Ok(false);
+        }
+
         let pagination = self.event_cache.pagination();
 
         let result = pagination
@@ -141,10 +179,102 @@ impl super::Timeline {
 
         Some((current_value, stream))
     }
+
+    /// SC: Subscribe to the back-pagination status of the timeline, whether it
+    /// is live or focused on an event.
+    ///
+    /// Unlike [`Self::live_back_pagination_status()`], the stream follows a
+    /// timeline focused on an event once it's attached to the live sync, see
+    /// [`Self::focused_paginate_forwards()`]. A detached timeline can't be
+    /// paginated, so it's always at the start of the timeline.
+    pub async fn back_pagination_status(
+        &self,
+    ) -> (LiveBackPaginationStatus, impl Stream<Item = LiveBackPaginationStatus>) {
+        let inner = self.inner.clone();
+        let pagination = self.event_cache.pagination();
+        let focused_state = inner.focused_paginator_state().await;
+
+        let mut stream = Box::pin(
+            stream! {
+                if let Some(mut state) = focused_state {
+                    let current = state.next_now();
+                    yield LiveBackPaginationStatus::from_focused_paginator_state(
+                        current,
+                        inner.focused_hit_timeline_start().await,
+                    );
+
+                    // The paginator is dropped once the timeline is attached to the live sync.
+                    while let Some(state) = state.next().await {
+                        yield LiveBackPaginationStatus::from_focused_paginator_state(
+                            state,
+                            inner.focused_hit_timeline_start().await,
+                        );
+                    }
+                }
+
+                if !inner.is_live().await {
+                    yield LiveBackPaginationStatus::Idle { hit_start_of_timeline: true };
+                    return;
+                }
+
+                let mut status = pagination.status();
+                let current = status.next_now();
+                yield LiveBackPaginationStatus::from_paginator_status(&pagination, current);
+
+                while let Some(state) = status.next().await {
+                    yield LiveBackPaginationStatus::from_paginator_status(&pagination, state);
+                }
+            }
+            .dedup(),
+        );
+
+        let current = stream.next().await.expect("the current status is always emitted");
+        (current, stream)
+    }
+
+    /// SC: Subscribe to the forward-pagination status of the timeline.
+    ///
+    /// A live timeline, or one that can't be paginated, is always at the end
+    /// of the timeline.
+    pub async fn forward_pagination_status(
+        &self,
+    ) -> (ForwardPaginationStatus, impl Stream<Item = ForwardPaginationStatus>) {
+        let inner = self.inner.clone();
+        let focused_state = inner.focused_paginator_state().await;
+
+        let mut stream = Box::pin(
+            stream! {
+                if let Some(mut state) = focused_state {
+                    let current = state.next_now();
+                    yield ForwardPaginationStatus::from_focused_paginator_state(
+                        current,
+                        inner.focused_hit_timeline_end().await,
+                    );
+
+                    // The paginator is dropped once the timeline is attached to the live sync.
+                    while let Some(state) = state.next().await {
+                        yield ForwardPaginationStatus::from_focused_paginator_state(
+                            state,
+                            inner.focused_hit_timeline_end().await,
+                        );
+                    }
+                }
+
+                yield ForwardPaginationStatus::Idle { hit_end_of_timeline: true };
+            }
+            .dedup(),
+        );
+
+        let current = stream.next().await.expect("the current status is always emitted");
+        (current, stream)
+    }
 }
 
 /// Status for the back-pagination on a live timeline.
-#[derive(Debug, PartialEq)]
+///
+/// SC: Also used for timelines focused on an event, see
+/// [`Timeline::back_pagination_status`](super::Timeline::back_pagination_status).
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
 pub enum LiveBackPaginationStatus {
     /// No back-pagination is happening right now.
@@ -175,4 +305,41 @@ impl LiveBackPaginationStatus {
             PaginatorState::Paginating => Self::Paginating,
         }
     }
+
+    /// SC: Converts from the [`PaginatorState`] of a timeline focused on an
+    /// event.
+    fn from_focused_paginator_state(state: PaginatorState, hit_start_of_timeline: bool) -> Self {
+        match state {
+            PaginatorState::Initial => Self::Idle { hit_start_of_timeline: false },
+            PaginatorState::FetchingTargetEvent | PaginatorState::Paginating => Self::Paginating,
+            PaginatorState::Idle => Self::Idle { hit_start_of_timeline },
+        }
+    }
+}
+
+/// SC: Status for the forward-pagination of a timeline, see
+/// [`Timeline::forward_pagination_status`](super::Timeline::forward_pagination_status).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum ForwardPaginationStatus {
+    /// No forward-pagination is happening right now.
+    Idle {
+        /// Have we hit the end of the timeline, i.e. the timeline receives the
+        /// new events from sync?
+        hit_end_of_timeline: bool,
+    },
+
+    /// Forward-pagination is already running in the background.
+    Paginating,
+}
+
+impl ForwardPaginationStatus {
+    /// Converts from the [`PaginatorState`] of a timeline focused on an event.
+    fn from_focused_paginator_state(state: PaginatorState, hit_end_of_timeline: bool) -> Self {
+        match state {
+            PaginatorState::Initial => Self::Idle { hit_end_of_timeline: false },
+            PaginatorState::FetchingTargetEvent | PaginatorState::Paginating => Self::Paginating,
+            PaginatorState::Idle => Self::Idle { hit_end_of_timeline },
+        }
+    }
 }
@@ -0,0 +1,171 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: The local echoes of edits that haven't been received back from the
+//! server yet, so they can be cancelled, see
+//! [`Timeline::cancel_edit`](super::Timeline::cancel_edit).
+//!
+//! The local echo of an edit doesn't have its own timeline item, it's applied
+//! in place to the edited item, so the content from before the edit must be
+//! kept around to be able to restore it.
+
+use indexmap::IndexMap;
+use matrix_sdk::send_queue::AbortSendHandle;
+use ruma::{
+    events::AnySyncTimelineEvent, serde::Raw, EventId, OwnedEventId, OwnedTransactionId,
+    TransactionId,
+};
+
+use super::TimelineItemContent;
+
+/// The content of an event item before an edit was applied to it.
+#[derive(Clone, Debug)]
+pub(super) struct PreviousContent {
+    pub content: TimelineItemContent,
+    pub latest_edit_json: Option<Raw<AnySyncTimelineEvent>>,
+}
+
+#[derive(Clone, Debug)]
+struct PendingEdit {
+    edited_event_id: OwnedEventId,
+    previous: PreviousContent,
+    abort_handle: Option<AbortSendHandle>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(super) struct PendingEdits {
+    /// The pending edits by transaction ID, in the order they were applied.
+    edits: IndexMap<OwnedTransactionId, PendingEdit>,
+}
+
+impl PendingEdits {
+    /// Remember that the local echo of the edit `txn_id` has been applied to
+    /// the event `edited_event_id`, whose content was `previous`.
+    pub(super) fn add(
+        &mut self,
+        txn_id: OwnedTransactionId,
+        edited_event_id: OwnedEventId,
+        previous: PreviousContent,
+        abort_handle: Option<AbortSendHandle>,
+    ) {
+        self.edits.insert(txn_id, PendingEdit { edited_event_id, previous, abort_handle });
+    }
+
+    /// Forget about the edit `txn_id`, because its remote echo was received.
+    pub(super) fn mark_sent(&mut self, txn_id: &TransactionId) {
+        self.edits.shift_remove(txn_id);
+    }
+
+    /// Whether `txn_id` is the transaction ID of a pending edit.
+    pub(super) fn contains(&self, txn_id: &TransactionId) -> bool {
+        self.edits.contains_key(txn_id)
+    }
+
+    /// Forget about the edit `txn_id`, because it was cancelled.
+    ///
+    /// Returns the edited event and the content to restore it to, if the edit
+    /// is the latest pending edit of this event. Otherwise, the next pending
+    /// edit of the event now restores to the content from before this one.
+    pub(super) fn remove(
+        &mut self,
+        txn_id: &TransactionId,
+    ) -> Option<(OwnedEventId, PreviousContent)> {
+        let idx = self.edits.get_index_of(txn_id)?;
+        let (_, edit) = self.edits.shift_remove_index(idx)?;
+
+        match self
+            .edits
+            .values_mut()
+            .skip(idx)
+            .find(|next| next.edited_event_id == edit.edited_event_id)
+        {
+            Some(next) => {
+                next.previous = edit.previous;
+                None
+            }
+            None => Some((edit.edited_event_id, edit.previous)),
+        }
+    }
+
+    /// The handles to abort the pending edits of the event `event_id`, the
+    /// latest first.
+    pub(super) fn abort_handles(&self, event_id: &EventId) -> Vec<AbortSendHandle> {
+        self.edits
+            .values()
+            .rev()
+            .filter(|edit| edit.edited_event_id == event_id)
+            .filter_map(|edit| edit.abort_handle.clone())
+            .collect()
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.edits.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches2::assert_let;
+    use ruma::{event_id, EventId, TransactionId};
+
+    use super::{PendingEdits, PreviousContent};
+    use crate::timeline::TimelineItemContent;
+
+    fn previous() -> PreviousContent {
+        PreviousContent { content: TimelineItemContent::RedactedMessage, latest_edit_json: None }
+    }
+
+    fn add(edits: &mut PendingEdits, txn_id: &TransactionId, event_id: &EventId) {
+        edits.add(txn_id.to_owned(), event_id.to_owned(), previous(), None);
+    }
+
+    #[test]
+    fn test_remove_latest_edit_restores() {
+        let mut edits = PendingEdits::default();
+        let t0 = TransactionId::new();
+        add(&mut edits, &t0, event_id!("$a"));
+
+        assert_let!(Some((event_id, _)) = edits.remove(&t0));
+        assert_eq!(event_id, "$a");
+
+        // It's forgotten.
+        assert!(edits.remove(&t0).is_none());
+    }
+
+    #[test]
+    fn test_remove_earlier_edit_defers_to_next() {
+        let mut edits = PendingEdits::default();
+        let (t0, t1, t2) = (TransactionId::new(), TransactionId::new(), TransactionId::new());
+        add(&mut edits, &t0, event_id!("$a"));
+        add(&mut edits, &t1, event_id!("$b"));
+        add(&mut edits, &t2, event_id!("$a"));
+
+        // The event still shows the content of `t2`.
+        assert!(edits.remove(&t0).is_none());
+
+        // Now there's nothing left to restore after `t2`.
+        assert_let!(Some((event_id, _)) = edits.remove(&t2));
+        assert_eq!(event_id, "$a");
+    }
+
+    #[test]
+    fn test_mark_sent() {
+        let mut edits = PendingEdits::default();
+        let t0 = TransactionId::new();
+        add(&mut edits, &t0, event_id!("$a"));
+
+        edits.mark_sent(&t0);
+        assert!(edits.remove(&t0).is_none());
+    }
+}
@@ -34,6 +34,8 @@ pub(super) struct ResponseData {
     pub(super) sender: OwnedUserId,
     pub(super) timestamp: MilliSecondsSinceUnixEpoch,
     pub(super) answers: Vec<String>,
+    /// SC: Whether the response was sent by the own user.
+    pub(super) is_own: bool,
 }
 
 impl PollState {
@@ -64,6 +66,7 @@ impl PollState {
     pub(super) fn add_response(
         &self,
         sender: &UserId,
+        is_own: bool,
         timestamp: MilliSecondsSinceUnixEpoch,
         content: &UnstablePollResponseEventContent,
     ) -> Self {
@@ -72,6 +75,7 @@ impl PollState {
             sender: sender.to_owned(),
             timestamp,
             answers: content.poll_response.answers.clone(),
+            is_own,
         });
         clone
     }
@@ -104,6 +108,18 @@ impl PollState {
             self.end_event_timestamp,
         );
 
+        let own_user_id =
+            self.response_data.iter().find(|data| data.is_own).map(|data| &*data.sender);
+        let own_answers = own_user_id
+            .map(|user_id| {
+                results
+                    .iter()
+                    .filter(|(_, voters)| voters.contains(user_id))
+                    .map(|(answer, _)| (*answer).to_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+
         PollResult {
             question: self.start_event_content.poll_start.question.text.clone(),
             kind: self.start_event_content.poll_start.kind.clone(),
@@ -121,6 +137,7 @@ impl PollState {
                 .collect(),
             end_time: self.end_event_timestamp.map(|millis| millis.0.into()),
             has_been_edited: self.has_been_edited,
+            own_answers,
         }
     }
 }
@@ -152,6 +169,7 @@ impl PollPendingEvents {
         &mut self,
         start_id: &EventId,
         sender: &UserId,
+        is_own: bool,
         timestamp: MilliSecondsSinceUnixEpoch,
         content: &UnstablePollResponseEventContent,
     ) {
@@ -159,6 +177,7 @@ impl PollPendingEvents {
             sender: sender.to_owned(),
             timestamp,
             answers: content.poll_response.answers.clone(),
+            is_own,
         });
     }
 
@@ -187,6 +206,9 @@ pub struct PollResult {
     pub votes: HashMap<String, Vec<String>>,
     pub end_time: Option<u64>,
     pub has_been_edited: bool,
+    /// SC: The answers of the own user that are counted, empty if they
+    /// didn't vote.
+    pub own_answers: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -19,6 +19,7 @@ use std::{
 };
 
 use eyeball_im::ObservableVectorTransaction;
+use imbl::Vector;
 use indexmap::IndexMap;
 use ruma::{
     events::receipt::{Receipt, ReceiptEventContent, ReceiptThread, ReceiptType},
@@ -326,7 +327,9 @@ impl ReadReceiptTimelineUpdate {
 
         if let Some(remote_event_item) = event_item.as_remote_mut() {
             remote_event_item.read_receipts.insert(user_id, receipt);
+            remote_event_item.read_by_others |= remote_event_item.is_own;
             items.set(receipt_pos, TimelineItem::new(event_item, event_item_id));
+            mark_own_items_read_by_others(items, receipt_pos);
         } else {
             warn!("received a read receipt for a local item, this should not be possible");
         }
@@ -344,6 +347,44 @@ impl ReadReceiptTimelineUpdate {
     }
 }
 
+/// SC: Whether any event item from `start` on was read by another user, which
+/// means that the own event items before `start` were read too.
+pub(super) fn is_read_by_others_from(items: &Vector<Arc<TimelineItem>>, start: usize) -> bool {
+    items
+        .iter()
+        .skip(start)
+        .filter_map(|item| item.as_event()?.as_remote())
+        .any(|item| !item.read_receipts.is_empty() || item.read_by_others)
+}
+
+/// SC: Mark the own event items before `pos` as read by others, because
+/// another user read a later event.
+pub(super) fn mark_own_items_read_by_others(
+    items: &mut ObservableVectorTransaction<'_, Arc<TimelineItem>>,
+    pos: usize,
+) {
+    for idx in (0..pos).rev() {
+        let item = &items[idx];
+        let Some(event_item) = item.as_event() else { continue };
+        let Some(remote_event_item) = event_item.as_remote() else { continue };
+
+        if !remote_event_item.is_own {
+            continue;
+        }
+        if remote_event_item.read_by_others {
+            // All the previous own items were marked at the same time.
+            break;
+        }
+
+        let event_item_id = item.internal_id.to_owned();
+        let mut event_item = event_item.clone();
+        if let Some(remote_event_item) = event_item.as_remote_mut() {
+            remote_event_item.read_by_others = true;
+        }
+        items.set(idx, TimelineItem::new(event_item, event_item_id));
+    }
+}
+
 impl TimelineInnerStateTransaction<'_> {
     pub(super) fn handle_explicit_read_receipts(
         &mut self,
@@ -487,8 +528,14 @@ impl TimelineInnerStateTransaction<'_> {
             return;
         }
 
+        let is_read = !read_receipts.is_empty();
         remote_prev_event_item.read_receipts = read_receipts;
+        remote_prev_event_item.read_by_others |= is_read && remote_prev_event_item.is_own;
         self.items.set(prev_item_pos, TimelineItem::new(prev_event_item, prev_event_item_id));
+
+        if is_read {
+            mark_own_items_read_by_others(&mut self.items, prev_item_pos);
+        }
     }
 }
 
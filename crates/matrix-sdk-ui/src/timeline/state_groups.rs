@@ -0,0 +1,188 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: Algorithm placing [`VirtualTimelineItem::StateGroup`] headers in front
+//! of runs of consecutive state events.
+
+use std::{cmp::Reverse, sync::Arc};
+
+use eyeball_im::ObservableVectorTransaction;
+use tracing::trace;
+
+use super::{
+    inner::TimelineInnerMetadata, EventTimelineItem, StateGroupSummary, TimelineItem,
+    TimelineItemContent, TimelineItemKind, VirtualTimelineItem,
+};
+
+/// The minimum number of consecutive state events that get a group header.
+const MIN_STATE_GROUP_SIZE: usize = 2;
+
+/// Makes sure that every run of at least [`MIN_STATE_GROUP_SIZE`] consecutive
+/// state events is preceded by exactly one up-to-date state group header, and
+/// that there are no other state group headers.
+///
+/// Runs are interrupted by any item that isn't a state event, including day
+/// dividers and the read marker. Existing headers are reused where possible,
+/// so that their unique ID and their `expanded` flag are kept.
+pub(super) fn adjust_state_groups(
+    items: &mut ObservableVectorTransaction<'_, Arc<TimelineItem>>,
+    meta: &mut TimelineInnerMetadata,
+) {
+    let mut ops = Vec::new();
+    let mut run = StateRun::default();
+
+    for (i, item) in items.iter().enumerate() {
+        match item.kind() {
+            TimelineItemKind::Virtual(VirtualTimelineItem::StateGroup { .. }) => {
+                run.headers.push(i);
+            }
+            TimelineItemKind::Event(event) if is_state_event(event) => {
+                run.push_event(i, event);
+            }
+            _ => {
+                std::mem::take(&mut run).finish(items, meta, &mut ops);
+            }
+        }
+    }
+
+    run.finish(items, meta, &mut ops);
+
+    // Apply the operations from the back, so that the indices stay valid.
+    ops.sort_by_key(|op| Reverse(op.index()));
+
+    for op in ops {
+        match op {
+            StateGroupOperation::Insert(i, item) => {
+                trace!("inserting state group header @ {i}");
+                items.insert(i, item);
+            }
+            StateGroupOperation::Replace(i, item) => {
+                trace!("updating state group header @ {i}");
+                items.set(i, item);
+            }
+            StateGroupOperation::Remove(i) => {
+                trace!("removing state group header @ {i}");
+                items.remove(i);
+            }
+        }
+    }
+}
+
+/// Whether the event can be part of a state group.
+pub(super) fn is_state_event(event: &EventTimelineItem) -> bool {
+    matches!(
+        event.content(),
+        TimelineItemContent::MembershipChange(_)
+            | TimelineItemContent::ProfileChange(_)
+            | TimelineItemContent::PinnedEventsChange(_)
+            | TimelineItemContent::OtherState(_)
+    )
+}
+
+/// A run of consecutive state events, along with the state group headers
+/// found since the last item interrupting a run.
+#[derive(Default)]
+struct StateRun {
+    headers: Vec<usize>,
+    first_event: Option<usize>,
+    count: usize,
+    summary: StateGroupSummary,
+}
+
+impl StateRun {
+    fn push_event(&mut self, i: usize, event: &EventTimelineItem) {
+        self.first_event.get_or_insert(i);
+        self.count += 1;
+
+        if !self.summary.senders.iter().any(|sender| sender == event.sender()) {
+            self.summary.senders.push(event.sender().to_owned());
+        }
+
+        match event.content() {
+            TimelineItemContent::MembershipChange(_) => self.summary.membership_changes += 1,
+            TimelineItemContent::ProfileChange(_) => self.summary.profile_changes += 1,
+            _ => self.summary.other_state_changes += 1,
+        }
+    }
+
+    fn finish(
+        self,
+        items: &ObservableVectorTransaction<'_, Arc<TimelineItem>>,
+        meta: &mut TimelineInnerMetadata,
+        ops: &mut Vec<StateGroupOperation>,
+    ) {
+        let Self { headers, first_event, count, summary } = self;
+        let mut headers = headers.into_iter();
+
+        let Some(first_event) = first_event.filter(|_| count >= MIN_STATE_GROUP_SIZE) else {
+            ops.extend(headers.map(StateGroupOperation::Remove));
+            return;
+        };
+
+        match headers.next() {
+            Some(header_idx) => {
+                let header = &items[header_idx];
+                let expanded = matches!(
+                    header.as_virtual(),
+                    Some(VirtualTimelineItem::StateGroup { expanded: true, .. })
+                );
+                let up_to_date = matches!(
+                    header.as_virtual(),
+                    Some(VirtualTimelineItem::StateGroup { count: c, summary: s, .. })
+                        if *c == count && *s == summary
+                );
+                let item =
+                    header.with_kind(VirtualTimelineItem::StateGroup { count, summary, expanded });
+
+                if header_idx + 1 == first_event {
+                    if !up_to_date {
+                        ops.push(StateGroupOperation::Replace(header_idx, item));
+                    }
+                } else {
+                    // The header isn't right before the run anymore: move it.
+                    ops.push(StateGroupOperation::Remove(header_idx));
+                    ops.push(StateGroupOperation::Insert(first_event, item));
+                }
+            }
+
+            None => {
+                let item = meta.new_timeline_item(VirtualTimelineItem::StateGroup {
+                    count,
+                    summary,
+                    expanded: false,
+                });
+                ops.push(StateGroupOperation::Insert(first_event, item));
+            }
+        }
+
+        ops.extend(headers.map(StateGroupOperation::Remove));
+    }
+}
+
+enum StateGroupOperation {
+    /// Insert the item before the item at the given index.
+    Insert(usize, Arc<TimelineItem>),
+    Replace(usize, Arc<TimelineItem>),
+    Remove(usize),
+}
+
+impl StateGroupOperation {
+    fn index(&self) -> usize {
+        match self {
+            StateGroupOperation::Insert(i, _)
+            | StateGroupOperation::Replace(i, _)
+            | StateGroupOperation::Remove(i) => *i,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fmt,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use eyeball_im::VectorDiff;
+use futures_core::Stream;
+use imbl::Vector;
+
+use super::TimelineItem;
+
+/// SC: A subscription to the items of a [`Timeline`](super::Timeline), see
+/// [`Timeline::subscription`](super::Timeline::subscription).
+///
+/// The subscription starts with the items of the timeline at the time it was
+/// created, available with [`Self::initial_items`], and is a stream of the
+/// changes applied to them since.
+///
+/// The changes are buffered, but the buffer is bounded. If the stream isn't
+/// polled fast enough and changes would be dropped, a [`VectorDiff::Reset`]
+/// with the full current items is emitted instead of the missed changes. So
+/// applying every change to the initial items always results in the items of
+/// the timeline, even for a consumer that stalls.
+pub struct TimelineSubscription {
+    initial_items: Vector<Arc<TimelineItem>>,
+    stream: Pin<Box<dyn Stream<Item = VectorDiff<Arc<TimelineItem>>> + Send>>,
+}
+
+impl TimelineSubscription {
+    pub(super) fn new(
+        initial_items: Vector<Arc<TimelineItem>>,
+        stream: impl Stream<Item = VectorDiff<Arc<TimelineItem>>> + Send + 'static,
+    ) -> Self {
+        Self { initial_items, stream: Box::pin(stream) }
+    }
+
+    /// The items of the timeline when the subscription was created.
+    pub fn initial_items(&self) -> &Vector<Arc<TimelineItem>> {
+        &self.initial_items
+    }
+}
+
+impl Stream for TimelineSubscription {
+    type Item = VectorDiff<Arc<TimelineItem>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.as_mut().poll_next(cx)
+    }
+}
+
+impl fmt::Debug for TimelineSubscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimelineSubscription")
+            .field("initial_items", &self.initial_items.len())
+            .finish_non_exhaustive()
+    }
+}
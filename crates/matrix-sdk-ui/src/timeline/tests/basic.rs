@@ -12,9 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use assert_matches::assert_matches;
 use assert_matches2::assert_let;
 use eyeball_im::VectorDiff;
+use futures_util::{FutureExt, StreamExt};
+use imbl::Vector;
 use matrix_sdk::test_utils::events::EventFactory;
 use matrix_sdk_base::deserialized_responses::SyncTimelineEvent;
 use matrix_sdk_test::{async_test, sync_timeline_event, ALICE, BOB, CAROL};
@@ -31,16 +35,17 @@ use ruma::{
         },
         FullStateEventContent,
     },
-    owned_event_id, MilliSecondsSinceUnixEpoch,
+    owned_event_id, EventId, MilliSecondsSinceUnixEpoch, OwnedEventId,
 };
 use stream_assert::assert_next_matches;
 
-use super::TestTimeline;
+use super::{assert_event_is_updated, assert_no_more_updates, TestTimeline};
 use crate::timeline::{
     event_item::{AnyOtherFullStateEventContent, RemoteEventOrigin},
     inner::{TimelineEnd, TimelineInnerSettings},
     tests::{ReadReceiptMap, TestRoomDataProvider},
-    MembershipChange, TimelineDetails, TimelineItemContent, TimelineItemKind, VirtualTimelineItem,
+    MembershipChange, TimelineDetails, TimelineItem, TimelineItemContent, TimelineItemKind,
+    VirtualTimelineItem,
 };
 
 #[async_test]
@@ -147,6 +152,49 @@ async fn test_sticker() {
     assert_matches!(item.content(), TimelineItemContent::Sticker(_));
 }
 
+#[async_test]
+async fn test_timestamp_info_clock_skew() {
+    let timeline = TestTimeline::new();
+
+    // The server's clock is one hour ahead of ours.
+    let hour_ahead = u64::from(MilliSecondsSinceUnixEpoch::now().get()) + 3_600_000;
+
+    timeline
+        .handle_live_custom_event(sync_timeline_event!({
+            "content": { "body": "hello", "msgtype": "m.text" },
+            "event_id": "$skewed",
+            "origin_server_ts": hour_ahead,
+            "sender": "@alice:server.name",
+            "type": "m.room.message",
+            "unsigned": { "age": 0 },
+        }))
+        .await;
+
+    let items = timeline.inner.items().await;
+    let info = items.last().unwrap().as_event().unwrap().timestamp_info();
+    assert_eq!(u64::from(info.origin_server_ts.get()), hour_ahead);
+    assert!(info.age_derived_ts.is_some());
+
+    let skew = info.clock_skew_ms.unwrap();
+    assert!((3_590_000..=3_600_000).contains(&skew), "unexpected skew: {skew}");
+
+    // Without an age, there is nothing to estimate the skew from.
+    timeline
+        .handle_live_custom_event(sync_timeline_event!({
+            "content": { "body": "hi", "msgtype": "m.text" },
+            "event_id": "$no_age",
+            "origin_server_ts": hour_ahead,
+            "sender": "@bob:server.name",
+            "type": "m.room.message",
+        }))
+        .await;
+
+    let items = timeline.inner.items().await;
+    let info = items.last().unwrap().as_event().unwrap().timestamp_info();
+    assert_eq!(info.age_derived_ts, None);
+    assert_eq!(info.clock_skew_ms, None);
+}
+
 #[async_test]
 async fn test_room_member() {
     let timeline = TestTimeline::new();
@@ -215,6 +263,47 @@ async fn test_room_member() {
     assert_matches!(membership.change(), None);
 }
 
+#[async_test]
+async fn test_room_member_kick_reason() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+
+    let mut join_content = RoomMemberEventContent::new(MembershipState::Join);
+    join_content.displayname = Some("Alice".to_owned());
+    timeline
+        .handle_live_state_event_with_state_key(
+            &ALICE,
+            ALICE.to_owned(),
+            join_content.clone(),
+            None,
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert_let!(TimelineItemContent::MembershipChange(membership) = item.content());
+    assert_matches!(membership.change(), Some(MembershipChange::Joined));
+    assert_eq!(membership.display_name().as_deref(), Some("Alice"));
+    assert_eq!(membership.reason(), None);
+
+    let mut kick_content = RoomMemberEventContent::new(MembershipState::Leave);
+    kick_content.reason = Some("spamming".to_owned());
+    timeline
+        .handle_live_state_event_with_state_key(
+            &BOB,
+            ALICE.to_owned(),
+            kick_content,
+            Some(join_content),
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert_eq!(item.sender(), *BOB);
+    assert_let!(TimelineItemContent::MembershipChange(membership) = item.content());
+    assert_eq!(membership.user_id(), *ALICE);
+    assert_matches!(membership.change(), Some(MembershipChange::Kicked));
+    assert_eq!(membership.reason(), Some("spamming"));
+}
+
 #[async_test]
 async fn test_other_state() {
     let timeline = TestTimeline::new();
@@ -484,3 +573,109 @@ async fn test_thread() {
     assert_let!(TimelineDetails::Ready(replied_to_event) = &in_reply_to.event);
     assert_eq!(replied_to_event.sender(), *ALICE);
 }
+
+#[async_test]
+async fn test_pinned_events_update() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe().await;
+
+    let event_id = owned_event_id!("$a");
+    timeline
+        .handle_live_message_event_with_id(
+            &ALICE,
+            &event_id,
+            RoomMessageEventContent::text_plain("pin me"),
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert!(!item.as_event().unwrap().is_pinned());
+    let day_divider = assert_next_matches!(stream, VectorDiff::PushFront { value } => value);
+    assert!(day_divider.is_day_divider());
+
+    timeline.inner.update_pinned_events(&[event_id.clone()]).await;
+    let item = assert_event_is_updated(&mut stream, &event_id, 1).await;
+    assert!(item.is_pinned());
+
+    // Updating with the same list doesn't emit anything.
+    timeline.inner.update_pinned_events(&[event_id.clone()]).await;
+    assert_no_more_updates(&mut stream).await;
+
+    timeline.inner.update_pinned_events(&[]).await;
+    let item = assert_event_is_updated(&mut stream, &event_id, 1).await;
+    assert!(!item.is_pinned());
+}
+
+#[async_test]
+async fn test_max_items_eviction() {
+    let timeline = TestTimeline::new()
+        .with_settings(TimelineInnerSettings { max_items: Some(2), ..Default::default() });
+
+    let f = EventFactory::new().sender(*ALICE);
+    let events: Vec<SyncTimelineEvent> = ["$a", "$b", "$c"]
+        .into_iter()
+        .map(|event_id| {
+            f.text_msg(event_id).event_id(<&EventId>::try_from(event_id).unwrap()).into_sync()
+        })
+        .collect();
+
+    for event in &events {
+        timeline.handle_live_event(event.event.clone()).await;
+    }
+
+    let event_ids = |items: &[Arc<TimelineItem>]| -> Vec<String> {
+        items.iter().filter_map(|item| Some(item.as_event()?.event_id()?.to_string())).collect()
+    };
+
+    // The oldest event has been evicted, and the new first event has a day divider.
+    let items = timeline.inner.items().await.into_iter().collect::<Vec<_>>();
+    assert_eq!(event_ids(&items), ["$b", "$c"]);
+    assert!(items[0].is_day_divider());
+
+    // Only the events before the oldest timeline event are loaded from the
+    // cached events.
+    let events_before = |event_id: OwnedEventId, count: usize| {
+        let events = &events;
+        async move {
+            let end =
+                events.iter().position(|event| event.event_id().as_ref() == Some(&event_id))?;
+            Some(events[..end].iter().rev().take(count).cloned().collect::<Vec<_>>())
+        }
+    };
+
+    // It can be reloaded from the cached events.
+    assert!(timeline.inner.reload_evicted_events(10, events_before).await);
+    let items = timeline.inner.items().await.into_iter().collect::<Vec<_>>();
+    assert_eq!(event_ids(&items), ["$a", "$b", "$c"]);
+    assert!(items[0].is_day_divider());
+
+    // There's nothing left to reload.
+    assert!(!timeline.inner.reload_evicted_events(10, events_before).await);
+}
+
+#[async_test]
+async fn test_lagging_subscriber_is_reset() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe().await;
+
+    // Send more updates than the stream can buffer, without polling it.
+    for i in 0..50 {
+        timeline
+            .handle_live_message_event(*ALICE, RoomMessageEventContent::text_plain(format!("{i}")))
+            .await;
+    }
+
+    // The missed updates are replaced by a reset with the current state…
+    let mut items = assert_next_matches!(stream, VectorDiff::Reset { values } => values);
+
+    // … followed by the updates that were still buffered.
+    while let Some(Some(diff)) = stream.next().now_or_never() {
+        diff.apply(&mut items);
+    }
+
+    let event_ids = |items: &Vector<Arc<TimelineItem>>| -> Vec<String> {
+        items.iter().filter_map(|item| Some(item.as_event()?.event_id()?.to_string())).collect()
+    };
+    assert_eq!(items.len(), timeline.inner.items().await.len());
+    assert_eq!(event_ids(&items), event_ids(&timeline.inner.items().await));
+}
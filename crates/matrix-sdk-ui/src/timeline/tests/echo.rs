@@ -25,7 +25,10 @@ use ruma::{
 use stream_assert::assert_next_matches;
 
 use super::TestTimeline;
-use crate::timeline::event_item::EventSendState;
+use crate::timeline::{
+    event_item::{EventSendState, SendError},
+    OwnDeliveryStatus,
+};
 
 #[async_test]
 async fn test_remote_echo_full_trip() {
@@ -45,6 +48,7 @@ async fn test_remote_echo_full_trip() {
         let event_item = item.as_event().unwrap();
         assert!(event_item.is_local_echo());
         assert_matches!(event_item.send_state(), Some(EventSendState::NotSentYet));
+        assert_eq!(event_item.own_delivery_status(), Some(OwnDeliveryStatus::Sending));
         assert!(!event_item.can_be_replied_to());
         item.unique_id().to_owned()
     };
@@ -59,13 +63,16 @@ async fn test_remote_echo_full_trip() {
     // has failed. In this case, there is no event ID.
     {
         let some_io_error = Error::Io(io::Error::new(io::ErrorKind::Other, "this is a test"));
+        let send_error = SendError::from(&some_io_error);
         timeline
             .inner
             .update_event_send_state(
                 &txn_id,
                 EventSendState::SendingFailed {
                     error: Arc::new(some_io_error),
+                    send_error,
                     is_recoverable: true,
+                    retry_at: None,
                 },
             )
             .await;
@@ -77,6 +84,7 @@ async fn test_remote_echo_full_trip() {
             event_item.send_state(),
             Some(EventSendState::SendingFailed { is_recoverable: true, .. })
         );
+        assert_eq!(event_item.own_delivery_status(), Some(OwnDeliveryStatus::Failed));
         assert_eq!(item.unique_id(), id);
     }
 
@@ -96,6 +104,7 @@ async fn test_remote_echo_full_trip() {
         let event_item = item.as_event().unwrap();
         assert!(event_item.is_local_echo());
         assert_matches!(event_item.send_state(), Some(EventSendState::Sent { .. }));
+        assert_eq!(event_item.own_delivery_status(), Some(OwnDeliveryStatus::Sent));
         assert_eq!(item.unique_id(), id);
 
         event_item.timestamp()
@@ -119,6 +128,7 @@ async fn test_remote_echo_full_trip() {
     // The local echo is replaced with the remote echo.
     let item = assert_next_matches!(stream, VectorDiff::Set { index: 1, value } => value);
     assert!(!item.as_event().unwrap().is_local_echo());
+    assert_eq!(item.as_event().unwrap().own_delivery_status(), Some(OwnDeliveryStatus::Sent));
     assert_eq!(item.unique_id(), id);
 }
 
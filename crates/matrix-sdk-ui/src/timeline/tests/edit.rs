@@ -20,8 +20,10 @@ use ruma::{
     events::{
         relation::Replacement,
         room::message::{
-            self, MessageType, RedactedRoomMessageEventContent, RoomMessageEventContent,
+            self, MessageType, RedactedRoomMessageEventContent, ReplacementMetadata,
+            RoomMessageEventContent,
         },
+        AnyMessageLikeEventContent,
     },
     server_name, EventId,
 };
@@ -163,3 +165,50 @@ async fn test_aggregated_sanitized() {
     let day_divider = assert_next_matches!(stream, VectorDiff::PushFront { value } => value);
     assert!(day_divider.is_day_divider());
 }
+
+#[async_test]
+async fn test_local_edit_of_unknown_event() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+
+    // The edited event isn't in the timeline.
+    let edited_event_id = event_id!("$original");
+    let edit = RoomMessageEventContent::text_plain("edited")
+        .make_replacement(ReplacementMetadata::new(edited_event_id.to_owned(), None), None);
+    let txn_id = timeline.handle_local_event(AnyMessageLikeEventContent::RoomMessage(edit)).await;
+
+    // The edit is shown as a local echo that knows which event it replaces.
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert!(item.is_local_echo());
+    assert_eq!(item.replaces_event_id().as_deref(), Some(edited_event_id));
+    assert_let!(TimelineItemContent::Message(message) = item.content());
+    assert_eq!(message.body(), "edited");
+    assert!(message.is_edited());
+
+    // The remote echo of the edit removes the local echo.
+    timeline
+        .handle_live_custom_event(sync_timeline_event!({
+            "content": {
+                "body": " * edited",
+                "msgtype": "m.text",
+                "m.new_content": {
+                    "body": "edited",
+                    "msgtype": "m.text",
+                },
+                "m.relates_to": {
+                    "event_id": edited_event_id,
+                    "rel_type": "m.replace",
+                },
+            },
+            "sender": &*ALICE,
+            "event_id": "$edit",
+            "origin_server_ts": 5,
+            "type": "m.room.message",
+            "unsigned": {
+                "transaction_id": txn_id,
+            },
+        }))
+        .await;
+
+    assert!(timeline.inner.items().await.iter().all(|item| item.as_event().is_none()));
+}
@@ -31,9 +31,12 @@ use matrix_sdk_test::{async_test, BOB};
 use ruma::{
     assign,
     events::{
-        room::encrypted::{
-            EncryptedEventScheme, MegolmV1AesSha2ContentInit, Relation, Replacement,
-            RoomEncryptedEventContent,
+        room::{
+            encrypted::{
+                EncryptedEventScheme, MegolmV1AesSha2ContentInit, Relation, Replacement,
+                RoomEncryptedEventContent,
+            },
+            message::RoomMessageEventContent,
         },
         AnySyncTimelineEvent,
     },
@@ -46,7 +49,7 @@ use stream_assert::assert_next_matches;
 
 use super::TestTimeline;
 use crate::{
-    timeline::{EncryptedMessage, TimelineItemContent},
+    timeline::{EncryptedMessage, EventItemIdentifier, TimelineItemContent},
     unable_to_decrypt_hook::{UnableToDecryptHook, UnableToDecryptInfo, UtdHookManager},
 };
 
@@ -168,6 +171,93 @@ async fn test_retry_message_decryption() {
     }
 }
 
+#[async_test]
+async fn test_late_decryption_handler() {
+    const SESSION_ID: &str = "gM8i47Xhu0q52xLfgUXzanCMpLinoyVyH7R58cBuVBU";
+    const SESSION_KEY: &[u8] = b"\
+        -----BEGIN MEGOLM SESSION DATA-----\n\
+        ASKcWoiAVUM97482UAi83Avce62hSLce7i5JhsqoF6xeAAAACqt2Cg3nyJPRWTTMXxXH7TXnkfdlmBXbQtq5\
+        bpHo3LRijcq2Gc6TXilESCmJN14pIsfKRJrWjZ0squ/XsoTFytuVLWwkNaW3QF6obeg2IoVtJXLMPdw3b2vO\
+        vgwGY3OMP0XafH13j1vcb6YLzvgLkZQLnYvd47hv3yK/9GmKS9tokuaQ7dCVYckYcIOS09EDTs70YdxUd5WG\
+        rQynATCLFP1p/NAGv70r9MK7Cy/mNpjD0r4qC7UEDIoi1kOWzHgnLo19wtvwsb8Fg8ATxcs3Wmtj8hIUYpDx\
+        ia4sM10zbytUuaPUAfCDf42IyxdmOnGe1CueXhgI71y+RW0s0argNqUt7jB70JT0o9CyX6UBGRaqLk2MPY9T\
+        hUu5J8X3UgIa6rcbWigzohzWm9rdbEHFrSWqjpfQYMaAKQQgETrjSy4XTrp2RhC2oNqG/hylI4ab+F4X6fpH\
+        DYP1NqNMP5g36xNu7LhDnrUB5qsPjYOmWORxGLfudpF3oLYCSlr3DgHqEIB6HjQblLZ3KQuPBse3zxyROTnS\
+        AhdPH4a/z1wioFtKNVph3hecsiKEdqnz4Y2coSIdhz58mJ9JWNQoFAENE5CSsoEZAGvafYZVpW4C75YY2zq1\
+        wIeiFi1dT43/jLAUGkslsi1VvnyfUu8qO404RxYO3XHoGLMFoFLOO+lZ+VGci2Vz10AhxJhEBHxRKxw4k2uB\
+        HztoSJUr/2Y\n\
+        -----END MEGOLM SESSION DATA-----";
+
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    timeline
+        .inner
+        .set_late_decryption_handler(Box::new({
+            let reports = reports.clone();
+            move |id, time_to_decrypt| reports.lock().unwrap().push((id, time_to_decrypt))
+        }))
+        .await;
+
+    // A message that was never unable-to-decrypt isn't reported.
+    timeline.handle_live_message_event(&BOB, RoomMessageEventContent::text_plain("hi")).await;
+    assert_next_matches!(stream, VectorDiff::PushBack { .. });
+
+    timeline
+        .handle_live_message_event(
+            &BOB,
+            RoomEncryptedEventContent::new(
+                EncryptedEventScheme::MegolmV1AesSha2(
+                    MegolmV1AesSha2ContentInit {
+                        ciphertext: "\
+                            AwgAEtABPRMavuZMDJrPo6pGQP4qVmpcuapuXtzKXJyi3YpEsjSWdzuRKIgJzD4P\
+                            cSqJM1A8kzxecTQNJsC5q22+KSFEPxPnI4ltpm7GFowSoPSW9+bFdnlfUzEP1jPq\
+                            YevHAsMJp2fRKkzQQbPordrUk1gNqEpGl4BYFeRqKl9GPdKFwy45huvQCLNNueql\
+                            CFZVoYMuhxrfyMiJJAVNTofkr2um2mKjDTlajHtr39pTG8k0eOjSXkLOSdZvNOMz\
+                            hGhSaFNeERSA2G2YbeknOvU7MvjiO0AKuxaAe1CaVhAI14FCgzrJ8g0y5nly+n7x\
+                            QzL2G2Dn8EoXM5Iqj8W99iokQoVsSrUEnaQ1WnSIfewvDDt4LCaD/w7PGETMCQ"
+                            .to_owned(),
+                        sender_key: "DeHIg4gwhClxzFYcmNntPNF9YtsdZbmMy8+3kzCMXHA".to_owned(),
+                        device_id: "NLAZCWIOCO".into(),
+                        session_id: SESSION_ID.into(),
+                    }
+                    .into(),
+                ),
+                None,
+            ),
+        )
+        .await;
+
+    let utd = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert!(utd.content().as_unable_to_decrypt().is_some());
+    assert!(reports.lock().unwrap().is_empty());
+
+    let own_user_id = user_id!("@example:morheus.localhost");
+    let exported_keys = decrypt_room_key_export(Cursor::new(SESSION_KEY), "1234").unwrap();
+
+    let olm_machine = OlmMachine::new(own_user_id, "SomeDeviceId".into()).await;
+    olm_machine.store().import_exported_room_keys(exported_keys, |_, _| {}).await.unwrap();
+
+    timeline
+        .inner
+        .retry_event_decryption_test(
+            room_id!("!DovneieKSTkdHKpIXy:morpheus.localhost"),
+            olm_machine,
+            Some(iter::once(SESSION_ID.to_owned()).collect()),
+        )
+        .await;
+
+    let decrypted = assert_next_matches!(stream, VectorDiff::Set { index: 1, value } => value);
+    assert_let!(TimelineItemContent::Message(message) = decrypted.content());
+    assert_eq!(message.body(), "It's a secret to everybody");
+
+    // The late decryption is reported exactly once.
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].0, EventItemIdentifier::EventId(utd.event_id().unwrap().to_owned()));
+}
+
 #[async_test]
 async fn test_retry_edit_decryption() {
     const SESSION1_KEY: &[u8] = b"\
@@ -0,0 +1,132 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use assert_matches::assert_matches;
+use imbl::Vector;
+use matrix_sdk_test::{async_test, ALICE, BOB};
+use ruma::{event_id, events::room::message::RoomMessageEventContent};
+
+use super::{TestRoomDataProvider, TestTimeline};
+use crate::timeline::{inner::TimelineInner, Error, TimelineFocus, TimelineItem};
+
+#[async_test]
+async fn test_import_events() {
+    let timeline = TestTimeline {
+        inner: TimelineInner::new(
+            TestRoomDataProvider::default(),
+            TimelineFocus::Detached,
+            None,
+            None,
+            Default::default(),
+        ),
+        event_builder: Default::default(),
+    };
+    let f = &timeline.event_builder;
+
+    f.set_next_ts(20);
+    let second = f.make_sync_message_event_with_id(
+        &BOB,
+        event_id!("$second"),
+        RoomMessageEventContent::text_plain("second"),
+    );
+    f.set_next_ts(10);
+    let first = f.make_sync_message_event_with_id(
+        &ALICE,
+        event_id!("$first"),
+        RoomMessageEventContent::text_plain("first"),
+    );
+    f.set_next_ts(30);
+    let third = f.make_sync_message_event_with_id(
+        &ALICE,
+        event_id!("$third"),
+        RoomMessageEventContent::text_plain("third"),
+    );
+
+    timeline.inner.import_events(vec![second.clone(), first]).await.unwrap();
+    // Importing an event again doesn't duplicate it.
+    timeline.inner.import_events(vec![third, second]).await.unwrap();
+
+    let items = timeline.inner.items().await;
+    let event_ids: Vec<_> = items
+        .iter()
+        .filter_map(|item| item.as_event()?.event_id().map(ToString::to_string))
+        .collect();
+    assert_eq!(event_ids, ["$first", "$second", "$third"]);
+}
+
+#[async_test]
+async fn test_import_events_in_live_timeline() {
+    let timeline = TestTimeline::new();
+    let event = timeline
+        .event_builder
+        .make_sync_message_event(&ALICE, RoomMessageEventContent::text_plain("hi"));
+
+    assert_matches!(timeline.inner.import_events(vec![event]).await, Err(Error::NotDetached));
+    assert_eq!(timeline.len().await, 0);
+}
+
+#[async_test]
+async fn test_import_events_with_equal_timestamps() {
+    let new_timeline = || TestTimeline {
+        inner: TimelineInner::new(
+            TestRoomDataProvider::default(),
+            TimelineFocus::Detached,
+            None,
+            None,
+            Default::default(),
+        ),
+        event_builder: Default::default(),
+    };
+    let event_ids = |items: Vector<Arc<TimelineItem>>| -> Vec<String> {
+        items
+            .iter()
+            .filter_map(|item| item.as_event()?.event_id().map(ToString::to_string))
+            .collect()
+    };
+
+    let timeline = new_timeline();
+    let f = &timeline.event_builder;
+
+    // Two events with the same timestamp, and with event IDs sorting in the
+    // opposite order.
+    f.set_next_ts(10);
+    let first = f.make_sync_message_event_with_id(
+        &ALICE,
+        event_id!("$b"),
+        RoomMessageEventContent::text_plain("first"),
+    );
+    f.set_next_ts(10);
+    let second = f.make_sync_message_event_with_id(
+        &BOB,
+        event_id!("$a"),
+        RoomMessageEventContent::text_plain("second"),
+    );
+
+    // They are imported in different batches.
+    timeline.inner.import_events(vec![first.clone()]).await.unwrap();
+    timeline.inner.import_events(vec![second.clone()]).await.unwrap();
+    assert_eq!(event_ids(timeline.inner.items().await), ["$b", "$a"]);
+
+    // Importing them again doesn't change their order.
+    timeline.inner.import_events(vec![second.clone(), first.clone()]).await.unwrap();
+    assert_eq!(event_ids(timeline.inner.items().await), ["$b", "$a"]);
+
+    // Reloading them from the store, where they are in the order of the sync
+    // stream, into a new timeline keeps the same order.
+    let reloaded = new_timeline();
+    reloaded.inner.import_events(vec![first, second]).await.unwrap();
+    assert_eq!(event_ids(reloaded.inner.items().await), ["$b", "$a"]);
+}
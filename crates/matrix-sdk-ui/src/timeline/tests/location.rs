@@ -0,0 +1,154 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use assert_matches2::assert_let;
+use eyeball_im::VectorDiff;
+use matrix_sdk_test::{async_test, sync_timeline_event, ALICE};
+use ruma::{
+    events::{
+        room::message::{LocationMessageEventContent, MessageType, RoomMessageEventContent},
+        AnySyncTimelineEvent,
+    },
+    serde::Raw,
+    EventId, UserId,
+};
+use stream_assert::{assert_next_matches, assert_pending};
+
+use super::TestTimeline;
+use crate::timeline::{LiveLocationStatus, LocationAssetType, TimelineItemContent};
+
+fn beacon_info(event_id: &str, sender: &UserId, live: bool, ts: u64) -> Raw<AnySyncTimelineEvent> {
+    sync_timeline_event!({
+        "content": {
+            "description": "Alice's location",
+            "live": live,
+            "org.matrix.msc3488.ts": ts,
+            "timeout": 3_600_000,
+            "org.matrix.msc3488.asset": { "type": "m.self" },
+        },
+        "event_id": event_id,
+        "origin_server_ts": ts,
+        "sender": sender,
+        "state_key": sender,
+        "type": "org.matrix.msc3672.beacon_info",
+    })
+}
+
+fn beacon(event_id: &str, start_id: &EventId, uri: &str, ts: u64) -> Raw<AnySyncTimelineEvent> {
+    sync_timeline_event!({
+        "content": {
+            "m.relates_to": {
+                "rel_type": "m.reference",
+                "event_id": start_id,
+            },
+            "org.matrix.msc3488.location": { "uri": uri },
+            "org.matrix.msc3488.ts": ts,
+        },
+        "event_id": event_id,
+        "origin_server_ts": ts,
+        "sender": *ALICE,
+        "type": "org.matrix.msc3672.beacon",
+    })
+}
+
+#[async_test]
+async fn test_location_message() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+
+    timeline
+        .handle_live_message_event(
+            &ALICE,
+            RoomMessageEventContent::new(MessageType::Location(LocationMessageEventContent::new(
+                "Location was shared at geo:51.5008,0.1247;u=35".to_owned(),
+                "geo:51.5008,0.1247;u=35".to_owned(),
+            ))),
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert_let!(TimelineItemContent::Message(message) = item.content());
+    assert_let!(Some(location) = message.location());
+    assert_eq!(location.latitude, 51.5008);
+    assert_eq!(location.longitude, 0.1247);
+    assert_eq!(location.uncertainty, Some(35.0));
+    assert_eq!(location.asset, LocationAssetType::Sender);
+}
+
+#[async_test]
+async fn test_live_location_share() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+    let start_id = EventId::parse("$start").unwrap();
+
+    // The share begins.
+    timeline.handle_live_custom_event(beacon_info("$start", &ALICE, true, 1_000)).await;
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert_let!(TimelineItemContent::LiveLocation(state) = item.content());
+    assert_eq!(state.status(), LiveLocationStatus::Started);
+    assert_eq!(state.description(), Some("Alice's location"));
+    assert_eq!(state.asset(), LocationAssetType::Sender);
+    assert_eq!(u64::from(state.expires_at().get()), 3_601_000);
+    assert!(state.last_location().is_none());
+
+    // The location is updated.
+    timeline.handle_live_custom_event(beacon("$update1", &start_id, "geo:48.2,16.3", 2_000)).await;
+    let item = assert_next_matches!(stream, VectorDiff::Set { index: 0, value } => value);
+    assert_let!(TimelineItemContent::LiveLocation(state) = item.content());
+    assert_eq!(state.status(), LiveLocationStatus::Updated);
+    assert_let!(Some(update) = state.last_location());
+    assert_eq!(update.location.latitude, 48.2);
+    assert_eq!(u64::from(update.timestamp.get()), 2_000);
+
+    // Older updates are ignored.
+    timeline.handle_live_custom_event(beacon("$update0", &start_id, "geo:1,1", 1_500)).await;
+    let item = assert_next_matches!(stream, VectorDiff::Set { index: 0, value } => value);
+    assert_let!(TimelineItemContent::LiveLocation(state) = item.content());
+    assert_eq!(state.last_location().unwrap().location.latitude, 48.2);
+
+    // The share is stopped, without a new item.
+    timeline.handle_live_custom_event(beacon_info("$stop", &ALICE, false, 3_000)).await;
+    let item = assert_next_matches!(stream, VectorDiff::Set { index: 0, value } => value);
+    assert_let!(TimelineItemContent::LiveLocation(state) = item.content());
+    assert_eq!(state.status(), LiveLocationStatus::Stopped);
+    assert_eq!(state.stopped_at().map(|ts| u64::from(ts.get())), Some(3_000));
+    assert!(!state.is_live());
+    assert_pending!(stream);
+}
+
+#[async_test]
+async fn test_live_location_share_back_paginated() {
+    let timeline = TestTimeline::new();
+    let start_id = EventId::parse("$start").unwrap();
+
+    // When paginating backwards, the stop and the updates come before the start.
+    timeline
+        .handle_back_paginated_custom_event(beacon_info("$stop", &ALICE, false, 3_000).cast())
+        .await;
+    timeline
+        .handle_back_paginated_custom_event(
+            beacon("$update", &start_id, "geo:48.2,16.3", 2_000).cast(),
+        )
+        .await;
+    timeline
+        .handle_back_paginated_custom_event(beacon_info("$start", &ALICE, true, 1_000).cast())
+        .await;
+
+    let items = timeline.inner.items().await;
+    assert_eq!(items.len(), 2);
+    assert!(items[0].is_day_divider());
+    assert_let!(TimelineItemContent::LiveLocation(state) = items[1].as_event().unwrap().content());
+    assert_eq!(state.status(), LiveLocationStatus::Stopped);
+    assert_eq!(state.last_location().unwrap().location.longitude, 16.3);
+}
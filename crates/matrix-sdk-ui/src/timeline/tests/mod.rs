@@ -26,7 +26,7 @@ use futures_core::Stream;
 use futures_util::{FutureExt, StreamExt};
 use indexmap::IndexMap;
 use matrix_sdk::{
-    deserialized_responses::{SyncTimelineEvent, TimelineEvent},
+    deserialized_responses::{SyncTimelineEvent, TimelineEvent, VerificationState},
     event_cache::paginator::{PaginableRoom, PaginatorError},
     room::{EventWithContextResponse, Messages, MessagesOptions},
 };
@@ -46,8 +46,8 @@ use ruma::{
     push::{PushConditionPowerLevelsCtx, PushConditionRoomCtx, Ruleset},
     room_id,
     serde::Raw,
-    server_name, uint, EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedTransactionId,
-    OwnedUserId, RoomId, RoomVersionId, TransactionId, UInt, UserId,
+    server_name, uint, DeviceId, EventId, MilliSecondsSinceUnixEpoch, OwnedDeviceId, OwnedEventId,
+    OwnedTransactionId, OwnedUserId, RoomId, RoomVersionId, TransactionId, UInt, UserId,
 };
 
 use super::{
@@ -66,12 +66,16 @@ mod edit;
 #[cfg(feature = "e2e-encryption")]
 mod encryption;
 mod event_filter;
+mod import;
 mod invalid;
+mod location;
 mod polls;
 mod reaction_group;
 mod reactions;
 mod read_receipts;
 mod redaction;
+mod search;
+mod shields;
 mod virt;
 
 struct TestTimeline {
@@ -91,6 +95,7 @@ impl TestTimeline {
                 TimelineFocus::Live,
                 Some(prefix),
                 None,
+                Default::default(),
             ),
             event_builder: EventBuilder::new(),
         }
@@ -98,7 +103,13 @@ impl TestTimeline {
 
     fn with_room_data_provider(room_data_provider: TestRoomDataProvider) -> Self {
         Self {
-            inner: TimelineInner::new(room_data_provider, TimelineFocus::Live, None, None),
+            inner: TimelineInner::new(
+                room_data_provider,
+                TimelineFocus::Live,
+                None,
+                None,
+                Default::default(),
+            ),
             event_builder: EventBuilder::new(),
         }
     }
@@ -110,14 +121,23 @@ impl TestTimeline {
                 TimelineFocus::Live,
                 None,
                 Some(hook),
+                Default::default(),
             ),
             event_builder: EventBuilder::new(),
         }
     }
 
-    fn with_settings(mut self, settings: TimelineInnerSettings) -> Self {
-        self.inner = self.inner.with_settings(settings);
-        self
+    fn with_settings(self, settings: TimelineInnerSettings) -> Self {
+        Self {
+            inner: TimelineInner::new(
+                self.inner.room_data_provider().clone(),
+                TimelineFocus::Live,
+                None,
+                None,
+                settings,
+            ),
+            event_builder: self.event_builder,
+        }
     }
 
     async fn subscribe(&self) -> impl Stream<Item = VectorDiff<Arc<TimelineItem>>> {
@@ -299,6 +319,14 @@ impl TestTimeline {
     ) -> Result<ReactionAction, super::Error> {
         self.inner.resolve_reaction_response(annotation, result).await
     }
+
+    async fn settle_reaction(
+        &self,
+        annotation: &Annotation,
+        action: ReactionAction,
+    ) -> Result<ReactionAction, super::Error> {
+        self.inner.settle_reaction(annotation, action).await
+    }
 }
 
 type ReadReceiptMap =
@@ -308,6 +336,7 @@ type ReadReceiptMap =
 struct TestRoomDataProvider {
     initial_user_receipts: ReadReceiptMap,
     fully_read_marker: Option<OwnedEventId>,
+    device_verification_states: BTreeMap<(OwnedUserId, OwnedDeviceId), VerificationState>,
 }
 
 impl TestRoomDataProvider {
@@ -319,6 +348,16 @@ impl TestRoomDataProvider {
         self.fully_read_marker = Some(event_id);
         self
     }
+    fn with_device_verification_state(
+        mut self,
+        user_id: &UserId,
+        device_id: &DeviceId,
+        verification_state: VerificationState,
+    ) -> Self {
+        self.device_verification_states
+            .insert((user_id.to_owned(), device_id.to_owned()), verification_state);
+        self
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
@@ -348,6 +387,22 @@ impl RoomDataProvider for TestRoomDataProvider {
         RoomVersionId::V10
     }
 
+    fn is_pinned_event(&self, _event_id: &EventId) -> bool {
+        false
+    }
+
+    fn is_room_encrypted(&self) -> bool {
+        false
+    }
+
+    async fn device_verification_state(
+        &self,
+        user_id: &UserId,
+        device_id: &DeviceId,
+    ) -> Option<VerificationState> {
+        self.device_verification_states.get(&(user_id.to_owned(), device_id.to_owned())).cloned()
+    }
+
     async fn profile_from_user_id(&self, _user_id: &UserId) -> Option<Profile> {
         None
     }
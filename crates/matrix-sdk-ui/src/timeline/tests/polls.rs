@@ -159,6 +159,51 @@ async fn a_somewhat_complex_voting_session_yields_the_expected_outcome() {
     assert_eq!(results.votes["id_down"], vec![ALICE.to_string()]);
 }
 
+#[async_test]
+async fn own_answers_are_reported() {
+    let timeline = TestTimeline::new();
+    timeline.send_poll_start(&BOB, fakes::poll_a()).await;
+    let poll_id = timeline.poll_event().await.event_id().unwrap().to_owned();
+
+    // Only Bob votes
+    timeline.send_poll_response(&BOB, vec!["id_up"], &poll_id).await;
+    let results = timeline.poll_state().await.results();
+    assert!(results.own_answers.is_empty());
+
+    // Alice, the own user, votes
+    timeline.send_poll_response(&ALICE, vec!["id_down"], &poll_id).await;
+    let results = timeline.poll_state().await.results();
+    assert_eq!(results.own_answers, vec!["id_down".to_owned()]);
+
+    // Alice removes her vote
+    timeline.send_poll_response(&ALICE, vec![], &poll_id).await;
+    let results = timeline.poll_state().await.results();
+    assert!(results.own_answers.is_empty());
+}
+
+#[async_test]
+async fn local_echo_of_vote_is_counted() {
+    let timeline = TestTimeline::new();
+    timeline.send_poll_start(&BOB, fakes::poll_a()).await;
+    let poll_id = timeline.poll_event().await.event_id().unwrap().to_owned();
+
+    timeline
+        .handle_local_event(AnyMessageLikeEventContent::UnstablePollResponse(
+            UnstablePollResponseEventContent::new(vec!["id_up".to_owned()], poll_id.clone()),
+        ))
+        .await;
+    let results = timeline.poll_state().await.results();
+    assert_eq!(results.votes["id_up"], vec![ALICE.to_string()]);
+    assert_eq!(results.own_answers, vec!["id_up".to_owned()]);
+
+    timeline
+        .handle_local_event(AnyMessageLikeEventContent::UnstablePollEnd(
+            UnstablePollEndEventContent::new("ENDED", poll_id),
+        ))
+        .await;
+    assert!(timeline.poll_state().await.results().end_time.is_some());
+}
+
 #[async_test]
 async fn events_received_before_start_are_not_lost() {
     let timeline = TestTimeline::new();
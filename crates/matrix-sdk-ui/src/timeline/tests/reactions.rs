@@ -22,7 +22,7 @@ use matrix_sdk_base::deserialized_responses::SyncTimelineEvent;
 use matrix_sdk_test::{async_test, ALICE, BOB};
 use ruma::{
     events::{relation::Annotation, room::message::RoomMessageEventContent},
-    server_name, uint, EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, TransactionId,
+    mxc_uri, server_name, uint, EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, TransactionId,
 };
 use stream_assert::assert_next_matches;
 
@@ -31,7 +31,7 @@ use crate::timeline::{
     inner::{ReactionAction, TimelineEnd},
     reactions::ReactionToggleResult,
     tests::{assert_event_is_updated, assert_no_more_updates, TestTimeline},
-    TimelineItem,
+    ReactionKey, TimelineItem,
 };
 
 const REACTION_KEY: &str = "👍";
@@ -202,6 +202,87 @@ async fn test_toggle_during_request_resolves_new_action() {
     assert_no_more_updates(&mut stream).await;
 }
 
+#[async_test]
+async fn test_odd_burst_of_toggles_settles_to_added() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe().await;
+    let (msg_id, msg_pos) = send_first_message(&timeline, &mut stream).await;
+    let reaction = create_reaction(&msg_id);
+
+    // Add, remove and add the reaction again before the end of the burst.
+    let action = timeline.toggle_reaction_local(&reaction).await.unwrap();
+    assert_let!(ReactionAction::SendRemote(txn_id) = action);
+    assert_reaction_is_added(&mut stream, &msg_id, msg_pos).await;
+
+    let action = timeline.toggle_reaction_local(&reaction).await.unwrap();
+    assert_matches!(action, ReactionAction::None);
+    assert_reactions_are_removed(&mut stream, &msg_id, msg_pos).await;
+
+    let action = timeline.toggle_reaction_local(&reaction).await.unwrap();
+    assert_matches!(action, ReactionAction::None);
+    assert_reaction_is_updated(&mut stream, &msg_id, msg_pos, None, Some(&txn_id)).await;
+
+    // The net result is a single request to send the reaction.
+    let action =
+        timeline.settle_reaction(&reaction, ReactionAction::SendRemote(txn_id.clone())).await;
+    assert_let!(Ok(ReactionAction::SendRemote(settled_txn_id)) = action);
+    assert_eq!(settled_txn_id, txn_id);
+    assert_no_more_updates(&mut stream).await;
+
+    let event_id = EventId::new(server_name!("example.org"));
+    let action = timeline
+        .handle_reaction_response(
+            &reaction,
+            &ReactionToggleResult::AddSuccess { event_id: event_id.clone(), txn_id },
+        )
+        .await
+        .unwrap();
+    assert_matches!(action, ReactionAction::None);
+    assert_reaction_is_updated(&mut stream, &msg_id, msg_pos, Some(&event_id), None).await;
+
+    assert_no_more_updates(&mut stream).await;
+}
+
+#[async_test]
+async fn test_odd_burst_of_toggles_settles_to_redacted() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe().await;
+    let (msg_id, msg_pos) = send_first_message(&timeline, &mut stream).await;
+    let reaction = create_reaction(&msg_id);
+
+    let event_id = timeline.handle_live_reaction(&ALICE, &reaction).await;
+    assert_reaction_is_updated(&mut stream, &msg_id, msg_pos, Some(&event_id), None).await;
+
+    // Remove, add and remove the reaction again before the end of the burst.
+    let action = timeline.toggle_reaction_local(&reaction).await.unwrap();
+    assert_let!(ReactionAction::RedactRemote(redacted_event_id) = action);
+    assert_eq!(redacted_event_id, event_id);
+    assert_reactions_are_removed(&mut stream, &msg_id, msg_pos).await;
+
+    let action = timeline.toggle_reaction_local(&reaction).await.unwrap();
+    assert_matches!(action, ReactionAction::None);
+    assert_reaction_is_added(&mut stream, &msg_id, msg_pos).await;
+
+    let action = timeline.toggle_reaction_local(&reaction).await.unwrap();
+    assert_matches!(action, ReactionAction::None);
+    assert_reactions_are_removed(&mut stream, &msg_id, msg_pos).await;
+
+    // The net result is a single request to redact the remote echo.
+    let action =
+        timeline.settle_reaction(&reaction, ReactionAction::RedactRemote(event_id.clone())).await;
+    assert_let!(Ok(ReactionAction::RedactRemote(settled_event_id)) = action);
+    assert_eq!(settled_event_id, event_id);
+    assert_no_more_updates(&mut stream).await;
+
+    let action = timeline
+        .handle_reaction_response(&reaction, &ReactionToggleResult::RedactSuccess)
+        .await
+        .unwrap();
+    assert_matches!(action, ReactionAction::None);
+
+    assert_no_more_updates(&mut stream).await;
+}
+
 #[async_test]
 async fn test_reactions_store_timestamp() {
     let timeline = TestTimeline::new();
@@ -338,3 +419,33 @@ fn timestamp_range_until_now_from(
 ) -> RangeInclusive<MilliSecondsSinceUnixEpoch> {
     timestamp..=MilliSecondsSinceUnixEpoch::now()
 }
+
+#[async_test]
+async fn test_custom_emoji_reaction() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe().await;
+    let (msg_id, msg_pos) = send_first_message(&timeline, &mut stream).await;
+
+    let custom_key = "mxc://example.org/party_parrot";
+    let custom_reaction = Annotation::new(msg_id.clone(), custom_key.to_owned());
+    timeline.toggle_reaction_local(&custom_reaction).await.unwrap();
+    assert_event_is_updated(&mut stream, &msg_id, msg_pos).await;
+
+    timeline.toggle_reaction_local(&create_reaction(&msg_id)).await.unwrap();
+    let event = assert_event_is_updated(&mut stream, &msg_id, msg_pos).await;
+
+    // Custom emoji and unicode reactions are grouped separately.
+    let reactions = event.reactions();
+    assert_eq!(reactions.len(), 2);
+    assert_eq!(reactions[custom_key].len(), 1);
+    assert_eq!(reactions[REACTION_KEY].len(), 1);
+
+    assert_eq!(
+        ReactionKey::parse(custom_key).media_url(),
+        Some(mxc_uri!("mxc://example.org/party_parrot"))
+    );
+    assert_eq!(ReactionKey::parse(REACTION_KEY), ReactionKey::Text(REACTION_KEY.to_owned()));
+    assert_eq!(ReactionKey::parse(REACTION_KEY).media_url(), None);
+
+    assert_no_more_updates(&mut stream).await;
+}
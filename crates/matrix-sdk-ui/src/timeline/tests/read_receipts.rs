@@ -28,7 +28,7 @@ use ruma::{
 use stream_assert::{assert_next_matches, assert_pending};
 
 use super::{ReadReceiptMap, TestRoomDataProvider, TestTimeline};
-use crate::timeline::inner::TimelineInnerSettings;
+use crate::timeline::{inner::TimelineInnerSettings, OwnDeliveryStatus};
 
 fn filter_notice(ev: &AnySyncTimelineEvent, _room_version: &RoomVersionId) -> bool {
     match ev {
@@ -52,14 +52,22 @@ async fn test_read_receipts_updates_on_live_events() {
     let item_a = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
     let event_a = item_a.as_event().unwrap();
     assert!(event_a.read_receipts().is_empty());
+    assert_eq!(event_a.own_delivery_status(), Some(OwnDeliveryStatus::Sent));
 
     let _day_divider = assert_next_matches!(stream, VectorDiff::PushFront { value } => value);
 
+    // Bob read past our own event.
+    let item_a = assert_next_matches!(stream, VectorDiff::Set { index: 1, value } => value);
+    let event_a = item_a.as_event().unwrap();
+    assert!(event_a.read_receipts().is_empty());
+    assert_eq!(event_a.own_delivery_status(), Some(OwnDeliveryStatus::Read));
+
     // Implicit read receipt of Bob.
     let item_b = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
     let event_b = item_b.as_event().unwrap();
     assert_eq!(event_b.read_receipts().len(), 1);
     assert!(event_b.read_receipts().get(*BOB).is_some());
+    assert_eq!(event_b.own_delivery_status(), None);
 
     // Implicit read receipt of Bob is updated.
     timeline.handle_live_message_event(*BOB, RoomMessageEventContent::text_plain("C")).await;
@@ -78,6 +86,7 @@ async fn test_read_receipts_updates_on_live_events() {
     let item_d = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
     let event_d = item_d.as_event().unwrap();
     assert!(event_d.read_receipts().is_empty());
+    assert_eq!(event_d.own_delivery_status(), Some(OwnDeliveryStatus::Sent));
 
     // Explicit read receipt is updated.
     timeline
@@ -97,6 +106,7 @@ async fn test_read_receipts_updates_on_live_events() {
     let event_d = item_d.as_event().unwrap();
     assert_eq!(event_d.read_receipts().len(), 1);
     assert!(event_d.read_receipts().get(*BOB).is_some());
+    assert_eq!(event_d.own_delivery_status(), Some(OwnDeliveryStatus::Read));
 }
 
 #[async_test]
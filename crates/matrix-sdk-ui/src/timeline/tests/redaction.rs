@@ -248,3 +248,73 @@ async fn test_receive_unredacted() {
     assert!(items[1].as_event().unwrap().content.is_redacted());
     assert!(items[2].as_event().unwrap().content.is_redacted());
 }
+
+#[async_test]
+async fn test_redact_sticker() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+
+    timeline
+        .handle_live_custom_event(sync_timeline_event!({
+            "content": {
+                "body": "Happy sticker",
+                "info": {
+                    "h": 398,
+                    "mimetype": "image/jpeg",
+                    "size": 31037,
+                    "w": 394
+                },
+                "url": "mxc://server.name/JWEIFJgwEIhweiWJE",
+            },
+            "event_id": "$143273582443PhrSn",
+            "origin_server_ts": 143273582,
+            "sender": &*ALICE,
+            "type": "m.sticker",
+        }))
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert_matches!(item.content(), TimelineItemContent::Sticker(_));
+    let sticker_event_id = item.event_id().unwrap().to_owned();
+
+    // Stickers can be reacted to like messages.
+    let rel = Annotation::new(sticker_event_id.clone(), "+1".to_owned());
+    timeline.handle_live_message_event(&BOB, ReactionEventContent::new(rel)).await;
+    let item = assert_next_matches!(stream, VectorDiff::Set { index: 0, value } => value);
+    assert_matches!(item.content(), TimelineItemContent::Sticker(_));
+    assert_eq!(item.reactions().len(), 1);
+
+    timeline.handle_live_redaction(&ALICE, &sticker_event_id).await;
+    let item = assert_next_matches!(stream, VectorDiff::Set { index: 0, value } => value);
+    assert_matches!(item.content(), TimelineItemContent::RedactedMessage);
+    assert_matches!(item.original_json(), None);
+}
+
+#[async_test]
+async fn test_redacted_sticker() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+
+    timeline
+        .handle_live_custom_event(sync_timeline_event!({
+            "content": {},
+            "event_id": "$143273582443PhrSn",
+            "origin_server_ts": 143273582,
+            "sender": &*ALICE,
+            "type": "m.sticker",
+            "unsigned": {
+                "redacted_because": {
+                    "content": {},
+                    "redacts": "$143273582443PhrSn",
+                    "event_id": "$N6eUCBc3vu58PL8TobGaVQzM",
+                    "sender": &*ALICE,
+                    "origin_server_ts": 143273583,
+                    "type": "m.room.redaction",
+                },
+            },
+        }))
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert_matches!(item.content(), TimelineItemContent::RedactedMessage);
+}
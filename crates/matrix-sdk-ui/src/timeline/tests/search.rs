@@ -0,0 +1,68 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use matrix_sdk_test::{async_test, ALICE, BOB};
+use ruma::{
+    events::room::message::{
+        ImageMessageEventContent, MessageType, RedactedRoomMessageEventContent,
+        RoomMessageEventContent,
+    },
+    mxc_uri, owned_event_id,
+};
+
+use super::TestTimeline;
+use crate::timeline::EventItemIdentifier;
+
+#[async_test]
+async fn test_search_local() {
+    let timeline = TestTimeline::new();
+
+    let first = owned_event_id!("$first");
+    let second = owned_event_id!("$second");
+
+    timeline
+        .handle_live_message_event_with_id(
+            &ALICE,
+            &first,
+            RoomMessageEventContent::text_plain("Let's meet at the Café"),
+        )
+        .await;
+    timeline.handle_live_redacted_message_event(&BOB, RedactedRoomMessageEventContent::new()).await;
+    timeline
+        .handle_live_message_event(
+            &BOB,
+            RoomMessageEventContent::new(MessageType::Image(ImageMessageEventContent::plain(
+                "cafe.png".to_owned(),
+                mxc_uri!("mxc://example.org/cafe").to_owned(),
+            ))),
+        )
+        .await;
+    timeline
+        .handle_live_message_event_with_id(
+            &BOB,
+            &second,
+            RoomMessageEventContent::notice_plain("cafe is closed"),
+        )
+        .await;
+
+    // Case- and accent-insensitive, newest first, skipping non-text items.
+    assert_eq!(
+        timeline.inner.search_local("CAFÉ").await,
+        [EventItemIdentifier::EventId(second), EventItemIdentifier::EventId(first.clone())]
+    );
+    assert_eq!(timeline.inner.search_local("meet").await, [EventItemIdentifier::EventId(first)]);
+
+    assert!(timeline.inner.search_local("tea").await.is_empty());
+    assert!(timeline.inner.search_local(" ").await.is_empty());
+}
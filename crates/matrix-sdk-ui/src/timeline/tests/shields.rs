@@ -0,0 +1,225 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use assert_matches::assert_matches;
+use assert_matches2::assert_let;
+use eyeball_im::VectorDiff;
+use matrix_sdk::deserialized_responses::{
+    AlgorithmInfo, DeviceLinkProblem, EncryptionInfo, ShieldState, SyncTimelineEvent,
+    VerificationLevel, VerificationState,
+};
+use matrix_sdk_test::{async_test, ALICE, BOB};
+use ruma::{
+    device_id,
+    events::room::{message::RoomMessageEventContent, topic::RoomTopicEventContent},
+    owned_device_id, server_name, EventId,
+};
+use stream_assert::assert_next_matches;
+
+use super::{TestRoomDataProvider, TestTimeline};
+use crate::timeline::{
+    event_item::RemoteEventOrigin,
+    inner::{TimelineEnd, TimelineInnerSettings},
+    EventTimelineItem, ShieldReason, TimelineItemContent,
+};
+
+/// The verification states used by the tests, along with the expected
+/// [`ShieldReason`].
+fn verification_states() -> Vec<(VerificationState, Option<ShieldReason>)> {
+    vec![
+        (VerificationState::Verified, None),
+        (
+            VerificationState::Unverified(VerificationLevel::UnverifiedIdentity),
+            Some(ShieldReason::UnverifiedDevice),
+        ),
+        (
+            VerificationState::Unverified(VerificationLevel::UnsignedDevice),
+            Some(ShieldReason::UnsignedDevice),
+        ),
+        (
+            VerificationState::Unverified(VerificationLevel::None(
+                DeviceLinkProblem::MissingDevice,
+            )),
+            Some(ShieldReason::UnknownDevice),
+        ),
+        (
+            VerificationState::Unverified(VerificationLevel::None(
+                DeviceLinkProblem::InsecureSource,
+            )),
+            Some(ShieldReason::AuthenticityNotGuaranteed),
+        ),
+    ]
+}
+
+/// Add one encrypted message per verification state of
+/// [`verification_states`], then an unencrypted message, in an encrypted room.
+///
+/// Returns the event items.
+async fn add_shielded_events(timeline: &TestTimeline) -> Vec<EventTimelineItem> {
+    for (verification_state, _) in verification_states() {
+        let event_id = EventId::new(server_name!("dummy.server"));
+        let event = timeline.event_builder.make_sync_message_event_with_id(
+            &ALICE,
+            &event_id,
+            RoomMessageEventContent::text_plain("hi"),
+        );
+        let encryption_info = EncryptionInfo {
+            sender: ALICE.to_owned(),
+            sender_device: Some(owned_device_id!("ALICEDEVICE")),
+            algorithm_info: AlgorithmInfo::MegolmV1AesSha2 {
+                curve25519_key: "curve25519_key".to_owned(),
+                sender_claimed_keys: BTreeMap::new(),
+            },
+            verification_state,
+        };
+        timeline
+            .inner
+            .add_events_at(
+                vec![SyncTimelineEvent {
+                    event,
+                    encryption_info: Some(encryption_info),
+                    push_actions: vec![],
+                }],
+                TimelineEnd::Back,
+                RemoteEventOrigin::Sync,
+            )
+            .await;
+    }
+
+    timeline.handle_live_message_event(&ALICE, RoomMessageEventContent::text_plain("hi")).await;
+    timeline.inner.update_room_encrypted(true).await;
+
+    timeline.inner.items().await.iter().filter_map(|item| item.as_event().cloned()).collect()
+}
+
+#[async_test]
+async fn test_shield_reason_from_verification_state() {
+    let timeline = TestTimeline::new();
+    let items = add_shielded_events(&timeline).await;
+
+    let reasons = items.iter().map(EventTimelineItem::shield_reason).collect::<Vec<_>>();
+    let mut expected =
+        verification_states().into_iter().map(|(_, reason)| reason).collect::<Vec<_>>();
+    expected.push(Some(ShieldReason::SentInClear));
+    assert_eq!(reasons, expected);
+}
+
+#[async_test]
+async fn test_lax_shields() {
+    let timeline = TestTimeline::new();
+    let items = add_shielded_events(&timeline).await;
+
+    let shields = items.iter().map(EventTimelineItem::shield).collect::<Vec<_>>();
+    assert_matches!(
+        &shields[..],
+        [
+            ShieldState::None,
+            // A cross-signed device of an unverified user doesn't get a shield.
+            ShieldState::None,
+            ShieldState::Red { .. },
+            ShieldState::Red { .. },
+            // An insecure key source gets a grey shield.
+            ShieldState::Grey { .. },
+            ShieldState::Red { .. },
+        ]
+    );
+
+    // The mode can still be chosen per call.
+    let strict_shields = items.iter().map(|item| item.get_shield(true)).collect::<Vec<_>>();
+    assert_matches!(&strict_shields[1], ShieldState::Red { .. });
+}
+
+#[async_test]
+async fn test_strict_shields() {
+    let timeline = TestTimeline::new()
+        .with_settings(TimelineInnerSettings { strict_shields: true, ..Default::default() });
+    let items = add_shielded_events(&timeline).await;
+
+    let shields = items.iter().map(EventTimelineItem::shield).collect::<Vec<_>>();
+    assert_matches!(
+        &shields[..],
+        [
+            ShieldState::None,
+            ShieldState::Red { .. },
+            ShieldState::Red { .. },
+            ShieldState::Red { .. },
+            ShieldState::Red { .. },
+            ShieldState::Red { .. },
+        ]
+    );
+}
+
+#[async_test]
+async fn test_shield_reason_sent_in_clear() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+
+    timeline.handle_live_message_event(&ALICE, RoomMessageEventContent::text_plain("hi")).await;
+    timeline
+        .handle_live_state_event(&ALICE, RoomTopicEventContent::new("Topic".to_owned()), None)
+        .await;
+
+    let message = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert_eq!(message.shield_reason(), None);
+    let state = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert_eq!(state.shield_reason(), None);
+
+    // Once the room is encrypted, the unencrypted message gets a shield, but not
+    // the state event.
+    timeline.inner.update_room_encrypted(true).await;
+
+    let message = assert_next_matches!(stream, VectorDiff::Set { index: 0, value } => value);
+    assert_let!(TimelineItemContent::Message(_) = message.content());
+    assert_eq!(message.shield_reason(), Some(ShieldReason::SentInClear));
+    let state = assert_next_matches!(stream, VectorDiff::Set { index: 1, value } => value);
+    assert_eq!(state.shield_reason(), None);
+}
+
+#[async_test]
+async fn test_shield_reason_updated_on_verification() {
+    let room_data_provider = TestRoomDataProvider::default().with_device_verification_state(
+        &ALICE,
+        device_id!("ALICEDEVICE"),
+        VerificationState::Verified,
+    );
+    let timeline = TestTimeline::with_room_data_provider(room_data_provider);
+    let items = add_shielded_events(&timeline).await;
+    let initial_reasons = items.iter().map(EventTimelineItem::shield_reason).collect::<Vec<_>>();
+
+    // The events of other users are not updated.
+    timeline.inner.update_verification_states(Some(&BTreeSet::from([BOB.to_owned()]))).await;
+    assert_eq!(shield_reasons(&timeline).await, initial_reasons);
+
+    // Once the device of the sender is verified, only the events whose key can't
+    // be linked to the device, and the one sent in the clear, keep a shield.
+    timeline.inner.update_verification_states(Some(&BTreeSet::from([ALICE.to_owned()]))).await;
+    assert_eq!(
+        shield_reasons(&timeline).await,
+        [
+            None,
+            None,
+            None,
+            Some(ShieldReason::UnknownDevice),
+            Some(ShieldReason::AuthenticityNotGuaranteed),
+            Some(ShieldReason::SentInClear),
+        ]
+    );
+}
+
+async fn shield_reasons(timeline: &TestTimeline) -> Vec<Option<ShieldReason>> {
+    let items = timeline.inner.items().await;
+    items.iter().filter_map(|item| item.as_event()).map(EventTimelineItem::shield_reason).collect()
+}
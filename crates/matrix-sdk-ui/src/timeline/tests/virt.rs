@@ -16,15 +16,25 @@ use assert_matches::assert_matches;
 use assert_matches2::assert_let;
 use chrono::{Datelike, Local, TimeZone};
 use eyeball_im::VectorDiff;
-use matrix_sdk_test::{async_test, ALICE, BOB};
+use itertools::Itertools;
+use matrix_sdk_test::{async_test, ALICE, BOB, CAROL};
 use ruma::{
     event_id,
-    events::{room::message::RoomMessageEventContent, AnyMessageLikeEventContent},
+    events::{
+        room::{
+            member::{MembershipState, RoomMemberEventContent},
+            message::RoomMessageEventContent,
+        },
+        AnyMessageLikeEventContent,
+    },
 };
-use stream_assert::assert_next_matches;
+use stream_assert::{assert_next_matches, assert_pending};
 
 use super::TestTimeline;
-use crate::timeline::{TimelineItemKind, VirtualTimelineItem};
+use crate::timeline::{
+    inner::TimelineInnerSettings, DividerGranularity, TimelineItemKind, VirtualTimelineItem,
+    Weekday,
+};
 
 #[async_test]
 async fn test_day_divider() {
@@ -153,3 +163,166 @@ async fn test_update_read_marker() {
     let marker = assert_next_matches!(stream, VectorDiff::Insert { index: 4, value } => value);
     assert_matches!(marker.kind, TimelineItemKind::Virtual(VirtualTimelineItem::ReadMarker));
 }
+
+#[async_test]
+async fn test_no_date_dividers() {
+    let timeline = TestTimeline::new()
+        .with_settings(TimelineInnerSettings { date_dividers: false, ..Default::default() });
+    let mut stream = timeline.subscribe().await;
+
+    timeline.handle_live_message_event(&ALICE, RoomMessageEventContent::text_plain("A")).await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    let first_event_id = item.as_event().unwrap().event_id().unwrap().to_owned();
+
+    timeline.inner.set_fully_read_event(first_event_id).await;
+
+    // Even though the next message is on another day, no day divider is inserted.
+    timeline.event_builder.set_next_ts(24 * 60 * 60 * 1000);
+    timeline.handle_live_message_event(&BOB, RoomMessageEventContent::text_plain("B")).await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    item.as_event().unwrap();
+
+    // The read marker still appears after the first event.
+    let item = assert_next_matches!(stream, VectorDiff::Insert { index: 1, value } => value);
+    assert_matches!(item.as_virtual(), Some(VirtualTimelineItem::ReadMarker));
+
+    assert_pending!(stream);
+    assert!(!timeline.inner.items().await.iter().any(|item| item.is_day_divider()));
+}
+
+#[async_test]
+async fn test_divider_granularity() {
+    const HOUR: u64 = 60 * 60 * 1000;
+
+    let timeline = TestTimeline::new().with_settings(TimelineInnerSettings {
+        divider_granularity: DividerGranularity::Week,
+        ..Default::default()
+    });
+
+    // Thursday January 1st, Sunday January 4th and Monday January 5th 1970, at noon.
+    for (ts, body) in [(12 * HOUR, "A"), (84 * HOUR, "B"), (108 * HOUR, "C")] {
+        timeline.event_builder.set_next_ts(ts);
+        timeline.handle_live_message_event(&ALICE, RoomMessageEventContent::text_plain(body)).await;
+    }
+
+    let day_dividers = || async {
+        let items = timeline.inner.items().await;
+        items.iter().positions(|item| item.is_day_divider()).collect::<Vec<_>>()
+    };
+
+    // Weeks start on Monday by default.
+    assert_eq!(day_dividers().await, [0, 3]);
+
+    timeline.inner.set_divider_granularity(DividerGranularity::Week, Weekday::Sun).await;
+    assert_eq!(day_dividers().await, [0, 2]);
+
+    timeline.inner.set_divider_granularity(DividerGranularity::Day, Weekday::Sun).await;
+    assert_eq!(day_dividers().await, [0, 2, 4]);
+
+    timeline.inner.set_divider_granularity(DividerGranularity::Month, Weekday::Sun).await;
+    assert_eq!(day_dividers().await, [0]);
+}
+
+#[async_test]
+async fn test_state_groups() {
+    let timeline = TestTimeline::new()
+        .with_settings(TimelineInnerSettings { collapse_state_events: true, ..Default::default() });
+
+    for user in [*ALICE, *BOB, *CAROL] {
+        timeline
+            .handle_live_state_event_with_state_key(
+                user,
+                user.to_owned(),
+                RoomMemberEventContent::new(MembershipState::Join),
+                None,
+            )
+            .await;
+    }
+
+    let items = timeline.inner.items().await;
+    assert_eq!(items.len(), 5);
+    assert!(items[0].is_day_divider());
+    assert_let!(
+        Some(VirtualTimelineItem::StateGroup { count, summary, expanded }) = items[1].as_virtual()
+    );
+    assert_eq!(*count, 3);
+    assert_eq!(summary.senders, [ALICE.to_owned(), BOB.to_owned(), CAROL.to_owned()]);
+    assert_eq!(summary.membership_changes, 3);
+    assert!(!expanded);
+
+    // The read marker splits the group: the first event is left on its own, and
+    // the two other ones get a new group.
+    let first_event_id = items[2].as_event().unwrap().event_id().unwrap().to_owned();
+    timeline.inner.set_fully_read_event(first_event_id).await;
+
+    let items = timeline.inner.items().await;
+    assert_eq!(items.len(), 6);
+    assert!(items[1].as_event().is_some());
+    assert!(items[2].is_read_marker());
+    assert_let!(Some(VirtualTimelineItem::StateGroup { count, .. }) = items[3].as_virtual());
+    assert_eq!(*count, 2);
+    let group_id = items[3].unique_id().to_owned();
+
+    // A message isn't part of any group.
+    timeline.handle_live_message_event(&ALICE, RoomMessageEventContent::text_plain("A")).await;
+    assert_eq!(timeline.inner.items().await.len(), 7);
+
+    assert!(timeline.inner.expand_state_group(&group_id).await);
+    assert!(!timeline.inner.expand_state_group("unknown").await);
+
+    let items = timeline.inner.items().await;
+    assert_eq!(items[3].unique_id(), group_id);
+    assert_matches!(
+        items[3].as_virtual(),
+        Some(VirtualTimelineItem::StateGroup { count: 2, expanded: true, .. })
+    );
+}
+
+#[async_test]
+async fn test_hidden_messages() {
+    let timeline = TestTimeline::new()
+        .with_settings(TimelineInnerSettings { collapse_muted_users: true, ..Default::default() });
+    timeline.inner.set_muted_senders([BOB.to_owned()].into()).await;
+
+    for (sender, body) in [(*BOB, "A"), (*BOB, "B"), (*CAROL, "C"), (*BOB, "D")] {
+        timeline.handle_live_message_event(sender, RoomMessageEventContent::text_plain(body)).await;
+    }
+
+    let items = timeline.inner.items().await;
+    assert_eq!(items.len(), 7);
+    assert!(items[0].is_day_divider());
+    assert_let!(
+        Some(VirtualTimelineItem::HiddenMessages { sender, count, expanded }) =
+            items[1].as_virtual()
+    );
+    assert_eq!(*sender, *BOB);
+    assert_eq!(*count, 2);
+    assert!(!expanded);
+    assert_eq!(items[4].as_event().unwrap().sender(), *CAROL);
+    assert_matches!(
+        items[5].as_virtual(),
+        Some(VirtualTimelineItem::HiddenMessages { count: 1, expanded: false, .. })
+    );
+    let hidden_id = items[1].unique_id().to_owned();
+
+    assert!(timeline.inner.expand_hidden_messages(&hidden_id).await);
+    assert!(!timeline.inner.expand_hidden_messages("unknown").await);
+
+    let items = timeline.inner.items().await;
+    assert_eq!(items[1].unique_id(), hidden_id);
+    assert_matches!(
+        items[1].as_virtual(),
+        Some(VirtualTimelineItem::HiddenMessages { count: 2, expanded: true, .. })
+    );
+
+    // Unmuting the user removes the headers.
+    timeline.inner.set_muted_senders(Default::default()).await;
+
+    let items = timeline.inner.items().await;
+    assert_eq!(items.len(), 5);
+    assert!(items.iter().all(|item| {
+        !matches!(item.as_virtual(), Some(VirtualTimelineItem::HiddenMessages { .. }))
+    }));
+}
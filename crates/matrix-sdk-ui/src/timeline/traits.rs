@@ -12,11 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
+
 use async_trait::async_trait;
 use indexmap::IndexMap;
 #[cfg(feature = "e2e-encryption")]
 use matrix_sdk::{deserialized_responses::TimelineEvent, Result};
-use matrix_sdk::{event_cache::paginator::PaginableRoom, Room};
+use matrix_sdk::{
+    deserialized_responses::VerificationState, event_cache::paginator::PaginableRoom, Room,
+};
 use matrix_sdk_base::latest_event::LatestEvent;
 #[cfg(feature = "e2e-encryption")]
 use ruma::{events::AnySyncTimelineEvent, serde::Raw};
@@ -26,7 +30,7 @@ use ruma::{
         receipt::{Receipt, ReceiptThread, ReceiptType},
     },
     push::{PushConditionRoomCtx, Ruleset},
-    EventId, OwnedEventId, OwnedUserId, RoomVersionId, UserId,
+    DeviceId, EventId, OwnedEventId, OwnedUserId, RoomVersionId, UserId,
 };
 use tracing::{debug, error};
 
@@ -53,6 +57,18 @@ pub trait RoomExt {
     /// This allows to customize settings of the [`Timeline`] before
     /// constructing it.
     fn timeline_builder(&self) -> TimelineBuilder;
+
+    /// SC: Get the profiles of the given users in this room.
+    ///
+    /// The profiles are read from the store, and if some of them are missing
+    /// the member list is fetched from the server once, in a single request,
+    /// for all of them. The fetched members are persisted in the store the
+    /// timelines of this room read their profiles from.
+    ///
+    /// Users that aren't members of the room get an empty profile once the
+    /// member list is known. Users whose profile couldn't be loaded are
+    /// missing from the returned map.
+    async fn fetch_profiles(&self, user_ids: &[OwnedUserId]) -> BTreeMap<OwnedUserId, Profile>;
 }
 
 #[async_trait]
@@ -64,6 +80,39 @@ impl RoomExt for Room {
     fn timeline_builder(&self) -> TimelineBuilder {
         Timeline::builder(self).track_read_marker_and_receipts()
     }
+
+    async fn fetch_profiles(&self, user_ids: &[OwnedUserId]) -> BTreeMap<OwnedUserId, Profile> {
+        let mut profiles = BTreeMap::new();
+        let mut missing = Vec::new();
+
+        for user_id in user_ids {
+            match RoomDataProvider::profile_from_user_id(self, user_id).await {
+                Some(profile) => {
+                    profiles.insert(user_id.clone(), profile);
+                }
+                None => missing.push(user_id),
+            }
+        }
+
+        if missing.is_empty() {
+            return profiles;
+        }
+
+        debug!("Fetching the members to get {} missing profiles", missing.len());
+
+        if let Err(e) = self.sync_members().await {
+            error!("Failed to fetch the room members: {e}");
+            return profiles;
+        }
+
+        for user_id in missing {
+            if let Some(profile) = RoomDataProvider::profile_from_user_id(self, user_id).await {
+                profiles.insert(user_id.clone(), profile);
+            }
+        }
+
+        profiles
+    }
 }
 
 #[async_trait]
@@ -88,6 +137,20 @@ pub(super) trait RoomDataProvider: Clone + Send + Sync + 'static + PaginableRoom
     async fn load_fully_read_marker(&self) -> Option<OwnedEventId>;
 
     async fn push_rules_and_context(&self) -> Option<(Ruleset, PushConditionRoomCtx)>;
+
+    /// SC: Whether the given event is currently pinned in the room.
+    fn is_pinned_event(&self, event_id: &EventId) -> bool;
+
+    /// SC: Whether the room is encrypted, as far as we know.
+    fn is_room_encrypted(&self) -> bool;
+
+    /// SC: The current verification state of the given device, if it is
+    /// known.
+    async fn device_verification_state(
+        &self,
+        user_id: &UserId,
+        device_id: &DeviceId,
+    ) -> Option<VerificationState>;
 }
 
 #[async_trait]
@@ -100,12 +163,56 @@ impl RoomDataProvider for Room {
         (**self).clone_info().room_version_or_default()
     }
 
+    fn is_pinned_event(&self, event_id: &EventId) -> bool {
+        (**self).is_pinned_event(event_id)
+    }
+
+    fn is_room_encrypted(&self) -> bool {
+        (**self).is_encrypted()
+    }
+
+    #[cfg(feature = "e2e-encryption")]
+    async fn device_verification_state(
+        &self,
+        user_id: &UserId,
+        device_id: &DeviceId,
+    ) -> Option<VerificationState> {
+        use matrix_sdk::deserialized_responses::VerificationLevel;
+
+        let device = match self.client().encryption().get_device(user_id, device_id).await {
+            Ok(device) => device?,
+            Err(e) => {
+                error!(%user_id, %device_id, "Failed to get the device: {e}");
+                return None;
+            }
+        };
+
+        // Same as the verification state computed when decrypting an event.
+        Some(if device.is_verified_with_cross_signing() {
+            VerificationState::Verified
+        } else if device.is_cross_signed_by_owner() {
+            VerificationState::Unverified(VerificationLevel::UnverifiedIdentity)
+        } else {
+            VerificationState::Unverified(VerificationLevel::UnsignedDevice)
+        })
+    }
+
+    #[cfg(not(feature = "e2e-encryption"))]
+    async fn device_verification_state(
+        &self,
+        _user_id: &UserId,
+        _device_id: &DeviceId,
+    ) -> Option<VerificationState> {
+        None
+    }
+
     async fn profile_from_user_id(&self, user_id: &UserId) -> Option<Profile> {
         match self.get_member_no_sync(user_id).await {
             Ok(Some(member)) => Some(Profile {
                 display_name: member.display_name().map(ToOwned::to_owned),
                 display_name_ambiguous: member.name_ambiguous(),
                 avatar_url: member.avatar_url().map(ToOwned::to_owned),
+                membership: Some(member.membership().clone()),
             }),
             Ok(None) if self.are_members_synced() => Some(Profile::default()),
             Ok(None) => None,
@@ -125,6 +232,7 @@ impl RoomDataProvider for Room {
             display_name: latest_event.sender_display_name().map(ToOwned::to_owned),
             display_name_ambiguous: latest_event.sender_name_ambiguous().unwrap_or(false),
             avatar_url: latest_event.sender_avatar_url().map(ToOwned::to_owned),
+            membership: None,
         })
     }
 
@@ -14,13 +14,15 @@
 
 use std::{ops::Deref, sync::Arc};
 
-use chrono::{Datelike, Local, TimeZone};
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone, Weekday};
 use imbl::Vector;
 use ruma::{EventId, MilliSecondsSinceUnixEpoch};
 
 #[cfg(doc)]
 use super::inner::TimelineInnerMetadata;
-use super::{event_item::EventTimelineItemKind, EventTimelineItem, TimelineItem};
+use super::{
+    event_item::EventTimelineItemKind, DividerGranularity, EventTimelineItem, TimelineItem,
+};
 
 pub(super) struct EventTimelineItemWithId<'a> {
     pub inner: &'a EventTimelineItem,
@@ -91,15 +93,44 @@ pub(super) struct Date {
     day: u32,
 }
 
+impl From<NaiveDate> for Date {
+    fn from(date: NaiveDate) -> Self {
+        Self { year: date.year(), month: date.month(), day: date.day() }
+    }
+}
+
 /// Converts a timestamp since Unix Epoch to a year, month and day.
+#[cfg(test)]
 pub(super) fn timestamp_to_date(ts: MilliSecondsSinceUnixEpoch) -> Date {
-    let datetime = Local
+    timestamp_to_period(ts, DividerGranularity::Day, Weekday::Mon)
+}
+
+/// SC: Converts a timestamp since Unix Epoch to the first date of the period
+/// containing it, in local time.
+///
+/// `first_day_of_week` is only used with [`DividerGranularity::Week`].
+pub(super) fn timestamp_to_period(
+    ts: MilliSecondsSinceUnixEpoch,
+    granularity: DividerGranularity,
+    first_day_of_week: Weekday,
+) -> Date {
+    let date = Local
         .timestamp_millis_opt(ts.0.into())
         // Only returns `None` if date is after Dec 31, 262143 BCE.
         .single()
         // Fallback to the current date to avoid issues with malicious
         // homeservers.
-        .unwrap_or_else(Local::now);
+        .unwrap_or_else(Local::now)
+        .date_naive();
 
-    Date { year: datetime.year(), month: datetime.month(), day: datetime.day() }
+    match granularity {
+        DividerGranularity::Day => date.into(),
+        DividerGranularity::Week => {
+            let days_into_week = (7 + date.weekday().num_days_from_monday()
+                - first_day_of_week.num_days_from_monday())
+                % 7;
+            (date - Duration::days(days_into_week.into())).into()
+        }
+        DividerGranularity::Month => date.with_day(1).unwrap_or(date).into(),
+    }
 }
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use ruma::MilliSecondsSinceUnixEpoch;
+use ruma::{MilliSecondsSinceUnixEpoch, OwnedUserId};
 
 /// A [`TimelineItem`](super::TimelineItem) that doesn't correspond to an event.
 #[derive(Clone, Debug)]
@@ -21,8 +21,62 @@ pub enum VirtualTimelineItem {
     ///
     /// The value is a timestamp in milliseconds since Unix Epoch on the given
     /// day in local time.
+    ///
+    /// SC: With a coarser
+    /// [`DividerGranularity`](super::DividerGranularity), this is a divider
+    /// between messages of two weeks or months, and the timestamp is that of
+    /// its first message.
     DayDivider(MilliSecondsSinceUnixEpoch),
 
     /// The user's own read marker.
     ReadMarker,
+
+    /// SC: A header for a run of consecutive state events (membership changes,
+    /// profile changes and other state events).
+    ///
+    /// This is only emitted when the timeline has been built with
+    /// [`TimelineBuilder::collapse_state_events`](super::TimelineBuilder::collapse_state_events).
+    /// The header is placed right before the first event of the group, and the
+    /// grouped event items stay in the timeline: clients are expected to hide
+    /// the `count` items following the header while it isn't `expanded`.
+    StateGroup {
+        /// The number of event items in the group.
+        count: usize,
+        /// A summary of what happened in the group.
+        summary: StateGroupSummary,
+        /// Whether the group has been expanded with
+        /// [`Timeline::expand_state_group`](super::Timeline::expand_state_group).
+        expanded: bool,
+    },
+
+    /// SC: A header for a run of consecutive messages from a user muted with
+    /// [`Room::set_user_muted_locally`](matrix_sdk::Room::set_user_muted_locally).
+    ///
+    /// This is only emitted when the timeline has been built with
+    /// [`TimelineBuilder::collapse_muted_users`](super::TimelineBuilder::collapse_muted_users).
+    /// Like for [`VirtualTimelineItem::StateGroup`], the hidden event items stay
+    /// in the timeline: clients are expected to hide the `count` items
+    /// following the header while it isn't `expanded`.
+    HiddenMessages {
+        /// The muted user who sent the hidden messages.
+        sender: OwnedUserId,
+        /// The number of hidden event items.
+        count: usize,
+        /// Whether the messages have been revealed with
+        /// [`Timeline::expand_hidden_messages`](super::Timeline::expand_hidden_messages).
+        expanded: bool,
+    },
+}
+
+/// SC: A summary of the events grouped in a [`VirtualTimelineItem::StateGroup`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateGroupSummary {
+    /// The distinct senders of the grouped events, in timeline order.
+    pub senders: Vec<OwnedUserId>,
+    /// The number of membership changes in the group.
+    pub membership_changes: usize,
+    /// The number of profile changes in the group.
+    pub profile_changes: usize,
+    /// The number of other state events in the group.
+    pub other_state_changes: usize,
 }
@@ -0,0 +1,121 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: Sending read receipts only for the items that stayed visible on screen
+//! long enough, see [`Timeline::mark_visible`](super::Timeline::mark_visible).
+
+use std::{collections::HashMap, time::Duration};
+
+use matrix_sdk::executor::{spawn, JoinHandle};
+use ruma::{
+    api::client::receipt::create_receipt::v3::ReceiptType, events::receipt::ReceiptThread,
+    OwnedEventId,
+};
+use tokio::time::{sleep_until, Instant};
+use tracing::{trace, warn};
+
+use super::inner::TimelineInner;
+
+/// The event items that are currently visible on screen.
+#[derive(Debug, Default)]
+pub(super) struct VisibleItems {
+    /// The IDs of the events of the visible items, and since when they are
+    /// visible.
+    since: HashMap<OwnedEventId, Instant>,
+
+    /// The task sending the read receipt once items have been visible long
+    /// enough.
+    receipt_task: Option<JoinHandle<()>>,
+}
+
+impl VisibleItems {
+    /// Replace the visible items, and schedule sending the read receipt for
+    /// the ones that stay visible for at least `delay`.
+    pub(super) fn update(
+        &mut self,
+        event_ids: Vec<OwnedEventId>,
+        delay: Duration,
+        inner: TimelineInner,
+    ) {
+        let now = Instant::now();
+        let since: HashMap<_, _> = event_ids
+            .into_iter()
+            .map(|event_id| {
+                let since = self.since.get(&event_id).copied().unwrap_or(now);
+                (event_id, since)
+            })
+            .collect();
+
+        if self.receipt_task.is_some()
+            && since.len() == self.since.len()
+            && since.keys().all(|event_id| self.since.contains_key(event_id))
+        {
+            // Nothing changed, keep the scheduled receipt.
+            return;
+        }
+
+        self.since = since.clone();
+
+        if let Some(task) = self.receipt_task.take() {
+            task.abort();
+        }
+        self.receipt_task = Some(spawn(send_receipts_for_visible_items(inner, since, delay)));
+    }
+}
+
+impl Drop for VisibleItems {
+    fn drop(&mut self) {
+        if let Some(task) = self.receipt_task.take() {
+            task.abort();
+        }
+    }
+}
+
+/// Send a read receipt for the latest item every time some items reach the
+/// delay, until all of them did.
+async fn send_receipts_for_visible_items(
+    inner: TimelineInner,
+    mut since: HashMap<OwnedEventId, Instant>,
+    delay: Duration,
+) {
+    while let Some(deadline) = since.values().map(|since| *since + delay).min() {
+        sleep_until(deadline).await;
+
+        let now = Instant::now();
+        let (read, pending): (HashMap<_, _>, HashMap<_, _>) =
+            since.into_iter().partition(|(_, since)| *since + delay <= now);
+        since = pending;
+
+        let Some(event_id) = inner.latest_event_id_among(read.into_keys()).await else {
+            continue;
+        };
+
+        if !inner
+            .should_send_receipt(&ReceiptType::Read, &ReceiptThread::Unthreaded, &event_id)
+            .await
+        {
+            trace!("not sending receipt for visible items, a previous receipt covers them");
+            continue;
+        }
+
+        trace!(%event_id, "sending receipt for visible items");
+        if let Err(error) = inner
+            .room()
+            .send_single_receipt(ReceiptType::Read, ReceiptThread::Unthreaded, event_id)
+            .await
+        {
+            warn!("Failed to send the read receipt for the visible items: {error}");
+        }
+    }
+}
@@ -5,7 +5,10 @@ use std::{
 
 use assert_matches::assert_matches;
 use matrix_sdk::{config::SyncSettings, test_utils::logged_in_client_with_server};
-use matrix_sdk_test::{async_test, sync_timeline_event, JoinedRoomBuilder, SyncResponseBuilder};
+use matrix_sdk_test::{
+    async_test, sync_timeline_event, GlobalAccountDataTestEvent, JoinedRoomBuilder,
+    SyncResponseBuilder,
+};
 use matrix_sdk_ui::{
     notification_client::{
         NotificationClient, NotificationEvent, NotificationProcessSetup, NotificationStatus,
@@ -107,6 +110,137 @@ async fn test_notification_client_with_context() {
     });
     assert_eq!(item.sender_display_name.as_deref(), Some("John Mastodon"));
     assert_eq!(item.sender_avatar_url.as_deref(), Some("https://example.org/avatar.jpeg"));
+    assert!(item.is_decryptable);
+    assert_eq!(item.body_preview.as_deref(), Some("Hello world!"));
+}
+
+#[async_test]
+async fn test_notification_client_dnd_is_not_noisy() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    // The message mentions the user, so it's noisy by default.
+    let event_id = event_id!("$example_event_id");
+    let sender = user_id!("@user:example.org");
+    let event_json = json!({
+        "content": {
+            "body": "Hello example!",
+            "msgtype": "m.text",
+            "m.mentions": {
+                "user_ids": [client.user_id().unwrap()],
+            },
+        },
+        "room_id": room_id,
+        "event_id": event_id,
+        "origin_server_ts": 152049794,
+        "sender": sender,
+        "type": "m.room.message",
+    });
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id).add_timeline_event(sync_timeline_event!(event_json)),
+    );
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let dummy_sync_service = Arc::new(SyncService::builder(client.clone()).build().await.unwrap());
+    let process_setup =
+        NotificationProcessSetup::SingleProcess { sync_service: dummy_sync_service };
+    let notification_client = NotificationClient::new(client.clone(), process_setup).await.unwrap();
+
+    Mock::given(method("GET"))
+        .and(path(format!("/_matrix/client/r0/rooms/{room_id}/context/{event_id}")))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "event": event_json,
+            "state": [],
+        })))
+        .mount(&server)
+        .await;
+    mock_encryption_state(&server, false).await;
+
+    let item = notification_client.get_notification_with_context(room_id, event_id).await.unwrap();
+    let item = item.expect("the notification should be found");
+    assert_eq!(item.is_noisy, Some(true));
+
+    // Then the user enables Do-Not-Disturb.
+    sync_builder.add_global_account_data_event(GlobalAccountDataTestEvent::Custom(json!({
+        "content": { "enabled": true },
+        "type": "chat.schildi.dnd",
+    })));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings).await.unwrap();
+
+    let item = notification_client.get_notification_with_context(room_id, event_id).await.unwrap();
+    let item = item.expect("the notification should be found");
+    assert_eq!(item.is_noisy, Some(false));
+}
+
+#[async_test]
+async fn test_notification_client_with_context_undecryptable() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let event_id = event_id!("$example_event_id");
+    let sender = user_id!("@user:example.org");
+    let event_json = json!({
+        "content": {
+            "algorithm": "m.megolm.v1.aes-sha2",
+            "ciphertext": "AwgAEpABhetEzzZzyYrxtEVUtlJnZtJcURBlQUQJ9irVeklCTs06LwgTMQj61PMUS4Vy",
+            "device_id": "KCZSDUFEIA",
+            "sender_key": "LvryVyoCjdONdBCi2vvoSbI34yTOx7YrCFACUEKoXnc",
+            "session_id": "9GcCqZ5ODMsCtMoN6QhuBxhaGP8ukVeX8TbUBoTGLBA"
+        },
+        "room_id": room_id,
+        "event_id": event_id,
+        "origin_server_ts": 152049794,
+        "sender": sender,
+        "type": "m.room.encrypted",
+    });
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let dummy_sync_service = Arc::new(SyncService::builder(client.clone()).build().await.unwrap());
+    let process_setup =
+        NotificationProcessSetup::SingleProcess { sync_service: dummy_sync_service };
+    let notification_client = NotificationClient::new(client, process_setup).await.unwrap();
+
+    Mock::given(method("GET"))
+        .and(path(format!("/_matrix/client/r0/rooms/{room_id}/context/{event_id}")))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "event": event_json,
+            "state": [],
+        })))
+        .mount(&server)
+        .await;
+    mock_encryption_state(&server, true).await;
+
+    // The room key is missing, so the event can't be decrypted, but the
+    // notification is still returned.
+    let item = notification_client.get_notification_with_context(room_id, event_id).await.unwrap();
+
+    server.reset().await;
+
+    let item = item.expect("the notification should be found");
+
+    assert_matches!(item.event, NotificationEvent::Timeline(event) => {
+        assert_eq!(event.event_type(), TimelineEventType::RoomEncrypted);
+    });
+    assert!(!item.is_decryptable);
+    assert!(item.body_preview.is_none());
 }
 
 #[async_test]
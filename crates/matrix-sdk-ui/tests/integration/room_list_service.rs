@@ -12,7 +12,10 @@ use matrix_sdk_base::sync::UnreadNotificationsCount;
 use matrix_sdk_test::async_test;
 use matrix_sdk_ui::{
     room_list_service::{
-        filters::{new_filter_fuzzy_match_room_name, new_filter_non_left, new_filter_none},
+        filters::{
+            new_filter_fuzzy_match_room_name, new_filter_non_left, new_filter_none,
+            new_filter_orphan_rooms,
+        },
         Error, Input, InputResult, RoomListEntry, RoomListLoadingState, State, SyncIndicator,
         ALL_ROOMS_LIST_NAME as ALL_ROOMS, VISIBLE_ROOMS_LIST_NAME as VISIBLE_ROOMS,
     },
@@ -2017,6 +2020,125 @@ async fn test_dynamic_entries_stream_manual_update() -> Result<(), Error> {
     Ok(())
 }
 
+#[async_test]
+async fn test_dynamic_entries_stream_orphan_rooms_live() -> Result<(), Error> {
+    let (client, server, room_list) = new_room_list_service().await?;
+
+    let sync = room_list.sync();
+    pin_mut!(sync);
+
+    let all_rooms = room_list.all_rooms().await?;
+
+    let (dynamic_entries_stream, dynamic_entries) =
+        all_rooms.entries_with_dynamic_adapters(5, client.roominfo_update_receiver());
+    pin_mut!(dynamic_entries_stream);
+
+    sync_then_assert_request_and_fake_response! {
+        [server, room_list, sync]
+        states = Init => SettingUp,
+        assert request >= {
+            "lists": {
+                ALL_ROOMS: {
+                    "ranges": [[0, 19]],
+                },
+            },
+        },
+        respond with = {
+            "pos": "0",
+            "lists": {
+                ALL_ROOMS: {
+                    "count": 2,
+                    "ops": [
+                        {
+                            "op": "SYNC",
+                            "range": [0, 1],
+                            "room_ids": [
+                                "!space:bar.org",
+                                "!r0:bar.org",
+                            ],
+                        },
+                    ],
+                },
+            },
+            "rooms": {
+                "!space:bar.org": {
+                    "initial": true,
+                    "timeline": [],
+                },
+                "!r0:bar.org": {
+                    "initial": true,
+                    "timeline": [],
+                },
+            },
+        },
+    };
+
+    dynamic_entries.set_filter(Box::new(new_filter_orphan_rooms(
+        &client,
+        vec![room_id!("!space:bar.org").to_owned()],
+    )));
+
+    // The space has no children yet, so all the rooms are orphans.
+    assert_entries_batch! {
+        [dynamic_entries_stream]
+        reset [ F("!space:bar.org"), F("!r0:bar.org") ];
+        end;
+    };
+    assert_pending!(dynamic_entries_stream);
+
+    // The room is added to the space.
+    sync_then_assert_request_and_fake_response! {
+        [server, room_list, sync]
+        states = SettingUp => Running,
+        assert request >= {
+            "lists": {
+                ALL_ROOMS: {
+                    "ranges": [[0, 1]],
+                },
+            },
+        },
+        respond with = {
+            "pos": "1",
+            "lists": {
+                ALL_ROOMS: {
+                    "count": 2,
+                },
+            },
+            "rooms": {
+                "!space:bar.org": {
+                    "timeline": [
+                        {
+                            "content": {
+                                "via": ["bar.org"],
+                            },
+                            "event_id": "$1",
+                            "origin_server_ts": 42,
+                            "sender": "@example:bar.org",
+                            "state_key": "!r0:bar.org",
+                            "type": "m.space.child",
+                        },
+                    ],
+                },
+            },
+        },
+    };
+
+    // The room isn't an orphan anymore, without setting the filter again.
+    assert_entries_batch! {
+        [dynamic_entries_stream]
+        remove[1];
+        end;
+    };
+    assert_entries_batch! {
+        [dynamic_entries_stream]
+        set[0] [ F("!space:bar.org") ];
+        end;
+    };
+    assert_pending!(dynamic_entries_stream);
+
+    Ok(())
+}
+
 #[async_test]
 async fn test_room() -> Result<(), Error> {
     let (_, server, room_list) = new_room_list_service().await?;
@@ -2238,6 +2360,7 @@ async fn test_room_subscription() -> Result<(), Error> {
                         ["m.room.avatar", ""],
                         ["m.room.canonical_alias", ""],
                         ["m.room.create", ""], // Added even when it's not specified
+                        ["m.room.pinned_events", ""], // Same
                     ],
                     "timeline_limit": 30,
                 },
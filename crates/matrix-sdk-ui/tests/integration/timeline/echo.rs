@@ -23,7 +23,7 @@ use matrix_sdk::{
     test_utils::logged_in_client_with_server,
 };
 use matrix_sdk_test::{async_test, sync_timeline_event, JoinedRoomBuilder, SyncResponseBuilder};
-use matrix_sdk_ui::timeline::{EventSendState, RoomExt, TimelineItemContent};
+use matrix_sdk_ui::timeline::{EventSendState, RoomExt, SendErrorKind, TimelineItemContent};
 use ruma::{
     event_id,
     events::room::message::{MessageType, RoomMessageEventContent},
@@ -171,10 +171,13 @@ async fn test_retry_failed() {
     // Sending fails, because the error is a transient one that's recoverable,
     // indicating something's wrong on the client side.
     assert_let!(Some(VectorDiff::Set { index: 0, value: item }) = timeline_stream.next().await);
-    assert_matches!(
-        item.send_state(),
-        Some(EventSendState::SendingFailed { is_recoverable: true, .. })
+    assert_let!(
+        Some(EventSendState::SendingFailed { send_error, is_recoverable: true, .. }) =
+            item.send_state()
     );
+    assert_eq!(send_error.kind, SendErrorKind::Other);
+    assert_eq!(send_error.status_code, Some(500));
+    assert!(send_error.is_retryable());
 
     // This doesn't disable the send queue at the global level…
     assert!(client.send_queue().is_enabled());
@@ -203,6 +206,57 @@ async fn test_retry_failed() {
     assert_matches!(value.send_state(), Some(EventSendState::Sent { .. }));
 }
 
+#[async_test]
+async fn test_send_forbidden() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = Arc::new(room.timeline().await.unwrap());
+    let (_, mut timeline_stream) =
+        timeline.subscribe_filter_map(|item| item.as_event().cloned()).await;
+
+    server.reset().await;
+    mock_encryption_state(&server, false).await;
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/send/.*"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(403).set_body_json(&json!({
+            "errcode": "M_FORBIDDEN",
+            "error": "You don't have permission to post that to the room.",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    timeline.send(RoomMessageEventContent::text_plain("Hello, World!").into()).await.unwrap();
+
+    // Let the send queue handle the event.
+    yield_now().await;
+
+    assert_next_matches!(timeline_stream, VectorDiff::PushBack { value } => {
+        assert_matches!(value.send_state(), Some(EventSendState::NotSentYet));
+    });
+
+    // The error isn't worth retrying.
+    assert_let!(Some(VectorDiff::Set { index: 0, value: item }) = timeline_stream.next().await);
+    assert_let!(
+        Some(EventSendState::SendingFailed { send_error, is_recoverable: false, .. }) =
+            item.send_state()
+    );
+    assert_eq!(send_error.kind, SendErrorKind::Forbidden);
+    assert_eq!(send_error.status_code, Some(403));
+    assert_eq!(send_error.errcode.as_deref(), Some("M_FORBIDDEN"));
+    assert!(!send_error.is_retryable());
+}
+
 #[async_test]
 async fn test_dedup_by_event_id_late() {
     let room_id = room_id!("!a98sd12bjh:example.org");
@@ -18,11 +18,15 @@ use assert_matches::assert_matches;
 use assert_matches2::assert_let;
 use eyeball_im::VectorDiff;
 use futures_util::StreamExt;
-use matrix_sdk::{config::SyncSettings, test_utils::logged_in_client_with_server};
+use matrix_sdk::{config::SyncSettings, test_utils::logged_in_client_with_server, ComposerDraft};
+use matrix_sdk_base::store::ComposerDraftType;
 use matrix_sdk_test::{
-    async_test, EventBuilder, JoinedRoomBuilder, SyncResponseBuilder, ALICE, BOB,
+    async_test, sync_timeline_event, EventBuilder, JoinedRoomBuilder, SyncResponseBuilder, ALICE,
+    BOB,
+};
+use matrix_sdk_ui::timeline::{
+    RestoredComposerDraft, RoomExt, TimelineDetails, TimelineItemContent,
 };
-use matrix_sdk_ui::timeline::{RoomExt, TimelineDetails, TimelineItemContent};
 use ruma::{
     assign, event_id,
     events::{
@@ -227,6 +231,78 @@ async fn test_send_edit() {
     server.verify().await;
 }
 
+#[async_test]
+async fn test_cancel_edit() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let event_builder = EventBuilder::new();
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room.timeline().await.unwrap();
+    let (_, mut timeline_stream) =
+        timeline.subscribe_filter_map(|item| item.as_event().cloned()).await;
+
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id).add_timeline_event(
+        event_builder.make_sync_message_event_with_id(
+            // Same user as the logged_in_client
+            user_id!("@example:localhost"),
+            event_id!("$original_event"),
+            RoomMessageEventContent::text_plain("Hello, World!"),
+        ),
+    ));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let hello_world_item =
+        assert_next_matches!(timeline_stream, VectorDiff::PushBack { value } => value);
+
+    room.save_composer_draft(ComposerDraft {
+        plain_text: "Hello, Room!".to_owned(),
+        html_text: None,
+        draft_type: ComposerDraftType::Edit { event_id: "$original_event".to_owned() },
+    })
+    .await
+    .unwrap();
+
+    // Keep the edit in the send queue.
+    mock_encryption_state(&server, false).await;
+    room.send_queue().set_enabled(false);
+
+    timeline
+        .edit(RoomMessageEventContentWithoutRelation::text_plain("Hello, Room!"), &hello_world_item)
+        .await
+        .unwrap();
+    yield_now().await;
+
+    let edit_item =
+        assert_next_matches!(timeline_stream, VectorDiff::Set { index: 0, value } => value);
+    assert_eq!(edit_item.content().as_message().unwrap().body(), "Hello, Room!");
+
+    assert!(timeline.cancel_edit(&edit_item).await.unwrap());
+    yield_now().await;
+
+    // The original content is back, and the draft is gone.
+    let restored_item =
+        assert_next_matches!(timeline_stream, VectorDiff::Set { index: 0, value } => value);
+    let restored_message = restored_item.content().as_message().unwrap();
+    assert_eq!(restored_message.body(), "Hello, World!");
+    assert!(!restored_message.is_edited());
+    assert!(room.load_composer_draft().await.unwrap().is_none());
+
+    // There's nothing left to cancel.
+    assert!(!timeline.cancel_edit(&restored_item).await.unwrap());
+}
+
 #[async_test]
 async fn test_send_reply_edit() {
     let room_id = room_id!("!a98sd12bjh:example.org");
@@ -410,3 +486,74 @@ async fn test_send_edit_poll() {
 
     server.verify().await;
 }
+
+#[async_test]
+async fn test_restore_edit_draft() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let event_builder = EventBuilder::new();
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let event_id = event_id!("$msda7m:localhost");
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id).add_timeline_event(
+        event_builder.make_sync_message_event_with_id(
+            user_id!("@example:localhost"),
+            event_id,
+            RoomMessageEventContent::text_plain("hello"),
+        ),
+    ));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room.timeline().await.unwrap();
+    let (_, mut timeline_stream) = timeline.subscribe().await;
+
+    // No draft.
+    assert_matches!(timeline.restore_composer_draft().await.unwrap(), None);
+
+    let draft = ComposerDraft {
+        plain_text: "hello world".to_owned(),
+        html_text: None,
+        draft_type: ComposerDraftType::Edit { event_id: event_id.to_string() },
+    };
+    room.save_composer_draft(draft.clone()).await.unwrap();
+
+    // The edit mode is re-entered for the loaded item.
+    assert_let!(
+        Some(RestoredComposerDraft::Edit { draft: restored, item: Some(item) }) =
+            timeline.restore_composer_draft().await.unwrap()
+    );
+    assert_eq!(restored, draft);
+    assert_eq!(item.event_id(), Some(event_id));
+
+    // The edited event is redacted.
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id).add_timeline_event(
+        sync_timeline_event!({
+            "content": {},
+            "redacts": event_id,
+            "event_id": "$N6eUCBc3vu58PL8TobGaVQzM",
+            "sender": "@example:localhost",
+            "origin_server_ts": 152037280,
+            "type": "m.room.redaction",
+        }),
+    ));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    assert_let!(Some(VectorDiff::Set { value: redacted, .. }) = timeline_stream.next().await);
+    assert_matches!(redacted.as_event().unwrap().content(), TimelineItemContent::RedactedMessage);
+
+    // The draft is discarded, and returned one last time to notify the user.
+    assert_let!(
+        Some(RestoredComposerDraft::EditTargetRedacted(restored)) =
+            timeline.restore_composer_draft().await.unwrap()
+    );
+    assert_eq!(restored, draft);
+    assert_eq!(room.load_composer_draft().await.unwrap(), None);
+}
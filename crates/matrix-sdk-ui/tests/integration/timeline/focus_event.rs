@@ -26,9 +26,12 @@ use matrix_sdk::{
 use matrix_sdk_test::{
     async_test, sync_timeline_event, JoinedRoomBuilder, SyncResponseBuilder, ALICE, BOB,
 };
-use matrix_sdk_ui::{timeline::TimelineFocus, Timeline};
+use matrix_sdk_ui::{
+    timeline::{ForwardPaginationStatus, LiveBackPaginationStatus, TimelineFocus},
+    Timeline,
+};
 use ruma::{event_id, events::room::message::RoomMessageEventContent, room_id};
-use stream_assert::assert_pending;
+use stream_assert::{assert_next_eq, assert_pending};
 
 use crate::{mock_context, mock_messages, mock_sync};
 
@@ -176,6 +179,199 @@ async fn test_new_focused() {
     assert_pending!(timeline_stream);
 }
 
+#[async_test]
+async fn test_focused_timeline_attaches_to_sync() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_response_builder = SyncResponseBuilder::new();
+    sync_response_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    // Mark the room as joined.
+    mock_sync(&server, sync_response_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let f = EventFactory::new().room(room_id).sender(*ALICE);
+    let target_event = event_id!("$1");
+
+    mock_context(
+        &server,
+        room_id,
+        target_event,
+        None,
+        vec![],
+        f.text_msg("one").event_id(target_event).into_timeline(),
+        vec![],
+        Some("next1".to_owned()),
+        vec![],
+    )
+    .await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = Timeline::builder(&room)
+        .with_focus(TimelineFocus::Event {
+            target: target_event.to_owned(),
+            num_context_events: 20,
+        })
+        .build()
+        .await
+        .unwrap();
+
+    server.reset().await;
+
+    let (status, mut forward_pagination_status) = timeline.forward_pagination_status().await;
+    assert_eq!(status, ForwardPaginationStatus::Idle { hit_end_of_timeline: false });
+    let (status, mut back_pagination_status) = timeline.back_pagination_status().await;
+    assert_eq!(status, LiveBackPaginationStatus::Idle { hit_start_of_timeline: true });
+
+    // Events from sync are ignored while the timeline isn't at the end of the room.
+    sync_response_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id)
+            .add_timeline_event(f.text_msg("two").event_id(event_id!("$2")).into_raw_sync())
+            .add_timeline_event(f.text_msg("three").event_id(event_id!("$3")).into_raw_sync()),
+    );
+    mock_sync(&server, sync_response_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    assert_eq!(timeline.items().await.len(), 1 + 1); // event items + a day divider
+
+    // The forward pagination reaches the end of the room, and the events received
+    // via sync in the meantime are added.
+    mock_messages(
+        &server,
+        "next1".to_owned(),
+        None,
+        vec![f.text_msg("two").event_id(event_id!("$2")).into_timeline()],
+        vec![],
+    )
+    .await;
+
+    let hit_end = timeline.paginate_forwards(20).await.unwrap();
+    assert!(hit_end);
+    server.reset().await;
+
+    assert_next_eq!(
+        forward_pagination_status,
+        ForwardPaginationStatus::Idle { hit_end_of_timeline: true }
+    );
+    // The back-pagination status is now the one of the live timeline.
+    assert_next_eq!(
+        back_pagination_status,
+        LiveBackPaginationStatus::Idle { hit_start_of_timeline: false }
+    );
+
+    // The timeline now receives the events from sync, without duplicates.
+    sync_response_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id)
+            .add_timeline_event(f.text_msg("three").event_id(event_id!("$3")).into_raw_sync())
+            .add_timeline_event(f.text_msg("four").event_id(event_id!("$4")).into_raw_sync()),
+    );
+    mock_sync(&server, sync_response_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let items = timeline.items().await;
+    let bodies = items
+        .iter()
+        .filter_map(|item| item.as_event())
+        .map(|event| event.content().as_message().unwrap().body().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(bodies, ["one", "two", "three", "four"]);
+
+    // Local echoes are shown too, now that the timeline is live.
+    let (_, mut timeline_stream) = timeline.subscribe().await;
+    timeline.send(RoomMessageEventContent::text_plain("five").into()).await.unwrap();
+
+    assert_let!(Some(VectorDiff::PushBack { value }) = timeline_stream.next().await);
+    let event = value.as_event().unwrap();
+    assert!(event.is_local_echo());
+    assert_eq!(event.content().as_message().unwrap().body(), "five");
+}
+
+#[async_test]
+async fn test_focused_timeline_attaches_to_sync_with_a_gap() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_response_builder = SyncResponseBuilder::new();
+    sync_response_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    // Mark the room as joined.
+    mock_sync(&server, sync_response_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let f = EventFactory::new().room(room_id).sender(*ALICE);
+    let target_event = event_id!("$1");
+
+    mock_context(
+        &server,
+        room_id,
+        target_event,
+        None,
+        vec![],
+        f.text_msg("one").event_id(target_event).into_timeline(),
+        vec![],
+        Some("next1".to_owned()),
+        vec![],
+    )
+    .await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = Timeline::builder(&room)
+        .with_focus(TimelineFocus::Event {
+            target: target_event.to_owned(),
+            num_context_events: 20,
+        })
+        .build()
+        .await
+        .unwrap();
+
+    server.reset().await;
+
+    // A gappy sync, which doesn't contain the events returned by the forward
+    // pagination below.
+    sync_response_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id)
+            .set_timeline_limited()
+            .set_timeline_prev_batch("prev_sync".to_owned())
+            .add_timeline_event(f.text_msg("four").event_id(event_id!("$4")).into_raw_sync())
+            .add_timeline_event(f.text_msg("five").event_id(event_id!("$5")).into_raw_sync()),
+    );
+    mock_sync(&server, sync_response_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    // The forward pagination reaches the end of the room before the events of the
+    // event cache.
+    mock_messages(
+        &server,
+        "next1".to_owned(),
+        None,
+        vec![f.text_msg("two").event_id(event_id!("$2")).into_timeline()],
+        vec![],
+    )
+    .await;
+
+    let hit_end = timeline.paginate_forwards(20).await.unwrap();
+    assert!(hit_end);
+    server.reset().await;
+
+    // The timeline can't know whether events are missing between its last event
+    // and the ones of the event cache, so it's reset to the live timeline.
+    let items = timeline.items().await;
+    let bodies = items
+        .iter()
+        .filter_map(|item| item.as_event())
+        .map(|event| event.content().as_message().unwrap().body().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(bodies, ["four", "five"]);
+}
+
 #[async_test]
 async fn test_focused_timeline_reacts() {
     let room_id = room_id!("!a98sd12bjh:example.org");
@@ -12,12 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use assert_matches::assert_matches;
 use assert_matches2::assert_let;
-use eyeball_im::VectorDiff;
-use futures_util::StreamExt;
+use eyeball_im::{Vector, VectorDiff};
+use futures_util::{pin_mut, StreamExt};
 use matrix_sdk::{
     config::SyncSettings,
     test_utils::{events::EventFactory, logged_in_client_with_server},
@@ -26,12 +26,15 @@ use matrix_sdk_test::{
     async_test, sync_timeline_event, JoinedRoomBuilder, RoomAccountDataTestEvent, StateTestEvent,
     SyncResponseBuilder,
 };
-use matrix_sdk_ui::timeline::{EventSendState, RoomExt, TimelineItemContent, VirtualTimelineItem};
+use matrix_sdk_ui::timeline::{
+    EventSendState, RoomExt, TimelineItem, TimelineItemContent, VirtualTimelineItem,
+};
 use ruma::{
     event_id, events::room::message::RoomMessageEventContent, room_id, user_id,
     MilliSecondsSinceUnixEpoch,
 };
 use serde_json::json;
+use stream_assert::assert_pending;
 use wiremock::{
     matchers::{header, method, path_regex},
     Mock, ResponseTemplate,
@@ -43,8 +46,10 @@ mod echo;
 mod edit;
 mod focus_event;
 mod pagination;
+mod pinned_event;
 mod profiles;
 mod queue;
+mod reactions;
 mod read_receipts;
 mod replies;
 mod subscribe;
@@ -158,6 +163,81 @@ async fn test_reaction() {
     assert_eq!(event_item.reactions().len(), 0);
 }
 
+#[async_test]
+async fn test_reactions_summary() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room.timeline().await.unwrap();
+    let summary_stream = timeline.reactions_summary(event_id!("$TTvQUp1e17qkw41rBSjpZ")).await;
+    pin_mut!(summary_stream);
+
+    // The event isn't in the timeline yet.
+    assert_eq!(summary_stream.next().await, Some(vec![]));
+
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id)
+            .add_timeline_event(sync_timeline_event!({
+                "content": {
+                    "body": "hello",
+                    "msgtype": "m.text",
+                },
+                "event_id": "$TTvQUp1e17qkw41rBSjpZ",
+                "origin_server_ts": 152037280,
+                "sender": "@alice:example.org",
+                "type": "m.room.message",
+            }))
+            .add_timeline_event(sync_timeline_event!({
+                "content": {
+                    "m.relates_to": {
+                        "event_id": "$TTvQUp1e17qkw41rBSjpZ",
+                        "key": "👍",
+                        "rel_type": "m.annotation",
+                    },
+                },
+                "event_id": "$031IXQRi27504",
+                "origin_server_ts": 152038300,
+                "sender": "@bob:example.org",
+                "type": "m.reaction",
+            })),
+    );
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    // The implicit read receipt of the reaction doesn't produce a summary of its
+    // own, only the reaction does.
+    assert_eq!(summary_stream.next().await, Some(vec![("👍".to_owned(), 1, false)]));
+
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id).add_timeline_event(
+        sync_timeline_event!({
+            "content": {},
+            "redacts": "$031IXQRi27504",
+            "event_id": "$N6eUCBc3vu58PL8TobGaVQzM",
+            "sender": "@bob:example.org",
+            "origin_server_ts": 152037280,
+            "type": "m.room.redaction",
+        }),
+    ));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    assert_eq!(summary_stream.next().await, Some(vec![]));
+    assert_pending!(summary_stream);
+}
+
 #[async_test]
 async fn test_redacted_message() {
     let room_id = room_id!("!a98sd12bjh:example.org");
@@ -312,6 +392,9 @@ async fn test_read_marker() {
     let room = client.get_room(room_id).unwrap();
     let timeline = room.timeline().await.unwrap();
     let (_, mut timeline_stream) = timeline.subscribe().await;
+    let marker_position = timeline.read_marker_position().await;
+    pin_mut!(marker_position);
+    assert_eq!(marker_position.next().await, Some(None));
 
     sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id).add_timeline_event(
         sync_timeline_event!({
@@ -345,6 +428,7 @@ async fn test_read_marker() {
     server.reset().await;
 
     // Nothing should happen, the marker cannot be added at the end.
+    assert_eq!(timeline.first_unread_event_id().await, None);
 
     sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id).add_timeline_event(
         sync_timeline_event!({
@@ -370,6 +454,13 @@ async fn test_read_marker() {
         Some(VectorDiff::Insert { index: 2, value: marker }) = timeline_stream.next().await
     );
     assert_matches!(marker.as_virtual().unwrap(), VirtualTimelineItem::ReadMarker);
+
+    // The position of the marker is only emitted once it's been added.
+    assert_eq!(marker_position.next().await, Some(Some(2)));
+    assert_eq!(
+        timeline.first_unread_event_id().await.as_deref(),
+        Some(event_id!("$someotherplace:example.org"))
+    );
 }
 
 #[async_test]
@@ -507,3 +598,52 @@ async fn test_duplicate_maintains_correct_order() {
     let content = items[3].as_event().unwrap().content().as_message().unwrap().body();
     assert_eq!(content, "C");
 }
+
+#[async_test]
+async fn test_equal_timestamps_keep_sync_order() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room.timeline().await.unwrap();
+
+    let f = EventFactory::new().sender(user_id!("@a:b.c"));
+
+    // Two events with the same timestamp, in different sync batches, and with event
+    // IDs sorting in the opposite order.
+    for (body, event_id) in [("first", event_id!("$b")), ("second", event_id!("$a"))] {
+        f.set_next_ts(1000);
+        sync_builder.add_joined_room(
+            JoinedRoomBuilder::new(room_id)
+                .add_timeline_event(f.text_msg(body).event_id(event_id).into_raw_sync()),
+        );
+
+        mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+        let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+        server.reset().await;
+    }
+
+    let bodies = |items: Vector<Arc<TimelineItem>>| -> Vec<String> {
+        items
+            .iter()
+            .filter_map(|item| Some(item.as_event()?.content().as_message()?.body().to_owned()))
+            .collect()
+    };
+
+    // The timeline is ordered by the position of the events in the sync stream, not
+    // by their timestamps.
+    assert_eq!(bodies(timeline.items().await), ["first", "second"]);
+
+    // Reloading the timeline from the event cache keeps the same order.
+    drop(timeline);
+    let timeline = room.timeline().await.unwrap();
+    assert_eq!(bodies(timeline.items().await), ["first", "second"]);
+}
@@ -143,6 +143,51 @@ async fn test_back_pagination() {
     );
 }
 
+#[async_test]
+async fn test_back_pagination_status() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = Arc::new(room.timeline().await.unwrap());
+    let (status, mut back_pagination_status) = timeline.back_pagination_status().await;
+    assert_eq!(status, LiveBackPaginationStatus::Idle { hit_start_of_timeline: false });
+
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/messages$"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "chunk": [],
+            "start": "t47409-4357353_219380_26003_2269"
+        })))
+        .expect(1)
+        .named("messages_batch_1")
+        .mount(&server)
+        .await;
+
+    let paginate = async {
+        let hit_start = timeline.live_paginate_backwards(10).await.unwrap();
+        assert!(hit_start);
+    };
+    let observe_paginating = async {
+        assert_eq!(back_pagination_status.next().await, Some(LiveBackPaginationStatus::Paginating));
+    };
+    join(paginate, observe_paginating).await;
+
+    assert_next_eq!(
+        back_pagination_status,
+        LiveBackPaginationStatus::Idle { hit_start_of_timeline: true }
+    );
+}
+
 #[async_test]
 async fn test_back_pagination_highlighted() {
     let room_id = room_id!("!a98sd12bjh:example.org");
@@ -0,0 +1,400 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use assert_matches::assert_matches;
+use assert_matches2::assert_let;
+use eyeball_im::VectorDiff;
+use futures_util::StreamExt;
+use matrix_sdk::{
+    config::SyncSettings,
+    test_utils::{events::EventFactory, logged_in_client_with_server},
+};
+use matrix_sdk_test::{
+    async_test, sync_timeline_event, JoinedRoomBuilder, SyncResponseBuilder, ALICE, BOB,
+};
+use matrix_sdk_ui::timeline::{Error, RoomExt, TimelineItemContent};
+use ruma::{event_id, room_id};
+use serde_json::json;
+use stream_assert::assert_pending;
+use wiremock::{
+    matchers::{body_json, header, method, path_regex},
+    Mock, ResponseTemplate,
+};
+
+use crate::mock_sync;
+
+#[async_test]
+async fn test_pinned_timeline() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room.timeline().await.unwrap();
+    let (_, mut timeline_stream) = timeline.subscribe().await;
+
+    let f = EventFactory::new();
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id)
+            .add_timeline_event(
+                f.text_msg("in the timeline").sender(*ALICE).event_id(event_id!("$a")),
+            )
+            .add_timeline_event(sync_timeline_event!({
+                "content": { "pinned": ["$a", "$b", "$c", "$d"] },
+                "event_id": "$pinned",
+                "origin_server_ts": 152037280,
+                "sender": "@alice:example.org",
+                "state_key": "",
+                "type": "m.room.pinned_events",
+            })),
+    );
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    // Wait for both events to be in the timeline.
+    assert_let!(Some(VectorDiff::PushBack { .. }) = timeline_stream.next().await);
+    assert_let!(Some(VectorDiff::PushBack { .. }) = timeline_stream.next().await);
+
+    // `$b` is fetched from the server.
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/event/\$b"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(
+                f.text_msg("fetched")
+                    .sender(*BOB)
+                    .room(room_id)
+                    .event_id(event_id!("$b"))
+                    .into_raw_timeline(),
+            ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // `$c` has been redacted.
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/event/\$c"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "content": {},
+            "event_id": "$c",
+            "origin_server_ts": 152037280,
+            "room_id": room_id,
+            "sender": "@bob:example.org",
+            "type": "m.room.message",
+            "unsigned": {
+                "redacted_because": {
+                    "content": {},
+                    "event_id": "$redaction",
+                    "origin_server_ts": 152037290,
+                    "redacts": "$c",
+                    "room_id": room_id,
+                    "sender": "@bob:example.org",
+                    "type": "m.room.redaction",
+                },
+            },
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // `$d` is unknown to the server.
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/event/\$d"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "errcode": "M_NOT_FOUND",
+            "error": "Event not found.",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let pinned = timeline.pinned_timeline().await.unwrap();
+    assert_eq!(pinned.len(), 3);
+
+    assert_eq!(pinned[0].event_id(), Some(event_id!("$a")));
+    assert_eq!(pinned[0].content().as_message().unwrap().body(), "in the timeline");
+
+    assert_eq!(pinned[1].event_id(), Some(event_id!("$b")));
+    assert_eq!(pinned[1].content().as_message().unwrap().body(), "fetched");
+
+    assert_eq!(pinned[2].event_id(), Some(event_id!("$c")));
+    assert_matches!(pinned[2].content(), TimelineItemContent::RedactedMessage);
+}
+
+#[async_test]
+async fn test_pin_event() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room.timeline().await.unwrap();
+    let (_, mut timeline_stream) = timeline.subscribe().await;
+
+    let f = EventFactory::new();
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id)
+            .add_timeline_event(f.text_msg("pin me").sender(*ALICE).event_id(event_id!("$a"))),
+    );
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    assert_let!(Some(VectorDiff::PushBack { value: item }) = timeline_stream.next().await);
+    assert!(!item.as_event().unwrap().is_pinned());
+    assert_let!(Some(VectorDiff::PushFront { .. }) = timeline_stream.next().await);
+
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/state/m.room.pinned_events/$"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "event_id": "$pinned" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert!(timeline.pin_event(event_id!("$a")).await.unwrap());
+
+    // The item is marked as pinned right away.
+    assert_let!(Some(VectorDiff::Set { index: 1, value: item }) = timeline_stream.next().await);
+    assert!(item.as_event().unwrap().is_pinned());
+
+    // Pinning it again is a no-op.
+    assert!(!timeline.pin_event(event_id!("$a")).await.unwrap());
+    assert_pending!(timeline_stream);
+}
+
+#[async_test]
+async fn test_pin_event_is_returning_an_error() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room.timeline().await.unwrap();
+    let (_, mut timeline_stream) = timeline.subscribe().await;
+
+    let f = EventFactory::new();
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id)
+            .add_timeline_event(f.text_msg("pin me").sender(*ALICE).event_id(event_id!("$a"))),
+    );
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    assert_let!(Some(VectorDiff::PushBack { .. }) = timeline_stream.next().await);
+    assert_let!(Some(VectorDiff::PushFront { .. }) = timeline_stream.next().await);
+
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/state/m.room.pinned_events/$"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "errcode": "M_FORBIDDEN",
+            "error": "You don't have permission to pin events.",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert_matches!(
+        timeline.pin_event(event_id!("$a")).await,
+        Err(Error::FailedToUpdatePinnedEvents(_))
+    );
+
+    // The item was marked as pinned optimistically…
+    assert_let!(Some(VectorDiff::Set { index: 1, value: item }) = timeline_stream.next().await);
+    assert!(item.as_event().unwrap().is_pinned());
+
+    // …and is rolled back after the failure.
+    assert_let!(Some(VectorDiff::Set { index: 1, value: item }) = timeline_stream.next().await);
+    assert!(!item.as_event().unwrap().is_pinned());
+    assert_pending!(timeline_stream);
+
+    assert!(timeline.pinned_timeline().await.unwrap().is_empty());
+}
+
+#[async_test]
+async fn test_pin_latest() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room.timeline().await.unwrap();
+
+    // Nothing to pin in an empty timeline.
+    assert_matches!(timeline.pin_latest().await, Err(Error::RemoteEventNotInTimeline));
+
+    let f = EventFactory::new();
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id)
+            .add_timeline_event(f.text_msg("older").sender(*ALICE).event_id(event_id!("$a")))
+            .add_timeline_event(f.text_msg("latest").sender(*BOB).event_id(event_id!("$b"))),
+    );
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/state/m.room.pinned_events/$"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "event_id": "$pinned" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert!(timeline.pin_latest().await.unwrap());
+
+    let pinned = timeline.pinned_timeline().await.unwrap();
+    assert_eq!(pinned.len(), 1);
+    assert_eq!(pinned[0].event_id(), Some(event_id!("$b")));
+
+    // Pinning it again is a no-op.
+    assert!(!timeline.pin_latest().await.unwrap());
+}
+
+#[async_test]
+async fn test_pin_event_with_cap() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room.timeline().await.unwrap();
+
+    let f = EventFactory::new();
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id)
+            .add_timeline_event(f.text_msg("pin me").sender(*ALICE).event_id(event_id!("$c")))
+            .add_timeline_event(sync_timeline_event!({
+                "content": { "pinned": ["$a", "$b"] },
+                "event_id": "$pinned",
+                "origin_server_ts": 152037280,
+                "sender": "@alice:example.org",
+                "state_key": "",
+                "type": "m.room.pinned_events",
+            })),
+    );
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    // The oldest pinned event is unpinned in the same request.
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/state/m.room.pinned_events/$"))
+        .and(header("authorization", "Bearer 1234"))
+        .and(body_json(json!({ "pinned": ["$b", "$c"] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "event_id": "$pinned2" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let evicted = timeline.pin_event_with_cap(event_id!("$c"), 2).await.unwrap();
+    assert_eq!(evicted, [event_id!("$a").to_owned()]);
+
+    // Pinning it again is a no-op.
+    assert!(timeline.pin_event_with_cap(event_id!("$c"), 2).await.unwrap().is_empty());
+}
+
+#[async_test]
+async fn test_pinned_events_change_item() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room.timeline().await.unwrap();
+    let (_, mut timeline_stream) = timeline.subscribe().await;
+
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id).add_timeline_event(
+        sync_timeline_event!({
+            "content": { "pinned": ["$b", "$c"] },
+            "event_id": "$pinned",
+            "origin_server_ts": 152037280,
+            "sender": "@alice:example.org",
+            "state_key": "",
+            "type": "m.room.pinned_events",
+            "unsigned": {
+                "prev_content": { "pinned": ["$a", "$b"] },
+            },
+        }),
+    ));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    assert_let!(Some(VectorDiff::PushBack { value: item }) = timeline_stream.next().await);
+    assert_let!(
+        TimelineItemContent::PinnedEventsChange(change) = item.as_event().unwrap().content()
+    );
+    assert_eq!(change.pinned(), [event_id!("$c").to_owned()]);
+    assert_eq!(change.unpinned(), [event_id!("$a").to_owned()]);
+
+    assert_let!(Some(VectorDiff::PushFront { .. }) = timeline_stream.next().await);
+    assert_pending!(timeline_stream);
+}
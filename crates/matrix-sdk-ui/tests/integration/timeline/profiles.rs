@@ -15,12 +15,14 @@
 use std::{sync::Arc, time::Duration};
 
 use assert_matches::assert_matches;
+use eyeball_im::VectorDiff;
+use futures_util::StreamExt;
 use matrix_sdk::{config::SyncSettings, test_utils::logged_in_client_with_server};
 use matrix_sdk_test::{
     async_test, EventBuilder, JoinedRoomBuilder, SyncResponseBuilder, ALICE, BOB, CAROL,
     DEFAULT_TEST_ROOM_ID,
 };
-use matrix_sdk_ui::timeline::{RoomExt, TimelineDetails};
+use matrix_sdk_ui::timeline::{Profile, RoomExt, TimelineDetails};
 use ruma::events::room::{
     member::{MembershipState, RoomMemberEventContent},
     message::RoomMessageEventContent,
@@ -198,3 +200,134 @@ async fn test_update_sender_profiles() {
         TimelineDetails::Ready(_)
     );
 }
+
+#[async_test]
+async fn test_sender_membership_now() {
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let event_builder = EventBuilder::new();
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(&DEFAULT_TEST_ROOM_ID)
+            // Bob joins the room
+            .add_timeline_event(event_builder.make_sync_state_event(
+                &BOB,
+                BOB.as_str(),
+                RoomMemberEventContent::new(MembershipState::Join),
+                None,
+            ))
+            // Bob sends a text message
+            .add_timeline_event(event_builder.make_sync_message_event(
+                &BOB,
+                RoomMessageEventContent::text_plain("text message event"),
+            )),
+    );
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+    let timeline = room.timeline().await.unwrap();
+    let (items, mut timeline_stream) = timeline.subscribe().await;
+
+    assert_eq!(items.len(), 3);
+    let message = items[2].as_event().unwrap();
+    assert_eq!(message.sender(), *BOB);
+    assert_eq!(message.sender_membership_now(), Some(&MembershipState::Join));
+
+    // Carol kicks Bob while the timeline is open.
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(&DEFAULT_TEST_ROOM_ID).add_timeline_event(
+        event_builder.make_sync_state_event(
+            &CAROL,
+            BOB.as_str(),
+            RoomMemberEventContent::new(MembershipState::Leave),
+            Some(RoomMemberEventContent::new(MembershipState::Join)),
+        ),
+    ));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+
+    // The message from Bob is updated in place with his new membership.
+    let message = tokio::time::timeout(Duration::from_secs(1), async {
+        loop {
+            if let Some(VectorDiff::Set { index: 2, value }) = timeline_stream.next().await {
+                let message = value.as_event().unwrap().clone();
+                if message.sender_membership_now() == Some(&MembershipState::Leave) {
+                    break message;
+                }
+            }
+        }
+    })
+    .await
+    .unwrap();
+
+    // Its content is unchanged.
+    assert_eq!(message.content().as_message().unwrap().body(), "text message event");
+}
+
+#[async_test]
+async fn test_fetch_profiles() {
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let event_builder = EventBuilder::new();
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(&DEFAULT_TEST_ROOM_ID).add_timeline_event(
+        event_builder.make_sync_state_event(
+            &BOB,
+            BOB.as_str(),
+            RoomMemberEventContent::new(MembershipState::Join),
+            None,
+        ),
+    ));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    // The member list is fetched only once, for all the missing profiles.
+    let mut carol = RoomMemberEventContent::new(MembershipState::Join);
+    carol.displayname = Some("Carol".to_owned());
+    Mock::given(method("GET"))
+        .and(path_regex(r"/_matrix/client/r0/rooms/.*/members"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "chunk": [
+                event_builder.make_state_event(
+                    &CAROL,
+                    &DEFAULT_TEST_ROOM_ID,
+                    CAROL.as_str(),
+                    carol,
+                    None,
+                ),
+                event_builder.make_state_event(
+                    &BOB,
+                    &DEFAULT_TEST_ROOM_ID,
+                    BOB.as_str(),
+                    RoomMemberEventContent::new(MembershipState::Join),
+                    None,
+                ),
+            ]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+    let user_ids = [BOB.to_owned(), CAROL.to_owned(), ALICE.to_owned()];
+
+    let profiles = room.fetch_profiles(&user_ids).await;
+    assert_eq!(profiles.len(), 3);
+    assert_eq!(profiles[*BOB].membership, Some(MembershipState::Join));
+    assert_eq!(profiles[*CAROL].display_name.as_deref(), Some("Carol"));
+    // Alice isn't a member of the room.
+    assert_eq!(profiles[*ALICE], Profile::default());
+
+    // The profiles are now all in the store.
+    let profiles = room.fetch_profiles(&user_ids).await;
+    assert_eq!(profiles.len(), 3);
+
+    server.verify().await;
+}
@@ -0,0 +1,99 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use futures_util::future::join_all;
+use matrix_sdk::{
+    config::SyncSettings,
+    test_utils::{events::EventFactory, logged_in_client_with_server},
+};
+use matrix_sdk_test::{async_test, JoinedRoomBuilder, SyncResponseBuilder, BOB};
+use matrix_sdk_ui::timeline::RoomExt;
+use ruma::{event_id, events::relation::Annotation, room_id, user_id};
+use serde_json::json;
+use wiremock::{
+    matchers::{method, path_regex},
+    Mock, ResponseTemplate,
+};
+
+use crate::{mock_encryption_state, mock_sync};
+
+#[async_test]
+async fn test_toggle_reaction_burst() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let event_id = event_id!("$message");
+    let own_user_id = user_id!("@example:localhost");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id).add_timeline_event(
+            EventFactory::new().text_msg("hello").sender(*BOB).event_id(event_id),
+        ),
+    );
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    mock_encryption_state(&server, false).await;
+
+    // A single reaction is sent, and nothing is redacted.
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/send/m\.reaction/.*"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "event_id": "$reaction" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/redact/.*"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "event_id": "$redaction" })))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room
+        .timeline_builder()
+        .reaction_debounce(Duration::from_millis(100))
+        .build()
+        .await
+        .unwrap();
+    let annotation = Annotation::new(event_id.to_owned(), "👍".to_owned());
+
+    // 5 rapid toggles end up with the reaction, sent once.
+    for result in join_all((0..5).map(|_| timeline.toggle_reaction(&annotation))).await {
+        result.unwrap();
+    }
+
+    let item = timeline.item_by_event_id(event_id).await.unwrap();
+    let reactions: Vec<_> = item.reactions()["👍"].by_sender(own_user_id).collect();
+    assert_eq!(reactions.len(), 1);
+    assert_eq!(reactions[0].0, None);
+    assert_eq!(reactions[0].1.map(|id| id.as_str()), Some("$reaction"));
+
+    // 4 more rapid toggles cancel each other, without any request.
+    for result in join_all((0..4).map(|_| timeline.toggle_reaction(&annotation))).await {
+        result.unwrap();
+    }
+
+    let item = timeline.item_by_event_id(event_id).await.unwrap();
+    let reactions: Vec<_> = item.reactions()["👍"].by_sender(own_user_id).collect();
+    assert_eq!(reactions.len(), 1);
+    assert_eq!(reactions[0].1.map(|id| id.as_str()), Some("$reaction"));
+
+    server.verify().await;
+}
@@ -942,6 +942,80 @@ async fn test_mark_as_read() {
     server.reset().await;
 }
 
+#[async_test]
+async fn test_mark_visible_with_delay() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room
+        .timeline_builder()
+        .receipt_visibility_delay(Duration::from_millis(200))
+        .build()
+        .await
+        .unwrap();
+
+    let first_event_id = event_id!("$first_event_id");
+    let second_event_id = event_id!("$second_event_id");
+
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id)
+            .add_timeline_event(sync_timeline_event!({
+                "content": {
+                    "body": "I'm scrolled past",
+                    "msgtype": "m.text",
+                },
+                "event_id": first_event_id,
+                "origin_server_ts": 152037280,
+                "sender": &*ALICE,
+                "type": "m.room.message",
+            }))
+            .add_timeline_event(sync_timeline_event!({
+                "content": {
+                    "body": "I'm read",
+                    "msgtype": "m.text",
+                },
+                "event_id": second_event_id,
+                "origin_server_ts": 152039280,
+                "sender": &*BOB,
+                "type": "m.room.message",
+            })),
+    );
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/receipt/m\.read/"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+        .expect(1)
+        .named("Public read receipt")
+        .mount(&server)
+        .await;
+
+    // Scrolling quickly through the first event doesn't send a receipt for it.
+    timeline.mark_visible(vec![first_event_id.to_owned()]).await.unwrap();
+    timeline.mark_visible(vec![]).await.unwrap();
+
+    // The second event stays visible, so a receipt is sent after the delay.
+    timeline.mark_visible(vec![second_event_id.to_owned()]).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    timeline.mark_visible(vec![second_event_id.to_owned()]).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    server.verify().await;
+}
+
 #[async_test]
 async fn test_send_multiple_receipts() {
     let room_id = room_id!("!a98sd12bjh:example.org");
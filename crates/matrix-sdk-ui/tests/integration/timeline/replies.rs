@@ -248,6 +248,77 @@ async fn test_transfer_in_reply_to_details_to_re_received_item() {
     assert_matches!(in_reply_to.event, TimelineDetails::Ready(_));
 }
 
+#[async_test]
+async fn test_in_reply_to_redacted_event() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room.timeline().await.unwrap();
+
+    // Given a reply to an event that's not in the timeline...
+    let event_id_1 = event_id!("$event1");
+    let f = EventFactory::new();
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id)
+            .add_timeline_event(f.text_msg("indeed").reply_to(event_id_1).sender(*BOB)),
+    );
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let items = timeline.items().await;
+    let event_item = items[1].as_event().unwrap();
+    let in_reply_to = event_item.in_reply_to().unwrap();
+    assert_eq!(in_reply_to.event_id, event_id_1);
+    assert_matches!(in_reply_to.event, TimelineDetails::Unavailable);
+
+    // ... which was redacted in the meantime.
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/event/\$event1"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "content": {},
+            "event_id": "$event1",
+            "origin_server_ts": 152037280,
+            "room_id": room_id,
+            "sender": "@alice:server.name",
+            "type": "m.room.message",
+            "unsigned": {
+                "redacted_because": {
+                    "content": {},
+                    "event_id": "$redaction",
+                    "origin_server_ts": 152037290,
+                    "redacts": "$event1",
+                    "room_id": room_id,
+                    "sender": "@alice:server.name",
+                    "type": "m.room.redaction",
+                },
+            },
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    timeline.fetch_details_for_event(event_item.event_id().unwrap()).await.unwrap();
+
+    // The sender of the replied-to event is known, but its content was removed.
+    let items = timeline.items().await;
+    let in_reply_to = items[1].as_event().unwrap().in_reply_to().unwrap();
+    assert_let!(TimelineDetails::Ready(replied_to) = in_reply_to.event);
+    assert!(replied_to.is_redacted());
+    assert_eq!(replied_to.sender(), *ALICE);
+}
+
 #[async_test]
 async fn test_send_reply() {
     let room_id = room_id!("!a98sd12bjh:example.org");
@@ -12,12 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use assert_matches::assert_matches;
 use assert_matches2::assert_let;
 use eyeball_im::VectorDiff;
-use futures_util::{pin_mut, StreamExt};
+use futures_util::{pin_mut, FutureExt, StreamExt};
+use imbl::Vector;
 use matrix_sdk::{
     config::SyncSettings,
     test_utils::{events::EventFactory, logged_in_client_with_server},
@@ -26,7 +27,7 @@ use matrix_sdk_test::{
     async_test, sync_timeline_event, EventBuilder, GlobalAccountDataTestEvent, JoinedRoomBuilder,
     SyncResponseBuilder, ALICE, BOB,
 };
-use matrix_sdk_ui::timeline::{RoomExt, TimelineDetails, TimelineItemContent};
+use matrix_sdk_ui::timeline::{RoomExt, TimelineDetails, TimelineItem, TimelineItemContent};
 use ruma::{
     event_id,
     events::room::{
@@ -196,7 +197,118 @@ async fn test_event_filter() {
 }
 
 #[async_test]
-async fn test_timeline_is_reset_when_a_user_is_ignored_or_unignored() {
+async fn test_content_transform() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    // Mask the messages with a bad word, and hide the secret ones.
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room
+        .timeline_builder()
+        .with_content_transform(|content| match content.as_message().map(|msg| msg.body()) {
+            Some(body) if body.contains("darn") => Some(TimelineItemContent::RedactedMessage),
+            Some(body) if body.contains("secret") => None,
+            _ => Some(content.clone()),
+        })
+        .build()
+        .await
+        .unwrap();
+    let (_, mut timeline_stream) = timeline.subscribe().await;
+
+    let first_event_id = event_id!("$YTQwYl2ply");
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id)
+            .add_timeline_event(sync_timeline_event!({
+                "content": {
+                    "body": "hello",
+                    "msgtype": "m.text",
+                },
+                "event_id": first_event_id,
+                "origin_server_ts": 152037280,
+                "sender": "@alice:example.org",
+                "type": "m.room.message",
+            }))
+            .add_timeline_event(sync_timeline_event!({
+                "content": {
+                    "body": "a secret",
+                    "msgtype": "m.text",
+                },
+                "event_id": "$Ga6Y2l0gKY",
+                "origin_server_ts": 152038280,
+                "sender": "@bob:example.org",
+                "type": "m.room.message",
+            })),
+    );
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    assert_let!(Some(VectorDiff::PushBack { value: first }) = timeline_stream.next().await);
+    assert_let!(TimelineItemContent::Message(msg) = first.as_event().unwrap().content());
+    assert_eq!(msg.body(), "hello");
+
+    assert_let!(Some(VectorDiff::PushFront { value: day_divider }) = timeline_stream.next().await);
+    assert!(day_divider.is_day_divider());
+
+    // The secret message is hidden.
+    assert_pending!(timeline_stream);
+
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id).add_timeline_event(
+        sync_timeline_event!({
+            "content": {
+                "body": " * darn",
+                "m.new_content": {
+                    "body": "darn",
+                    "msgtype": "m.text",
+                },
+                "m.relates_to": {
+                    "event_id": first_event_id,
+                    "rel_type": "m.replace",
+                },
+                "msgtype": "m.text",
+            },
+            "event_id": "$7i9In0gEmB",
+            "origin_server_ts": 159056300,
+            "sender": "@alice:example.org",
+            "type": "m.room.message",
+        }),
+    ));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    // The implicit read receipt of Alice moves to the edit.
+    assert_let!(Some(VectorDiff::Set { index: 1, value: first }) = timeline_stream.next().await);
+    assert_let!(TimelineItemContent::Message(msg) = first.as_event().unwrap().content());
+    assert_eq!(msg.body(), "hello");
+
+    // The transform is applied again to the edited message.
+    assert_let!(Some(VectorDiff::Set { index: 1, value: first }) = timeline_stream.next().await);
+    assert_matches!(first.as_event().unwrap().content(), TimelineItemContent::RedactedMessage);
+
+    assert_pending!(timeline_stream);
+
+    // The items of the timeline are left untouched.
+    let items = timeline.items().await;
+    assert_eq!(items.len(), 3);
+    assert_let!(TimelineItemContent::Message(msg) = items[1].as_event().unwrap().content());
+    assert_eq!(msg.body(), "darn");
+    assert_let!(TimelineItemContent::Message(msg) = items[2].as_event().unwrap().content());
+    assert_eq!(msg.body(), "a secret");
+}
+
+#[async_test]
+async fn test_timeline_removes_and_restores_events_of_ignored_users() {
     let room_id = room_id!("!a98sd12bjh:example.org");
     let (client, server) = logged_in_client_with_server().await;
     let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
@@ -220,7 +332,7 @@ async fn test_timeline_is_reset_when_a_user_is_ignored_or_unignored() {
     let second_event_id = event_id!("$YTQwYl2pl2");
     let third_event_id = event_id!("$YTQwYl2pl3");
 
-    let mut ev_factory = EventFactory::new().room(room_id);
+    let ev_factory = EventFactory::new().room(room_id);
 
     sync_builder.add_joined_room(
         JoinedRoomBuilder::new(room_id)
@@ -265,40 +377,34 @@ async fn test_timeline_is_reset_when_a_user_is_ignored_or_unignored() {
     let _response = client.sync_once(sync_settings.clone()).await.unwrap();
     server.reset().await;
 
-    // The timeline has been emptied.
-    assert_next_matches!(timeline_stream, VectorDiff::Clear);
+    // The message of Bob is removed from the timeline.
+    assert_matches!(timeline_stream.next().await, Some(VectorDiff::Remove { index: 2 }));
     assert_pending!(timeline_stream);
 
-    let fourth_event_id = event_id!("$YTQwYl2pl4");
-    let fifth_event_id = event_id!("$YTQwYl2pl5");
-
-    // All the next events are sent by Alice now.
-    ev_factory = ev_factory.sender(alice);
+    let items = timeline.items().await;
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[1].as_event().unwrap().event_id(), Some(first_event_id));
+    assert_eq!(items[2].as_event().unwrap().event_id(), Some(third_event_id));
 
-    sync_builder.add_joined_room(
-        JoinedRoomBuilder::new(room_id)
-            .add_timeline_event(ev_factory.text_msg("hello").event_id(fourth_event_id))
-            .add_timeline_event(ev_factory.text_msg("hello").event_id(fifth_event_id)),
-    );
+    sync_builder.add_global_account_data_event(GlobalAccountDataTestEvent::Custom(json!({
+        "content": {
+            "ignored_users": {}
+        },
+        "type": "m.ignored_user_list",
+    })));
 
     mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
     let _response = client.sync_once(sync_settings.clone()).await.unwrap();
     server.reset().await;
 
-    // Timeline receives events as before.
-    assert_next_matches!(timeline_stream, VectorDiff::PushBack { value } => {
-        assert_eq!(value.as_event().unwrap().event_id(), Some(fourth_event_id));
-    });
-    assert_next_matches!(timeline_stream, VectorDiff::Set { index: 0, value } => {
-        assert_eq!(value.as_event().unwrap().event_id(), Some(fourth_event_id));
-    });
-    assert_next_matches!(timeline_stream, VectorDiff::PushBack { value } => {
-        assert_eq!(value.as_event().unwrap().event_id(), Some(fifth_event_id));
-    });
-    assert_next_matches!(timeline_stream, VectorDiff::PushFront { value } => {
-        assert!(value.is_day_divider());
-    });
-    assert_pending!(timeline_stream);
+    // The timeline is reloaded from the event cache, with the message of Bob.
+    assert_matches!(timeline_stream.next().await, Some(VectorDiff::Clear));
+
+    let items = timeline.items().await;
+    assert_eq!(items.len(), 4);
+    assert_eq!(items[1].as_event().unwrap().event_id(), Some(first_event_id));
+    assert_eq!(items[2].as_event().unwrap().event_id(), Some(second_event_id));
+    assert_eq!(items[3].as_event().unwrap().event_id(), Some(third_event_id));
 }
 
 #[async_test]
@@ -559,3 +665,56 @@ async fn test_profile_updates() {
 
     assert_pending!(timeline_stream);
 }
+
+#[async_test]
+async fn test_lagging_subscription_is_reset() {
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let (client, server) = logged_in_client_with_server().await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+
+    let event_builder = EventBuilder::new();
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(room_id).unwrap();
+    let timeline = room.timeline().await.unwrap();
+    let mut subscription = timeline.subscription().await;
+    assert_eq!(subscription.initial_items().len(), timeline.items().await.len());
+
+    // Receive more updates than the subscription can buffer, without polling it.
+    for i in 0..50 {
+        sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id).add_timeline_event(
+            event_builder.make_sync_message_event(
+                &ALICE,
+                RoomMessageEventContent::text_plain(format!("message {i}")),
+            ),
+        ));
+
+        mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+        let _response = client.sync_once(sync_settings.clone()).await.unwrap();
+        server.reset().await;
+    }
+
+    // Let the timeline handle the updates.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    // The missed updates are replaced by a reset with the current items…
+    let mut items = assert_next_matches!(subscription, VectorDiff::Reset { values } => values);
+
+    // … followed by the updates that were still buffered.
+    while let Some(Some(diff)) = subscription.next().now_or_never() {
+        diff.apply(&mut items);
+    }
+
+    let event_ids = |items: &Vector<Arc<TimelineItem>>| -> Vec<String> {
+        items.iter().filter_map(|item| Some(item.as_event()?.event_id()?.to_string())).collect()
+    };
+    let timeline_items = timeline.items().await;
+    assert_eq!(event_ids(&timeline_items).len(), 50);
+    assert_eq!(items.len(), timeline_items.len());
+    assert_eq!(event_ids(&items), event_ids(&timeline_items));
+}
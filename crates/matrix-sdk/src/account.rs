@@ -51,7 +51,9 @@ use ruma::{
 use serde::Deserialize;
 use tracing::error;
 
-use crate::{config::RequestConfig, Client, Error, Result};
+use crate::{
+    config::RequestConfig, notification_settings::ScDndEventContent, Client, Error, Result,
+};
 
 /// A high-level API to manage the client owner's account.
 ///
@@ -708,6 +710,17 @@ impl Account {
         get_raw_content(self.client.store().get_account_data_event_static::<C>().await?)
     }
 
+    /// SC: Get whether Do-Not-Disturb is enabled, see [`ScDndEventContent`].
+    ///
+    /// This is read from the local store, so it's up to date as of the last
+    /// sync.
+    pub async fn is_dnd_enabled(&self) -> Result<bool> {
+        let Some(raw) = self.account_data::<ScDndEventContent>().await? else {
+            return Ok(false);
+        };
+        Ok(raw.deserialize()?.enabled)
+    }
+
     /// Get the content of an account data event of a given type.
     pub async fn account_data_raw(
         &self,
@@ -887,8 +900,8 @@ impl Account {
 
         // Updating the account data
         self.set_account_data(ignored_user_list).await?;
-        // TODO: I think I should reset all the storage and perform a new local sync
-        // here but I don't know how
+        // SC: The live timelines remove the events of the user once the new list is
+        // received from sync.
         Ok(())
     }
 
@@ -899,8 +912,8 @@ impl Account {
 
         // Updating the account data
         self.set_account_data(ignored_user_list).await?;
-        // TODO: I think I should reset all the storage and perform a new local sync
-        // here but I don't know how
+        // SC: The live timelines restore the events of the user once the new list is
+        // received from sync.
         Ok(())
     }
 
@@ -15,7 +15,9 @@
 
 use std::{fmt, sync::Arc};
 
-use matrix_sdk_base::{store::StoreConfig, BaseClient};
+use matrix_sdk_base::{
+    store::StoreConfig, BaseClient, DisplayNameGenerator, DisplayNameGeneratorFn,
+};
 use ruma::{
     api::{
         client::discovery::{discover_homeserver, get_supported_versions},
@@ -94,6 +96,7 @@ pub struct ClientBuilder {
     base_client: Option<BaseClient>,
     #[cfg(feature = "e2e-encryption")]
     encryption_settings: EncryptionSettings,
+    display_name_generator: Option<DisplayNameGenerator>,
 }
 
 impl ClientBuilder {
@@ -111,6 +114,7 @@ impl ClientBuilder {
             base_client: None,
             #[cfg(feature = "e2e-encryption")]
             encryption_settings: Default::default(),
+            display_name_generator: None,
         }
     }
 
@@ -364,6 +368,17 @@ impl ClientBuilder {
         self
     }
 
+    /// SC: Use a custom room name computation for rooms that have neither an
+    /// explicit name nor a canonical alias.
+    ///
+    /// The generator is consulted whenever a room's display name is
+    /// (re)computed. Returning `None` falls back to the default heroes-based
+    /// name.
+    pub fn with_display_name_generator(mut self, generator: Box<DisplayNameGeneratorFn>) -> Self {
+        self.display_name_generator = Some(generator.into());
+        self
+    }
+
     /// Enables specific encryption settings that will persist throughout the
     /// entire lifetime of the `Client`.
     #[cfg(feature = "e2e-encryption")]
@@ -407,6 +422,12 @@ impl ClientBuilder {
             BaseClient::with_store_config(build_store_config(self.store_config).await?)
         };
 
+        let base_client = if let Some(generator) = self.display_name_generator {
+            base_client.with_display_name_generator(generator)
+        } else {
+            base_client
+        };
+
         let http_client = HttpClient::new(inner_http_client.clone(), self.request_config);
 
         let (homeserver, well_known) = match homeserver_cfg {
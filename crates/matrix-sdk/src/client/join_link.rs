@@ -0,0 +1,270 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: Joining a room from a `matrix:` URI or a `matrix.to` link, see
+//! [`Client::join_by_link`].
+
+use matrix_sdk_base::RoomState;
+use ruma::{
+    api::client::{error::ErrorKind, room::get_summary, state::get_state_events_for_key},
+    events::{
+        room::{
+            join_rules::{AllowRule, JoinRule, RoomJoinRulesEventContent},
+            member::MembershipState,
+        },
+        StateEventType,
+    },
+    matrix_uri::MatrixId,
+    space::SpaceRoomJoinRule,
+    MatrixToUri, MatrixUri, OwnedRoomId, OwnedRoomOrAliasId, OwnedServerName, RoomId,
+    RoomOrAliasId,
+};
+use thiserror::Error;
+use tracing::{debug, instrument};
+
+use crate::{Client, Error, Room};
+
+/// SC: Errors of [`Client::join_by_link`].
+#[derive(Debug, Error)]
+pub enum JoinByLinkError {
+    /// The link isn't a `matrix:` URI or a `matrix.to` link.
+    #[error("Invalid link")]
+    InvalidLink,
+
+    /// The link doesn't point to a room.
+    #[error("The link doesn't point to a room")]
+    NotARoom,
+
+    /// The room can only be joined after knocking.
+    #[error("The room requires knocking to join")]
+    KnockRequired,
+
+    /// The user is banned from the room.
+    #[error("The user is banned from the room")]
+    Banned,
+
+    /// Another error happened while joining the room.
+    #[error(transparent)]
+    Sdk(#[from] Error),
+}
+
+impl Client {
+    /// SC: Join the room a `matrix:` URI or a `matrix.to` link points to.
+    ///
+    /// The `via` servers of the link are completed with the ones of the joined
+    /// spaces containing the room. If joining fails, and the join rules of the
+    /// room allow the members of some rooms the user has joined to join it,
+    /// joining is retried through the servers of these rooms.
+    #[instrument(skip(self))]
+    pub async fn join_by_link(&self, link: &str) -> Result<Room, JoinByLinkError> {
+        let (matrix_id, mut via) = if let Ok(uri) = MatrixUri::parse(link) {
+            (uri.id().clone(), uri.via().to_owned())
+        } else if let Ok(uri) = MatrixToUri::parse(link) {
+            (uri.id().clone(), uri.via().to_owned())
+        } else {
+            return Err(JoinByLinkError::InvalidLink);
+        };
+
+        let room_or_alias_id: OwnedRoomOrAliasId = match matrix_id {
+            MatrixId::Room(room_id) => room_id.into(),
+            MatrixId::RoomAlias(alias) => alias.into(),
+            MatrixId::Event(room_or_alias_id, _) => room_or_alias_id,
+            _ => return Err(JoinByLinkError::NotARoom),
+        };
+
+        if let Ok(room_id) = <&RoomId>::try_from(&*room_or_alias_id) {
+            extend_via(&mut via, self.via_from_joined_spaces(room_id));
+        }
+
+        let mut error = match self.join_room_by_id_or_alias(&room_or_alias_id, &via).await {
+            Ok(room) => return Ok(room),
+            Err(error) => error,
+        };
+
+        // A restricted room can only be joined through a server that is in one of the
+        // rooms allowing to join it.
+        if error.client_api_error_kind().is_some() {
+            if let Some(room_id) = self.room_id_of(&room_or_alias_id).await {
+                let allowed_room_ids = self.allowed_room_ids(&room_id).await;
+                let allowed_via = self.via_from_allowed_rooms(&room_id, &allowed_room_ids).await;
+
+                if extend_via(&mut via, allowed_via) {
+                    debug!("Retrying to join through the servers of the allowed rooms");
+                    match self.join_room_by_id_or_alias(&room_or_alias_id, &via).await {
+                        Ok(room) => return Ok(room),
+                        Err(retry_error) => error = retry_error,
+                    }
+                }
+            }
+        }
+
+        Err(self.join_by_link_error(&room_or_alias_id, via, error).await)
+    }
+
+    /// The ID of the room with the given ID or alias, if it can be resolved.
+    async fn room_id_of(&self, room_or_alias_id: &RoomOrAliasId) -> Option<OwnedRoomId> {
+        match <&RoomId>::try_from(room_or_alias_id) {
+            Ok(room_id) => Some(room_id.to_owned()),
+            Err(alias) => Some(self.resolve_room_alias(alias).await.ok()?.room_id),
+        }
+    }
+
+    /// The `via` servers of the room in the joined spaces containing it.
+    fn via_from_joined_spaces(&self, room_id: &RoomId) -> Vec<OwnedServerName> {
+        self.joined_rooms()
+            .into_iter()
+            .filter(|room| room.is_space())
+            .filter_map(|space| {
+                let child = space.space_children().remove(room_id)?;
+                Some(child.as_original()?.content.via.clone())
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// The rooms whose members are allowed to join the room with the given ID,
+    /// according to its join rules, if they are known.
+    async fn allowed_room_ids(&self, room_id: &RoomId) -> Vec<OwnedRoomId> {
+        if let Some(room) = self.get_room(room_id) {
+            return allowed_room_ids(&room.join_rule());
+        }
+
+        // The join rules of a room the user isn't in can only be fetched if its history
+        // is world-readable.
+        let request = get_state_events_for_key::v3::Request::new(
+            room_id.to_owned(),
+            StateEventType::RoomJoinRules,
+            "".to_owned(),
+        );
+        match self.send(request, None).await {
+            Ok(response) => response
+                .content
+                .deserialize_as::<RoomJoinRulesEventContent>()
+                .map(|content| allowed_room_ids(&content.join_rule))
+                .unwrap_or_default(),
+            Err(error) => {
+                debug!("Could not get the join rules of the room: {error}");
+                Vec::new()
+            }
+        }
+    }
+
+    /// The `via` servers to join the room with the given ID through the allowed
+    /// rooms the user has joined.
+    async fn via_from_allowed_rooms(
+        &self,
+        room_id: &RoomId,
+        allowed_room_ids: &[OwnedRoomId],
+    ) -> Vec<OwnedServerName> {
+        let mut via = Vec::new();
+
+        for allowed_room_id in allowed_room_ids {
+            let Some(room) = self.get_room(allowed_room_id) else {
+                continue;
+            };
+            if room.state() != RoomState::Joined {
+                continue;
+            }
+
+            // The allowed room is usually the space containing the room.
+            if let Some(child) = room.space_children().remove(room_id) {
+                if let Some(child) = child.as_original() {
+                    via.extend(child.content.via.iter().cloned());
+                }
+            }
+
+            via.extend(room.suggested_via_servers().await.unwrap_or_default());
+        }
+
+        via
+    }
+
+    /// Explain why joining the room failed, if the user can do something about
+    /// it.
+    async fn join_by_link_error(
+        &self,
+        room_or_alias_id: &RoomOrAliasId,
+        via: Vec<OwnedServerName>,
+        error: Error,
+    ) -> JoinByLinkError {
+        if !matches!(error.client_api_error_kind(), Some(ErrorKind::Forbidden { .. })) {
+            return error.into();
+        }
+
+        if let Ok(room_id) = <&RoomId>::try_from(room_or_alias_id) {
+            if let Some(room) = self.get_room(room_id) {
+                if let Ok(Some(member)) = room.get_member_no_sync(room.own_user_id()).await {
+                    if *member.membership() == MembershipState::Ban {
+                        return JoinByLinkError::Banned;
+                    }
+                }
+            }
+        }
+
+        // When the room isn't known locally, the room summary tells us the membership
+        // of the user.
+        let request = get_summary::msc3266::Request::new(room_or_alias_id.to_owned(), via.clone());
+        if let Ok(summary) = self.send(request, None).await {
+            return if summary.membership == Some(MembershipState::Ban) {
+                JoinByLinkError::Banned
+            } else if is_knock_join_rule(&summary.join_rule) {
+                JoinByLinkError::KnockRequired
+            } else {
+                error.into()
+            };
+        }
+
+        match self.get_room_preview(room_or_alias_id, via).await {
+            Ok(preview) if is_knock_join_rule(&preview.join_rule) => JoinByLinkError::KnockRequired,
+            _ => error.into(),
+        }
+    }
+}
+
+/// The rooms whose members are allowed to join a room with the given join
+/// rule.
+fn allowed_room_ids(join_rule: &JoinRule) -> Vec<OwnedRoomId> {
+    let (JoinRule::Restricted(restricted) | JoinRule::KnockRestricted(restricted)) = join_rule
+    else {
+        return Vec::new();
+    };
+
+    restricted
+        .allow
+        .iter()
+        .filter_map(|rule| match rule {
+            AllowRule::RoomMembership(membership) => Some(membership.room_id.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether the room can only be joined after knocking.
+fn is_knock_join_rule(join_rule: &SpaceRoomJoinRule) -> bool {
+    matches!(join_rule, SpaceRoomJoinRule::Knock | SpaceRoomJoinRule::KnockRestricted)
+}
+
+/// Add the servers to `via` that it doesn't contain yet, and return whether
+/// any was added.
+fn extend_via(via: &mut Vec<OwnedServerName>, servers: Vec<OwnedServerName>) -> bool {
+    let len = via.len();
+
+    for server in servers {
+        if !via.contains(&server) {
+            via.push(server);
+        }
+    }
+
+    via.len() > len
+}
@@ -19,7 +19,10 @@ use std::{
     fmt::{self, Debug},
     future::Future,
     pin::Pin,
-    sync::{Arc, Mutex as StdMutex, RwLock as StdRwLock, Weak},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex as StdMutex, RwLock as StdRwLock, Weak,
+    },
 };
 
 use eyeball::{SharedObservable, Subscriber};
@@ -58,9 +61,10 @@ use ruma::{
         MatrixVersion, OutgoingRequest,
     },
     assign,
+    events::presence::PresenceState,
     push::Ruleset,
-    DeviceId, OwnedDeviceId, OwnedEventId, OwnedRoomId, OwnedServerName, RoomAliasId, RoomId,
-    RoomOrAliasId, ServerName, UInt, UserId,
+    DeviceId, OwnedDeviceId, OwnedEventId, OwnedRoomId, OwnedServerName, OwnedUserId, RoomAliasId,
+    RoomId, RoomOrAliasId, ServerName, UInt, UserId,
 };
 use serde::de::DeserializeOwned;
 use tokio::sync::{broadcast, Mutex, OnceCell, RwLock, RwLockReadGuard};
@@ -81,7 +85,9 @@ use crate::{
     },
     http_client::HttpClient,
     matrix_auth::MatrixAuth,
+    media::AvatarCache,
     notification_settings::NotificationSettings,
+    room::UrlPreviewCache,
     room_preview::RoomPreview,
     send_queue::SendQueueData,
     sync::{RoomUpdate, SyncResponse},
@@ -96,8 +102,12 @@ use crate::{
 
 mod builder;
 pub(crate) mod futures;
+mod join_link;
 
-pub use self::builder::{sanitize_server_name, ClientBuildError, ClientBuilder};
+pub use self::{
+    builder::{sanitize_server_name, ClientBuildError, ClientBuilder},
+    join_link::JoinByLinkError,
+};
 
 #[cfg(not(target_arch = "wasm32"))]
 type NotificationHandlerFut = Pin<Box<dyn Future<Output = ()> + Send>>;
@@ -288,6 +298,17 @@ pub(crate) struct ClientInner {
     ///
     /// [`SendQueue`]: crate::send_queue::SendQueue
     pub(crate) send_queue_data: Arc<SendQueueData>,
+
+    /// SC: The previews of URLs that have been fetched, see
+    /// [`Room::get_url_preview`].
+    pub(crate) url_previews: StdMutex<UrlPreviewCache>,
+
+    /// SC: The cache of the avatars, see [`Media::get_avatar`].
+    pub(crate) avatar_cache: AvatarCache,
+
+    /// SC: Whether to join the successor of the rooms that get a tombstone
+    /// during sync, see [`Client::set_auto_follow_tombstones`].
+    auto_follow_tombstones: AtomicBool,
 }
 
 impl ClientInner {
@@ -331,6 +352,9 @@ impl ClientInner {
             sync_beat: event_listener::Event::new(),
             event_cache,
             send_queue_data: send_queue,
+            url_previews: Default::default(),
+            avatar_cache: Default::default(),
+            auto_follow_tombstones: Default::default(),
             #[cfg(feature = "e2e-encryption")]
             e2ee: EncryptionData::new(encryption_settings),
             #[cfg(feature = "e2e-encryption")]
@@ -379,6 +403,76 @@ impl Client {
         self.inner.base_client.subscribe_to_ignore_user_list_changes()
     }
 
+    /// SC: Get the users currently ignored by the user, as of the last sync.
+    ///
+    /// Use [`Client::subscribe_to_ignore_user_list_changes`] to follow the
+    /// changes of this list.
+    pub fn ignored_users(&self) -> Vec<String> {
+        self.inner.base_client.ignored_users()
+    }
+
+    /// SC: Add `user_id` to the `m.ignored_user_list` of the account.
+    ///
+    /// The server stops sending the events of ignored users, and once the
+    /// change is received back from sync, the live timelines remove the events
+    /// of the user that they already loaded.
+    pub async fn ignore_user(&self, user_id: &UserId) -> Result<()> {
+        self.account().ignore_user(user_id).await
+    }
+
+    /// SC: Remove `user_id` from the `m.ignored_user_list` of the account.
+    ///
+    /// Once the change is received back from sync, the live timelines restore
+    /// the events of the user that are still in the event cache.
+    pub async fn unignore_user(&self, user_id: &UserId) -> Result<()> {
+        self.account().unignore_user(user_id).await
+    }
+
+    /// SC: Get the last-known presence of the given user.
+    ///
+    /// This reflects the presence events received in sync responses, so it
+    /// may be stale, or missing if the server doesn't send presence.
+    pub fn presence(&self, user_id: &UserId) -> Option<PresenceState> {
+        self.inner.base_client.presence(user_id)
+    }
+
+    /// SC: Whether the given room is a valid child of any of the given spaces,
+    /// i.e. it is listed with a non-empty `via` in an `m.space.child` event
+    /// of one of the spaces.
+    ///
+    /// The children of the spaces are indexed as the spaces are synced, and
+    /// the rooms that are added to or removed from a space receive a room
+    /// info update, so room lists can filter them again.
+    pub fn is_space_child(&self, room_id: &RoomId, space_ids: &[OwnedRoomId]) -> bool {
+        self.inner.base_client.is_space_child(room_id, space_ids)
+    }
+
+    /// SC: Returns a subscriber that publishes the last-known presence of all
+    /// the users every time it changes.
+    pub fn subscribe_to_presence_changes(
+        &self,
+    ) -> Subscriber<BTreeMap<OwnedUserId, PresenceState>> {
+        self.inner.base_client.subscribe_to_presence_changes()
+    }
+
+    /// SC: Set whether the successor of a room is joined automatically when
+    /// the room gets a tombstone during sync, see [`Room::follow_tombstone`].
+    ///
+    /// Only the tombstones received in the timeline of an incremental sync
+    /// without a gap are followed, not the ones that might have been sent
+    /// long ago.
+    ///
+    /// Disabled by default.
+    pub fn set_auto_follow_tombstones(&self, enabled: bool) {
+        self.inner.auto_follow_tombstones.store(enabled, Ordering::SeqCst);
+    }
+
+    /// SC: Whether the successor of a room is joined automatically when the
+    /// room gets a tombstone during sync.
+    pub fn auto_follows_tombstones(&self) -> bool {
+        self.inner.auto_follow_tombstones.load(Ordering::SeqCst)
+    }
+
     /// Create a new [`ClientBuilder`].
     pub fn builder() -> ClientBuilder {
         ClientBuilder::new()
@@ -193,11 +193,7 @@ impl HttpError {
 /// How should we behave with respect to retry behavior after an `HttpError`
 /// happened?
 pub(crate) enum RetryKind {
-    Transient {
-        // This is used only for attempts to retry, so on non-wasm32 code (in the `native` module).
-        #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
-        retry_after: Option<Duration>,
-    },
+    Transient { retry_after: Option<Duration> },
 
     Permanent,
 }
@@ -299,6 +295,22 @@ pub enum Error {
     #[error("wrong room state: {0}")]
     WrongRoomState(WrongRoomState),
 
+    /// SC: An error occurred while managing the children of a space.
+    #[error(transparent)]
+    SpaceChild(#[from] crate::room::SpaceChildError),
+
+    /// SC: Encryption couldn't be enabled in a room.
+    #[error(transparent)]
+    EnableEncryption(#[from] crate::room::EnableEncryptionError),
+
+    /// SC: The tombstone of a room couldn't be followed.
+    #[error(transparent)]
+    Tombstone(#[from] crate::room::TombstoneError),
+
+    /// SC: The preview of a URL couldn't be fetched.
+    #[error(transparent)]
+    UrlPreview(#[from] crate::room::UrlPreviewError),
+
     /// The client is in inconsistent state. This happens when we set a room to
     /// a specific type, but then cannot get it in this type.
     #[error("The internal client state is inconsistent.")]
@@ -508,6 +520,9 @@ pub enum NotificationSettingsError {
     /// Unable to save the push rules
     #[error("Unable to save push rules")]
     UnableToSavePushRules,
+    /// SC: Unable to save the Do-Not-Disturb state
+    #[error("Unable to save the Do-Not-Disturb state")]
+    UnableToSaveDnd,
 }
 
 impl From<InsertPushRuleError> for NotificationSettingsError {
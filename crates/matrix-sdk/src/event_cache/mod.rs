@@ -47,7 +47,6 @@ use std::{
     sync::{Arc, OnceLock},
 };
 
-use eyeball::Subscriber;
 use matrix_sdk_base::{
     deserialized_responses::{AmbiguityChange, SyncTimelineEvent, TimelineEvent},
     sync::{JoinedRoomUpdate, LeftRoomUpdate, RoomUpdates, Timeline},
@@ -56,13 +55,13 @@ use matrix_sdk_common::executor::{spawn, JoinHandle};
 use ruma::{
     events::{AnyRoomAccountDataEvent, AnySyncEphemeralRoomEvent},
     serde::Raw,
-    OwnedEventId, OwnedRoomId, RoomId,
+    EventId, OwnedEventId, OwnedRoomId, RoomId,
 };
 use tokio::sync::{
     broadcast::{error::RecvError, Receiver, Sender},
     Mutex, RwLock, RwLockWriteGuard,
 };
-use tracing::{error, info_span, instrument, trace, warn, Instrument as _, Span};
+use tracing::{error, instrument, trace, warn};
 
 use self::{
     pagination::RoomPaginationData,
@@ -122,9 +121,6 @@ pub type Result<T> = std::result::Result<T, EventCacheError>;
 pub struct EventCacheDropHandles {
     /// Task that listens to room updates.
     listen_updates_task: JoinHandle<()>,
-
-    /// Task that listens to updates to the user's ignored list.
-    ignore_user_list_update_task: JoinHandle<()>,
 }
 
 impl Debug for EventCacheDropHandles {
@@ -136,7 +132,6 @@ impl Debug for EventCacheDropHandles {
 impl Drop for EventCacheDropHandles {
     fn drop(&mut self) {
         self.listen_updates_task.abort();
-        self.ignore_user_list_update_task.abort();
     }
 }
 
@@ -185,34 +180,15 @@ impl EventCache {
                 client.subscribe_to_all_room_updates(),
             ));
 
-            let ignore_user_list_update_task = spawn(Self::ignore_user_list_update_task(
-                self.inner.clone(),
-                client.subscribe_to_ignore_user_list_changes(),
-            ));
-
-            Arc::new(EventCacheDropHandles { listen_updates_task, ignore_user_list_update_task })
+            // SC: The rooms aren't cleared when the ignored user list changes anymore:
+            // the timelines filter out the events of the ignored users themselves, and
+            // need the cached events to restore them once the users are unignored.
+            Arc::new(EventCacheDropHandles { listen_updates_task })
         });
 
         Ok(())
     }
 
-    #[instrument(skip_all)]
-    async fn ignore_user_list_update_task(
-        inner: Arc<EventCacheInner>,
-        mut ignore_user_list_stream: Subscriber<Vec<String>>,
-    ) {
-        let span = info_span!(parent: Span::none(), "ignore_user_list_update_task");
-        span.follows_from(Span::current());
-
-        async move {
-            while ignore_user_list_stream.next().await.is_some() {
-                inner.clear_all_rooms().await;
-            }
-        }
-        .instrument(span)
-        .await;
-    }
-
     #[instrument(skip_all)]
     async fn listen_task(
         inner: Arc<EventCacheInner>,
@@ -441,6 +417,24 @@ impl RoomEventCache {
         Ok((events, self.inner.sender.subscribe()))
     }
 
+    /// SC: Get up to `count` of the events that are right before the event
+    /// with the given ID in the cache, the most recent first.
+    ///
+    /// Unlike [`Self::subscribe`], only the returned events are cloned.
+    /// Returns `None` if the event isn't in the cache.
+    pub async fn events_before(
+        &self,
+        event_id: &EventId,
+        count: usize,
+    ) -> Option<Vec<SyncTimelineEvent>> {
+        let events = self.inner.events.read().await;
+        let mut revents = events.revents().map(|(_position, event)| event);
+
+        revents.by_ref().find(|event| event.event_id().as_deref() == Some(event_id))?;
+
+        Some(revents.take(count).cloned().collect())
+    }
+
     /// Return a [`RoomPagination`] API object useful for running
     /// back-pagination queries in the current room.
     pub fn pagination(&self) -> RoomPagination {
@@ -759,13 +753,13 @@ pub enum EventsOrigin {
 mod tests {
     use assert_matches2::assert_matches;
     use futures_util::FutureExt as _;
-    use matrix_sdk_base::sync::JoinedRoomUpdate;
+    use matrix_sdk_base::{deserialized_responses::SyncTimelineEvent, sync::JoinedRoomUpdate};
     use matrix_sdk_test::async_test;
-    use ruma::{room_id, serde::Raw};
+    use ruma::{event_id, room_id, serde::Raw, user_id, EventId};
     use serde_json::json;
 
     use super::{EventCacheError, RoomEventCacheUpdate};
-    use crate::test_utils::logged_in_client;
+    use crate::test_utils::{events::EventFactory, logged_in_client};
 
     #[async_test]
     async fn test_must_explicitly_subscribe() {
@@ -828,4 +822,43 @@ mod tests {
 
         assert!(stream.recv().now_or_never().is_none());
     }
+
+    #[async_test]
+    async fn test_events_before() {
+        let client = logged_in_client(None).await;
+        let room_id = room_id!("!galette:saucisse.bzh");
+        client.base_client().get_or_create_room(room_id, matrix_sdk_base::RoomState::Joined);
+
+        let event_cache = client.event_cache();
+        event_cache.subscribe().unwrap();
+
+        let f = EventFactory::new().room(room_id).sender(user_id!("@a:b.c"));
+        let events = ["$a", "$b", "$c", "$d"]
+            .into_iter()
+            .map(|event_id| {
+                f.text_msg(event_id).event_id(<&EventId>::try_from(event_id).unwrap()).into_sync()
+            })
+            .collect();
+        event_cache.add_initial_events(room_id, events, None).await.unwrap();
+
+        let (room_event_cache, _drop_handles) = event_cache.for_room(room_id).await.unwrap();
+        let room_event_cache = room_event_cache.unwrap();
+
+        let event_ids = |events: Vec<SyncTimelineEvent>| -> Vec<String> {
+            events.iter().filter_map(|event| Some(event.event_id()?.to_string())).collect()
+        };
+
+        // The events before the given one are returned, the most recent first.
+        let before = room_event_cache.events_before(event_id!("$d"), 2).await.unwrap();
+        assert_eq!(event_ids(before), ["$c", "$b"]);
+
+        // There might be fewer events than requested.
+        let before = room_event_cache.events_before(event_id!("$b"), 10).await.unwrap();
+        assert_eq!(event_ids(before), ["$a"]);
+        let before = room_event_cache.events_before(event_id!("$a"), 10).await.unwrap();
+        assert!(before.is_empty());
+
+        // The event isn't in the cache.
+        assert!(room_event_cache.events_before(event_id!("$z"), 10).await.is_none());
+    }
 }
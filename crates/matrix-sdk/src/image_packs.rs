@@ -0,0 +1,254 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: Custom emoji and sticker packs, as described in [MSC2545].
+//!
+//! Packs are read from the `im.ponies.user_emotes` global account data of the
+//! user and from the `im.ponies.room_emotes` state events of the joined rooms.
+//!
+//! [MSC2545]: https://github.com/matrix-org/matrix-spec-proposals/pull/2545
+
+use std::collections::{BTreeMap, HashSet};
+
+use matrix_sdk_base::deserialized_responses::RawAnySyncOrStrippedState;
+use ruma::{OwnedMxcUri, OwnedRoomId};
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::{Client, Result};
+
+/// The type of the global account data event holding the user's own pack.
+pub const USER_EMOTES_EVENT_TYPE: &str = "im.ponies.user_emotes";
+
+/// The type of the state events holding the packs of a room.
+pub const ROOM_EMOTES_EVENT_TYPE: &str = "im.ponies.room_emotes";
+
+/// Where an [`ImagePack`] comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImagePackSource {
+    /// The `im.ponies.user_emotes` account data of the user.
+    User,
+    /// An `im.ponies.room_emotes` state event of a room.
+    Room {
+        /// The room the pack is defined in.
+        room_id: OwnedRoomId,
+        /// The state key of the event defining the pack.
+        state_key: String,
+    },
+}
+
+/// An image of an [`ImagePack`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackImage {
+    /// The shortcode of the image, without the surrounding colons.
+    pub shortcode: String,
+    /// The URI of the image.
+    pub url: OwnedMxcUri,
+    /// A textual description of the image, if any.
+    pub body: Option<String>,
+}
+
+/// A pack of custom emojis or stickers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImagePack {
+    /// Where the pack comes from.
+    pub source: ImagePackSource,
+    /// The display name of the pack, if any.
+    pub display_name: Option<String>,
+    /// The images of the pack, sorted by shortcode.
+    pub images: Vec<PackImage>,
+}
+
+impl Client {
+    /// SC: Get the custom emoji and sticker packs available to the user.
+    ///
+    /// This is the user's own `im.ponies.user_emotes` pack, followed by the
+    /// `im.ponies.room_emotes` packs of the joined rooms, ordered by room ID
+    /// and state key. A shortcode that appears in several packs only belongs
+    /// to the first one, so that every shortcode resolves to a single image.
+    /// Packs left without any image are omitted.
+    ///
+    /// This is read from the local store, so it's up to date as of the last
+    /// sync.
+    pub async fn image_packs(&self) -> Result<Vec<ImagePack>> {
+        let mut packs = Vec::new();
+
+        if let Some(raw) = self.account().account_data_raw(USER_EMOTES_EVENT_TYPE.into()).await? {
+            match raw.deserialize_as::<ImagePackContent>() {
+                Ok(content) => packs.push((ImagePackSource::User, content)),
+                Err(error) => warn!("Ignoring malformed user image pack: {error}"),
+            }
+        }
+
+        let mut rooms = self.joined_rooms();
+        rooms.sort_by(|lhs, rhs| lhs.room_id().cmp(rhs.room_id()));
+
+        for room in rooms {
+            let mut room_packs = Vec::new();
+
+            for raw in room.get_state_events(ROOM_EMOTES_EVENT_TYPE.into()).await? {
+                let RawAnySyncOrStrippedState::Sync(raw) = raw else { continue };
+
+                match raw.deserialize_as::<RoomImagePackEvent>() {
+                    Ok(event) => room_packs.push(event),
+                    Err(error) => warn!(
+                        room_id = ?room.room_id(),
+                        "Ignoring malformed room image pack: {error}"
+                    ),
+                }
+            }
+
+            room_packs.sort_by(|lhs, rhs| lhs.state_key.cmp(&rhs.state_key));
+            packs.extend(room_packs.into_iter().map(|event| {
+                let source = ImagePackSource::Room {
+                    room_id: room.room_id().to_owned(),
+                    state_key: event.state_key,
+                };
+                (source, event.content)
+            }));
+        }
+
+        Ok(collect_image_packs(packs))
+    }
+}
+
+/// An `im.ponies.room_emotes` state event.
+#[derive(Debug, Deserialize)]
+struct RoomImagePackEvent {
+    state_key: String,
+    #[serde(default)]
+    content: ImagePackContent,
+}
+
+/// The content of an `im.ponies.user_emotes` or `im.ponies.room_emotes`
+/// event.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct ImagePackContent {
+    #[serde(default)]
+    images: BTreeMap<String, ImagePackImageContent>,
+    #[serde(default)]
+    pack: Option<ImagePackInfoContent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImagePackImageContent {
+    url: OwnedMxcUri,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImagePackInfoContent {
+    #[serde(default)]
+    display_name: Option<String>,
+}
+
+/// Build the list of packs from the parsed contents.
+///
+/// Packs are ordered with the user's own pack first, then the room packs in
+/// the order they are given. A shortcode that appears in several packs only
+/// belongs to the first one, so that every shortcode resolves to a single
+/// image; packs left without any image are dropped.
+pub(crate) fn collect_image_packs(
+    packs: impl IntoIterator<Item = (ImagePackSource, ImagePackContent)>,
+) -> Vec<ImagePack> {
+    let mut seen_shortcodes = HashSet::new();
+
+    packs
+        .into_iter()
+        .filter_map(|(source, content)| {
+            let images: Vec<_> = content
+                .images
+                .into_iter()
+                .filter(|(shortcode, _)| seen_shortcodes.insert(shortcode.clone()))
+                .map(|(shortcode, image)| PackImage { shortcode, url: image.url, body: image.body })
+                .collect();
+
+            (!images.is_empty()).then(|| ImagePack {
+                source,
+                display_name: content.pack.and_then(|pack| pack.display_name),
+                images,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ruma::{mxc_uri, owned_room_id};
+    use serde_json::json;
+
+    use super::{collect_image_packs, ImagePackContent, ImagePackSource};
+
+    fn content(value: serde_json::Value) -> ImagePackContent {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_duplicate_shortcodes_belong_to_the_first_pack() {
+        let room_source = ImagePackSource::Room {
+            room_id: owned_room_id!("!room:example.org"),
+            state_key: "".to_owned(),
+        };
+
+        let packs = collect_image_packs([
+            (
+                ImagePackSource::User,
+                content(json!({
+                    "images": {
+                        "wave": { "url": "mxc://example.org/user-wave" },
+                    },
+                    "pack": { "display_name": "Mine" },
+                })),
+            ),
+            (
+                room_source.clone(),
+                content(json!({
+                    "images": {
+                        "wave": { "url": "mxc://example.org/room-wave" },
+                        "cat": { "url": "mxc://example.org/cat", "body": "A cat" },
+                    },
+                })),
+            ),
+        ]);
+
+        assert_eq!(packs.len(), 2);
+        assert_eq!(packs[0].source, ImagePackSource::User);
+        assert_eq!(packs[0].display_name.as_deref(), Some("Mine"));
+        assert_eq!(packs[0].images.len(), 1);
+        assert_eq!(packs[0].images[0].url, mxc_uri!("mxc://example.org/user-wave"));
+
+        assert_eq!(packs[1].source, room_source);
+        assert_eq!(packs[1].images.len(), 1);
+        assert_eq!(packs[1].images[0].shortcode, "cat");
+        assert_eq!(packs[1].images[0].body.as_deref(), Some("A cat"));
+    }
+
+    #[test]
+    fn test_empty_packs_are_dropped() {
+        let packs = collect_image_packs([
+            (ImagePackSource::User, content(json!({}))),
+            (
+                ImagePackSource::Room {
+                    room_id: owned_room_id!("!room:example.org"),
+                    state_key: "".to_owned(),
+                },
+                content(json!({ "images": { "wave": { "url": "mxc://example.org/wave" } } })),
+            ),
+        ]);
+
+        assert_eq!(packs.len(), 1);
+        assert_eq!(packs[0].images[0].shortcode, "wave");
+    }
+}
@@ -21,11 +21,11 @@ pub use bytes;
 #[cfg(feature = "e2e-encryption")]
 pub use matrix_sdk_base::crypto;
 pub use matrix_sdk_base::{
-    deserialized_responses,
+    avatar_initials, deserialized_responses,
     store::{ComposerDraft, DynStateStore, MemoryStore, StateStoreExt},
-    DisplayName, Room as BaseRoom, RoomCreateWithCreatorEventContent, RoomHero, RoomInfo,
-    RoomMember as BaseRoomMember, RoomMemberships, RoomState, SessionMeta, StateChanges,
-    StateStore, StoreError,
+    user_color_index, DisplayName, Room as BaseRoom, RoomCreateWithCreatorEventContent, RoomHero,
+    RoomInfo, RoomMember as BaseRoomMember, RoomMemberships, RoomNameContext, RoomState,
+    ScUnreadCountSource, SessionMeta, StateChanges, StateStore, StoreError,
 };
 pub use matrix_sdk_common::*;
 pub use reqwest;
@@ -42,6 +42,7 @@ mod error;
 pub mod event_cache;
 pub mod event_handler;
 mod http_client;
+pub mod image_packs;
 pub mod matrix_auth;
 pub mod media;
 pub mod notification_settings;
@@ -67,7 +68,8 @@ pub mod widget;
 pub use account::Account;
 pub use authentication::{AuthApi, AuthSession, SessionTokens};
 pub use client::{
-    sanitize_server_name, Client, ClientBuildError, ClientBuilder, LoopCtrl, SessionChange,
+    sanitize_server_name, Client, ClientBuildError, ClientBuilder, JoinByLinkError, LoopCtrl,
+    SessionChange,
 };
 #[cfg(feature = "image-proc")]
 pub use error::ImageError;
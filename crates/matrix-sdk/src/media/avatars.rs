@@ -0,0 +1,489 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: A bounded cache for the thumbnails of the avatars, see
+//! [`Media::get_avatar`](super::Media::get_avatar).
+
+use std::time::Duration;
+
+use matrix_sdk_base::media::{MediaFormat, MediaRequest, MediaThumbnailSize};
+use ruma::{
+    api::client::{error::ErrorKind, media::get_content_thumbnail::v3::Method},
+    events::room::MediaSource,
+    MilliSecondsSinceUnixEpoch, MxcUri, OwnedMxcUri, UInt,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+use crate::{deduplicating_handler::DeduplicatingHandler, Client, Error, Result};
+
+/// The maximum number of avatars kept in the cache, the least recently used
+/// ones are removed first.
+const MAX_CACHED_AVATARS: usize = 1000;
+
+/// The age after which a cached avatar is downloaded again, to notice when it
+/// was deleted or when access to it was revoked.
+const AVATAR_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The key of the index of the cached avatars in the custom values of the
+/// state store.
+const AVATAR_INDEX_KEY: &[u8] = b"sc_avatar_cache_index";
+
+/// SC: The size of the thumbnail of an avatar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum AvatarSize {
+    /// 48x48, e.g. for the room list or the timeline.
+    Small,
+    /// 96x96, e.g. for the room details.
+    Medium,
+    /// 384x384, e.g. for the user profile.
+    Large,
+}
+
+impl AvatarSize {
+    fn pixels(self) -> UInt {
+        match self {
+            Self::Small => UInt::new_saturating(48),
+            Self::Medium => UInt::new_saturating(96),
+            Self::Large => UInt::new_saturating(384),
+        }
+    }
+
+    /// The request of the thumbnail of the avatar with the given URI at this
+    /// size.
+    fn media_request(self, uri: &MxcUri) -> MediaRequest {
+        MediaRequest {
+            source: MediaSource::Plain(uri.to_owned()),
+            format: MediaFormat::Thumbnail(MediaThumbnailSize {
+                method: Method::Crop,
+                width: self.pixels(),
+                height: self.pixels(),
+            }),
+        }
+    }
+}
+
+/// SC: Errors that can happen when getting an avatar.
+#[derive(Debug, Error)]
+pub enum AvatarError {
+    /// The media doesn't exist, or was deleted.
+    #[error("the avatar was not found")]
+    NotFound,
+
+    /// The homeserver refused to return the media.
+    #[error("access to the avatar is forbidden")]
+    Forbidden,
+
+    /// Another error happened while getting the avatar.
+    #[error(transparent)]
+    Sdk(Error),
+}
+
+impl From<Error> for AvatarError {
+    fn from(error: Error) -> Self {
+        match error.client_api_error_kind() {
+            Some(ErrorKind::NotFound) => Self::NotFound,
+            Some(ErrorKind::Forbidden { .. }) => Self::Forbidden,
+            _ => Self::Sdk(error),
+        }
+    }
+}
+
+/// Whether the error means that the avatar can't be downloaded anymore.
+fn is_unavailable(error: &Error) -> bool {
+    matches!(error.client_api_error_kind(), Some(ErrorKind::NotFound | ErrorKind::Forbidden { .. }))
+}
+
+/// An avatar in the cache.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct CachedAvatar {
+    uri: OwnedMxcUri,
+    size: AvatarSize,
+    /// When the avatar was downloaded.
+    fetched_at: MilliSecondsSinceUnixEpoch,
+}
+
+impl CachedAvatar {
+    fn is(&self, uri: &MxcUri, size: AvatarSize) -> bool {
+        self.uri == uri && self.size == size
+    }
+
+    fn is_stale(&self) -> bool {
+        let now = u64::from(MilliSecondsSinceUnixEpoch::now().get());
+        let age = now.saturating_sub(self.fetched_at.get().into());
+        age > AVATAR_MAX_AGE.as_millis() as u64
+    }
+}
+
+/// The client-wide cache of avatars.
+///
+/// The thumbnails are kept in the media cache of the state store, so they are
+/// encrypted like the rest of the store. The cache only keeps an index of
+/// them, to remove the least recently used ones and to download the old ones
+/// again.
+pub(crate) struct AvatarCache {
+    capacity: usize,
+    /// The cached avatars, from the least to the most recently used, or `None`
+    /// if the index wasn't loaded from the store yet.
+    ///
+    /// The order is only persisted when an avatar is added or removed, to
+    /// avoid writing to the store every time an avatar is shown.
+    index: Mutex<Option<Vec<CachedAvatar>>>,
+    /// Handler making sure that concurrent requests for the same avatar only
+    /// download it once.
+    downloads: DeduplicatingHandler<(OwnedMxcUri, AvatarSize)>,
+}
+
+impl Default for AvatarCache {
+    fn default() -> Self {
+        Self::with_capacity(MAX_CACHED_AVATARS)
+    }
+}
+
+impl AvatarCache {
+    fn with_capacity(capacity: usize) -> Self {
+        Self { capacity, index: Default::default(), downloads: Default::default() }
+    }
+
+    /// Get the thumbnail of the avatar with the given URI, from the cache if
+    /// possible.
+    pub(crate) async fn get(
+        &self,
+        client: &Client,
+        uri: &MxcUri,
+        size: AvatarSize,
+    ) -> Result<Vec<u8>, AvatarError> {
+        let request = size.media_request(uri);
+
+        let cached = match self.touch(client, uri, size).await? {
+            Some(avatar) => client
+                .store()
+                .get_media_content(&request)
+                .await
+                .map_err(Error::from)?
+                .map(|data| (data, avatar.is_stale())),
+            None => None,
+        };
+
+        let stale_data = match cached {
+            Some((data, false)) => return Ok(data),
+            Some((data, true)) => Some(data),
+            None => None,
+        };
+
+        let mut downloaded = None;
+        let result = self
+            .downloads
+            .run((uri.to_owned(), size), async {
+                match client.media().get_media_content(&request, false).await {
+                    Ok(data) => {
+                        self.insert(client, uri, size, &request, data.clone()).await?;
+                        downloaded = Some(data);
+                        Ok(())
+                    }
+                    Err(error) => {
+                        if is_unavailable(&error) {
+                            self.remove(client, uri, size, &request).await?;
+                        }
+                        Err(error)
+                    }
+                }
+            })
+            .await;
+
+        match result {
+            Ok(()) => match downloaded {
+                Some(data) => Ok(data),
+                // A concurrent request downloaded the avatar.
+                None => {
+                    match client.store().get_media_content(&request).await.map_err(Error::from)? {
+                        Some(data) => Ok(data),
+                        None => Ok(client.media().get_media_content(&request, false).await?),
+                    }
+                }
+            },
+            Err(error) => match (AvatarError::from(error), stale_data) {
+                // The homeserver couldn't be reached, the old avatar is better than nothing.
+                (AvatarError::Sdk(error), Some(data)) => {
+                    debug!("Failed to download a stale avatar again: {error}");
+                    Ok(data)
+                }
+                (error, _) => Err(error),
+            },
+        }
+    }
+
+    /// Mark the avatar as the most recently used, and return it if it is in
+    /// the cache.
+    async fn touch(
+        &self,
+        client: &Client,
+        uri: &MxcUri,
+        size: AvatarSize,
+    ) -> Result<Option<CachedAvatar>> {
+        let mut guard = self.index.lock().await;
+        let index = load_index(client, &mut guard).await?;
+
+        let Some(position) = index.iter().position(|avatar| avatar.is(uri, size)) else {
+            return Ok(None);
+        };
+        let avatar = index.remove(position);
+        index.push(avatar.clone());
+
+        Ok(Some(avatar))
+    }
+
+    /// Add the avatar to the cache, and remove the least recently used ones
+    /// if the cache is full.
+    async fn insert(
+        &self,
+        client: &Client,
+        uri: &MxcUri,
+        size: AvatarSize,
+        request: &MediaRequest,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let mut guard = self.index.lock().await;
+        let index = load_index(client, &mut guard).await?;
+
+        client.store().add_media_content(request, data).await?;
+
+        index.retain(|avatar| !avatar.is(uri, size));
+        index.push(CachedAvatar {
+            uri: uri.to_owned(),
+            size,
+            fetched_at: MilliSecondsSinceUnixEpoch::now(),
+        });
+
+        let excess = index.len().saturating_sub(self.capacity);
+        for evicted in index.drain(..excess).collect::<Vec<_>>() {
+            let request = evicted.size.media_request(&evicted.uri);
+            if let Err(error) = client.store().remove_media_content(&request).await {
+                warn!("Failed to remove an avatar from the cache: {error}");
+            }
+        }
+
+        save_index(client, index).await
+    }
+
+    /// Remove the avatar from the cache.
+    async fn remove(
+        &self,
+        client: &Client,
+        uri: &MxcUri,
+        size: AvatarSize,
+        request: &MediaRequest,
+    ) -> Result<()> {
+        let mut guard = self.index.lock().await;
+        let index = load_index(client, &mut guard).await?;
+
+        client.store().remove_media_content(request).await?;
+
+        let len = index.len();
+        index.retain(|avatar| !avatar.is(uri, size));
+        if index.len() != len {
+            save_index(client, index).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Get the index of the cached avatars, loading it from the store if needed.
+async fn load_index<'a>(
+    client: &Client,
+    index: &'a mut Option<Vec<CachedAvatar>>,
+) -> Result<&'a mut Vec<CachedAvatar>> {
+    if index.is_none() {
+        let loaded = match client.store().get_custom_value(AVATAR_INDEX_KEY).await? {
+            Some(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|error| {
+                warn!("Failed to deserialize the index of the cached avatars: {error}");
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        *index = Some(loaded);
+    }
+
+    Ok(index.as_mut().expect("the index was just loaded"))
+}
+
+async fn save_index(client: &Client, index: &[CachedAvatar]) -> Result<()> {
+    let bytes = serde_json::to_vec(index)?;
+    client.store().set_custom_value_no_read(AVATAR_INDEX_KEY, bytes).await?;
+    Ok(())
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::time::Duration;
+
+    use assert_matches2::assert_matches;
+    use matrix_sdk_test::async_test;
+    use ruma::{mxc_uri, uint, MilliSecondsSinceUnixEpoch};
+    use serde_json::json;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::{AvatarCache, AvatarError, AvatarSize, CachedAvatar};
+    use crate::test_utils::logged_in_client_with_server;
+
+    async fn mock_avatar(server: &MockServer, media_id: &str, response: ResponseTemplate) {
+        Mock::given(method("GET"))
+            .and(path(format!("/_matrix/media/r0/thumbnail/localhost/{media_id}")))
+            .respond_with(response)
+            .mount(server)
+            .await;
+    }
+
+    fn avatar_response(data: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_bytes(data.as_bytes().to_vec())
+    }
+
+    #[async_test]
+    async fn test_concurrent_requests_download_the_avatar_once() {
+        let (client, server) = logged_in_client_with_server().await;
+        let cache = AvatarCache::default();
+        let uri = mxc_uri!("mxc://localhost/alice");
+
+        Mock::given(method("GET"))
+            .and(path("/_matrix/media/r0/thumbnail/localhost/alice"))
+            .respond_with(avatar_response("alice").set_delay(Duration::from_millis(100)))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let (first, second) = tokio::join!(
+            cache.get(&client, uri, AvatarSize::Small),
+            cache.get(&client, uri, AvatarSize::Small),
+        );
+        assert_eq!(first.unwrap(), b"alice");
+        assert_eq!(second.unwrap(), b"alice");
+
+        // Later requests are served from the cache.
+        assert_eq!(cache.get(&client, uri, AvatarSize::Small).await.unwrap(), b"alice");
+    }
+
+    #[async_test]
+    async fn test_least_recently_used_avatar_is_evicted() {
+        let (client, server) = logged_in_client_with_server().await;
+        let cache = AvatarCache::with_capacity(2);
+        let alice = mxc_uri!("mxc://localhost/alice");
+        let bob = mxc_uri!("mxc://localhost/bob");
+        let carol = mxc_uri!("mxc://localhost/carol");
+
+        for (media_id, downloads) in [("alice", 1), ("bob", 2), ("carol", 1)] {
+            Mock::given(method("GET"))
+                .and(path(format!("/_matrix/media/r0/thumbnail/localhost/{media_id}")))
+                .respond_with(avatar_response(media_id))
+                .expect(downloads)
+                .mount(&server)
+                .await;
+        }
+
+        cache.get(&client, alice, AvatarSize::Small).await.unwrap();
+        cache.get(&client, bob, AvatarSize::Small).await.unwrap();
+        // Alice's avatar is now more recently used than Bob's.
+        cache.get(&client, alice, AvatarSize::Small).await.unwrap();
+        cache.get(&client, carol, AvatarSize::Small).await.unwrap();
+
+        let bob_request = AvatarSize::Small.media_request(bob);
+        assert!(client.store().get_media_content(&bob_request).await.unwrap().is_none());
+
+        // Alice's avatar is still cached, but Bob's must be downloaded again.
+        assert_eq!(cache.get(&client, alice, AvatarSize::Small).await.unwrap(), b"alice");
+        assert_eq!(cache.get(&client, bob, AvatarSize::Small).await.unwrap(), b"bob");
+    }
+
+    #[async_test]
+    async fn test_avatar_errors() {
+        let (client, server) = logged_in_client_with_server().await;
+        let cache = AvatarCache::default();
+
+        mock_avatar(
+            &server,
+            "deleted",
+            ResponseTemplate::new(404)
+                .set_body_json(json!({ "errcode": "M_NOT_FOUND", "error": "Not found" })),
+        )
+        .await;
+        mock_avatar(
+            &server,
+            "quarantined",
+            ResponseTemplate::new(403)
+                .set_body_json(json!({ "errcode": "M_FORBIDDEN", "error": "Forbidden" })),
+        )
+        .await;
+        mock_avatar(
+            &server,
+            "broken",
+            ResponseTemplate::new(500)
+                .set_body_json(json!({ "errcode": "M_UNKNOWN", "error": "Broken" })),
+        )
+        .await;
+
+        let result =
+            cache.get(&client, mxc_uri!("mxc://localhost/deleted"), AvatarSize::Small).await;
+        assert_matches!(result, Err(AvatarError::NotFound));
+        let result =
+            cache.get(&client, mxc_uri!("mxc://localhost/quarantined"), AvatarSize::Small).await;
+        assert_matches!(result, Err(AvatarError::Forbidden));
+        let result =
+            cache.get(&client, mxc_uri!("mxc://localhost/broken"), AvatarSize::Small).await;
+        assert_matches!(result, Err(AvatarError::Sdk(_)));
+    }
+
+    #[async_test]
+    async fn test_stale_avatar_is_downloaded_again() {
+        let (client, server) = logged_in_client_with_server().await;
+        let cache = AvatarCache::default();
+        let uri = mxc_uri!("mxc://localhost/alice");
+        let request = AvatarSize::Small.media_request(uri);
+
+        client.store().add_media_content(&request, b"old".to_vec()).await.unwrap();
+        *cache.index.lock().await = Some(vec![CachedAvatar {
+            uri: uri.to_owned(),
+            size: AvatarSize::Small,
+            fetched_at: MilliSecondsSinceUnixEpoch(uint!(0)),
+        }]);
+
+        // If the homeserver can't be reached, the stale avatar is returned.
+        Mock::given(method("GET"))
+            .and(path("/_matrix/media/r0/thumbnail/localhost/alice"))
+            .respond_with(ResponseTemplate::new(502))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        assert_eq!(cache.get(&client, uri, AvatarSize::Small).await.unwrap(), b"old");
+
+        // Once the avatar is deleted, it is removed from the cache.
+        mock_avatar(
+            &server,
+            "alice",
+            ResponseTemplate::new(404)
+                .set_body_json(json!({ "errcode": "M_NOT_FOUND", "error": "Not found" })),
+        )
+        .await;
+        let result = cache.get(&client, uri, AvatarSize::Small).await;
+        assert_matches!(result, Err(AvatarError::NotFound));
+        assert!(client.store().get_media_content(&request).await.unwrap().is_none());
+        assert!(cache.index.lock().await.as_ref().unwrap().is_empty());
+    }
+}
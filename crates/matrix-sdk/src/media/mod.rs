@@ -43,12 +43,16 @@ use tempfile::{Builder as TempFileBuilder, NamedTempFile, TempDir};
 #[cfg(not(target_arch = "wasm32"))]
 use tokio::{fs::File as TokioFile, io::AsyncWriteExt};
 
+pub(crate) use self::avatars::AvatarCache;
+pub use self::avatars::{AvatarError, AvatarSize};
 use crate::{
     attachment::{AttachmentConfig, AttachmentInfo, Thumbnail},
     futures::SendRequest,
     Client, Result, TransmissionProgress,
 };
 
+mod avatars;
+
 /// A conservative upload speed of 1Mbps
 const DEFAULT_UPLOAD_SPEED: u64 = 125_000;
 /// 5 min minimal upload request timeout, used to clamp the request timeout.
@@ -332,6 +336,19 @@ impl Media {
         Ok(self.client.store().remove_media_content_for_uri(uri).await?)
     }
 
+    /// SC: Get the thumbnail of the avatar with the given URI, at the given
+    /// size.
+    ///
+    /// The thumbnails are kept in a bounded cache in the media cache of the
+    /// store, the least recently used ones being removed first. Concurrent
+    /// requests for the same avatar only download it once, and the cached
+    /// avatars are downloaded again after a while, to notice when they are
+    /// deleted. If that fails for another reason, e.g. because the homeserver
+    /// can't be reached, the cached avatar is returned.
+    pub async fn get_avatar(&self, uri: &MxcUri, size: AvatarSize) -> Result<Vec<u8>, AvatarError> {
+        self.client.inner.avatar_cache.get(&self.client, uri, size).await
+    }
+
     /// Get the file of the given media event content.
     ///
     /// If the content is encrypted and encryption is enabled, the content will
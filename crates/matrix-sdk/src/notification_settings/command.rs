@@ -19,7 +19,10 @@ pub(crate) enum Command {
     /// Set a new `Override` push rule matching a `RoomId`
     SetOverridePushRule { scope: RuleScope, rule_id: String, room_id: OwnedRoomId, notify: bool },
     /// Set a new push rule for a keyword.
-    SetKeywordPushRule { scope: RuleScope, keyword: String },
+    ///
+    /// SC: With `highlight`, the messages matching the keyword are also
+    /// highlighted.
+    SetKeywordPushRule { scope: RuleScope, keyword: String, highlight: bool },
     /// Set whether a push rule is enabled
     SetPushRuleEnabled { scope: RuleScope, kind: RuleKind, rule_id: String, enabled: bool },
     /// Delete a push rule
@@ -36,6 +39,16 @@ fn get_notify_actions(notify: bool) -> Vec<Action> {
     }
 }
 
+/// SC: Keywords notify, and are highlighted on request, like the server
+/// default rule for the user name.
+fn get_keyword_actions(highlight: bool) -> Vec<Action> {
+    let mut actions = get_notify_actions(true);
+    if highlight {
+        actions.push(Action::SetTweak(Tweak::Highlight(true)));
+    }
+    actions
+}
+
 impl Command {
     /// Tries to create a push rule corresponding to this command
     pub(crate) fn to_push_rule(&self) -> Result<NewPushRule, NotificationSettingsError> {
@@ -59,12 +72,12 @@ impl Command {
                 Ok(NewPushRule::Override(new_rule))
             }
 
-            Self::SetKeywordPushRule { scope: _, keyword } => {
+            Self::SetKeywordPushRule { scope: _, keyword, highlight } => {
                 // `Content` push rule matching this keyword
                 let new_rule = NewPatternedPushRule::new(
                     keyword.clone(),
                     keyword.clone(),
-                    get_notify_actions(true),
+                    get_keyword_actions(*highlight),
                 );
                 Ok(NewPushRule::Content(new_rule))
             }
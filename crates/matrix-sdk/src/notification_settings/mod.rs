@@ -2,17 +2,20 @@
 
 use std::sync::Arc;
 
-use indexmap::IndexSet;
+use async_stream::stream;
+use futures_core::Stream;
+use indexmap::{IndexMap, IndexSet};
 use ruma::{
     api::client::push::{
         delete_pushrule, set_pushrule, set_pushrule_actions, set_pushrule_enabled,
     },
-    events::push_rules::PushRulesEvent,
+    events::{macros::EventContent, push_rules::PushRulesEvent, GlobalAccountDataEvent},
     push::{Action, PredefinedUnderrideRuleId, RuleKind, Ruleset, Tweak},
     RoomId,
 };
+use serde::{Deserialize, Serialize};
 use tokio::sync::{
-    broadcast::{self, Receiver},
+    broadcast::{self, error::RecvError, Receiver},
     RwLock,
 };
 use tracing::{debug, error};
@@ -77,6 +80,24 @@ impl From<bool> for IsOneToOne {
     }
 }
 
+/// SC: The content of a global account data event enabling Do-Not-Disturb.
+///
+/// While Do-Not-Disturb is enabled, notifications are still shown but never
+/// noisy, whatever the push rules say.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, EventContent)]
+#[ruma_event(type = "chat.schildi.dnd", kind = GlobalAccountData)]
+pub struct ScDndEventContent {
+    /// Whether Do-Not-Disturb is enabled.
+    pub enabled: bool,
+}
+
+impl ScDndEventContent {
+    /// Create a new `ScDndEventContent` with the given state.
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+}
+
 /// A high-level API to manage the client owner's push notification settings.
 #[derive(Debug, Clone)]
 pub struct NotificationSettings {
@@ -86,6 +107,8 @@ pub struct NotificationSettings {
     rules: Arc<RwLock<Rules>>,
     /// Drop guard of event handler for push rules event.
     _push_rules_event_handler_guard: Arc<EventHandlerDropGuard>,
+    /// SC: Drop guard of event handler for the Do-Not-Disturb event.
+    _dnd_event_handler_guard: Arc<EventHandlerDropGuard>,
     changes_sender: broadcast::Sender<()>,
 }
 
@@ -112,7 +135,23 @@ impl NotificationSettings {
         let _push_rules_event_handler_guard =
             client.event_handler_drop_guard(push_rules_event_handler_handle).into();
 
-        Self { client, rules, _push_rules_event_handler_guard, changes_sender }
+        // SC: Listen for the Do-Not-Disturb event
+        let dnd_event_handler_handle = client.add_event_handler({
+            let changes_sender = changes_sender.clone();
+            move |_: GlobalAccountDataEvent<ScDndEventContent>| async move {
+                let _ = changes_sender.send(());
+            }
+        });
+        let _dnd_event_handler_guard =
+            client.event_handler_drop_guard(dnd_event_handler_handle).into();
+
+        Self {
+            client,
+            rules,
+            _push_rules_event_handler_guard,
+            _dnd_event_handler_guard,
+            changes_sender,
+        }
     }
 
     /// Subscribe to changes in the `NotificationSettings`.
@@ -391,7 +430,7 @@ impl NotificationSettings {
 
         if existing_rules.is_empty() {
             // Create a rule.
-            rule_commands.insert_keyword_rule(keyword)?;
+            rule_commands.insert_keyword_rule(keyword, false)?;
         } else {
             if existing_rules.iter().any(|r| r.enabled) {
                 // Nothing to do.
@@ -410,6 +449,161 @@ impl NotificationSettings {
         Ok(())
     }
 
+    /// SC: Replace all the keywords which have enabled rules by `keywords`.
+    ///
+    /// Like the server, which matches keywords case-insensitively on the word
+    /// boundaries of the body of the messages, keywords are trimmed, and
+    /// keywords that only differ by their case are considered the same. The
+    /// messages matching a keyword notify with a sound.
+    ///
+    /// # Arguments
+    ///
+    /// * `keywords` - The keywords to match.
+    ///
+    /// * `highlight` - Whether the messages matching the keywords should also
+    ///   be highlighted. The highlight tweak is then added to the existing
+    ///   rules too, otherwise their actions are kept.
+    pub async fn set_keywords(
+        &self,
+        keywords: Vec<String>,
+        highlight: bool,
+    ) -> Result<(), NotificationSettingsError> {
+        let rules = self.rules.read().await.clone();
+
+        let mut rule_commands = RuleCommands::new(rules.clone().ruleset);
+
+        // Deduplicate the keywords as the server matches them, keeping the first
+        // spelling.
+        let mut wanted_keywords = IndexMap::new();
+        for keyword in keywords {
+            let keyword = keyword.trim();
+            if !keyword.is_empty() {
+                wanted_keywords.entry(keyword.to_lowercase()).or_insert_with(|| keyword.to_owned());
+            }
+        }
+
+        // Remove the rules for the keywords that aren't wanted anymore.
+        for rule in rules.all_keyword_rules() {
+            if !wanted_keywords.contains_key(&rule.pattern.to_lowercase()) {
+                rule_commands.delete_rule(RuleKind::Content, rule.rule_id.clone())?;
+            }
+        }
+
+        // Create or enable the rules for the wanted keywords.
+        for keyword in wanted_keywords.into_values() {
+            let existing_rules = rules.keyword_rules_ignoring_case(&keyword);
+
+            let Some(rule) =
+                existing_rules.iter().find(|r| r.enabled).or_else(|| existing_rules.first())
+            else {
+                rule_commands.insert_keyword_rule(keyword, highlight)?;
+                continue;
+            };
+
+            if !rule.enabled {
+                rule_commands.set_rule_enabled(RuleKind::Content, &rule.rule_id, true)?;
+            }
+
+            if highlight && !rule.actions.iter().any(Action::is_highlight) {
+                let mut actions = rule.actions.clone();
+                actions.push(Action::SetTweak(Tweak::Highlight(true)));
+                rule_commands.set_rule_actions(RuleKind::Content, &rule.rule_id, actions)?;
+            }
+        }
+
+        if rule_commands.commands.is_empty() {
+            // Nothing to do.
+            return Ok(());
+        }
+
+        self.run_server_commands(&rule_commands).await?;
+
+        let rules = &mut *self.rules.write().await;
+        rules.apply(rule_commands);
+
+        Ok(())
+    }
+
+    /// SC: Get a stream of the keywords which have enabled rules.
+    ///
+    /// The current keywords are yielded first. Then, new keywords are yielded
+    /// every time they change, see [`Self::subscribe_to_changes`].
+    pub fn subscribe_to_keywords(&self) -> impl Stream<Item = IndexSet<String>> {
+        let settings = self.clone();
+        let mut changes = self.subscribe_to_changes();
+
+        stream! {
+            let mut keywords = settings.enabled_keywords().await;
+            yield keywords.clone();
+
+            loop {
+                if let Err(RecvError::Closed) = changes.recv().await {
+                    break;
+                }
+
+                let new_keywords = settings.enabled_keywords().await;
+                if new_keywords != keywords {
+                    keywords = new_keywords;
+                    yield keywords.clone();
+                }
+            }
+        }
+    }
+
+    /// SC: Get whether Do-Not-Disturb is enabled.
+    ///
+    /// This is read from the global account data of the user, so it's shared
+    /// with their other sessions.
+    pub async fn is_dnd_enabled(&self) -> bool {
+        self.client.account().is_dnd_enabled().await.unwrap_or_else(|error| {
+            error!("Unable to load the Do-Not-Disturb state: {error}");
+            false
+        })
+    }
+
+    /// SC: Enable or disable Do-Not-Disturb.
+    ///
+    /// While it's enabled, notifications are never noisy, without changing the
+    /// push rules. The new state is stored in the global account data of the
+    /// user, and is reflected locally after the next sync, which also notifies
+    /// [`Self::subscribe_to_changes`].
+    pub async fn set_dnd(&self, enabled: bool) -> Result<(), NotificationSettingsError> {
+        self.client.account().set_account_data(ScDndEventContent::new(enabled)).await.map_err(
+            |error| {
+                error!("Unable to save the Do-Not-Disturb event: {error}");
+                NotificationSettingsError::UnableToSaveDnd
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// SC: Get a stream of whether Do-Not-Disturb is enabled.
+    ///
+    /// The current state is yielded first. Then, the new state is yielded every
+    /// time it changes.
+    pub fn subscribe_to_dnd(&self) -> impl Stream<Item = bool> {
+        let settings = self.clone();
+        let mut changes = self.subscribe_to_changes();
+
+        stream! {
+            let mut enabled = settings.is_dnd_enabled().await;
+            yield enabled;
+
+            loop {
+                if let Err(RecvError::Closed) = changes.recv().await {
+                    break;
+                }
+
+                let new_enabled = settings.is_dnd_enabled().await;
+                if new_enabled != enabled {
+                    enabled = new_enabled;
+                    yield enabled;
+                }
+            }
+        }
+    }
+
     /// Remove the rules for the given keyword.
     ///
     /// # Arguments
@@ -473,7 +667,7 @@ impl NotificationSettings {
                         NotificationSettingsError::UnableToAddPushRule
                     })?;
                 }
-                Command::SetKeywordPushRule { scope, keyword: _ } => {
+                Command::SetKeywordPushRule { scope, .. } => {
                     let push_rule = command.to_push_rule()?;
                     let request = set_pushrule::v3::Request::new(scope.clone(), push_rule);
                     self.client
@@ -520,6 +714,8 @@ mod tests {
     };
 
     use assert_matches::assert_matches;
+    use futures_util::pin_mut;
+    use indexmap::IndexSet;
     use matrix_sdk_test::{
         async_test,
         notification_settings::{build_ruleset, get_server_default_ruleset},
@@ -599,6 +795,52 @@ mod tests {
         assert_pending!(stream);
     }
 
+    #[async_test]
+    async fn subscribe_to_keywords() {
+        let server = MockServer::start().await;
+        let client = logged_in_client(Some(server.uri())).await;
+
+        let mut ruleset = get_server_default_ruleset();
+        ruleset
+            .insert(
+                NewPushRule::Content(NewPatternedPushRule::new(
+                    "banana".to_owned(),
+                    "banana".to_owned(),
+                    vec![],
+                )),
+                None,
+                None,
+            )
+            .unwrap();
+        let settings = NotificationSettings::new(client.clone(), ruleset);
+
+        Mock::given(method("GET"))
+            .and(path("/_matrix/client/r0/sync"))
+            .and(header("authorization", "Bearer 1234"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "next_batch": "1234",
+                "account_data": {
+                    "events": [*test_json::PUSH_RULES]
+                }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let stream = settings.subscribe_to_keywords();
+        pin_mut!(stream);
+
+        // The current keywords are yielded first.
+        assert_next_eq!(stream, IndexSet::from(["banana".to_owned()]));
+        assert_pending!(stream);
+
+        // The push rules from the sync don't have any keyword rule.
+        client.sync_once(SyncSettings::default()).await.unwrap();
+
+        assert_next_eq!(stream, IndexSet::new());
+        assert_pending!(stream);
+    }
+
     #[async_test]
     async fn test_get_custom_rules_for_room() {
         let server = MockServer::start().await;
@@ -1462,6 +1704,101 @@ mod tests {
         assert!(second_rule_enabled);
     }
 
+    #[async_test]
+    async fn set_keywords() {
+        let server = MockServer::start().await;
+        let client = logged_in_client(Some(server.uri())).await;
+
+        let mut ruleset = get_server_default_ruleset();
+        for (rule_id, pattern) in [("banana", "banana"), ("apple", "Apple")] {
+            ruleset
+                .insert(
+                    NewPushRule::Content(NewPatternedPushRule::new(
+                        rule_id.to_owned(),
+                        pattern.to_owned(),
+                        vec![],
+                    )),
+                    None,
+                    None,
+                )
+                .unwrap();
+        }
+        ruleset.set_enabled(RuleKind::Content, "apple", false).unwrap();
+
+        let settings = NotificationSettings::new(client, ruleset);
+
+        Mock::given(method("DELETE"))
+            .and(path("/_matrix/client/r0/pushrules/global/content/banana"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/_matrix/client/r0/pushrules/global/content/apple/enabled"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/_matrix/client/r0/pushrules/global/content/apple/actions"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/_matrix/client/r0/pushrules/global/content/cherry"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // Keywords are trimmed and deduplicated regardless of their case.
+        settings
+            .set_keywords(
+                vec![" apple ".to_owned(), "cherry".to_owned(), "CHERRY".to_owned(), "".to_owned()],
+                true,
+            )
+            .await
+            .unwrap();
+
+        let keywords = settings.enabled_keywords().await;
+        assert_eq!(keywords.len(), 2);
+        assert!(keywords.get("Apple").is_some());
+        assert!(keywords.get("cherry").is_some());
+
+        // The new and existing keyword rules highlight, as requested.
+        let rules = settings.rules.read().await;
+        for rule_id in ["apple", "cherry"] {
+            let rule = rules.ruleset.get(RuleKind::Content, rule_id).unwrap();
+            assert!(rule.actions().iter().any(Action::is_highlight));
+        }
+        drop(rules);
+
+        // Setting the same keywords again is a no-op.
+        settings.set_keywords(vec!["apple".to_owned(), "cherry".to_owned()], true).await.unwrap();
+
+        Mock::given(method("PUT"))
+            .and(path("/_matrix/client/r0/pushrules/global/content/date"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // Without highlight, the actions of the existing rules are kept, and the new
+        // rules only notify.
+        settings
+            .set_keywords(vec!["apple".to_owned(), "cherry".to_owned(), "date".to_owned()], false)
+            .await
+            .unwrap();
+
+        let rules = settings.rules.read().await;
+        let apple = rules.ruleset.get(RuleKind::Content, "apple").unwrap();
+        assert!(apple.actions().iter().any(Action::is_highlight));
+        let date = rules.ruleset.get(RuleKind::Content, "date").unwrap();
+        assert!(!date.actions().iter().any(Action::is_highlight));
+        assert!(date.actions().iter().any(|action| matches!(action, Action::Notify)));
+    }
+
     #[async_test]
     async fn remove_keyword_all() {
         let server = MockServer::start().await;
@@ -56,11 +56,15 @@ impl RuleCommands {
     }
 
     /// Insert a new rule for a keyword.
+    ///
+    /// SC: With `highlight`, the messages matching the keyword are also
+    /// highlighted.
     pub(crate) fn insert_keyword_rule(
         &mut self,
         keyword: String,
+        highlight: bool,
     ) -> Result<(), NotificationSettingsError> {
-        let command = Command::SetKeywordPushRule { scope: RuleScope::Global, keyword };
+        let command = Command::SetKeywordPushRule { scope: RuleScope::Global, keyword, highlight };
 
         self.rules.insert(command.to_push_rule()?, None, None)?;
         self.commands.push(command);
@@ -229,6 +229,21 @@ impl Rules {
         self.ruleset.content.iter().filter(|r| !r.default && r.pattern == keyword).collect()
     }
 
+    /// SC: The rules for a keyword, ignoring the case like the server does when
+    /// matching the keyword, if any.
+    pub(crate) fn keyword_rules_ignoring_case(&self, keyword: &str) -> Vec<&PatternedPushRule> {
+        self.ruleset
+            .content
+            .iter()
+            .filter(|r| !r.default && r.pattern.to_lowercase() == keyword.to_lowercase())
+            .collect()
+    }
+
+    /// SC: All the keyword rules, whether they are enabled or not.
+    pub(crate) fn all_keyword_rules(&self) -> Vec<&PatternedPushRule> {
+        self.ruleset.content.iter().filter(|r| !r.default).collect()
+    }
+
     /// Get whether a rule is enabled.
     pub(crate) fn is_enabled(
         &self,
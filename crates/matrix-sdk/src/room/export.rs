@@ -0,0 +1,287 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: Export of the timeline of a room, see
+//! [`Room::export_timeline`](super::Room::export_timeline).
+
+use std::collections::HashMap;
+
+use chrono::{TimeZone, Utc};
+use matrix_sdk_common::deserialized_responses::TimelineEvent;
+use ruma::{
+    events::{
+        room::{message::MessageType, MediaSource},
+        AnyMessageLikeEvent, AnyMessageLikeEventContent, AnyTimelineEvent, MessageLikeEventType,
+    },
+    serde::Base64,
+    MilliSecondsSinceUnixEpoch, OwnedUserId,
+};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use super::{MessagesOptions, Room};
+use crate::{
+    media::{MediaFormat, MediaRequest},
+    Result,
+};
+
+/// SC: The format of an export of a room's timeline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum ExportFormat {
+    /// A JSON array of the raw events, oldest first.
+    Json,
+
+    /// A transcript of the messages as an HTML document, with the sender names
+    /// and timestamps.
+    Html {
+        /// Whether to download the media and embed them in the document.
+        /// Otherwise they are referenced by their `mxc://` URI.
+        inline_media: bool,
+    },
+}
+
+pub(super) async fn export_timeline(
+    room: &Room,
+    mut writer: impl AsyncWrite + Unpin + Send,
+    format: ExportFormat,
+) -> Result<()> {
+    let mut history = History::new(room);
+
+    match format {
+        ExportFormat::Json => write_json(&mut history, &mut writer).await?,
+        ExportFormat::Html { inline_media } => {
+            write_html(room, &mut history, inline_media, &mut writer).await?
+        }
+    }
+
+    writer.flush().await?;
+
+    Ok(())
+}
+
+/// The history of a room, loaded batch by batch from its start.
+struct History<'a> {
+    room: &'a Room,
+    /// The token to load the next batch, or `None` to start from the start of
+    /// the room.
+    from: Option<String>,
+    reached_end: bool,
+}
+
+impl<'a> History<'a> {
+    fn new(room: &'a Room) -> Self {
+        Self { room, from: None, reached_end: false }
+    }
+
+    /// Load the next batch of events, in chronological order, or `None` if
+    /// the end of the room was reached.
+    async fn next_batch(&mut self) -> Result<Option<Vec<TimelineEvent>>> {
+        if self.reached_end {
+            return Ok(None);
+        }
+
+        let messages =
+            self.room.messages(MessagesOptions::forward().from(self.from.as_deref())).await?;
+
+        self.reached_end = messages.chunk.is_empty() || messages.end.is_none();
+        self.from = messages.end;
+
+        Ok(Some(messages.chunk))
+    }
+}
+
+async fn write_json(
+    history: &mut History<'_>,
+    writer: &mut (impl AsyncWrite + Unpin + Send),
+) -> Result<()> {
+    writer.write_all(b"[").await?;
+
+    let mut is_first = true;
+    while let Some(events) = history.next_batch().await? {
+        for event in events {
+            if !is_first {
+                writer.write_all(b",").await?;
+            }
+            is_first = false;
+            writer.write_all(event.event.json().get().as_bytes()).await?;
+        }
+    }
+
+    writer.write_all(b"]").await?;
+
+    Ok(())
+}
+
+async fn write_html(
+    room: &Room,
+    history: &mut History<'_>,
+    inline_media: bool,
+    writer: &mut (impl AsyncWrite + Unpin + Send),
+) -> Result<()> {
+    let title = escape_html(&room.name().unwrap_or_else(|| room.room_id().to_string()));
+    let header = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         </head>\n<body>\n<h1>{title}</h1>\n"
+    );
+    writer.write_all(header.as_bytes()).await?;
+
+    let mut sender_names = HashMap::<OwnedUserId, String>::new();
+
+    while let Some(events) = history.next_batch().await? {
+        for event in events {
+            let Ok(AnyTimelineEvent::MessageLike(event)) = event.event.deserialize() else {
+                continue;
+            };
+
+            let Some(body) = render_event(room, &event, inline_media).await? else {
+                continue;
+            };
+
+            let sender = event.sender();
+            let sender_name = match sender_names.get(sender) {
+                Some(name) => name.clone(),
+                None => {
+                    let name = match room.get_member_no_sync(sender).await? {
+                        Some(member) => member.name().to_owned(),
+                        None => sender.to_string(),
+                    };
+                    sender_names.insert(sender.to_owned(), name.clone());
+                    name
+                }
+            };
+
+            let line = format!(
+                "<div class=\"event\"><span class=\"timestamp\">{}</span> \
+                 <span class=\"sender\" title=\"{}\">{}</span>: {body}</div>\n",
+                format_timestamp(event.origin_server_ts()),
+                escape_html(sender.as_str()),
+                escape_html(&sender_name),
+            );
+            writer.write_all(line.as_bytes()).await?;
+        }
+    }
+
+    writer.write_all(b"</body>\n</html>\n").await?;
+
+    Ok(())
+}
+
+/// Render the content of a message as HTML, or `None` if the event isn't part
+/// of the transcript.
+async fn render_event(
+    room: &Room,
+    event: &AnyMessageLikeEvent,
+    inline_media: bool,
+) -> Result<Option<String>> {
+    let Some(content) = event.original_content() else {
+        return Ok((event.event_type() == MessageLikeEventType::RoomMessage)
+            .then(|| "<em>[removed]</em>".to_owned()));
+    };
+
+    let msgtype = match content {
+        AnyMessageLikeEventContent::RoomMessage(content) => content.msgtype,
+        AnyMessageLikeEventContent::RoomEncrypted(_) => {
+            return Ok(Some("<em>[unable to decrypt]</em>".to_owned()));
+        }
+        _ => return Ok(None),
+    };
+
+    let (source, mimetype, is_image) = match &msgtype {
+        MessageType::Image(c) => {
+            (&c.source, c.info.as_ref().and_then(|i| i.mimetype.clone()), true)
+        }
+        MessageType::File(c) => {
+            (&c.source, c.info.as_ref().and_then(|i| i.mimetype.clone()), false)
+        }
+        MessageType::Audio(c) => {
+            (&c.source, c.info.as_ref().and_then(|i| i.mimetype.clone()), false)
+        }
+        MessageType::Video(c) => {
+            (&c.source, c.info.as_ref().and_then(|i| i.mimetype.clone()), false)
+        }
+        MessageType::Emote(_) => {
+            return Ok(Some(format!("<em>* {}</em>", escape_html(msgtype.body()))));
+        }
+        _ => return Ok(Some(escape_html(msgtype.body()))),
+    };
+
+    let body = escape_html(msgtype.body());
+    let url = if inline_media {
+        let request = MediaRequest { source: source.clone(), format: MediaFormat::File };
+        let data = room.client.media().get_media_content(&request, true).await?;
+        let mimetype = mimetype.unwrap_or_else(|| "application/octet-stream".to_owned());
+        format!("data:{mimetype};base64,{}", Base64::new(data).encode())
+    } else {
+        let uri = match source {
+            MediaSource::Plain(uri) => uri,
+            MediaSource::Encrypted(file) => &file.url,
+        };
+        escape_html(uri.as_str())
+    };
+
+    Ok(Some(if is_image {
+        format!("<img src=\"{url}\" alt=\"{body}\">")
+    } else {
+        format!("<a href=\"{url}\" download=\"{body}\">{body}</a>")
+    }))
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Format a timestamp as `YYYY-MM-DD HH:MM:SS`, in UTC.
+fn format_timestamp(ts: MilliSecondsSinceUnixEpoch) -> String {
+    match Utc.timestamp_millis_opt(ts.0.into()).single() {
+        Some(date_time) => date_time.format("%Y-%m-%d %H:%M:%S").to_string(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ruma::{uint, MilliSecondsSinceUnixEpoch};
+
+    use super::{escape_html, format_timestamp};
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("<b>\"Tom\" & 'Jerry'</b>"),
+            "&lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(MilliSecondsSinceUnixEpoch(uint!(0))), "1970-01-01 00:00:00");
+        assert_eq!(
+            format_timestamp(MilliSecondsSinceUnixEpoch(uint!(1_709_251_199_000))),
+            "2024-02-29 23:59:59"
+        );
+    }
+}
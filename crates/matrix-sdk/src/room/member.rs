@@ -1,5 +1,6 @@
 use std::ops::Deref;
 
+use matrix_sdk_base::RoomMemberships;
 use ruma::events::room::MediaSource;
 
 use crate::{
@@ -125,6 +126,36 @@ impl RoomMemberRole {
     }
 }
 
+/// SC: The memberships of the members returned by
+/// [`Room::members_filtered`](crate::Room::members_filtered).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum RoomMemberFilter {
+    /// Members of any membership.
+    All,
+    /// Joined and invited members.
+    Active,
+    /// Joined members.
+    Joined,
+    /// Invited members.
+    Invited,
+    /// Banned members.
+    Banned,
+}
+
+impl RoomMemberFilter {
+    /// The memberships matching this filter.
+    pub fn memberships(&self) -> RoomMemberships {
+        match self {
+            Self::All => RoomMemberships::empty(),
+            Self::Active => RoomMemberships::ACTIVE,
+            Self::Joined => RoomMemberships::JOIN,
+            Self::Invited => RoomMemberships::INVITE,
+            Self::Banned => RoomMemberships::BAN,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -23,7 +23,7 @@ use ruma::{
     assign,
     events::AnyStateEvent,
     serde::Raw,
-    uint, RoomId, UInt,
+    uint, OwnedEventId, RoomId, UInt,
 };
 
 /// Options for [`messages`][super::Room::messages].
@@ -174,3 +174,30 @@ pub struct EventWithContextResponse {
     /// membership events.
     pub state: Vec<Raw<AnyStateEvent>>,
 }
+
+/// SC: The messages found by [`search_messages`][super::Room::search_messages].
+#[derive(Debug)]
+pub struct SearchResults {
+    /// The messages found, from the most recent to the oldest one.
+    pub results: Vec<SearchResult>,
+
+    /// The token to pass to the next search to get the next results, if
+    /// there are more.
+    pub next_batch: Option<String>,
+}
+
+/// SC: A message found by [`search_messages`][super::Room::search_messages].
+#[derive(Debug)]
+pub struct SearchResult {
+    /// The ID of the event, e.g. to focus a timeline on it.
+    pub event_id: OwnedEventId,
+
+    /// The event, decrypted if needs be and if the keys are available.
+    pub event: TimelineEvent,
+
+    /// Whether the event is encrypted and couldn't be decrypted.
+    pub is_undecryptable: bool,
+
+    /// The rank of the result, as computed by the server.
+    pub rank: Option<f64>,
+}
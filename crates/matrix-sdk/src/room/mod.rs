@@ -2,12 +2,14 @@
 
 use std::{
     borrow::Borrow,
-    collections::{BTreeMap, HashMap},
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashMap},
     ops::Deref,
     sync::Arc,
     time::Duration,
 };
 
+use async_stream::stream;
 use eyeball::SharedObservable;
 use futures_core::Stream;
 use futures_util::{
@@ -20,7 +22,9 @@ use matrix_sdk_base::{
     },
     instant::Instant,
     store::StateStoreExt,
-    ComposerDraft, RoomMemberships, StateChanges, StateStoreDataKey, StateStoreDataValue,
+    ComposerDraft, DisplayName, RoomInfo, RoomMemberships, ScBoldUnreadEventContent,
+    ScSnoozeEventContent, ScUnreadCountSource, ScUnreadCountSourceEventContent, StateChanges,
+    StateStoreDataKey, StateStoreDataValue, StoreError,
 };
 use matrix_sdk_common::timeout::timeout;
 use mime::Mime;
@@ -34,7 +38,7 @@ use ruma::{
         config::{set_global_account_data, set_room_account_data},
         context,
         error::ErrorKind,
-        filter::LazyLoadOptions,
+        filter::{LazyLoadOptions, RoomEventFilter},
         membership::{
             ban_user, forget_room, get_member_events,
             invite_user::{self, v3::InvitationRecipient},
@@ -45,6 +49,7 @@ use ruma::{
         receipt::create_receipt,
         redact::redact_event,
         room::{get_room_event, report_content},
+        search::search_events,
         state::{get_state_events_for_key, send_state_event},
         tag::{create_tag, delete_tag},
         typing::create_typing_event::{self, v3::Typing},
@@ -54,6 +59,7 @@ use ruma::{
         call::notify::{ApplicationType, CallNotifyEventContent, NotifyType},
         direct::DirectEventContent,
         marked_unread::MarkedUnreadEventContent,
+        presence::PresenceState,
         receipt::{Receipt, ReceiptThread, ReceiptType},
         room::{
             avatar::{self, RoomAvatarEventContent},
@@ -63,6 +69,7 @@ use ruma::{
             name::RoomNameEventContent,
             power_levels::{RoomPowerLevels, RoomPowerLevelsEventContent},
             server_acl::RoomServerAclEventContent,
+            tombstone::RoomTombstoneEventContent,
             topic::RoomTopicEventContent,
             MediaSource,
         },
@@ -75,10 +82,11 @@ use ruma::{
         StateEventContent, StateEventType, StaticEventContent, StaticStateEventContent,
         SyncStateEvent,
     },
-    push::{Action, PushConditionRoomCtx},
+    push::{Action, AnyPushRule, AnyPushRuleRef, PushConditionRoomCtx, Ruleset, Tweak},
     serde::Raw,
-    EventId, Int, MatrixToUri, MatrixUri, MxcUri, OwnedEventId, OwnedRoomId, OwnedServerName,
-    OwnedTransactionId, OwnedUserId, RoomId, TransactionId, UInt, UserId,
+    EventId, Int, MatrixToUri, MatrixUri, MilliSecondsSinceUnixEpoch, MxcUri, OwnedEventId,
+    OwnedRoomId, OwnedServerName, OwnedTransactionId, OwnedUserId, RoomId, TransactionId, UInt,
+    UserId,
 };
 use serde::de::DeserializeOwned;
 use thiserror::Error;
@@ -87,8 +95,11 @@ use tracing::{debug, info, instrument, warn};
 
 use self::futures::{SendAttachment, SendMessageLikeEvent, SendRawMessageLikeEvent};
 pub use self::{
-    member::{RoomMember, RoomMemberRole},
-    messages::{EventWithContextResponse, Messages, MessagesOptions},
+    export::ExportFormat,
+    member::{RoomMember, RoomMemberFilter, RoomMemberRole},
+    messages::{EventWithContextResponse, Messages, MessagesOptions, SearchResult, SearchResults},
+    space_summary::SpaceChildSummary,
+    url_preview::{UrlPreview, UrlPreviewError},
 };
 #[cfg(doc)]
 use crate::event_cache::EventCache;
@@ -107,10 +118,13 @@ use crate::{
     BaseRoom, Client, Error, HttpResult, Result, RoomState, TransmissionProgress,
 };
 
+mod export;
 pub mod futures;
 mod member;
 mod messages;
 pub mod power_levels;
+mod space_summary;
+mod url_preview;
 
 /// A struct containing methods that are common for Joined, Invited and Left
 /// Rooms
@@ -144,7 +158,7 @@ impl Room {
 
     /// Leave this room.
     ///
-    /// Only invited and joined rooms can be left.
+    /// Only invited, knocked and joined rooms can be left.
     #[doc(alias = "reject_invitation")]
     pub async fn leave(&self) -> Result<()> {
         let state = self.state();
@@ -158,6 +172,18 @@ impl Room {
         Ok(())
     }
 
+    /// SC: Retract the knock on this room.
+    ///
+    /// Only knocked rooms can have their knock cancelled.
+    pub async fn cancel_knock(&self) -> Result<()> {
+        let state = self.state();
+        if state != RoomState::Knocked {
+            return Err(Error::WrongRoomState(WrongRoomState::new("Knocked", state)));
+        }
+
+        self.leave().await
+    }
+
     /// Join this room.
     ///
     /// Only invited and left rooms can be joined via this method.
@@ -187,6 +213,145 @@ impl Room {
         Ok(())
     }
 
+    /// SC: Accept the invite to this room, by joining it.
+    ///
+    /// Only invited rooms can be accepted. Contrary to [`Self::join`], the room
+    /// lists are notified of the new state of the room, so that the room
+    /// leaves the lists filtered on invites.
+    pub async fn accept_invite(&self) -> Result<()> {
+        let state = self.state();
+        if state != RoomState::Invited {
+            return Err(Error::WrongRoomState(WrongRoomState::new("Invited", state)));
+        }
+
+        self.join().await?;
+        self.notify_room_list();
+
+        Ok(())
+    }
+
+    /// SC: Decline the invite to this room, by leaving it with an optional
+    /// `reason`.
+    ///
+    /// Only invited rooms can be declined. Contrary to [`Self::leave`], the
+    /// room lists are notified of the new state of the room, so that the room
+    /// leaves the lists filtered on invites.
+    pub async fn decline_invite(&self, reason: Option<String>) -> Result<()> {
+        let state = self.state();
+        if state != RoomState::Invited {
+            return Err(Error::WrongRoomState(WrongRoomState::new("Invited", state)));
+        }
+
+        let request =
+            assign!(leave_room::v3::Request::new(self.inner.room_id().to_owned()), { reason });
+        self.client.send(request, None).await?;
+        self.client.base_client().room_left(self.room_id()).await?;
+        self.notify_room_list();
+
+        Ok(())
+    }
+
+    /// SC: Get the room that replaced this room, if it has a tombstone.
+    pub fn successor_room(&self) -> Option<SuccessorRoom> {
+        let tombstone = self.tombstone()?;
+        let is_joined = self
+            .client
+            .get_room(&tombstone.replacement_room)
+            .is_some_and(|room| room.state() == RoomState::Joined);
+
+        Some(SuccessorRoom {
+            room_id: tombstone.replacement_room,
+            reason: (!tombstone.body.is_empty()).then_some(tombstone.body),
+            is_joined,
+        })
+    }
+
+    /// SC: Join the room that replaced this tombstoned room, and carry over
+    /// the favourite and low priority tags and the unread marker of this room.
+    ///
+    /// If the successor was itself upgraded and is already joined, the chain
+    /// of upgrades is followed until the latest room. Returns
+    /// [`TombstoneError::UpgradeLoop`] if the chain loops back to a room that
+    /// was already visited.
+    ///
+    /// The successor is joined through the server of the sender of the
+    /// tombstone, and the servers suggested to join the tombstoned room.
+    pub async fn follow_tombstone(&self) -> Result<Room> {
+        let mut visited = BTreeSet::from([self.room_id().to_owned()]);
+        let mut current = self.clone();
+
+        let successor = loop {
+            let Some(tombstone) = current.tombstone() else {
+                if current.room_id() == self.room_id() {
+                    return Err(TombstoneError::NotTombstoned.into());
+                }
+                break current;
+            };
+
+            let successor_id = tombstone.replacement_room;
+            if !visited.insert(successor_id.clone()) {
+                return Err(TombstoneError::UpgradeLoop(successor_id).into());
+            }
+
+            match self.client.get_room(&successor_id) {
+                Some(room) if room.state() == RoomState::Joined => current = room,
+                _ => {
+                    let via = current.tombstone_via_servers().await;
+                    break self
+                        .client
+                        .join_room_by_id_or_alias(successor_id.as_ref(), &via)
+                        .await?;
+                }
+            }
+        };
+
+        let tags = self.tags().await?.unwrap_or_default();
+        let tag_order = |tag: TagName| tags.get(&tag).and_then(|info| info.order);
+
+        if self.is_favourite() && !successor.is_favourite() {
+            successor.set_is_favourite(true, tag_order(TagName::Favorite)).await?;
+        } else if self.is_low_priority() && !successor.is_low_priority() {
+            successor.set_is_low_priority(true, tag_order(TagName::LowPriority)).await?;
+        }
+
+        if self.is_marked_unread() && !successor.is_marked_unread() {
+            successor.set_unread_flag(true).await?;
+        }
+
+        Ok(successor)
+    }
+
+    /// The servers to join the successor of this tombstoned room through: the
+    /// server of the sender of the tombstone first, as it is likely in the
+    /// successor, then the servers suggested to join this room.
+    async fn tombstone_via_servers(&self) -> Vec<OwnedServerName> {
+        let sender_server = match self.get_state_event_static::<RoomTombstoneEventContent>().await {
+            Ok(Some(event)) => {
+                event.deserialize().ok().map(|e| e.sender().server_name().to_owned())
+            }
+            Ok(None) => None,
+            Err(error) => {
+                debug!("Could not get the tombstone event: {error}");
+                None
+            }
+        };
+
+        let mut via: Vec<_> = sender_server.into_iter().collect();
+        for server in self.suggested_via_servers().await.unwrap_or_default() {
+            if !via.contains(&server) {
+                via.push(server);
+            }
+        }
+
+        via
+    }
+
+    /// Notify the room lists that this room has changed, e.g. after a change
+    /// of its state that must be reflected by the filters.
+    fn notify_room_list(&self) {
+        self.inner.set_room_info(self.inner.clone_info(), true);
+    }
+
     /// Get the inner client saved in this room instance.
     ///
     /// Returns the client this room is part of.
@@ -435,6 +600,119 @@ impl Room {
         })
     }
 
+    /// SC: Export the history of this room, as far as it's visible to the
+    /// user, to `writer`.
+    ///
+    /// The history is loaded from the start of the room with
+    /// [`Self::messages`], and each batch is written as soon as it's loaded.
+    /// Encrypted events are decrypted if the keys are available. In the HTML
+    /// format, redacted messages are represented as "[removed]".
+    pub async fn export_timeline(
+        &self,
+        writer: impl tokio::io::AsyncWrite + Unpin + Send,
+        format: ExportFormat,
+    ) -> Result<()> {
+        export::export_timeline(self, writer, format).await
+    }
+
+    /// SC: Get the preview of `url`, as generated by the homeserver.
+    ///
+    /// Previews are cached by URL for the whole client. Fails with
+    /// [`UrlPreviewError::Disabled`] if the user disabled URL previews in this
+    /// room, see [`Self::are_url_previews_enabled`], and with
+    /// [`UrlPreviewError::Unsupported`] if the homeserver doesn't generate
+    /// previews, so the UI can hide them.
+    pub async fn get_url_preview(&self, url: &str) -> Result<UrlPreview> {
+        url_preview::get_url_preview(self, url).await
+    }
+
+    /// SC: Whether the user enabled URL previews in this room.
+    ///
+    /// This follows the `org.matrix.room.preview_urls` room account data, then
+    /// the `org.matrix.preview_urls` global account data. Without any setting,
+    /// previews are enabled except in encrypted rooms.
+    pub async fn are_url_previews_enabled(&self) -> Result<bool> {
+        url_preview::are_url_previews_enabled(self).await
+    }
+
+    /// SC: Search the messages of this room on the server, using the
+    /// `/search` endpoint.
+    ///
+    /// The results are ordered from the most recent to the oldest one.
+    /// Batches are requested from the server until `limit` results are found,
+    /// the server doesn't have more results, or it returns an empty batch.
+    /// The number of results is also limited in the filter sent to the server,
+    /// so that no result is skipped when paginating. Encrypted results are
+    /// decrypted if the keys are available, and marked as undecryptable
+    /// otherwise. Note that servers can't search the content of encrypted
+    /// events.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The terms to search for.
+    ///
+    /// * `limit` - The maximum number of results to return.
+    ///
+    /// * `from` - The [`SearchResults::next_batch`] token of a previous search
+    ///   with the same query, to get the next results, or `None` to start from
+    ///   the most recent results.
+    pub async fn search_messages(
+        &self,
+        query: &str,
+        limit: usize,
+        from: Option<String>,
+    ) -> Result<SearchResults> {
+        let mut results = Vec::new();
+        let mut next_batch = from;
+
+        while results.len() < limit {
+            let remaining = limit - results.len();
+            let criteria = assign!(search_events::v3::Criteria::new(query.to_owned()), {
+                filter: assign!(RoomEventFilter::default(), {
+                    rooms: Some(vec![self.room_id().to_owned()]),
+                    limit: Some(UInt::try_from(remaining).unwrap_or(UInt::MAX)),
+                }),
+                order_by: Some(search_events::v3::OrderBy::Recent),
+            });
+            let request = assign!(
+                search_events::v3::Request::new(assign!(search_events::v3::Categories::new(), {
+                    room_events: Some(criteria),
+                })),
+                { next_batch: next_batch.take() }
+            );
+
+            let room_events = self.client.send(request, None).await?.search_categories.room_events;
+            let is_empty_batch = room_events.results.is_empty();
+
+            for result in room_events.results {
+                let Some(event) = result.result else {
+                    continue;
+                };
+
+                let event = self.try_decrypt_event(event).await?;
+                let Ok(Some(event_id)) = event.event.get_field::<OwnedEventId>("event_id") else {
+                    continue;
+                };
+                let is_undecryptable = event.event.get_field::<String>("type").ok().flatten()
+                    == Some("m.room.encrypted".to_owned());
+
+                results.push(SearchResult { event_id, event, is_undecryptable, rank: result.rank });
+            }
+
+            next_batch = room_events.next_batch;
+            // Don't loop forever if the server keeps returning a token without
+            // any results.
+            if next_batch.is_none() || is_empty_batch {
+                break;
+            }
+        }
+
+        // The server might not respect the limit of the filter.
+        results.truncate(limit);
+
+        Ok(SearchResults { results, next_batch })
+    }
+
     pub(crate) async fn request_members(&self) -> Result<()> {
         self.client
             .locks()
@@ -541,6 +819,295 @@ impl Room {
         }
     }
 
+    /// SC: Make sure the full member list of this room is available, fetching
+    /// it from the server if needed.
+    ///
+    /// The returned stream reports the progress of the fetch, and ends after
+    /// yielding either [`MemberLoadProgress::Loaded`] or
+    /// [`MemberLoadProgress::Failed`]. The members are requested at most once
+    /// at a time: concurrent callers wait for the same request.
+    pub fn ensure_members(&self) -> impl Stream<Item = MemberLoadProgress> + '_ {
+        stream! {
+            if !self.are_events_visible() || self.are_members_synced() {
+                yield MemberLoadProgress::Loaded;
+                return;
+            }
+
+            yield MemberLoadProgress::Loading;
+
+            match self.request_members().await {
+                Ok(()) => yield MemberLoadProgress::Loaded,
+                Err(error) => yield MemberLoadProgress::Failed(Arc::new(error)),
+            }
+        }
+    }
+
+    /// SC: Recompute the display name of this room, update its cached value
+    /// and notify the room lists about it.
+    ///
+    /// If the room has neither a name, a canonical alias nor heroes, e.g.
+    /// right after joining a group DM, the members are fetched from the server
+    /// first so the name isn't computed as an empty room.
+    pub async fn recompute_display_name(&self) -> Result<DisplayName> {
+        if self.name().is_none()
+            && self.canonical_alias().is_none()
+            && self.heroes().is_empty()
+            && !self.are_members_synced()
+        {
+            self.sync_members().await?;
+        }
+
+        let display_name = self.compute_display_name().await?;
+        self.notify_room_list();
+
+        Ok(display_name)
+    }
+
+    /// SC: Record that the user opened this room, see
+    /// [`BaseRoom::was_opened`].
+    ///
+    /// This is persisted, and the room lists are notified the first time the
+    /// room is opened.
+    pub async fn mark_opened(&self) -> Result<(), StoreError> {
+        if self.was_opened() {
+            return Ok(());
+        }
+
+        let _sync_lock = self.client.base_client().sync_lock().lock().await;
+
+        let mut room_info = self.clone_info();
+        room_info.mark_opened();
+        let mut changes = StateChanges::default();
+        changes.add_room(room_info.clone());
+
+        self.client.store().save_changes(&changes).await?;
+        self.set_room_info(room_info, true);
+
+        Ok(())
+    }
+
+    /// SC: The other member of this room, if it's a DM with a single other
+    /// member.
+    fn dm_partner(&self) -> Option<OwnedUserId> {
+        let dm_targets = self.direct_targets();
+
+        if dm_targets.len() != 1 {
+            return None;
+        }
+
+        dm_targets.into_iter().next()
+    }
+
+    /// SC: Get the last-known presence of the other member of this DM.
+    ///
+    /// Returns `None` if this room isn't a DM with a single other member, or
+    /// if the presence of that member is unknown. The presence is only
+    /// updated from the presence events received in sync responses, so it may
+    /// be stale if the server doesn't push presence.
+    pub fn dm_partner_presence(&self) -> Option<PresenceState> {
+        self.client.presence(&self.dm_partner()?)
+    }
+
+    /// SC: Subscribe to the last-known presence of the other member of this
+    /// DM.
+    ///
+    /// The stream yields the current value of
+    /// [`Self::dm_partner_presence`] first, then every change of it. If this
+    /// room isn't a DM with a single other member, the stream yields `None`
+    /// and ends.
+    pub fn subscribe_to_dm_partner_presence(&self) -> impl Stream<Item = Option<PresenceState>> {
+        let dm_partner = self.dm_partner();
+        let mut subscriber = self.client.subscribe_to_presence_changes();
+
+        stream! {
+            let Some(dm_partner) = dm_partner else {
+                yield None;
+                return;
+            };
+
+            let mut current = subscriber.read().get(&dm_partner).cloned();
+            yield current.clone();
+
+            while let Some(presence) = subscriber.next().await {
+                let presence = presence.get(&dm_partner).cloned();
+
+                if presence != current {
+                    current = presence.clone();
+                    yield presence;
+                }
+            }
+        }
+    }
+
+    /// SC: Subscribe to the changes of the [`RoomInfo`] of this room,
+    /// coalescing the bursts of changes.
+    ///
+    /// After a change, the stream waits for `interval` then yields the latest
+    /// `RoomInfo`, so it yields at most once per `interval`, e.g. once for a
+    /// sync response that changes the name, the members and the unread counts
+    /// of the room at once.
+    pub fn info_stream_debounced(&self, interval: Duration) -> impl Stream<Item = RoomInfo> {
+        let mut subscriber = self.subscribe_info();
+
+        stream! {
+            while subscriber.next().await.is_some() {
+                #[cfg(target_arch = "wasm32")]
+                gloo_timers::future::sleep(interval).await;
+
+                #[cfg(not(target_arch = "wasm32"))]
+                tokio::time::sleep(interval).await;
+
+                yield subscriber.next_now();
+            }
+        }
+    }
+
+    /// SC: Subscribe to the state of the call in this room.
+    ///
+    /// The stream yields the current [`CallState`] first, then every change of
+    /// it, as `m.call.member` state events are received. Memberships that
+    /// expire without a new state event are only dropped on the next change of
+    /// the room info.
+    pub fn call_state_stream(&self) -> impl Stream<Item = CallState> {
+        let mut subscriber = self.subscribe_info();
+
+        stream! {
+            let mut current = CallState::from_room_info(&subscriber.get());
+            yield current.clone();
+
+            while let Some(info) = subscriber.next().await {
+                let state = CallState::from_room_info(&info);
+
+                if state != current {
+                    current = state.clone();
+                    yield state;
+                }
+            }
+        }
+    }
+
+    /// SC: Get the children of this space, sorted for display.
+    ///
+    /// The children with an `order` come first, sorted lexicographically by
+    /// it, then the other ones; the room ID breaks ties so the result is
+    /// stable. Children whose `m.space.child` event was emptied, i.e. without
+    /// `via` servers, are ignored. Returns an empty list if this room isn't a
+    /// space.
+    pub fn sorted_space_children(&self) -> Vec<SpaceChild> {
+        if !self.is_space() {
+            return Vec::new();
+        }
+
+        let mut children: Vec<_> = self
+            .space_children()
+            .into_iter()
+            .filter_map(|(room_id, event)| {
+                let content = &event.as_original()?.content;
+                (!content.via.is_empty()).then(|| SpaceChild {
+                    room_id,
+                    order: content.order.clone(),
+                    suggested: content.suggested,
+                })
+            })
+            .collect();
+
+        children.sort_by_key(|child| {
+            (child.order.is_none(), child.order.clone(), child.room_id.clone())
+        });
+
+        children
+    }
+
+    /// SC: Fetch a summary of the children of this space from the homeserver.
+    ///
+    /// The direct children are fetched from the `/hierarchy` endpoint,
+    /// following its pagination, and merged with the locally known children.
+    /// The children the user has joined report their live local state, the
+    /// other ones the summary returned by the homeserver.
+    ///
+    /// If `suggested_only` is set, only the suggested children are returned.
+    pub async fn fetch_space_summary(
+        &self,
+        suggested_only: bool,
+    ) -> Result<Vec<SpaceChildSummary>> {
+        space_summary::fetch_space_summary(self, suggested_only).await
+    }
+
+    /// SC: Subscribe to the children of this space.
+    ///
+    /// The stream yields the current [`Self::sorted_space_children`] first,
+    /// then the new list every time it changes, but not on the other changes
+    /// of the room info.
+    pub fn space_children_stream(&self) -> impl Stream<Item = Vec<SpaceChild>> {
+        let room = self.clone();
+        let mut subscriber = self.subscribe_info();
+
+        stream! {
+            let mut current = room.sorted_space_children();
+            yield current.clone();
+
+            while subscriber.next().await.is_some() {
+                let children = room.sorted_space_children();
+
+                if children != current {
+                    current = children.clone();
+                    yield children;
+                }
+            }
+        }
+    }
+
+    /// SC: Subscribe to the encryption state of this room, e.g. for a padlock
+    /// in the room header.
+    ///
+    /// The stream yields the current [`EncryptionState`] first, then every
+    /// change of it, e.g. when encryption is enabled in the room. If the state
+    /// isn't known yet, it's requested from the server once.
+    pub fn encryption_state_stream(&self) -> impl Stream<Item = EncryptionState> + '_ {
+        let mut subscriber = self.subscribe_info();
+
+        stream! {
+            let mut current = EncryptionState::from_room_info(&subscriber.get());
+            yield current;
+
+            if current == EncryptionState::Unknown {
+                // On success, the updated room info is received by the subscriber.
+                if let Err(error) = self.request_encryption_state().await {
+                    warn!("Failed to request the encryption state of the room: {error}");
+                }
+            }
+
+            while let Some(info) = subscriber.next().await {
+                let state = EncryptionState::from_room_info(&info);
+
+                if state != current {
+                    current = state;
+                    yield state;
+                }
+            }
+        }
+    }
+
+    /// SC: Get the URL of the Element Call widget for this room, to offer to
+    /// join the call.
+    ///
+    /// This is a shortcut for
+    /// [`WidgetSettings::new_virtual_element_call_widget`] followed by
+    /// [`WidgetSettings::generate_webview_url`].
+    ///
+    /// [`WidgetSettings::new_virtual_element_call_widget`]: crate::widget::WidgetSettings::new_virtual_element_call_widget
+    /// [`WidgetSettings::generate_webview_url`]: crate::widget::WidgetSettings::generate_webview_url
+    #[cfg(feature = "experimental-widgets")]
+    pub async fn element_call_url(
+        &self,
+        options: crate::widget::VirtualElementCallWidgetOptions,
+        props: crate::widget::ClientProperties,
+    ) -> Result<url::Url, url::ParseError> {
+        crate::widget::WidgetSettings::new_virtual_element_call_widget(options)?
+            .generate_webview_url(self, props)
+            .await
+    }
+
     /// Get active members for this room, includes invited, joined members.
     ///
     /// *Note*: This method will fetch the members from the homeserver if the
@@ -665,6 +1232,45 @@ impl Room {
             .collect())
     }
 
+    /// SC: Get a page of the members of this room matching the given query.
+    ///
+    /// Members match if the query is a case-insensitive substring of their
+    /// display name or user ID, and if their membership matches the given
+    /// filter. The matching members are sorted by descending power level, then
+    /// by name, before `offset` and `limit` are applied.
+    ///
+    /// *Note*: Like [`Room::members_no_sync`], this method doesn't fetch the
+    /// members from the homeserver. Use [`Room::ensure_members`] first to get
+    /// results from the full member list.
+    pub async fn members_filtered(
+        &self,
+        query: &str,
+        membership: RoomMemberFilter,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<RoomMember>> {
+        let query = query.to_lowercase();
+
+        let mut members: Vec<_> = self
+            .members_no_sync(membership.memberships())
+            .await?
+            .into_iter()
+            .filter(|member| {
+                query.is_empty()
+                    || member.user_id().as_str().to_lowercase().contains(&query)
+                    || member
+                        .display_name()
+                        .is_some_and(|name| name.to_lowercase().contains(&query))
+            })
+            .collect();
+
+        members.sort_by_cached_key(|member| {
+            (Reverse(member.power_level()), member.name().to_lowercase())
+        });
+
+        Ok(members.into_iter().skip(offset).take(limit).collect())
+    }
+
     /// Get all state events of a given type in this room.
     pub async fn get_state_events(
         &self,
@@ -1457,6 +2063,24 @@ impl Room {
         Ok(())
     }
 
+    /// SC: Enable End-to-end encryption in this room, after checking that the
+    /// current user is allowed to do so.
+    ///
+    /// Enabling encryption can't be undone, so unlike
+    /// [`Room::enable_encryption`] this checks the power levels of the room
+    /// first and returns [`EnableEncryptionError::NotAllowed`] instead of
+    /// trying to send the `m.room.encryption` state event when the user
+    /// doesn't have the permission to send it.
+    ///
+    /// This is a noop if encryption is already enabled.
+    pub async fn enable_encryption_checked(&self) -> Result<()> {
+        if !self.can_user_enable_encryption(self.own_user_id()).await? {
+            return Err(EnableEncryptionError::NotAllowed.into());
+        }
+
+        self.enable_encryption().await
+    }
+
     /// Share a room key with users in the given room.
     ///
     /// This will create Olm sessions with all the users/device pairs in the
@@ -1973,6 +2597,77 @@ impl Room {
         self.set_avatar_url(&upload_response.content_uri, Some(info)).await
     }
 
+    /// SC: Adds a room as a child of this space, by sending a `m.space.child`
+    /// state event.
+    ///
+    /// # Arguments
+    ///
+    /// * `child` - The ID of the room to add to this space.
+    /// * `order` - An optional ordering string, which must consist of at most
+    ///   50 printable ASCII characters.
+    /// * `suggested` - Whether the child should be suggested to users.
+    /// * `via` - Servers to try to join the child through, must not be empty.
+    pub async fn add_space_child(
+        &self,
+        child: &RoomId,
+        order: Option<String>,
+        suggested: bool,
+        via: Vec<OwnedServerName>,
+    ) -> Result<send_state_event::v3::Response> {
+        if let Some(order) = &order {
+            if !is_valid_space_child_order(order) {
+                return Err(SpaceChildError::InvalidOrder(order.clone()).into());
+            }
+        }
+        if via.is_empty() {
+            return Err(SpaceChildError::MissingVia.into());
+        }
+
+        let mut content = SpaceChildEventContent::new(via);
+        content.order = order;
+        content.suggested = suggested;
+
+        self.send_state_event_for_key(child, content).await
+    }
+
+    /// SC: Removes a room from the children of this space, by replacing its
+    /// `m.space.child` state event with an empty one.
+    pub async fn remove_space_child(
+        &self,
+        child: &RoomId,
+    ) -> Result<send_state_event::v3::Response> {
+        self.send_state_event_for_key(child, SpaceChildEventContent::new(Vec::new())).await
+    }
+
+    /// SC: Sets, or clears if `None`, the ordering string of a child of this
+    /// space.
+    ///
+    /// The other fields of the child's `m.space.child` state event are kept as
+    /// they are.
+    pub async fn set_space_child_order(
+        &self,
+        child: &RoomId,
+        order: Option<String>,
+    ) -> Result<send_state_event::v3::Response> {
+        let Some(mut content) = self
+            .space_children()
+            .remove(child)
+            .and_then(|event| event.as_original().map(|ev| ev.content.clone()))
+            .filter(|content| !content.via.is_empty())
+        else {
+            return Err(SpaceChildError::NotAChild(child.to_owned()).into());
+        };
+
+        if let Some(order) = &order {
+            if !is_valid_space_child_order(order) {
+                return Err(SpaceChildError::InvalidOrder(order.clone()).into());
+            }
+        }
+        content.order = order;
+
+        self.send_state_event_for_key(child, content).await
+    }
+
     /// Send a state event with an empty state key to the homeserver.
     ///
     /// For state events with a non-empty state key, see
@@ -2236,6 +2931,22 @@ impl Room {
         Ok(self.room_power_levels().await?.user_can_send_state(user_id, state_event))
     }
 
+    /// SC: Returns true if the user with the given user_id is able to manage
+    /// the children of this space, i.e. send `m.space.child` state events.
+    ///
+    /// The call may fail if there is an error in getting the power levels.
+    pub async fn can_user_manage_spaces(&self, user_id: &UserId) -> Result<bool> {
+        self.can_user_send_state(user_id, StateEventType::SpaceChild).await
+    }
+
+    /// SC: Returns true if the user with the given user_id is able to enable
+    /// encryption in this room, i.e. send a `m.room.encryption` state event.
+    ///
+    /// The call may fail if there is an error in getting the power levels.
+    pub async fn can_user_enable_encryption(&self, user_id: &UserId) -> Result<bool> {
+        self.can_user_send_state(user_id, StateEventType::RoomEncryption).await
+    }
+
     /// Returns true if the user with the given user_id is able to send a
     /// specific message type in the room.
     ///
@@ -2315,43 +3026,71 @@ impl Room {
             .collect())
     }
 
+    /// SC: Get a list of servers that are likely to know this room, suitable
+    /// as `via` parameters when sharing a link to it or joining it.
+    ///
+    /// Uses [`Room::route()`], and falls back to the servers of the room
+    /// heroes if the member list isn't known locally.
+    ///
+    /// Returns at most three servers.
+    pub async fn suggested_via_servers(&self) -> Result<Vec<OwnedServerName>> {
+        let servers = self.route().await?;
+        if !servers.is_empty() {
+            return Ok(servers);
+        }
+
+        let servers = self
+            .heroes()
+            .into_iter()
+            .map(|hero| hero.user_id.server_name().to_owned())
+            .filter(|server| !server.is_ip_literal())
+            .fold(BTreeMap::<_, u32>::new(), |mut servers, server| {
+                *servers.entry(server).or_default() += 1;
+                servers
+            });
+        let mut servers: Vec<_> = servers.into_iter().collect();
+        servers.sort_by(|(_, count_a), (_, count_b)| count_b.cmp(count_a));
+
+        Ok(servers.into_iter().map(|(server, _)| server).take(3).collect())
+    }
+
     /// Get a `matrix.to` permalink to this room.
     ///
-    /// If this room has an alias, we use it. Otherwise, we try to use the
-    /// synced members in the room for [routing] the room ID.
+    /// If this room has an alias, we use it. Otherwise, we use the
+    /// [suggested via servers] of the room to route the room ID.
     ///
-    /// [routing]: https://spec.matrix.org/v1.3/appendices/#routing
+    /// [suggested via servers]: Self::suggested_via_servers
     pub async fn matrix_to_permalink(&self) -> Result<MatrixToUri> {
         if let Some(alias) = self.canonical_alias().or_else(|| self.alt_aliases().pop()) {
             return Ok(alias.matrix_to_uri());
         }
 
-        let via = self.route().await?;
+        let via = self.suggested_via_servers().await?;
         Ok(self.room_id().matrix_to_uri_via(via))
     }
 
     /// Get a `matrix:` permalink to this room.
     ///
-    /// If this room has an alias, we use it. Otherwise, we try to use the
-    /// synced members in the room for [routing] the room ID.
+    /// If this room has an alias, we use it. Otherwise, we use the
+    /// [suggested via servers] of the room to route the room ID.
     ///
     /// # Arguments
     ///
     /// * `join` - Whether the user should join the room.
     ///
-    /// [routing]: https://spec.matrix.org/v1.3/appendices/#routing
+    /// [suggested via servers]: Self::suggested_via_servers
     pub async fn matrix_permalink(&self, join: bool) -> Result<MatrixUri> {
         if let Some(alias) = self.canonical_alias().or_else(|| self.alt_aliases().pop()) {
             return Ok(alias.matrix_uri(join));
         }
 
-        let via = self.route().await?;
+        let via = self.suggested_via_servers().await?;
         Ok(self.room_id().matrix_uri_via(via, join))
     }
 
     /// Get a `matrix.to` permalink to an event in this room.
     ///
-    /// We try to use the synced members in the room for [routing] the room ID.
+    /// We use the [suggested via servers] of the room to route the room ID.
     ///
     /// *Note*: This method does not check if the given event ID is actually
     /// part of this room. It needs to be checked before calling this method
@@ -2361,20 +3100,20 @@ impl Room {
     ///
     /// * `event_id` - The ID of the event.
     ///
-    /// [routing]: https://spec.matrix.org/v1.3/appendices/#routing
+    /// [suggested via servers]: Self::suggested_via_servers
     pub async fn matrix_to_event_permalink(
         &self,
         event_id: impl Into<OwnedEventId>,
     ) -> Result<MatrixToUri> {
         // Don't use the alias because an event is tied to a room ID, but an
         // alias might point to another room, e.g. after a room upgrade.
-        let via = self.route().await?;
+        let via = self.suggested_via_servers().await?;
         Ok(self.room_id().matrix_to_event_uri_via(event_id, via))
     }
 
     /// Get a `matrix:` permalink to an event in this room.
     ///
-    /// We try to use the synced members in the room for [routing] the room ID.
+    /// We use the [suggested via servers] of the room to route the room ID.
     ///
     /// *Note*: This method does not check if the given event ID is actually
     /// part of this room. It needs to be checked before calling this method
@@ -2384,14 +3123,14 @@ impl Room {
     ///
     /// * `event_id` - The ID of the event.
     ///
-    /// [routing]: https://spec.matrix.org/v1.3/appendices/#routing
+    /// [suggested via servers]: Self::suggested_via_servers
     pub async fn matrix_event_permalink(
         &self,
         event_id: impl Into<OwnedEventId>,
     ) -> Result<MatrixUri> {
         // Don't use the alias because an event is tied to a room ID, but an
         // alias might point to another room, e.g. after a room upgrade.
-        let via = self.route().await?;
+        let via = self.suggested_via_servers().await?;
         Ok(self.room_id().matrix_event_uri_via(event_id, via))
     }
 
@@ -2483,6 +3222,34 @@ impl Room {
         Ok(Some(push_rules.get_actions(event, &push_context).to_owned()))
     }
 
+    /// SC: Evaluate how the push rules classify the given event with the
+    /// current room state, e.g. to explain why it did or didn't notify.
+    ///
+    /// Returns `None` if the push context couldn't be built, like
+    /// [`Room::event_push_actions`].
+    pub async fn evaluate_push_rules(
+        &self,
+        event: &Raw<AnySyncTimelineEvent>,
+    ) -> Result<Option<PushEvaluation>> {
+        let Some(push_context) = self.push_context().await? else {
+            debug!("Could not aggregate push context");
+            return Ok(None);
+        };
+
+        let push_rules = self.client().account().push_rules().await?;
+
+        let (matched_rule, actions) = match push_rules.get_match(event, &push_context) {
+            Some(rule) => {
+                let actions = rule.actions().to_owned();
+                (Some(rule.to_owned()), actions)
+            }
+            None => (None, Vec::new()),
+        };
+        let matched_keywords = matched_keywords(&push_rules, event, &push_context);
+
+        Ok(Some(PushEvaluation::new(matched_rule, actions, matched_keywords, push_context)))
+    }
+
     /// The membership details of the (latest) invite for the logged-in user in
     /// this room.
     pub async fn invite_details(&self) -> Result<Invite> {
@@ -2615,6 +3382,97 @@ impl Room {
         Ok(())
     }
 
+    /// SC: Snooze the notifications of this room until `until`.
+    ///
+    /// The snooze is stored in the room account data, so it's shared with the
+    /// other sessions of the user. It has no effect anymore once `until` has
+    /// elapsed, so a timestamp in the past cancels the snooze.
+    pub async fn snooze_notifications(&self, until: MilliSecondsSinceUnixEpoch) -> Result<()> {
+        let user_id = self.client.user_id().ok_or(Error::AuthenticationRequired)?;
+
+        let content = ScSnoozeEventContent::new(until);
+
+        let request = set_room_account_data::v3::Request::new(
+            user_id.to_owned(),
+            self.inner.room_id().to_owned(),
+            &content,
+        )?;
+
+        self.client.send(request, None).await?;
+        Ok(())
+    }
+
+    /// SC: Choose where the unread counts of this room come from, overriding
+    /// the default source of the client, or `None` to follow the default
+    /// source again.
+    ///
+    /// The choice is stored in the room account data, so it's shared with the
+    /// other sessions of the user.
+    pub async fn set_unread_count_source(&self, source: Option<ScUnreadCountSource>) -> Result<()> {
+        let user_id = self.client.user_id().ok_or(Error::AuthenticationRequired)?;
+
+        let content = ScUnreadCountSourceEventContent::new(source);
+
+        let request = set_room_account_data::v3::Request::new(
+            user_id.to_owned(),
+            self.inner.room_id().to_owned(),
+            &content,
+        )?;
+
+        self.client.send(request, None).await?;
+        Ok(())
+    }
+
+    /// SC: Choose whether the notifications of this room only make it unread,
+    /// e.g. shown in bold, without counting in the badges.
+    ///
+    /// The choice is stored in the room account data, so it's shared with the
+    /// other sessions of the user.
+    pub async fn set_bold_unread_only(&self, enabled: bool) -> Result<()> {
+        let user_id = self.client.user_id().ok_or(Error::AuthenticationRequired)?;
+
+        let content = ScBoldUnreadEventContent::new(enabled);
+
+        let request = set_room_account_data::v3::Request::new(
+            user_id.to_owned(),
+            self.inner.room_id().to_owned(),
+            &content,
+        )?;
+
+        self.client.send(request, None).await?;
+        Ok(())
+    }
+
+    /// SC: Mute or unmute the messages of `user_id` in this room.
+    ///
+    /// The muted users are only kept in the local store, so this doesn't
+    /// affect the other sessions of the user, and nothing is sent to the
+    /// homeserver. This is different from
+    /// [`Account::ignore_user`](crate::Account::ignore_user): the events of a
+    /// muted user are still received, and they're only collapsed by timelines
+    /// that are configured to do so. The muted users can be read with
+    /// [`BaseRoom::locally_muted_users`].
+    pub async fn set_user_muted_locally(
+        &self,
+        user_id: &UserId,
+        muted: bool,
+    ) -> Result<(), StoreError> {
+        let _sync_lock = self.client.base_client().sync_lock().lock().await;
+
+        let mut room_info = self.clone_info();
+        if !room_info.set_user_muted_locally(user_id, muted) {
+            return Ok(());
+        }
+
+        let mut changes = StateChanges::default();
+        changes.add_room(room_info.clone());
+
+        self.client.store().save_changes(&changes).await?;
+        self.set_room_info(room_info, true);
+
+        Ok(())
+    }
+
     /// Returns the [`RoomEventCache`] associated to this room, assuming the
     /// global [`EventCache`] has been enabled for subscription.
     pub async fn event_cache(
@@ -2748,6 +3606,73 @@ pub struct Invite {
     pub inviter: Option<RoomMember>,
 }
 
+/// SC: How the push rules classify an event, see
+/// [`Room::evaluate_push_rules`].
+#[derive(Debug, Clone)]
+pub struct PushEvaluation {
+    /// The first enabled rule that matched the event, if any.
+    pub matched_rule: Option<AnyPushRule>,
+    /// The actions of the matched rule.
+    pub actions: Vec<Action>,
+    /// The keywords that caused the event to match, if it was matched by
+    /// keyword rules.
+    ///
+    /// It is empty if the event was matched by another rule, like a mention.
+    pub matched_keywords: Vec<String>,
+    /// Whether the event notifies.
+    pub notify: bool,
+    /// Whether the event is highlighted.
+    pub highlight: bool,
+    /// The sound to play for the event, if any.
+    pub sound: Option<String>,
+    /// The room context the rules were evaluated with, e.g. the member count
+    /// and the power levels.
+    pub context: PushConditionRoomCtx,
+}
+
+impl PushEvaluation {
+    fn new(
+        matched_rule: Option<AnyPushRule>,
+        actions: Vec<Action>,
+        matched_keywords: Vec<String>,
+        context: PushConditionRoomCtx,
+    ) -> Self {
+        let notify = actions.iter().any(|action| action.should_notify());
+        let highlight = actions.iter().any(|action| action.is_highlight());
+        let sound = actions.iter().find_map(|action| match action {
+            Action::SetTweak(Tweak::Sound(sound)) => Some(sound.clone()),
+            _ => None,
+        });
+
+        Self { matched_rule, actions, matched_keywords, notify, highlight, sound, context }
+    }
+}
+
+/// The patterns of the keyword rules matching the event, in priority order,
+/// if the first rule matching the event is a keyword rule.
+fn matched_keywords(
+    push_rules: &Ruleset,
+    event: &Raw<AnySyncTimelineEvent>,
+    context: &PushConditionRoomCtx,
+) -> Vec<String> {
+    let mut remaining_rules = push_rules.clone();
+    let mut keywords = Vec::new();
+
+    // Only the first matching rule is returned, so remove each matching keyword
+    // rule until the event is matched by something else.
+    while let Some(AnyPushRuleRef::Content(rule)) = remaining_rules.get_match(event, context) {
+        if rule.default {
+            break;
+        }
+
+        let rule_id = rule.rule_id.clone();
+        keywords.push(rule.pattern.clone());
+        remaining_rules.content.retain(|rule| rule.rule_id != rule_id);
+    }
+
+    keywords
+}
+
 #[derive(Error, Debug)]
 enum InvitationError {
     #[error("No membership event found")]
@@ -2811,6 +3736,92 @@ impl Receipts {
     }
 }
 
+/// SC: The progress of loading the full member list of a room, as reported by
+/// [`Room::ensure_members`].
+#[derive(Debug, Clone)]
+pub enum MemberLoadProgress {
+    /// The member list is being fetched from the server.
+    Loading,
+    /// The full member list is available.
+    Loaded,
+    /// The member list couldn't be fetched.
+    Failed(Arc<Error>),
+}
+
+/// SC: The room that replaced a tombstoned room, see
+/// [`Room::successor_room`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuccessorRoom {
+    /// The ID of the new room.
+    pub room_id: OwnedRoomId,
+    /// The reason of the upgrade given in the tombstone, if any.
+    pub reason: Option<String>,
+    /// Whether the user has already joined the new room.
+    pub is_joined: bool,
+}
+
+/// SC: The state of the call in a room, as reported by
+/// [`Room::call_state_stream`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallState {
+    /// The participants of the call, ordered from the oldest membership to
+    /// the newest. A user can occur several times if they joined with several
+    /// devices.
+    pub participants: Vec<OwnedUserId>,
+    /// When the call started, i.e. when its oldest membership was created.
+    pub started_at: Option<MilliSecondsSinceUnixEpoch>,
+}
+
+impl CallState {
+    fn from_room_info(info: &RoomInfo) -> Self {
+        Self {
+            participants: info.active_room_call_participants(),
+            started_at: info.active_room_call_start_ts(),
+        }
+    }
+
+    /// Whether there is an ongoing call in the room.
+    pub fn is_active(&self) -> bool {
+        !self.participants.is_empty()
+    }
+}
+
+/// SC: A child of a space, as reported by [`Room::sorted_space_children`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpaceChild {
+    /// The ID of the child room.
+    pub room_id: OwnedRoomId,
+    /// The ordering string of the child in the space, if any.
+    pub order: Option<String>,
+    /// Whether the child is suggested to the members of the space.
+    pub suggested: bool,
+}
+
+/// SC: The encryption state of a room, as reported by
+/// [`Room::encryption_state_stream`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncryptionState {
+    /// The room is encrypted.
+    Encrypted,
+    /// The room isn't encrypted.
+    NotEncrypted,
+    /// The encryption state of the room hasn't been received from the server
+    /// yet.
+    Unknown,
+}
+
+impl EncryptionState {
+    fn from_room_info(info: &RoomInfo) -> Self {
+        if info.is_encrypted() {
+            Self::Encrypted
+        } else if info.is_encryption_state_synced() {
+            Self::NotEncrypted
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
 /// [Parent space](https://spec.matrix.org/v1.8/client-server-api/#mspaceparent-relationships)
 /// listed by a room, possibly validated by checking the space's state.
 #[derive(Debug)]
@@ -2965,6 +3976,52 @@ impl TryFrom<Int> for ReportedContentScore {
 #[error("out of range conversion attempted")]
 pub struct TryFromReportedContentScoreError(());
 
+/// SC: Errors that can happen when following the tombstone of a room, see
+/// [`Room::follow_tombstone`].
+#[derive(Debug, Error)]
+pub enum TombstoneError {
+    /// The room doesn't have a tombstone.
+    #[error("the room was not replaced")]
+    NotTombstoned,
+
+    /// The chain of room upgrades loops back to the given room.
+    #[error("the room upgrades loop back to {0}")]
+    UpgradeLoop(OwnedRoomId),
+}
+
+/// SC: Errors that can happen when enabling encryption in a room, see
+/// [`Room::enable_encryption_checked`].
+#[derive(Debug, Error)]
+pub enum EnableEncryptionError {
+    /// The user isn't allowed to send the `m.room.encryption` state event in
+    /// the room.
+    #[error("the user is not allowed to enable encryption in this room")]
+    NotAllowed,
+}
+
+/// SC: Errors that can happen when managing the children of a space.
+#[derive(Debug, Error)]
+pub enum SpaceChildError {
+    /// The ordering string isn't made of at most 50 printable ASCII
+    /// characters.
+    #[error("invalid space child order: {0:?}")]
+    InvalidOrder(String),
+
+    /// No servers to join the child through were given.
+    #[error("a space child needs at least one via server")]
+    MissingVia,
+
+    /// The room isn't a child of this space.
+    #[error("{0} is not a child of this space")]
+    NotAChild(OwnedRoomId),
+}
+
+/// Whether the given string is a valid `order` for a `m.space.child` event,
+/// i.e. at most 50 characters in the `\x20` (space) to `\x7E` (`~`) range.
+pub fn is_valid_space_child_order(order: &str) -> bool {
+    order.len() <= 50 && order.bytes().all(|b| (0x20..=0x7E).contains(&b))
+}
+
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
     use matrix_sdk_base::{store::ComposerDraftType, ComposerDraft, SessionMeta};
@@ -2977,7 +4034,7 @@ mod tests {
         Mock, MockServer, ResponseTemplate,
     };
 
-    use super::ReportedContentScore;
+    use super::{is_valid_space_child_order, ReportedContentScore};
     use crate::{
         config::RequestConfig,
         matrix_auth::{MatrixSession, MatrixSessionTokens},
@@ -3075,6 +4132,17 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_space_child_order_validation() {
+        assert!(is_valid_space_child_order(""));
+        assert!(is_valid_space_child_order("a"));
+        assert!(is_valid_space_child_order("~ !"));
+        assert!(is_valid_space_child_order(&"z".repeat(50)));
+        assert!(!is_valid_space_child_order(&"z".repeat(51)));
+        assert!(!is_valid_space_child_order("tab\t"));
+        assert!(!is_valid_space_child_order("é"));
+    }
+
     #[test]
     fn reported_content_score() {
         // i8
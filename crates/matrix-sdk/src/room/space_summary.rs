@@ -0,0 +1,171 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: Summaries of the children of a space, see
+//! [`Room::fetch_space_summary`](super::Room::fetch_space_summary).
+
+use std::collections::{HashMap, HashSet};
+
+use matrix_sdk_base::RoomState;
+use ruma::{
+    api::client::space::{get_hierarchy, SpaceHierarchyRoomsChunk},
+    events::room::join_rules::JoinRule,
+    room::RoomType,
+    space::SpaceRoomJoinRule,
+    OwnedMxcUri, OwnedRoomId,
+};
+
+use super::Room;
+use crate::Result;
+
+/// SC: Summary of a child of a space, merging the local state for joined
+/// children with what the homeserver reports via `/hierarchy` for the other
+/// ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpaceChildSummary {
+    /// The ID of the child room.
+    pub room_id: OwnedRoomId,
+    /// The child's name, if set.
+    pub name: Option<String>,
+    /// The child's topic, if set.
+    pub topic: Option<String>,
+    /// The MXC URI to the child's avatar, if set.
+    pub avatar_url: Option<OwnedMxcUri>,
+    /// The number of joined members.
+    pub num_joined_members: u64,
+    /// The join rule of the child.
+    pub join_rule: SpaceRoomJoinRule,
+    /// The room type (space, custom) or nothing, if it's a regular room.
+    pub room_type: Option<RoomType>,
+    /// Whether the current user has joined the child, in which case the
+    /// other fields reflect the live local state.
+    pub is_joined: bool,
+    /// The ordering string of the child in the space, if any.
+    pub order: Option<String>,
+    /// Whether the child is suggested to the members of the space.
+    pub suggested: bool,
+}
+
+impl SpaceChildSummary {
+    fn from_joined_room(room: &Room, order: Option<String>, suggested: bool) -> Self {
+        Self {
+            room_id: room.room_id().to_owned(),
+            name: room.cached_display_name().map(|name| name.to_string()).or_else(|| room.name()),
+            topic: room.topic(),
+            avatar_url: room.avatar_url(),
+            num_joined_members: room.joined_members_count(),
+            join_rule: space_room_join_rule(&room.join_rule()),
+            room_type: room.room_type(),
+            is_joined: true,
+            order,
+            suggested,
+        }
+    }
+
+    fn from_hierarchy_chunk(
+        chunk: SpaceHierarchyRoomsChunk,
+        order: Option<String>,
+        suggested: bool,
+    ) -> Self {
+        Self {
+            room_id: chunk.room_id,
+            name: chunk.name,
+            topic: chunk.topic,
+            avatar_url: chunk.avatar_url,
+            num_joined_members: chunk.num_joined_members.into(),
+            join_rule: chunk.join_rule,
+            room_type: chunk.room_type,
+            is_joined: false,
+            order,
+            suggested,
+        }
+    }
+}
+
+/// The join rule of a space child, as it would be reported by `/hierarchy`.
+fn space_room_join_rule(join_rule: &JoinRule) -> SpaceRoomJoinRule {
+    match join_rule {
+        JoinRule::Public => SpaceRoomJoinRule::Public,
+        JoinRule::Knock => SpaceRoomJoinRule::Knock,
+        JoinRule::Invite => SpaceRoomJoinRule::Invite,
+        JoinRule::Private => SpaceRoomJoinRule::Private,
+        JoinRule::Restricted(_) => SpaceRoomJoinRule::Restricted,
+        JoinRule::KnockRestricted(_) => SpaceRoomJoinRule::KnockRestricted,
+        other => SpaceRoomJoinRule::from(other.as_str()),
+    }
+}
+
+pub(super) async fn fetch_space_summary(
+    room: &Room,
+    suggested_only: bool,
+) -> Result<Vec<SpaceChildSummary>> {
+    let client = room.client();
+
+    // Order and suggested flag as present in our local `m.space.child` events.
+    let local_children: HashMap<OwnedRoomId, (Option<String>, bool)> = room
+        .sorted_space_children()
+        .into_iter()
+        .map(|child| (child.room_id, (child.order, child.suggested)))
+        .collect();
+
+    let mut summaries = Vec::new();
+    let mut seen = HashSet::new();
+    let mut from = None;
+
+    loop {
+        let mut request = get_hierarchy::v1::Request::new(room.room_id().to_owned());
+        request.from = from.take();
+        request.max_depth = Some(1u32.into());
+        request.suggested_only = suggested_only;
+
+        let response = client.send(request, None).await?;
+
+        for chunk in response.rooms {
+            // The space itself is part of the hierarchy, skip it.
+            if chunk.room_id == room.room_id() || !seen.insert(chunk.room_id.clone()) {
+                continue;
+            }
+
+            let (order, suggested) =
+                local_children.get(&chunk.room_id).cloned().unwrap_or((None, suggested_only));
+
+            match client.get_room(&chunk.room_id) {
+                Some(child) if child.state() == RoomState::Joined => {
+                    summaries.push(SpaceChildSummary::from_joined_room(&child, order, suggested));
+                }
+                _ => {
+                    summaries.push(SpaceChildSummary::from_hierarchy_chunk(chunk, order, suggested))
+                }
+            }
+        }
+
+        match response.next_batch {
+            Some(next_batch) => from = Some(next_batch),
+            None => break,
+        }
+    }
+
+    // The server may omit children it can't see, keep the joined ones we know
+    // about locally.
+    for (room_id, (order, suggested)) in local_children {
+        if seen.contains(&room_id) || (suggested_only && !suggested) {
+            continue;
+        }
+        if let Some(child) = client.get_room(&room_id).filter(|r| r.state() == RoomState::Joined) {
+            summaries.push(SpaceChildSummary::from_joined_room(&child, order, suggested));
+        }
+    }
+
+    Ok(summaries)
+}
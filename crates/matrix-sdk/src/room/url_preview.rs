@@ -0,0 +1,228 @@
+// Copyright 2024 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SC: Previews of the URLs of messages, see
+//! [`Room::get_url_preview`](super::Room::get_url_preview).
+
+use std::collections::HashMap;
+
+use ruma::{
+    api::client::{error::ErrorKind, media::get_media_preview},
+    events::{GlobalAccountDataEventType, RoomAccountDataEventType},
+    OwnedMxcUri, UInt,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::Room;
+use crate::{Error, Result};
+
+/// The type of the global account data that disables URL previews.
+const PREVIEW_URLS_GLOBAL_TYPE: &str = "org.matrix.preview_urls";
+
+/// The type of the room account data that disables URL previews in a room.
+const PREVIEW_URLS_ROOM_TYPE: &str = "org.matrix.room.preview_urls";
+
+/// The maximum number of previews kept in the cache of a client.
+const MAX_CACHED_PREVIEWS: usize = 256;
+
+/// SC: The preview of a URL, as generated by the homeserver from the
+/// OpenGraph metadata of the page.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct UrlPreview {
+    /// The title of the page.
+    #[serde(rename = "og:title")]
+    pub title: Option<String>,
+
+    /// The description of the page.
+    #[serde(rename = "og:description")]
+    pub description: Option<String>,
+
+    /// The name of the site of the page.
+    #[serde(rename = "og:site_name")]
+    pub site_name: Option<String>,
+
+    /// The image of the page, uploaded to the homeserver.
+    #[serde(rename = "og:image")]
+    pub image: Option<OwnedMxcUri>,
+
+    /// The width of the image, in pixels.
+    #[serde(rename = "og:image:width")]
+    pub image_width: Option<UInt>,
+
+    /// The height of the image, in pixels.
+    #[serde(rename = "og:image:height")]
+    pub image_height: Option<UInt>,
+
+    /// The size of the image, in bytes.
+    #[serde(rename = "matrix:image:size")]
+    pub image_size: Option<UInt>,
+}
+
+/// SC: Errors that can happen when getting the preview of a URL.
+#[derive(Debug, Error)]
+pub enum UrlPreviewError {
+    /// URL previews are disabled in the room, by the user.
+    #[error("URL previews are disabled in this room")]
+    Disabled,
+
+    /// The homeserver doesn't generate URL previews.
+    #[error("the homeserver doesn't support URL previews")]
+    Unsupported,
+}
+
+/// The content of the `org.matrix.preview_urls` and
+/// `org.matrix.room.preview_urls` account data.
+#[derive(Deserialize)]
+struct PreviewUrlsContent {
+    #[serde(default)]
+    disable: bool,
+}
+
+/// The client-wide cache of URL previews.
+#[derive(Debug, Default)]
+pub(crate) struct UrlPreviewCache {
+    previews: HashMap<String, UrlPreview>,
+}
+
+impl UrlPreviewCache {
+    fn get(&self, url: &str) -> Option<UrlPreview> {
+        self.previews.get(url).cloned()
+    }
+
+    fn insert(&mut self, url: String, preview: UrlPreview) {
+        // Previews are cheap to fetch again, so don't bother with an LRU.
+        if self.previews.len() >= MAX_CACHED_PREVIEWS {
+            self.previews.clear();
+        }
+
+        self.previews.insert(url, preview);
+    }
+}
+
+/// Whether the user enabled URL previews in `room`.
+///
+/// The setting of the room takes precedence over the global one. Without any
+/// setting, previews are enabled except in encrypted rooms, since they would
+/// leak the URLs to the homeserver.
+pub(super) async fn are_url_previews_enabled(room: &Room) -> Result<bool> {
+    let room_setting = room
+        .account_data(RoomAccountDataEventType::from(PREVIEW_URLS_ROOM_TYPE))
+        .await?
+        .and_then(|raw| raw.get_field::<PreviewUrlsContent>("content").ok().flatten());
+
+    let setting = match room_setting {
+        Some(setting) => Some(setting),
+        None => room
+            .client
+            .account()
+            .account_data_raw(GlobalAccountDataEventType::from(PREVIEW_URLS_GLOBAL_TYPE))
+            .await?
+            .and_then(|raw| raw.deserialize_as::<PreviewUrlsContent>().ok()),
+    };
+
+    match setting {
+        Some(setting) => Ok(!setting.disable),
+        None => Ok(!room.is_encrypted().await?),
+    }
+}
+
+pub(super) async fn get_url_preview(room: &Room, url: &str) -> Result<UrlPreview> {
+    // The cache is shared by all the rooms, so a preview fetched for another room
+    // must not leak into a room where previews are disabled.
+    if !are_url_previews_enabled(room).await? {
+        return Err(UrlPreviewError::Disabled.into());
+    }
+
+    if let Some(preview) = room.client.inner.url_previews.lock().unwrap().get(url) {
+        return Ok(preview);
+    }
+
+    let request = get_media_preview::v3::Request::new(url.to_owned());
+    let response = match room.client.send(request, None).await {
+        Ok(response) => response,
+        Err(error) => {
+            let error = Error::from(error);
+            return Err(if is_unsupported(&error) {
+                UrlPreviewError::Unsupported.into()
+            } else {
+                error
+            });
+        }
+    };
+
+    let preview = match response.data {
+        Some(data) => serde_json::from_str(data.get())?,
+        None => UrlPreview::default(),
+    };
+
+    room.client.inner.url_previews.lock().unwrap().insert(url.to_owned(), preview.clone());
+
+    Ok(preview)
+}
+
+/// Whether `error` means that the homeserver doesn't know the preview
+/// endpoint.
+fn is_unsupported(error: &Error) -> bool {
+    if error.client_api_error_kind() == Some(&ErrorKind::Unrecognized) {
+        return true;
+    }
+
+    // Some homeservers don't use the `M_UNRECOGNIZED` error code.
+    error
+        .as_client_api_error()
+        .is_some_and(|error| error.status_code == 404 || error.status_code == 405)
+}
+
+#[cfg(test)]
+mod tests {
+    use ruma::{mxc_uri, uint};
+    use serde_json::json;
+
+    use super::{UrlPreview, UrlPreviewCache, MAX_CACHED_PREVIEWS};
+
+    #[test]
+    fn test_deserialize_preview() {
+        let preview: UrlPreview = serde_json::from_value(json!({
+            "og:title": "Matrix",
+            "og:description": "An open network",
+            "og:image": "mxc://localhost/logo",
+            "og:image:width": 48,
+            "matrix:image:size": 1024,
+            "og:type": "website",
+        }))
+        .unwrap();
+
+        assert_eq!(preview.title.as_deref(), Some("Matrix"));
+        assert_eq!(preview.description.as_deref(), Some("An open network"));
+        assert_eq!(preview.image.as_deref(), Some(mxc_uri!("mxc://localhost/logo")));
+        assert_eq!(preview.image_width, Some(uint!(48)));
+        assert_eq!(preview.image_height, None);
+        assert_eq!(preview.image_size, Some(uint!(1024)));
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let mut cache = UrlPreviewCache::default();
+
+        for i in 0..MAX_CACHED_PREVIEWS {
+            cache.insert(format!("https://localhost/{i}"), UrlPreview::default());
+        }
+        assert!(cache.get("https://localhost/0").is_some());
+
+        cache.insert("https://localhost/new".to_owned(), UrlPreview::default());
+        assert!(cache.get("https://localhost/0").is_none());
+        assert!(cache.get("https://localhost/new").is_some());
+    }
+}
@@ -20,7 +20,7 @@
 
 use matrix_sdk_base::{RoomInfo, RoomState};
 use ruma::{
-    api::client::{membership::joined_members, state::get_state_events},
+    api::client::{membership::joined_members, space::get_hierarchy, state::get_state_events},
     events::room::{history_visibility::HistoryVisibility, join_rules::JoinRule},
     room::RoomType,
     space::SpaceRoomJoinRule,
@@ -29,7 +29,7 @@ use ruma::{
 use tokio::try_join;
 use tracing::{instrument, warn};
 
-use crate::{Client, Room};
+use crate::{Client, Error, Room};
 
 /// The preview of a room, be it invited/joined/left, or not.
 #[derive(Debug)]
@@ -130,6 +130,15 @@ impl RoomPreview {
             }
         }
 
+        // SC: The space hierarchy endpoint also summarizes rooms that aren't spaces,
+        // as long as they're public or the user could join them.
+        match Self::from_space_hierarchy(client, &room_id).await {
+            Ok(res) => return Ok(res),
+            Err(err) => {
+                warn!("error when previewing room from the space hierarchy endpoint: {err}");
+            }
+        }
+
         // TODO: (optimization) Use the room search directory, if available:
         // - if the room directory visibility is public,
         // - then use a public room filter set to this room id
@@ -180,6 +189,43 @@ impl RoomPreview {
         })
     }
 
+    /// SC: Get a [`RoomPreview`] using the summary of the room returned by the
+    /// space hierarchy endpoint.
+    ///
+    /// This works for rooms that aren't spaces too, but only if the user could
+    /// join or peek into the room.
+    ///
+    /// This method is exposed for testing purposes; clients should prefer
+    /// `Client::get_room_preview` in general over this.
+    pub async fn from_space_hierarchy(client: &Client, room_id: &RoomId) -> crate::Result<Self> {
+        let mut request = get_hierarchy::v1::Request::new(room_id.to_owned());
+        request.max_depth = Some(0u32.into());
+        request.limit = Some(1u32.into());
+
+        let response = client.send(request, None).await?;
+
+        let chunk = response
+            .rooms
+            .into_iter()
+            .find(|chunk| chunk.room_id == room_id)
+            .ok_or(Error::InsufficientData)?;
+
+        let state = client.get_room(room_id).map(|room| room.state());
+
+        Ok(RoomPreview {
+            room_id: chunk.room_id,
+            canonical_alias: chunk.canonical_alias,
+            name: chunk.name,
+            topic: chunk.topic,
+            avatar_url: chunk.avatar_url,
+            num_joined_members: chunk.num_joined_members.into(),
+            room_type: chunk.room_type,
+            join_rule: chunk.join_rule,
+            is_world_readable: chunk.world_readable,
+            state,
+        })
+    }
+
     /// Get a [`RoomPreview`] using the room state endpoint.
     ///
     /// This is always available on a remote server, but will only work if one
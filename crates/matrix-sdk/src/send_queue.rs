@@ -20,13 +20,15 @@ use std::{
         atomic::{AtomicBool, Ordering},
         Arc, RwLock as SyncRwLock,
     },
+    time::Duration,
 };
 
-use matrix_sdk_base::RoomState;
+use matrix_sdk_base::{instant::Instant, RoomState};
 use matrix_sdk_common::executor::{spawn, JoinHandle};
 use ruma::{
-    events::AnyMessageLikeEventContent, OwnedEventId, OwnedRoomId, OwnedTransactionId,
-    TransactionId,
+    api::client::error::{ErrorKind, RetryAfter},
+    events::AnyMessageLikeEventContent,
+    MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedRoomId, OwnedTransactionId, TransactionId, UInt,
 };
 use tokio::sync::{broadcast, Notify, RwLock};
 use tracing::{debug, error, info, instrument, trace, warn};
@@ -254,6 +256,10 @@ impl RoomSendQueue {
     /// retried a few times. If sending failed, the entire client's sending
     /// queue will be disabled, and it will need to be manually re-enabled
     /// by the caller.
+    ///
+    /// SC: If sending failed because the user is rate-limited, the queue stays
+    /// enabled and sending is retried after the delay requested by the
+    /// homeserver instead, or after a few seconds if it didn't request any.
     pub async fn send(
         &self,
         content: AnyMessageLikeEventContent,
@@ -313,6 +319,10 @@ impl RoomSendQueue {
     ) {
         info!("spawned the sending task");
 
+        // SC: Until when the homeserver asked not to send again, after having been
+        // rate-limited.
+        let mut rate_limited_until: Option<Instant> = None;
+
         loop {
             // A request to shut down should be preferred above everything else.
             if is_dropping.load(Ordering::SeqCst) {
@@ -320,6 +330,22 @@ impl RoomSendQueue {
                 break;
             }
 
+            if let Some(until) = rate_limited_until {
+                let now = Instant::now();
+                if now < until {
+                    let delay = until - now;
+                    trace!(?delay, "rate-limited, sleeping");
+                    // Wake up early to check whether the queue is shutting down; the rest of
+                    // the delay is waited for at the next iteration otherwise.
+                    tokio::select! {
+                        _ = sleep(delay) => {}
+                        _ = notifier.notified() => {}
+                    }
+                    continue;
+                }
+                rate_limited_until = None;
+            }
+
             if !locally_enabled.load(Ordering::SeqCst) {
                 trace!("not enabled, sleeping");
                 // Wait for an explicit wakeup.
@@ -362,14 +388,27 @@ impl RoomSendQueue {
                 }
 
                 Err(err) => {
-                    let is_recoverable = if let crate::Error::Http(ref http_err) = err {
-                        // All transient errors are recoverable.
-                        matches!(http_err.retry_kind(), RetryKind::Transient { .. })
+                    let retry_kind = if let crate::Error::Http(ref http_err) = err {
+                        Some(http_err.retry_kind())
                     } else {
-                        false
+                        None
                     };
 
-                    if is_recoverable {
+                    // SC: The delay is only set for rate-limiting errors.
+                    let retry_after = rate_limit_delay(&err);
+
+                    // All transient errors are recoverable.
+                    let is_recoverable = retry_after.is_some()
+                        || matches!(retry_kind, Some(RetryKind::Transient { .. }));
+
+                    if let Some(delay) = retry_after {
+                        warn!(txn_id = %queued_event.transaction_id, ?delay, "Rate-limited when sending event, retrying later");
+
+                        // Keep the event in the queue and the queue enabled, but only send again
+                        // after the requested delay.
+                        queue.mark_as_not_being_sent(&queued_event.transaction_id).await;
+                        rate_limited_until = Some(Instant::now() + delay);
+                    } else if is_recoverable {
                         warn!(txn_id = %queued_event.transaction_id, error = ?err, "Recoverable error when sending event: {err}, disabling send queue");
 
                         // In this case, we intentionally keep the event in the queue, but mark it
@@ -395,16 +434,26 @@ impl RoomSendQueue {
 
                     let error = Arc::new(err);
 
-                    let _ = global_error_reporter.send(SendQueueRoomError {
-                        room_id: room.room_id().to_owned(),
-                        error: error.clone(),
-                        is_recoverable,
+                    // The room only disables itself if it doesn't retry on its own.
+                    if retry_after.is_none() {
+                        let _ = global_error_reporter.send(SendQueueRoomError {
+                            room_id: room.room_id().to_owned(),
+                            error: error.clone(),
+                            is_recoverable,
+                        });
+                    }
+
+                    let retry_at = retry_after.map(|delay| {
+                        let delay = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX);
+                        let now = MilliSecondsSinceUnixEpoch::now().0;
+                        MilliSecondsSinceUnixEpoch(now.saturating_add(UInt::new_saturating(delay)))
                     });
 
                     let _ = updates.send(RoomSendQueueUpdate::SendError {
                         transaction_id: queued_event.transaction_id,
                         error,
                         is_recoverable,
+                        retry_at,
                     });
                 }
             }
@@ -633,6 +682,11 @@ pub enum RoomSendQueueUpdate {
         /// while an unrecoverable error will be parked, until the user
         /// decides to cancel sending it.
         is_recoverable: bool,
+        /// SC: When sending the event will be retried automatically, if the
+        /// error is a rate-limiting error.
+        ///
+        /// In this case, the room's send queue isn't disabled.
+        retry_at: Option<MilliSecondsSinceUnixEpoch>,
     },
 
     /// The event has been sent to the server, and the query returned
@@ -684,6 +738,41 @@ impl AbortSendHandle {
     }
 }
 
+/// SC: The delay used when the homeserver rate-limits the user without telling
+/// how long to wait.
+const DEFAULT_RATE_LIMIT_DELAY: Duration = Duration::from_secs(5);
+
+/// SC: The delay to wait for before sending again, if the error is because the
+/// user is rate-limited.
+fn rate_limit_delay(error: &crate::Error) -> Option<Duration> {
+    let Some(ErrorKind::LimitExceeded { retry_after }) = error.client_api_error_kind() else {
+        return None;
+    };
+
+    Some(match retry_after {
+        Some(RetryAfter::Delay(delay)) => *delay,
+        Some(RetryAfter::DateTime(time)) => {
+            // A date in the past, or that can't be represented, means sending again right
+            // away.
+            let retry_at = MilliSecondsSinceUnixEpoch::from_system_time(*time)
+                .map(|time| u64::from(time.get()))
+                .unwrap_or_default();
+            let now = u64::from(MilliSecondsSinceUnixEpoch::now().get());
+            Duration::from_millis(retry_at.saturating_sub(now))
+        }
+        None => DEFAULT_RATE_LIMIT_DELAY,
+    })
+}
+
+/// SC: Wait for `duration`.
+async fn sleep(duration: Duration) {
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::sleep(duration).await;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(duration).await;
+}
+
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
     use std::{sync::Arc, time::Duration};
@@ -30,7 +30,7 @@ impl Client {
         let response = self.base_client().process_sliding_sync(response, &()).await?;
 
         tracing::debug!("done processing on base_client");
-        self.call_sync_response_handlers(&response).await?;
+        self.call_sync_response_handlers(&response, false).await?;
 
         Ok(response)
     }
@@ -58,11 +58,18 @@ pub(crate) struct SlidingSyncResponseProcessor<'a> {
     to_device_events: Vec<Raw<AnyToDeviceEvent>>,
     response: Option<SyncResponse>,
     rooms: &'a BTreeMap<OwnedRoomId, SlidingSyncRoom>,
+    /// SC: Whether this is the response to the first request of the sliding
+    /// sync.
+    is_initial_sync: bool,
 }
 
 impl<'a> SlidingSyncResponseProcessor<'a> {
-    pub fn new(client: Client, rooms: &'a BTreeMap<OwnedRoomId, SlidingSyncRoom>) -> Self {
-        Self { client, to_device_events: Vec::new(), response: None, rooms }
+    pub fn new(
+        client: Client,
+        rooms: &'a BTreeMap<OwnedRoomId, SlidingSyncRoom>,
+        is_initial_sync: bool,
+    ) -> Self {
+        Self { client, to_device_events: Vec::new(), response: None, rooms, is_initial_sync }
     }
 
     #[cfg(feature = "e2e-encryption")]
@@ -95,7 +102,7 @@ impl<'a> SlidingSyncResponseProcessor<'a> {
 
         response.to_device.extend(self.to_device_events);
 
-        self.client.call_sync_response_handlers(&response).await?;
+        self.client.call_sync_response_handlers(&response, self.is_initial_sync).await?;
 
         Ok(response)
     }
@@ -331,8 +331,11 @@ impl SlidingSync {
             let _sync_lock = self.inner.client.base_client().sync_lock().lock().await;
 
             let rooms = &*self.inner.rooms.read().await;
-            let mut response_processor =
-                SlidingSyncResponseProcessor::new(self.inner.client.clone(), rooms);
+            let mut response_processor = SlidingSyncResponseProcessor::new(
+                self.inner.client.clone(),
+                rooms,
+                position.pos.is_none(),
+            );
 
             #[cfg(feature = "e2e-encryption")]
             if self.is_e2ee_enabled() {
@@ -26,9 +26,12 @@ use matrix_sdk_base::{
     instant::Instant,
     sync::SyncResponse as BaseSyncResponse,
 };
+use matrix_sdk_common::executor::spawn;
 use ruma::{
     api::client::sync::sync_events::{self, v3::InvitedRoom},
-    events::{presence::PresenceEvent, AnyGlobalAccountDataEvent, AnyToDeviceEvent},
+    events::{
+        presence::PresenceEvent, AnyGlobalAccountDataEvent, AnyToDeviceEvent, StateEventType,
+    },
     serde::Raw,
     OwnedRoomId, RoomId,
 };
@@ -130,13 +133,17 @@ impl Client {
         &self,
         response: sync_events::v3::Response,
     ) -> Result<BaseSyncResponse> {
+        // SC: Without a sync token, the response describes the rooms as they are, not
+        // what happened since the last sync.
+        let is_initial_sync = self.sync_token().await.is_none();
+
         let response = Box::pin(self.base_client().receive_sync_response(response)).await?;
 
         // Some new keys might have been received, so trigger a backup if needed.
         #[cfg(feature = "e2e-encryption")]
         self.encryption().backups().maybe_trigger_backup();
 
-        self.call_sync_response_handlers(&response).await?;
+        self.call_sync_response_handlers(&response, is_initial_sync).await?;
 
         Ok(response)
     }
@@ -147,10 +154,15 @@ impl Client {
     /// At this point, the sync response's data has been taken into account and
     /// persisted in the store, if needs be. This function is only calling
     /// the event, room update and notification handlers.
+    ///
+    /// SC: `is_initial_sync` is whether the response is the first one of the
+    /// sync, in which case the tombstones it contains are not followed, even
+    /// if [`Client::set_auto_follow_tombstones`] is enabled.
     #[tracing::instrument(skip(self, response))]
     pub(crate) async fn call_sync_response_handlers(
         &self,
         response: &BaseSyncResponse,
+        is_initial_sync: bool,
     ) -> Result<()> {
         let BaseSyncResponse { rooms, presence, account_data, to_device, notifications } = response;
 
@@ -183,6 +195,15 @@ impl Client {
                 ambiguity_changes: _,
             } = room_info;
 
+            if self.auto_follows_tombstones() && !is_initial_sync && has_new_tombstone(timeline) {
+                let room = room.clone();
+                spawn(async move {
+                    if let Err(error) = room.follow_tombstone().await {
+                        warn!(room_id = ?room.room_id(), "Failed to follow the tombstone: {error}");
+                    }
+                });
+            }
+
             let room = Some(&room);
             self.handle_sync_events(HandlerKind::RoomAccountData, room, account_data).await?;
             self.handle_sync_state_events(room, state).await?;
@@ -310,3 +331,17 @@ impl Client {
         *last_sync_time = Some(now);
     }
 }
+
+/// SC: Whether the timeline of a room received a new `m.room.tombstone` event.
+///
+/// The state events, and the timeline of a gappy sync, can contain a tombstone
+/// sent a long time ago, that the user might not want to follow anymore.
+fn has_new_tombstone(timeline: &Timeline) -> bool {
+    !timeline.limited
+        && timeline.events.iter().any(|event| {
+            event.event.get_field::<StateEventType>("type").ok().flatten()
+                == Some(StateEventType::RoomTombstone)
+                && event.event.get_field::<String>("state_key").ok().flatten().as_deref()
+                    == Some("")
+        })
+}
@@ -7,6 +7,7 @@ use matrix_sdk::{
     media::{MediaFormat, MediaRequest, MediaThumbnailSize},
     sync::RoomUpdate,
     test_utils::no_retry_test_client_with_server,
+    JoinByLinkError,
 };
 use matrix_sdk_base::{sync::RoomUpdates, RoomState};
 use matrix_sdk_test::{
@@ -15,7 +16,7 @@ use matrix_sdk_test::{
         self,
         sync::{MIXED_INVITED_ROOM_ID, MIXED_JOINED_ROOM_ID, MIXED_LEFT_ROOM_ID, MIXED_SYNC},
     },
-    JoinedRoomBuilder, SyncResponseBuilder, DEFAULT_TEST_ROOM_ID,
+    JoinedRoomBuilder, LeftRoomBuilder, SyncResponseBuilder, DEFAULT_TEST_ROOM_ID,
 };
 use ruma::{
     api::client::{
@@ -36,13 +37,14 @@ use ruma::{
     },
     mxc_uri, room_id,
     serde::Raw,
+    space::SpaceRoomJoinRule,
     uint, user_id, OwnedUserId,
 };
 use serde_json::{json, Value as JsonValue};
 use stream_assert::{assert_next_matches, assert_pending};
 use tokio_stream::wrappers::BroadcastStream;
 use wiremock::{
-    matchers::{header, method, path, path_regex},
+    matchers::{header, method, path, path_regex, query_param},
     Mock, Request, ResponseTemplate,
 };
 
@@ -222,6 +224,143 @@ async fn test_join_room_by_id_or_alias() {
     );
 }
 
+#[async_test]
+async fn test_join_by_link_with_alias() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/join/%23room:example\.org"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "room_id": "!room:example.org" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let room = client.join_by_link("https://matrix.to/#/%23room:example.org").await.unwrap();
+    assert_eq!(room.room_id(), room_id!("!room:example.org"));
+}
+
+#[async_test]
+async fn test_join_by_link_with_room_id() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/join/"))
+        .and(query_param("server_name", "example.org"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "room_id": "!room:example.org" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let room = client.join_by_link("matrix:roomid/room:example.org?via=example.org").await.unwrap();
+    assert_eq!(room.room_id(), room_id!("!room:example.org"));
+
+    // Links that don't point to a room are rejected before any request.
+    assert_let!(
+        Err(JoinByLinkError::NotARoom) = client.join_by_link("matrix:u/alice:example.org").await
+    );
+    assert_let!(Err(JoinByLinkError::InvalidLink) = client.join_by_link("not a link").await);
+}
+
+#[async_test]
+async fn test_join_by_link_restricted_room() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    // The user is in a room whose members are allowed to join the restricted room,
+    // with a member on another server.
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(room_id!("!allowed:localhost")).add_state_bulk([
+            sync_state_event!({
+                "content": { "membership": "join" },
+                "event_id": "$bob_join",
+                "origin_server_ts": 151800140,
+                "sender": "@bob:other.org",
+                "state_key": "@bob:other.org",
+                "type": "m.room.member",
+            }),
+        ]),
+    );
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::default()).await.unwrap();
+    server.reset().await;
+
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/state/m\.room\.join_rules"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "join_rule": "restricted",
+            "allow": [{ "type": "m.room_membership", "room_id": "!allowed:localhost" }],
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Joining works through the server of the member of the allowed room.
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/join/"))
+        .and(query_param("server_name", "other.org"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "room_id": "!restricted:example.org" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/join/"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "errcode": "M_UNABLE_TO_AUTHORISE_JOIN",
+            "error": "No server can authorise the join",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let room = client
+        .join_by_link("https://matrix.to/#/!restricted:example.org?via=example.org")
+        .await
+        .unwrap();
+    assert_eq!(room.room_id(), room_id!("!restricted:example.org"));
+}
+
+#[async_test]
+async fn test_join_by_link_banned() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    let room_id = room_id!("!banned:example.org");
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_left_room(LeftRoomBuilder::new(room_id).add_state_bulk([sync_state_event!({
+        "content": { "membership": "ban" },
+        "event_id": "$ban",
+        "origin_server_ts": 151800140,
+        "sender": "@bob:example.org",
+        "state_key": "@example:localhost",
+        "type": "m.room.member",
+    })]));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::default()).await.unwrap();
+    server.reset().await;
+
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/join/"))
+        .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+            "errcode": "M_FORBIDDEN",
+            "error": "You are banned from this room",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert_let!(
+        Err(JoinByLinkError::Banned) =
+            client.join_by_link("matrix:roomid/banned:example.org?via=example.org").await
+    );
+}
+
 #[async_test]
 async fn test_room_search_all() {
     let (client, server) = no_retry_test_client_with_server().await;
@@ -1260,3 +1399,38 @@ async fn test_test_ambiguity_changes() {
 
     assert_pending!(updates);
 }
+
+#[async_test]
+async fn test_room_preview_from_space_hierarchy() {
+    let (client, server) = logged_in_client_with_server().await;
+    let room_id = room_id!("!unknown:localhost");
+
+    // The room summary endpoint isn't available, so the hierarchy is used.
+    Mock::given(method("GET"))
+        .and(path(format!("/_matrix/client/v1/rooms/{room_id}/hierarchy")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "rooms": [{
+                "room_id": room_id,
+                "name": "Public room",
+                "topic": "Come in",
+                "num_joined_members": 42,
+                "join_rule": "public",
+                "world_readable": true,
+                "guest_can_join": false,
+                "children_state": [],
+            }]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let preview = client.get_room_preview(room_id.into(), Vec::new()).await.unwrap();
+
+    assert_eq!(preview.room_id, room_id);
+    assert_eq!(preview.name.as_deref(), Some("Public room"));
+    assert_eq!(preview.topic.as_deref(), Some("Come in"));
+    assert_eq!(preview.num_joined_members, 42);
+    assert_eq!(preview.join_rule, SpaceRoomJoinRule::Public);
+    assert!(preview.is_world_readable);
+    assert!(preview.state.is_none());
+}
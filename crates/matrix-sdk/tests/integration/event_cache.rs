@@ -219,14 +219,9 @@ async fn test_ignored_unignored() {
     client.sync_once(Default::default()).await.unwrap();
     server.reset().await;
 
-    // It does receive one update,
-    let update = timeout(Duration::from_secs(2), subscriber.recv())
-        .await
-        .expect("timeout after receiving a sync update")
-        .expect("should've received a room event cache update");
-
-    // Which notifies about the clear.
-    assert_matches!(update, RoomEventCacheUpdate::Clear);
+    // The rooms aren't cleared, so that timelines can restore the events of the
+    // ignored users once they're unignored.
+    assert!(subscriber.is_empty());
 
     // Receiving new events still works.
     sync_builder.add_joined_room(
@@ -247,12 +242,13 @@ async fn test_ignored_unignored() {
     assert_eq!(events.len(), 1);
     assert_event_matches_msg(&events[0], "i don't like this dexter");
 
-    // The other room has been cleared too.
+    // The events of the other room are kept too.
     {
         let room = client.get_room(other_room_id).unwrap();
         let (room_event_cache, _drop_handles) = room.event_cache().await.unwrap();
         let (events, _) = room_event_cache.subscribe().await.unwrap();
-        assert!(events.is_empty());
+        assert_eq!(events.len(), 1);
+        assert_event_matches_msg(&events[0], "demat!");
     }
 
     // That's all, folks!
@@ -2,9 +2,10 @@
 #![cfg(not(target_arch = "wasm32"))]
 
 use matrix_sdk::{config::SyncSettings, test_utils::logged_in_client_with_server, Client, Room};
-use matrix_sdk_test::{test_json, SyncResponseBuilder};
+use matrix_sdk_test::{test_json, StateTestEvent, SyncResponseBuilder};
 use ruma::RoomId;
 use serde::Serialize;
+use serde_json::json;
 use wiremock::{
     matchers::{header, method, path, path_regex, query_param, query_param_is_missing},
     Mock, MockGuard, MockServer, ResponseTemplate,
@@ -125,3 +126,15 @@ async fn mock_sync_with_new_room<F: Fn(&mut SyncResponseBuilder)>(
 
     client.get_room(room_id).expect("we should find the room we just sync'd from")
 }
+
+/// An `m.room.name` state event setting the name of the room to `name`.
+fn room_name_event(name: &str) -> StateTestEvent {
+    StateTestEvent::Custom(json!({
+        "content": { "name": name },
+        "event_id": format!("$name_{name}"),
+        "origin_server_ts": 151800140,
+        "sender": "@example:localhost",
+        "state_key": "",
+        "type": "m.room.name",
+    }))
+}
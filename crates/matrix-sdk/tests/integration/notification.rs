@@ -3,7 +3,7 @@ use matrix_sdk::{config::SyncSettings, sync::Notification};
 use matrix_sdk_base::deserialized_responses::RawAnySyncOrStrippedTimelineEvent;
 use matrix_sdk_test::{
     async_test, stripped_state_event, sync_state_event, sync_timeline_event, test_json,
-    InvitedRoomBuilder, JoinedRoomBuilder, SyncResponseBuilder,
+    GlobalAccountDataTestEvent, InvitedRoomBuilder, JoinedRoomBuilder, SyncResponseBuilder,
 };
 use ruma::{
     events::{room::message::RoomMessageEventContent, Mentions, StateEventType},
@@ -11,6 +11,7 @@ use ruma::{
     serde::Raw,
     OwnedRoomId,
 };
+use serde_json::json;
 use stream_assert::{assert_pending, assert_ready};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
@@ -155,3 +156,23 @@ async fn notifications_invite() {
 
     assert_pending!(receiver_stream);
 }
+
+#[async_test]
+async fn dnd_is_read_from_account_data() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    assert!(!client.account().is_dnd_enabled().await.unwrap());
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_global_account_data_event(GlobalAccountDataTestEvent::Custom(json!({
+        "content": {
+            "enabled": true
+        },
+        "type": "chat.schildi.dnd",
+    })));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::default()).await.unwrap();
+
+    assert!(client.account().is_dnd_enabled().await.unwrap());
+    assert!(client.notification_settings().await.is_dnd_enabled().await);
+}
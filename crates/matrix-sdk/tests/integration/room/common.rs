@@ -20,7 +20,7 @@ use wiremock::{
     Mock, ResponseTemplate,
 };
 
-use crate::{logged_in_client_with_server, mock_sync};
+use crate::{logged_in_client_with_server, mock_sync, mock_sync_with_new_room};
 
 #[async_test]
 async fn test_user_presence() {
@@ -93,6 +93,55 @@ async fn test_room_names() {
     );
 }
 
+#[async_test]
+async fn test_recompute_display_name_fetches_members() {
+    let (client, server) = logged_in_client_with_server().await;
+    let room_id = &*DEFAULT_TEST_ROOM_ID;
+
+    // A group DM we just joined, without heroes nor members yet.
+    let room = mock_sync_with_new_room(
+        |builder| {
+            builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+        },
+        &client,
+        &server,
+        room_id,
+    )
+    .await;
+
+    let member = |user_id: &str, display_name: &str| {
+        json!({
+            "content": { "displayname": display_name, "membership": "join" },
+            "event_id": format!("$member_{display_name}"),
+            "origin_server_ts": 151800140,
+            "room_id": room_id,
+            "sender": user_id,
+            "state_key": user_id,
+            "type": "m.room.member",
+        })
+    };
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/members"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "chunk": [
+                member("@example:localhost", "example"),
+                member("@bob:localhost", "Bob"),
+                member("@carol:localhost", "Carol"),
+            ]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let display_name = room.recompute_display_name().await.unwrap();
+    assert_eq!(display_name, DisplayName::Calculated("Bob, Carol".to_owned()));
+    assert_eq!(room.cached_display_name(), Some(display_name));
+
+    // The members are synced now, so they aren't fetched again.
+    room.recompute_display_name().await.unwrap();
+}
+
 #[async_test]
 async fn test_state_event_getting() {
     let (client, server) = logged_in_client_with_server().await;
@@ -505,6 +554,39 @@ async fn test_room_event_permalink() {
     );
 }
 
+#[async_test]
+async fn test_permalink_via_heroes_without_members() {
+    let (client, server) = logged_in_client_with_server().await;
+    let mut sync_builder = SyncResponseBuilder::new();
+    let room_id = room_id!("!test_room:127.0.0.1");
+    let event_id = event_id!("$15139375512JaHAW");
+
+    // The members aren't synced, only the heroes from the room summary are known.
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id).set_room_summary(json!({
+        "m.heroes": ["@alice:example.org", "@bob:example.org", "@carol:other.org"],
+        "m.joined_member_count": 4,
+        "m.invited_member_count": 0,
+    })));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::new()).await.unwrap();
+    let room = client.get_room(room_id).unwrap();
+
+    assert!(room.route().await.unwrap().is_empty());
+    let via = room.suggested_via_servers().await.unwrap();
+    assert_eq!(via.len(), 2);
+    assert_eq!(via[0], "example.org");
+    assert_eq!(via[1], "other.org");
+
+    assert_eq!(
+        room.matrix_to_permalink().await.unwrap().to_string(),
+        "https://matrix.to/#/!test_room:127.0.0.1?via=example.org&via=other.org"
+    );
+    assert_eq!(
+        room.matrix_event_permalink(event_id).await.unwrap().to_string(),
+        "matrix:roomid/test_room:127.0.0.1/e/15139375512JaHAW?via=example.org&via=other.org"
+    );
+}
+
 #[async_test]
 async fn test_event() {
     let event_id = event_id!("$foun39djjod0f");
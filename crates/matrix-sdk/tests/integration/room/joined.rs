@@ -3,29 +3,49 @@ use std::{
     time::Duration,
 };
 
-use futures_util::future::join_all;
+use assert_matches::assert_matches;
+use futures_util::{future::join_all, pin_mut, FutureExt, StreamExt};
 use matrix_sdk::{
     config::SyncSettings,
-    room::{Receipts, ReportedContentScore, RoomMemberRole},
+    room::{
+        EnableEncryptionError, EncryptionState, ExportFormat, MemberLoadProgress, Receipts,
+        ReportedContentScore, RoomMember, RoomMemberFilter, RoomMemberRole, SearchResults,
+    },
+    Error, ScUnreadCountSource,
 };
 use matrix_sdk_base::RoomState;
 use matrix_sdk_test::{
-    async_test, test_json, test_json::sync::CUSTOM_ROOM_POWER_LEVELS, EphemeralTestEvent,
-    GlobalAccountDataTestEvent, JoinedRoomBuilder, SyncResponseBuilder, DEFAULT_TEST_ROOM_ID,
+    async_test, sync_state_event, test_json, test_json::sync::CUSTOM_ROOM_POWER_LEVELS,
+    EphemeralTestEvent, GlobalAccountDataTestEvent, JoinedRoomBuilder, PresenceTestEvent,
+    RoomAccountDataTestEvent, StateTestEvent, SyncResponseBuilder, DEFAULT_TEST_ROOM_ID,
 };
 use ruma::{
     api::client::{membership::Invite3pidInit, receipt::create_receipt::v3::ReceiptType},
     assign, event_id,
-    events::{receipt::ReceiptThread, room::message::RoomMessageEventContent, TimelineEventType},
-    int, mxc_uri, owned_event_id, room_id, thirdparty, user_id, OwnedUserId, TransactionId,
+    events::{
+        presence::PresenceState, receipt::ReceiptThread, room::message::RoomMessageEventContent,
+        AnySyncStateEvent, AnySyncTimelineEvent, TimelineEventType,
+    },
+    int, mxc_uri, owned_event_id,
+    push::{
+        Action, AnyPushRule, NewPatternedPushRule, NewPushRule, PredefinedOverrideRuleId, Ruleset,
+    },
+    room_id,
+    serde::Raw,
+    thirdparty, uint, user_id, MilliSecondsSinceUnixEpoch, OwnedUserId, TransactionId,
 };
 use serde_json::{json, Value};
 use wiremock::{
-    matchers::{body_json, body_partial_json, header, method, path_regex},
+    matchers::{
+        body_json, body_partial_json, header, method, path_regex, query_param,
+        query_param_is_missing,
+    },
     Mock, ResponseTemplate,
 };
 
-use crate::{logged_in_client_with_server, mock_encryption_state, mock_sync, synced_client};
+use crate::{
+    logged_in_client_with_server, mock_encryption_state, mock_sync, room_name_event, synced_client,
+};
 
 #[async_test]
 async fn test_invite_user_by_id() {
@@ -108,6 +128,45 @@ async fn test_leave_room() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[async_test]
+async fn test_decline_invite() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/leave$"))
+        .and(header("authorization", "Bearer 1234"))
+        .and(body_json(json!({ "reason": "Not interested" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&*test_json::EMPTY))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    mock_sync(&server, &*test_json::INVITE_SYNC, None).await;
+
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+    let _response = client.sync_once(sync_settings).await.unwrap();
+
+    let room_id = room_id!("!696r7674:example.com");
+    let room = client.get_room(room_id).unwrap();
+    assert_eq!(room.state(), RoomState::Invited);
+
+    let mut roominfo_update_recv = client.roominfo_update_receiver();
+
+    room.decline_invite(Some("Not interested".to_owned())).await.unwrap();
+    assert_eq!(room.state(), RoomState::Left);
+
+    // The room lists are notified of the new state of the room.
+    let mut triggered = false;
+    while let Ok(update) = roominfo_update_recv.try_recv() {
+        assert_eq!(update.room_id, room_id);
+        triggered |= update.trigger_room_list_update;
+    }
+    assert!(triggered);
+
+    // An invite can't be declined twice.
+    room.decline_invite(None).await.unwrap_err();
+}
+
 #[async_test]
 async fn test_ban_user() {
     let (client, server) = logged_in_client_with_server().await;
@@ -180,6 +239,236 @@ async fn test_mark_as_unread() {
     room.set_unread_flag(false).await.unwrap();
 }
 
+#[async_test]
+async fn test_evaluate_push_rules() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    mock_sync(&server, &*test_json::SYNC, None).await;
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+    client.sync_once(sync_settings).await.unwrap();
+
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+
+    let message = |content: Value| {
+        Raw::new(&json!({
+            "content": content,
+            "event_id": "$message",
+            "origin_server_ts": 152037280,
+            "sender": "@alice:localhost",
+            "type": "m.room.message",
+        }))
+        .unwrap()
+        .cast::<AnySyncTimelineEvent>()
+    };
+
+    // A plain message notifies without highlighting.
+    let evaluation = room
+        .evaluate_push_rules(&message(json!({ "body": "Hello", "msgtype": "m.text" })))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(evaluation.matched_rule.is_some());
+    assert!(evaluation.matched_keywords.is_empty());
+    assert!(evaluation.notify);
+    assert!(!evaluation.highlight);
+    assert_eq!(evaluation.context.user_display_name, "example");
+
+    // A mention highlights.
+    let evaluation = room
+        .evaluate_push_rules(&message(json!({
+            "body": "Hello example",
+            "msgtype": "m.text",
+            "m.mentions": { "user_ids": ["@example:localhost"] },
+        })))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_matches!(evaluation.matched_rule, Some(AnyPushRule::Override(rule)) => {
+        assert_eq!(rule.rule_id, PredefinedOverrideRuleId::IsUserMention.as_str());
+    });
+    assert!(evaluation.matched_keywords.is_empty());
+    assert!(evaluation.notify);
+    assert!(evaluation.highlight);
+    assert_eq!(evaluation.sound.as_deref(), Some("default"));
+
+    // Add some keywords.
+    let mut ruleset = Ruleset::server_default(client.user_id().unwrap());
+    for keyword in ["rust", "crab", "ferris"] {
+        let rule = NewPatternedPushRule::new(keyword.into(), keyword.into(), vec![Action::Notify]);
+        ruleset.insert(NewPushRule::Content(rule), None, None).unwrap();
+    }
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_global_account_data_event(GlobalAccountDataTestEvent::Custom(json!({
+        "content": { "global": ruleset },
+        "type": "m.push_rules",
+    })));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::default()).await.unwrap();
+
+    // The matching keywords are reported.
+    let evaluation = room
+        .evaluate_push_rules(&message(json!({ "body": "A rust crab", "msgtype": "m.text" })))
+        .await
+        .unwrap()
+        .unwrap();
+    let mut matched_keywords = evaluation.matched_keywords;
+    matched_keywords.sort();
+    assert_eq!(matched_keywords, ["crab", "rust"]);
+    assert!(evaluation.notify);
+}
+
+#[async_test]
+async fn test_snooze_notifications() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::default());
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+    client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+    assert!(!room.is_snoozed());
+
+    // An hour from now.
+    let until = MilliSecondsSinceUnixEpoch(MilliSecondsSinceUnixEpoch::now().0 + uint!(3_600_000));
+
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/r0/user/.*/rooms/.*/account_data/chat.schildi.snooze"))
+        .and(header("authorization", "Bearer 1234"))
+        .and(body_json(json!({ "until": until })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&*test_json::EMPTY))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    room.snooze_notifications(until).await.unwrap();
+
+    // The room is snoozed once the account data comes back from the server.
+    sync_builder.add_joined_room(JoinedRoomBuilder::default().add_account_data(
+        RoomAccountDataTestEvent::Custom(json!({
+            "content": { "until": until },
+            "type": "chat.schildi.snooze",
+        })),
+    ));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    assert!(room.is_snoozed());
+    assert_eq!(room.snoozed_until(), Some(until));
+
+    // An elapsed snooze has no effect anymore.
+    sync_builder.add_joined_room(JoinedRoomBuilder::default().add_account_data(
+        RoomAccountDataTestEvent::Custom(json!({
+            "content": { "until": 1 },
+            "type": "chat.schildi.snooze",
+        })),
+    ));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(sync_settings).await.unwrap();
+
+    assert!(!room.is_snoozed());
+    assert_eq!(room.snoozed_until(), None);
+}
+
+#[async_test]
+async fn test_unread_count_source() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::default());
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+    client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+    assert_eq!(room.unread_count_source(), None);
+
+    Mock::given(method("PUT"))
+        .and(path_regex(
+            r"^/_matrix/client/r0/user/.*/rooms/.*/account_data/chat.schildi.unread_count_source",
+        ))
+        .and(header("authorization", "Bearer 1234"))
+        .and(body_json(json!({ "source": "server" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&*test_json::EMPTY))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    room.set_unread_count_source(Some(ScUnreadCountSource::Server)).await.unwrap();
+
+    // The override is applied once the account data comes back from the server.
+    sync_builder.add_joined_room(JoinedRoomBuilder::default().add_account_data(
+        RoomAccountDataTestEvent::Custom(json!({
+            "content": { "source": "server" },
+            "type": "chat.schildi.unread_count_source",
+        })),
+    ));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    assert_eq!(room.unread_count_source(), Some(ScUnreadCountSource::Server));
+
+    // An empty override follows the default source again.
+    sync_builder.add_joined_room(JoinedRoomBuilder::default().add_account_data(
+        RoomAccountDataTestEvent::Custom(json!({
+            "content": {},
+            "type": "chat.schildi.unread_count_source",
+        })),
+    ));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(sync_settings).await.unwrap();
+
+    assert_eq!(room.unread_count_source(), None);
+}
+
+#[async_test]
+async fn test_bold_unread_only() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::default());
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+
+    let sync_settings = SyncSettings::new().timeout(Duration::from_millis(3000));
+    client.sync_once(sync_settings.clone()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+    assert!(!room.is_bold_unread_only());
+
+    Mock::given(method("PUT"))
+        .and(path_regex(
+            r"^/_matrix/client/r0/user/.*/rooms/.*/account_data/chat.schildi.bold_unread",
+        ))
+        .and(header("authorization", "Bearer 1234"))
+        .and(body_json(json!({ "enabled": true })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&*test_json::EMPTY))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    room.set_bold_unread_only(true).await.unwrap();
+
+    // The setting is applied once the account data comes back from the server.
+    sync_builder.add_joined_room(JoinedRoomBuilder::default().add_account_data(
+        RoomAccountDataTestEvent::Custom(json!({
+            "content": { "enabled": true },
+            "type": "chat.schildi.bold_unread",
+        })),
+    ));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(sync_settings).await.unwrap();
+
+    assert!(room.is_bold_unread_only());
+}
+
 #[async_test]
 async fn test_kick_user() {
     let (client, server) = logged_in_client_with_server().await;
@@ -393,6 +682,394 @@ async fn test_fetch_members_deduplication() {
     server.verify().await;
 }
 
+#[async_test]
+async fn test_ensure_members() {
+    let (client, server) = synced_client().await;
+
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/members"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "chunk": [] })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+
+    let progress: Vec<_> = room.ensure_members().collect().await;
+    assert_matches!(progress.as_slice(), [MemberLoadProgress::Loading, MemberLoadProgress::Loaded]);
+
+    // Once the members are synced, they aren't requested again.
+    let progress: Vec<_> = room.ensure_members().collect().await;
+    assert_matches!(progress.as_slice(), [MemberLoadProgress::Loaded]);
+}
+
+fn member_event(user_id: &str, name: &str, membership: &str) -> Raw<AnySyncStateEvent> {
+    sync_state_event!({
+        "content": {
+            "displayname": name,
+            "membership": membership,
+        },
+        "event_id": format!("$member_{name}"),
+        "origin_server_ts": 151800140,
+        "sender": user_id,
+        "state_key": user_id,
+        "type": "m.room.member",
+    })
+}
+
+fn user_ids(members: &[RoomMember]) -> Vec<&str> {
+    members.iter().map(|member| member.user_id().as_str()).collect()
+}
+
+#[async_test]
+async fn test_members_filtered() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(&DEFAULT_TEST_ROOM_ID).add_state_bulk([
+        member_event("@alice:localhost", "Alice", "join"),
+        member_event("@bob:localhost", "Bob", "join"),
+        member_event("@carol:localhost", "Carol", "invite"),
+        member_event("@dave:localhost", "Dave", "ban"),
+        sync_state_event!({
+            "content": {
+                "users": { "@bob:localhost": 50 },
+                "users_default": 0,
+            },
+            "event_id": "$power_levels",
+            "origin_server_ts": 151800140,
+            "sender": "@bob:localhost",
+            "state_key": "",
+            "type": "m.room.power_levels",
+        }),
+    ]));
+
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    let _response = client.sync_once(SyncSettings::default()).await.unwrap();
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+
+    // Sorted by power level, then by name.
+    let members = room.members_filtered("", RoomMemberFilter::Active, 10, 0).await.unwrap();
+    assert_eq!(user_ids(&members), ["@bob:localhost", "@alice:localhost", "@carol:localhost"]);
+
+    // Pagination.
+    let members = room.members_filtered("", RoomMemberFilter::Active, 1, 1).await.unwrap();
+    assert_eq!(user_ids(&members), ["@alice:localhost"]);
+
+    // Case-insensitive match on the display name or the user ID.
+    let members = room.members_filtered("CAR", RoomMemberFilter::All, 10, 0).await.unwrap();
+    assert_eq!(user_ids(&members), ["@carol:localhost"]);
+    let members = room.members_filtered("dave:", RoomMemberFilter::All, 10, 0).await.unwrap();
+    assert_eq!(user_ids(&members), ["@dave:localhost"]);
+
+    // Filtered by membership.
+    let members = room.members_filtered("dave", RoomMemberFilter::Joined, 10, 0).await.unwrap();
+    assert!(members.is_empty());
+    let members = room.members_filtered("", RoomMemberFilter::Banned, 10, 0).await.unwrap();
+    assert_eq!(user_ids(&members), ["@dave:localhost"]);
+}
+
+#[async_test]
+async fn test_dm_partner_presence() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    let presence = |presence: &str| {
+        PresenceTestEvent::Custom(json!({
+            "content": { "presence": presence },
+            "sender": "@alice:localhost",
+            "type": "m.presence",
+        }))
+    };
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(&DEFAULT_TEST_ROOM_ID));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::default()).await.unwrap();
+    server.reset().await;
+
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+
+    // Not a DM yet.
+    assert_eq!(room.dm_partner_presence(), None);
+
+    sync_builder
+        .add_global_account_data_event(GlobalAccountDataTestEvent::Custom(json!({
+            "content": { "@alice:localhost": [*DEFAULT_TEST_ROOM_ID] },
+            "type": "m.direct",
+        })))
+        .add_presence_event(presence("online"));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::default()).await.unwrap();
+    server.reset().await;
+
+    assert_eq!(room.dm_partner_presence(), Some(PresenceState::Online));
+
+    let stream = room.subscribe_to_dm_partner_presence();
+    pin_mut!(stream);
+    assert_eq!(stream.next().await, Some(Some(PresenceState::Online)));
+
+    sync_builder.add_presence_event(presence("unavailable"));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::default()).await.unwrap();
+
+    assert_eq!(stream.next().await, Some(Some(PresenceState::Unavailable)));
+    assert_eq!(room.dm_partner_presence(), Some(PresenceState::Unavailable));
+}
+
+#[async_test]
+async fn test_info_stream_debounced() {
+    let (client, server) = synced_client().await;
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+
+    let stream = room.info_stream_debounced(Duration::from_millis(500));
+    pin_mut!(stream);
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    for name in ["First", "Second"] {
+        sync_builder.add_joined_room(
+            JoinedRoomBuilder::new(&DEFAULT_TEST_ROOM_ID).add_state_event(room_name_event(name)),
+        );
+        mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+        client.sync_once(SyncSettings::default()).await.unwrap();
+        server.reset().await;
+    }
+
+    // Both changes are coalesced.
+    let info = stream.next().await.unwrap();
+    assert_eq!(info.name(), Some("Second"));
+    assert!(stream.next().now_or_never().is_none());
+}
+
+/// A result of a search for "hello", as returned by the homeserver.
+fn search_result(event_id: &str, event_type: &str) -> Value {
+    json!({
+        "rank": 1.0,
+        "result": {
+            "content": { "body": "hello", "msgtype": "m.text" },
+            "event_id": event_id,
+            "origin_server_ts": 151800140,
+            "room_id": *DEFAULT_TEST_ROOM_ID,
+            "sender": "@alice:localhost",
+            "type": event_type,
+        },
+    })
+}
+
+fn search_event_ids(search: &SearchResults) -> Vec<&str> {
+    search.results.iter().map(|result| result.event_id.as_str()).collect()
+}
+
+#[async_test]
+async fn test_search_messages() {
+    let (client, server) = synced_client().await;
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/search"))
+        .and(query_param("next_batch", "batch_2"))
+        // Only the remaining number of results is requested.
+        .and(body_partial_json(json!({
+            "search_categories": { "room_events": { "filter": { "limit": 1 } } },
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "search_categories": {
+                "room_events": {
+                    "results": [search_result("$third", "m.room.message")],
+                    "next_batch": "batch_3",
+                },
+            },
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/search"))
+        .and(query_param("next_batch", "batch_3"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "search_categories": {
+                "room_events": {
+                    "results": [search_result("$fourth", "m.room.message")],
+                },
+            },
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/search"))
+        .and(body_partial_json(json!({
+            "search_categories": {
+                "room_events": {
+                    "search_term": "hello",
+                    "order_by": "recent",
+                    "filter": { "rooms": [*DEFAULT_TEST_ROOM_ID], "limit": 3 },
+                },
+            },
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "search_categories": {
+                "room_events": {
+                    "results": [
+                        search_result("$first", "m.room.message"),
+                        search_result("$second", "m.room.encrypted"),
+                    ],
+                    "next_batch": "batch_2",
+                },
+            },
+        })))
+        .mount(&server)
+        .await;
+
+    let search = room.search_messages("hello", 3, None).await.unwrap();
+
+    let results = &search.results;
+    assert_eq!(search_event_ids(&search), ["$first", "$second", "$third"]);
+    assert!(!results[0].is_undecryptable);
+    assert!(results[1].is_undecryptable);
+    assert_eq!(results[2].rank, Some(1.0));
+    assert_eq!(search.next_batch.as_deref(), Some("batch_3"));
+
+    // The next results are requested with the token of the previous search.
+
+    let search = room.search_messages("hello", 3, search.next_batch).await.unwrap();
+
+    assert_eq!(search_event_ids(&search), ["$fourth"]);
+    assert_eq!(search.next_batch, None);
+}
+
+#[async_test]
+async fn test_search_messages_limit() {
+    let (client, server) = synced_client().await;
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+
+    // The server ignores the limit of the filter.
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/search"))
+        .and(query_param_is_missing("next_batch"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "search_categories": {
+                "room_events": {
+                    "results": [
+                        search_result("$first", "m.room.message"),
+                        search_result("$second", "m.room.message"),
+                        search_result("$third", "m.room.message"),
+                    ],
+                    "next_batch": "batch_2",
+                },
+            },
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let search = room.search_messages("hello", 2, None).await.unwrap();
+
+    assert_eq!(search_event_ids(&search), ["$first", "$second"]);
+    assert_eq!(search.next_batch.as_deref(), Some("batch_2"));
+
+    // The server returns a token without any results.
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/search"))
+        .and(query_param("next_batch", "batch_2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "search_categories": {
+                "room_events": {
+                    "results": [],
+                    "next_batch": "batch_3",
+                },
+            },
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let search = room.search_messages("hello", 2, search.next_batch).await.unwrap();
+
+    assert!(search.results.is_empty());
+    assert_eq!(search.next_batch.as_deref(), Some("batch_3"));
+}
+
+#[async_test]
+async fn test_export_timeline() {
+    let (client, server) = synced_client().await;
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+
+    let message = |event_id: &str, body: &str| {
+        json!({
+            "content": { "body": body, "msgtype": "m.text" },
+            "event_id": event_id,
+            "origin_server_ts": 1709251199000u64,
+            "room_id": *DEFAULT_TEST_ROOM_ID,
+            "sender": "@alice:localhost",
+            "type": "m.room.message",
+        })
+    };
+    let redacted = json!({
+        "content": {},
+        "event_id": "$redacted",
+        "origin_server_ts": 1709251199000u64,
+        "room_id": *DEFAULT_TEST_ROOM_ID,
+        "sender": "@alice:localhost",
+        "type": "m.room.message",
+        "unsigned": {
+            "redacted_because": {
+                "content": {},
+                "event_id": "$redaction",
+                "origin_server_ts": 1709251199000u64,
+                "redacts": "$redacted",
+                "room_id": *DEFAULT_TEST_ROOM_ID,
+                "sender": "@alice:localhost",
+                "type": "m.room.redaction",
+            },
+        },
+    });
+
+    // The history is loaded from the start of the room.
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/messages$"))
+        .and(query_param("dir", "f"))
+        .and(query_param("from", "page_2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "chunk": [redacted, message("$third", "Bye")],
+            "start": "page_2",
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/messages$"))
+        .and(query_param("dir", "f"))
+        .and(query_param_is_missing("from"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "chunk": [message("$first", "<b>Hi</b>")],
+            "start": "page_1",
+            "end": "page_2",
+        })))
+        .mount(&server)
+        .await;
+
+    let mut json_export = Vec::new();
+    room.export_timeline(&mut json_export, ExportFormat::Json).await.unwrap();
+    let events: Vec<Value> = serde_json::from_slice(&json_export).unwrap();
+    let event_ids: Vec<_> =
+        events.iter().map(|event| event["event_id"].as_str().unwrap()).collect();
+    assert_eq!(event_ids, ["$first", "$redacted", "$third"]);
+
+    let mut html_export = Vec::new();
+    room.export_timeline(&mut html_export, ExportFormat::Html { inline_media: false })
+        .await
+        .unwrap();
+    let html = String::from_utf8(html_export).unwrap();
+    let first = html.find("&lt;b&gt;Hi&lt;/b&gt;").unwrap();
+    let removed = html.find("[removed]").unwrap();
+    let third = html.find("Bye").unwrap();
+    assert!(first < removed && removed < third);
+    assert!(html.contains("2024-02-29 23:59:59"));
+}
+
 #[async_test]
 async fn test_set_name() {
     let (client, server) = synced_client().await;
@@ -713,3 +1390,112 @@ async fn test_call_notifications_notify_for_rooms() {
 
     room.send_call_notification_if_needed().await.unwrap();
 }
+
+#[async_test]
+async fn test_encryption_state_stream() {
+    let (client, server) = synced_client().await;
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+    server.reset().await;
+
+    mock_encryption_state(&server, false).await;
+
+    let stream = room.encryption_state_stream();
+    pin_mut!(stream);
+
+    // The state is unknown until it's requested from the server.
+    assert_eq!(stream.next().await, Some(EncryptionState::Unknown));
+    assert_eq!(stream.next().await, Some(EncryptionState::NotEncrypted));
+
+    // Encryption is enabled while the stream is alive.
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(&DEFAULT_TEST_ROOM_ID).add_state_event(StateTestEvent::Encryption),
+    );
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::default()).await.unwrap();
+
+    assert_eq!(stream.next().await, Some(EncryptionState::Encrypted));
+    assert!(stream.next().now_or_never().is_none());
+}
+
+#[async_test]
+async fn test_enable_encryption_checked_not_allowed() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(&DEFAULT_TEST_ROOM_ID).add_state_event(
+        StateTestEvent::Custom(json!({
+            "content": {
+                "events": {
+                    "m.room.encryption": 100,
+                },
+                "state_default": 50,
+                "users": {
+                    "@example:localhost": 50,
+                },
+            },
+            "event_id": "$power_levels",
+            "origin_server_ts": 151393755,
+            "sender": "@admin:localhost",
+            "state_key": "",
+            "type": "m.room.power_levels",
+        })),
+    ));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::default()).await.unwrap();
+
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+    assert!(!room.can_user_enable_encryption(client.user_id().unwrap()).await.unwrap());
+
+    // The state event must not be sent at all.
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/state/m.room.encryption/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&*test_json::EVENT_ID))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    assert_matches!(
+        room.enable_encryption_checked().await,
+        Err(Error::EnableEncryption(EnableEncryptionError::NotAllowed))
+    );
+}
+
+#[async_test]
+async fn test_set_user_muted_locally() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(&DEFAULT_TEST_ROOM_ID));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::default()).await.unwrap();
+
+    // The muted users are never sent to the homeserver.
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/r0/user/.*/rooms/.*/account_data/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let room = client.get_room(&DEFAULT_TEST_ROOM_ID).unwrap();
+    let bob = user_id!("@bob:localhost");
+    assert!(room.locally_muted_users().is_empty());
+
+    room.set_user_muted_locally(bob, true).await.unwrap();
+    assert_eq!(room.locally_muted_users(), [bob.to_owned()].into());
+
+    // The muted users are kept in the store.
+    let stored_info = client
+        .store()
+        .get_room_infos()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|info| info.room_id() == &**DEFAULT_TEST_ROOM_ID)
+        .unwrap();
+    assert!(stored_info.locally_muted_users().contains(bob));
+
+    room.set_user_muted_locally(bob, false).await.unwrap();
+    assert!(room.locally_muted_users().is_empty());
+}
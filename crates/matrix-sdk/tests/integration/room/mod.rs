@@ -5,3 +5,5 @@ mod left;
 mod notification_mode;
 mod spaces;
 mod tags;
+mod tombstone;
+mod url_preview;
@@ -1,18 +1,24 @@
 use std::time::Duration;
 
 use assert_matches2::assert_let;
-use futures_util::StreamExt;
+use futures_util::{pin_mut, StreamExt};
 use matrix_sdk::{config::SyncSettings, room::ParentSpace, Client};
-use matrix_sdk_test::{async_test, test_json, DEFAULT_TEST_ROOM_ID};
+use matrix_sdk_test::{
+    async_test, test_json, JoinedRoomBuilder, StateTestEvent, SyncResponseBuilder,
+    DEFAULT_TEST_ROOM_ID,
+};
 use once_cell::sync::Lazy;
-use ruma::{room_id, RoomId};
+use ruma::{mxc_uri, room_id, space::SpaceRoomJoinRule, RoomId};
 use serde_json::{json, Value as JsonValue};
+use stream_assert::{assert_next_matches, assert_pending};
 use wiremock::{
-    matchers::{header, method, path_regex},
+    matchers::{header, method, path_regex, query_param, query_param_is_missing},
     Mock, ResponseTemplate,
 };
 
-use crate::{logged_in_client_with_server, mock_sync, MockServer};
+use crate::{
+    logged_in_client_with_server, mock_sync, mock_sync_scoped, room_name_event, MockServer,
+};
 
 pub static DEFAULT_TEST_SPACE_ID: Lazy<&RoomId> =
     Lazy::new(|| room_id!("!hIMjEx205EXNyjVPCV:localhost"));
@@ -405,3 +411,187 @@ async fn parent_space_powerlevel_too_low() {
     assert_let!(ParentSpace::Illegitimate(space) = spaces.first().unwrap());
     assert_eq!(space.room_id(), *DEFAULT_TEST_SPACE_ID);
 }
+
+fn space_create_event() -> StateTestEvent {
+    StateTestEvent::Custom(json!({
+        "content": {
+            "creator": "@example:localhost",
+            "type": "m.space",
+        },
+        "event_id": "$create",
+        "origin_server_ts": 151800140,
+        "sender": "@example:localhost",
+        "state_key": "",
+        "type": "m.room.create",
+    }))
+}
+
+fn space_child_event(child: &RoomId, order: Option<&str>, via: &[&str]) -> StateTestEvent {
+    StateTestEvent::Custom(json!({
+        "content": {
+            "order": order,
+            "via": via,
+        },
+        "event_id": format!("$child_{}", child.localpart()),
+        "origin_server_ts": 151800140,
+        "sender": "@example:localhost",
+        "state_key": child,
+        "type": "m.space.child",
+    }))
+}
+
+#[async_test]
+async fn test_space_children_stream() {
+    let (client, server) = logged_in_client_with_server().await;
+    let space_id = room_id!("!space:localhost");
+    let first_id = room_id!("!first:localhost");
+    let second_id = room_id!("!second:localhost");
+    let unordered_id = room_id!("!a_unordered:localhost");
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(space_id)
+            .add_state_event(space_create_event())
+            .add_state_event(space_child_event(unordered_id, None, &["localhost"]))
+            .add_state_event(space_child_event(second_id, Some("b"), &["localhost"])),
+    );
+    {
+        let _guard = mock_sync_scoped(&server, sync_builder.build_json_sync_response(), None).await;
+        client.sync_once(SyncSettings::new()).await.unwrap();
+    }
+
+    let space = client.get_room(space_id).unwrap();
+    let stream = space.space_children_stream();
+    pin_mut!(stream);
+
+    // The children with an order come first.
+    let children = assert_next_matches!(stream, children => children);
+    let children: Vec<_> = children.iter().map(|child| &*child.room_id).collect();
+    assert_eq!(children, [second_id, unordered_id]);
+
+    // Unrelated changes of the space don't update the children.
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(space_id).add_state_event(room_name_event("Space")),
+    );
+    {
+        let _guard = mock_sync_scoped(&server, sync_builder.build_json_sync_response(), None).await;
+        client.sync_once(SyncSettings::new()).await.unwrap();
+    }
+    assert_eq!(space.name().as_deref(), Some("Space"));
+    assert_pending!(stream);
+
+    // A new child and a removed child update the children.
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(space_id)
+            .add_state_event(space_child_event(first_id, Some("a"), &["localhost"]))
+            .add_state_event(space_child_event(unordered_id, None, &[])),
+    );
+    {
+        let _guard = mock_sync_scoped(&server, sync_builder.build_json_sync_response(), None).await;
+        client.sync_once(SyncSettings::new()).await.unwrap();
+    }
+
+    let children = assert_next_matches!(stream, children => children);
+    assert_eq!(children.len(), 2);
+    assert_eq!(&*children[0].room_id, first_id);
+    assert_eq!(children[0].order.as_deref(), Some("a"));
+    assert_eq!(&*children[1].room_id, second_id);
+    assert_pending!(stream);
+}
+
+fn hierarchy_chunk(room_id: &RoomId, name: &str) -> JsonValue {
+    json!({
+        "room_id": room_id,
+        "name": name,
+        "topic": format!("The topic of {name}"),
+        "avatar_url": "mxc://localhost/avatar",
+        "num_joined_members": 42,
+        "world_readable": false,
+        "guest_can_join": false,
+        "join_rule": "public",
+        "children_state": [],
+    })
+}
+
+#[async_test]
+async fn test_fetch_space_summary() {
+    let (client, server) = logged_in_client_with_server().await;
+    let space_id = room_id!("!space:localhost");
+    let joined_id = room_id!("!joined:localhost");
+    let unjoined_id = room_id!("!unjoined:localhost");
+    let local_id = room_id!("!local:localhost");
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder
+        .add_joined_room(
+            JoinedRoomBuilder::new(space_id)
+                .add_state_event(space_create_event())
+                .add_state_event(space_child_event(joined_id, None, &["localhost"]))
+                .add_state_event(space_child_event(unjoined_id, Some("a"), &["localhost"]))
+                .add_state_event(space_child_event(local_id, None, &["localhost"])),
+        )
+        .add_joined_room(
+            JoinedRoomBuilder::new(joined_id).add_state_event(room_name_event("Joined room")),
+        )
+        .add_joined_room(
+            JoinedRoomBuilder::new(local_id).add_state_event(room_name_event("Local room")),
+        );
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::new()).await.unwrap();
+
+    // The first page contains the space itself and an unjoined child.
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/client/.*/rooms/.*/hierarchy"))
+        .and(query_param_is_missing("from"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "rooms": [
+                hierarchy_chunk(space_id, "Space"),
+                hierarchy_chunk(unjoined_id, "Unjoined room"),
+            ],
+            "next_batch": "next",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    // The second page contains a joined child, and the unjoined child again.
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/client/.*/rooms/.*/hierarchy"))
+        .and(query_param("from", "next"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "rooms": [
+                hierarchy_chunk(joined_id, "Outdated name"),
+                hierarchy_chunk(unjoined_id, "Unjoined room"),
+            ],
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let space = client.get_room(space_id).unwrap();
+    let summaries = space.fetch_space_summary(false).await.unwrap();
+    assert_eq!(summaries.len(), 3);
+
+    // The unjoined child is described by the homeserver.
+    let unjoined = &summaries[0];
+    assert_eq!(&*unjoined.room_id, unjoined_id);
+    assert_eq!(unjoined.name.as_deref(), Some("Unjoined room"));
+    assert_eq!(unjoined.topic.as_deref(), Some("The topic of Unjoined room"));
+    assert_eq!(unjoined.avatar_url.as_deref(), Some(mxc_uri!("mxc://localhost/avatar")));
+    assert_eq!(unjoined.num_joined_members, 42);
+    assert_eq!(unjoined.join_rule, SpaceRoomJoinRule::Public);
+    assert!(!unjoined.is_joined);
+    assert_eq!(unjoined.order.as_deref(), Some("a"));
+
+    // The joined child reports its local state.
+    let joined = &summaries[1];
+    assert_eq!(&*joined.room_id, joined_id);
+    assert_eq!(joined.name.as_deref(), Some("Joined room"));
+    assert!(joined.is_joined);
+    assert_eq!(joined.order, None);
+
+    // The joined child omitted by the homeserver is kept.
+    let local = &summaries[2];
+    assert_eq!(&*local.room_id, local_id);
+    assert_eq!(local.name.as_deref(), Some("Local room"));
+    assert!(local.is_joined);
+}
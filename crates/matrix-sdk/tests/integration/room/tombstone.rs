@@ -0,0 +1,228 @@
+use std::time::Duration;
+
+use assert_matches2::assert_let;
+use matrix_sdk::{config::SyncSettings, room::TombstoneError, Error};
+use matrix_sdk_test::{
+    async_test, sync_timeline_event, test_json, JoinedRoomBuilder, RoomAccountDataTestEvent,
+    StateTestEvent, SyncResponseBuilder,
+};
+use ruma::{room_id, RoomId};
+use serde_json::{json, Value as JsonValue};
+use tokio::time::sleep;
+use wiremock::{
+    matchers::{header, method, path_regex, query_param},
+    Mock, ResponseTemplate,
+};
+
+use crate::{logged_in_client_with_server, mock_sync, mock_sync_scoped};
+
+fn tombstone_json(replacement_room: &RoomId) -> JsonValue {
+    json!({
+        "content": {
+            "body": "This room has been replaced",
+            "replacement_room": replacement_room,
+        },
+        "event_id": format!("$tombstone_{}", replacement_room.localpart()),
+        "origin_server_ts": 151800140,
+        "sender": "@admin:upgrade.org",
+        "state_key": "",
+        "type": "m.room.tombstone",
+    })
+}
+
+fn tombstone(replacement_room: &RoomId) -> StateTestEvent {
+    StateTestEvent::Custom(tombstone_json(replacement_room))
+}
+
+#[async_test]
+async fn test_follow_tombstone() {
+    let (client, server) = logged_in_client_with_server().await;
+    let old_room_id = room_id!("!old:localhost");
+    let new_room_id = room_id!("!new:localhost");
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(old_room_id)
+            .add_state_event(tombstone(new_room_id))
+            .add_account_data(RoomAccountDataTestEvent::Custom(json!({
+                "content": { "tags": { "m.favourite": { "order": 0.5 } } },
+                "type": "m.tag",
+            })))
+            .add_account_data(RoomAccountDataTestEvent::Custom(json!({
+                "content": { "unread": true },
+                "type": "com.famedly.marked_unread",
+            }))),
+    );
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::new().timeout(Duration::from_millis(3000))).await.unwrap();
+
+    // The successor is joined through the server of the sender of the tombstone.
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/join/!new:localhost"))
+        .and(query_param("server_name", "upgrade.org"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "room_id": new_room_id })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/r0/user/.*/rooms/!new:localhost/tags/m.favourite"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&*test_json::EMPTY))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path_regex(
+            r"^/_matrix/client/r0/user/.*/rooms/!new:localhost/account_data/com.famedly.marked_unread",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&*test_json::EMPTY))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let old_room = client.get_room(old_room_id).unwrap();
+    let new_room = old_room.follow_tombstone().await.unwrap();
+    assert_eq!(new_room.room_id(), new_room_id);
+}
+
+#[async_test]
+async fn test_follow_tombstone_guards_against_upgrade_loops() {
+    let (client, server) = logged_in_client_with_server().await;
+    let room_a_id = room_id!("!a:localhost");
+    let room_b_id = room_id!("!b:localhost");
+
+    // Both rooms claim to be replaced by the other one.
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder
+        .add_joined_room(JoinedRoomBuilder::new(room_a_id).add_state_event(tombstone(room_b_id)))
+        .add_joined_room(JoinedRoomBuilder::new(room_b_id).add_state_event(tombstone(room_a_id)));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::new().timeout(Duration::from_millis(3000))).await.unwrap();
+
+    let room_a = client.get_room(room_a_id).unwrap();
+    assert_let!(
+        Err(Error::Tombstone(TombstoneError::UpgradeLoop(room_id))) =
+            room_a.follow_tombstone().await
+    );
+    assert_eq!(room_id, room_a_id);
+}
+
+#[async_test]
+async fn test_follow_tombstone_without_tombstone() {
+    let (client, server) = logged_in_client_with_server().await;
+    let room_id = room_id!("!a:localhost");
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::new().timeout(Duration::from_millis(3000))).await.unwrap();
+
+    let room = client.get_room(room_id).unwrap();
+    assert_let!(
+        Err(Error::Tombstone(TombstoneError::NotTombstoned)) = room.follow_tombstone().await
+    );
+}
+
+#[async_test]
+async fn test_successor_room() {
+    let (client, server) = logged_in_client_with_server().await;
+    let old_room_id = room_id!("!old:localhost");
+    let joined_room_id = room_id!("!joined:localhost");
+    let unknown_room_id = room_id!("!unknown:localhost");
+
+    let mut sync_builder = SyncResponseBuilder::new();
+    sync_builder
+        .add_joined_room(
+            JoinedRoomBuilder::new(old_room_id).add_state_event(tombstone(joined_room_id)),
+        )
+        .add_joined_room(
+            JoinedRoomBuilder::new(joined_room_id).add_state_event(tombstone(unknown_room_id)),
+        );
+    mock_sync(&server, sync_builder.build_json_sync_response(), None).await;
+    client.sync_once(SyncSettings::new().timeout(Duration::from_millis(3000))).await.unwrap();
+
+    let successor = client.get_room(old_room_id).unwrap().successor_room().unwrap();
+    assert_eq!(successor.room_id, joined_room_id);
+    assert_eq!(successor.reason.as_deref(), Some("This room has been replaced"));
+    assert!(successor.is_joined);
+
+    let successor = client.get_room(joined_room_id).unwrap().successor_room().unwrap();
+    assert_eq!(successor.room_id, unknown_room_id);
+    assert!(!successor.is_joined);
+}
+
+#[async_test]
+async fn test_auto_follow_only_new_timeline_tombstones() {
+    let (client, server) = logged_in_client_with_server().await;
+    client.set_auto_follow_tombstones(true);
+    let old_room_id = room_id!("!old:localhost");
+    let new_room_id = room_id!("!new:localhost");
+
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/join/!new:localhost"))
+        .and(query_param("server_name", "upgrade.org"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "room_id": new_room_id })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/r0/join/"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let mut sync_builder = SyncResponseBuilder::new();
+
+    // A tombstone in the initial sync was sent before the user started the app.
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(old_room_id).add_timeline_event(sync_timeline_event!(
+            tombstone_json(room_id!("!initial:localhost"))
+        )),
+    );
+    {
+        let _guard = mock_sync_scoped(&server, sync_builder.build_json_sync_response(), None).await;
+        client.sync_once(SyncSettings::new()).await.unwrap();
+    }
+
+    // A tombstone in a gappy sync might be old.
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(old_room_id)
+            .add_timeline_event(sync_timeline_event!(tombstone_json(room_id!(
+                "!limited:localhost"
+            ))))
+            .set_timeline_limited(),
+    );
+    {
+        let _guard = mock_sync_scoped(&server, sync_builder.build_json_sync_response(), None).await;
+        client.sync_once(SyncSettings::new()).await.unwrap();
+    }
+
+    // A tombstone in the state events is not new.
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(old_room_id)
+            .add_state_event(tombstone(room_id!("!state:localhost"))),
+    );
+    {
+        let _guard = mock_sync_scoped(&server, sync_builder.build_json_sync_response(), None).await;
+        client.sync_once(SyncSettings::new()).await.unwrap();
+    }
+
+    // A tombstone received live in the timeline is followed.
+    sync_builder.add_joined_room(
+        JoinedRoomBuilder::new(old_room_id)
+            .add_timeline_event(sync_timeline_event!(tombstone_json(new_room_id))),
+    );
+    {
+        let _guard = mock_sync_scoped(&server, sync_builder.build_json_sync_response(), None).await;
+        client.sync_once(SyncSettings::new()).await.unwrap();
+    }
+
+    for _ in 0..50 {
+        if client.get_room(new_room_id).is_some() {
+            break;
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+    assert!(client.get_room(new_room_id).is_some(), "the new room should have been joined");
+}
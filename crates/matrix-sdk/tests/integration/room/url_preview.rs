@@ -0,0 +1,116 @@
+use assert_matches2::assert_let;
+use matrix_sdk::{room::UrlPreviewError, Error};
+use matrix_sdk_test::{async_test, GlobalAccountDataTestEvent, JoinedRoomBuilder};
+use ruma::{mxc_uri, room_id, uint};
+use serde_json::json;
+use wiremock::{
+    matchers::{method, path_regex, query_param},
+    Mock, ResponseTemplate,
+};
+
+use crate::{logged_in_client_with_server, mock_encryption_state, mock_sync_with_new_room};
+
+#[async_test]
+async fn test_url_preview_disabled_by_account_data() {
+    let (client, server) = logged_in_client_with_server().await;
+    let room_id = room_id!("!a:localhost");
+
+    let room = mock_sync_with_new_room(
+        |builder| {
+            builder.add_joined_room(JoinedRoomBuilder::new(room_id)).add_global_account_data_event(
+                GlobalAccountDataTestEvent::Custom(json!({
+                    "content": { "disable": true },
+                    "type": "org.matrix.preview_urls",
+                })),
+            );
+        },
+        &client,
+        &server,
+        room_id,
+    )
+    .await;
+
+    // No request is sent to the homeserver.
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/media/.*/preview_url"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    assert!(!room.are_url_previews_enabled().await.unwrap());
+    assert_let!(
+        Err(Error::UrlPreview(UrlPreviewError::Disabled)) =
+            room.get_url_preview("https://matrix.org").await
+    );
+}
+
+#[async_test]
+async fn test_url_preview_unsupported() {
+    let (client, server) = logged_in_client_with_server().await;
+    let room_id = room_id!("!a:localhost");
+
+    let room = mock_sync_with_new_room(
+        |builder| {
+            builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+        },
+        &client,
+        &server,
+        room_id,
+    )
+    .await;
+    mock_encryption_state(&server, false).await;
+
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/media/.*/preview_url"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "errcode": "M_UNRECOGNIZED",
+            "error": "Unrecognized request",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert_let!(
+        Err(Error::UrlPreview(UrlPreviewError::Unsupported)) =
+            room.get_url_preview("https://matrix.org").await
+    );
+}
+
+#[async_test]
+async fn test_url_preview_is_cached() {
+    let (client, server) = logged_in_client_with_server().await;
+    let room_id = room_id!("!a:localhost");
+
+    let room = mock_sync_with_new_room(
+        |builder| {
+            builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+        },
+        &client,
+        &server,
+        room_id,
+    )
+    .await;
+    mock_encryption_state(&server, false).await;
+
+    // The preview is only fetched once.
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/media/.*/preview_url"))
+        .and(query_param("url", "https://matrix.org"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "og:title": "Matrix",
+            "og:image": "mxc://localhost/logo",
+            "og:image:width": 48,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let preview = room.get_url_preview("https://matrix.org").await.unwrap();
+    assert_eq!(preview.title.as_deref(), Some("Matrix"));
+    assert_eq!(preview.image.as_deref(), Some(mxc_uri!("mxc://localhost/logo")));
+    assert_eq!(preview.image_width, Some(uint!(48)));
+
+    let cached_preview = room.get_url_preview("https://matrix.org").await.unwrap();
+    assert_eq!(cached_preview, preview);
+}
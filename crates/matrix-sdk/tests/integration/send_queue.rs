@@ -15,7 +15,7 @@ use matrix_sdk_test::{async_test, InvitedRoomBuilder, JoinedRoomBuilder, LeftRoo
 use ruma::{
     event_id,
     events::{room::message::RoomMessageEventContent, AnyMessageLikeEventContent},
-    room_id, EventId, OwnedEventId,
+    room_id, uint, EventId, MilliSecondsSinceUnixEpoch, OwnedEventId,
 };
 use serde_json::json;
 use tokio::{sync::Mutex, time::timeout};
@@ -80,7 +80,7 @@ macro_rules! assert_update {
     // Returns the error for additional checks.
     ($watch:ident => error { $(recoverable=$recoverable:expr,)? $(txn=$txn:expr)? }) => {{
         assert_let!(
-            Ok(Ok(RoomSendQueueUpdate::SendError { transaction_id: _txn, error, is_recoverable: _is_recoverable })) =
+            Ok(Ok(RoomSendQueueUpdate::SendError { transaction_id: _txn, error, is_recoverable: _is_recoverable, .. })) =
                 timeout(Duration::from_secs(10), $watch.recv()).await
         );
 
@@ -860,6 +860,192 @@ async fn test_unrecoverable_errors() {
     assert!(client.send_queue().is_enabled());
 }
 
+#[async_test]
+async fn test_rate_limited_retries_after_delay() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    // Mark the room as joined.
+    let room_id = room_id!("!a:b.c");
+
+    let room = mock_sync_with_new_room(
+        |builder| {
+            builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+        },
+        &client,
+        &server,
+        room_id,
+    )
+    .await;
+
+    let q = room.send_queue();
+    let (_, mut watch) = q.subscribe().await;
+
+    mock_encryption_state(&server, false).await;
+
+    // The homeserver rate-limits all the attempts of the HTTP client.
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/send/.*"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(429).set_body_json(json!({
+            "errcode": "M_LIMIT_EXCEEDED",
+            "error": "Too many requests",
+            "retry_after_ms": 500,
+        })))
+        .up_to_n_times(3)
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    mock_send_event(event_id!("$1")).expect(1).mount(&server).await;
+
+    q.send(RoomMessageEventContent::text_plain("1").into()).await.unwrap();
+
+    let (txn, _) = assert_update!(watch => local echo { body = "1" });
+
+    assert_let!(
+        Ok(Ok(RoomSendQueueUpdate::SendError {
+            transaction_id,
+            is_recoverable: true,
+            retry_at: Some(retry_at),
+            ..
+        })) = timeout(Duration::from_secs(10), watch.recv()).await
+    );
+    assert_eq!(transaction_id, txn);
+    let failed_at = MilliSecondsSinceUnixEpoch::now();
+    assert!(retry_at > failed_at);
+
+    // The queue isn't disabled, it sends the event again after the delay.
+    assert!(q.is_enabled());
+    assert!(watch.is_empty());
+
+    assert_update!(watch => sent { txn = txn, event_id = event_id!("$1") });
+    assert!(MilliSecondsSinceUnixEpoch::now().0 >= failed_at.0 + uint!(400));
+}
+
+#[async_test]
+async fn test_rate_limited_until_date_retries() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    // Mark the room as joined.
+    let room_id = room_id!("!a:b.c");
+
+    let room = mock_sync_with_new_room(
+        |builder| {
+            builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+        },
+        &client,
+        &server,
+        room_id,
+    )
+    .await;
+
+    let q = room.send_queue();
+    let (_, mut watch) = q.subscribe().await;
+
+    mock_encryption_state(&server, false).await;
+
+    // The homeserver asks to retry at a date that has already passed.
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/send/.*"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("Retry-After", "Wed, 21 Oct 2015 07:28:00 GMT")
+                .set_body_json(json!({
+                    "errcode": "M_LIMIT_EXCEEDED",
+                    "error": "Too many requests",
+                })),
+        )
+        .up_to_n_times(3)
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    mock_send_event(event_id!("$1")).expect(1).mount(&server).await;
+
+    q.send(RoomMessageEventContent::text_plain("1").into()).await.unwrap();
+
+    let (txn, _) = assert_update!(watch => local echo { body = "1" });
+
+    assert_let!(
+        Ok(Ok(RoomSendQueueUpdate::SendError {
+            transaction_id,
+            is_recoverable: true,
+            retry_at: Some(_),
+            ..
+        })) = timeout(Duration::from_secs(10), watch.recv()).await
+    );
+    assert_eq!(transaction_id, txn);
+
+    // The queue isn't disabled, it sends the event again right away.
+    assert!(q.is_enabled());
+    assert_update!(watch => sent { txn = txn, event_id = event_id!("$1") });
+}
+
+#[async_test]
+async fn test_rate_limited_without_delay_retries_later_and_stops_on_drop() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    // Mark the room as joined.
+    let room_id = room_id!("!a:b.c");
+
+    let room = mock_sync_with_new_room(
+        |builder| {
+            builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+        },
+        &client,
+        &server,
+        room_id,
+    )
+    .await;
+
+    let q = room.send_queue();
+    let (_, mut watch) = q.subscribe().await;
+
+    mock_encryption_state(&server, false).await;
+
+    // The homeserver doesn't say how long to wait.
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/r0/rooms/.*/send/.*"))
+        .and(header("authorization", "Bearer 1234"))
+        .respond_with(ResponseTemplate::new(429).set_body_json(json!({
+            "errcode": "M_LIMIT_EXCEEDED",
+            "error": "Too many requests",
+        })))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    q.send(RoomMessageEventContent::text_plain("1").into()).await.unwrap();
+
+    let (txn, _) = assert_update!(watch => local echo { body = "1" });
+
+    assert_let!(
+        Ok(Ok(RoomSendQueueUpdate::SendError {
+            transaction_id,
+            is_recoverable: true,
+            retry_at: Some(retry_at),
+            ..
+        })) = timeout(Duration::from_secs(10), watch.recv()).await
+    );
+    assert_eq!(transaction_id, txn);
+
+    // A default delay is used, and the queue isn't disabled.
+    assert!(retry_at.0 >= MilliSecondsSinceUnixEpoch::now().0 + uint!(4000));
+    assert!(q.is_enabled());
+
+    // Dropping the client while the queue waits for the delay stops the sending
+    // task right away, which closes the updates channel.
+    drop(q);
+    drop(room);
+    drop(client);
+
+    assert_let!(
+        Ok(Err(tokio::sync::broadcast::error::RecvError::Closed)) =
+            timeout(Duration::from_secs(1), watch.recv()).await
+    );
+}
+
 #[async_test]
 async fn test_no_network_access_error_is_recoverable() {
     // This is subtle, but for the `drop(server)` below to be effectful, it needs to
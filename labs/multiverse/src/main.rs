@@ -751,6 +751,7 @@ impl App {
                         TimelineItemContent::Sticker(_)
                         | TimelineItemContent::MembershipChange(_)
                         | TimelineItemContent::ProfileChange(_)
+                        | TimelineItemContent::PinnedEventsChange(_)
                         | TimelineItemContent::OtherState(_)
                         | TimelineItemContent::FailedToParseMessageLike { .. }
                         | TimelineItemContent::FailedToParseState { .. }